@@ -31,7 +31,7 @@ use datafusion::datasource::TableProvider;
 use datafusion::error::{DataFusionError, Result};
 use datafusion::execution::dataframe_impl::DataFrameImpl;
 use datafusion::logical_plan::{CreateExternalTable, LogicalPlan, TableScan};
-use datafusion::prelude::{AvroReadOptions, CsvReadOptions};
+use datafusion::prelude::{ArrowReadOptions, AvroReadOptions, CsvReadOptions};
 use datafusion::sql::parser::FileType;
 
 struct BallistaContextState {
@@ -150,6 +150,30 @@ impl BallistaContext {
         Ok(df)
     }
 
+    /// Create a DataFrame representing an Arrow IPC table scan
+    /// TODO fetch schema from scheduler instead of resolving locally
+    pub async fn read_arrow(
+        &self,
+        path: &str,
+        options: ArrowReadOptions<'_>,
+    ) -> Result<Arc<dyn DataFrame>> {
+        // convert to absolute path because the executor likely has a different working directory
+        let path = PathBuf::from(path);
+        let path = fs::canonicalize(&path)?;
+
+        // use local DataFusion context for now but later this might call the scheduler
+        let mut ctx = {
+            let guard = self.state.lock().unwrap();
+            create_df_ctx_with_ballista_query_planner(
+                &guard.scheduler_host,
+                guard.scheduler_port,
+                guard.config(),
+            )
+        };
+        let df = ctx.read_arrow(path.to_str().unwrap(), options).await?;
+        Ok(df)
+    }
+
     /// Create a DataFrame representing a Parquet table scan
     /// TODO fetch schema from scheduler instead of resolving locally
     pub async fn read_parquet(&self, path: &str) -> Result<Arc<dyn DataFrame>> {
@@ -242,6 +266,20 @@ impl BallistaContext {
         }
     }
 
+    pub async fn register_arrow(
+        &self,
+        name: &str,
+        path: &str,
+        options: ArrowReadOptions<'_>,
+    ) -> Result<()> {
+        match self.read_arrow(path, options).await?.to_logical_plan() {
+            LogicalPlan::TableScan(TableScan { source, .. }) => {
+                self.register_table(name, source)
+            }
+            _ => Err(DataFusionError::Internal("Expected tables scan".to_owned())),
+        }
+    }
+
     /// Create a DataFrame from a SQL statement.
     ///
     /// This method is `async` because queries of type `CREATE EXTERNAL TABLE`
@@ -275,6 +313,7 @@ impl BallistaContext {
                 ref location,
                 ref file_type,
                 ref has_header,
+                file_compression_type,
             }) => match file_type {
                 FileType::CSV => {
                     self.register_csv(
@@ -282,7 +321,8 @@ impl BallistaContext {
                         location,
                         CsvReadOptions::new()
                             .schema(&schema.as_ref().to_owned().into())
-                            .has_header(*has_header),
+                            .has_header(*has_header)
+                            .file_compression_type(file_compression_type),
                     )
                     .await?;
                     Ok(Arc::new(DataFrameImpl::new(ctx.state, &plan)))
@@ -296,6 +336,11 @@ impl BallistaContext {
                         .await?;
                     Ok(Arc::new(DataFrameImpl::new(ctx.state, &plan)))
                 }
+                FileType::Arrow => {
+                    self.register_arrow(name, location, ArrowReadOptions::default())
+                        .await?;
+                    Ok(Arc::new(DataFrameImpl::new(ctx.state, &plan)))
+                }
                 _ => Err(DataFusionError::NotImplemented(format!(
                     "Unsupported file type {:?}.",
                     file_type
@@ -21,6 +21,7 @@ use crate::error::BallistaError;
 use crate::serde::{from_proto_binary_op, proto_error, protobuf, str_to_byte};
 use crate::{convert_box_required, convert_required};
 use datafusion::arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use datafusion::datasource::file_format::arrow::ArrowFormat;
 use datafusion::datasource::file_format::avro::AvroFormat;
 use datafusion::datasource::file_format::csv::CsvFormat;
 use datafusion::datasource::file_format::parquet::ParquetFormat;
@@ -29,7 +30,7 @@ use datafusion::datasource::listing::{ListingOptions, ListingTable};
 use datafusion::datasource::object_store::local::LocalFileSystem;
 use datafusion::datasource::object_store::{FileMeta, SizedFile};
 use datafusion::logical_plan::window_frames::{
-    WindowFrame, WindowFrameBound, WindowFrameUnits,
+    WindowFrame, WindowFrameBound, WindowFrameOffset, WindowFrameUnits,
 };
 use datafusion::logical_plan::{
     abs, acos, asin, atan, ceil, cos, digest, exp, floor, ln, log10, log2, round, signum,
@@ -166,28 +167,37 @@ impl TryInto<LogicalPlan> for &protobuf::LogicalPlanNode {
                     .map(|e| e.try_into())
                     .collect::<Result<Vec<_>, _>>()?;
 
-                let file_format: Arc<dyn FileFormat> =
-                    match scan.file_format_type.as_ref().ok_or_else(|| {
+                let file_format: Arc<dyn FileFormat> = match scan
+                    .file_format_type
+                    .as_ref()
+                    .ok_or_else(|| {
                         proto_error(format!(
                             "logical_plan::from_proto() Unsupported file format '{:?}'",
                             self
                         ))
                     })? {
-                        &FileFormatType::Parquet(protobuf::ParquetFormat {
-                            enable_pruning,
-                        }) => Arc::new(
-                            ParquetFormat::default().with_enable_pruning(enable_pruning),
-                        ),
-                        FileFormatType::Csv(protobuf::CsvFormat {
-                            has_header,
-                            delimiter,
-                        }) => Arc::new(
+                    &FileFormatType::Parquet(protobuf::ParquetFormat {
+                        enable_pruning,
+                    }) => Arc::new(
+                        ParquetFormat::default().with_enable_pruning(enable_pruning),
+                    ),
+                    FileFormatType::Csv(protobuf::CsvFormat {
+                        has_header,
+                        delimiter,
+                        file_compression_type,
+                    }) => {
+                        let file_compression_type: protobuf::FileCompressionType =
+                            (*file_compression_type).try_into()?;
+                        Arc::new(
                             CsvFormat::default()
                                 .with_has_header(*has_header)
-                                .with_delimiter(str_to_byte(delimiter)?),
-                        ),
-                        FileFormatType::Avro(..) => Arc::new(AvroFormat::default()),
-                    };
+                                .with_delimiter(str_to_byte(delimiter)?)
+                                .with_file_compression_type(file_compression_type.into()),
+                        )
+                    }
+                    FileFormatType::Avro(..) => Arc::new(AvroFormat::default()),
+                    FileFormatType::Arrow(..) => Arc::new(ArrowFormat::default()),
+                };
 
                 let options = ListingOptions {
                     file_extension: scan.file_extension.clone(),
@@ -270,6 +280,8 @@ impl TryInto<LogicalPlan> for &protobuf::LogicalPlanNode {
 
                 let pb_file_type: protobuf::FileType =
                     create_extern_table.file_type.try_into()?;
+                let pb_file_compression_type: protobuf::FileCompressionType =
+                    create_extern_table.file_compression_type.try_into()?;
 
                 Ok(LogicalPlan::CreateExternalTable(CreateExternalTable {
                     schema: pb_schema.try_into()?,
@@ -277,6 +289,7 @@ impl TryInto<LogicalPlan> for &protobuf::LogicalPlanNode {
                     location: create_extern_table.location.clone(),
                     file_type: pb_file_type.into(),
                     has_header: create_extern_table.has_header,
+                    file_compression_type: pb_file_compression_type.into(),
                 }))
             }
             LogicalPlanType::Analyze(analyze) => {
@@ -962,8 +975,18 @@ impl TryInto<Expr> for &protobuf::LogicalExprNode {
 
                 Ok(Expr::AggregateFunction {
                     fun,
-                    args: vec![parse_required_expr(&expr.expr)?],
+                    args: expr
+                        .expr
+                        .iter()
+                        .map(|e| e.try_into())
+                        .collect::<Result<Vec<_>, _>>()?,
                     distinct: false, //TODO
+                    filter: parse_optional_expr(&expr.filter)?.map(Box::new),
+                    order_by: expr
+                        .order_by
+                        .iter()
+                        .map(|e| e.try_into())
+                        .collect::<Result<Vec<_>, _>>()?,
                 })
             }
             ExprType::Alias(alias) => Ok(Expr::Alias(
@@ -1184,6 +1207,7 @@ impl TryFrom<i32> for protobuf::FileType {
             _x if _x == FileType::Parquet as i32 => Ok(FileType::Parquet),
             _x if _x == FileType::Csv as i32 => Ok(FileType::Csv),
             _x if _x == FileType::Avro as i32 => Ok(FileType::Avro),
+            _x if _x == FileType::Arrow as i32 => Ok(FileType::Arrow),
             invalid => Err(BallistaError::General(format!(
                 "Attempted to convert invalid i32 to protobuf::Filetype: {}",
                 invalid
@@ -1201,6 +1225,49 @@ impl Into<datafusion::sql::parser::FileType> for protobuf::FileType {
             protobuf::FileType::Parquet => FileType::Parquet,
             protobuf::FileType::Csv => FileType::CSV,
             protobuf::FileType::Avro => FileType::Avro,
+            protobuf::FileType::Arrow => FileType::Arrow,
+        }
+    }
+}
+
+impl TryFrom<i32> for protobuf::FileCompressionType {
+    type Error = BallistaError;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        use protobuf::FileCompressionType;
+        match value {
+            _x if _x == FileCompressionType::Uncompressed as i32 => {
+                Ok(FileCompressionType::Uncompressed)
+            }
+            _x if _x == FileCompressionType::Gzip as i32 => Ok(FileCompressionType::Gzip),
+            _x if _x == FileCompressionType::Bzip2 as i32 => {
+                Ok(FileCompressionType::Bzip2)
+            }
+            _x if _x == FileCompressionType::Xz as i32 => Ok(FileCompressionType::Xz),
+            _x if _x == FileCompressionType::Zstd as i32 => Ok(FileCompressionType::Zstd),
+            invalid => Err(BallistaError::General(format!(
+                "Attempted to convert invalid i32 to protobuf::FileCompressionType: {}",
+                invalid
+            ))),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<datafusion::datasource::file_format::compression::FileCompressionType>
+    for protobuf::FileCompressionType
+{
+    fn into(
+        self,
+    ) -> datafusion::datasource::file_format::compression::FileCompressionType {
+        use datafusion::datasource::file_format::compression::FileCompressionType;
+        match self {
+            protobuf::FileCompressionType::Uncompressed => {
+                FileCompressionType::Uncompressed
+            }
+            protobuf::FileCompressionType::Gzip => FileCompressionType::Gzip,
+            protobuf::FileCompressionType::Bzip2 => FileCompressionType::Bzip2,
+            protobuf::FileCompressionType::Xz => FileCompressionType::Xz,
+            protobuf::FileCompressionType::Zstd => FileCompressionType::Zstd,
         }
     }
 }
@@ -1247,16 +1314,22 @@ impl TryFrom<protobuf::WindowFrameBound> for WindowFrameBound {
             protobuf::WindowFrameBoundType::CurrentRow => {
                 Ok(WindowFrameBound::CurrentRow)
             }
-            protobuf::WindowFrameBoundType::Preceding => {
-                // FIXME implement bound value parsing
-                // https://github.com/apache/arrow-datafusion/issues/361
-                Ok(WindowFrameBound::Preceding(Some(1)))
-            }
-            protobuf::WindowFrameBoundType::Following => {
-                // FIXME implement bound value parsing
-                // https://github.com/apache/arrow-datafusion/issues/361
-                Ok(WindowFrameBound::Following(Some(1)))
-            }
+            protobuf::WindowFrameBoundType::Preceding => Ok(WindowFrameBound::Preceding(
+                bound.bound_value.map(Into::into),
+            )),
+            protobuf::WindowFrameBoundType::Following => Ok(WindowFrameBound::Following(
+                bound.bound_value.map(Into::into),
+            )),
+        }
+    }
+}
+
+impl From<protobuf::window_frame_bound::BoundValue> for WindowFrameOffset {
+    fn from(value: protobuf::window_frame_bound::BoundValue) -> Self {
+        match value {
+            protobuf::window_frame_bound::BoundValue::Value(v) => Self::Value(v),
+            protobuf::window_frame_bound::BoundValue::YearMonth(v) => Self::YearMonth(v),
+            protobuf::window_frame_bound::BoundValue::DayTime(v) => Self::DayTime(v),
         }
     }
 }
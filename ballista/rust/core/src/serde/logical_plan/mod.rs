@@ -24,6 +24,10 @@ mod roundtrip_tests {
     use super::super::{super::error::Result, protobuf};
     use crate::error::BallistaError;
     use core::panic;
+    use datafusion::datasource::file_format::compression::FileCompressionType;
+    use datafusion::logical_plan::window_frames::{
+        WindowFrame, WindowFrameBound, WindowFrameOffset, WindowFrameUnits,
+    };
     use datafusion::logical_plan::Repartition;
     use datafusion::{
         arrow::datatypes::{DataType, Field, IntervalUnit, Schema, TimeUnit},
@@ -649,11 +653,12 @@ mod roundtrip_tests {
 
         let df_schema_ref = schema.to_dfschema_ref()?;
 
-        let filetypes: [FileType; 4] = [
+        let filetypes: [FileType; 5] = [
             FileType::NdJson,
             FileType::Parquet,
             FileType::CSV,
             FileType::Avro,
+            FileType::Arrow,
         ];
 
         for file in filetypes.iter() {
@@ -664,6 +669,7 @@ mod roundtrip_tests {
                     location: String::from("employee.csv"),
                     file_type: *file,
                     has_header: true,
+                    file_compression_type: FileCompressionType::Uncompressed,
                 });
 
             roundtrip_test!(create_table_node);
@@ -838,6 +844,26 @@ mod roundtrip_tests {
         Ok(())
     }
 
+    #[test]
+    fn recursive_query_not_supported() -> Result<()> {
+        let recursive_term = LogicalPlanBuilder::empty(false)
+            .build()
+            .map_err(BallistaError::DataFusionError)?;
+        let plan = LogicalPlanBuilder::empty(true)
+            .to_recursive_query("t", recursive_term)
+            .and_then(|builder| builder.build())
+            .map_err(BallistaError::DataFusionError)?;
+
+        let result: Result<protobuf::LogicalPlanNode> = (&plan).try_into();
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string().contains("Not yet supported in Ballista"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn roundtrip_logical_plan() -> Result<()> {
         let schema = Schema::new(vec![
@@ -988,4 +1014,44 @@ mod roundtrip_tests {
 
         Ok(())
     }
+
+    #[test]
+    fn roundtrip_aggregate_filter_and_order_by() -> Result<()> {
+        let test_expr = max(col("a"))
+            .filter(col("b").gt(lit(1)))?
+            .order_by(vec![col("c").sort(false, true)])?;
+
+        roundtrip_test!(test_expr, protobuf::LogicalExprNode, Expr);
+
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_window_frame() -> Result<()> {
+        let frames = vec![
+            WindowFrame {
+                units: WindowFrameUnits::Rows,
+                start_bound: WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(
+                    3,
+                ))),
+                end_bound: WindowFrameBound::Following(None),
+            },
+            WindowFrame {
+                units: WindowFrameUnits::Range,
+                start_bound: WindowFrameBound::Preceding(Some(
+                    WindowFrameOffset::DayTime(1 << 32),
+                )),
+                end_bound: WindowFrameBound::Following(Some(
+                    WindowFrameOffset::YearMonth(2),
+                )),
+            },
+        ];
+        for frame in frames {
+            let proto: protobuf::WindowFrame = frame.into();
+            let round_trip: WindowFrame = proto.try_into()?;
+            assert_eq!(format!("{:?}", frame), format!("{:?}", round_trip));
+        }
+
+        Ok(())
+    }
 }
@@ -24,7 +24,9 @@ use crate::serde::{byte_to_string, protobuf, BallistaError};
 use datafusion::arrow::datatypes::{
     DataType, Field, IntervalUnit, Schema, SchemaRef, TimeUnit,
 };
+use datafusion::datasource::file_format::arrow::ArrowFormat;
 use datafusion::datasource::file_format::avro::AvroFormat;
+use datafusion::datasource::file_format::compression::FileCompressionType;
 use datafusion::datasource::file_format::csv::CsvFormat;
 use datafusion::datasource::TableProvider;
 
@@ -35,7 +37,7 @@ use datafusion::logical_plan::plan::{
 };
 use datafusion::logical_plan::{
     exprlist_to_fields,
-    window_frames::{WindowFrame, WindowFrameBound, WindowFrameUnits},
+    window_frames::{WindowFrame, WindowFrameBound, WindowFrameOffset, WindowFrameUnits},
     Column, CreateExternalTable, CrossJoin, Expr, JoinConstraint, JoinType, Limit,
     LogicalPlan, Repartition, TableScan, Values,
 };
@@ -632,9 +634,14 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
                         FileFormatType::Csv(protobuf::CsvFormat {
                             delimiter: byte_to_string(csv.delimiter())?,
                             has_header: csv.has_header(),
+                            file_compression_type: protobuf::FileCompressionType::from(
+                                csv.file_compression_type(),
+                            ) as i32,
                         })
                     } else if any.is::<AvroFormat>() {
                         FileFormatType::Avro(protobuf::AvroFormat {})
+                    } else if any.is::<ArrowFormat>() {
+                        FileFormatType::Arrow(protobuf::ArrowFormat {})
                     } else {
                         return Err(proto_error(format!(
                             "Error converting file format, {:?} is invalid as a datafusion foramt.",
@@ -845,6 +852,7 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
                 location,
                 file_type,
                 has_header,
+                file_compression_type,
                 schema: df_schema,
             }) => {
                 use datafusion::sql::parser::FileType;
@@ -854,7 +862,10 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
                     FileType::Parquet => protobuf::FileType::Parquet,
                     FileType::CSV => protobuf::FileType::Csv,
                     FileType::Avro => protobuf::FileType::Avro,
+                    FileType::Arrow => protobuf::FileType::Arrow,
                 };
+                let pb_file_compression_type =
+                    protobuf::FileCompressionType::from(*file_compression_type);
 
                 Ok(protobuf::LogicalPlanNode {
                     logical_plan_type: Some(LogicalPlanType::CreateExternalTable(
@@ -864,6 +875,7 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
                             file_type: pb_file_type as i32,
                             has_header: *has_header,
                             schema: Some(df_schema.into()),
+                            file_compression_type: pb_file_compression_type as i32,
                         },
                     )),
                 })
@@ -892,6 +904,9 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
             }
             LogicalPlan::Extension { .. } => unimplemented!(),
             LogicalPlan::Union(_) => unimplemented!(),
+            LogicalPlan::RecursiveQuery(_) => Err(proto_error(
+                "Error converting RecursiveQuery. Not yet supported in Ballista",
+            )),
             LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
                 let left: protobuf::LogicalPlanNode = left.as_ref().try_into()?;
                 let right: protobuf::LogicalPlanNode = right.as_ref().try_into()?;
@@ -910,6 +925,21 @@ impl TryInto<protobuf::LogicalPlanNode> for &LogicalPlan {
             LogicalPlan::DropTable(_) => Err(proto_error(
                 "Error converting DropTable. Not yet supported in Ballista",
             )),
+            LogicalPlan::CreateView(_) => Err(proto_error(
+                "Error converting CreateView. Not yet supported in Ballista",
+            )),
+            LogicalPlan::DropView(_) => Err(proto_error(
+                "Error converting DropView. Not yet supported in Ballista",
+            )),
+            LogicalPlan::RefreshTable(_) => Err(proto_error(
+                "Error converting RefreshTable. Not yet supported in Ballista",
+            )),
+            LogicalPlan::Insert(_) => Err(proto_error(
+                "Error converting Insert. Not yet supported in Ballista",
+            )),
+            LogicalPlan::CopyTo(_) => Err(proto_error(
+                "Error converting CopyTo. Not yet supported in Ballista",
+            )),
         }
     }
 }
@@ -1014,24 +1044,28 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
                 })
             }
             Expr::AggregateFunction {
-                ref fun, ref args, ..
+                ref fun,
+                ref args,
+                ref filter,
+                ref order_by,
+                ..
             } => {
-                let aggr_function = match fun {
-                    AggregateFunction::ApproxDistinct => {
-                        protobuf::AggregateFunction::ApproxDistinct
-                    }
-                    AggregateFunction::ArrayAgg => protobuf::AggregateFunction::ArrayAgg,
-                    AggregateFunction::Min => protobuf::AggregateFunction::Min,
-                    AggregateFunction::Max => protobuf::AggregateFunction::Max,
-                    AggregateFunction::Sum => protobuf::AggregateFunction::Sum,
-                    AggregateFunction::Avg => protobuf::AggregateFunction::Avg,
-                    AggregateFunction::Count => protobuf::AggregateFunction::Count,
+                let aggr_function = protobuf::AggregateFunction::from(fun);
+                let filter: Option<Box<protobuf::LogicalExprNode>> = match filter {
+                    Some(filter) => Some(Box::new(filter.as_ref().try_into()?)),
+                    None => None,
                 };
-
-                let arg = &args[0];
                 let aggregate_expr = Box::new(protobuf::AggregateExprNode {
                     aggr_function: aggr_function.into(),
-                    expr: Some(Box::new(arg.try_into()?)),
+                    expr: args
+                        .iter()
+                        .map(|e| e.try_into())
+                        .collect::<Result<Vec<_>, _>>()?,
+                    filter,
+                    order_by: order_by
+                        .iter()
+                        .map(|e| e.try_into())
+                        .collect::<Result<Vec<_>, _>>()?,
                 });
                 Ok(protobuf::LogicalExprNode {
                     expr_type: Some(ExprType::AggregateExpr(aggregate_expr)),
@@ -1180,6 +1214,17 @@ impl TryInto<protobuf::LogicalExprNode> for &Expr {
             Expr::Wildcard => Ok(protobuf::LogicalExprNode {
                 expr_type: Some(protobuf::logical_expr_node::ExprType::Wildcard(true)),
             }),
+            Expr::GroupingSet(_) => Err(BallistaError::NotImplemented(format!(
+                "Grouping sets are not supported: {:?}",
+                self
+            ))),
+            Expr::Exists { .. } | Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => {
+                Err(BallistaError::General(format!(
+                    "Subquery expressions are not supported, they must be rewritten \
+                    into joins by the optimizer: {:?}",
+                    self
+                )))
+            }
             _ => unimplemented!(),
         }
     }
@@ -1256,6 +1301,17 @@ impl From<&AggregateFunction> for protobuf::AggregateFunction {
             AggregateFunction::Count => Self::Count,
             AggregateFunction::ApproxDistinct => Self::ApproxDistinct,
             AggregateFunction::ArrayAgg => Self::ArrayAgg,
+            AggregateFunction::Grouping => Self::Grouping,
+            AggregateFunction::Stddev => Self::Stddev,
+            AggregateFunction::StddevPop => Self::StddevPop,
+            AggregateFunction::Variance => Self::Variance,
+            AggregateFunction::VariancePop => Self::VariancePop,
+            AggregateFunction::Covariance => Self::Covariance,
+            AggregateFunction::CovariancePop => Self::CovariancePop,
+            AggregateFunction::Correlation => Self::Correlation,
+            AggregateFunction::Median => Self::Median,
+            AggregateFunction::PercentileCont => Self::PercentileCont,
+            AggregateFunction::ApproxPercentileCont => Self::ApproxPercentileCont,
         }
     }
 }
@@ -1298,16 +1354,38 @@ impl From<WindowFrameBound> for protobuf::WindowFrameBound {
             },
             WindowFrameBound::Preceding(v) => protobuf::WindowFrameBound {
                 window_frame_bound_type: protobuf::WindowFrameBoundType::Preceding.into(),
-                bound_value: v.map(protobuf::window_frame_bound::BoundValue::Value),
+                bound_value: v.map(Into::into),
             },
             WindowFrameBound::Following(v) => protobuf::WindowFrameBound {
                 window_frame_bound_type: protobuf::WindowFrameBoundType::Following.into(),
-                bound_value: v.map(protobuf::window_frame_bound::BoundValue::Value),
+                bound_value: v.map(Into::into),
             },
         }
     }
 }
 
+impl From<FileCompressionType> for protobuf::FileCompressionType {
+    fn from(file_compression_type: FileCompressionType) -> Self {
+        match file_compression_type {
+            FileCompressionType::Uncompressed => Self::Uncompressed,
+            FileCompressionType::Gzip => Self::Gzip,
+            FileCompressionType::Bzip2 => Self::Bzip2,
+            FileCompressionType::Xz => Self::Xz,
+            FileCompressionType::Zstd => Self::Zstd,
+        }
+    }
+}
+
+impl From<WindowFrameOffset> for protobuf::window_frame_bound::BoundValue {
+    fn from(offset: WindowFrameOffset) -> Self {
+        match offset {
+            WindowFrameOffset::Value(v) => Self::Value(v),
+            WindowFrameOffset::YearMonth(v) => Self::YearMonth(v),
+            WindowFrameOffset::DayTime(v) => Self::DayTime(v),
+        }
+    }
+}
+
 impl From<WindowFrame> for protobuf::WindowFrame {
     fn from(window: WindowFrame) -> Self {
         protobuf::WindowFrame {
@@ -41,13 +41,14 @@ use datafusion::datasource::PartitionedFile;
 use datafusion::execution::context::{
     ExecutionConfig, ExecutionContextState, ExecutionProps,
 };
+use datafusion::execution::runtime_env::RuntimeEnv;
 use datafusion::logical_plan::{
     window_frames::WindowFrame, DFSchema, Expr, JoinConstraint, JoinType,
 };
 use datafusion::physical_plan::aggregates::{create_aggregate_expr, AggregateFunction};
 use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::file_format::{
-    AvroExec, CsvExec, ParquetExec, PhysicalPlanConfig,
+    ArrowExec, AvroExec, CsvExec, ParquetExec, PhysicalPlanConfig,
 };
 use datafusion::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
 use datafusion::physical_plan::hash_join::PartitionMode;
@@ -62,9 +63,9 @@ use datafusion::physical_plan::{
     cross_join::CrossJoinExec,
     empty::EmptyExec,
     expressions::{
-        col, Avg, BinaryExpr, CaseExpr, CastExpr, Column, InListExpr, IsNotNullExpr,
-        IsNullExpr, Literal, NegativeExpr, NotExpr, PhysicalSortExpr, TryCastExpr,
-        DEFAULT_DATAFUSION_CAST_OPTIONS,
+        col, AggregateFilter, Avg, BinaryExpr, CaseExpr, CastExpr, Column, InListExpr,
+        IsNotNullExpr, IsNullExpr, Literal, NegativeExpr, NotExpr, PhysicalSortExpr,
+        TryCastExpr, DEFAULT_DATAFUSION_CAST_OPTIONS,
     },
     filter::FilterExec,
     functions::{self, BuiltinScalarFunction, ScalarFunctionExpr},
@@ -120,11 +121,18 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                     .try_into()?;
                 Ok(Arc::new(FilterExec::try_new(predicate, input)?))
             }
-            PhysicalPlanType::CsvScan(scan) => Ok(Arc::new(CsvExec::new(
-                scan.base_conf.as_ref().unwrap().try_into()?,
-                scan.has_header,
-                str_to_byte(&scan.delimiter)?,
-            ))),
+            PhysicalPlanType::CsvScan(scan) => {
+                let file_compression_type: protobuf::FileCompressionType =
+                    scan.file_compression_type.try_into()?;
+                Ok(Arc::new(
+                    CsvExec::new(
+                        scan.base_conf.as_ref().unwrap().try_into()?,
+                        scan.has_header,
+                        str_to_byte(&scan.delimiter)?,
+                    )
+                    .with_file_compression_type(file_compression_type.into()),
+                ))
+            }
             PhysicalPlanType::ParquetScan(scan) => {
                 Ok(Arc::new(ParquetExec::new(
                     scan.base_conf.as_ref().unwrap().try_into()?,
@@ -135,6 +143,9 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
             PhysicalPlanType::AvroScan(scan) => Ok(Arc::new(AvroExec::new(
                 scan.base_conf.as_ref().unwrap().try_into()?,
             ))),
+            PhysicalPlanType::ArrowScan(scan) => Ok(Arc::new(ArrowExec::new(
+                scan.base_conf.as_ref().unwrap().try_into()?,
+            ))),
             PhysicalPlanType::CoalesceBatches(coalesce_batches) => {
                 let input: Arc<dyn ExecutionPlan> =
                     convert_box_required!(coalesce_batches.input)?;
@@ -302,13 +313,28 @@ impl TryInto<Arc<dyn ExecutionPlan>> for &protobuf::PhysicalPlanNode {
                                         },
                                     )?;
 
-                                Ok(create_aggregate_expr(
+                                let input_phy_expr = agg_node
+                                    .expr
+                                    .iter()
+                                    .map(|e| e.try_into())
+                                    .collect::<Result<Vec<Arc<dyn PhysicalExpr>>, _>>()?;
+
+                                let aggregate = create_aggregate_expr(
                                     &aggr_function.into(),
                                     false,
-                                    &[convert_box_required!(agg_node.expr)?],
+                                    &input_phy_expr,
                                     &physical_schema,
                                     name.to_string(),
-                                )?)
+                                )?;
+                                match &agg_node.filter {
+                                    Some(filter) => Ok(Arc::new(AggregateFilter::try_new(
+                                        aggregate,
+                                        filter.as_ref().try_into()?,
+                                        &physical_schema,
+                                    )?)
+                                        as Arc<dyn AggregateExpr>),
+                                    None => Ok(aggregate),
+                                }
                             }
                             _ => Err(BallistaError::General(
                                 "Invalid aggregate  expression for HashAggregateExec"
@@ -626,6 +652,7 @@ impl TryFrom<&protobuf::PhysicalExprNode> for Arc<dyn PhysicalExpr> {
                     config: ExecutionConfig::new(),
                     execution_props: ExecutionProps::new(),
                     object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+                    runtime_env: Arc::new(RuntimeEnv::default()),
                 };
 
                 let fun_expr = functions::create_physical_fun(
@@ -798,6 +825,7 @@ impl TryInto<PhysicalPlanConfig> for &protobuf::FileScanExecConf {
             batch_size: self.batch_size as usize,
             limit: self.limit.as_ref().map(|sl| sl.limit as usize),
             table_partition_cols: vec![],
+            metadata_cache: None,
         })
     }
 }
@@ -27,18 +27,26 @@ mod roundtrip_tests {
             compute::kernels::sort::SortOptions,
             datatypes::{DataType, Field, Schema},
         },
+        datasource::{
+            file_format::compression::FileCompressionType,
+            object_store::local::LocalFileSystem,
+        },
         logical_plan::{JoinType, Operator},
         physical_plan::{
             empty::EmptyExec,
             expressions::{binary, col, lit, InListExpr, NotExpr},
-            expressions::{Avg, Column, PhysicalSortExpr},
+            expressions::{
+                AggregateFilter, ApproxPercentileCont, Avg, Column, Covariance,
+                PhysicalSortExpr, StatsType,
+            },
+            file_format::{CsvExec, PhysicalPlanConfig},
             filter::FilterExec,
             hash_aggregate::{AggregateMode, HashAggregateExec},
             hash_join::{HashJoinExec, PartitionMode},
             limit::{GlobalLimitExec, LocalLimitExec},
             sort::SortExec,
             AggregateExpr, ColumnarValue, Distribution, ExecutionPlan, Partitioning,
-            PhysicalExpr,
+            PhysicalExpr, Statistics,
         },
         scalar::ScalarValue,
     };
@@ -138,6 +146,60 @@ mod roundtrip_tests {
         )?))
     }
 
+    #[test]
+    fn roundtrip_hash_aggregate_with_filter() -> Result<()> {
+        let field_a = Field::new("a", DataType::Int64, false);
+        let field_b = Field::new("b", DataType::Int64, false);
+        let schema = Arc::new(Schema::new(vec![field_a, field_b]));
+
+        let avg = Arc::new(Avg::new(col("b", &schema)?, "AVG(b)", DataType::Float64));
+        let predicate = binary(
+            col("a", &schema)?,
+            Operator::Gt,
+            lit(ScalarValue::Int64(Some(1))),
+            &schema,
+        )?;
+        let aggregates: Vec<Arc<dyn AggregateExpr>> =
+            vec![Arc::new(AggregateFilter::try_new(avg, predicate, &schema)?)];
+
+        roundtrip_test(Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![(col("a", &schema)?, "unused".to_string())],
+            aggregates,
+            Arc::new(EmptyExec::new(false, schema.clone())),
+            schema,
+        )?))
+    }
+
+    #[test]
+    fn roundtrip_hash_aggregate_with_statistics() -> Result<()> {
+        let field_a = Field::new("a", DataType::Int64, false);
+        let field_b = Field::new("b", DataType::Float64, false);
+        let schema = Arc::new(Schema::new(vec![field_a, field_b]));
+
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![
+            Arc::new(Covariance::new(
+                col("a", &schema)?,
+                col("b", &schema)?,
+                "COVARIANCEPOP(a,b)",
+                StatsType::Population,
+            )),
+            Arc::new(ApproxPercentileCont::try_new(
+                col("b", &schema)?,
+                lit(ScalarValue::Float64(Some(0.9))),
+                "APPROXPERCENTILECONT(b,Float64(0.9))",
+            )?),
+        ];
+
+        roundtrip_test(Arc::new(HashAggregateExec::try_new(
+            AggregateMode::Partial,
+            vec![],
+            aggregates,
+            Arc::new(EmptyExec::new(false, schema.clone())),
+            schema,
+        )?))
+    }
+
     #[test]
     fn roundtrip_filter_with_not_and_in_list() -> Result<()> {
         let field_a = Field::new("a", DataType::Boolean, false);
@@ -201,4 +263,35 @@ mod roundtrip_tests {
             Some(Partitioning::Hash(vec![Arc::new(Column::new("a", 0))], 4)),
         )?))
     }
+
+    #[test]
+    fn roundtrip_csv_scan_compression() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]));
+        let exec_plan: Arc<dyn ExecutionPlan> = Arc::new(
+            CsvExec::new(
+                PhysicalPlanConfig {
+                    object_store: Arc::new(LocalFileSystem {}),
+                    file_schema: schema,
+                    file_groups: vec![],
+                    statistics: Statistics::default(),
+                    projection: None,
+                    batch_size: 1024,
+                    limit: None,
+                    table_partition_cols: vec![],
+                    metadata_cache: None,
+                },
+                false,
+                b'|',
+            )
+            .with_file_compression_type(FileCompressionType::Gzip),
+        );
+
+        let proto: protobuf::PhysicalPlanNode = exec_plan.try_into()?;
+        let result_exec_plan: Arc<dyn ExecutionPlan> = (&proto).try_into()?;
+        let csv_exec = result_exec_plan.as_any().downcast_ref::<CsvExec>().unwrap();
+        assert_eq!(FileCompressionType::Gzip, csv_exec.file_compression_type());
+        assert!(!csv_exec.has_header());
+        assert_eq!(b'|', csv_exec.delimiter());
+        Ok(())
+    }
 }
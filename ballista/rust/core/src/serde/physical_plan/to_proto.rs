@@ -41,10 +41,13 @@ use datafusion::physical_plan::{
     expressions::{CastExpr, TryCastExpr},
     file_format::ParquetExec,
 };
-use datafusion::physical_plan::{file_format::AvroExec, filter::FilterExec};
 use datafusion::physical_plan::{
     file_format::PhysicalPlanConfig, hash_aggregate::AggregateMode,
 };
+use datafusion::physical_plan::{
+    file_format::{ArrowExec, AvroExec},
+    filter::FilterExec,
+};
 use datafusion::{
     datasource::PartitionedFile, physical_plan::coalesce_batches::CoalesceBatchesExec,
 };
@@ -56,7 +59,10 @@ use datafusion::{
 
 use datafusion::physical_plan::{
     empty::EmptyExec,
-    expressions::{Avg, BinaryExpr, Column, Max, Min, Sum},
+    expressions::{
+        AggregateFilter, ApproxPercentileCont, Avg, BinaryExpr, Column, Correlation,
+        Covariance, Max, Median, Min, PercentileCont, StatsType, Stddev, Sum, Variance,
+    },
     Partitioning,
 };
 use datafusion::physical_plan::{AggregateExpr, ExecutionPlan, PhysicalExpr};
@@ -178,6 +184,12 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                 ))),
             })
         } else if let Some(exec) = plan.downcast_ref::<HashAggregateExec>() {
+            if !exec.grouping_sets().is_empty() {
+                return Err(BallistaError::NotImplemented(format!(
+                    "Grouping sets are not supported: {:?}",
+                    exec
+                )));
+            }
             let groups = exec
                 .group_expr()
                 .iter()
@@ -253,6 +265,9 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                         base_conf: Some(exec.base_config().try_into()?),
                         has_header: exec.has_header(),
                         delimiter: byte_to_string(exec.delimiter())?,
+                        file_compression_type: protobuf::FileCompressionType::from(
+                            exec.file_compression_type(),
+                        ) as i32,
                     },
                 )),
             })
@@ -273,6 +288,14 @@ impl TryInto<protobuf::PhysicalPlanNode> for Arc<dyn ExecutionPlan> {
                     },
                 )),
             })
+        } else if let Some(exec) = plan.downcast_ref::<ArrowExec>() {
+            Ok(protobuf::PhysicalPlanNode {
+                physical_plan_type: Some(PhysicalPlanType::ArrowScan(
+                    protobuf::ArrowScanExecNode {
+                        base_conf: Some(exec.base_config().try_into()?),
+                    },
+                )),
+            })
         } else if let Some(exec) = plan.downcast_ref::<ShuffleReaderExec>() {
             let mut partition = vec![];
             for location in &exec.partition {
@@ -412,6 +435,17 @@ impl TryInto<protobuf::PhysicalExprNode> for Arc<dyn AggregateExpr> {
     type Error = BallistaError;
 
     fn try_into(self) -> Result<protobuf::PhysicalExprNode, Self::Error> {
+        if let Some(filter) = self.as_any().downcast_ref::<AggregateFilter>() {
+            let mut node: protobuf::PhysicalExprNode =
+                filter.expr().clone().try_into()?;
+            if let Some(protobuf::physical_expr_node::ExprType::AggregateExpr(
+                aggregate,
+            )) = &mut node.expr_type
+            {
+                aggregate.filter = Some(Box::new(filter.predicate().clone().try_into()?));
+            }
+            return Ok(node);
+        }
         let aggr_function = if self.as_any().downcast_ref::<Avg>().is_some() {
             Ok(protobuf::AggregateFunction::Avg.into())
         } else if self.as_any().downcast_ref::<Sum>().is_some() {
@@ -422,6 +456,36 @@ impl TryInto<protobuf::PhysicalExprNode> for Arc<dyn AggregateExpr> {
             Ok(protobuf::AggregateFunction::Min.into())
         } else if self.as_any().downcast_ref::<Max>().is_some() {
             Ok(protobuf::AggregateFunction::Max.into())
+        } else if let Some(stddev) = self.as_any().downcast_ref::<Stddev>() {
+            Ok(match stddev.stats_type() {
+                StatsType::Sample => protobuf::AggregateFunction::Stddev,
+                StatsType::Population => protobuf::AggregateFunction::StddevPop,
+            }
+            .into())
+        } else if let Some(variance) = self.as_any().downcast_ref::<Variance>() {
+            Ok(match variance.stats_type() {
+                StatsType::Sample => protobuf::AggregateFunction::Variance,
+                StatsType::Population => protobuf::AggregateFunction::VariancePop,
+            }
+            .into())
+        } else if let Some(covariance) = self.as_any().downcast_ref::<Covariance>() {
+            Ok(match covariance.stats_type() {
+                StatsType::Sample => protobuf::AggregateFunction::Covariance,
+                StatsType::Population => protobuf::AggregateFunction::CovariancePop,
+            }
+            .into())
+        } else if self.as_any().downcast_ref::<Correlation>().is_some() {
+            Ok(protobuf::AggregateFunction::Correlation.into())
+        } else if self.as_any().downcast_ref::<Median>().is_some() {
+            Ok(protobuf::AggregateFunction::Median.into())
+        } else if self.as_any().downcast_ref::<PercentileCont>().is_some() {
+            Ok(protobuf::AggregateFunction::PercentileCont.into())
+        } else if self
+            .as_any()
+            .downcast_ref::<ApproxPercentileCont>()
+            .is_some()
+        {
+            Ok(protobuf::AggregateFunction::ApproxPercentileCont.into())
         } else {
             Err(BallistaError::NotImplemented(format!(
                 "Aggregate function not supported: {:?}",
@@ -437,7 +501,8 @@ impl TryInto<protobuf::PhysicalExprNode> for Arc<dyn AggregateExpr> {
             expr_type: Some(protobuf::physical_expr_node::ExprType::AggregateExpr(
                 Box::new(protobuf::PhysicalAggregateExprNode {
                     aggr_function,
-                    expr: Some(Box::new(expressions[0].clone())),
+                    expr: expressions,
+                    filter: None,
                 }),
             )),
         })
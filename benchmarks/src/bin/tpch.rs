@@ -295,8 +295,7 @@ async fn benchmark_datafusion(opt: DataFusionBenchmarkOpt) -> Result<Vec<RecordB
     let mut result: Vec<RecordBatch> = Vec::with_capacity(1);
     for i in 0..opt.iterations {
         let start = Instant::now();
-        let plan = create_logical_plan(&mut ctx, opt.query)?;
-        result = execute_query(&mut ctx, &plan, opt.debug).await?;
+        result = execute_query_sql(&mut ctx, opt.query, opt.debug).await?;
         let elapsed = start.elapsed().as_secs_f64() * 1000.0;
         millis.push(elapsed as f64);
         println!("Query {} iteration {} took {:.1} ms", opt.query, i, elapsed);
@@ -525,6 +524,26 @@ fn create_logical_plan(ctx: &mut ExecutionContext, query: usize) -> Result<Logic
     ctx.create_logical_plan(&sql)
 }
 
+/// Plans and runs the statements of a query, returning the results of the
+/// last one that is not a DDL statement, such as the views of q15
+async fn execute_query_sql(
+    ctx: &mut ExecutionContext,
+    query: usize,
+    debug: bool,
+) -> Result<Vec<RecordBatch>> {
+    let sql = get_query_sql(query)?;
+    let mut result = vec![];
+    for statement in sql.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match ctx.create_logical_plan(statement)? {
+            LogicalPlan::CreateView(_) | LogicalPlan::DropView(_) => {
+                ctx.sql(statement).await?;
+            }
+            plan => result = execute_query(ctx, &plan, debug).await?,
+        }
+    }
+    Ok(result)
+}
+
 async fn execute_query(
     ctx: &mut ExecutionContext,
     plan: &LogicalPlan,
@@ -903,11 +922,21 @@ mod tests {
         run_query(1).await
     }
 
+    #[tokio::test]
+    async fn run_q2() -> Result<()> {
+        run_query(2).await
+    }
+
     #[tokio::test]
     async fn run_q3() -> Result<()> {
         run_query(3).await
     }
 
+    #[tokio::test]
+    async fn run_q4() -> Result<()> {
+        run_query(4).await
+    }
+
     #[tokio::test]
     async fn run_q5() -> Result<()> {
         run_query(5).await
@@ -938,6 +967,11 @@ mod tests {
         run_query(10).await
     }
 
+    #[tokio::test]
+    async fn run_q11() -> Result<()> {
+        run_query(11).await
+    }
+
     #[tokio::test]
     async fn run_q12() -> Result<()> {
         run_query(12).await
@@ -953,11 +987,46 @@ mod tests {
         run_query(14).await
     }
 
+    #[tokio::test]
+    async fn run_q15() -> Result<()> {
+        run_query(15).await
+    }
+
+    #[tokio::test]
+    async fn run_q16() -> Result<()> {
+        run_query(16).await
+    }
+
+    #[tokio::test]
+    async fn run_q17() -> Result<()> {
+        run_query(17).await
+    }
+
+    #[tokio::test]
+    async fn run_q18() -> Result<()> {
+        run_query(18).await
+    }
+
     #[tokio::test]
     async fn run_q19() -> Result<()> {
         run_query(19).await
     }
 
+    #[tokio::test]
+    async fn run_q20() -> Result<()> {
+        run_query(20).await
+    }
+
+    #[tokio::test]
+    async fn run_q21() -> Result<()> {
+        run_query(21).await
+    }
+
+    #[tokio::test]
+    async fn run_q22() -> Result<()> {
+        run_query(22).await
+    }
+
     /// Specialised String representation
     fn col_str(column: &ArrayRef, row_index: usize) -> String {
         if column.is_null(row_index) {
@@ -1186,8 +1255,7 @@ mod tests {
             ctx.register_table(table, Arc::new(provider))?;
         }
 
-        let plan = create_logical_plan(&mut ctx, n)?;
-        execute_query(&mut ctx, &plan, false).await?;
+        execute_query_sql(&mut ctx, n, false).await?;
 
         Ok(())
     }
@@ -18,6 +18,7 @@
 //! DataFrame API for building and executing query plans.
 
 use crate::arrow::record_batch::RecordBatch;
+use crate::datasource::file_format::FileFormat;
 use crate::error::Result;
 use crate::logical_plan::{
     DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, Partitioning,
@@ -405,4 +406,43 @@ pub trait DataFrame: Send + Sync {
     /// # }
     /// ```
     fn except(&self, dataframe: Arc<dyn DataFrame>) -> Result<Arc<dyn DataFrame>>;
+
+    /// Execute the DataFrame and write its rows to the new directory `path` in
+    /// the given format, in Hive-style `col=value` subdirectories for the
+    /// columns `partition_cols`. The partition columns are not stored in the
+    /// files, the directory can be read back with
+    /// [`register_listing_table`](crate::execution::context::ExecutionContext::register_listing_table)
+    /// using the same columns as `table_partition_cols`.
+    ///
+    /// Each output partition writes its own files, starting a new one when
+    /// the current file of a directory has `max_rows_per_file` rows.
+    ///
+    /// ```
+    /// # use datafusion::prelude::*;
+    /// # use datafusion::error::Result;
+    /// # use datafusion::datasource::file_format::parquet::ParquetFormat;
+    /// # use std::sync::Arc;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<()> {
+    /// let mut ctx = ExecutionContext::new();
+    /// let df = ctx.read_csv("tests/example.csv", CsvReadOptions::new()).await?;
+    /// let dir = tempfile::tempdir()?;
+    /// let path = dir.path().join("out");
+    /// df.write_partitioned(
+    ///     path.to_str().unwrap(),
+    ///     Arc::new(ParquetFormat::default()),
+    ///     &["a"],
+    ///     Some(1000),
+    /// )
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn write_partitioned(
+        &self,
+        path: &str,
+        format: Arc<dyn FileFormat>,
+        partition_cols: &[&str],
+        max_rows_per_file: Option<usize>,
+    ) -> Result<()>;
 }
@@ -0,0 +1,85 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! The table referenced by the recursive term of a recursive common table
+//! expression.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+
+use crate::datasource::datasource::TableType;
+use crate::datasource::TableProvider;
+use crate::error::Result;
+use crate::logical_plan::Expr;
+use crate::physical_plan::work_table::WorkTableExec;
+use crate::physical_plan::ExecutionPlan;
+
+/// The rows produced by the previous iteration of a recursive common table
+/// expression, referenced by name in its recursive term.
+pub struct CteWorkTable {
+    name: String,
+    schema: SchemaRef,
+}
+
+impl CteWorkTable {
+    /// Initialize the work table of the recursive common table expression
+    /// `name`, which produces rows of `schema`
+    pub fn new(name: &str, schema: SchemaRef) -> Self {
+        Self {
+            name: name.to_owned(),
+            schema,
+        }
+    }
+
+    /// The name of the recursive common table expression
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[async_trait]
+impl TableProvider for CteWorkTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        projection: &Option<Vec<usize>>,
+        _batch_size: usize,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // the work table is filled by the enclosing `RecursiveQueryExec`
+        Ok(Arc::new(WorkTableExec::try_new(
+            self.name.clone(),
+            self.schema.clone(),
+            projection.clone(),
+        )?))
+    }
+}
@@ -23,7 +23,7 @@ use std::sync::Arc;
 use async_trait::async_trait;
 
 use crate::arrow::datatypes::SchemaRef;
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
 use crate::physical_plan::ExecutionPlan;
 
@@ -94,4 +94,18 @@ pub trait TableProvider: Sync + Send {
     ) -> Result<TableProviderFilterPushDown> {
         Ok(TableProviderFilterPushDown::Unsupported)
     }
+
+    /// Create an ExecutionPlan that will insert the rows produced by `input`
+    /// into the table, replacing the current content of the table if
+    /// `overwrite` is set. The plan produces a single row with the number of
+    /// rows inserted.
+    async fn insert_into(
+        &self,
+        _input: Arc<dyn ExecutionPlan>,
+        _overwrite: bool,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        Err(DataFusionError::NotImplemented(
+            "Inserting into this table is not supported".to_string(),
+        ))
+    }
 }
@@ -0,0 +1,279 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Apache Arrow IPC file format abstractions
+
+use std::any::Any;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::ipc;
+use arrow::ipc::writer::FileWriter as IpcFileWriter;
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use super::FileFormat;
+use crate::datasource::object_store::{ObjectReader, ObjectReaderStream};
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Expr;
+use crate::physical_plan::file_format::{ArrowExec, FileWriter, PhysicalPlanConfig};
+use crate::physical_plan::ExecutionPlan;
+use crate::physical_plan::Statistics;
+
+/// The default file extension of Arrow IPC files
+pub const DEFAULT_ARROW_EXTENSION: &str = ".arrow";
+
+/// The magic bytes at the start and at the end of Arrow IPC files
+const ARROW_MAGIC: [u8; 6] = *b"ARROW1";
+
+/// Arrow IPC `FileFormat` implementation.
+#[derive(Default, Debug)]
+pub struct ArrowFormat;
+
+#[async_trait]
+impl FileFormat for ArrowFormat {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    async fn infer_schema(&self, mut readers: ObjectReaderStream) -> Result<SchemaRef> {
+        let mut schemas = vec![];
+        while let Some(obj_reader) = readers.next().await {
+            let schema = read_arrow_schema(obj_reader?.as_ref())?;
+            schemas.push(schema);
+        }
+        let merged_schema = Schema::try_merge(schemas)?;
+        Ok(Arc::new(merged_schema))
+    }
+
+    async fn infer_stats(
+        &self,
+        _reader: Arc<dyn ObjectReader>,
+        _table_schema: SchemaRef,
+    ) -> Result<Statistics> {
+        Ok(Statistics::default())
+    }
+
+    async fn create_physical_plan(
+        &self,
+        conf: PhysicalPlanConfig,
+        _filters: &[Expr],
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exec = ArrowExec::new(conf);
+        Ok(Arc::new(exec))
+    }
+
+    fn file_extension(&self) -> &str {
+        DEFAULT_ARROW_EXTENSION
+    }
+
+    fn create_writer(
+        &self,
+        file: File,
+        schema: SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        Ok(Box::new(IpcFileWriter::try_new(file, &schema)?))
+    }
+}
+
+/// Read the schema of an Arrow IPC file from its footer, without reading the
+/// record batches of the file.
+///
+/// The file ends with the footer, the length of the footer as a little endian
+/// 32 bits integer and the magic bytes.
+fn read_arrow_schema(reader: &dyn ObjectReader) -> Result<Schema> {
+    let length = reader.length();
+    let trailer_len = 4 + ARROW_MAGIC.len();
+    if length < (ARROW_MAGIC.len() + trailer_len) as u64 {
+        return Err(DataFusionError::Execution(
+            "Invalid Arrow IPC file: the file is too small".to_string(),
+        ));
+    }
+
+    let mut trailer = vec![0; trailer_len];
+    reader
+        .sync_chunk_reader(length - trailer_len as u64, trailer_len)?
+        .read_exact(&mut trailer)?;
+    if trailer[4..] != ARROW_MAGIC {
+        return Err(DataFusionError::Execution(
+            "Invalid Arrow IPC file: the file does not end with the magic bytes"
+                .to_string(),
+        ));
+    }
+
+    let footer_len = i32::from_le_bytes(trailer[..4].try_into().unwrap());
+    let footer_start = (length - trailer_len as u64)
+        .checked_sub(footer_len as u64)
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "Invalid Arrow IPC file: invalid footer length {}",
+                footer_len
+            ))
+        })?;
+
+    let mut footer = vec![0; footer_len as usize];
+    reader
+        .sync_chunk_reader(footer_start, footer_len as usize)?
+        .read_exact(&mut footer)?;
+    let footer = ipc::root_as_footer(&footer).map_err(|e| {
+        DataFusionError::Execution(format!(
+            "Unable to read the footer of the Arrow IPC file: {:?}",
+            e
+        ))
+    })?;
+    let schema = footer.schema().ok_or_else(|| {
+        DataFusionError::Execution(
+            "The footer of the Arrow IPC file has no schema".to_string(),
+        )
+    })?;
+    Ok(ipc::convert::fb_to_schema(schema))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field};
+    use arrow::record_batch::RecordBatch;
+    use std::path::Path;
+
+    use super::*;
+    use crate::{
+        datasource::object_store::local::{
+            local_object_reader, local_object_reader_stream, local_unpartitioned_file,
+            LocalFileSystem,
+        },
+        physical_plan::collect,
+    };
+
+    #[tokio::test]
+    async fn read_projection_and_limit() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let filename = write_test_file(tmp_dir.path())?;
+
+        let exec = get_exec(&filename, &Some(vec![1]), None).await?;
+        let batches = collect(exec).await?;
+        assert_eq!(3, batches.len());
+        assert_eq!(1, batches[0].num_columns());
+        assert_eq!("s", batches[0].schema().field(0).name());
+        let values = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!("a", values.value(0));
+
+        // the reading stops at the first record batch holding the limit
+        let exec = get_exec(&filename, &None, Some(3)).await?;
+        let batches = collect(exec).await?;
+        assert_eq!(2, batches.len());
+        assert_eq!(3, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn infer_schema_from_footer() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let filename = write_test_file(tmp_dir.path())?;
+
+        let schema = ArrowFormat {}
+            .infer_schema(local_object_reader_stream(vec![filename]))
+            .await?;
+        assert_eq!(test_schema(), schema);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn infer_schema_invalid_file() -> Result<()> {
+        let filename = "tests/jsons/2.json".to_owned();
+        let err = ArrowFormat {}
+            .infer_schema(local_object_reader_stream(vec![filename]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Invalid Arrow IPC file"),
+            "{}",
+            err
+        );
+
+        Ok(())
+    }
+
+    fn test_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("i", DataType::Int32, false),
+            Field::new("s", DataType::Utf8, true),
+        ]))
+    }
+
+    /// Write a file of three record batches of two rows each
+    fn write_test_file(dir: &Path) -> Result<String> {
+        let filename = dir.join("test.arrow").to_str().unwrap().to_owned();
+        let schema = test_schema();
+        let mut writer = IpcFileWriter::try_new(File::create(&filename)?, &schema)?;
+        for i in 0..3 {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![2 * i, 2 * i + 1])),
+                    Arc::new(StringArray::from(vec![Some("a"), None])),
+                ],
+            )?;
+            writer.write(&batch)?;
+        }
+        writer.finish()?;
+        Ok(filename)
+    }
+
+    async fn get_exec(
+        filename: &str,
+        projection: &Option<Vec<usize>>,
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let format = ArrowFormat {};
+        let file_schema = format
+            .infer_schema(local_object_reader_stream(vec![filename.to_owned()]))
+            .await?;
+        let statistics = format
+            .infer_stats(
+                local_object_reader(filename.to_owned()),
+                Arc::clone(&file_schema),
+            )
+            .await?;
+        let file_groups = vec![vec![local_unpartitioned_file(filename.to_owned())]];
+        format
+            .create_physical_plan(
+                PhysicalPlanConfig {
+                    object_store: Arc::new(LocalFileSystem {}),
+                    file_schema,
+                    file_groups,
+                    statistics,
+                    projection: projection.clone(),
+                    batch_size: 1024,
+                    limit,
+                    table_partition_cols: vec![],
+                    metadata_cache: None,
+                },
+                &[],
+            )
+            .await
+    }
+}
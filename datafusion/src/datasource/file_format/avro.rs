@@ -55,7 +55,11 @@ impl FileFormat for AvroFormat {
         Ok(Arc::new(merged_schema))
     }
 
-    async fn infer_stats(&self, _reader: Arc<dyn ObjectReader>) -> Result<Statistics> {
+    async fn infer_stats(
+        &self,
+        _reader: Arc<dyn ObjectReader>,
+        _table_schema: SchemaRef,
+    ) -> Result<Statistics> {
         Ok(Statistics::default())
     }
 
@@ -67,6 +71,10 @@ impl FileFormat for AvroFormat {
         let exec = AvroExec::new(conf);
         Ok(Arc::new(exec))
     }
+
+    fn file_extension(&self) -> &str {
+        ".avro"
+    }
 }
 
 #[cfg(test)]
@@ -345,7 +353,10 @@ mod tests {
             .await
             .expect("Schema inference");
         let statistics = format
-            .infer_stats(local_object_reader(filename.clone()))
+            .infer_stats(
+                local_object_reader(filename.clone()),
+                Arc::clone(&file_schema),
+            )
             .await
             .expect("Stats inference");
         let file_groups = vec![vec![local_unpartitioned_file(filename.to_owned())]];
@@ -360,6 +371,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    metadata_cache: None,
                 },
                 &[],
             )
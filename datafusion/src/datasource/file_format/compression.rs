@@ -0,0 +1,203 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Compression of the files read by the row based formats
+
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::error::{DataFusionError, Result};
+
+#[cfg(feature = "compression")]
+use bzip2::read::BzDecoder;
+#[cfg(feature = "compression")]
+use flate2::read::MultiGzDecoder;
+#[cfg(feature = "compression")]
+use xz2::read::XzDecoder;
+
+/// The compression of a whole file, decompressed while the file is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCompressionType {
+    /// Uncompressed file
+    Uncompressed,
+    /// Gzip compressed file, `.gz`
+    Gzip,
+    /// Bzip2 compressed file, `.bz2`
+    Bzip2,
+    /// Xz compressed file, `.xz`
+    Xz,
+    /// Zstandard compressed file, `.zst`
+    Zstd,
+}
+
+impl Default for FileCompressionType {
+    fn default() -> Self {
+        Self::Uncompressed
+    }
+}
+
+impl FileCompressionType {
+    /// The extension of the files compressed with this compression type,
+    /// including the leading dot
+    pub fn get_ext(&self) -> &'static str {
+        match self {
+            Self::Uncompressed => "",
+            Self::Gzip => ".gz",
+            Self::Bzip2 => ".bz2",
+            Self::Xz => ".xz",
+            Self::Zstd => ".zst",
+        }
+    }
+
+    /// The compression type of a file named `path`, or of the files with the
+    /// extension `path`, inferred from its last extension
+    pub fn from_extension(path: &str) -> Self {
+        let path = path.to_lowercase();
+        [Self::Gzip, Self::Bzip2, Self::Xz, Self::Zstd]
+            .into_iter()
+            .find(|compression| path.ends_with(compression.get_ext()))
+            .unwrap_or(Self::Uncompressed)
+    }
+
+    /// Wrap `reader` so that it returns the decompressed content of the file
+    #[cfg(feature = "compression")]
+    pub fn convert_read(
+        &self,
+        reader: Box<dyn Read + Send + Sync>,
+    ) -> Result<Box<dyn Read + Send + Sync>> {
+        Ok(match self {
+            Self::Uncompressed => reader,
+            Self::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Self::Bzip2 => Box::new(BzDecoder::new(reader)),
+            Self::Xz => Box::new(XzDecoder::new(reader)),
+            Self::Zstd => Box::new(zstd::Decoder::new(reader)?),
+        })
+    }
+
+    /// Wrap `reader` so that it returns the decompressed content of the file
+    #[cfg(not(feature = "compression"))]
+    pub fn convert_read(
+        &self,
+        reader: Box<dyn Read + Send + Sync>,
+    ) -> Result<Box<dyn Read + Send + Sync>> {
+        match self {
+            Self::Uncompressed => Ok(reader),
+            _ => Err(DataFusionError::NotImplemented(format!(
+                "Reading {:?} compressed files requires the `compression` feature",
+                self
+            ))),
+        }
+    }
+}
+
+impl FromStr for FileCompressionType {
+    type Err = DataFusionError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_uppercase().as_str() {
+            "UNCOMPRESSED" | "NONE" => Ok(Self::Uncompressed),
+            "GZIP" | "GZ" => Ok(Self::Gzip),
+            "BZIP2" | "BZ2" => Ok(Self::Bzip2),
+            "XZ" => Ok(Self::Xz),
+            "ZSTD" | "ZST" => Ok(Self::Zstd),
+            other => Err(DataFusionError::Plan(format!(
+                "expect one of GZIP, BZIP2, XZ, ZSTD or UNCOMPRESSED, found: {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_from_extension() {
+        let cases = vec![
+            ("logs.csv", FileCompressionType::Uncompressed),
+            ("", FileCompressionType::Uncompressed),
+            ("logs.csv.gz", FileCompressionType::Gzip),
+            (".json.BZ2", FileCompressionType::Bzip2),
+            ("dir/logs.csv.xz", FileCompressionType::Xz),
+            (".zst", FileCompressionType::Zstd),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                expected,
+                FileCompressionType::from_extension(path),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn compression_from_str() {
+        assert_eq!(
+            FileCompressionType::Gzip,
+            "gzip".parse::<FileCompressionType>().unwrap()
+        );
+        assert_eq!(
+            FileCompressionType::Zstd,
+            "ZSTD".parse::<FileCompressionType>().unwrap()
+        );
+        assert!("lz4".parse::<FileCompressionType>().is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn decompress() -> Result<()> {
+        use std::io::Write;
+
+        let content = b"a,b\n1,2\n".to_vec();
+
+        let mut encoder =
+            flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&content)?;
+        let gzip = encoder.finish()?;
+        let bzip2 = {
+            let mut encoder =
+                bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+            encoder.write_all(&content)?;
+            encoder.finish()?
+        };
+        let xz = {
+            let mut encoder = xz2::write::XzEncoder::new(vec![], 6);
+            encoder.write_all(&content)?;
+            encoder.finish()?
+        };
+        let zstd = zstd::encode_all(content.as_slice(), 0)?;
+
+        let cases = vec![
+            (FileCompressionType::Uncompressed, content.clone()),
+            (FileCompressionType::Gzip, gzip),
+            (FileCompressionType::Bzip2, bzip2),
+            (FileCompressionType::Xz, xz),
+            (FileCompressionType::Zstd, zstd),
+        ];
+        for (compression, compressed) in cases {
+            let mut reader =
+                compression.convert_read(Box::new(std::io::Cursor::new(compressed)))?;
+            let mut decompressed = vec![];
+            reader.read_to_end(&mut decompressed)?;
+            assert_eq!(content, decompressed, "{:?}", compression);
+        }
+
+        Ok(())
+    }
+}
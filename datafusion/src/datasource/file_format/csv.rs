@@ -18,6 +18,7 @@
 //! CSV format abstractions
 
 use std::any::Any;
+use std::fs::File;
 use std::sync::Arc;
 
 use arrow::datatypes::Schema;
@@ -25,11 +26,12 @@ use arrow::{self, datatypes::SchemaRef};
 use async_trait::async_trait;
 use futures::StreamExt;
 
+use super::compression::FileCompressionType;
 use super::FileFormat;
 use crate::datasource::object_store::{ObjectReader, ObjectReaderStream};
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
-use crate::physical_plan::file_format::{CsvExec, PhysicalPlanConfig};
+use crate::physical_plan::file_format::{CsvExec, FileWriter, PhysicalPlanConfig};
 use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::Statistics;
 
@@ -39,6 +41,7 @@ pub struct CsvFormat {
     has_header: bool,
     delimiter: u8,
     schema_infer_max_rec: Option<usize>,
+    file_compression_type: FileCompressionType,
 }
 
 impl Default for CsvFormat {
@@ -47,6 +50,7 @@ impl Default for CsvFormat {
             schema_infer_max_rec: None,
             has_header: true,
             delimiter: b',',
+            file_compression_type: FileCompressionType::Uncompressed,
         }
     }
 }
@@ -82,6 +86,32 @@ impl CsvFormat {
     pub fn delimiter(&self) -> u8 {
         self.delimiter
     }
+
+    /// Set the compression of the files, which are decompressed while read.
+    /// - default to `FileCompressionType::Uncompressed`
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = file_compression_type;
+        self
+    }
+
+    /// The compression of the files.
+    pub fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
+
+    /// Files can only be written uncompressed
+    fn check_writable(&self) -> Result<()> {
+        match self.file_compression_type {
+            FileCompressionType::Uncompressed => Ok(()),
+            compression => Err(DataFusionError::NotImplemented(format!(
+                "Writing {:?} compressed CSV files is not supported",
+                compression
+            ))),
+        }
+    }
 }
 
 #[async_trait]
@@ -96,7 +126,9 @@ impl FileFormat for CsvFormat {
         let mut records_to_read = self.schema_infer_max_rec.unwrap_or(std::usize::MAX);
 
         while let Some(obj_reader) = readers.next().await {
-            let mut reader = obj_reader?.sync_reader()?;
+            let mut reader = self
+                .file_compression_type
+                .convert_read(obj_reader?.sync_reader()?)?;
             let (schema, records_read) = arrow::csv::reader::infer_reader_schema(
                 &mut reader,
                 self.delimiter,
@@ -117,7 +149,11 @@ impl FileFormat for CsvFormat {
         Ok(Arc::new(merged_schema))
     }
 
-    async fn infer_stats(&self, _reader: Arc<dyn ObjectReader>) -> Result<Statistics> {
+    async fn infer_stats(
+        &self,
+        _reader: Arc<dyn ObjectReader>,
+        _table_schema: SchemaRef,
+    ) -> Result<Statistics> {
         Ok(Statistics::default())
     }
 
@@ -126,9 +162,40 @@ impl FileFormat for CsvFormat {
         conf: PhysicalPlanConfig,
         _filters: &[Expr],
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let exec = CsvExec::new(conf, self.has_header, self.delimiter);
+        let exec = CsvExec::new(conf, self.has_header, self.delimiter)
+            .with_file_compression_type(self.file_compression_type);
         Ok(Arc::new(exec))
     }
+
+    fn file_extension(&self) -> &str {
+        ".csv"
+    }
+
+    fn with_inferred_compression(
+        &self,
+        file_compression_type: FileCompressionType,
+    ) -> Option<Arc<dyn FileFormat>> {
+        match self.file_compression_type {
+            FileCompressionType::Uncompressed => Some(Arc::new(Self {
+                file_compression_type,
+                ..*self
+            })),
+            _ => None,
+        }
+    }
+
+    fn create_writer(
+        &self,
+        file: File,
+        _schema: SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        self.check_writable()?;
+        let writer = arrow::csv::WriterBuilder::new()
+            .has_headers(self.has_header)
+            .with_delimiter(self.delimiter)
+            .build(file);
+        Ok(Box::new(writer))
+    }
 }
 
 #[cfg(test)]
@@ -257,7 +324,10 @@ mod tests {
             .await
             .expect("Schema inference");
         let statistics = format
-            .infer_stats(local_object_reader(filename.clone()))
+            .infer_stats(
+                local_object_reader(filename.clone()),
+                Arc::clone(&file_schema),
+            )
             .await
             .expect("Stats inference");
         let file_groups = vec![vec![local_unpartitioned_file(filename.to_owned())]];
@@ -272,6 +342,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    metadata_cache: None,
                 },
                 &[],
             )
@@ -18,6 +18,7 @@
 //! Line delimited JSON format abstractions
 
 use std::any::Any;
+use std::fs::File;
 use std::io::BufReader;
 use std::sync::Arc;
 
@@ -28,12 +29,13 @@ use arrow::json::reader::ValueIter;
 use async_trait::async_trait;
 use futures::StreamExt;
 
+use super::compression::FileCompressionType;
 use super::FileFormat;
 use super::PhysicalPlanConfig;
 use crate::datasource::object_store::{ObjectReader, ObjectReaderStream};
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
-use crate::physical_plan::file_format::NdJsonExec;
+use crate::physical_plan::file_format::{FileWriter, NdJsonExec};
 use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::Statistics;
 
@@ -41,6 +43,7 @@ use crate::physical_plan::Statistics;
 #[derive(Debug, Default)]
 pub struct JsonFormat {
     schema_infer_max_rec: Option<usize>,
+    file_compression_type: FileCompressionType,
 }
 
 impl JsonFormat {
@@ -50,6 +53,32 @@ impl JsonFormat {
         self.schema_infer_max_rec = max_rec;
         self
     }
+
+    /// Set the compression of the files, which are decompressed while read.
+    /// - defaults to `FileCompressionType::Uncompressed`
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = file_compression_type;
+        self
+    }
+
+    /// The compression of the files.
+    pub fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
+
+    /// Files can only be written uncompressed
+    fn check_writable(&self) -> Result<()> {
+        match self.file_compression_type {
+            FileCompressionType::Uncompressed => Ok(()),
+            compression => Err(DataFusionError::NotImplemented(format!(
+                "Writing {:?} compressed JSON files is not supported",
+                compression
+            ))),
+        }
+    }
 }
 
 #[async_trait]
@@ -62,7 +91,10 @@ impl FileFormat for JsonFormat {
         let mut schemas = Vec::new();
         let mut records_to_read = self.schema_infer_max_rec.unwrap_or(usize::MAX);
         while let Some(obj_reader) = readers.next().await {
-            let mut reader = BufReader::new(obj_reader?.sync_reader()?);
+            let mut reader = BufReader::new(
+                self.file_compression_type
+                    .convert_read(obj_reader?.sync_reader()?)?,
+            );
             let iter = ValueIter::new(&mut reader, None);
             let schema = infer_json_schema_from_iterator(iter.take_while(|_| {
                 let should_take = records_to_read > 0;
@@ -79,7 +111,11 @@ impl FileFormat for JsonFormat {
         Ok(Arc::new(schema))
     }
 
-    async fn infer_stats(&self, _reader: Arc<dyn ObjectReader>) -> Result<Statistics> {
+    async fn infer_stats(
+        &self,
+        _reader: Arc<dyn ObjectReader>,
+        _table_schema: SchemaRef,
+    ) -> Result<Statistics> {
         Ok(Statistics::default())
     }
 
@@ -88,9 +124,36 @@ impl FileFormat for JsonFormat {
         conf: PhysicalPlanConfig,
         _filters: &[Expr],
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let exec = NdJsonExec::new(conf);
+        let exec =
+            NdJsonExec::new(conf).with_file_compression_type(self.file_compression_type);
         Ok(Arc::new(exec))
     }
+
+    fn file_extension(&self) -> &str {
+        ".json"
+    }
+
+    fn with_inferred_compression(
+        &self,
+        file_compression_type: FileCompressionType,
+    ) -> Option<Arc<dyn FileFormat>> {
+        match self.file_compression_type {
+            FileCompressionType::Uncompressed => Some(Arc::new(Self {
+                file_compression_type,
+                ..*self
+            })),
+            _ => None,
+        }
+    }
+
+    fn create_writer(
+        &self,
+        file: File,
+        _schema: SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        self.check_writable()?;
+        Ok(Box::new(arrow::json::LineDelimitedWriter::new(file)))
+    }
 }
 
 #[cfg(test)]
@@ -202,7 +265,10 @@ mod tests {
             .await
             .expect("Schema inference");
         let statistics = format
-            .infer_stats(local_object_reader(filename.to_owned()))
+            .infer_stats(
+                local_object_reader(filename.to_owned()),
+                Arc::clone(&file_schema),
+            )
             .await
             .expect("Stats inference");
         let file_groups = vec![vec![local_unpartitioned_file(filename.to_owned())]];
@@ -217,6 +283,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    metadata_cache: None,
                 },
                 &[],
             )
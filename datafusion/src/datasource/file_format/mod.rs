@@ -17,19 +17,23 @@
 
 //! Module containing helper methods for the various file formats
 
+pub mod arrow;
 pub mod avro;
+pub mod compression;
 pub mod csv;
 pub mod json;
 pub mod parquet;
 
 use std::any::Any;
 use std::fmt;
+use std::fs::File;
 use std::sync::Arc;
 
+use self::compression::FileCompressionType;
 use crate::arrow::datatypes::SchemaRef;
-use crate::error::Result;
+use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
-use crate::physical_plan::file_format::PhysicalPlanConfig;
+use crate::physical_plan::file_format::{FileWriter, PhysicalPlanConfig};
 use crate::physical_plan::{ExecutionPlan, Statistics};
 
 use async_trait::async_trait;
@@ -53,7 +57,15 @@ pub trait FileFormat: Send + Sync + fmt::Debug {
 
     /// Infer the statistics for the provided object. The cost and accuracy of the
     /// estimated statistics might vary greatly between file formats.
-    async fn infer_stats(&self, reader: Arc<dyn ObjectReader>) -> Result<Statistics>;
+    ///
+    /// The column statistics are those of the columns of `table_schema`, matched
+    /// by name with the columns of the object, which might be missing some of
+    /// them or have them in a different order.
+    async fn infer_stats(
+        &self,
+        reader: Arc<dyn ObjectReader>,
+        table_schema: SchemaRef,
+    ) -> Result<Statistics>;
 
     /// Take a list of files and convert it to the appropriate executor
     /// according to this file format.
@@ -62,4 +74,32 @@ pub trait FileFormat: Send + Sync + fmt::Debug {
         conf: PhysicalPlanConfig,
         filters: &[Expr],
     ) -> Result<Arc<dyn ExecutionPlan>>;
+
+    /// The extension of the files written in this format, including the
+    /// leading dot
+    fn file_extension(&self) -> &str {
+        ""
+    }
+
+    /// This format reading the files compressed with `file_compression_type`,
+    /// if it reads compressed files and its compression was not set, see
+    /// [`ListingOptions::infer_file_compression`](crate::datasource::listing::ListingOptions::infer_file_compression)
+    fn with_inferred_compression(
+        &self,
+        _file_compression_type: FileCompressionType,
+    ) -> Option<Arc<dyn FileFormat>> {
+        None
+    }
+
+    /// Create a writer appending record batches with the given schema to the
+    /// newly created `file`
+    fn create_writer(
+        &self,
+        _file: File,
+        _schema: SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        Err(DataFusionError::NotImplemented(
+            "Writing files is not supported by this format".to_string(),
+        ))
+    }
 }
@@ -18,6 +18,7 @@
 //! Parquet format abstractions
 
 use std::any::Any;
+use std::fs::File;
 use std::io::Read;
 use std::sync::Arc;
 
@@ -26,9 +27,11 @@ use arrow::datatypes::SchemaRef;
 use async_trait::async_trait;
 use futures::stream::StreamExt;
 use parquet::arrow::ArrowReader;
+use parquet::arrow::ArrowWriter;
 use parquet::arrow::ParquetFileArrowReader;
 use parquet::errors::ParquetError;
 use parquet::errors::Result as ParquetResult;
+use parquet::file::properties::WriterProperties;
 use parquet::file::reader::ChunkReader;
 use parquet::file::reader::Length;
 use parquet::file::serialized_reader::SerializedFileReader;
@@ -44,9 +47,9 @@ use crate::error::Result;
 use crate::logical_plan::combine_filters;
 use crate::logical_plan::Expr;
 use crate::physical_plan::expressions::{MaxAccumulator, MinAccumulator};
-use crate::physical_plan::file_format::ParquetExec;
+use crate::physical_plan::file_format::{FileWriter, ParquetExec};
 use crate::physical_plan::ExecutionPlan;
-use crate::physical_plan::{Accumulator, Statistics};
+use crate::physical_plan::{Accumulator, ColumnStatistics, Statistics};
 use crate::scalar::ScalarValue;
 
 /// The default file exetension of parquet files
@@ -56,18 +59,23 @@ pub const DEFAULT_PARQUET_EXTENSION: &str = ".parquet";
 #[derive(Debug)]
 pub struct ParquetFormat {
     enable_pruning: bool,
+    enable_row_filter: bool,
+    writer_properties: Option<WriterProperties>,
 }
 
 impl Default for ParquetFormat {
     fn default() -> Self {
         Self {
             enable_pruning: true,
+            enable_row_filter: true,
+            writer_properties: None,
         }
     }
 }
 
 impl ParquetFormat {
-    /// Activate statistics based row group level pruning
+    /// Activate pruning of row groups using their statistics and the bloom
+    /// filters of their columns, and of pages using the page index
     /// - defaults to true
     pub fn with_enable_pruning(mut self, enable: bool) -> Self {
         self.enable_pruning = enable;
@@ -77,6 +85,29 @@ impl ParquetFormat {
     pub fn enable_pruning(&self) -> bool {
         self.enable_pruning
     }
+    /// Activate the evaluation of the filters on the rows of the row groups
+    /// read, before decoding the columns they do not reference. Only applies
+    /// if pruning is enabled.
+    /// - defaults to true
+    pub fn with_enable_row_filter(mut self, enable: bool) -> Self {
+        self.enable_row_filter = enable;
+        self
+    }
+    /// Return true if the row filter is enabled
+    pub fn enable_row_filter(&self) -> bool {
+        self.enable_row_filter
+    }
+    /// Set the properties, such as the compression or the size of the row
+    /// groups, of the files written in this format
+    /// - defaults to the default `WriterProperties`
+    pub fn with_writer_properties(mut self, properties: WriterProperties) -> Self {
+        self.writer_properties = Some(properties);
+        self
+    }
+    /// Return the properties of the files written, if set
+    pub fn writer_properties(&self) -> Option<&WriterProperties> {
+        self.writer_properties.as_ref()
+    }
 }
 
 #[async_trait]
@@ -97,8 +128,12 @@ impl FileFormat for ParquetFormat {
         Ok(Arc::new(schema))
     }
 
-    async fn infer_stats(&self, reader: Arc<dyn ObjectReader>) -> Result<Statistics> {
-        let stats = fetch_statistics(reader)?;
+    async fn infer_stats(
+        &self,
+        reader: Arc<dyn ObjectReader>,
+        table_schema: SchemaRef,
+    ) -> Result<Statistics> {
+        let stats = fetch_statistics(reader, &table_schema)?;
         Ok(stats)
     }
 
@@ -116,7 +151,25 @@ impl FileFormat for ParquetFormat {
             None
         };
 
-        Ok(Arc::new(ParquetExec::new(conf, predicate)))
+        Ok(Arc::new(
+            ParquetExec::new(conf, predicate).with_row_filter(self.enable_row_filter),
+        ))
+    }
+
+    fn file_extension(&self) -> &str {
+        DEFAULT_PARQUET_EXTENSION
+    }
+
+    fn create_writer(
+        &self,
+        file: File,
+        schema: SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        Ok(Box::new(ArrowWriter::try_new(
+            file,
+            schema,
+            self.writer_properties.clone(),
+        )?))
     }
 }
 
@@ -252,8 +305,12 @@ fn fetch_schema(object_reader: Arc<dyn ObjectReader>) -> Result<Schema> {
     Ok(schema)
 }
 
-/// Read and parse the statistics of the Parquet file at location `path`
-fn fetch_statistics(object_reader: Arc<dyn ObjectReader>) -> Result<Statistics> {
+/// Read and parse the statistics of the Parquet file at location `path`, for
+/// the columns of `table_schema`
+fn fetch_statistics(
+    object_reader: Arc<dyn ObjectReader>,
+    table_schema: &Schema,
+) -> Result<Statistics> {
     let obj_reader = ChunkObjectReader(object_reader);
     let file_reader = Arc::new(SerializedFileReader::new(obj_reader)?);
     let mut arrow_reader = ParquetFileArrowReader::new(file_reader);
@@ -291,11 +348,13 @@ fn fetch_statistics(object_reader: Arc<dyn ObjectReader>) -> Result<Statistics>
     }
 
     let column_stats = if has_statistics {
-        Some(get_col_stats(
+        let column_stats =
+            get_col_stats(&schema, null_counts, &mut max_values, &mut min_values);
+        Some(map_col_stats(
             &schema,
-            null_counts,
-            &mut max_values,
-            &mut min_values,
+            column_stats,
+            table_schema,
+            num_rows as usize,
         ))
     } else {
         None
@@ -311,6 +370,50 @@ fn fetch_statistics(object_reader: Arc<dyn ObjectReader>) -> Result<Statistics>
     Ok(statistics)
 }
 
+/// Map the statistics of the columns of `file_schema` to the columns of
+/// `table_schema` with the same names, with the min and max values cast to
+/// the table types. The columns missing from the file only have nulls.
+fn map_col_stats(
+    file_schema: &Schema,
+    column_stats: Vec<ColumnStatistics>,
+    table_schema: &Schema,
+    num_rows: usize,
+) -> Vec<ColumnStatistics> {
+    table_schema
+        .fields()
+        .iter()
+        .map(|field| match file_schema.index_of(field.name()) {
+            Ok(i) => {
+                let stats = &column_stats[i];
+                let cast_value = |value: &Option<ScalarValue>| {
+                    value
+                        .as_ref()
+                        .and_then(|value| cast_scalar(value, field.data_type()))
+                };
+                ColumnStatistics {
+                    null_count: stats.null_count,
+                    max_value: cast_value(&stats.max_value),
+                    min_value: cast_value(&stats.min_value),
+                    distinct_count: stats.distinct_count,
+                }
+            }
+            Err(_) => ColumnStatistics {
+                null_count: Some(num_rows),
+                ..Default::default()
+            },
+        })
+        .collect()
+}
+
+/// `value` cast to `data_type`, `None` if it can't be cast
+fn cast_scalar(value: &ScalarValue, data_type: &DataType) -> Option<ScalarValue> {
+    if &value.get_datatype() == data_type {
+        return Some(value.clone());
+    }
+    let array = arrow::compute::cast(&value.to_array(), data_type).ok()?;
+    ScalarValue::try_from_array(&array, 0).ok()
+}
+
 /// A wrapper around the object reader to make it implement `ChunkReader`
 pub struct ChunkObjectReader(pub Arc<dyn ObjectReader>);
 
@@ -347,6 +450,58 @@ mod tests {
     };
     use futures::StreamExt;
 
+    #[test]
+    fn col_stats_mapped_by_name() {
+        let file_schema = Schema::new(vec![
+            Field::new("c2", DataType::Int32, true),
+            Field::new("c1", DataType::Utf8, true),
+        ]);
+        let table_schema = Schema::new(vec![
+            Field::new("c1", DataType::Utf8, true),
+            Field::new("c2", DataType::Int64, true),
+            Field::new("c3", DataType::Utf8, true),
+        ]);
+        let column_stats = vec![
+            ColumnStatistics {
+                null_count: Some(1),
+                max_value: Some(ScalarValue::Int32(Some(5))),
+                min_value: Some(ScalarValue::Int32(Some(2))),
+                distinct_count: None,
+            },
+            ColumnStatistics {
+                null_count: Some(0),
+                max_value: Some(ScalarValue::Utf8(Some("b".to_owned()))),
+                min_value: Some(ScalarValue::Utf8(Some("a".to_owned()))),
+                distinct_count: None,
+            },
+        ];
+
+        let mapped = map_col_stats(&file_schema, column_stats, &table_schema, 3);
+        assert_eq!(
+            mapped,
+            vec![
+                ColumnStatistics {
+                    null_count: Some(0),
+                    max_value: Some(ScalarValue::Utf8(Some("b".to_owned()))),
+                    min_value: Some(ScalarValue::Utf8(Some("a".to_owned()))),
+                    distinct_count: None,
+                },
+                ColumnStatistics {
+                    null_count: Some(1),
+                    max_value: Some(ScalarValue::Int64(Some(5))),
+                    min_value: Some(ScalarValue::Int64(Some(2))),
+                    distinct_count: None,
+                },
+                ColumnStatistics {
+                    null_count: Some(3),
+                    max_value: None,
+                    min_value: None,
+                    distinct_count: None,
+                },
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn read_small_batches() -> Result<()> {
         let projection = None;
@@ -600,7 +755,10 @@ mod tests {
             .await
             .expect("Schema inference");
         let statistics = format
-            .infer_stats(local_object_reader(filename.clone()))
+            .infer_stats(
+                local_object_reader(filename.clone()),
+                Arc::clone(&file_schema),
+            )
             .await
             .expect("Stats inference");
         let file_groups = vec![vec![local_unpartitioned_file(filename.clone())]];
@@ -615,6 +773,7 @@ mod tests {
                     batch_size,
                     limit,
                     table_partition_cols: vec![],
+                    metadata_cache: None,
                 },
                 &[],
             )
@@ -0,0 +1,320 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Cache of the listings and of the file metadata of listing tables
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, RwLock};
+
+use arrow::datatypes::SchemaRef;
+use chrono::{DateTime, Utc};
+use parquet::file::metadata::ParquetMetaData;
+
+use crate::datasource::object_store::FileMeta;
+use crate::physical_plan::Statistics;
+
+/// Identifies the version of a file the cached metadata was read from.
+///
+/// A file that is rewritten gets a new size or modification time, so the
+/// metadata cached for its previous version is not used anymore.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FileCacheKey {
+    /// The path of the file in its object store
+    pub path: String,
+    /// The size of the file
+    pub size: u64,
+    /// The last modification time of the file, if known
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+impl From<&FileMeta> for FileCacheKey {
+    fn from(file_meta: &FileMeta) -> Self {
+        Self {
+            path: file_meta.path().to_owned(),
+            size: file_meta.size(),
+            last_modified: file_meta.last_modified,
+        }
+    }
+}
+
+/// Identifies a schema inferred from a set of files
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchemaCacheKey {
+    /// The files the schema was inferred from
+    pub files: Vec<FileCacheKey>,
+    /// The description of the file format and of its options, as the
+    /// schema inferred from the same files depends on them
+    pub format: String,
+}
+
+/// Identifies the statistics read from a file for the columns of a table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatisticsCacheKey {
+    /// The file the statistics were read from
+    pub file: FileCacheKey,
+    /// The description of the schema of the table, as the column statistics
+    /// are those of its columns
+    pub table_schema: String,
+}
+
+/// A cache of the files listed by the listing tables and of the metadata read
+/// from these files, shared by the tables of an
+/// [`ExecutionContext`](crate::execution::context::ExecutionContext).
+///
+/// The listings are cached by table path and are only invalidated explicitly,
+/// e.g. by `REFRESH TABLE`, as the cache can't know when files are added to
+/// or removed from a table. The schemas and statistics are cached by the
+/// [`FileCacheKey`]s of the files they were read from (for Parquet files, from
+/// their footers), the statistics also by the schema of the table they were
+/// read for. The footers of the Parquet files are cached as well, so that
+/// the scans of a file do not read and decode them again.
+pub trait MetadataCache: Debug + Send + Sync {
+    /// The files listed under `table_path`
+    fn get_listing(&self, table_path: &str) -> Option<Arc<Vec<FileMeta>>>;
+
+    /// Cache the files listed under `table_path`
+    fn put_listing(&self, table_path: &str, files: Arc<Vec<FileMeta>>);
+
+    /// The schema inferred from the files of `key`
+    fn get_schema(&self, key: &SchemaCacheKey) -> Option<SchemaRef>;
+
+    /// Cache the schema inferred from the files of `key`
+    fn put_schema(&self, key: SchemaCacheKey, schema: SchemaRef);
+
+    /// The statistics of a file for the columns of a table
+    fn get_statistics(&self, key: &StatisticsCacheKey) -> Option<Statistics>;
+
+    /// Cache the statistics of a file for the columns of a table
+    fn put_statistics(&self, key: StatisticsCacheKey, statistics: Statistics);
+
+    /// The metadata decoded from the footer of the Parquet file of `key`
+    fn get_parquet_metadata(&self, key: &FileCacheKey) -> Option<Arc<ParquetMetaData>>;
+
+    /// Cache the metadata decoded from the footer of the Parquet file of `key`
+    fn put_parquet_metadata(&self, key: FileCacheKey, metadata: Arc<ParquetMetaData>);
+
+    /// Remove the listing of `table_path` and the metadata of the files
+    /// under it, i.e. of `table_path` itself and of the paths it is a
+    /// directory of
+    fn invalidate(&self, table_path: &str);
+
+    /// Remove all the cached listings and metadata
+    fn clear(&self);
+}
+
+/// The default [`MetadataCache`], which keeps all the entries in memory
+#[derive(Debug, Default)]
+pub struct DefaultMetadataCache {
+    listings: RwLock<HashMap<String, Arc<Vec<FileMeta>>>>,
+    schemas: RwLock<HashMap<SchemaCacheKey, SchemaRef>>,
+    statistics: RwLock<HashMap<StatisticsCacheKey, Statistics>>,
+    parquet_metadata: RwLock<HashMap<FileCacheKey, Arc<ParquetMetaData>>>,
+}
+
+impl DefaultMetadataCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MetadataCache for DefaultMetadataCache {
+    fn get_listing(&self, table_path: &str) -> Option<Arc<Vec<FileMeta>>> {
+        self.listings.read().unwrap().get(table_path).cloned()
+    }
+
+    fn put_listing(&self, table_path: &str, files: Arc<Vec<FileMeta>>) {
+        self.listings
+            .write()
+            .unwrap()
+            .insert(table_path.to_owned(), files);
+    }
+
+    fn get_schema(&self, key: &SchemaCacheKey) -> Option<SchemaRef> {
+        self.schemas.read().unwrap().get(key).cloned()
+    }
+
+    fn put_schema(&self, key: SchemaCacheKey, schema: SchemaRef) {
+        self.schemas.write().unwrap().insert(key, schema);
+    }
+
+    fn get_statistics(&self, key: &StatisticsCacheKey) -> Option<Statistics> {
+        self.statistics.read().unwrap().get(key).cloned()
+    }
+
+    fn put_statistics(&self, key: StatisticsCacheKey, statistics: Statistics) {
+        self.statistics.write().unwrap().insert(key, statistics);
+    }
+
+    fn get_parquet_metadata(&self, key: &FileCacheKey) -> Option<Arc<ParquetMetaData>> {
+        self.parquet_metadata.read().unwrap().get(key).cloned()
+    }
+
+    fn put_parquet_metadata(&self, key: FileCacheKey, metadata: Arc<ParquetMetaData>) {
+        self.parquet_metadata.write().unwrap().insert(key, metadata);
+    }
+
+    fn invalidate(&self, table_path: &str) {
+        self.listings.write().unwrap().remove(table_path);
+        self.schemas
+            .write()
+            .unwrap()
+            .retain(|key, _| !key.files.iter().any(|f| is_in_table(&f.path, table_path)));
+        self.statistics
+            .write()
+            .unwrap()
+            .retain(|key, _| !is_in_table(&key.file.path, table_path));
+        self.parquet_metadata
+            .write()
+            .unwrap()
+            .retain(|key, _| !is_in_table(&key.path, table_path));
+    }
+
+    fn clear(&self) {
+        self.listings.write().unwrap().clear();
+        self.schemas.write().unwrap().clear();
+        self.statistics.write().unwrap().clear();
+        self.parquet_metadata.write().unwrap().clear();
+    }
+}
+
+/// Whether `path` is the file `table_path` or is under the directory
+/// `table_path`, e.g. `table_1/file` is not under `table_`
+fn is_in_table(path: &str, table_path: &str) -> bool {
+    match path.strip_prefix(table_path) {
+        Some(rest) => {
+            rest.is_empty()
+                || table_path.ends_with(is_separator)
+                || rest.starts_with(is_separator)
+        }
+        None => false,
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == std::path::MAIN_SEPARATOR
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::datasource::object_store::SizedFile;
+
+    #[test]
+    fn invalidate_table_path() {
+        let cache = DefaultMetadataCache::new();
+        let file_a = file_meta("table_a/file.parquet", 10);
+        let file_b = file_meta("table_b/file.parquet", 20);
+        let schema = Arc::new(Schema::new(vec![Field::new("c", DataType::Int32, true)]));
+        for (table_path, file) in [("table_a", &file_a), ("table_b", &file_b)] {
+            cache.put_listing(table_path, Arc::new(vec![file.clone()]));
+            cache.put_schema(schema_key(file), schema.clone());
+            cache.put_statistics(statistics_key(file), Statistics::default());
+        }
+
+        cache.invalidate("table_a");
+        assert!(cache.get_listing("table_a").is_none());
+        assert!(cache.get_schema(&schema_key(&file_a)).is_none());
+        assert!(cache.get_statistics(&statistics_key(&file_a)).is_none());
+        assert_eq!(
+            Some(Arc::new(vec![file_b.clone()])),
+            cache.get_listing("table_b")
+        );
+        assert_eq!(Some(schema), cache.get_schema(&schema_key(&file_b)));
+        assert!(cache.get_statistics(&statistics_key(&file_b)).is_some());
+
+        cache.clear();
+        assert!(cache.get_listing("table_b").is_none());
+        assert!(cache.get_statistics(&statistics_key(&file_b)).is_none());
+    }
+
+    #[test]
+    fn invalidate_sibling_table_path() {
+        let cache = DefaultMetadataCache::new();
+        let files = [
+            file_meta("data/table/file.parquet", 10),
+            file_meta("data/table_1/file.parquet", 20),
+            file_meta("data/table.parquet", 30),
+        ];
+        for file in &files {
+            cache.put_statistics(statistics_key(file), Statistics::default());
+        }
+
+        // only the files of the directory are removed, not the ones of the
+        // paths starting with the same characters
+        cache.invalidate("data/table");
+        assert!(cache.get_statistics(&statistics_key(&files[0])).is_none());
+        assert!(cache.get_statistics(&statistics_key(&files[1])).is_some());
+        assert!(cache.get_statistics(&statistics_key(&files[2])).is_some());
+
+        // the single file tables and the paths ending with a separator
+        cache.invalidate("data/table.parquet");
+        assert!(cache.get_statistics(&statistics_key(&files[2])).is_none());
+        cache.invalidate("data/");
+        assert!(cache.get_statistics(&statistics_key(&files[1])).is_none());
+    }
+
+    #[test]
+    fn statistics_of_rewritten_file() {
+        let cache = DefaultMetadataCache::new();
+        let statistics = Statistics {
+            num_rows: Some(5),
+            ..Default::default()
+        };
+        let key = statistics_key(&file_meta("file.parquet", 10));
+        cache.put_statistics(key.clone(), statistics);
+
+        // the size of the file changed
+        assert!(cache
+            .get_statistics(&statistics_key(&file_meta("file.parquet", 12)))
+            .is_none());
+        // the statistics were read for another table schema
+        assert!(cache
+            .get_statistics(&StatisticsCacheKey {
+                table_schema: "other".to_owned(),
+                ..key
+            })
+            .is_none());
+    }
+
+    fn file_meta(path: &str, size: u64) -> FileMeta {
+        FileMeta {
+            sized_file: SizedFile {
+                path: path.to_owned(),
+                size,
+            },
+            last_modified: None,
+        }
+    }
+
+    fn statistics_key(file: &FileMeta) -> StatisticsCacheKey {
+        StatisticsCacheKey {
+            file: file.into(),
+            table_schema: "c: Int32".to_owned(),
+        }
+    }
+
+    fn schema_key(file: &FileMeta) -> SchemaCacheKey {
+        SchemaCacheKey {
+            files: vec![file.into()],
+            format: "ParquetFormat".to_owned(),
+        }
+    }
+}
@@ -24,7 +24,7 @@ use arrow::{
         Array, ArrayBuilder, ArrayRef, Date64Array, Date64Builder, StringArray,
         StringBuilder, UInt64Array, UInt64Builder,
     },
-    datatypes::{DataType, Field, Schema},
+    datatypes::{DataType, Field, Schema, SchemaRef},
     record_batch::RecordBatch,
 };
 use chrono::{TimeZone, Utc};
@@ -37,13 +37,17 @@ use log::debug;
 use crate::{
     error::Result,
     execution::context::ExecutionContext,
-    logical_plan::{self, Expr, ExpressionVisitor, Recursion},
-    physical_plan::functions::Volatility,
+    logical_plan::{self, combine_filters, Column, Expr, ExpressionVisitor, Recursion},
+    physical_optimizer::pruning::{PruningPredicate, PruningStatistics},
+    physical_plan::{
+        file_format::HIVE_DEFAULT_PARTITION, functions::Volatility, ColumnStatistics,
+        Statistics,
+    },
     scalar::ScalarValue,
 };
 
 use crate::datasource::{
-    object_store::{FileMeta, ObjectStore, SizedFile},
+    object_store::{FileMeta, FileMetaStream, ObjectStore, SizedFile},
     MemTable, PartitionedFile, PartitionedFileStream,
 };
 
@@ -107,7 +111,11 @@ impl ExpressionVisitor for ApplicabilityVisitor<'_> {
             | Expr::AggregateFunction { .. }
             | Expr::Sort { .. }
             | Expr::WindowFunction { .. }
-            | Expr::Wildcard => {
+            | Expr::Exists { .. }
+            | Expr::InSubquery { .. }
+            | Expr::ScalarSubquery(_)
+            | Expr::Wildcard
+            | Expr::GroupingSet(_) => {
                 *self.is_applicable = false;
                 Recursion::Stop(self)
             }
@@ -147,6 +155,103 @@ pub fn split_files(
         .collect()
 }
 
+/// The min/max column statistics of a list of files, one container per file
+struct FilePruningStatistics<'a> {
+    /// The schema of the files, without the partition columns
+    file_schema: &'a Schema,
+    /// The files with their statistics, whose column statistics are those of
+    /// the columns of `file_schema` as inferred by
+    /// [`FileFormat::infer_stats`](crate::datasource::file_format::FileFormat::infer_stats)
+    files: &'a [(PartitionedFile, Statistics)],
+}
+
+impl<'a> FilePruningStatistics<'a> {
+    /// The statistic `value` of `column` for each file, `None` if the values
+    /// can't be gathered in an array of the column type
+    fn values(
+        &self,
+        column: &Column,
+        value: impl Fn(&ColumnStatistics) -> &Option<ScalarValue>,
+    ) -> Option<ArrayRef> {
+        let (index, field) = self.file_schema.column_with_name(&column.name)?;
+        let null_value = ScalarValue::try_from(field.data_type()).ok()?;
+        let values = self.files.iter().map(|(_, statistics)| {
+            statistics
+                .column_statistics
+                .as_ref()
+                .and_then(|column_statistics| column_statistics.get(index))
+                .and_then(|column_statistics| value(column_statistics).clone())
+                .unwrap_or_else(|| null_value.clone())
+        });
+        // ignore errors converting to arrays (e.g. different types)
+        ScalarValue::iter_to_array(values).ok()
+    }
+}
+
+impl<'a> PruningStatistics for FilePruningStatistics<'a> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, |statistics| &statistics.min_value)
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.values(column, |statistics| &statistics.max_value)
+    }
+
+    fn num_containers(&self) -> usize {
+        self.files.len()
+    }
+}
+
+/// Prune out the files whose min/max column statistics show that none of
+/// their rows match `filters`. The files are kept when their statistics are
+/// unknown or the filters can't be evaluated on the statistics.
+pub fn prune_files_by_statistics(
+    files: Vec<(PartitionedFile, Statistics)>,
+    file_schema: SchemaRef,
+    filters: &[Expr],
+) -> Vec<(PartitionedFile, Statistics)> {
+    let predicate = match combine_filters(filters) {
+        Some(predicate) => predicate,
+        None => return files,
+    };
+    let pruning_predicate = match PruningPredicate::try_new(&predicate, file_schema) {
+        Ok(pruning_predicate) => pruning_predicate,
+        Err(e) => {
+            debug!(
+                "Could not create the file pruning predicate for {:?}: {}",
+                predicate, e
+            );
+            return files;
+        }
+    };
+
+    let statistics = FilePruningStatistics {
+        file_schema: pruning_predicate.schema(),
+        files: &files,
+    };
+    match pruning_predicate.prune(&statistics) {
+        Ok(keep) => {
+            let num_files = files.len();
+            let files = files
+                .into_iter()
+                .zip(keep)
+                .filter(|(_, keep)| *keep)
+                .map(|(file, _)| file)
+                .collect::<Vec<_>>();
+            debug!(
+                "Pruned {} of {} files with their statistics",
+                num_files - files.len(),
+                num_files
+            );
+            files
+        }
+        Err(e) => {
+            debug!("Error evaluating the file pruning predicate: {}", e);
+            files
+        }
+    }
+}
+
 /// Discover the partitions on the given path and prune out files
 /// that belong to irrelevant partitions using `filters` expressions.
 /// `filters` might contain expressions that can be resolved only at the
@@ -161,20 +266,30 @@ pub async fn pruned_partition_list(
     filters: &[Expr],
     file_extension: &str,
     table_partition_cols: &[String],
+) -> Result<PartitionedFileStream> {
+    let files = store
+        .list_file_with_suffix(table_path, file_extension)
+        .await?;
+    prune_partitions(files, table_path, filters, table_partition_cols).await
+}
+
+/// Prune out the `files` listed on the given path that belong to irrelevant
+/// partitions using `filters` expressions, as `pruned_partition_list` does
+/// for the files it lists.
+pub async fn prune_partitions(
+    files: FileMetaStream,
+    table_path: &str,
+    filters: &[Expr],
+    table_partition_cols: &[String],
 ) -> Result<PartitionedFileStream> {
     // if no partition col => simply list all the files
     if table_partition_cols.is_empty() {
-        return Ok(Box::pin(
-            store
-                .list_file_with_suffix(table_path, file_extension)
-                .await?
-                .map(|f| {
-                    Ok(PartitionedFile {
-                        partition_values: vec![],
-                        file_meta: f?,
-                    })
-                }),
-        ));
+        return Ok(Box::pin(files.map(|f| {
+            Ok(PartitionedFile {
+                partition_values: vec![],
+                file_meta: f?,
+            })
+        })));
     }
 
     let applicable_filters: Vec<_> = filters
@@ -188,44 +303,39 @@ pub async fn pruned_partition_list(
         // but the cost of parsing will likely be far dominated by the time to fetch the listing from
         // the object store.
         let table_partition_cols_stream = table_partition_cols.to_vec();
-        Ok(Box::pin(
-            store
-                .list_file_with_suffix(table_path, file_extension)
-                .await?
-                .filter_map(move |f| {
-                    let stream_path = stream_path.clone();
-                    let table_partition_cols_stream = table_partition_cols_stream.clone();
-                    async move {
-                        let file_meta = match f {
-                            Ok(fm) => fm,
-                            Err(err) => return Some(Err(err)),
-                        };
-                        let parsed_path = parse_partitions_for_path(
-                            &stream_path,
-                            file_meta.path(),
-                            &table_partition_cols_stream,
-                        )
-                        .map(|p| {
-                            p.iter()
-                                .map(|&pn| ScalarValue::Utf8(Some(pn.to_owned())))
-                                .collect()
-                        });
-
-                        parsed_path.map(|partition_values| {
-                            Ok(PartitionedFile {
-                                partition_values,
-                                file_meta,
-                            })
+        Ok(Box::pin(files.filter_map(move |f| {
+            let stream_path = stream_path.clone();
+            let table_partition_cols_stream = table_partition_cols_stream.clone();
+            async move {
+                let file_meta = match f {
+                    Ok(fm) => fm,
+                    Err(err) => return Some(Err(err)),
+                };
+                let parsed_path = parse_partitions_for_path(
+                    &stream_path,
+                    file_meta.path(),
+                    &table_partition_cols_stream,
+                )
+                .map(|p| {
+                    p.iter()
+                        .map(|&pn| {
+                            ScalarValue::Utf8(partition_value(pn).map(str::to_owned))
                         })
-                    }
-                }),
-        ))
+                        .collect()
+                });
+
+                parsed_path.map(|partition_values| {
+                    Ok(PartitionedFile {
+                        partition_values,
+                        file_meta,
+                    })
+                })
+            }
+        })))
     } else {
         // parse the partition values and serde them as a RecordBatch to filter them
         // TODO avoid collecting but have a streaming memory table instead
-        let batches: Vec<RecordBatch> = store
-            .list_file_with_suffix(table_path, file_extension)
-            .await?
+        let batches: Vec<RecordBatch> = files
             // TODO we set an arbitrary high batch size here, it does not matter as we list
             // all the files anyway. This number will need to be adjusted according to the object
             // store if we switch to a streaming-stlye pruning of the files. For instance S3 lists
@@ -284,7 +394,10 @@ fn paths_to_batch(
                 None => modified_builder.append_null()?,
             }
             for (i, part_val) in partition_values.iter().enumerate() {
-                partition_builders[i].append_value(part_val)?;
+                match partition_value(part_val) {
+                    Some(part_val) => partition_builders[i].append_value(part_val)?,
+                    None => partition_builders[i].append_null()?,
+                }
             }
         } else {
             debug!("No partitioning for path {}", file_meta.path());
@@ -308,7 +421,7 @@ fn paths_to_batch(
         Field::new(FILE_MODIFIED_COLUMN_NAME, DataType::Date64, false),
     ];
     for pn in table_partition_cols {
-        fields.push(Field::new(pn, DataType::Utf8, false));
+        fields.push(Field::new(pn, DataType::Utf8, true));
     }
 
     let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), col_arrays)?;
@@ -357,6 +470,15 @@ fn batches_to_paths(batches: &[RecordBatch]) -> Vec<PartitionedFile> {
         .collect()
 }
 
+/// The value of a partition from its directory name, `None` for the
+/// [`HIVE_DEFAULT_PARTITION`] of the null values
+fn partition_value(dir_value: &str) -> Option<&str> {
+    match dir_value {
+        HIVE_DEFAULT_PARTITION => None,
+        _ => Some(dir_value),
+    }
+}
+
 /// Extract the partition values for the given `file_path` (in the given `table_path`)
 /// associated to the partitions defined by `table_partition_cols`
 fn parse_partitions_for_path<'a>(
@@ -546,6 +668,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_partition_value() {
+        assert_eq!(Some("v1"), partition_value("v1"));
+        assert_eq!(None, partition_value("__HIVE_DEFAULT_PARTITION__"));
+    }
+
     #[test]
     fn test_parse_partitions_for_path() {
         assert_eq!(
@@ -18,7 +18,11 @@
 //! A table that uses the `ObjectStore` listing capability
 //! to get the list of files to process.
 
+mod cache;
 mod helpers;
 mod table;
 
+pub use cache::{
+    DefaultMetadataCache, FileCacheKey, MetadataCache, SchemaCacheKey, StatisticsCacheKey,
+};
 pub use table::{ListingOptions, ListingTable};
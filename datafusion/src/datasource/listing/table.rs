@@ -17,28 +17,39 @@
 
 //! The table implementation.
 
-use std::{any::Any, sync::Arc};
+use std::{any::Any, fs, path::PathBuf, sync::Arc};
 
 use arrow::datatypes::{Field, Schema, SchemaRef};
 use async_trait::async_trait;
-use futures::StreamExt;
+use futures::{stream, StreamExt, TryStreamExt};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
 use crate::{
-    error::Result,
+    error::{DataFusionError, Result},
     logical_plan::Expr,
     physical_plan::{
         empty::EmptyExec,
-        file_format::{PhysicalPlanConfig, DEFAULT_PARTITION_COLUMN_DATATYPE},
+        file_format::{
+            write_partitions, PhysicalPlanConfig, DEFAULT_PARTITION_COLUMN_DATATYPE,
+        },
+        insert::{DataSink, InsertExec},
         ExecutionPlan, Statistics,
     },
 };
 
 use crate::datasource::{
-    datasource::TableProviderFilterPushDown, file_format::FileFormat,
-    get_statistics_with_limit, object_store::ObjectStore, PartitionedFile, TableProvider,
+    datasource::TableProviderFilterPushDown,
+    file_format::{compression::FileCompressionType, FileFormat},
+    get_statistics_with_limit,
+    object_store::{FileMeta, ObjectStore},
+    PartitionedFile, TableProvider,
 };
 
-use super::helpers::{expr_applicable_for_cols, pruned_partition_list, split_files};
+use super::cache::{FileCacheKey, MetadataCache, SchemaCacheKey, StatisticsCacheKey};
+use super::helpers::{
+    expr_applicable_for_cols, prune_files_by_statistics, prune_partitions,
+    pruned_partition_list, split_files,
+};
 
 /// Options for creating a `ListingTable`
 pub struct ListingOptions {
@@ -57,7 +68,8 @@ pub struct ListingOptions {
     pub table_partition_cols: Vec<String>,
     /// Set true to try to guess statistics from the files.
     /// This can add a lot of overhead as it will usually require files
-    /// to be opened and at least partially parsed.
+    /// to be opened and at least partially parsed. The statistics are also
+    /// used to skip the files that can't match the filters of a scan.
     pub collect_stat: bool,
     /// Group files to avoid that the number of partitions exceeds
     /// this limit
@@ -92,11 +104,87 @@ impl ListingOptions {
         object_store: Arc<dyn ObjectStore>,
         path: &'a str,
     ) -> Result<SchemaRef> {
+        let (format, file_extension) = self.format_for(path);
         let file_stream = object_store
-            .list_file_with_suffix(path, &self.file_extension)
+            .list_file_with_suffix(path, &file_extension)
             .await?
             .map(move |file_meta| object_store.file_reader(file_meta?.sized_file));
-        let file_schema = self.format.infer_schema(Box::pin(file_stream)).await?;
+        let file_schema = format.infer_schema(Box::pin(file_stream)).await?;
+        Ok(file_schema)
+    }
+
+    /// Read the files of `path` with the compression inferred from the
+    /// extension of `path`, or else from `file_extension`, e.g. gzip for
+    /// `logs.csv.gz`, if the format reads compressed files and its compression
+    /// is not set. The extension of the compression is appended to
+    /// `file_extension` if it is missing.
+    pub fn infer_file_compression(mut self, path: &str) -> Self {
+        let (format, file_extension) = self.format_for(path);
+        self.format = format;
+        self.file_extension = file_extension;
+        self
+    }
+
+    /// The format and the file extension reading the files of `path`, see
+    /// [`ListingOptions::infer_file_compression`]
+    fn format_for(&self, path: &str) -> (Arc<dyn FileFormat>, String) {
+        let compression = match FileCompressionType::from_extension(path) {
+            FileCompressionType::Uncompressed => {
+                FileCompressionType::from_extension(&self.file_extension)
+            }
+            compression => compression,
+        };
+        let format = match compression {
+            FileCompressionType::Uncompressed => None,
+            compression => self.format.with_inferred_compression(compression),
+        };
+        match format {
+            Some(format) => {
+                let mut file_extension = self.file_extension.clone();
+                if !file_extension
+                    .to_lowercase()
+                    .ends_with(compression.get_ext())
+                {
+                    file_extension.push_str(compression.get_ext());
+                }
+                (format, file_extension)
+            }
+            None => (Arc::clone(&self.format), self.file_extension.clone()),
+        }
+    }
+
+    /// Infer the schema of the files at the given path like `infer_schema`,
+    /// but reuse the schema cached in `metadata_cache` if it was already
+    /// inferred from the same files with the same format.
+    ///
+    /// The files are always listed, and the listing cached for the path is
+    /// replaced by the new one.
+    pub async fn infer_schema_with_cache<'a>(
+        &'a self,
+        object_store: Arc<dyn ObjectStore>,
+        path: &'a str,
+        metadata_cache: &'a dyn MetadataCache,
+    ) -> Result<SchemaRef> {
+        let (format, file_extension) = self.format_for(path);
+        let all_files: Vec<_> = object_store.list_file(path).await?.try_collect().await?;
+        let files: Vec<_> = all_files
+            .iter()
+            .filter(|f| f.path().ends_with(&file_extension))
+            .cloned()
+            .collect();
+        metadata_cache.put_listing(path, Arc::new(all_files));
+
+        let key = SchemaCacheKey {
+            files: files.iter().map(FileCacheKey::from).collect(),
+            format: format!("{:?}", format),
+        };
+        if let Some(file_schema) = metadata_cache.get_schema(&key) {
+            return Ok(file_schema);
+        }
+        let file_stream = stream::iter(files)
+            .map(move |file_meta| object_store.file_reader(file_meta.sized_file));
+        let file_schema = format.infer_schema(Box::pin(file_stream)).await?;
+        metadata_cache.put_schema(key, Arc::clone(&file_schema));
         Ok(file_schema)
     }
 }
@@ -111,6 +199,7 @@ pub struct ListingTable {
     /// File fields + partition columns
     table_schema: SchemaRef,
     options: ListingOptions,
+    metadata_cache: Option<Arc<dyn MetadataCache>>,
 }
 
 impl ListingTable {
@@ -124,13 +213,14 @@ impl ListingTable {
         file_schema: SchemaRef,
         options: ListingOptions,
     ) -> Self {
+        let options = options.infer_file_compression(&table_path);
         // Add the partition columns to the file schema
         let mut table_fields = file_schema.fields().clone();
         for part in &options.table_partition_cols {
             table_fields.push(Field::new(
                 part,
                 DEFAULT_PARTITION_COLUMN_DATATYPE.clone(),
-                false,
+                true,
             ));
         }
 
@@ -140,9 +230,18 @@ impl ListingTable {
             file_schema,
             table_schema: Arc::new(Schema::new(table_fields)),
             options,
+            metadata_cache: None,
         }
     }
 
+    /// Cache the listing of the table and the statistics of its files in
+    /// `metadata_cache`, instead of listing the files and reading their
+    /// statistics for each scan
+    pub fn with_metadata_cache(mut self, metadata_cache: Arc<dyn MetadataCache>) -> Self {
+        self.metadata_cache = Some(metadata_cache);
+        self
+    }
+
     /// Get object store ref
     pub fn object_store(&self) -> &Arc<dyn ObjectStore> {
         &self.object_store
@@ -155,6 +254,10 @@ impl ListingTable {
     pub fn options(&self) -> &ListingOptions {
         &self.options
     }
+    /// Get the cache of the listing and of the file metadata, if any
+    pub fn metadata_cache(&self) -> Option<&Arc<dyn MetadataCache>> {
+        self.metadata_cache.as_ref()
+    }
 }
 
 #[async_trait]
@@ -202,6 +305,7 @@ impl TableProvider for ListingTable {
                     batch_size,
                     limit,
                     table_partition_cols: self.options.table_partition_cols.clone(),
+                    metadata_cache: self.metadata_cache.clone(),
                 },
                 filters,
             )
@@ -221,6 +325,104 @@ impl TableProvider for ListingTable {
             Ok(TableProviderFilterPushDown::Inexact)
         }
     }
+
+    async fn insert_into(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        overwrite: bool,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if !self.options.table_partition_cols.is_empty() {
+            return Err(DataFusionError::NotImplemented(
+                "Inserting into a partitioned listing table is not supported".to_string(),
+            ));
+        }
+        let dir = self
+            .object_store
+            .local_path(&self.table_path)
+            .ok_or_else(|| {
+                DataFusionError::NotImplemented(format!(
+                    "Inserting into the listing table {} is only supported on the local \
+                file system, not with {:?}",
+                    self.table_path, self.object_store
+                ))
+            })?;
+        if dir.is_file() {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Inserting into the listing table of the single file {} is not supported",
+                self.table_path
+            )));
+        }
+
+        let sink = ListingSink {
+            table_path: self.table_path.clone(),
+            dir,
+            file_extension: self.options.file_extension.clone(),
+            format: self.options.format.clone(),
+            overwrite,
+            metadata_cache: self.metadata_cache.clone(),
+        };
+        Ok(Arc::new(InsertExec::new(input, Arc::new(sink))))
+    }
+}
+
+/// Writes the rows inserted into a [`ListingTable`] to new files in its
+/// directory of the local file system
+#[derive(Debug)]
+struct ListingSink {
+    table_path: String,
+    /// The directory of the table in the local file system
+    dir: PathBuf,
+    file_extension: String,
+    format: Arc<dyn FileFormat>,
+    overwrite: bool,
+    metadata_cache: Option<Arc<dyn MetadataCache>>,
+}
+
+#[async_trait]
+impl DataSink for ListingSink {
+    async fn write_all(&self, input: Arc<dyn ExecutionPlan>) -> Result<usize> {
+        let dir = self.dir.as_path();
+        fs::create_dir_all(dir)?;
+
+        // the files of each insert get a unique prefix so that they do not
+        // replace the files written before
+        let suffix: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect();
+        let file_prefix = format!("part-{}", suffix);
+        let num_rows = write_partitions(
+            input,
+            dir,
+            &file_prefix,
+            &self.file_extension,
+            self.format.as_ref(),
+        )
+        .await?;
+
+        // the previous files are only removed once the new ones are written
+        if self.overwrite {
+            for entry in fs::read_dir(dir)? {
+                let path = entry?.path();
+                let is_previous_file = path.is_file()
+                    && path.file_name().map_or(false, |name| {
+                        let name = name.to_string_lossy();
+                        name.ends_with(&self.file_extension)
+                            && !name.starts_with(&file_prefix)
+                    });
+                if is_previous_file {
+                    fs::remove_file(path)?;
+                }
+            }
+        }
+
+        // the next scans must list the new files
+        if let Some(metadata_cache) = &self.metadata_cache {
+            metadata_cache.invalidate(&self.table_path);
+        }
+        Ok(num_rows)
+    }
 }
 
 impl ListingTable {
@@ -233,14 +435,34 @@ impl ListingTable {
         limit: Option<usize>,
     ) -> Result<(Vec<Vec<PartitionedFile>>, Statistics)> {
         // list files (with partitions)
-        let file_list = pruned_partition_list(
-            self.object_store.as_ref(),
-            &self.table_path,
-            filters,
-            &self.options.file_extension,
-            &self.options.table_partition_cols,
-        )
-        .await?;
+        let file_list = match &self.metadata_cache {
+            Some(metadata_cache) => {
+                let files = self
+                    .list_files_with_cache(metadata_cache.as_ref())
+                    .await?
+                    .iter()
+                    .filter(|f| f.path().ends_with(&self.options.file_extension))
+                    .map(|f| Ok(f.clone()))
+                    .collect::<Vec<_>>();
+                prune_partitions(
+                    Box::pin(stream::iter(files)),
+                    &self.table_path,
+                    filters,
+                    &self.options.table_partition_cols,
+                )
+                .await?
+            }
+            None => {
+                pruned_partition_list(
+                    self.object_store.as_ref(),
+                    &self.table_path,
+                    filters,
+                    &self.options.file_extension,
+                    &self.options.table_partition_cols,
+                )
+                .await?
+            }
+        };
 
         // collect the statistics if required by the config
         let object_store = Arc::clone(&self.object_store);
@@ -249,9 +471,30 @@ impl ListingTable {
             async move {
                 let part_file = part_file?;
                 let statistics = if self.options.collect_stat {
-                    let object_reader = object_store
-                        .file_reader(part_file.file_meta.sized_file.clone())?;
-                    self.options.format.infer_stats(object_reader).await?
+                    let key = StatisticsCacheKey {
+                        file: FileCacheKey::from(&part_file.file_meta),
+                        table_schema: format!("{:?}", self.file_schema),
+                    };
+                    let cached = self
+                        .metadata_cache
+                        .as_ref()
+                        .and_then(|cache| cache.get_statistics(&key));
+                    match cached {
+                        Some(statistics) => statistics,
+                        None => {
+                            let object_reader = object_store
+                                .file_reader(part_file.file_meta.sized_file.clone())?;
+                            let statistics = self
+                                .options
+                                .format
+                                .infer_stats(object_reader, Arc::clone(&self.file_schema))
+                                .await?;
+                            if let Some(metadata_cache) = &self.metadata_cache {
+                                metadata_cache.put_statistics(key, statistics.clone());
+                            }
+                            statistics
+                        }
+                    }
                 } else {
                     Statistics::default()
                 };
@@ -259,31 +502,110 @@ impl ListingTable {
             }
         });
 
-        let (files, statistics) =
-            get_statistics_with_limit(files, self.schema(), limit).await?;
+        let (files, statistics) = if self.options.collect_stat && !filters.is_empty() {
+            // the files are pruned with the statistics of all of them, so the
+            // listing can't stop at the limit
+            let files = files.try_collect::<Vec<_>>().await?;
+            let files =
+                prune_files_by_statistics(files, Arc::clone(&self.file_schema), filters);
+            get_statistics_with_limit(stream::iter(files).map(Ok), self.schema(), limit)
+                .await?
+        } else {
+            get_statistics_with_limit(files, self.schema(), limit).await?
+        };
 
         Ok((
             split_files(files, self.options.target_partitions),
             statistics,
         ))
     }
+
+    /// All the files under the table path, listed from the object store only
+    /// if they are not in `metadata_cache`
+    async fn list_files_with_cache(
+        &self,
+        metadata_cache: &dyn MetadataCache,
+    ) -> Result<Arc<Vec<FileMeta>>> {
+        if let Some(files) = metadata_cache.get_listing(&self.table_path) {
+            return Ok(files);
+        }
+        let files: Vec<_> = self
+            .object_store
+            .list_file(&self.table_path)
+            .await?
+            .try_collect()
+            .await?;
+        let files = Arc::new(files);
+        metadata_cache.put_listing(&self.table_path, Arc::clone(&files));
+        Ok(files)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use arrow::array::{ArrayRef, Int32Array};
     use arrow::datatypes::DataType;
+    use arrow::record_batch::RecordBatch;
+    use parquet::arrow::ArrowWriter;
 
     use crate::{
         datasource::{
-            file_format::{avro::AvroFormat, parquet::ParquetFormat},
+            file_format::{avro::AvroFormat, csv::CsvFormat, parquet::ParquetFormat},
             object_store::local::LocalFileSystem,
         },
         logical_plan::{col, lit},
+        physical_plan::{collect, file_format::ParquetExec, memory::MemoryExec},
         test::{columns, object_store::TestObjectStore},
     };
 
     use super::*;
 
+    #[test]
+    fn infer_file_compression() {
+        let csv_compression = |options: &ListingOptions| {
+            options
+                .format
+                .as_any()
+                .downcast_ref::<CsvFormat>()
+                .unwrap()
+                .file_compression_type()
+        };
+        let csv_options = |file_extension: &str| {
+            let mut options = ListingOptions::new(Arc::new(CsvFormat::default()));
+            options.file_extension = file_extension.to_owned();
+            options
+        };
+
+        // from the path of a single file, whose extension is then listed
+        let options = csv_options(".csv").infer_file_compression("logs/day.csv.gz");
+        assert_eq!(FileCompressionType::Gzip, csv_compression(&options));
+        assert_eq!(".csv.gz", options.file_extension);
+
+        // from the extension of the files of a directory
+        let options = csv_options(".csv.BZ2").infer_file_compression("logs");
+        assert_eq!(FileCompressionType::Bzip2, csv_compression(&options));
+        assert_eq!(".csv.BZ2", options.file_extension);
+
+        let options = csv_options(".csv").infer_file_compression("logs");
+        assert_eq!(FileCompressionType::Uncompressed, csv_compression(&options));
+        assert_eq!(".csv", options.file_extension);
+
+        // the compression set on the format is kept
+        let mut options = ListingOptions::new(Arc::new(
+            CsvFormat::default().with_file_compression_type(FileCompressionType::Xz),
+        ));
+        options.file_extension = ".csv".to_owned();
+        let options = options.infer_file_compression("logs/day.csv.gz");
+        assert_eq!(FileCompressionType::Xz, csv_compression(&options));
+        assert_eq!(".csv", options.file_extension);
+
+        // formats without whole file compression are not changed
+        let options = ListingOptions::new(Arc::new(ParquetFormat::default()))
+            .infer_file_compression("data.parquet.gz");
+        assert!(options.format.as_any().is::<ParquetFormat>());
+        assert_eq!("", options.file_extension);
+    }
+
     #[tokio::test]
     async fn read_single_file() -> Result<()> {
         let table = load_table("alltypes_plain.parquet").await?;
@@ -320,6 +642,80 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn prune_files_with_statistics() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        for (name, values) in [("1.parquet", vec![1, 2]), ("2.parquet", vec![10, 20])] {
+            let batch = RecordBatch::try_from_iter(vec![(
+                "a",
+                Arc::new(Int32Array::from(values)) as ArrayRef,
+            )])?;
+            let file = fs::File::create(tmp_dir.path().join(name))?;
+            let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+            writer.write(&batch)?;
+            writer.close()?;
+        }
+        let path = tmp_dir.path().to_str().unwrap().to_owned();
+        let opt = ListingOptions::new(Arc::new(ParquetFormat::default()));
+        let schema = opt
+            .infer_schema(Arc::new(LocalFileSystem {}), &path)
+            .await?;
+        let table = ListingTable::new(Arc::new(LocalFileSystem {}), path, schema, opt);
+
+        let exec = table
+            .scan(&None, 1024, &[col("a").gt(lit(5))], None)
+            .await?;
+        let files = exec
+            .as_any()
+            .downcast_ref::<ParquetExec>()
+            .expect("ParquetExec")
+            .base_config()
+            .file_groups
+            .iter()
+            .flatten()
+            .map(|file| file.file_meta.path().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(1, files.len());
+        assert!(files[0].ends_with("2.parquet"));
+        assert_eq!(exec.statistics().num_rows, Some(2));
+
+        // no file can match the filter
+        let exec = table
+            .scan(&None, 1024, &[col("a").lt(lit(0))], None)
+            .await?;
+        assert!(exec.as_any().is::<EmptyExec>());
+
+        // the filters on columns without statistics keep all the files
+        let exec = table
+            .scan(&None, 1024, &[col("a").not_eq(col("a"))], None)
+            .await?;
+        assert_eq!(exec.statistics().num_rows, Some(4));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn insert_into_remote_store() -> Result<()> {
+        let store = TestObjectStore::new_arc(&[("table/file.avro", 100)]);
+        let opt = ListingOptions::new(Arc::new(AvroFormat {}));
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Boolean, false)]));
+        let table = ListingTable::new(store, "table/".to_owned(), file_schema, opt);
+
+        let input = Arc::new(EmptyExec::new(false, table.schema()));
+        let err = table.insert_into(input, false).await.unwrap_err();
+        assert!(
+            err.to_string().starts_with(
+                "This feature is not implemented: Inserting into the listing table \
+                table/ is only supported on the local file system"
+            ),
+            "{}",
+            err
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn read_empty_table() -> Result<()> {
         let store = TestObjectStore::new_arc(&[("table/p1=v1/file.avro", 100)]);
@@ -422,6 +818,51 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn insert_into_directory() -> Result<()> {
+        let tmp_dir = tempfile::TempDir::new()?;
+        let table_path = tmp_dir.path().to_str().unwrap().to_owned();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let opt = ListingOptions {
+            file_extension: ".parquet".to_owned(),
+            format: Arc::new(ParquetFormat::default()),
+            table_partition_cols: vec![],
+            target_partitions: 1,
+            collect_stat: true,
+        };
+        let table = ListingTable::new(
+            Arc::new(LocalFileSystem {}),
+            table_path,
+            schema.clone(),
+            opt,
+        );
+
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+        )?;
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch]],
+            schema,
+            None,
+        )?);
+
+        let insert = table.insert_into(input.clone(), false).await?;
+        collect(insert.clone()).await?;
+        collect(insert).await?;
+        assert_eq!(4, fs::read_dir(tmp_dir.path())?.count());
+        let exec = table.scan(&None, 1024, &[], None).await?;
+        assert_eq!(Some(12), exec.statistics().num_rows);
+
+        let insert = table.insert_into(input, true).await?;
+        collect(insert).await?;
+        assert_eq!(2, fs::read_dir(tmp_dir.path())?.count());
+        let exec = table.scan(&None, 1024, &[], None).await?;
+        assert_eq!(Some(6), exec.statistics().num_rows);
+
+        Ok(())
+    }
+
     async fn load_table(name: &str) -> Result<Arc<dyn TableProvider>> {
         let testdata = crate::test_util::parquet_test_data();
         let filename = format!("{}/{}", testdata, name);
@@ -21,9 +21,11 @@
 
 use futures::StreamExt;
 use std::any::Any;
-use std::sync::Arc;
+use std::fmt::{self, Debug};
+use std::sync::{Arc, RwLock};
 
 use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 use async_trait::async_trait;
 
@@ -31,6 +33,7 @@ use crate::datasource::TableProvider;
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::Expr;
 use crate::physical_plan::common;
+use crate::physical_plan::insert::{DataSink, InsertExec};
 use crate::physical_plan::memory::MemoryExec;
 use crate::physical_plan::ExecutionPlan;
 use crate::physical_plan::{repartition::RepartitionExec, Partitioning};
@@ -38,7 +41,7 @@ use crate::physical_plan::{repartition::RepartitionExec, Partitioning};
 /// In-memory table
 pub struct MemTable {
     schema: SchemaRef,
-    batches: Vec<Vec<RecordBatch>>,
+    batches: Arc<RwLock<Vec<Vec<RecordBatch>>>>,
 }
 
 impl MemTable {
@@ -51,7 +54,7 @@ impl MemTable {
         {
             Ok(Self {
                 schema,
-                batches: partitions,
+                batches: Arc::new(RwLock::new(partitions)),
             })
         } else {
             Err(DataFusionError::Plan(
@@ -132,18 +135,102 @@ impl TableProvider for MemTable {
         _filters: &[Expr],
         _limit: Option<usize>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
+        let batches = self.batches.read().unwrap();
         Ok(Arc::new(MemoryExec::try_new(
-            &self.batches.clone(),
+            &batches,
             self.schema(),
             projection.clone(),
         )?))
     }
+
+    async fn insert_into(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        overwrite: bool,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let sink = MemSink {
+            schema: self.schema.clone(),
+            batches: self.batches.clone(),
+            overwrite,
+        };
+        Ok(Arc::new(InsertExec::new(input, Arc::new(sink))))
+    }
+}
+
+/// Adds the rows inserted into a [`MemTable`] to its partitions
+struct MemSink {
+    schema: SchemaRef,
+    batches: Arc<RwLock<Vec<Vec<RecordBatch>>>>,
+    overwrite: bool,
+}
+
+impl Debug for MemSink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemSink")
+            .field("overwrite", &self.overwrite)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl DataSink for MemSink {
+    async fn write_all(&self, input: Arc<dyn ExecutionPlan>) -> Result<usize> {
+        let tasks = (0..input.output_partitioning().partition_count())
+            .map(|part_i| {
+                let input = input.clone();
+                tokio::spawn(async move {
+                    let stream = input.execute(part_i).await?;
+                    common::collect(stream).await
+                })
+            })
+            // this collect *is needed* so that the join below can
+            // switch between tasks
+            .collect::<Vec<_>>();
+
+        let mut new_partitions = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            let batches = task.await.map_err(|e| {
+                DataFusionError::Execution(format!(
+                    "MemTable insert could not join task: {}",
+                    e
+                ))
+            })??;
+            // the columns of the input are in the order of the table but may
+            // be named differently
+            let batches = batches
+                .iter()
+                .map(|batch| {
+                    RecordBatch::try_new(self.schema.clone(), batch.columns().to_vec())
+                })
+                .collect::<ArrowResult<Vec<_>>>()?;
+            new_partitions.push(batches);
+        }
+        let num_rows = new_partitions
+            .iter()
+            .flatten()
+            .map(|batch| batch.num_rows())
+            .sum();
+
+        let mut partitions = self.batches.write().unwrap();
+        if self.overwrite || partitions.is_empty() {
+            if new_partitions.is_empty() {
+                new_partitions.push(vec![]);
+            }
+            *partitions = new_partitions;
+        } else {
+            let partition_count = partitions.len();
+            for (i, batches) in new_partitions.into_iter().enumerate() {
+                partitions[i % partition_count].extend(batches);
+            }
+        }
+        Ok(num_rows)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use arrow::array::Int32Array;
+    use arrow::array::{Array, Int32Array, UInt64Array};
     use arrow::datatypes::{DataType, Field, Schema};
     use futures::StreamExt;
     use std::collections::HashMap;
@@ -359,4 +446,42 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_insert_into() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2, 3]))],
+        )?;
+        let provider = MemTable::try_new(schema.clone(), vec![vec![batch.clone()]])?;
+
+        let input = Arc::new(MemoryExec::try_new(
+            &[vec![batch.clone()], vec![batch.clone()]],
+            schema.clone(),
+            None,
+        )?);
+        let insert = provider.insert_into(input.clone(), false).await?;
+        let count = common::collect(insert.execute(0).await?).await?;
+        let count = count[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap();
+        assert_eq!(6, count.value(0));
+
+        let exec = provider.scan(&None, 1024, &[], None).await?;
+        let batches = common::collect(exec.execute(0).await?).await?;
+        assert_eq!(9, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+
+        // overwrite
+        let insert = provider.insert_into(input, true).await?;
+        common::collect(insert.execute(0).await?).await?;
+        let exec = provider.scan(&None, 1024, &[], None).await?;
+        assert_eq!(2, exec.output_partitioning().partition_count());
+        let batches = common::collect(exec.execute(0).await?).await?;
+        assert_eq!(3, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+
+        Ok(())
+    }
 }
@@ -17,12 +17,14 @@
 
 //! DataFusion data sources
 
+pub mod cte_worktable;
 pub mod datasource;
 pub mod empty;
 pub mod file_format;
 pub mod listing;
 pub mod memory;
 pub mod object_store;
+pub mod view;
 
 use futures::Stream;
 
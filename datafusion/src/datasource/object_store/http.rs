@@ -0,0 +1,574 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Object store that reads the files served by HTTP servers.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::io::{self, Cursor, Read};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{stream, AsyncRead, StreamExt, TryStreamExt};
+
+use crate::datasource::object_store::{
+    FileMeta, FileMetaStream, ListEntry, ListEntryStream, ObjectReader, ObjectStore,
+    SizedFile,
+};
+use crate::error::{DataFusionError, Result};
+
+/// The default name of the manifest file listing the files of a directory
+pub const DEFAULT_MANIFEST_FILE_NAME: &str = "_manifest";
+
+/// The maximum number of concurrent `HEAD` requests fetching the metadata of
+/// the files listed in a manifest
+const MAX_CONCURRENT_HEADS: usize = 16;
+
+/// Object store that reads the files served by HTTP servers with range
+/// requests, so that e.g. only the footer and the needed column chunks of
+/// Parquet files are downloaded.
+///
+/// The paths of the files are their URLs without the scheme of the store:
+/// the path `host/data/file.parquet` of the `https` store is read from
+/// `https://host/data/file.parquet`.
+///
+/// HTTP servers can't list directories, so a directory is listed by reading
+/// its manifest file, e.g. `https://host/data/_manifest`, which holds the
+/// paths of the files of the directory relative to it, one per line. A path
+/// is listed as a single file if the server has a file at this path, and a
+/// path ending with `/` is always listed as a directory.
+///
+/// The requests are blocking, like the reads of the files of the
+/// [`LocalFileSystem`](super::local::LocalFileSystem), so the async methods
+/// send them from the threads reserved for blocking tasks.
+pub struct HttpObjectStore {
+    scheme: String,
+    manifest_file_name: String,
+    agent: ureq::Agent,
+}
+
+impl fmt::Debug for HttpObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpObjectStore")
+            .field("scheme", &self.scheme)
+            .field("manifest_file_name", &self.manifest_file_name)
+            .finish()
+    }
+}
+
+impl HttpObjectStore {
+    /// Create a store reading the files with the scheme `http` or `https`
+    pub fn new(scheme: impl Into<String>) -> Self {
+        Self {
+            scheme: scheme.into(),
+            manifest_file_name: DEFAULT_MANIFEST_FILE_NAME.to_owned(),
+            agent: ureq::AgentBuilder::new().build(),
+        }
+    }
+
+    /// Set the name of the manifest file listing the files of a directory
+    pub fn with_manifest_file_name(
+        mut self,
+        manifest_file_name: impl Into<String>,
+    ) -> Self {
+        self.manifest_file_name = manifest_file_name.into();
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}://{}", self.scheme, path)
+    }
+
+    /// The metadata of the file `path`, or `None` if the server has no file
+    /// at this path
+    async fn head(&self, path: &str) -> Result<Option<FileMeta>> {
+        let agent = self.agent.clone();
+        let url = self.url(path);
+        let path = path.to_owned();
+        spawn_request(move || fetch_head(&agent, &url, path)).await
+    }
+
+    /// The files listed in the manifest file of the directory `dir`, whose
+    /// metadata is fetched concurrently
+    async fn list_manifest(&self, dir: &str) -> Result<Vec<FileMeta>> {
+        let dir = dir.trim_end_matches('/');
+        let manifest_url = self.url(&format!("{}/{}", dir, self.manifest_file_name));
+        let agent = self.agent.clone();
+        let url = manifest_url.clone();
+        let manifest = spawn_request(move || {
+            Ok(agent
+                .get(&url)
+                .call()
+                .map_err(|e| request_error(&url, e))?
+                .into_string()?)
+        })
+        .await?;
+
+        let manifest_url = &manifest_url;
+        let paths = manifest
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|name| format!("{}/{}", dir, name))
+            .collect::<Vec<_>>();
+        stream::iter(paths)
+            .map(|path| async move {
+                self.head(&path).await?.ok_or_else(|| {
+                    DataFusionError::Execution(format!(
+                        "The file {} listed in {} does not exist",
+                        self.url(&path),
+                        manifest_url
+                    ))
+                })
+            })
+            .buffered(MAX_CONCURRENT_HEADS)
+            .try_collect()
+            .await
+    }
+
+    /// The file `prefix`, or the files of the directory `prefix`
+    async fn list(&self, prefix: &str) -> Result<Vec<FileMeta>> {
+        if !prefix.ends_with('/') {
+            if let Some(file) = self.head(prefix).await? {
+                return Ok(vec![file]);
+            }
+        }
+        self.list_manifest(prefix).await
+    }
+}
+
+/// Run the blocking request `f` on the threads reserved for blocking tasks
+async fn spawn_request<T, F>(f: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| DataFusionError::Execution(e.to_string()))?
+}
+
+/// The metadata of the file `path` at `url` from the headers of a `HEAD`
+/// request, or `None` if the server has no file at this path
+fn fetch_head(agent: &ureq::Agent, url: &str, path: String) -> Result<Option<FileMeta>> {
+    let response = match agent.head(url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(None),
+        Err(e) => return Err(request_error(url, e)),
+    };
+
+    let size = response
+        .header("Content-Length")
+        .and_then(|length| length.parse::<u64>().ok())
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!(
+                "The response to HEAD {} has no valid Content-Length",
+                url
+            ))
+        })?;
+    let last_modified = response
+        .header("Last-Modified")
+        .and_then(|date| DateTime::parse_from_rfc2822(date).ok())
+        .map(|date| date.with_timezone(&Utc));
+    Ok(Some(FileMeta {
+        sized_file: SizedFile { path, size },
+        last_modified,
+    }))
+}
+
+fn request_error(url: &str, e: ureq::Error) -> DataFusionError {
+    DataFusionError::Execution(format!("Request to {} failed: {}", url, e))
+}
+
+#[async_trait]
+impl ObjectStore for HttpObjectStore {
+    async fn list_file(&self, prefix: &str) -> Result<FileMetaStream> {
+        let files = self.list(prefix).await?;
+        Ok(Box::pin(stream::iter(files.into_iter().map(Ok))))
+    }
+
+    async fn list_dir(
+        &self,
+        prefix: &str,
+        delimiter: Option<String>,
+    ) -> Result<ListEntryStream> {
+        let dir = format!("{}/", prefix.trim_end_matches('/'));
+        let mut entries = vec![];
+        let mut prefixes = BTreeSet::new();
+        for file in self.list(prefix).await? {
+            // the entries after the first delimiter are grouped in a prefix,
+            // which includes the delimiter
+            let delimiter_idx = file.path().strip_prefix(&dir).and_then(|rest| {
+                delimiter
+                    .as_deref()
+                    .filter(|d| !d.is_empty())
+                    .and_then(|d| rest.find(d).map(|idx| dir.len() + idx + d.len()))
+            });
+            match delimiter_idx {
+                Some(idx) => {
+                    prefixes.insert(file.path()[..idx].to_owned());
+                }
+                None => entries.push(Ok(ListEntry::FileMeta(file))),
+            }
+        }
+        entries.extend(prefixes.into_iter().map(|p| Ok(ListEntry::Prefix(p))));
+        Ok(Box::pin(stream::iter(entries)))
+    }
+
+    fn file_reader(&self, file: SizedFile) -> Result<Arc<dyn ObjectReader>> {
+        Ok(Arc::new(HttpFileReader {
+            agent: self.agent.clone(),
+            url: self.url(&file.path),
+            size: file.size,
+        }))
+    }
+}
+
+/// Reads the chunks of a file with range requests
+struct HttpFileReader {
+    agent: ureq::Agent,
+    url: String,
+    size: u64,
+}
+
+/// Download the bytes `[start, start + length]` of the file at `url`
+fn fetch_range(
+    agent: &ureq::Agent,
+    url: &str,
+    start: u64,
+    length: usize,
+) -> Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(length);
+    if length == 0 {
+        return Ok(buf);
+    }
+
+    let range = format!("bytes={}-{}", start, start + length as u64 - 1);
+    let response = agent
+        .get(url)
+        .set("Range", &range)
+        .call()
+        .map_err(|e| request_error(url, e))?;
+    let partial = response.status() == 206;
+    let mut reader = response.into_reader();
+    if !partial {
+        // the server ignored the range and sends the whole file
+        io::copy(&mut (&mut reader).take(start), &mut io::sink())?;
+    }
+    reader.take(length as u64).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+#[async_trait]
+impl ObjectReader for HttpFileReader {
+    async fn chunk_reader(
+        &self,
+        start: u64,
+        length: usize,
+    ) -> Result<Box<dyn AsyncRead>> {
+        let agent = self.agent.clone();
+        let url = self.url.clone();
+        let buf = spawn_request(move || fetch_range(&agent, &url, start, length)).await?;
+        Ok(Box::new(futures::io::Cursor::new(buf)))
+    }
+
+    fn sync_chunk_reader(
+        &self,
+        start: u64,
+        length: usize,
+    ) -> Result<Box<dyn Read + Send + Sync>> {
+        let buf = fetch_range(&self.agent, &self.url, start, length)?;
+        Ok(Box::new(Cursor::new(buf)))
+    }
+
+    fn length(&self) -> u64 {
+        self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    use futures::{AsyncReadExt, StreamExt, TryStreamExt};
+
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::execution::context::ExecutionContext;
+
+    /// A minimal HTTP server serving `files` by path, which records the
+    /// method and the range of the requests, and the maximum number of `HEAD`
+    /// requests it served concurrently
+    struct TestServer {
+        address: String,
+        requests: Arc<Mutex<Vec<(String, Option<String>)>>>,
+        heads: Arc<ConcurrentHeads>,
+    }
+
+    #[derive(Default)]
+    struct ConcurrentHeads {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl TestServer {
+        fn start(files: Vec<(&str, Vec<u8>)>) -> Self {
+            let files: HashMap<_, _> = files
+                .into_iter()
+                .map(|(path, data)| (format!("/{}", path), data))
+                .collect();
+            let files = Arc::new(files);
+            let requests = Arc::new(Mutex::new(vec![]));
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+
+            let heads = Arc::new(ConcurrentHeads::default());
+
+            let server_requests = Arc::clone(&requests);
+            let server_heads = Arc::clone(&heads);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let files = Arc::clone(&files);
+                    let requests = Arc::clone(&server_requests);
+                    let heads = Arc::clone(&server_heads);
+                    thread::spawn(move || {
+                        serve(stream.unwrap(), &files, &requests, &heads).unwrap()
+                    });
+                }
+            });
+
+            Self {
+                address,
+                requests,
+                heads,
+            }
+        }
+
+        fn url_path(&self, path: &str) -> String {
+            format!("{}/{}", self.address, path)
+        }
+    }
+
+    fn serve(
+        mut stream: TcpStream,
+        files: &HashMap<String, Vec<u8>>,
+        requests: &Mutex<Vec<(String, Option<String>)>>,
+        heads: &ConcurrentHeads,
+    ) -> io::Result<()> {
+        let mut lines = BufReader::new(stream.try_clone()?).lines();
+        let request_line = lines.next().unwrap()?;
+        let mut parts = request_line.split(' ');
+        let method = parts.next().unwrap().to_owned();
+        let path = parts.next().unwrap().to_owned();
+        let mut range = None;
+        for line in lines {
+            let line = line?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("range") {
+                    range = Some(value.trim().to_owned());
+                }
+            }
+        }
+        requests
+            .lock()
+            .unwrap()
+            .push((method.clone(), range.clone()));
+        if method == "HEAD" {
+            // slow the HEAD requests down so that concurrent ones overlap
+            let current = heads.current.fetch_add(1, Ordering::SeqCst) + 1;
+            heads.max.fetch_max(current, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            heads.current.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        let data = match files.get(&path) {
+            Some(data) => data,
+            None => {
+                return stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                )
+            }
+        };
+        let (status, body) = match range.as_deref().and_then(|r| r.strip_prefix("bytes="))
+        {
+            Some(range) => {
+                let (start, end) = range.split_once('-').unwrap();
+                let start: usize = start.parse().unwrap();
+                let end = end.parse::<usize>().unwrap().min(data.len() - 1);
+                ("206 Partial Content", &data[start..=end])
+            }
+            None => ("200 OK", &data[..]),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\n\
+             Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\nConnection: close\r\n\r\n",
+            status,
+            body.len()
+        )?;
+        if method != "HEAD" {
+            stream.write_all(body)?;
+        }
+        Ok(())
+    }
+
+    fn test_server() -> TestServer {
+        TestServer::start(vec![
+            ("data/a.csv", b"c1\n1\n".to_vec()),
+            ("data/year=2021/b.csv", b"c1\n2\n".to_vec()),
+            ("data/_manifest", b"a.csv\nyear=2021/b.csv\n".to_vec()),
+            ("file", b"0123456789".to_vec()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn list_files() -> Result<()> {
+        let server = test_server();
+        let store = HttpObjectStore::new("http");
+
+        let files: Vec<_> = store
+            .list_file(&server.url_path("file"))
+            .await?
+            .try_collect()
+            .await?;
+        assert_eq!(1, files.len());
+        assert_eq!(10, files[0].size());
+        assert_eq!(
+            "2015-10-21T07:28:00+00:00",
+            files[0].last_modified.unwrap().to_rfc3339()
+        );
+
+        // the directory is listed with its manifest
+        let paths: Vec<_> = store
+            .list_file(&server.url_path("data"))
+            .await?
+            .map_ok(|f| f.path().to_owned())
+            .try_collect()
+            .await?;
+        assert_eq!(
+            vec![
+                server.url_path("data/a.csv"),
+                server.url_path("data/year=2021/b.csv")
+            ],
+            paths
+        );
+        // the files of the manifest are requested concurrently
+        assert_eq!(2, server.heads.max.load(Ordering::SeqCst));
+
+        let entries: Vec<_> = store
+            .list_dir(&server.url_path("data/"), Some("/".to_owned()))
+            .await?
+            .map(|entry| match entry.unwrap() {
+                ListEntry::FileMeta(f) => format!("file {}", f.path()),
+                ListEntry::Prefix(p) => format!("prefix {}", p),
+            })
+            .collect()
+            .await;
+        assert_eq!(
+            vec![
+                format!("file {}", server.url_path("data/a.csv")),
+                format!("prefix {}", server.url_path("data/year=2021/")),
+            ],
+            entries
+        );
+
+        let err = store
+            .list_file(&server.url_path("missing"))
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("missing/_manifest"), "{}", err);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn range_reads() -> Result<()> {
+        let server = test_server();
+        let store = HttpObjectStore::new("http");
+        let reader = store.file_reader(SizedFile {
+            path: server.url_path("file"),
+            size: 10,
+        })?;
+
+        let mut buf = String::new();
+        reader.sync_chunk_reader(2, 3)?.read_to_string(&mut buf)?;
+        assert_eq!("234", buf);
+
+        let mut buf = String::new();
+        let mut chunk = Box::into_pin(reader.chunk_reader(5, 2).await?);
+        chunk.read_to_string(&mut buf).await?;
+        assert_eq!("56", buf);
+
+        assert_eq!(
+            vec![
+                ("GET".to_owned(), Some("bytes=2-4".to_owned())),
+                ("GET".to_owned(), Some("bytes=5-6".to_owned())),
+            ],
+            *server.requests.lock().unwrap()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_parquet_file() -> Result<()> {
+        let path = format!(
+            "{}/alltypes_plain.parquet",
+            crate::test_util::parquet_test_data()
+        );
+        let server =
+            TestServer::start(vec![("alltypes_plain.parquet", std::fs::read(path)?)]);
+
+        let mut ctx = ExecutionContext::new();
+        let uri = format!("http://{}", server.url_path("alltypes_plain.parquet"));
+        ctx.register_parquet("t", &uri).await?;
+
+        let batches = ctx
+            .sql("SELECT COUNT(*) AS n, MAX(id) AS max_id FROM t")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+---+--------+",
+            "| n | max_id |",
+            "+---+--------+",
+            "| 8 | 7      |",
+            "+---+--------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        // the file is only read with range requests
+        let requests = server.requests.lock().unwrap();
+        assert!(requests.iter().any(|(method, _)| method == "GET"));
+        assert!(requests
+            .iter()
+            .all(|(method, range)| method == "HEAD" || range.is_some()));
+
+        Ok(())
+    }
+}
@@ -19,6 +19,7 @@
 
 use std::fs::{self, File, Metadata};
 use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -54,6 +55,10 @@ impl ObjectStore for LocalFileSystem {
     fn file_reader(&self, file: SizedFile) -> Result<Arc<dyn ObjectReader>> {
         Ok(Arc::new(LocalFileReader::new(file)?))
     }
+
+    fn local_path(&self, prefix: &str) -> Option<PathBuf> {
+        Some(PathBuf::from(prefix))
+    }
 }
 
 struct LocalFileReader {
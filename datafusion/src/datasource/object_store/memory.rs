@@ -0,0 +1,407 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Object store that keeps its files in memory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Cursor, Read};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{stream, AsyncRead};
+
+use crate::datasource::object_store::{
+    FileMeta, FileMetaStream, ListEntry, ListEntryStream, ObjectReader, ObjectStore,
+    SizedFile,
+};
+use crate::error::{DataFusionError, Result};
+
+/// The scheme the in-memory object stores are usually registered with, so
+/// that the URI `memory://data/file.csv` refers to the file `data/file.csv`
+pub const MEMORY_SCHEME: &str = "memory";
+
+/// Object store that keeps its files in memory, e.g. to query buffers
+/// received by a service or to write hermetic tests.
+///
+/// The paths of the files are split by `/` into directories, so that
+/// listing the prefix `data` returns the files `data` and `data/...`, like
+/// [`LocalFileSystem`](super::local::LocalFileSystem) does.
+#[derive(Debug, Default)]
+pub struct InMemoryObjectStore {
+    files: RwLock<BTreeMap<String, InMemoryFile>>,
+}
+
+#[derive(Debug, Clone)]
+struct InMemoryFile {
+    data: Arc<Vec<u8>>,
+    last_modified: DateTime<Utc>,
+}
+
+impl InMemoryFile {
+    fn meta(&self, path: &str) -> FileMeta {
+        FileMeta {
+            sized_file: SizedFile {
+                path: path.to_owned(),
+                size: self.data.len() as u64,
+            },
+            last_modified: Some(self.last_modified),
+        }
+    }
+}
+
+impl InMemoryObjectStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the file `path` holding `data` to the store, replacing the file
+    /// previously stored at the same path
+    pub fn put(&self, path: impl Into<String>, data: impl Into<Vec<u8>>) {
+        let file = InMemoryFile {
+            data: Arc::new(data.into()),
+            last_modified: Utc::now(),
+        };
+        self.files.write().unwrap().insert(path.into(), file);
+    }
+
+    /// Remove the file `path` from the store. Returns whether the file
+    /// existed
+    pub fn remove(&self, path: &str) -> bool {
+        self.files.write().unwrap().remove(path).is_some()
+    }
+}
+
+/// Whether `path` is the file `prefix` or is in the directory `prefix`
+fn is_in_prefix(path: &str, prefix: &str) -> bool {
+    match path.strip_prefix(prefix) {
+        Some(rest) => {
+            prefix.is_empty()
+                || prefix.ends_with('/')
+                || rest.is_empty()
+                || rest.starts_with('/')
+        }
+        None => false,
+    }
+}
+
+#[async_trait]
+impl ObjectStore for InMemoryObjectStore {
+    async fn list_file(&self, prefix: &str) -> Result<FileMetaStream> {
+        let files: Vec<_> = self
+            .files
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(path, _)| is_in_prefix(path, prefix))
+            .map(|(path, file)| Ok(file.meta(path)))
+            .collect();
+        Ok(Box::pin(stream::iter(files)))
+    }
+
+    async fn list_dir(
+        &self,
+        prefix: &str,
+        delimiter: Option<String>,
+    ) -> Result<ListEntryStream> {
+        let files = self.files.read().unwrap();
+        let mut entries = vec![];
+        let mut prefixes = BTreeSet::new();
+        for (path, file) in files.range(prefix.to_owned()..) {
+            let rest = match path.strip_prefix(prefix) {
+                Some(rest) => rest,
+                None => break,
+            };
+            // the paths of a sibling directory, e.g. `data2/` for `data`, also
+            // start with the prefix
+            if !is_in_prefix(path, prefix) {
+                continue;
+            }
+            // the entries after the first delimiter are grouped in a prefix,
+            // which includes the delimiter
+            let delimiter_idx = delimiter
+                .as_deref()
+                .filter(|d| !d.is_empty())
+                .and_then(|d| rest.find(d).map(|idx| idx + d.len()));
+            match delimiter_idx {
+                Some(idx) => {
+                    prefixes.insert(path[..prefix.len() + idx].to_owned());
+                }
+                None => entries.push(Ok(ListEntry::FileMeta(file.meta(path)))),
+            }
+        }
+        entries.extend(prefixes.into_iter().map(|p| Ok(ListEntry::Prefix(p))));
+        Ok(Box::pin(stream::iter(entries)))
+    }
+
+    fn file_reader(&self, file: SizedFile) -> Result<Arc<dyn ObjectReader>> {
+        match self.files.read().unwrap().get(&file.path) {
+            Some(stored) => Ok(Arc::new(InMemoryFileReader {
+                data: Arc::clone(&stored.data),
+            })),
+            None => Err(DataFusionError::IoError(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("File {} not found in the in-memory object store", file.path),
+            ))),
+        }
+    }
+}
+
+/// Reads the data of a file as it was when the reader was created, even if
+/// the file is replaced afterwards
+struct InMemoryFileReader {
+    data: Arc<Vec<u8>>,
+}
+
+impl InMemoryFileReader {
+    /// The bytes `[start, start + length]` of the file, truncated at the end
+    /// of the file
+    fn chunk(&self, start: u64, length: usize) -> Chunk {
+        let size = self.data.len();
+        let start = (start as usize).min(size);
+        let end = start.saturating_add(length).min(size);
+        Chunk {
+            data: Arc::clone(&self.data),
+            start,
+            end,
+        }
+    }
+}
+
+/// A range of the data of a file, read without copying the data
+struct Chunk {
+    data: Arc<Vec<u8>>,
+    start: usize,
+    end: usize,
+}
+
+impl AsRef<[u8]> for Chunk {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[self.start..self.end]
+    }
+}
+
+#[async_trait]
+impl ObjectReader for InMemoryFileReader {
+    async fn chunk_reader(
+        &self,
+        start: u64,
+        length: usize,
+    ) -> Result<Box<dyn AsyncRead>> {
+        Ok(Box::new(futures::io::Cursor::new(
+            self.chunk(start, length),
+        )))
+    }
+
+    fn sync_chunk_reader(
+        &self,
+        start: u64,
+        length: usize,
+    ) -> Result<Box<dyn Read + Send + Sync>> {
+        Ok(Box::new(Cursor::new(self.chunk(start, length))))
+    }
+
+    fn length(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{AsyncReadExt, TryStreamExt};
+
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::execution::context::ExecutionContext;
+    use crate::prelude::CsvReadOptions;
+
+    fn test_store() -> InMemoryObjectStore {
+        let store = InMemoryObjectStore::new();
+        store.put("data/a.csv", "c1,c2\n1,a\n");
+        store.put("data/year=2021/b.csv", "c1,c2\n2,b\n");
+        store.put("data/year=2022/c.csv", "c1,c2\n3,c\n");
+        store.put("data2/d.csv", "c1,c2\n4,d\n");
+        store
+    }
+
+    #[tokio::test]
+    async fn list_file() -> Result<()> {
+        let store = test_store();
+
+        let list = |prefix: &'static str| {
+            let store = &store;
+            async move {
+                store
+                    .list_file(prefix)
+                    .await?
+                    .map_ok(|f| f.path().to_owned())
+                    .try_collect::<Vec<_>>()
+                    .await
+            }
+        };
+        assert_eq!(
+            vec!["data/a.csv", "data/year=2021/b.csv", "data/year=2022/c.csv"],
+            list("data").await?
+        );
+        assert_eq!(vec!["data/a.csv"], list("data/a.csv").await?);
+        assert_eq!(4, list("").await?.len());
+        assert!(list("dat").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn list_dir() -> Result<()> {
+        let store = test_store();
+
+        let list = |prefix: &'static str, delimiter: Option<&'static str>| {
+            let store = &store;
+            async move {
+                store
+                    .list_dir(prefix, delimiter.map(str::to_owned))
+                    .await?
+                    .map_ok(|entry| match entry {
+                        ListEntry::FileMeta(f) => format!("file {}", f.path()),
+                        ListEntry::Prefix(p) => format!("prefix {}", p),
+                    })
+                    .try_collect::<Vec<_>>()
+                    .await
+            }
+        };
+        assert_eq!(
+            vec![
+                "file data/a.csv",
+                "prefix data/year=2021/",
+                "prefix data/year=2022/"
+            ],
+            list("data/", Some("/")).await?
+        );
+        assert_eq!(vec!["prefix data/"], list("data", Some("/")).await?);
+
+        // without delimiter, all the files of the prefix are listed, but not
+        // the files of the sibling directory `data2`
+        assert_eq!(
+            vec![
+                "file data/a.csv",
+                "file data/year=2021/b.csv",
+                "file data/year=2022/c.csv"
+            ],
+            list("data", None).await?
+        );
+        assert_eq!(vec!["file data2/d.csv"], list("data2", None).await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn range_reads() -> Result<()> {
+        let store = InMemoryObjectStore::new();
+        store.put("file", b"0123456789".to_vec());
+        let reader = store.file_reader(SizedFile {
+            path: "file".to_owned(),
+            size: 10,
+        })?;
+        assert_eq!(10, reader.length());
+
+        let mut buf = String::new();
+        reader.sync_chunk_reader(2, 3)?.read_to_string(&mut buf)?;
+        assert_eq!("234", buf);
+
+        // the chunk is truncated at the end of the file
+        let mut buf = String::new();
+        reader.sync_chunk_reader(8, 5)?.read_to_string(&mut buf)?;
+        assert_eq!("89", buf);
+
+        let mut buf = String::new();
+        let mut chunk = Box::into_pin(reader.chunk_reader(5, 2).await?);
+        chunk.read_to_string(&mut buf).await?;
+        assert_eq!("56", buf);
+
+        let err = store
+            .file_reader(SizedFile {
+                path: "missing".to_owned(),
+                size: 0,
+            })
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string().contains("File missing not found"),
+            "{}",
+            err
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_csv_files() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        ctx.register_object_store(MEMORY_SCHEME, Arc::new(test_store()));
+        ctx.register_csv("t", "memory://data", CsvReadOptions::new())
+            .await?;
+
+        let batches = ctx
+            .sql("SELECT c1, c2 FROM t ORDER BY c1")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+----+----+",
+            "| c1 | c2 |",
+            "+----+----+",
+            "| 1  | a  |",
+            "| 2  | b  |",
+            "| 3  | c  |",
+            "+----+----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query_parquet_file() -> Result<()> {
+        let path = format!(
+            "{}/alltypes_plain.parquet",
+            crate::test_util::parquet_test_data()
+        );
+        let store = InMemoryObjectStore::new();
+        store.put("alltypes_plain.parquet", std::fs::read(path)?);
+
+        let mut ctx = ExecutionContext::new();
+        ctx.register_object_store(MEMORY_SCHEME, Arc::new(store));
+        ctx.register_parquet("t", "memory://alltypes_plain.parquet")
+            .await?;
+
+        let batches = ctx
+            .sql("SELECT COUNT(*) AS n, MAX(id) AS max_id FROM t")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+---+--------+",
+            "| n | max_id |",
+            "+---+--------+",
+            "| 8 | 7      |",
+            "+---+--------+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+}
@@ -17,11 +17,15 @@
 
 //! Object Store abstracts access to an underlying file/object storage.
 
+#[cfg(feature = "http")]
+pub mod http;
 pub mod local;
+pub mod memory;
 
 use std::collections::HashMap;
 use std::fmt::{self, Debug};
 use std::io::Read;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
 
@@ -158,6 +162,13 @@ pub trait ObjectStore: Sync + Send + Debug {
 
     /// Get object reader for one file
     fn file_reader(&self, file: SizedFile) -> Result<Arc<dyn ObjectReader>>;
+
+    /// The path of the local file system at which the files under `prefix`
+    /// can be written, `None` if the store is not backed by the local file
+    /// system and can only be read
+    fn local_path(&self, _prefix: &str) -> Option<PathBuf> {
+        None
+    }
 }
 
 static LOCAL_SCHEME: &str = "file";
@@ -189,9 +200,18 @@ impl fmt::Debug for ObjectStoreRegistry {
 impl ObjectStoreRegistry {
     /// Create the registry that object stores can registered into.
     /// ['LocalFileSystem'] store is registered in by default to support read local files natively.
+    /// With the `http` feature, an [`HttpObjectStore`](http::HttpObjectStore) is also
+    /// registered for the `http` and `https` schemes.
     pub fn new() -> Self {
         let mut map: HashMap<String, Arc<dyn ObjectStore>> = HashMap::new();
         map.insert(LOCAL_SCHEME.to_string(), Arc::new(LocalFileSystem));
+        #[cfg(feature = "http")]
+        for scheme in ["http", "https"] {
+            map.insert(
+                scheme.to_string(),
+                Arc::new(http::HttpObjectStore::new(scheme)),
+            );
+        }
 
         Self {
             object_stores: RwLock::new(map),
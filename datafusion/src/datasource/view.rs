@@ -0,0 +1,215 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! View data source which uses a LogicalPlan as it's input.
+
+use std::any::Any;
+use std::sync::{Arc, Mutex, Weak};
+
+use arrow::datatypes::SchemaRef;
+use async_trait::async_trait;
+
+use crate::datasource::datasource::TableType;
+use crate::datasource::TableProvider;
+use crate::error::Result;
+use crate::execution::context::{ExecutionContext, ExecutionContextState};
+use crate::logical_plan::{Expr, LogicalPlan, LogicalPlanBuilder};
+use crate::physical_plan::ExecutionPlan;
+
+/// A view, whose rows are produced by a logical plan. The SQL planner inlines
+/// the plan of the views referenced by a query in the plan of the query.
+pub struct ViewTable {
+    /// The logical plan of the view
+    logical_plan: LogicalPlan,
+    /// The schema of the view
+    schema: SchemaRef,
+    /// The SQL statement that created the view, if any
+    definition: Option<String>,
+    /// The state of the context the view is registered in, whose
+    /// configuration and planners plan the scans of the view. It is not owned
+    /// by the view as the context owns its tables.
+    context_state: Option<Weak<Mutex<ExecutionContextState>>>,
+}
+
+impl ViewTable {
+    /// Create a new view from the logical plan producing its rows and the SQL
+    /// statement that created it, if any
+    pub fn try_new(
+        logical_plan: LogicalPlan,
+        definition: Option<String>,
+    ) -> Result<Self> {
+        let schema = Arc::new(logical_plan.schema().as_ref().to_owned().into());
+        Ok(Self {
+            logical_plan,
+            schema,
+            definition,
+            context_state: None,
+        })
+    }
+
+    /// Plan the scans of the view with the state of the context the view is
+    /// registered in, instead of with a default context
+    pub fn with_context_state(
+        mut self,
+        context_state: &Arc<Mutex<ExecutionContextState>>,
+    ) -> Self {
+        self.context_state = Some(Arc::downgrade(context_state));
+        self
+    }
+
+    /// The logical plan producing the rows of the view
+    pub fn logical_plan(&self) -> &LogicalPlan {
+        &self.logical_plan
+    }
+
+    /// The SQL statement that created the view, if any
+    pub fn definition(&self) -> Option<&str> {
+        self.definition.as_deref()
+    }
+}
+
+#[async_trait]
+impl TableProvider for ViewTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::View
+    }
+
+    async fn scan(
+        &self,
+        projection: &Option<Vec<usize>>,
+        _batch_size: usize,
+        _filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        // views are usually inlined by the SQL planner, they are only scanned
+        // when referenced through the `DataFrame` API
+        let plan = match projection {
+            Some(projection) => {
+                let fields = self.logical_plan.schema().fields();
+                let expr = projection
+                    .iter()
+                    .map(|i| Expr::Column(fields[*i].qualified_column()))
+                    .collect::<Vec<_>>();
+                LogicalPlanBuilder::from(self.logical_plan.clone())
+                    .project(expr)?
+                    .build()?
+            }
+            None => self.logical_plan.clone(),
+        };
+
+        // views that aren't registered in a context, or whose context was
+        // dropped, are planned with the default configuration
+        let ctx = match self.context_state.as_ref().and_then(Weak::upgrade) {
+            Some(context_state) => ExecutionContext::from(context_state),
+            None => ExecutionContext::new(),
+        };
+        let plan = ctx.optimize(&plan)?;
+        ctx.create_physical_plan(&plan).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::execution::context::{ExecutionConfig, QueryPlanner};
+    use crate::physical_plan::collect;
+    use crate::physical_plan::planner::DefaultPhysicalPlanner;
+    use crate::physical_plan::PhysicalPlanner;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn scan_view() -> Result<()> {
+        let mut ctx = ExecutionContext::new();
+        let plan = ctx.create_logical_plan("VALUES (1, 2), (3, 4)")?;
+        let view = ViewTable::try_new(plan, None)?;
+        assert_eq!(TableType::View, view.table_type());
+
+        let exec = view.scan(&Some(vec![1]), 1024, &[], None).await?;
+        let actual = collect(exec).await?;
+        let expected = vec![
+            "+---------+",
+            "| column2 |",
+            "+---------+",
+            "| 2       |",
+            "| 4       |",
+            "+---------+",
+        ];
+        assert_batches_eq!(expected, &actual);
+
+        // the view can be queried with the DataFrame API
+        ctx.register_table("v", Arc::new(view))?;
+        let actual = ctx.table("v")?.collect().await?;
+        assert_eq!(2, actual.iter().map(|b| b.num_rows()).sum::<usize>());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn scan_view_with_context_state() -> Result<()> {
+        /// Counts the plans it creates
+        struct CountingQueryPlanner {
+            count: Arc<AtomicUsize>,
+        }
+
+        #[async_trait]
+        impl QueryPlanner for CountingQueryPlanner {
+            async fn create_physical_plan(
+                &self,
+                logical_plan: &LogicalPlan,
+                ctx_state: &ExecutionContextState,
+            ) -> Result<Arc<dyn ExecutionPlan>> {
+                self.count.fetch_add(1, Ordering::SeqCst);
+                DefaultPhysicalPlanner::default()
+                    .create_physical_plan(logical_plan, ctx_state)
+                    .await
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let mut ctx = ExecutionContext::with_config(
+            ExecutionConfig::new().with_query_planner(Arc::new(CountingQueryPlanner {
+                count: count.clone(),
+            })),
+        );
+        ctx.sql("CREATE VIEW v AS VALUES (1, 2)").await?;
+        count.store(0, Ordering::SeqCst);
+
+        // both the query and the view it scans are planned by the context
+        let actual = ctx.table("v")?.collect().await?;
+        assert_eq!(1, actual.iter().map(|b| b.num_rows()).sum::<usize>());
+        assert_eq!(2, count.load(Ordering::SeqCst));
+
+        // the view does not keep its context alive
+        let plan = ctx.create_logical_plan("VALUES (1, 2)")?;
+        let view = ViewTable::try_new(plan, None)?.with_context_state(&ctx.state);
+        drop(ctx);
+        let exec = view.scan(&None, 1024, &[], None).await?;
+        assert_eq!(1, collect(exec).await?[0].num_rows());
+        assert_eq!(2, count.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+}
@@ -21,15 +21,18 @@ use crate::{
         catalog::{CatalogList, MemoryCatalogList},
         information_schema::CatalogWithInformationSchema,
     },
-    datasource::listing::{ListingOptions, ListingTable},
+    datasource::listing::{ListingOptions, ListingTable, MetadataCache},
     datasource::{
         file_format::{
+            arrow::ArrowFormat,
             avro::AvroFormat,
             csv::CsvFormat,
+            json::JsonFormat,
             parquet::{ParquetFormat, DEFAULT_PARQUET_EXTENSION},
             FileFormat,
         },
-        MemTable,
+        view::ViewTable,
+        MemTable, TableType,
     },
     logical_plan::{PlanType, ToStringifiedPlan},
     optimizer::eliminate_limit::EliminateLimit,
@@ -48,10 +51,7 @@ use std::{
     sync::Mutex,
 };
 
-use futures::{StreamExt, TryStreamExt};
-use tokio::task::{self, JoinHandle};
-
-use arrow::{csv, datatypes::SchemaRef};
+use arrow::datatypes::SchemaRef;
 
 use crate::catalog::{
     catalog::{CatalogProvider, MemoryCatalogProvider},
@@ -62,9 +62,10 @@ use crate::datasource::object_store::{ObjectStore, ObjectStoreRegistry};
 use crate::datasource::TableProvider;
 use crate::error::{DataFusionError, Result};
 use crate::execution::dataframe_impl::DataFrameImpl;
+use crate::execution::runtime_env::RuntimeEnv;
 use crate::logical_plan::{
-    CreateExternalTable, CreateMemoryTable, DropTable, FunctionRegistry, LogicalPlan,
-    LogicalPlanBuilder, UNNAMED_TABLE,
+    CreateExternalTable, CreateMemoryTable, CreateView, DropTable, DropView,
+    FunctionRegistry, LogicalPlan, LogicalPlanBuilder, RefreshTable, UNNAMED_TABLE,
 };
 use crate::optimizer::common_subexpr_eliminate::CommonSubexprEliminate;
 use crate::optimizer::filter_push_down::FilterPushDown;
@@ -78,6 +79,8 @@ use crate::physical_optimizer::repartition::Repartition;
 
 use crate::logical_plan::plan::Explain;
 use crate::optimizer::single_distinct_to_groupby::SingleDistinctToGroupBy;
+use crate::optimizer::subquery_decorrelation::SubqueryDecorrelation;
+use crate::physical_plan::file_format::write_partitions;
 use crate::physical_plan::planner::DefaultPhysicalPlanner;
 use crate::physical_plan::udf::ScalarUDF;
 use crate::physical_plan::ExecutionPlan;
@@ -90,10 +93,9 @@ use crate::variable::{VarProvider, VarType};
 use crate::{dataframe::DataFrame, physical_plan::udaf::AggregateUDF};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use parquet::arrow::ArrowWriter;
 use parquet::file::properties::WriterProperties;
 
-use super::options::{AvroReadOptions, CsvReadOptions};
+use super::options::{ArrowReadOptions, AvroReadOptions, CsvReadOptions};
 
 /// ExecutionContext is the main interface for executing queries with DataFusion. The context
 /// provides the following functionality:
@@ -172,6 +174,8 @@ impl ExecutionContext {
                 .register_catalog(config.default_catalog.clone(), default_catalog);
         }
 
+        let runtime_env = Arc::new(RuntimeEnv::new(&config));
+
         Self {
             state: Arc::new(Mutex::new(ExecutionContextState {
                 catalog_list,
@@ -181,6 +185,7 @@ impl ExecutionContext {
                 config,
                 execution_props: ExecutionProps::new(),
                 object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+                runtime_env,
             })),
         }
     }
@@ -198,23 +203,22 @@ impl ExecutionContext {
                 ref location,
                 ref file_type,
                 ref has_header,
+                file_compression_type,
             }) => {
-                let file_format = match file_type {
-                    FileType::CSV => {
-                        Ok(Arc::new(CsvFormat::default().with_has_header(*has_header))
-                            as Arc<dyn FileFormat>)
-                    }
-                    FileType::Parquet => {
-                        Ok(Arc::new(ParquetFormat::default()) as Arc<dyn FileFormat>)
-                    }
-                    FileType::Avro => {
-                        Ok(Arc::new(AvroFormat::default()) as Arc<dyn FileFormat>)
-                    }
-                    _ => Err(DataFusionError::NotImplemented(format!(
-                        "Unsupported file type {:?}.",
-                        file_type
-                    ))),
-                }?;
+                let file_format: Arc<dyn FileFormat> = match file_type {
+                    FileType::CSV => Arc::new(
+                        CsvFormat::default()
+                            .with_has_header(*has_header)
+                            .with_file_compression_type(file_compression_type),
+                    ),
+                    FileType::NdJson => Arc::new(
+                        JsonFormat::default()
+                            .with_file_compression_type(file_compression_type),
+                    ),
+                    FileType::Parquet => Arc::new(ParquetFormat::default()),
+                    FileType::Avro => Arc::new(AvroFormat::default()),
+                    FileType::Arrow => Arc::new(ArrowFormat::default()),
+                };
 
                 let options = ListingOptions {
                     format: file_format,
@@ -270,6 +274,83 @@ impl ExecutionContext {
                 }
             }
 
+            LogicalPlan::CreateView(CreateView {
+                name,
+                input,
+                or_replace,
+                definition,
+            }) => {
+                let table_ref = TableReference::from(name.as_str());
+                let schema = self.state.lock().unwrap().schema_for_ref(table_ref)?;
+                match schema.table(table_ref.table()) {
+                    Some(table) if table.table_type() != TableType::View => {
+                        return Err(DataFusionError::Execution(format!(
+                            "{:?} is not a view",
+                            name
+                        )))
+                    }
+                    Some(_) if !or_replace => {
+                        return Err(DataFusionError::Execution(format!(
+                            "View {:?} already exists",
+                            name
+                        )))
+                    }
+                    _ => {}
+                }
+
+                // the plan of the view is optimized with the queries using it
+                let view = ViewTable::try_new(input.as_ref().clone(), definition)?
+                    .with_context_state(&self.state);
+                schema.register_table(table_ref.table().to_owned(), Arc::new(view))?;
+
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
+            LogicalPlan::DropView(DropView { name, if_exist, .. }) => {
+                let table_ref = TableReference::from(name.as_str());
+                let schema = self.state.lock().unwrap().schema_for_ref(table_ref)?;
+                match schema.table(table_ref.table()) {
+                    Some(table) if table.table_type() == TableType::View => {
+                        schema.deregister_table(table_ref.table())?;
+                    }
+                    Some(_) => {
+                        return Err(DataFusionError::Execution(format!(
+                            "{:?} is not a view",
+                            name
+                        )))
+                    }
+                    None if !if_exist => {
+                        return Err(DataFusionError::Execution(format!(
+                            "View {:?} doesn't exist.",
+                            name
+                        )))
+                    }
+                    None => {}
+                }
+
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
+            LogicalPlan::RefreshTable(RefreshTable { name, .. }) => {
+                let table_ref = TableReference::from(name.as_str());
+                let schema = self.state.lock().unwrap().schema_for_ref(table_ref)?;
+                let table = schema.table(table_ref.table()).ok_or_else(|| {
+                    DataFusionError::Execution(format!("Table {:?} doesn't exist.", name))
+                })?;
+
+                // only the listing tables cache their listing and file metadata
+                if let Some(table) = table.as_any().downcast_ref::<ListingTable>() {
+                    if let Some(metadata_cache) = table.metadata_cache() {
+                        metadata_cache.invalidate(table.table_path());
+                    }
+                }
+
+                let plan = LogicalPlanBuilder::empty(false).build()?;
+                Ok(Arc::new(DataFrameImpl::new(self.state.clone(), &plan)))
+            }
+
             plan => Ok(Arc::new(DataFrameImpl::new(
                 self.state.clone(),
                 &self.optimize(&plan)?,
@@ -362,6 +443,29 @@ impl ExecutionContext {
         )))
     }
 
+    /// Creates a DataFrame for reading an Arrow IPC data source.
+    pub async fn read_arrow(
+        &mut self,
+        uri: impl Into<String>,
+        options: ArrowReadOptions<'_>,
+    ) -> Result<Arc<dyn DataFrame>> {
+        let uri: String = uri.into();
+        let (object_store, path) = self.object_store(&uri)?;
+        let target_partitions = self.state.lock().unwrap().config.target_partitions;
+        Ok(Arc::new(DataFrameImpl::new(
+            self.state.clone(),
+            &LogicalPlanBuilder::scan_arrow(
+                object_store,
+                path,
+                options,
+                None,
+                target_partitions,
+            )
+            .await?
+            .build()?,
+        )))
+    }
+
     /// Creates an empty DataFrame.
     pub fn read_empty(&self) -> Result<Arc<dyn DataFrame>> {
         Ok(Arc::new(DataFrameImpl::new(
@@ -433,16 +537,29 @@ impl ExecutionContext {
         provided_schema: Option<SchemaRef>,
     ) -> Result<()> {
         let (object_store, path) = self.object_store(uri)?;
-        let resolved_schema = match provided_schema {
-            None => {
+        let metadata_cache = self.metadata_cache();
+        let resolved_schema = match (provided_schema, &metadata_cache) {
+            (Some(s), _) => s,
+            (None, Some(metadata_cache)) => {
+                options
+                    .infer_schema_with_cache(
+                        Arc::clone(&object_store),
+                        path,
+                        metadata_cache.as_ref(),
+                    )
+                    .await?
+            }
+            (None, None) => {
                 options
                     .infer_schema(Arc::clone(&object_store), path)
                     .await?
             }
-            Some(s) => s,
         };
-        let table =
+        let mut table =
             ListingTable::new(object_store, path.to_owned(), resolved_schema, options);
+        if let Some(metadata_cache) = metadata_cache {
+            table = table.with_metadata_cache(metadata_cache);
+        }
         self.register_table(name, Arc::new(table))?;
         Ok(())
     }
@@ -507,6 +624,22 @@ impl ExecutionContext {
         Ok(())
     }
 
+    /// Registers an Arrow IPC data source so that it can be referenced from SQL
+    /// statements executed against this context.
+    pub async fn register_arrow(
+        &mut self,
+        name: &str,
+        uri: &str,
+        options: ArrowReadOptions<'_>,
+    ) -> Result<()> {
+        let listing_options = options
+            .to_listing_options(self.state.lock().unwrap().config.target_partitions);
+
+        self.register_listing_table(name, uri, listing_options, options.schema)
+            .await?;
+        Ok(())
+    }
+
     /// Registers a named catalog using a custom `CatalogProvider` so that
     /// it can be referenced from SQL statements executed against this
     /// context.
@@ -538,6 +671,17 @@ impl ExecutionContext {
         self.state.lock().unwrap().catalog_list.catalog(name)
     }
 
+    /// Retrieves the runtime resources shared by the queries of this context
+    pub fn runtime_env(&self) -> Arc<RuntimeEnv> {
+        self.state.lock().unwrap().runtime_env.clone()
+    }
+
+    /// Retrieves the cache of the listings and of the file metadata of the
+    /// listing tables, if the context has one
+    pub fn metadata_cache(&self) -> Option<Arc<dyn MetadataCache>> {
+        self.state.lock().unwrap().config.metadata_cache.clone()
+    }
+
     /// Registers a object store with scheme using a custom `ObjectStore` so that
     /// an external file system or object storage system could be used against this context.
     ///
@@ -709,24 +853,8 @@ impl ExecutionContext {
         let fs_path = Path::new(path);
         match fs::create_dir(fs_path) {
             Ok(()) => {
-                let mut tasks = vec![];
-                for i in 0..plan.output_partitioning().partition_count() {
-                    let plan = plan.clone();
-                    let filename = format!("part-{}.csv", i);
-                    let path = fs_path.join(&filename);
-                    let file = fs::File::create(path)?;
-                    let mut writer = csv::Writer::new(file);
-                    let stream = plan.execute(i).await?;
-                    let handle: JoinHandle<Result<()>> = task::spawn(async move {
-                        stream
-                            .map(|batch| writer.write(&batch?))
-                            .try_collect()
-                            .await
-                            .map_err(DataFusionError::from)
-                    });
-                    tasks.push(handle);
-                }
-                futures::future::join_all(tasks).await;
+                let format = CsvFormat::default();
+                write_partitions(plan, fs_path, "part", ".csv", &format).await?;
                 Ok(())
             }
             Err(e) => Err(DataFusionError::Execution(format!(
@@ -748,29 +876,11 @@ impl ExecutionContext {
         let fs_path = Path::new(path);
         match fs::create_dir(fs_path) {
             Ok(()) => {
-                let mut tasks = vec![];
-                for i in 0..plan.output_partitioning().partition_count() {
-                    let plan = plan.clone();
-                    let filename = format!("part-{}.parquet", i);
-                    let path = fs_path.join(&filename);
-                    let file = fs::File::create(path)?;
-                    let mut writer = ArrowWriter::try_new(
-                        file.try_clone().unwrap(),
-                        plan.schema(),
-                        writer_properties.clone(),
-                    )?;
-                    let stream = plan.execute(i).await?;
-                    let handle: JoinHandle<Result<()>> = task::spawn(async move {
-                        stream
-                            .map(|batch| writer.write(&batch?))
-                            .try_collect()
-                            .await
-                            .map_err(DataFusionError::from)?;
-                        writer.close().map_err(DataFusionError::from).map(|_| ())
-                    });
-                    tasks.push(handle);
+                let mut format = ParquetFormat::default();
+                if let Some(writer_properties) = writer_properties {
+                    format = format.with_writer_properties(writer_properties);
                 }
-                futures::future::join_all(tasks).await;
+                write_partitions(plan, fs_path, "part", ".parquet", &format).await?;
                 Ok(())
             }
             Err(e) => Err(DataFusionError::Execution(format!(
@@ -885,8 +995,23 @@ pub struct ExecutionConfig {
     /// Should DataFusion repartition data using the partition keys to execute window functions in
     /// parallel using the provided `target_partitions` level
     pub repartition_windows: bool,
+    /// Should DataFusion execute equi-joins with a sort merge join rather than a hash join
+    pub prefer_sort_merge_join: bool,
     /// Should Datafusion parquet reader using the predicate to prune data
     parquet_pruning: bool,
+    /// Number of bytes of input a sort buffers in memory before spilling
+    /// sorted runs to disk. `None` keeps the whole input in memory
+    pub sort_spill_threshold: Option<usize>,
+    /// Maximum number of bytes the operators of running queries can
+    /// reserve at once. `None` leaves memory use unbounded
+    pub memory_limit: Option<usize>,
+    /// Maximum number of times the recursive term of a recursive query
+    /// (`WITH RECURSIVE`) is evaluated before the query fails
+    pub max_recursive_iterations: usize,
+    /// Cache of the listings and of the file metadata of the listing tables
+    /// registered with the context. `None` lists the files and reads their
+    /// metadata for each scan
+    metadata_cache: Option<Arc<dyn MetadataCache>>,
 }
 
 impl Default for ExecutionConfig {
@@ -895,6 +1020,9 @@ impl Default for ExecutionConfig {
             target_partitions: num_cpus::get(),
             batch_size: 8192,
             optimizers: vec![
+                // Subqueries are rewritten into joins before any other
+                // rule, as the other rules do not look into subqueries
+                Arc::new(SubqueryDecorrelation::new()),
                 // Simplify expressions first to maximize the chance
                 // of applying other optimizations
                 Arc::new(SimplifyExpressions::new()),
@@ -920,7 +1048,12 @@ impl Default for ExecutionConfig {
             repartition_joins: true,
             repartition_aggregations: true,
             repartition_windows: true,
+            prefer_sort_merge_join: false,
             parquet_pruning: true,
+            sort_spill_threshold: None,
+            memory_limit: None,
+            max_recursive_iterations: 1000,
+            metadata_cache: None,
         }
     }
 }
@@ -1033,11 +1166,48 @@ impl ExecutionConfig {
         self
     }
 
+    /// Enables or disables the use of sort merge joins for all equi-joins, instead of only
+    /// for inputs already sorted on the join keys or too large to build a hash table on
+    pub fn with_prefer_sort_merge_join(mut self, enabled: bool) -> Self {
+        self.prefer_sort_merge_join = enabled;
+        self
+    }
+
     /// Enables or disables the use of pruning predicate for parquet readers to skip row groups
     pub fn with_parquet_pruning(mut self, enabled: bool) -> Self {
         self.parquet_pruning = enabled;
         self
     }
+
+    /// Customize the number of bytes of input a sort buffers in memory
+    /// before spilling sorted runs to disk
+    pub fn with_sort_spill_threshold(mut self, n: usize) -> Self {
+        self.sort_spill_threshold = Some(n);
+        self
+    }
+
+    /// Customize the maximum number of bytes the operators of running
+    /// queries can reserve at once. Queries needing more memory than this
+    /// fail with [`DataFusionError::ResourcesExhausted`]
+    pub fn with_memory_limit(mut self, n: usize) -> Self {
+        self.memory_limit = Some(n);
+        self
+    }
+
+    /// Customize the maximum number of times the recursive term of a
+    /// recursive query is evaluated before the query fails
+    pub fn with_max_recursive_iterations(mut self, n: usize) -> Self {
+        self.max_recursive_iterations = n;
+        self
+    }
+
+    /// Share `metadata_cache` between the listing tables registered with the
+    /// context, so that their files are only listed and read for their
+    /// metadata once, until the table is refreshed with `REFRESH TABLE`
+    pub fn with_metadata_cache(mut self, metadata_cache: Arc<dyn MetadataCache>) -> Self {
+        self.metadata_cache = Some(metadata_cache);
+        self
+    }
 }
 
 /// Holds per-execution properties and data (such as starting timestamps, etc).
@@ -1066,6 +1236,8 @@ pub struct ExecutionContextState {
     pub execution_props: ExecutionProps,
     /// Object Store that are registered with the context
     pub object_store_registry: Arc<ObjectStoreRegistry>,
+    /// Runtime resources shared by the queries of the context
+    pub runtime_env: Arc<RuntimeEnv>,
 }
 
 impl ExecutionProps {
@@ -1094,6 +1266,7 @@ impl ExecutionContextState {
             config: ExecutionConfig::new(),
             execution_props: ExecutionProps::new(),
             object_store_registry: Arc::new(ObjectStoreRegistry::new()),
+            runtime_env: Arc::new(RuntimeEnv::default()),
         }
     }
 
@@ -1203,6 +1376,7 @@ mod tests {
     use arrow::datatypes::*;
     use arrow::record_batch::RecordBatch;
     use async_trait::async_trait;
+    use parquet::arrow::ArrowWriter;
     use std::fs::File;
     use std::sync::Weak;
     use std::thread::{self, JoinHandle};
@@ -3508,6 +3682,36 @@ mod tests {
         assert_batches_sorted_eq!(expected, &result);
     }
 
+    #[tokio::test]
+    async fn information_schema_tables_views() {
+        let mut ctx = ExecutionContext::with_config(
+            ExecutionConfig::new().with_information_schema(true),
+        );
+
+        ctx.register_table("t", test::table_with_sequence(1, 1).unwrap())
+            .unwrap();
+        plan_and_collect(&mut ctx, "CREATE VIEW v AS SELECT * FROM t")
+            .await
+            .unwrap();
+
+        let result = plan_and_collect(
+            &mut ctx,
+            "SELECT * from information_schema.tables WHERE table_schema = 'public'",
+        )
+        .await
+        .unwrap();
+
+        let expected = vec![
+            "+---------------+--------------+------------+------------+",
+            "| table_catalog | table_schema | table_name | table_type |",
+            "+---------------+--------------+------------+------------+",
+            "| datafusion    | public       | t          | BASE TABLE |",
+            "| datafusion    | public       | v          | VIEW       |",
+            "+---------------+--------------+------------+------------+",
+        ];
+        assert_batches_sorted_eq!(expected, &result);
+    }
+
     #[tokio::test]
     async fn information_schema_tables_tables_with_multiple_catalogs() {
         let mut ctx = ExecutionContext::with_config(
@@ -4268,6 +4472,7 @@ mod tests {
     trait CallReadTrait {
         async fn call_read_csv(&self) -> Arc<dyn DataFrame>;
         async fn call_read_avro(&self) -> Arc<dyn DataFrame>;
+        async fn call_read_arrow(&self) -> Arc<dyn DataFrame>;
         async fn call_read_parquet(&self) -> Arc<dyn DataFrame>;
     }
 
@@ -4287,6 +4492,13 @@ mod tests {
                 .unwrap()
         }
 
+        async fn call_read_arrow(&self) -> Arc<dyn DataFrame> {
+            let mut ctx = ExecutionContext::new();
+            ctx.read_arrow("dummy", ArrowReadOptions::default())
+                .await
+                .unwrap()
+        }
+
         async fn call_read_parquet(&self) -> Arc<dyn DataFrame> {
             let mut ctx = ExecutionContext::new();
             ctx.read_parquet("dummy").await.unwrap()
@@ -17,10 +17,13 @@
 
 //! Implementation of DataFrame API.
 
+use std::fs;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use crate::arrow::record_batch::RecordBatch;
-use crate::error::Result;
+use crate::datasource::file_format::FileFormat;
+use crate::error::{DataFusionError, Result};
 use crate::execution::context::{ExecutionContext, ExecutionContextState};
 use crate::logical_plan::{
     col, DFSchema, Expr, FunctionRegistry, JoinType, LogicalPlan, LogicalPlanBuilder,
@@ -32,6 +35,7 @@ use crate::{
 };
 
 use crate::arrow::util::pretty;
+use crate::physical_plan::file_format::write_hive_partitions;
 use crate::physical_plan::{
     execute_stream, execute_stream_partitioned, ExecutionPlan, SendableRecordBatchStream,
 };
@@ -249,6 +253,30 @@ impl DataFrame for DataFrameImpl {
             &LogicalPlanBuilder::except(left_plan, right_plan, true)?,
         )))
     }
+
+    async fn write_partitioned(
+        &self,
+        path: &str,
+        format: Arc<dyn FileFormat>,
+        partition_cols: &[&str],
+        max_rows_per_file: Option<usize>,
+    ) -> Result<()> {
+        let plan = self.create_physical_plan().await?;
+        let fs_path = Path::new(path);
+        fs::create_dir(fs_path).map_err(|e| {
+            DataFusionError::Execution(format!(
+                "Could not create directory {}: {:?}",
+                path, e
+            ))
+        })?;
+        let partition_cols = partition_cols
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        write_hive_partitions(plan, fs_path, &partition_cols, format, max_rows_per_file)
+            .await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -256,13 +284,17 @@ mod tests {
     use std::vec;
 
     use super::*;
+    use crate::datasource::file_format::parquet::ParquetFormat;
+    use crate::datasource::listing::ListingOptions;
+    use crate::datasource::MemTable;
     use crate::execution::options::CsvReadOptions;
     use crate::physical_plan::functions::ScalarFunctionImplementation;
     use crate::physical_plan::functions::Volatility;
     use crate::physical_plan::{window_functions, ColumnarValue};
     use crate::{assert_batches_sorted_eq, execution::context::ExecutionContext};
     use crate::{logical_plan::*, test_util};
-    use arrow::datatypes::DataType;
+    use arrow::array::{Int32Array, StringArray};
+    use arrow::datatypes::{DataType, Field, Schema};
 
     #[tokio::test]
     async fn select_columns() -> Result<()> {
@@ -321,6 +353,38 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn select_with_ntile_and_percent_rank() -> Result<()> {
+        let t = test_table().await?;
+        let window_expr = |fun, args| Expr::WindowFunction {
+            fun: window_functions::WindowFunction::BuiltInWindowFunction(fun),
+            args,
+            partition_by: vec![col("aggregate_test_100.c2")],
+            order_by: vec![col("aggregate_test_100.c3").sort(true, false)],
+            window_frame: None,
+        };
+        let ntile = window_expr(
+            window_functions::BuiltInWindowFunction::Ntile,
+            vec![lit(4_i64)],
+        );
+        let percent_rank =
+            window_expr(window_functions::BuiltInWindowFunction::PercentRank, vec![]);
+        let t2 = t.select(vec![col("c1"), ntile, percent_rank])?;
+        let plan = t2.to_logical_plan();
+
+        let sql_plan = create_plan(
+            "select c1, ntile(4) over (partition by c2 order by c3), \
+             percent_rank() over (partition by c2 order by c3) from aggregate_test_100",
+        )
+        .await?;
+
+        assert_same_plan(&plan, &sql_plan);
+
+        let batches = t2.collect().await?;
+        assert_eq!(100, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn aggregate() -> Result<()> {
         // build plan using DataFrame API
@@ -484,6 +548,131 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn write_partitioned() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Utf8, false),
+            Field::new("v", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec!["x", "y", "x", "x", "y", "x"])),
+                Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5, 6])),
+            ],
+        )?;
+        let mut ctx = ExecutionContext::new();
+        let table = MemTable::try_new(schema, vec![vec![batch]])?;
+        ctx.register_table("src", Arc::new(table))?;
+
+        let tmp_dir = tempfile::tempdir()?;
+        let out_dir = tmp_dir.path().join("out");
+        let path = out_dir.to_str().unwrap();
+        let format = Arc::new(ParquetFormat::default());
+        ctx.table("src")?
+            .write_partitioned(path, format.clone(), &["k"], Some(2))
+            .await?;
+
+        // the rows of each value are split in files of at most 2 rows
+        let count_files =
+            |dir: &str| std::fs::read_dir(out_dir.join(dir)).unwrap().count();
+        assert_eq!(2, count_files("k=x"));
+        assert_eq!(1, count_files("k=y"));
+
+        // the directory is read back with the same partition columns
+        let options = ListingOptions {
+            file_extension: ".parquet".to_owned(),
+            table_partition_cols: vec!["k".to_owned()],
+            ..ListingOptions::new(format)
+        };
+        ctx.register_listing_table("t", path, options, None).await?;
+        let results = ctx.sql("SELECT k, v FROM t").await?.collect().await?;
+        let expected = vec![
+            "+---+---+",
+            "| k | v |",
+            "+---+---+",
+            "| x | 1 |",
+            "| x | 3 |",
+            "| x | 4 |",
+            "| x | 6 |",
+            "| y | 2 |",
+            "| y | 5 |",
+            "+---+---+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        // the directory must not exist yet
+        let err = ctx
+            .table("src")?
+            .write_partitioned(path, Arc::new(ParquetFormat::default()), &["k"], None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Could not create directory"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_partitioned_null_values() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("k", DataType::Utf8, true),
+            Field::new("v", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(vec![Some("x"), None, None])),
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+            ],
+        )?;
+        let mut ctx = ExecutionContext::new();
+        let table = MemTable::try_new(schema, vec![vec![batch]])?;
+        ctx.register_table("src", Arc::new(table))?;
+
+        let tmp_dir = tempfile::tempdir()?;
+        let out_dir = tmp_dir.path().join("out");
+        let path = out_dir.to_str().unwrap();
+        let format = Arc::new(ParquetFormat::default());
+        ctx.table("src")?
+            .write_partitioned(path, format.clone(), &["k"], None)
+            .await?;
+        assert!(out_dir.join("k=__HIVE_DEFAULT_PARTITION__").is_dir());
+
+        // the null values are read back from the default partition
+        let options = ListingOptions {
+            file_extension: ".parquet".to_owned(),
+            table_partition_cols: vec!["k".to_owned()],
+            ..ListingOptions::new(format)
+        };
+        ctx.register_listing_table("t", path, options, None).await?;
+        let results = ctx
+            .sql("SELECT k, v, k IS NULL AS n FROM t")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec![
+            "+---+---+-------+",
+            "| k | v | n     |",
+            "+---+---+-------+",
+            "|   | 2 | true  |",
+            "|   | 3 | true  |",
+            "| x | 1 | false |",
+            "+---+---+-------+",
+        ];
+        assert_batches_sorted_eq!(expected, &results);
+
+        // the partitions are pruned on their null values
+        let results = ctx
+            .sql("SELECT v FROM t WHERE k IS NULL")
+            .await?
+            .collect()
+            .await?;
+        let expected = vec!["+---+", "| v |", "+---+", "| 2 |", "| 3 |", "+---+"];
+        assert_batches_sorted_eq!(expected, &results);
+
+        Ok(())
+    }
+
     /// Compare the formatted string representation of two plans for equality
     fn assert_same_plan(plan1: &LogicalPlan, plan2: &LogicalPlan) {
         assert_eq!(format!("{:?}", plan1), format!("{:?}", plan2));
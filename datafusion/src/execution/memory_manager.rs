@@ -0,0 +1,334 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Manages the memory reserved by the operators of running queries
+
+use crate::error::{DataFusionError, Result};
+use hashbrown::HashMap;
+use log::debug;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Tracks and bounds the memory used by the operators of the queries run
+/// by an [`ExecutionContext`](crate::execution::context::ExecutionContext).
+///
+/// Operators that buffer data register themselves as consumers with
+/// [`register_consumer`](Self::register_consumer) and grow or shrink the
+/// returned [`MemoryReservation`] as their buffers change size. A
+/// reservation that would take the total above the pool size fails with
+/// [`DataFusionError::ResourcesExhausted`].
+///
+/// Consumers registered as able to spill are additionally asked to spill
+/// to disk (see [`MemoryReservation::take_spill_request`]) whenever another
+/// consumer fails to reserve memory, so that the memory they hold can be
+/// given to others.
+///
+/// ```
+/// use std::sync::Arc;
+/// use datafusion::execution::memory_manager::MemoryManager;
+///
+/// let memory_manager = Arc::new(MemoryManager::new(100));
+///
+/// let mut reservation = memory_manager.register_consumer("my operator", false);
+/// reservation.try_grow(60).unwrap();
+/// assert_eq!(memory_manager.reserved(), 60);
+///
+/// // the pool only has 40 bytes left
+/// assert!(reservation.try_grow(50).is_err());
+///
+/// // memory is returned to the pool when the reservation is dropped
+/// drop(reservation);
+/// assert_eq!(memory_manager.reserved(), 0);
+/// ```
+#[derive(Debug)]
+pub struct MemoryManager {
+    /// Maximum number of bytes that can be reserved at once
+    pool_size: usize,
+    /// Reservations of the registered consumers
+    state: Mutex<MemoryManagerState>,
+    /// Identifier of the next registered consumer
+    next_consumer_id: AtomicUsize,
+}
+
+#[derive(Debug, Default)]
+struct MemoryManagerState {
+    /// Total number of bytes reserved by all consumers
+    reserved: usize,
+    /// Registered consumers, by id
+    consumers: HashMap<usize, ConsumerState>,
+}
+
+#[derive(Debug)]
+struct ConsumerState {
+    /// Name of the consumer, used in error messages
+    name: String,
+    /// Number of bytes reserved by the consumer
+    reserved: usize,
+    /// Set when the consumer should spill, `None` if it can not spill
+    spill_requested: Option<Arc<AtomicBool>>,
+}
+
+impl MemoryManager {
+    /// Create a new memory manager allowing at most `pool_size` bytes to
+    /// be reserved at once
+    pub fn new(pool_size: usize) -> Self {
+        Self {
+            pool_size,
+            state: Mutex::new(MemoryManagerState::default()),
+            next_consumer_id: AtomicUsize::new(0),
+        }
+    }
+
+    /// Create a new memory manager that tracks reservations without
+    /// limiting them
+    pub fn unbounded() -> Arc<Self> {
+        Arc::new(Self::new(usize::MAX))
+    }
+
+    /// Maximum number of bytes that can be reserved at once
+    pub fn pool_size(&self) -> usize {
+        self.pool_size
+    }
+
+    /// Number of bytes currently reserved by all consumers
+    pub fn reserved(&self) -> usize {
+        self.state.lock().expect("not poisoned").reserved
+    }
+
+    /// Number of bytes that can still be reserved
+    pub fn available(&self) -> usize {
+        self.pool_size - self.reserved()
+    }
+
+    /// Registers a new consumer with an empty reservation. If `can_spill`
+    /// is true the consumer will be asked to spill when memory runs out.
+    pub fn register_consumer(
+        self: &Arc<Self>,
+        name: impl Into<String>,
+        can_spill: bool,
+    ) -> MemoryReservation {
+        let id = self.next_consumer_id.fetch_add(1, Ordering::Relaxed);
+        let spill_requested = can_spill.then(|| Arc::new(AtomicBool::new(false)));
+
+        self.state.lock().expect("not poisoned").consumers.insert(
+            id,
+            ConsumerState {
+                name: name.into(),
+                reserved: 0,
+                spill_requested: spill_requested.clone(),
+            },
+        );
+
+        MemoryReservation {
+            id,
+            size: 0,
+            spill_requested,
+            manager: Arc::clone(self),
+        }
+    }
+
+    fn try_grow(&self, id: usize, bytes: usize) -> Result<()> {
+        let mut state = self.state.lock().expect("not poisoned");
+
+        let available = self.pool_size - state.reserved;
+        if bytes > available {
+            // ask everyone else holding memory to give it back
+            let mut requested = 0;
+            for (_, consumer) in state.consumers.iter().filter(|(c, _)| **c != id) {
+                if let Some(spill_requested) = &consumer.spill_requested {
+                    if consumer.reserved > 0 {
+                        spill_requested.store(true, Ordering::Relaxed);
+                        requested += 1;
+                    }
+                }
+            }
+
+            let name = &state.consumers[&id].name;
+            debug!(
+                "{} failed to reserve {} bytes, requested {} consumers to spill",
+                name, bytes, requested
+            );
+
+            return Err(DataFusionError::ResourcesExhausted(format!(
+                "Failed to reserve {} bytes for {}: {} of the {} bytes of the memory pool \
+                 are already reserved",
+                bytes, name, state.reserved, self.pool_size
+            )));
+        }
+
+        state.reserved += bytes;
+        if let Some(consumer) = state.consumers.get_mut(&id) {
+            consumer.reserved += bytes;
+        }
+        Ok(())
+    }
+
+    fn shrink(&self, id: usize, bytes: usize) {
+        let mut state = self.state.lock().expect("not poisoned");
+        state.reserved -= bytes;
+        if let Some(consumer) = state.consumers.get_mut(&id) {
+            consumer.reserved -= bytes;
+        }
+    }
+
+    fn unregister(&self, id: usize) {
+        let mut state = self.state.lock().expect("not poisoned");
+        if let Some(consumer) = state.consumers.remove(&id) {
+            state.reserved -= consumer.reserved;
+        }
+    }
+}
+
+/// The memory reserved by a consumer registered with a [`MemoryManager`].
+///
+/// All the memory is returned to the pool when the reservation is dropped.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    /// Identifier of the consumer in the memory manager
+    id: usize,
+    /// Number of bytes reserved
+    size: usize,
+    /// Set by the memory manager when this consumer should spill
+    spill_requested: Option<Arc<AtomicBool>>,
+    /// The memory manager the memory is reserved from
+    manager: Arc<MemoryManager>,
+}
+
+impl MemoryReservation {
+    /// Number of bytes reserved
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Reserves `bytes` additional bytes, or returns
+    /// [`DataFusionError::ResourcesExhausted`] if the memory pool does not
+    /// have enough memory left
+    pub fn try_grow(&mut self, bytes: usize) -> Result<()> {
+        self.manager.try_grow(self.id, bytes)?;
+        self.size += bytes;
+        Ok(())
+    }
+
+    /// Returns `bytes` bytes of this reservation to the memory pool
+    pub fn shrink(&mut self, bytes: usize) {
+        let bytes = bytes.min(self.size);
+        self.manager.shrink(self.id, bytes);
+        self.size -= bytes;
+    }
+
+    /// Grows or shrinks this reservation to `size` bytes
+    pub fn try_resize(&mut self, size: usize) -> Result<()> {
+        if size > self.size {
+            self.try_grow(size - self.size)
+        } else {
+            self.shrink(self.size - size);
+            Ok(())
+        }
+    }
+
+    /// Returns all of this reservation to the memory pool, returning the
+    /// number of bytes freed
+    pub fn free(&mut self) -> usize {
+        let size = self.size;
+        self.shrink(size);
+        size
+    }
+
+    /// Returns true if the memory manager asked this consumer to spill
+    /// since the last call, clearing the request
+    pub fn take_spill_request(&self) -> bool {
+        self.spill_requested
+            .as_ref()
+            .map(|spill_requested| spill_requested.swap(false, Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.manager.unregister(self.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservations() -> Result<()> {
+        let memory_manager = Arc::new(MemoryManager::new(100));
+
+        let mut a = memory_manager.register_consumer("a", false);
+        let mut b = memory_manager.register_consumer("b", false);
+
+        a.try_grow(40)?;
+        b.try_grow(40)?;
+        assert_eq!(memory_manager.reserved(), 80);
+        assert_eq!(memory_manager.available(), 20);
+
+        let err = b.try_grow(30).unwrap_err();
+        assert!(
+            matches!(err, DataFusionError::ResourcesExhausted(_)),
+            "{:?}",
+            err
+        );
+        assert_eq!(b.size(), 40);
+
+        a.shrink(30);
+        b.try_grow(30)?;
+        assert_eq!(memory_manager.reserved(), 80);
+
+        b.try_resize(10)?;
+        assert_eq!(b.size(), 10);
+        assert_eq!(memory_manager.reserved(), 20);
+
+        assert_eq!(a.free(), 10);
+        assert_eq!(memory_manager.reserved(), 10);
+
+        drop(b);
+        assert_eq!(memory_manager.reserved(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spill_requests() -> Result<()> {
+        let memory_manager = Arc::new(MemoryManager::new(100));
+
+        let mut spillable = memory_manager.register_consumer("spillable", true);
+        let mut empty = memory_manager.register_consumer("empty", true);
+        let mut other = memory_manager.register_consumer("other", false);
+
+        spillable.try_grow(90)?;
+        assert!(!spillable.take_spill_request());
+
+        assert!(other.try_grow(20).is_err());
+
+        // only the consumer holding memory is asked to spill, once
+        assert!(spillable.take_spill_request());
+        assert!(!spillable.take_spill_request());
+        assert!(!empty.take_spill_request());
+        assert!(!other.take_spill_request());
+
+        spillable.free();
+        other.try_grow(20)?;
+        empty.try_grow(80)?;
+        assert_eq!(memory_manager.available(), 0);
+
+        Ok(())
+    }
+}
@@ -19,4 +19,6 @@
 
 pub mod context;
 pub mod dataframe_impl;
+pub mod memory_manager;
 pub mod options;
+pub mod runtime_env;
@@ -22,7 +22,13 @@ use std::sync::Arc;
 use arrow::datatypes::{Schema, SchemaRef};
 
 use crate::datasource::{
-    file_format::{avro::AvroFormat, csv::CsvFormat},
+    file_format::{
+        arrow::{ArrowFormat, DEFAULT_ARROW_EXTENSION},
+        avro::AvroFormat,
+        compression::FileCompressionType,
+        csv::CsvFormat,
+        json::JsonFormat,
+    },
     listing::ListingOptions,
 };
 
@@ -44,6 +50,9 @@ pub struct CsvReadOptions<'a> {
     /// File extension; only files with this extension are selected for data input.
     /// Defaults to ".csv".
     pub file_extension: &'a str,
+    /// The compression of the files. If None, it is inferred from `file_extension`,
+    /// so that files with the extension ".csv.gz" are decompressed with gzip.
+    pub file_compression_type: Option<FileCompressionType>,
 }
 
 impl<'a> CsvReadOptions<'a> {
@@ -55,6 +64,7 @@ impl<'a> CsvReadOptions<'a> {
             schema_infer_max_records: 1000,
             delimiter: b',',
             file_extension: ".csv",
+            file_compression_type: None,
         }
     }
 
@@ -76,6 +86,15 @@ impl<'a> CsvReadOptions<'a> {
         self
     }
 
+    /// Specify the compression of the CSV files
+    pub fn file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = Some(file_compression_type);
+        self
+    }
+
     /// Configure delimiter setting with Option, None value will be ignored
     pub fn delimiter_option(mut self, delimiter: Option<u8>) -> Self {
         if let Some(d) = delimiter {
@@ -101,7 +120,10 @@ impl<'a> CsvReadOptions<'a> {
         let file_format = CsvFormat::default()
             .with_has_header(self.has_header)
             .with_delimiter(self.delimiter)
-            .with_schema_infer_max_rec(Some(self.schema_infer_max_records));
+            .with_schema_infer_max_rec(Some(self.schema_infer_max_records))
+            .with_file_compression_type(self.file_compression_type.unwrap_or_else(
+                || FileCompressionType::from_extension(self.file_extension),
+            ));
 
         ListingOptions {
             format: Arc::new(file_format),
@@ -148,6 +170,41 @@ impl<'a> AvroReadOptions<'a> {
     }
 }
 
+/// Arrow IPC read options
+#[derive(Clone)]
+pub struct ArrowReadOptions<'a> {
+    /// The data source schema.
+    pub schema: Option<SchemaRef>,
+
+    /// File extension; only files with this extension are selected for data input.
+    /// Defaults to ".arrow".
+    pub file_extension: &'a str,
+}
+
+impl<'a> Default for ArrowReadOptions<'a> {
+    fn default() -> Self {
+        Self {
+            schema: None,
+            file_extension: DEFAULT_ARROW_EXTENSION,
+        }
+    }
+}
+
+impl<'a> ArrowReadOptions<'a> {
+    /// Helper to convert these user facing options to `ListingTable` options
+    pub fn to_listing_options(&self, target_partitions: usize) -> ListingOptions {
+        let file_format = ArrowFormat::default();
+
+        ListingOptions {
+            format: Arc::new(file_format),
+            collect_stat: false,
+            file_extension: self.file_extension.to_owned(),
+            target_partitions,
+            table_partition_cols: vec![],
+        }
+    }
+}
+
 /// Line-delimited JSON read options
 #[derive(Clone)]
 pub struct NdJsonReadOptions<'a> {
@@ -160,6 +217,10 @@ pub struct NdJsonReadOptions<'a> {
     /// File extension; only files with this extension are selected for data input.
     /// Defaults to ".json".
     pub file_extension: &'a str,
+
+    /// The compression of the files. If None, it is inferred from `file_extension`,
+    /// so that files with the extension ".json.gz" are decompressed with gzip.
+    pub file_compression_type: Option<FileCompressionType>,
 }
 
 impl<'a> Default for NdJsonReadOptions<'a> {
@@ -168,6 +229,41 @@ impl<'a> Default for NdJsonReadOptions<'a> {
             schema: None,
             schema_infer_max_records: 1000,
             file_extension: ".json",
+            file_compression_type: None,
+        }
+    }
+}
+
+impl<'a> NdJsonReadOptions<'a> {
+    /// Specify the file extension for JSON file selection
+    pub fn file_extension(mut self, file_extension: &'a str) -> Self {
+        self.file_extension = file_extension;
+        self
+    }
+
+    /// Specify the compression of the JSON files
+    pub fn file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = Some(file_compression_type);
+        self
+    }
+
+    /// Helper to convert these user facing options to `ListingTable` options
+    pub fn to_listing_options(&self, target_partitions: usize) -> ListingOptions {
+        let file_format = JsonFormat::default()
+            .with_schema_infer_max_rec(Some(self.schema_infer_max_records))
+            .with_file_compression_type(self.file_compression_type.unwrap_or_else(
+                || FileCompressionType::from_extension(self.file_extension),
+            ));
+
+        ListingOptions {
+            format: Arc::new(file_format),
+            collect_stat: false,
+            file_extension: self.file_extension.to_owned(),
+            target_partitions,
+            table_partition_cols: vec![],
         }
     }
 }
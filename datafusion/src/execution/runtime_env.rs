@@ -0,0 +1,48 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Runtime resources shared by the queries of an execution context
+
+use crate::execution::context::ExecutionConfig;
+use crate::execution::memory_manager::MemoryManager;
+use std::sync::Arc;
+
+/// Resources shared by all the queries executed by an
+/// [`ExecutionContext`](crate::execution::context::ExecutionContext)
+#[derive(Debug, Clone)]
+pub struct RuntimeEnv {
+    /// Tracks and bounds the memory used by running queries
+    pub memory_manager: Arc<MemoryManager>,
+}
+
+impl RuntimeEnv {
+    /// Create the runtime environment described by `config`
+    pub fn new(config: &ExecutionConfig) -> Self {
+        let memory_manager = match config.memory_limit {
+            Some(memory_limit) => Arc::new(MemoryManager::new(memory_limit)),
+            None => MemoryManager::unbounded(),
+        };
+
+        Self { memory_manager }
+    }
+}
+
+impl Default for RuntimeEnv {
+    fn default() -> Self {
+        Self::new(&ExecutionConfig::default())
+    }
+}
@@ -26,8 +26,8 @@ use crate::datasource::{
 };
 use crate::error::{DataFusionError, Result};
 use crate::logical_plan::plan::{
-    Aggregate, Analyze, EmptyRelation, Explain, Filter, Join, Projection, Sort,
-    TableScan, ToStringifiedPlan, Union, Window,
+    Aggregate, Analyze, EmptyRelation, Explain, Filter, Join, Projection, RecursiveQuery,
+    Sort, TableScan, ToStringifiedPlan, Union, Window,
 };
 use crate::optimizer::utils;
 use crate::prelude::*;
@@ -46,9 +46,11 @@ use std::{
 use super::dfschema::ToDFSchema;
 use super::{exprlist_to_fields, Expr, JoinConstraint, JoinType, LogicalPlan, PlanType};
 use crate::logical_plan::{
-    columnize_expr, normalize_col, normalize_cols, rewrite_sort_cols_by_aggs, Column,
-    CrossJoin, DFField, DFSchema, DFSchemaRef, Limit, Partitioning, Repartition, Values,
+    columnize_expr, expand_grouping_sets, normalize_col, normalize_cols,
+    rewrite_sort_cols_by_aggs, Column, CrossJoin, DFField, DFSchema, DFSchemaRef, Limit,
+    Partitioning, Repartition, Values, GROUPING_ID_COLUMN,
 };
+use crate::physical_plan::aggregates::AggregateFunction;
 use crate::sql::utils::group_window_expr_by_sort_keys;
 
 /// Default table name for unnamed table
@@ -344,6 +346,53 @@ impl LogicalPlanBuilder {
         Self::scan(table_name, Arc::new(provider), projection)
     }
 
+    /// Scan an Arrow IPC data source
+    pub async fn scan_arrow(
+        object_store: Arc<dyn ObjectStore>,
+        path: impl Into<String>,
+        options: ArrowReadOptions<'_>,
+        projection: Option<Vec<usize>>,
+        target_partitions: usize,
+    ) -> Result<Self> {
+        let path = path.into();
+        Self::scan_arrow_with_name(
+            object_store,
+            path.clone(),
+            options,
+            projection,
+            path,
+            target_partitions,
+        )
+        .await
+    }
+
+    /// Scan an Arrow IPC data source and register it with a given table name
+    pub async fn scan_arrow_with_name(
+        object_store: Arc<dyn ObjectStore>,
+        path: impl Into<String>,
+        options: ArrowReadOptions<'_>,
+        projection: Option<Vec<usize>>,
+        table_name: impl Into<String>,
+        target_partitions: usize,
+    ) -> Result<Self> {
+        let listing_options = options.to_listing_options(target_partitions);
+
+        let path: String = path.into();
+
+        let resolved_schema = match options.schema {
+            Some(s) => s,
+            None => {
+                listing_options
+                    .infer_schema(Arc::clone(&object_store), &path)
+                    .await?
+            }
+        };
+        let provider =
+            ListingTable::new(object_store, path, resolved_schema, listing_options);
+
+        Self::scan(table_name, Arc::new(provider), projection)
+    }
+
     /// Scan an empty data source, mainly used in tests
     pub fn scan_empty(
         name: Option<&str>,
@@ -576,6 +625,58 @@ impl LogicalPlanBuilder {
         Ok(Self::from(union_with_alias(self.plan.clone(), plan, None)?))
     }
 
+    /// Apply a recursive query named `name`, starting from the rows of this
+    /// plan and evaluating `recursive_term` on the rows it produced last
+    /// until it produces no rows. The columns of `recursive_term` are cast to
+    /// the types of the columns of this plan.
+    pub fn to_recursive_query(
+        &self,
+        name: &str,
+        recursive_term: LogicalPlan,
+    ) -> Result<Self> {
+        let static_fields = self.plan.schema().fields();
+        let recursive_fields = recursive_term.schema().fields();
+        if static_fields.len() != recursive_fields.len() {
+            return Err(DataFusionError::Plan(format!(
+                "The static and recursive terms of recursive query {} produce {} and \
+                {} columns, they must produce the same number of columns",
+                name,
+                static_fields.len(),
+                recursive_fields.len()
+            )));
+        }
+
+        // the rows produced by the recursive term are scanned by its next
+        // evaluation, as if they were produced by the static term
+        let recursive_term = if static_fields.iter().zip(recursive_fields).all(
+            |(static_field, recursive_field)| {
+                static_field.name() == recursive_field.name()
+                    && static_field.data_type() == recursive_field.data_type()
+            },
+        ) {
+            recursive_term
+        } else {
+            let expr = static_fields
+                .iter()
+                .zip(recursive_fields)
+                .map(|(static_field, recursive_field)| {
+                    Ok(Expr::Column(recursive_field.qualified_column())
+                        .cast_to(static_field.data_type(), recursive_term.schema())?
+                        .alias(static_field.name()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Self::from(recursive_term).project(expr)?.build()?
+        };
+
+        let schema = (**self.plan.schema()).clone().replace_qualifier(name);
+        Ok(Self::from(LogicalPlan::RecursiveQuery(RecursiveQuery {
+            name: name.to_string(),
+            static_term: Arc::new(self.plan.clone()),
+            recursive_term: Arc::new(recursive_term),
+            schema: Arc::new(schema),
+        })))
+    }
+
     /// Apply deduplication: Only distinct (different) values are returned)
     pub fn distinct(&self) -> Result<Self> {
         let projection_expr = expand_wildcard(self.plan.schema(), &self.plan)?;
@@ -773,16 +874,73 @@ impl LogicalPlanBuilder {
     ) -> Result<Self> {
         let group_expr = normalize_cols(group_expr, &self.plan)?;
         let aggr_expr = normalize_cols(aggr_expr, &self.plan)?;
-        let all_expr = group_expr.iter().chain(aggr_expr.iter());
-        validate_unique_names("Aggregations", all_expr.clone(), self.plan.schema())?;
-        let aggr_schema =
-            DFSchema::new(exprlist_to_fields(all_expr, self.plan.schema())?)?;
-        Ok(Self::from(LogicalPlan::Aggregate(Aggregate {
+        let (grouping_expr, grouping_sets) = expand_grouping_sets(&group_expr)?;
+
+        // GROUPING(...) is computed from the grouping id after the aggregation
+        let aggregates = aggr_expr
+            .iter()
+            .filter(|expr| !is_grouping(expr))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let all_expr = grouping_expr.iter().chain(aggregates.iter());
+        validate_unique_names("Aggregations", all_expr, self.plan.schema())?;
+        let mut fields = exprlist_to_fields(&grouping_expr, self.plan.schema())?;
+        if !grouping_sets.is_empty() {
+            // the grouping expressions are null when not in the grouping set
+            fields = fields
+                .into_iter()
+                .map(|field| {
+                    DFField::new(
+                        field.qualifier().map(|qualifier| qualifier.as_str()),
+                        field.name(),
+                        field.data_type().clone(),
+                        true,
+                    )
+                })
+                .collect();
+            fields.push(DFField::new(
+                None,
+                GROUPING_ID_COLUMN,
+                DataType::UInt32,
+                false,
+            ));
+        }
+        fields.extend(exprlist_to_fields(&aggregates, self.plan.schema())?);
+        let aggregate = LogicalPlan::Aggregate(Aggregate {
             input: Arc::new(self.plan.clone()),
             group_expr,
-            aggr_expr,
-            schema: DFSchemaRef::new(aggr_schema),
-        })))
+            aggr_expr: aggregates,
+            schema: DFSchemaRef::new(DFSchema::new(fields)?),
+        });
+        if grouping_sets.is_empty() && !aggr_expr.iter().any(is_grouping) {
+            return Ok(Self::from(aggregate));
+        }
+
+        // project away the grouping id and compute GROUPING(...) from it
+        let grouping_columns = aggregate.schema().fields()[..grouping_expr.len()]
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()))
+            .collect::<Vec<_>>();
+        let aggregate_columns =
+            aggr_expr
+                .iter()
+                .map(|expr| {
+                    let name = expr.name(self.plan.schema())?;
+                    if is_grouping(expr) {
+                        Ok(grouping_from_id(
+                            expr,
+                            &grouping_expr,
+                            !grouping_sets.is_empty(),
+                        )?
+                        .alias(&name))
+                    } else {
+                        Ok(Expr::Column(Column::from_name(name)))
+                    }
+                })
+                .collect::<Result<Vec<_>>>()?;
+        Self::from(aggregate)
+            .project(grouping_columns.into_iter().chain(aggregate_columns))
     }
 
     /// Create an expression to represent the explanation of the plan
@@ -903,6 +1061,77 @@ pub fn build_join_schema(
 }
 
 /// Errors if one or more expressions have equal names.
+/// Whether `expr` is a call of the `GROUPING` aggregate function
+fn is_grouping(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::AggregateFunction {
+            fun: AggregateFunction::Grouping,
+            ..
+        }
+    )
+}
+
+/// Returns the expression computing the `GROUPING` function call `grouping`
+/// from the [`GROUPING_ID_COLUMN`] of an aggregation grouping by
+/// `grouping_expr`, whose bits are in the order of `grouping_expr` while the
+/// bits of the result are in the order of the arguments of the call.
+fn grouping_from_id(
+    grouping: &Expr,
+    grouping_expr: &[Expr],
+    has_grouping_sets: bool,
+) -> Result<Expr> {
+    let args = match grouping {
+        Expr::AggregateFunction { args, .. } => args,
+        _ => {
+            return Err(DataFusionError::Internal(format!(
+                "Expected a GROUPING function call, got {:?}",
+                grouping
+            )))
+        }
+    };
+    if args.len() > 32 {
+        return Err(DataFusionError::Plan(format!(
+            "GROUPING supports at most 32 arguments, got {}",
+            args.len()
+        )));
+    }
+
+    let mut result = None;
+    for (i, arg) in args.iter().enumerate() {
+        let index = grouping_expr
+            .iter()
+            .position(|expr| expr == arg)
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!(
+                    "Arguments to GROUPING must be grouping expressions, got {:?}",
+                    arg
+                ))
+            })?;
+        // without grouping sets, every row groups by all of the expressions
+        if !has_grouping_sets {
+            continue;
+        }
+
+        // move the bit of `arg` in the grouping id to its bit in the result
+        let mut bit = Expr::Column(Column::from_name(GROUPING_ID_COLUMN));
+        let id_shift = grouping_expr.len() - 1 - index;
+        if id_shift > 0 {
+            bit = bit / lit(1_u32 << id_shift);
+        }
+        bit = bit % lit(2_u32);
+        let result_shift = args.len() - 1 - i;
+        if result_shift > 0 {
+            bit = bit * lit(1_u32 << result_shift);
+        }
+        result = Some(match result {
+            Some(result) => result + bit,
+            None => bit,
+        });
+    }
+    Ok(result.unwrap_or_else(|| lit(0_u32)))
+}
+
 fn validate_unique_names<'a>(
     node_name: &str,
     expressions: impl IntoIterator<Item = &'a Expr>,
@@ -22,7 +22,8 @@ pub use super::Operator;
 use crate::error::{DataFusionError, Result};
 use crate::field_util::get_indexed_field;
 use crate::logical_plan::{
-    plan::Aggregate, window_frames, DFField, DFSchema, LogicalPlan,
+    plan::{Aggregate, Subquery},
+    window_frames, DFField, DFSchema, LogicalPlan,
 };
 use crate::physical_plan::functions::Volatility;
 use crate::physical_plan::{
@@ -338,6 +339,12 @@ pub enum Expr {
         args: Vec<Expr>,
         /// Whether this is a DISTINCT aggregation or not
         distinct: bool,
+        /// Optional `FILTER (WHERE ...)` predicate, only the rows for which it
+        /// is true are fed to the function
+        filter: Option<Box<Expr>>,
+        /// List of sort expressions the rows are fed to the function in, e.g.
+        /// `ARRAY_AGG(x ORDER BY y)`
+        order_by: Vec<Expr>,
     },
     /// Represents the call of a window function with arguments.
     WindowFunction {
@@ -368,8 +375,138 @@ pub enum Expr {
         /// Whether the expression is negated
         negated: bool,
     },
+    /// Whether the subquery returns any rows (`EXISTS (SELECT ...)`).
+    Exists {
+        /// The subquery
+        subquery: Subquery,
+        /// Whether the expression is negated
+        negated: bool,
+    },
+    /// Returns whether the subquery, which must produce a single column,
+    /// contains the expr value.
+    InSubquery {
+        /// The expression to compare
+        expr: Box<Expr>,
+        /// The subquery producing the values to compare against
+        subquery: Subquery,
+        /// Whether the expression is negated
+        negated: bool,
+    },
+    /// The single value produced by a subquery returning one column and at
+    /// most one row.
+    ScalarSubquery(Subquery),
     /// Represents a reference to all fields in a schema.
     Wildcard,
+    /// A set of grouping sets, only valid as an expression of a `GROUP BY`.
+    GroupingSet(GroupingSet),
+}
+
+/// The grouping sets of a `GROUP BY ROLLUP (...)`, `CUBE (...)` or
+/// `GROUPING SETS (...)` clause.
+#[derive(Clone, PartialEq, PartialOrd, Debug)]
+pub enum GroupingSet {
+    /// `ROLLUP (a, b)` groups by `(a, b)`, `(a)` and `()`
+    Rollup(Vec<Expr>),
+    /// `CUBE (a, b)` groups by every subset of `(a, b)`
+    Cube(Vec<Expr>),
+    /// `GROUPING SETS ((a, b), (a), ())` groups by each of the listed sets
+    GroupingSets(Vec<Vec<Expr>>),
+}
+
+/// The maximum number of expressions in a `CUBE`, which expands to
+/// 2 to the power of that many grouping sets.
+pub const MAX_CUBE_EXPRESSIONS: usize = 12;
+
+impl GroupingSet {
+    /// Returns the distinct expressions of all grouping sets, in the order
+    /// of their first appearance
+    pub fn distinct_expr(&self) -> Vec<&Expr> {
+        let mut distinct: Vec<&Expr> = vec![];
+        let exprs: Box<dyn Iterator<Item = &Expr>> = match self {
+            GroupingSet::Rollup(exprs) | GroupingSet::Cube(exprs) => {
+                Box::new(exprs.iter())
+            }
+            GroupingSet::GroupingSets(sets) => Box::new(sets.iter().flatten()),
+        };
+        for expr in exprs {
+            if !distinct.contains(&expr) {
+                distinct.push(expr);
+            }
+        }
+        distinct
+    }
+
+    /// Returns the expressions of every grouping set this expands to
+    pub fn sets(&self) -> Result<Vec<Vec<Expr>>> {
+        match self {
+            GroupingSet::Rollup(exprs) => Ok((0..=exprs.len())
+                .rev()
+                .map(|len| exprs[..len].to_vec())
+                .collect()),
+            GroupingSet::Cube(exprs) => {
+                if exprs.len() > MAX_CUBE_EXPRESSIONS {
+                    return Err(DataFusionError::Plan(format!(
+                        "CUBE supports at most {} expressions, got {}",
+                        MAX_CUBE_EXPRESSIONS,
+                        exprs.len()
+                    )));
+                }
+                // a set bit of the mask excludes the expression, so the
+                // sets go from all expressions to the empty set
+                Ok((0..1_usize << exprs.len())
+                    .map(|mask| {
+                        exprs
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| mask & (1 << (exprs.len() - 1 - i)) == 0)
+                            .map(|(_, expr)| expr.clone())
+                            .collect()
+                    })
+                    .collect())
+            }
+            GroupingSet::GroupingSets(sets) => Ok(sets.clone()),
+        }
+    }
+
+    /// Applies `f` to every expression of the grouping sets
+    pub fn map_exprs<F>(self, mut f: F) -> Result<Self>
+    where
+        F: FnMut(Expr) -> Result<Expr>,
+    {
+        let mut map = |exprs: Vec<Expr>| -> Result<Vec<Expr>> {
+            exprs.into_iter().map(&mut f).collect()
+        };
+        Ok(match self {
+            GroupingSet::Rollup(exprs) => GroupingSet::Rollup(map(exprs)?),
+            GroupingSet::Cube(exprs) => GroupingSet::Cube(map(exprs)?),
+            GroupingSet::GroupingSets(sets) => GroupingSet::GroupingSets(
+                sets.into_iter().map(&mut map).collect::<Result<_>>()?,
+            ),
+        })
+    }
+}
+
+impl fmt::Display for GroupingSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fmt_exprs = |exprs: &[Expr]| {
+            exprs
+                .iter()
+                .map(|e| format!("{:?}", e))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            GroupingSet::Rollup(exprs) => write!(f, "ROLLUP ({})", fmt_exprs(exprs)),
+            GroupingSet::Cube(exprs) => write!(f, "CUBE ({})", fmt_exprs(exprs)),
+            GroupingSet::GroupingSets(sets) => {
+                let sets = sets
+                    .iter()
+                    .map(|set| format!("({})", fmt_exprs(set)))
+                    .collect::<Vec<_>>();
+                write!(f, "GROUPING SETS ({})", sets.join(", "))
+            }
+        }
+    }
 }
 
 impl Expr {
@@ -440,9 +577,17 @@ impl Expr {
             Expr::Sort { ref expr, .. } => expr.get_type(schema),
             Expr::Between { .. } => Ok(DataType::Boolean),
             Expr::InList { .. } => Ok(DataType::Boolean),
+            Expr::Exists { .. } => Ok(DataType::Boolean),
+            Expr::InSubquery { .. } => Ok(DataType::Boolean),
+            Expr::ScalarSubquery(subquery) => {
+                Ok(subquery.subquery.schema().field(0).data_type().clone())
+            }
             Expr::Wildcard => Err(DataFusionError::Internal(
                 "Wildcard expressions are not valid in a logical query plan".to_owned(),
             )),
+            Expr::GroupingSet(_) => Err(DataFusionError::Plan(
+                "Grouping sets are only valid in a GROUP BY".to_owned(),
+            )),
             Expr::GetIndexedField { ref expr, key } => {
                 let data_type = expr.get_type(schema)?;
 
@@ -500,9 +645,15 @@ impl Expr {
             Expr::Sort { ref expr, .. } => expr.nullable(input_schema),
             Expr::Between { ref expr, .. } => expr.nullable(input_schema),
             Expr::InList { ref expr, .. } => expr.nullable(input_schema),
+            Expr::Exists { .. } => Ok(false),
+            Expr::InSubquery { ref expr, .. } => expr.nullable(input_schema),
+            Expr::ScalarSubquery(_) => Ok(true),
             Expr::Wildcard => Err(DataFusionError::Internal(
                 "Wildcard expressions are not valid in a logical query plan".to_owned(),
             )),
+            Expr::GroupingSet(_) => Err(DataFusionError::Plan(
+                "Grouping sets are only valid in a GROUP BY".to_owned(),
+            )),
             Expr::GetIndexedField { ref expr, key } => {
                 let data_type = expr.get_type(input_schema)?;
                 get_indexed_field(&data_type, key).map(|x| x.is_nullable())
@@ -664,6 +815,85 @@ impl Expr {
         }
     }
 
+    /// Feeds only the rows for which `predicate` is true to an aggregate
+    /// function, like `FILTER (WHERE predicate)` in SQL. Predicates of the
+    /// same aggregate are combined with `AND`.
+    ///
+    /// ```
+    /// # use datafusion::logical_plan::{col, count, lit};
+    /// let errors = count(lit(1)).filter(col("status").eq(lit("error"))).unwrap();
+    /// ```
+    pub fn filter(self, predicate: Expr) -> Result<Expr> {
+        match self {
+            Expr::AggregateFunction {
+                fun,
+                args,
+                distinct,
+                filter,
+                order_by,
+            } => Ok(Expr::AggregateFunction {
+                fun,
+                args,
+                distinct,
+                filter: Some(Box::new(match filter {
+                    Some(filter) => filter.and(predicate),
+                    None => predicate,
+                })),
+                order_by,
+            }),
+            other => Err(DataFusionError::Plan(format!(
+                "FILTER is only supported for built-in aggregate functions, got {:?}",
+                other
+            ))),
+        }
+    }
+
+    /// Feeds the rows to an aggregate function in the order of the sort
+    /// expressions `order_by`, like `ARRAY_AGG(x ORDER BY y)` in SQL. An
+    /// expression that is not a sort expression sorts ascending with nulls
+    /// last. The order of `DISTINCT` aggregates is not supported yet.
+    ///
+    /// ```
+    /// # use datafusion::logical_plan::{col, max};
+    /// let latest = max(col("a")).order_by(vec![col("ts").sort(false, true)]).unwrap();
+    /// ```
+    pub fn order_by(self, order_by: Vec<Expr>) -> Result<Expr> {
+        let order_by = order_by
+            .into_iter()
+            .map(|e| match e {
+                Expr::Sort { .. } => e,
+                _ => e.sort(true, false),
+            })
+            .collect();
+        match self {
+            Expr::AggregateFunction {
+                fun,
+                distinct: true,
+                ..
+            } if !order_by.is_empty() => Err(DataFusionError::NotImplemented(format!(
+                "ORDER BY is not supported for DISTINCT aggregate functions, got {}",
+                fun
+            ))),
+            Expr::AggregateFunction {
+                fun,
+                args,
+                distinct,
+                filter,
+                ..
+            } => Ok(Expr::AggregateFunction {
+                fun,
+                args,
+                distinct,
+                filter,
+                order_by,
+            }),
+            other => Err(DataFusionError::Plan(format!(
+                "ORDER BY is only supported for built-in aggregate functions, got {:?}",
+                other
+            ))),
+        }
+    }
+
     /// Performs a depth first walk of an expression and
     /// its children, calling [`ExpressionVisitor::pre_visit`] and
     /// `visitor.post_visit`.
@@ -773,9 +1003,23 @@ impl Expr {
                     .try_fold(visitor, |visitor, arg| arg.accept(visitor))?;
                 Ok(visitor)
             }
-            Expr::AggregateFunction { args, .. } => args
-                .iter()
-                .try_fold(visitor, |visitor, arg| arg.accept(visitor)),
+            Expr::AggregateFunction {
+                args,
+                filter,
+                order_by,
+                ..
+            } => {
+                let visitor = args
+                    .iter()
+                    .try_fold(visitor, |visitor, arg| arg.accept(visitor))?;
+                let visitor = match filter {
+                    Some(filter) => filter.accept(visitor)?,
+                    None => visitor,
+                };
+                order_by
+                    .iter()
+                    .try_fold(visitor, |visitor, arg| arg.accept(visitor))
+            }
             Expr::AggregateUDF { args, .. } => args
                 .iter()
                 .try_fold(visitor, |visitor, arg| arg.accept(visitor)),
@@ -784,7 +1028,15 @@ impl Expr {
                 list.iter()
                     .try_fold(visitor, |visitor, arg| arg.accept(visitor))
             }
+            // the plan of a subquery is not part of the expression tree
+            Expr::Exists { .. } => Ok(visitor),
+            Expr::InSubquery { expr, .. } => expr.accept(visitor),
+            Expr::ScalarSubquery(_) => Ok(visitor),
             Expr::Wildcard => Ok(visitor),
+            Expr::GroupingSet(grouping_set) => grouping_set
+                .distinct_expr()
+                .into_iter()
+                .try_fold(visitor, |visitor, arg| arg.accept(visitor)),
             Expr::GetIndexedField { ref expr, .. } => expr.accept(visitor),
         }?;
 
@@ -927,10 +1179,14 @@ impl Expr {
                 args,
                 fun,
                 distinct,
+                filter,
+                order_by,
             } => Expr::AggregateFunction {
                 args: rewrite_vec(args, rewriter)?,
                 fun,
                 distinct,
+                filter: rewrite_option_box(filter, rewriter)?,
+                order_by: rewrite_vec(order_by, rewriter)?,
             },
             Expr::AggregateUDF { args, fun } => Expr::AggregateUDF {
                 args: rewrite_vec(args, rewriter)?,
@@ -945,7 +1201,21 @@ impl Expr {
                 list: rewrite_vec(list, rewriter)?,
                 negated,
             },
+            Expr::Exists { subquery, negated } => Expr::Exists { subquery, negated },
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => Expr::InSubquery {
+                expr: rewrite_boxed(expr, rewriter)?,
+                subquery,
+                negated,
+            },
+            Expr::ScalarSubquery(subquery) => Expr::ScalarSubquery(subquery),
             Expr::Wildcard => Expr::Wildcard,
+            Expr::GroupingSet(grouping_set) => {
+                Expr::GroupingSet(grouping_set.map_exprs(|expr| expr.rewrite(rewriter))?)
+            }
             Expr::GetIndexedField { expr, key } => Expr::GetIndexedField {
                 expr: rewrite_boxed(expr, rewriter)?,
                 key,
@@ -984,7 +1254,16 @@ impl std::fmt::Display for Expr {
                 ref args,
                 /// Whether this is a DISTINCT aggregation or not
                 ref distinct,
-            } => fmt_function(f, &fun.to_string(), *distinct, args, true),
+                ref filter,
+                ref order_by,
+            } => {
+                fmt_function(f, &fun.to_string(), *distinct, args, true)?;
+                fmt_aggregate_order_by(f, order_by)?;
+                if let Some(filter) = filter {
+                    write!(f, " FILTER (WHERE {})", filter)?;
+                }
+                Ok(())
+            }
             Expr::ScalarFunction {
                 /// Name of the function
                 ref fun,
@@ -1277,8 +1556,9 @@ pub fn normalize_col(expr: Expr, plan: &LogicalPlan) -> Result<Expr> {
 }
 
 /// Recursively call [`Column::normalize`] on all Column expressions
-/// in the `expr` expression tree.
-fn normalize_col_with_schemas(
+/// in the `expr` expression tree, resolving them against `schemas` in
+/// order.
+pub fn normalize_col_with_schemas(
     expr: Expr,
     schemas: &[&Arc<DFSchema>],
     using_columns: &[HashSet<Column>],
@@ -1439,6 +1719,8 @@ pub fn min(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Min,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1448,6 +1730,8 @@ pub fn max(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Max,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1457,6 +1741,8 @@ pub fn sum(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Sum,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1466,6 +1752,8 @@ pub fn avg(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Avg,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1475,6 +1763,8 @@ pub fn count(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Count,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1484,6 +1774,8 @@ pub fn count_distinct(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::Count,
         distinct: true,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1496,6 +1788,45 @@ pub fn in_list(expr: Expr, list: Vec<Expr>, negated: bool) -> Expr {
     }
 }
 
+/// Create an EXISTS subquery expression
+pub fn exists(subquery: Arc<LogicalPlan>) -> Expr {
+    Expr::Exists {
+        subquery: Subquery { subquery },
+        negated: false,
+    }
+}
+
+/// Create a NOT EXISTS subquery expression
+pub fn not_exists(subquery: Arc<LogicalPlan>) -> Expr {
+    Expr::Exists {
+        subquery: Subquery { subquery },
+        negated: true,
+    }
+}
+
+/// Create an IN subquery expression
+pub fn in_subquery(expr: Expr, subquery: Arc<LogicalPlan>) -> Expr {
+    Expr::InSubquery {
+        expr: Box::new(expr),
+        subquery: Subquery { subquery },
+        negated: false,
+    }
+}
+
+/// Create a NOT IN subquery expression
+pub fn not_in_subquery(expr: Expr, subquery: Arc<LogicalPlan>) -> Expr {
+    Expr::InSubquery {
+        expr: Box::new(expr),
+        subquery: Subquery { subquery },
+        negated: true,
+    }
+}
+
+/// Create a scalar subquery expression
+pub fn scalar_subquery(subquery: Arc<LogicalPlan>) -> Expr {
+    Expr::ScalarSubquery(Subquery { subquery })
+}
+
 /// Trait for converting a type to a [`Literal`] literal expression.
 pub trait Literal {
     /// convert the value to a Literal expression
@@ -1632,6 +1963,157 @@ pub fn approx_distinct(expr: Expr) -> Expr {
         fun: aggregates::AggregateFunction::ApproxDistinct,
         distinct: false,
         args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the stddev() aggregate function,
+/// the sample standard deviation
+pub fn stddev(expr: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Stddev,
+        distinct: false,
+        args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the stddev_pop() aggregate function,
+/// the population standard deviation
+pub fn stddev_pop(expr: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::StddevPop,
+        distinct: false,
+        args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the var_samp() aggregate function,
+/// the sample variance
+pub fn var_samp(expr: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Variance,
+        distinct: false,
+        args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the var_pop() aggregate function,
+/// the population variance
+pub fn var_pop(expr: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::VariancePop,
+        distinct: false,
+        args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the covar() aggregate function,
+/// the sample covariance of the pairs in which neither value is null
+pub fn covar(expr1: Expr, expr2: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Covariance,
+        distinct: false,
+        args: vec![expr1, expr2],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the covar_pop() aggregate function,
+/// the population covariance of the pairs in which neither value is null
+pub fn covar_pop(expr1: Expr, expr2: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::CovariancePop,
+        distinct: false,
+        args: vec![expr1, expr2],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the corr() aggregate function, the
+/// Pearson correlation coefficient of the pairs in which neither value is null
+pub fn corr(expr1: Expr, expr2: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Correlation,
+        distinct: false,
+        args: vec![expr1, expr2],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the median() aggregate function
+pub fn median(expr: Expr) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Median,
+        distinct: false,
+        args: vec![expr],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the percentile_cont() aggregate
+/// function, the value at `percentile` (between 0 and 1) of the values,
+/// interpolated between the closest values
+pub fn percentile_cont(expr: Expr, percentile: f64) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::PercentileCont,
+        distinct: false,
+        args: vec![expr, lit(percentile)],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Create an expression to represent the approx_percentile_cont() aggregate
+/// function, an approximation of [`percentile_cont`] that summarizes the
+/// values with a t-digest instead of keeping them all
+pub fn approx_percentile_cont(expr: Expr, percentile: f64) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::ApproxPercentileCont,
+        distinct: false,
+        args: vec![expr, lit(percentile)],
+        filter: None,
+        order_by: vec![],
+    }
+}
+
+/// Groups by every prefix of `exprs`, see [`GroupingSet::Rollup`]
+pub fn rollup(exprs: Vec<Expr>) -> Expr {
+    Expr::GroupingSet(GroupingSet::Rollup(exprs))
+}
+
+/// Groups by every subset of `exprs`, see [`GroupingSet::Cube`]
+pub fn cube(exprs: Vec<Expr>) -> Expr {
+    Expr::GroupingSet(GroupingSet::Cube(exprs))
+}
+
+/// Groups by each of `sets`, see [`GroupingSet::GroupingSets`]
+pub fn grouping_sets(sets: Vec<Vec<Expr>>) -> Expr {
+    Expr::GroupingSet(GroupingSet::GroupingSets(sets))
+}
+
+/// Returns a bit mask with a bit for every one of `exprs`, the first being the
+/// most significant, which is set if the grouping set of a row does not group by
+/// that expression.
+pub fn grouping(exprs: Vec<Expr>) -> Expr {
+    Expr::AggregateFunction {
+        fun: aggregates::AggregateFunction::Grouping,
+        distinct: false,
+        args: exprs,
+        filter: None,
+        order_by: vec![],
     }
 }
 
@@ -1807,6 +2289,15 @@ fn fmt_function(
     write!(f, "{}({}{})", fun, distinct_str, args.join(", "))
 }
 
+/// Writes the `ORDER BY` clause of an aggregate function, if any
+fn fmt_aggregate_order_by(f: &mut fmt::Formatter, order_by: &[Expr]) -> fmt::Result {
+    if order_by.is_empty() {
+        return Ok(());
+    }
+    let keys: Vec<String> = order_by.iter().map(|key| format!("{:?}", key)).collect();
+    write!(f, " ORDER BY {}", keys.join(", "))
+}
+
 impl fmt::Debug for Expr {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -1896,8 +2387,16 @@ impl fmt::Debug for Expr {
                 fun,
                 distinct,
                 ref args,
-                ..
-            } => fmt_function(f, &fun.to_string(), *distinct, args, true),
+                filter,
+                order_by,
+            } => {
+                fmt_function(f, &fun.to_string(), *distinct, args, true)?;
+                fmt_aggregate_order_by(f, order_by)?;
+                if let Some(filter) = filter {
+                    write!(f, " FILTER (WHERE {:?})", filter)?;
+                }
+                Ok(())
+            }
             Expr::AggregateUDF { fun, ref args, .. } => {
                 fmt_function(f, &fun.name, false, args, false)
             }
@@ -1924,7 +2423,27 @@ impl fmt::Debug for Expr {
                     write!(f, "{:?} IN ({:?})", expr, list)
                 }
             }
+            Expr::Exists { subquery, negated } => {
+                if *negated {
+                    write!(f, "NOT EXISTS ({:?})", subquery)
+                } else {
+                    write!(f, "EXISTS ({:?})", subquery)
+                }
+            }
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => {
+                if *negated {
+                    write!(f, "{:?} NOT IN ({:?})", expr, subquery)
+                } else {
+                    write!(f, "{:?} IN ({:?})", expr, subquery)
+                }
+            }
+            Expr::ScalarSubquery(subquery) => write!(f, "({:?})", subquery),
             Expr::Wildcard => write!(f, "*"),
+            Expr::GroupingSet(grouping_set) => write!(f, "{}", grouping_set),
             Expr::GetIndexedField { ref expr, key } => {
                 write!(f, "({:?})[{}]", expr, key)
             }
@@ -1949,8 +2468,40 @@ fn create_function_name(
     Ok(format!("{}({}{})", fun, distinct_str, names.join(",")))
 }
 
+/// Names a subquery after its plan, written on a single line, so that
+/// different subqueries get different names
+fn subquery_name(subquery: &Subquery) -> String {
+    format!("{:?}", subquery.subquery)
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Returns a readable name of an expression based on the input schema.
 /// This function recursively transverses the expression for names such as "CAST(a > 2)".
+/// Returns the name of the sort expression `e` of the `ORDER BY` clause of an
+/// aggregate function
+fn create_sort_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
+    match e {
+        Expr::Sort {
+            expr,
+            asc,
+            nulls_first,
+        } => Ok(format!(
+            "{} {} {}",
+            create_name(expr, input_schema)?,
+            if *asc { "ASC" } else { "DESC" },
+            if *nulls_first {
+                "NULLS FIRST"
+            } else {
+                "NULLS LAST"
+            }
+        )),
+        other => create_name(other, input_schema),
+    }
+}
+
 fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
     match e {
         Expr::Alias(_, name) => Ok(name.clone()),
@@ -2046,8 +2597,30 @@ fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
             fun,
             distinct,
             args,
-            ..
-        } => create_function_name(&fun.to_string(), *distinct, args, input_schema),
+            filter,
+            order_by,
+        } => {
+            let mut parts: Vec<String> = vec![create_function_name(
+                &fun.to_string(),
+                *distinct,
+                args,
+                input_schema,
+            )?];
+            if !order_by.is_empty() {
+                let keys = order_by
+                    .iter()
+                    .map(|key| create_sort_name(key, input_schema))
+                    .collect::<Result<Vec<_>>>()?;
+                parts.push(format!("ORDER BY {}", keys.join(", ")));
+            }
+            if let Some(filter) = filter {
+                parts.push(format!(
+                    "FILTER (WHERE {})",
+                    create_name(filter, input_schema)?
+                ));
+            }
+            Ok(parts.join(" "))
+        }
         Expr::AggregateUDF { fun, args } => {
             let mut names = Vec::with_capacity(args.len());
             for e in args {
@@ -2068,6 +2641,28 @@ fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
                 Ok(format!("{} IN ({:?})", expr, list))
             }
         }
+        Expr::Exists { subquery, negated } => {
+            let subquery = subquery_name(subquery);
+            if *negated {
+                Ok(format!("NOT EXISTS ({})", subquery))
+            } else {
+                Ok(format!("EXISTS ({})", subquery))
+            }
+        }
+        Expr::InSubquery {
+            expr,
+            subquery,
+            negated,
+        } => {
+            let expr = create_name(expr, input_schema)?;
+            let subquery = subquery_name(subquery);
+            if *negated {
+                Ok(format!("{} NOT IN ({})", expr, subquery))
+            } else {
+                Ok(format!("{} IN ({})", expr, subquery))
+            }
+        }
+        Expr::ScalarSubquery(subquery) => Ok(format!("({})", subquery_name(subquery))),
         Expr::Between {
             expr,
             negated,
@@ -2089,7 +2684,104 @@ fn create_name(e: &Expr, input_schema: &DFSchema) -> Result<String> {
         Expr::Wildcard => Err(DataFusionError::Internal(
             "Create name does not support wildcard".to_string(),
         )),
+        Expr::GroupingSet(grouping_set) => {
+            let names = |exprs: &[Expr]| -> Result<String> {
+                let names = exprs
+                    .iter()
+                    .map(|e| create_name(e, input_schema))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(names.join(", "))
+            };
+            match grouping_set {
+                GroupingSet::Rollup(exprs) => Ok(format!("ROLLUP ({})", names(exprs)?)),
+                GroupingSet::Cube(exprs) => Ok(format!("CUBE ({})", names(exprs)?)),
+                GroupingSet::GroupingSets(sets) => {
+                    let sets = sets
+                        .iter()
+                        .map(|set| Ok(format!("({})", names(set)?)))
+                        .collect::<Result<Vec<_>>>()?;
+                    Ok(format!("GROUPING SETS ({})", sets.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+/// The name of the column identifying the grouping set of the rows produced by
+/// an aggregation with grouping sets. Bit `n - 1 - i` of the id is set if the
+/// grouping set does not group by the `i`th of the `n` grouping expressions.
+pub const GROUPING_ID_COLUMN: &str = "__grouping_id";
+
+/// The maximum number of grouping sets a `GROUP BY` may expand to.
+pub const MAX_GROUPING_SETS: usize = 4096;
+
+/// The maximum number of distinct grouping expressions of the grouping sets
+/// of a `GROUP BY`, one bit of the [`GROUPING_ID_COLUMN`] each.
+pub const MAX_GROUPING_EXPRESSIONS: usize = 32;
+
+/// Expands the expressions of a `GROUP BY`, which may contain
+/// [`Expr::GroupingSet`]s, into the distinct grouping expressions and, for
+/// every grouping set, whether it groups by each of them.
+///
+/// Expressions outside of a grouping set are part of every set and several
+/// grouping sets combine to their cross product. No sets are returned if
+/// there is no [`Expr::GroupingSet`].
+pub fn expand_grouping_sets(group_expr: &[Expr]) -> Result<(Vec<Expr>, Vec<Vec<bool>>)> {
+    if !group_expr
+        .iter()
+        .any(|expr| matches!(expr, Expr::GroupingSet(_)))
+    {
+        return Ok((group_expr.to_vec(), vec![]));
+    }
+
+    let mut distinct: Vec<Expr> = vec![];
+    let mut add_distinct = |expr: &Expr| {
+        if !distinct.contains(expr) {
+            distinct.push(expr.clone());
+        }
+        if distinct.len() > MAX_GROUPING_EXPRESSIONS {
+            return Err(DataFusionError::Plan(format!(
+                "Grouping sets support at most {} distinct grouping expressions",
+                MAX_GROUPING_EXPRESSIONS
+            )));
+        }
+        Ok(())
+    };
+    let mut sets: Vec<Vec<Expr>> = vec![vec![]];
+    for expr in group_expr {
+        match expr {
+            Expr::GroupingSet(grouping_set) => {
+                for expr in grouping_set.distinct_expr() {
+                    add_distinct(expr)?;
+                }
+                let grouping_sets = grouping_set.sets()?;
+                if sets.len() * grouping_sets.len() > MAX_GROUPING_SETS {
+                    return Err(DataFusionError::Plan(format!(
+                        "GROUP BY expands to more than {} grouping sets",
+                        MAX_GROUPING_SETS
+                    )));
+                }
+                sets = sets
+                    .iter()
+                    .flat_map(|set| {
+                        grouping_sets
+                            .iter()
+                            .map(move |other| set.iter().chain(other).cloned().collect())
+                    })
+                    .collect();
+            }
+            expr => {
+                add_distinct(expr)?;
+                sets.iter_mut().for_each(|set| set.push(expr.clone()));
+            }
+        }
     }
+
+    let masks = sets
+        .iter()
+        .map(|set| distinct.iter().map(|expr| set.contains(expr)).collect())
+        .collect();
+    Ok((distinct, masks))
 }
 
 /// Create field meta-data from an expression, for use in a result set schema
@@ -2468,4 +3160,93 @@ mod tests {
             combine_filters(&[filter1.clone(), filter2.clone(), filter3.clone()]);
         assert_eq!(result, Some(and(and(filter1, filter2), filter3)));
     }
+
+    #[test]
+    fn expand_grouping_sets_limits() -> Result<()> {
+        let columns =
+            |n: usize| -> Vec<Expr> { (0..n).map(|i| col(&format!("c{}", i))).collect() };
+
+        let (exprs, sets) = expand_grouping_sets(&[rollup(columns(32))])?;
+        assert_eq!(exprs.len(), 32);
+        assert_eq!(sets.len(), 33);
+        let err = expand_grouping_sets(&[col("c32"), rollup(columns(32))]).unwrap_err();
+        assert!(
+            matches!(&err, DataFusionError::Plan(m) if m.contains("at most 32 distinct grouping expressions")),
+            "{}",
+            err
+        );
+
+        let (_, sets) = expand_grouping_sets(&[cube(columns(MAX_CUBE_EXPRESSIONS))])?;
+        assert_eq!(sets.len(), 1 << MAX_CUBE_EXPRESSIONS);
+        let err =
+            expand_grouping_sets(&[cube(columns(MAX_CUBE_EXPRESSIONS + 1))]).unwrap_err();
+        assert!(matches!(err, DataFusionError::Plan(_)), "{}", err);
+        let err = expand_grouping_sets(&[
+            cube(columns(MAX_CUBE_EXPRESSIONS)),
+            cube(vec![col("d")]),
+        ])
+        .unwrap_err();
+        assert!(matches!(err, DataFusionError::Plan(_)), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_filter_and_order_by() -> Result<()> {
+        let expr = sum(col("a"))
+            .filter(col("b").gt(lit(1)))?
+            .filter(col("c"))?
+            .order_by(vec![col("d"), col("e").sort(false, true)])?;
+        assert_eq!(
+            format!("{:?}", expr),
+            "SUM(#a) ORDER BY #d ASC NULLS LAST, #e DESC NULLS FIRST FILTER (WHERE #b > Int32(1) AND #c)"
+        );
+        assert_eq!(
+            format!("{}", expr),
+            "SUM(#a) ORDER BY #d ASC NULLS LAST, #e DESC NULLS FIRST FILTER (WHERE #b > Int32(1) AND #c)"
+        );
+
+        let schema = DFSchema::new(vec![])?;
+        let mut columns = HashSet::new();
+        crate::optimizer::utils::expr_to_columns(&expr, &mut columns)?;
+        assert_eq!(columns.len(), 5);
+        assert_eq!(
+            expr.name(&schema)?,
+            "SUM(a) ORDER BY d ASC NULLS LAST, e DESC NULLS FIRST FILTER (WHERE b > Int32(1) AND c)"
+        );
+
+        let err = col("a").filter(col("b")).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("FILTER is only supported for built-in aggregate functions"));
+
+        let distinct = Expr::AggregateFunction {
+            fun: aggregates::AggregateFunction::ArrayAgg,
+            args: vec![col("a")],
+            distinct: true,
+            filter: None,
+            order_by: vec![],
+        };
+        let err = distinct.order_by(vec![col("b")]).unwrap_err();
+        assert!(matches!(err, DataFusionError::NotImplemented(_)), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn scalar_subquery_names() -> Result<()> {
+        let subquery = |value: i64| -> Result<Expr> {
+            let plan = crate::logical_plan::LogicalPlanBuilder::empty(true)
+                .project(vec![lit(value)])?
+                .build()?;
+            Ok(scalar_subquery(Arc::new(plan)))
+        };
+        let schema = DFSchema::empty();
+        assert_eq!(
+            subquery(1)?.name(&schema)?,
+            "(Projection: Int64(1), EmptyRelation)"
+        );
+        assert_ne!(subquery(1)?.name(&schema)?, subquery(2)?.name(&schema)?);
+        // the plan of a subquery is not shown in the plan of the query
+        assert_eq!(format!("{:?}", subquery(1)?), "(<subquery>)");
+        Ok(())
+    }
 }
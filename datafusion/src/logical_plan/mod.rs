@@ -36,24 +36,30 @@ pub use builder::{
 pub use dfschema::{DFField, DFSchema, DFSchemaRef, ToDFSchema};
 pub use display::display_schema;
 pub use expr::{
-    abs, acos, and, approx_distinct, array, ascii, asin, atan, avg, binary_expr,
-    bit_length, btrim, case, ceil, character_length, chr, col, columnize_expr,
-    combine_filters, concat, concat_ws, cos, count, count_distinct, create_udaf,
-    create_udf, date_part, date_trunc, digest, exp, exprlist_to_fields, floor, in_list,
-    initcap, left, length, lit, lit_timestamp_nano, ln, log10, log2, lower, lpad, ltrim,
-    max, md5, min, normalize_col, normalize_cols, now, octet_length, or, random,
-    regexp_match, regexp_replace, repeat, replace, replace_col, reverse,
-    rewrite_sort_cols_by_aggs, right, round, rpad, rtrim, sha224, sha256, sha384, sha512,
-    signum, sin, split_part, sqrt, starts_with, strpos, substr, sum, tan, to_hex,
-    translate, trim, trunc, unalias, unnormalize_col, unnormalize_cols, upper, when,
-    Column, Expr, ExprRewriter, ExpressionVisitor, Literal, Recursion, RewriteRecursion,
+    abs, acos, and, approx_distinct, approx_percentile_cont, array, ascii, asin, atan,
+    avg, binary_expr, bit_length, btrim, case, ceil, character_length, chr, col,
+    columnize_expr, combine_filters, concat, concat_ws, corr, cos, count, count_distinct,
+    covar, covar_pop, create_udaf, create_udf, cube, date_part, date_trunc, digest,
+    exists, exp, expand_grouping_sets, exprlist_to_fields, floor, grouping,
+    grouping_sets, in_list, in_subquery, initcap, left, length, lit, lit_timestamp_nano,
+    ln, log10, log2, lower, lpad, ltrim, max, md5, median, min, normalize_col,
+    normalize_col_with_schemas, normalize_cols, not_exists, not_in_subquery, now,
+    octet_length, or, percentile_cont, random, regexp_match, regexp_replace, repeat,
+    replace, replace_col, reverse, rewrite_sort_cols_by_aggs, right, rollup, round, rpad,
+    rtrim, scalar_subquery, sha224, sha256, sha384, sha512, signum, sin, split_part,
+    sqrt, starts_with, stddev, stddev_pop, strpos, substr, sum, tan, to_hex, translate,
+    trim, trunc, unalias, unnormalize_col, unnormalize_cols, upper, var_pop, var_samp,
+    when, Column, Expr, ExprRewriter, ExpressionVisitor, GroupingSet, Literal, Recursion,
+    RewriteRecursion, GROUPING_ID_COLUMN, MAX_CUBE_EXPRESSIONS, MAX_GROUPING_EXPRESSIONS,
+    MAX_GROUPING_SETS,
 };
 pub use extension::UserDefinedLogicalNode;
 pub use operators::Operator;
 pub use plan::{
-    CreateExternalTable, CreateMemoryTable, CrossJoin, DropTable, EmptyRelation,
-    JoinConstraint, JoinType, Limit, LogicalPlan, Partitioning, PlanType, PlanVisitor,
-    Repartition, TableScan, Union, Values,
+    CopyTo, CreateExternalTable, CreateMemoryTable, CreateView, CrossJoin, DropTable,
+    DropView, EmptyRelation, Insert, JoinConstraint, JoinType, Limit, LogicalPlan,
+    Partitioning, PlanType, PlanVisitor, RecursiveQuery, RefreshTable, Repartition,
+    Subquery, TableScan, Union, Values,
 };
 pub(crate) use plan::{StringifiedPlan, ToStringifiedPlan};
 pub use registry::FunctionRegistry;
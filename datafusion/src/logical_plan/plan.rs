@@ -20,6 +20,8 @@
 use super::display::{GraphvizVisitor, IndentVisitor};
 use super::expr::{Column, Expr};
 use super::extension::UserDefinedLogicalNode;
+use crate::datasource::file_format::compression::FileCompressionType;
+use crate::datasource::file_format::FileFormat;
 use crate::datasource::TableProvider;
 use crate::error::DataFusionError;
 use crate::logical_plan::dfschema::DFSchemaRef;
@@ -146,6 +148,22 @@ pub struct Union {
     pub alias: Option<String>,
 }
 
+/// A recursive common table expression (`WITH RECURSIVE`). Its recursive
+/// term, which references the query by `name`, is evaluated on the rows
+/// produced by its previous evaluation until it produces no rows.
+#[derive(Clone)]
+pub struct RecursiveQuery {
+    /// Name of the query, by which the recursive term scans the rows
+    /// produced by its previous evaluation
+    pub name: String,
+    /// The term producing the initial rows
+    pub static_term: Arc<LogicalPlan>,
+    /// The term evaluated on the rows of the previous iteration
+    pub recursive_term: Arc<LogicalPlan>,
+    /// The schema of the static term, qualified by `name`
+    pub schema: DFSchemaRef,
+}
+
 /// Creates an in memory table.
 #[derive(Clone)]
 pub struct CreateMemoryTable {
@@ -155,6 +173,19 @@ pub struct CreateMemoryTable {
     pub input: Arc<LogicalPlan>,
 }
 
+/// Creates a view.
+#[derive(Clone)]
+pub struct CreateView {
+    /// The view name
+    pub name: String,
+    /// The logical plan producing the rows of the view
+    pub input: Arc<LogicalPlan>,
+    /// Whether an existing view of the same name is replaced
+    pub or_replace: bool,
+    /// The SQL statement creating the view, if any
+    pub definition: Option<String>,
+}
+
 /// Creates an external table.
 #[derive(Clone)]
 pub struct CreateExternalTable {
@@ -168,6 +199,38 @@ pub struct CreateExternalTable {
     pub file_type: FileType,
     /// Whether the CSV file contains a header
     pub has_header: bool,
+    /// The compression of the files
+    pub file_compression_type: FileCompressionType,
+}
+
+/// Inserts the rows produced by a plan into a table.
+#[derive(Clone)]
+pub struct Insert {
+    /// The name of the table
+    pub table_name: String,
+    /// The table receiving the rows
+    pub table: Arc<dyn TableProvider>,
+    /// The plan producing the rows, with the columns of the table
+    pub input: Arc<LogicalPlan>,
+    /// Whether the rows replace the current content of the table
+    pub overwrite: bool,
+    /// The schema of the output, the number of rows inserted
+    pub schema: DFSchemaRef,
+}
+
+/// Writes the rows produced by a plan to files in a new directory.
+#[derive(Clone)]
+pub struct CopyTo {
+    /// The plan producing the rows
+    pub input: Arc<LogicalPlan>,
+    /// The directory the files are written to
+    pub path: String,
+    /// The file type of the files
+    pub file_type: FileType,
+    /// The format writing the files, configured with the options of the copy
+    pub format: Arc<dyn FileFormat>,
+    /// The schema of the output, the number of rows written
+    pub schema: DFSchemaRef,
 }
 
 /// Drops a table.
@@ -181,6 +244,26 @@ pub struct DropTable {
     pub schema: DFSchemaRef,
 }
 
+/// Drops a view.
+#[derive(Clone)]
+pub struct DropView {
+    /// The view name
+    pub name: String,
+    /// If the view exists
+    pub if_exist: bool,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
+/// Invalidates the cached listing and file metadata of a table.
+#[derive(Clone)]
+pub struct RefreshTable {
+    /// The table name
+    pub name: String,
+    /// Dummy schema
+    pub schema: DFSchemaRef,
+}
+
 /// Produces a relation with string representations of
 /// various parts of the plan
 #[derive(Clone)]
@@ -243,6 +326,43 @@ pub struct Values {
     pub values: Vec<Vec<Expr>>,
 }
 
+/// A query nested in an expression, such as the right side of `IN
+/// (SELECT ...)`. Its plan may reference columns of the enclosing query
+/// (a correlated subquery).
+#[derive(Clone)]
+pub struct Subquery {
+    /// The plan of the nested query
+    pub subquery: Arc<LogicalPlan>,
+}
+
+impl Subquery {
+    /// Creates a subquery from the plan of the nested query
+    pub fn new(plan: LogicalPlan) -> Self {
+        Self {
+            subquery: Arc::new(plan),
+        }
+    }
+}
+
+impl PartialEq for Subquery {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.subquery, &other.subquery)
+            || format!("{:?}", self.subquery) == format!("{:?}", other.subquery)
+    }
+}
+
+impl PartialOrd for Subquery {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        (self == other).then(|| std::cmp::Ordering::Equal)
+    }
+}
+
+impl fmt::Debug for Subquery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<subquery>")
+    }
+}
+
 /// Aggregates its input based on a set of grouping and aggregate
 /// expressions (e.g. SUM).
 #[derive(Clone)]
@@ -321,6 +441,8 @@ pub enum LogicalPlan {
     Repartition(Repartition),
     /// Union multiple inputs
     Union(Union),
+    /// Evaluates a recursive common table expression
+    RecursiveQuery(RecursiveQuery),
     /// Produces rows from a table provider by reference or from the context
     TableScan(TableScan),
     /// Produces no rows: An empty relation with an empty schema
@@ -331,8 +453,18 @@ pub enum LogicalPlan {
     CreateExternalTable(CreateExternalTable),
     /// Creates an in memory table.
     CreateMemoryTable(CreateMemoryTable),
+    /// Inserts rows into a table.
+    Insert(Insert),
+    /// Writes rows to files.
+    CopyTo(CopyTo),
     /// Drops a table.
     DropTable(DropTable),
+    /// Creates a view.
+    CreateView(CreateView),
+    /// Drops a view.
+    DropView(DropView),
+    /// Invalidates the cached listing and file metadata of a table.
+    RefreshTable(RefreshTable),
     /// Values expression. See
     /// [Postgres VALUES](https://www.postgresql.org/docs/current/queries-values.html)
     /// documentation for more details.
@@ -372,10 +504,16 @@ impl LogicalPlan {
             LogicalPlan::Analyze(analyze) => &analyze.schema,
             LogicalPlan::Extension(extension) => extension.node.schema(),
             LogicalPlan::Union(Union { schema, .. }) => schema,
+            LogicalPlan::RecursiveQuery(RecursiveQuery { schema, .. }) => schema,
             LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
                 input.schema()
             }
+            LogicalPlan::Insert(Insert { schema, .. }) => schema,
+            LogicalPlan::CopyTo(CopyTo { schema, .. }) => schema,
             LogicalPlan::DropTable(DropTable { schema, .. }) => schema,
+            LogicalPlan::CreateView(CreateView { input, .. }) => input.schema(),
+            LogicalPlan::DropView(DropView { schema, .. }) => schema,
+            LogicalPlan::RefreshTable(RefreshTable { schema, .. }) => schema,
         }
     }
 
@@ -388,7 +526,9 @@ impl LogicalPlan {
             LogicalPlan::Values(Values { schema, .. }) => vec![schema],
             LogicalPlan::Window(Window { input, schema, .. })
             | LogicalPlan::Projection(Projection { input, schema, .. })
-            | LogicalPlan::Aggregate(Aggregate { input, schema, .. }) => {
+            | LogicalPlan::Aggregate(Aggregate { input, schema, .. })
+            | LogicalPlan::Insert(Insert { input, schema, .. })
+            | LogicalPlan::CopyTo(CopyTo { input, schema, .. }) => {
                 let mut schemas = input.all_schemas();
                 schemas.insert(0, schema);
                 schemas
@@ -409,7 +549,8 @@ impl LogicalPlan {
                 schemas.insert(0, schema);
                 schemas
             }
-            LogicalPlan::Union(Union { schema, .. }) => {
+            LogicalPlan::Union(Union { schema, .. })
+            | LogicalPlan::RecursiveQuery(RecursiveQuery { schema, .. }) => {
                 vec![schema]
             }
             LogicalPlan::Extension(extension) => vec![extension.node.schema()],
@@ -423,8 +564,11 @@ impl LogicalPlan {
             | LogicalPlan::Repartition(Repartition { input, .. })
             | LogicalPlan::Sort(Sort { input, .. })
             | LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::CreateView(CreateView { input, .. })
             | LogicalPlan::Filter(Filter { input, .. }) => input.all_schemas(),
-            LogicalPlan::DropTable(_) => vec![],
+            LogicalPlan::DropTable(_)
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshTable(_) => vec![],
         }
     }
 
@@ -471,11 +615,17 @@ impl LogicalPlan {
             | LogicalPlan::Limit(_)
             | LogicalPlan::CreateExternalTable(_)
             | LogicalPlan::CreateMemoryTable(_)
+            | LogicalPlan::Insert(_)
+            | LogicalPlan::CopyTo(_)
             | LogicalPlan::DropTable(_)
+            | LogicalPlan::CreateView(_)
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshTable(_)
             | LogicalPlan::CrossJoin(_)
             | LogicalPlan::Analyze { .. }
             | LogicalPlan::Explain { .. }
-            | LogicalPlan::Union(_) => {
+            | LogicalPlan::Union(_)
+            | LogicalPlan::RecursiveQuery(_) => {
                 vec![]
             }
         }
@@ -496,17 +646,25 @@ impl LogicalPlan {
             LogicalPlan::Limit(Limit { input, .. }) => vec![input],
             LogicalPlan::Extension(extension) => extension.node.inputs(),
             LogicalPlan::Union(Union { inputs, .. }) => inputs.iter().collect(),
+            LogicalPlan::RecursiveQuery(RecursiveQuery {
+                static_term,
+                recursive_term,
+                ..
+            }) => vec![static_term, recursive_term],
             LogicalPlan::Explain(explain) => vec![&explain.plan],
             LogicalPlan::Analyze(analyze) => vec![&analyze.input],
-            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
-                vec![input]
-            }
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Insert(Insert { input, .. })
+            | LogicalPlan::CopyTo(CopyTo { input, .. })
+            | LogicalPlan::CreateView(CreateView { input, .. }) => vec![input],
             // plans without inputs
             LogicalPlan::TableScan { .. }
             | LogicalPlan::EmptyRelation { .. }
             | LogicalPlan::Values { .. }
             | LogicalPlan::CreateExternalTable(_)
-            | LogicalPlan::DropTable(_) => vec![],
+            | LogicalPlan::DropTable(_)
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshTable(_) => vec![],
         }
     }
 
@@ -640,8 +798,16 @@ impl LogicalPlan {
                 }
                 true
             }
+            LogicalPlan::RecursiveQuery(RecursiveQuery {
+                static_term,
+                recursive_term,
+                ..
+            }) => static_term.accept(visitor)? && recursive_term.accept(visitor)?,
             LogicalPlan::Limit(Limit { input, .. }) => input.accept(visitor)?,
-            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. }) => {
+            LogicalPlan::CreateMemoryTable(CreateMemoryTable { input, .. })
+            | LogicalPlan::Insert(Insert { input, .. })
+            | LogicalPlan::CopyTo(CopyTo { input, .. })
+            | LogicalPlan::CreateView(CreateView { input, .. }) => {
                 input.accept(visitor)?
             }
             LogicalPlan::Extension(extension) => {
@@ -659,7 +825,9 @@ impl LogicalPlan {
             | LogicalPlan::EmptyRelation(_)
             | LogicalPlan::Values(_)
             | LogicalPlan::CreateExternalTable(_)
-            | LogicalPlan::DropTable(_) => true,
+            | LogicalPlan::DropTable(_)
+            | LogicalPlan::DropView(_)
+            | LogicalPlan::RefreshTable(_) => true,
         };
         if !recurse {
             return Ok(false);
@@ -982,12 +1150,38 @@ impl LogicalPlan {
                     }) => {
                         write!(f, "CreateMemoryTable: {:?}", name)
                     }
+                    LogicalPlan::Insert(Insert {
+                        table_name,
+                        overwrite,
+                        ..
+                    }) => {
+                        write!(f, "Insert: {} overwrite={}", table_name, overwrite)
+                    }
+                    LogicalPlan::CopyTo(CopyTo {
+                        path, file_type, ..
+                    }) => {
+                        write!(f, "CopyTo: {} format={:?}", path, file_type)
+                    }
                     LogicalPlan::DropTable(DropTable { name, if_exist, .. }) => {
                         write!(f, "DropTable: {:?} if not exist:={}", name, if_exist)
                     }
+                    LogicalPlan::CreateView(CreateView {
+                        name, or_replace, ..
+                    }) => {
+                        write!(f, "CreateView: {:?} or replace:={}", name, or_replace)
+                    }
+                    LogicalPlan::DropView(DropView { name, if_exist, .. }) => {
+                        write!(f, "DropView: {:?} if not exist:={}", name, if_exist)
+                    }
+                    LogicalPlan::RefreshTable(RefreshTable { name, .. }) => {
+                        write!(f, "RefreshTable: {:?}", name)
+                    }
                     LogicalPlan::Explain { .. } => write!(f, "Explain"),
                     LogicalPlan::Analyze { .. } => write!(f, "Analyze"),
                     LogicalPlan::Union(_) => write!(f, "Union"),
+                    LogicalPlan::RecursiveQuery(RecursiveQuery { name, .. }) => {
+                        write!(f, "RecursiveQuery: name={}", name)
+                    }
                     LogicalPlan::Extension(e) => e.node.fmt_for_explain(f),
                 }
             }
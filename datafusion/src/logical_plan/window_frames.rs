@@ -24,6 +24,7 @@
 //! - An EXCLUDE clause.
 
 use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
 use sqlparser::ast;
 use std::cmp::Ordering;
 use std::convert::{From, TryFrom};
@@ -59,10 +60,12 @@ impl TryFrom<ast::WindowFrame> for WindowFrame {
     type Error = DataFusionError;
 
     fn try_from(value: ast::WindowFrame) -> Result<Self> {
-        let start_bound = value.start_bound.into();
+        let units = value.units.into();
+        let start_bound = WindowFrameBound::try_from(value.start_bound)?;
         let end_bound = value
             .end_bound
-            .map(WindowFrameBound::from)
+            .map(WindowFrameBound::try_from)
+            .transpose()?
             .unwrap_or(WindowFrameBound::CurrentRow);
 
         if let WindowFrameBound::Following(None) = start_bound {
@@ -75,29 +78,19 @@ impl TryFrom<ast::WindowFrame> for WindowFrame {
                 "Invalid window frame: end bound cannot be unbounded preceding"
                     .to_owned(),
             ))
-        } else if start_bound > end_bound {
+        } else if units != WindowFrameUnits::Range
+            && [start_bound, end_bound].iter().any(|b| b.is_interval())
+        {
+            Err(DataFusionError::Plan(format!(
+                "Invalid window frame: {} frames can't have interval offsets",
+                units
+            )))
+        } else if start_bound > end_bound && start_bound.is_comparable(&end_bound) {
             Err(DataFusionError::Execution(format!(
             "Invalid window frame: start bound ({}) cannot be larger than end bound ({})",
             start_bound, end_bound
         )))
         } else {
-            let units = value.units.into();
-            if units == WindowFrameUnits::Range {
-                for bound in &[start_bound, end_bound] {
-                    match bound {
-                        WindowFrameBound::Preceding(Some(v))
-                        | WindowFrameBound::Following(Some(v))
-                            if *v > 0 =>
-                        {
-                            Err(DataFusionError::NotImplemented(format!(
-                                "With WindowFrameUnits={}, the bound cannot be {} PRECEDING or FOLLOWING at the moment",
-                                units, v
-                            )))
-                        }
-                        _ => Ok(()),
-                    }?;
-                }
-            }
             Ok(Self {
                 units,
                 start_bound,
@@ -125,7 +118,8 @@ impl Default for WindowFrame {
 /// 4. <expr> FOLLOWING
 /// 5. UNBOUNDED FOLLOWING
 ///
-/// in this implementation we'll only allow <expr> to be u64 (i.e. no dynamic boundary)
+/// in this implementation we'll only allow <expr> to be a constant [`WindowFrameOffset`]
+/// (i.e. no dynamic boundary)
 #[derive(Debug, Clone, Copy, Eq)]
 pub enum WindowFrameBound {
     /// 1. UNBOUNDED PRECEDING
@@ -134,7 +128,7 @@ pub enum WindowFrameBound {
     /// 2. <expr> PRECEDING
     /// <expr> must be a non-negative constant numeric expression. The boundary is a row that
     /// is <expr> "units" prior to the current row.
-    Preceding(Option<u64>),
+    Preceding(Option<WindowFrameOffset>),
     /// 3. The current row.
     ///
     /// For RANGE and GROUPS frame types, peers of the current row are also
@@ -147,16 +141,22 @@ pub enum WindowFrameBound {
     ///
     /// 5. UNBOUNDED FOLLOWING
     /// The frame boundary is the last row in the partition.
-    Following(Option<u64>),
+    Following(Option<WindowFrameOffset>),
 }
 
-impl From<ast::WindowFrameBound> for WindowFrameBound {
-    fn from(value: ast::WindowFrameBound) -> Self {
-        match value {
-            ast::WindowFrameBound::Preceding(v) => Self::Preceding(v),
-            ast::WindowFrameBound::Following(v) => Self::Following(v),
+impl TryFrom<ast::WindowFrameBound> for WindowFrameBound {
+    type Error = DataFusionError;
+
+    fn try_from(value: ast::WindowFrameBound) -> Result<Self> {
+        Ok(match value {
+            ast::WindowFrameBound::Preceding(v) => {
+                Self::Preceding(v.map(WindowFrameOffset::decode).transpose()?)
+            }
+            ast::WindowFrameBound::Following(v) => {
+                Self::Following(v.map(WindowFrameOffset::decode).transpose()?)
+            }
             ast::WindowFrameBound::CurrentRow => Self::CurrentRow,
-        }
+        })
     }
 }
 
@@ -193,18 +193,155 @@ impl Ord for WindowFrameBound {
 impl WindowFrameBound {
     /// get the rank of this window frame bound.
     ///
-    /// the rank is a tuple of (u8, u64) because we'll firstly compare the kind and then the value
-    /// which requires special handling e.g. with preceding the larger the value the smaller the
-    /// rank and also for 0 preceding / following it is the same as current row
+    /// the rank is a tuple of (u8, u8, u64) because we'll firstly compare the kind and then the
+    /// offset, whose kind is compared before its value which requires special handling e.g. with
+    /// preceding the larger the value the smaller the rank and also for 0 preceding / following
+    /// it is the same as current row
+    fn get_rank(&self) -> (u8, u8, u64) {
+        match self {
+            WindowFrameBound::Preceding(None) => (0, 0, 0),
+            WindowFrameBound::Following(None) => (4, 0, 0),
+            WindowFrameBound::CurrentRow => (2, 0, 0),
+            WindowFrameBound::Preceding(Some(v))
+            | WindowFrameBound::Following(Some(v))
+                if v.is_zero() =>
+            {
+                (2, 0, 0)
+            }
+            WindowFrameBound::Preceding(Some(v)) => {
+                let (kind, value) = v.get_rank();
+                (1, kind, u64::MAX - value)
+            }
+            WindowFrameBound::Following(Some(v)) => {
+                let (kind, value) = v.get_rank();
+                (3, kind, value)
+            }
+        }
+    }
+
+    /// whether the bound is `<interval> PRECEDING` or `<interval> FOLLOWING`
+    pub fn is_interval(&self) -> bool {
+        matches!(
+            self,
+            WindowFrameBound::Preceding(Some(v)) | WindowFrameBound::Following(Some(v))
+                if v.is_interval()
+        )
+    }
+
+    /// whether the order of the bounds is known, which is not the case of interval offsets
+    /// in months and in days on the same side of the current row (a month has 28 to 31 days)
+    fn is_comparable(&self, other: &Self) -> bool {
+        let (position, kind, _) = self.get_rank();
+        let (other_position, other_kind, _) = other.get_rank();
+        position != other_position || kind == other_kind
+    }
+}
+
+/// The offset of a `<expr> PRECEDING` or `<expr> FOLLOWING` frame bound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFrameOffset {
+    /// A number of rows for ROWS frames, of peer groups for GROUPS frames, or the difference
+    /// with the numeric value of the ORDER BY expression for RANGE frames
+    Value(u64),
+    /// A number of months, the difference with the date or timestamp value of the ORDER BY
+    /// expression of a RANGE frame
+    YearMonth(i32),
+    /// A number of days and milliseconds, packed like the values of
+    /// [`DataType::Interval(IntervalUnit::DayTime)`](arrow::datatypes::IntervalUnit::DayTime),
+    /// the difference with the date or timestamp value of the ORDER BY expression of a RANGE
+    /// frame
+    DayTime(i64),
+}
+
+/// The SQL parser only parses integer frame offsets, so `INTERVAL '...' PRECEDING` is rewritten
+/// into an integer with this bit set and the interval in the lower bits, see
+/// [`WindowFrameOffset::encode`]
+const INTERVAL_OFFSET: u64 = 1 << 63;
+/// Set with [`INTERVAL_OFFSET`] for intervals in months
+const YEAR_MONTH_OFFSET: u64 = 1 << 62;
+/// The largest numeric frame offset, as the larger integers encode intervals
+pub(crate) const MAX_OFFSET_VALUE: u64 = INTERVAL_OFFSET - 1;
+
+impl WindowFrameOffset {
+    /// The interval `value`, a [`ScalarValue::IntervalYearMonth`] or
+    /// [`ScalarValue::IntervalDayTime`] whose parts must not be negative, as an integer offset
+    /// the SQL parser accepts and [`WindowFrameOffset::decode`] turns back into an interval
+    pub(crate) fn encode(value: &ScalarValue) -> Result<u64> {
+        let encoded = match value {
+            ScalarValue::IntervalYearMonth(Some(months)) if *months >= 0 => {
+                Some(INTERVAL_OFFSET | YEAR_MONTH_OFFSET | *months as u64)
+            }
+            ScalarValue::IntervalDayTime(Some(value))
+                if *value >= 0
+                    && (*value as u64) < YEAR_MONTH_OFFSET
+                    && (*value as i32) >= 0 =>
+            {
+                Some(INTERVAL_OFFSET | *value as u64)
+            }
+            _ => None,
+        };
+        encoded.ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "Window frame offsets must be non-negative intervals, got {:?}",
+                value
+            ))
+        })
+    }
+
+    /// The offset parsed by the SQL parser as the integer `value`
+    fn decode(value: u64) -> Result<Self> {
+        if value & INTERVAL_OFFSET == 0 {
+            Ok(Self::Value(value))
+        } else if value & YEAR_MONTH_OFFSET != 0 {
+            let months = value & !(INTERVAL_OFFSET | YEAR_MONTH_OFFSET);
+            i32::try_from(months).map(Self::YearMonth).map_err(|_| {
+                DataFusionError::Plan(format!("Invalid window frame offset {}", value))
+            })
+        } else {
+            Ok(Self::DayTime((value & !INTERVAL_OFFSET) as i64))
+        }
+    }
+
+    /// whether the offset is zero, making the bound the same as the current row
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Self::Value(v) => *v == 0,
+            Self::YearMonth(v) => *v == 0,
+            Self::DayTime(v) => *v == 0,
+        }
+    }
+
+    /// whether the offset is an interval
+    pub fn is_interval(&self) -> bool {
+        !matches!(self, Self::Value(_))
+    }
+
+    /// the kind of the offset and an increasing value of the offset within its kind
     fn get_rank(&self) -> (u8, u64) {
         match self {
-            WindowFrameBound::Preceding(None) => (0, 0),
-            WindowFrameBound::Following(None) => (4, 0),
-            WindowFrameBound::Preceding(Some(0))
-            | WindowFrameBound::CurrentRow
-            | WindowFrameBound::Following(Some(0)) => (2, 0),
-            WindowFrameBound::Preceding(Some(v)) => (1, u64::MAX - *v),
-            WindowFrameBound::Following(Some(v)) => (3, *v),
+            Self::Value(v) => (0, *v),
+            Self::YearMonth(v) => (1, *v as u64),
+            // the days are in the upper 32 bits
+            Self::DayTime(v) => (2, *v as u64),
+        }
+    }
+}
+
+impl fmt::Display for WindowFrameOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Value(v) => write!(f, "{}", v),
+            Self::YearMonth(months) => write!(f, "INTERVAL '{} MONTH'", months),
+            Self::DayTime(v) => {
+                let (days, millis) = (*v >> 32, *v as i32);
+                match (days, millis) {
+                    (0, millis) => write!(f, "INTERVAL '{} MILLISECOND'", millis),
+                    (days, 0) => write!(f, "INTERVAL '{} DAY'", days),
+                    (days, millis) => {
+                        write!(f, "INTERVAL '{} DAY {} MILLISECOND'", days, millis)
+                    }
+                }
+            }
         }
     }
 }
@@ -292,10 +429,15 @@ mod tests {
             start_bound: ast::WindowFrameBound::Preceding(Some(2)),
             end_bound: Some(ast::WindowFrameBound::Preceding(Some(1))),
         };
-        let result = WindowFrame::try_from(window_frame);
+        let result = WindowFrame::try_from(window_frame)?;
+        assert_eq!(result.units, WindowFrameUnits::Range);
         assert_eq!(
-            result.err().unwrap().to_string(),
-            "This feature is not implemented: With WindowFrameUnits=RANGE, the bound cannot be 2 PRECEDING or FOLLOWING at the moment".to_owned()
+            result.start_bound,
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(2)))
+        );
+        assert_eq!(
+            result.end_bound,
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(1)))
         );
 
         let window_frame = ast::WindowFrame {
@@ -311,24 +453,24 @@ mod tests {
     #[test]
     fn test_eq() {
         assert_eq!(
-            WindowFrameBound::Preceding(Some(0)),
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(0))),
             WindowFrameBound::CurrentRow
         );
         assert_eq!(
             WindowFrameBound::CurrentRow,
-            WindowFrameBound::Following(Some(0))
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(0)))
         );
         assert_eq!(
-            WindowFrameBound::Following(Some(2)),
-            WindowFrameBound::Following(Some(2))
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(2))),
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(2)))
         );
         assert_eq!(
             WindowFrameBound::Following(None),
             WindowFrameBound::Following(None)
         );
         assert_eq!(
-            WindowFrameBound::Preceding(Some(2)),
-            WindowFrameBound::Preceding(Some(2))
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(2))),
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(2)))
         );
         assert_eq!(
             WindowFrameBound::Preceding(None),
@@ -338,35 +480,116 @@ mod tests {
 
     #[test]
     fn test_ord() {
-        assert!(WindowFrameBound::Preceding(Some(1)) < WindowFrameBound::CurrentRow);
+        assert!(
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(1)))
+                < WindowFrameBound::CurrentRow
+        );
         // ! yes this is correct!
         assert!(
-            WindowFrameBound::Preceding(Some(2)) < WindowFrameBound::Preceding(Some(1))
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(2)))
+                < WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(1)))
         );
         assert!(
-            WindowFrameBound::Preceding(Some(u64::MAX))
-                < WindowFrameBound::Preceding(Some(u64::MAX - 1))
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(u64::MAX)))
+                < WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(
+                    u64::MAX - 1
+                )))
         );
         assert!(
             WindowFrameBound::Preceding(None)
-                < WindowFrameBound::Preceding(Some(1000000))
+                < WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(1000000)))
         );
         assert!(
             WindowFrameBound::Preceding(None)
-                < WindowFrameBound::Preceding(Some(u64::MAX))
+                < WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(u64::MAX)))
         );
-        assert!(WindowFrameBound::Preceding(None) < WindowFrameBound::Following(Some(0)));
         assert!(
-            WindowFrameBound::Preceding(Some(1)) < WindowFrameBound::Following(Some(1))
+            WindowFrameBound::Preceding(None)
+                < WindowFrameBound::Following(Some(WindowFrameOffset::Value(0)))
         );
-        assert!(WindowFrameBound::CurrentRow < WindowFrameBound::Following(Some(1)));
         assert!(
-            WindowFrameBound::Following(Some(1)) < WindowFrameBound::Following(Some(2))
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::Value(1)))
+                < WindowFrameBound::Following(Some(WindowFrameOffset::Value(1)))
         );
-        assert!(WindowFrameBound::Following(Some(2)) < WindowFrameBound::Following(None));
         assert!(
-            WindowFrameBound::Following(Some(u64::MAX))
+            WindowFrameBound::CurrentRow
+                < WindowFrameBound::Following(Some(WindowFrameOffset::Value(1)))
+        );
+        assert!(
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(1)))
+                < WindowFrameBound::Following(Some(WindowFrameOffset::Value(2)))
+        );
+        assert!(
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(2)))
                 < WindowFrameBound::Following(None)
         );
+        assert!(
+            WindowFrameBound::Following(Some(WindowFrameOffset::Value(u64::MAX)))
+                < WindowFrameBound::Following(None)
+        );
+    }
+
+    #[test]
+    fn test_interval_offsets() -> Result<()> {
+        let day = ScalarValue::IntervalDayTime(Some(1 << 32));
+        let month = ScalarValue::IntervalYearMonth(Some(1));
+        let interval_frame = |units, start: &ScalarValue, end: &ScalarValue| {
+            Ok(ast::WindowFrame {
+                units,
+                start_bound: ast::WindowFrameBound::Preceding(Some(
+                    WindowFrameOffset::encode(start)?,
+                )),
+                end_bound: Some(ast::WindowFrameBound::Following(Some(
+                    WindowFrameOffset::encode(end)?,
+                ))),
+            }) as Result<_>
+        };
+
+        let window_frame = WindowFrame::try_from(interval_frame(
+            ast::WindowFrameUnits::Range,
+            &day,
+            &month,
+        )?)?;
+        assert_eq!(
+            window_frame.start_bound,
+            WindowFrameBound::Preceding(Some(WindowFrameOffset::DayTime(1 << 32)))
+        );
+        assert_eq!(
+            window_frame.end_bound,
+            WindowFrameBound::Following(Some(WindowFrameOffset::YearMonth(1)))
+        );
+        assert_eq!(
+            window_frame.to_string(),
+            "RANGE BETWEEN INTERVAL '1 DAY' PRECEDING AND INTERVAL '1 MONTH' FOLLOWING"
+        );
+
+        let result = WindowFrame::try_from(interval_frame(
+            ast::WindowFrameUnits::Rows,
+            &day,
+            &day,
+        )?);
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Error during planning: Invalid window frame: ROWS frames can't have interval offsets"
+        );
+
+        let result = WindowFrameOffset::encode(&ScalarValue::IntervalYearMonth(Some(-1)));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Error during planning: Window frame offsets must be non-negative intervals, got IntervalYearMonth(\"-1\")"
+        );
+
+        // 40 days preceding and one month preceding can't be compared
+        let window_frame = ast::WindowFrame {
+            units: ast::WindowFrameUnits::Range,
+            start_bound: ast::WindowFrameBound::Preceding(Some(
+                WindowFrameOffset::encode(&ScalarValue::IntervalDayTime(Some(40 << 32)))?,
+            )),
+            end_bound: Some(ast::WindowFrameBound::Preceding(Some(
+                WindowFrameOffset::encode(&month)?,
+            ))),
+        };
+        assert!(WindowFrame::try_from(window_frame).is_ok());
+        Ok(())
     }
 }
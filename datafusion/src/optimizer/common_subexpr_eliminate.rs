@@ -201,6 +201,7 @@ fn optimize(plan: &LogicalPlan, execution_props: &ExecutionProps) -> Result<Logi
         | LogicalPlan::CrossJoin(_)
         | LogicalPlan::Repartition(_)
         | LogicalPlan::Union(_)
+        | LogicalPlan::RecursiveQuery(_)
         | LogicalPlan::TableScan { .. }
         | LogicalPlan::Values(_)
         | LogicalPlan::EmptyRelation(_)
@@ -209,7 +210,12 @@ fn optimize(plan: &LogicalPlan, execution_props: &ExecutionProps) -> Result<Logi
         | LogicalPlan::Explain { .. }
         | LogicalPlan::Analyze { .. }
         | LogicalPlan::CreateMemoryTable(_)
+        | LogicalPlan::Insert(_)
+        | LogicalPlan::CopyTo(_)
         | LogicalPlan::DropTable(_)
+        | LogicalPlan::CreateView(_)
+        | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshTable(_)
         | LogicalPlan::Extension { .. } => {
             // apply the optimization to all inputs of the plan
             let expr = plan.expressions();
@@ -431,10 +437,17 @@ impl ExprIdentifierVisitor<'_> {
                 desc.push_str(&fun.to_string());
                 desc.push_str(&format!("{:?}", window_frame));
             }
-            Expr::AggregateFunction { fun, distinct, .. } => {
+            Expr::AggregateFunction {
+                fun,
+                distinct,
+                filter,
+                ..
+            } => {
                 desc.push_str("AggregateFunction-");
                 desc.push_str(&fun.to_string());
                 desc.push_str(&distinct.to_string());
+                // tells the filter apart from the arguments
+                desc.push_str(&filter.is_some().to_string());
             }
             Expr::AggregateUDF { fun, .. } => {
                 desc.push_str("AggregateUDF-");
@@ -444,9 +457,23 @@ impl ExprIdentifierVisitor<'_> {
                 desc.push_str("InList-");
                 desc.push_str(&negated.to_string());
             }
+            Expr::Exists { negated, .. } => {
+                desc.push_str("Exists-");
+                desc.push_str(&negated.to_string());
+            }
+            Expr::InSubquery { negated, .. } => {
+                desc.push_str("InSubquery-");
+                desc.push_str(&negated.to_string());
+            }
+            Expr::ScalarSubquery(_) => {
+                desc.push_str("ScalarSubquery-");
+            }
             Expr::Wildcard => {
                 desc.push_str("Wildcard-");
             }
+            Expr::GroupingSet(_) => {
+                desc.push_str("GroupingSet-");
+            }
             Expr::GetIndexedField { key, .. } => {
                 desc.push_str("GetIndexedField-");
                 desc.push_str(&key.to_string());
@@ -498,7 +525,11 @@ impl ExpressionVisitor for ExprIdentifierVisitor<'_> {
                 | Expr::ScalarVariable(..)
                 | Expr::Alias(..)
                 | Expr::Sort { .. }
+                | Expr::Exists { .. }
+                | Expr::InSubquery { .. }
+                | Expr::ScalarSubquery(_)
                 | Expr::Wildcard
+                | Expr::GroupingSet(_)
         ) {
             self.id_array[idx].0 = self.series_number;
             let desc = Self::desc_expr(expr);
@@ -359,7 +359,10 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
             utils::from_plan(plan, expr, &[new_input])
         }
         LogicalPlan::Aggregate(Aggregate {
-            aggr_expr, input, ..
+            aggr_expr,
+            group_expr,
+            input,
+            ..
         }) => {
             // An aggregate's aggreagate columns are _not_ filter-commutable => collect these:
             // * columns whose aggregation expression depends on
@@ -369,6 +372,21 @@ fn optimize(plan: &LogicalPlan, mut state: State) -> Result<LogicalPlan> {
             let mut used_columns = HashSet::new();
             utils::exprlist_to_columns(aggr_expr, &mut used_columns)?;
 
+            // with grouping sets, filtering the input would also filter the rows
+            // aggregated for the sets not grouping by the filtered columns
+            if group_expr
+                .iter()
+                .any(|expr| matches!(expr, Expr::GroupingSet(_)))
+            {
+                utils::exprlist_to_columns(group_expr, &mut used_columns)?;
+                used_columns.extend(
+                    plan.schema()
+                        .fields()
+                        .iter()
+                        .map(|field| field.qualified_column()),
+                );
+            }
+
             let agg_columns = aggr_expr
                 .iter()
                 .map(|x| Ok(Column::from_name(x.name(input.schema())?)))
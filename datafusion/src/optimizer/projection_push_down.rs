@@ -25,7 +25,7 @@ use crate::logical_plan::plan::{
 };
 use crate::logical_plan::{
     build_join_schema, Column, DFField, DFSchema, DFSchemaRef, LogicalPlan,
-    LogicalPlanBuilder, ToDFSchema, Union,
+    LogicalPlanBuilder, ToDFSchema, Union, GROUPING_ID_COLUMN,
 };
 use crate::optimizer::optimizer::OptimizerRule;
 use crate::optimizer::utils;
@@ -306,11 +306,15 @@ fn optimize_plan(
                 }
             })?;
 
+            // the grouping id of grouping sets is always produced
             let new_schema = DFSchema::new(
                 schema
                     .fields()
                     .iter()
-                    .filter(|x| new_required_columns.contains(&x.qualified_column()))
+                    .filter(|x| {
+                        new_required_columns.contains(&x.qualified_column())
+                            || (x.qualifier().is_none() && x.name() == GROUPING_ID_COLUMN)
+                    })
                     .cloned()
                     .collect(),
             )?;
@@ -426,6 +430,34 @@ fn optimize_plan(
                 alias: alias.clone(),
             }))
         }
+        LogicalPlan::RecursiveQuery(_)
+        | LogicalPlan::Insert(_)
+        | LogicalPlan::CopyTo(_) => {
+            // the terms of a recursive query and the input of an insert or a
+            // copy produce all of their columns, whether they are required
+            // or not
+            let new_inputs = plan
+                .inputs()
+                .into_iter()
+                .map(|term| {
+                    let required_columns = term
+                        .schema()
+                        .fields()
+                        .iter()
+                        .map(|f| f.qualified_column())
+                        .collect();
+                    optimize_plan(
+                        optimizer,
+                        term,
+                        &required_columns,
+                        has_projection,
+                        execution_props,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            utils::from_plan(plan, &[], &new_inputs)
+        }
         // all other nodes: Add any additional columns used by
         // expressions in this node to the list of required columns
         LogicalPlan::Limit(_)
@@ -437,6 +469,9 @@ fn optimize_plan(
         | LogicalPlan::CreateExternalTable(_)
         | LogicalPlan::CreateMemoryTable(_)
         | LogicalPlan::DropTable(_)
+        | LogicalPlan::CreateView(_)
+        | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshTable(_)
         | LogicalPlan::CrossJoin(_)
         | LogicalPlan::Extension { .. } => {
             let expr = plan.expressions();
@@ -342,6 +342,10 @@ impl ConstEvaluator {
             Expr::WindowFunction { .. } => false,
             Expr::Sort { .. } => false,
             Expr::Wildcard => false,
+            Expr::GroupingSet(_) => false,
+            Expr::Exists { .. } => false,
+            Expr::InSubquery { .. } => false,
+            Expr::ScalarSubquery(_) => false,
 
             Expr::Literal(_) => true,
             Expr::BinaryExpr { .. } => true,
@@ -78,6 +78,8 @@ fn optimize(plan: &LogicalPlan) -> Result<LogicalPlan> {
                                 fun: fun.clone(),
                                 args: vec![col(SINGLE_DISTINCT_ALIAS)],
                                 distinct: false,
+                                filter: None,
+                                order_by: vec![],
                             }
                         }
                         _ => agg_expr.clone(),
@@ -154,15 +156,34 @@ fn optimize_children(plan: &LogicalPlan) -> Result<LogicalPlan> {
 fn is_single_distinct_agg(plan: &LogicalPlan) -> bool {
     match plan {
         LogicalPlan::Aggregate(Aggregate {
-            input, aggr_expr, ..
+            input,
+            aggr_expr,
+            group_expr,
+            ..
         }) => {
+            // the grouping sets would have to group by the distinct argument too
+            if group_expr
+                .iter()
+                .any(|expr| matches!(expr, Expr::GroupingSet(_)))
+            {
+                return false;
+            }
             let mut fields_set = HashSet::new();
             aggr_expr
                 .iter()
                 .filter(|expr| {
                     let mut is_distinct = false;
-                    if let Expr::AggregateFunction { distinct, args, .. } = expr {
-                        is_distinct = *distinct;
+                    if let Expr::AggregateFunction {
+                        distinct,
+                        args,
+                        filter,
+                        order_by,
+                        ..
+                    } = expr
+                    {
+                        // the rewrite would lose the filter and the ordering
+                        is_distinct =
+                            *distinct && filter.is_none() && order_by.is_empty();
                         args.iter().for_each(|expr| {
                             fields_set.insert(expr.name(input.schema()).unwrap());
                         })
@@ -307,6 +328,8 @@ mod tests {
                         fun: aggregates::AggregateFunction::Max,
                         distinct: true,
                         args: vec![col("b")],
+                        filter: None,
+                        order_by: vec![],
                     },
                 ],
             )?
@@ -0,0 +1,1462 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Optimizer rule rewriting subquery expressions into joins
+
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionProps;
+use crate::logical_plan::plan::{Aggregate, EmptyRelation, Filter, Projection, Sort};
+use crate::logical_plan::{
+    binary_expr, combine_filters, count, create_udaf, lit, replace_col, sum, when,
+    Column, DFSchema, Expr, ExprRewriter, ExpressionVisitor, JoinType, LogicalPlan,
+    LogicalPlanBuilder, Operator, Recursion, Subquery,
+};
+use crate::optimizer::optimizer::OptimizerRule;
+use crate::optimizer::utils;
+use crate::physical_plan::aggregates::AccumulatorFunctionImplementation;
+use crate::physical_plan::functions::Volatility;
+use crate::physical_plan::Accumulator;
+use crate::scalar::ScalarValue;
+use arrow::datatypes::DataType;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::iter;
+use std::sync::Arc;
+
+/// Optimizer rule rewriting `EXISTS`, `IN` and scalar subqueries into joins,
+/// so that they run on the existing join operators:
+///
+/// * `[NOT] EXISTS (<subquery>)` and `<column> [NOT] IN (<subquery>)`
+///   conjuncts of a filter become semi (anti) joins with the subquery.
+///   `NOT IN` is then filtered with the number of NULL values of the
+///   subquery, so that it is NULL rather than true when the subquery
+///   produces NULL values or the column is NULL
+/// * scalar subqueries in filters and projections become cross joins with
+///   the subquery when it is uncorrelated, checked to produce at most one
+///   row, and left joins with the subquery aggregated per correlation key
+///   otherwise. Outer rows without a correlated row get the value of the
+///   aggregate of no rows (e.g. 0 for `COUNT`)
+///
+/// A subquery is decorrelated by pulling the predicates comparing its
+/// columns with columns of the outer query up into the join keys. For
+/// example
+///
+/// ```text
+/// SELECT * FROM orders WHERE EXISTS
+///   (SELECT * FROM lineitem WHERE l_orderkey = o_orderkey AND l_quantity > 10)
+/// ```
+///
+/// is planned as
+///
+/// ```text
+/// Semi Join: #orders.o_orderkey = #__sq_1.__key_1
+///   TableScan: orders
+///   Projection: #lineitem.l_orderkey AS __value, #lineitem.l_orderkey AS __key_1, alias=__sq_1
+///     Filter: #lineitem.l_quantity > Int64(10)
+///       TableScan: lineitem
+/// ```
+///
+/// The other correlated predicates of an `EXISTS` subquery, such as
+/// `l2.l_suppkey <> l1.l_suppkey`, are evaluated by joining the subquery
+/// with the distinct values of the outer columns they reference.
+///
+/// Limitations:
+///
+/// * correlated predicates of `IN` and scalar subqueries must be equalities
+///   between a column of the subquery and a column of the outer query
+/// * correlated scalar subqueries must be aggregates without `GROUP BY`
+pub struct SubqueryDecorrelation {}
+
+/// Name of the column holding the value of a decorrelated subquery
+const VALUE_COLUMN: &str = "__value";
+
+/// Name of the aggregate returning the value of the single row of an
+/// uncorrelated scalar subquery
+const SINGLE_VALUE: &str = "__single_value";
+
+impl SubqueryDecorrelation {
+    #[allow(missing_docs)]
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OptimizerRule for SubqueryDecorrelation {
+    fn optimize(
+        &self,
+        plan: &LogicalPlan,
+        _execution_props: &ExecutionProps,
+    ) -> Result<LogicalPlan> {
+        decorrelate(plan, &mut 0)
+    }
+
+    fn name(&self) -> &str {
+        "subquery_decorrelation"
+    }
+}
+
+/// Rewrites the subqueries of `plan` and its inputs into joins. Every joined
+/// subquery is aliased as `__sq_<n>`, numbered with `next_alias`.
+fn decorrelate(plan: &LogicalPlan, next_alias: &mut usize) -> Result<LogicalPlan> {
+    match plan {
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            let input = decorrelate(input, next_alias)?;
+            decorrelate_filter(predicate, input, next_alias)
+        }
+        LogicalPlan::Projection(Projection {
+            expr, input, alias, ..
+        }) if expr.iter().any(contains_subquery) => {
+            let input = decorrelate(input, next_alias)?;
+            let input_schema = input.schema().clone();
+            let mut rewriter = ScalarSubqueryRewriter {
+                plan: input,
+                next_alias,
+            };
+            let expr = expr
+                .iter()
+                .map(|e| {
+                    if !contains_subquery(e) {
+                        return Ok(e.clone());
+                    }
+                    // keep the name of the projected expression
+                    let name = e.name(&input_schema)?;
+                    Ok(match e.clone().rewrite(&mut rewriter)? {
+                        rewritten @ Expr::Alias(..) => rewritten,
+                        rewritten => rewritten.alias(&name),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            LogicalPlanBuilder::from(rewriter.plan)
+                .project_with_alias(expr, alias.clone())?
+                .build()
+        }
+        _ => {
+            let expr = plan.expressions();
+            if expr.iter().any(contains_subquery) {
+                return Err(DataFusionError::NotImplemented(
+                    "Subqueries are only supported in filters and projections"
+                        .to_string(),
+                ));
+            }
+
+            let inputs = plan
+                .inputs()
+                .into_iter()
+                .map(|input| decorrelate(input, next_alias))
+                .collect::<Result<Vec<_>>>()?;
+
+            utils::from_plan(plan, &expr, &inputs)
+        }
+    }
+}
+
+/// Rewrites the subqueries in the conjuncts of `predicate` into joins with
+/// `input` and filters the result with the remaining conjuncts
+fn decorrelate_filter(
+    predicate: &Expr,
+    input: LogicalPlan,
+    next_alias: &mut usize,
+) -> Result<LogicalPlan> {
+    let mut conjuncts = vec![];
+    split_conjunction(predicate, &mut conjuncts);
+    if !conjuncts.iter().any(|conjunct| contains_subquery(conjunct)) {
+        return Ok(LogicalPlan::Filter(Filter {
+            predicate: predicate.clone(),
+            input: Arc::new(input),
+        }));
+    }
+
+    // the conjuncts without subqueries filter the input before it is joined
+    // with the subqueries
+    let (conjuncts, plain_conjuncts): (Vec<_>, Vec<_>) = conjuncts
+        .into_iter()
+        .partition(|conjunct| contains_subquery(conjunct));
+    let plain_conjuncts = plain_conjuncts.into_iter().cloned().collect::<Vec<_>>();
+    let schema = input.schema().clone();
+    let mut plan = match combine_filters(&plain_conjuncts) {
+        Some(predicate) => LogicalPlanBuilder::from(input).filter(predicate)?.build()?,
+        None => input,
+    };
+    let mut predicates = vec![];
+    for conjunct in conjuncts {
+        match negate_subquery(conjunct) {
+            Expr::Exists { subquery, negated } => {
+                let (joined, predicate) =
+                    exists_to_join(plan, &subquery, negated, next_alias)?;
+                plan = joined;
+                predicates.extend(predicate);
+            }
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => {
+                let (joined, predicate) =
+                    in_subquery_to_join(plan, &expr, &subquery, negated, next_alias)?;
+                plan = joined;
+                predicates.extend(predicate);
+            }
+            conjunct => {
+                let mut rewriter = ScalarSubqueryRewriter {
+                    plan,
+                    next_alias: &mut *next_alias,
+                };
+                predicates.push(conjunct.rewrite(&mut rewriter)?);
+                plan = rewriter.plan;
+            }
+        }
+    }
+
+    if let Some(predicate) = combine_filters(&predicates) {
+        plan = LogicalPlanBuilder::from(plan).filter(predicate)?.build()?;
+    }
+
+    // drop the columns of the subqueries joined to the input
+    if plan.schema() == &schema {
+        Ok(plan)
+    } else {
+        let expr = schema
+            .fields()
+            .iter()
+            .map(|field| Expr::Column(field.qualified_column()));
+        LogicalPlanBuilder::from(plan).project(expr)?.build()
+    }
+}
+
+/// Folds a `NOT` into the `EXISTS` or `IN` subquery it is applied to
+fn negate_subquery(expr: &Expr) -> Expr {
+    match expr {
+        Expr::Not(negated_expr) => match negated_expr.as_ref() {
+            Expr::Exists { subquery, negated } => Expr::Exists {
+                subquery: subquery.clone(),
+                negated: !negated,
+            },
+            Expr::InSubquery {
+                expr,
+                subquery,
+                negated,
+            } => Expr::InSubquery {
+                expr: expr.clone(),
+                subquery: subquery.clone(),
+                negated: !negated,
+            },
+            _ => expr.clone(),
+        },
+        _ => expr.clone(),
+    }
+}
+
+/// Joins `plan` with an `EXISTS` subquery, returning the joined plan and the
+/// predicate it must then be filtered with, if any
+fn exists_to_join(
+    plan: LogicalPlan,
+    subquery: &Subquery,
+    negated: bool,
+    next_alias: &mut usize,
+) -> Result<(LogicalPlan, Option<Expr>)> {
+    let outer_schema = plan.schema().clone();
+    let subquery = decorrelate_subquery(subquery, &outer_schema, next_alias)?;
+
+    if !subquery.residuals.is_empty() {
+        let plan = exists_with_residuals_to_join(plan, subquery, negated, next_alias)?;
+        Ok((plan, None))
+    } else if subquery.correlations.is_empty() {
+        // whether the subquery produces any row is computed once and
+        // compared for every row of the outer query
+        let count_plan = LogicalPlanBuilder::from(subquery.plan)
+            .limit(1)?
+            .aggregate(iter::empty::<Expr>(), vec![count(lit(1u8))])?
+            .build()?;
+        let value = Expr::Column(count_plan.schema().field(0).qualified_column());
+        let subquery =
+            alias_subquery(count_plan, value, &[], &[], &outer_schema, next_alias)?;
+
+        let plan = LogicalPlanBuilder::from(plan)
+            .cross_join(&subquery.plan)?
+            .build()?;
+        let op = if negated { Operator::Eq } else { Operator::Gt };
+        let predicate = binary_expr(Expr::Column(subquery.value), op, lit(0u64));
+        Ok((plan, Some(predicate)))
+    } else {
+        let subquery = alias_subquery(
+            subquery.plan,
+            Expr::Column(subquery.value),
+            &subquery.correlations,
+            &[],
+            &outer_schema,
+            next_alias,
+        )?;
+
+        let join_type = if negated {
+            JoinType::Anti
+        } else {
+            JoinType::Semi
+        };
+        let plan = LogicalPlanBuilder::from(plan)
+            .join(
+                &subquery.plan,
+                join_type,
+                (subquery.outer_keys, subquery.inner_keys),
+            )?
+            .build()?;
+        Ok((plan, None))
+    }
+}
+
+/// Joins `plan` with an `EXISTS` subquery whose correlated predicates are not
+/// all equalities. The distinct values of the outer columns referenced by
+/// the correlated predicates for which the subquery produces a row are
+/// computed first, by joining them with the subquery, and `plan` is then
+/// semi (anti) joined with them.
+fn exists_with_residuals_to_join(
+    plan: LogicalPlan,
+    subquery: DecorrelatedSubquery,
+    negated: bool,
+    next_alias: &mut usize,
+) -> Result<LogicalPlan> {
+    let outer_schema = plan.schema().clone();
+    let inner_columns = columns_in(&subquery.residuals, subquery.plan.schema())?;
+    let mut outer_columns: Vec<Column> = vec![];
+    let correlated_columns = subquery
+        .correlations
+        .iter()
+        .map(|(outer, _)| outer.clone())
+        .chain(columns_in(&subquery.residuals, &outer_schema)?);
+    for column in correlated_columns {
+        if !inner_columns.contains(&column) && !outer_columns.contains(&column) {
+            outer_columns.push(column);
+        }
+    }
+
+    let residuals = subquery.residuals;
+    let subquery = alias_subquery(
+        subquery.plan,
+        Expr::Column(subquery.value),
+        &subquery.correlations,
+        &inner_columns,
+        &outer_schema,
+        next_alias,
+    )?;
+    let replace_map = inner_columns
+        .iter()
+        .zip(subquery.columns.iter())
+        .collect::<HashMap<_, _>>();
+    let residuals = residuals
+        .into_iter()
+        .map(|residual| replace_col(residual, &replace_map))
+        .collect::<Result<Vec<_>>>()?;
+
+    let outer_values = LogicalPlanBuilder::from(plan.clone())
+        .project(outer_columns.iter().cloned().map(Expr::Column))?;
+    let matches = if subquery.outer_keys.is_empty() {
+        outer_values.cross_join(&subquery.plan)?
+    } else {
+        outer_values.join(
+            &subquery.plan,
+            JoinType::Inner,
+            (subquery.outer_keys, subquery.inner_keys),
+        )?
+    };
+    let matches = match combine_filters(&residuals) {
+        Some(predicate) => matches.filter(predicate)?,
+        None => matches,
+    };
+    let matches = matches
+        .aggregate(
+            outer_columns.iter().cloned().map(Expr::Column),
+            iter::empty::<Expr>(),
+        )?
+        .build()?;
+
+    // the matching values are projected under a new alias, as they have the
+    // names of the columns of `plan`
+    *next_alias += 1;
+    let alias = format!("__sq_{}", next_alias);
+    let (expr, inner_keys): (Vec<_>, Vec<_>) = outer_columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let name = format!("__key_{}", i + 1);
+            let key = Column {
+                relation: Some(alias.clone()),
+                name: name.clone(),
+            };
+            (Expr::Column(column.clone()).alias(&name), key)
+        })
+        .unzip();
+    let matches = LogicalPlanBuilder::from(matches)
+        .project_with_alias(expr, Some(alias))?
+        .build()?;
+
+    let join_type = if negated {
+        JoinType::Anti
+    } else {
+        JoinType::Semi
+    };
+    LogicalPlanBuilder::from(plan)
+        .join(&matches, join_type, (outer_columns, inner_keys))?
+        .build()
+}
+
+/// Joins `plan` with an `<expr> IN (<subquery>)` subquery, returning the
+/// joined plan and the predicate it must then be filtered with, if any
+fn in_subquery_to_join(
+    plan: LogicalPlan,
+    expr: &Expr,
+    subquery: &Subquery,
+    negated: bool,
+    next_alias: &mut usize,
+) -> Result<(LogicalPlan, Option<Expr>)> {
+    let column = match expr {
+        Expr::Column(column) => column.clone(),
+        _ => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "IN subqueries are only supported on columns, not on {:?}",
+                expr
+            )))
+        }
+    };
+
+    let outer_schema = plan.schema().clone();
+    let value_type = expr.get_type(&outer_schema)?;
+    let subquery = decorrelate_subquery(subquery, &outer_schema, next_alias)?;
+    if !subquery.residuals.is_empty() {
+        return Err(unsupported_residuals(&subquery.residuals));
+    }
+
+    // `<expr> NOT IN (<subquery>)` is NULL rather than true when `<expr>` is
+    // NULL or the subquery produces a NULL value, unless it produces no row
+    let null_check_input = if negated
+        && (expr.nullable(&outer_schema)?
+            || subquery
+                .plan
+                .schema()
+                .field_from_column(&subquery.value)?
+                .is_nullable())
+    {
+        Some(subquery.plan.clone())
+    } else {
+        None
+    };
+
+    let value = Expr::Column(subquery.value.clone())
+        .cast_to(&value_type, subquery.plan.schema())?;
+    let aliased = alias_subquery(
+        subquery.plan,
+        value,
+        &subquery.correlations,
+        &[],
+        &outer_schema,
+        next_alias,
+    )?;
+
+    let join_type = if negated {
+        JoinType::Anti
+    } else {
+        JoinType::Semi
+    };
+    let left_keys = iter::once(column)
+        .chain(aliased.outer_keys)
+        .collect::<Vec<_>>();
+    let right_keys = iter::once(aliased.value)
+        .chain(aliased.inner_keys)
+        .collect::<Vec<_>>();
+    let plan = LogicalPlanBuilder::from(plan)
+        .join(&aliased.plan, join_type, (left_keys, right_keys))?
+        .build()?;
+
+    match null_check_input {
+        Some(input) => {
+            let nulls = null_count_subquery(
+                input,
+                subquery.value,
+                &subquery.correlations,
+                &outer_schema,
+                next_alias,
+            )?;
+            let builder = LogicalPlanBuilder::from(plan);
+            let builder = if nulls.outer_keys.is_empty() {
+                builder.cross_join(&nulls.plan)?
+            } else {
+                builder.join(
+                    &nulls.plan,
+                    JoinType::Left,
+                    (nulls.outer_keys, nulls.inner_keys),
+                )?
+            };
+            let null_count = Expr::Column(nulls.value);
+            let predicate = null_count
+                .clone()
+                .is_null()
+                .or(null_count.eq(lit(0u64)).and(expr.clone().is_not_null()));
+            Ok((builder.build()?, Some(predicate)))
+        }
+        None => Ok((plan, None)),
+    }
+}
+
+/// Aggregates the number of NULL values produced by a decorrelated subquery
+/// per correlation key. The count is NULL when the subquery produces no row.
+fn null_count_subquery(
+    plan: LogicalPlan,
+    value: Column,
+    correlations: &[(Column, Column)],
+    outer_schema: &DFSchema,
+    next_alias: &mut usize,
+) -> Result<AliasedSubquery> {
+    let mut group_expr = vec![];
+    for (_, inner) in correlations {
+        let key = Expr::Column(inner.clone());
+        if !group_expr.contains(&key) {
+            group_expr.push(key);
+        }
+    }
+    let null_count =
+        sum(when(Expr::Column(value).is_null(), lit(1u64)).otherwise(lit(0u64))?);
+    let plan = LogicalPlanBuilder::from(plan)
+        .aggregate(group_expr, vec![null_count])?
+        .build()?;
+    let null_count =
+        Expr::Column(plan.schema().fields().last().unwrap().qualified_column());
+    alias_subquery(
+        plan,
+        null_count,
+        correlations,
+        &[],
+        outer_schema,
+        next_alias,
+    )
+}
+
+/// Joins `plan` with a scalar subquery, returning the joined plan and the
+/// expression of the value of the subquery
+fn scalar_subquery_to_join(
+    plan: LogicalPlan,
+    subquery: &Subquery,
+    next_alias: &mut usize,
+) -> Result<(LogicalPlan, Expr)> {
+    let outer_schema = plan.schema().clone();
+    let subquery = decorrelate_subquery(subquery, &outer_schema, next_alias)?;
+    if !subquery.residuals.is_empty() {
+        return Err(unsupported_residuals(&subquery.residuals));
+    }
+
+    if subquery.correlations.is_empty() {
+        let (subquery_plan, value) = if produces_one_row(&subquery.plan) {
+            (subquery.plan, Expr::Column(subquery.value))
+        } else {
+            // the subquery must produce at most one row, and its value is
+            // NULL when it produces none
+            let data_type = subquery
+                .plan
+                .schema()
+                .field_from_column(&subquery.value)?
+                .data_type()
+                .clone();
+            let single_row_plan = LogicalPlanBuilder::from(subquery.plan)
+                .limit(2)?
+                .aggregate(
+                    iter::empty::<Expr>(),
+                    vec![single_value(Expr::Column(subquery.value), data_type)],
+                )?
+                .build()?;
+            let value =
+                Expr::Column(single_row_plan.schema().field(0).qualified_column());
+            (single_row_plan, value)
+        };
+        let subquery =
+            alias_subquery(subquery_plan, value, &[], &[], &outer_schema, next_alias)?;
+        let plan = LogicalPlanBuilder::from(plan)
+            .cross_join(&subquery.plan)?
+            .build()?;
+        return Ok((plan, Expr::Column(subquery.value)));
+    }
+
+    if !subquery.aggregated {
+        return Err(DataFusionError::NotImplemented(
+            "Correlated scalar subqueries must be aggregates without GROUP BY"
+                .to_string(),
+        ));
+    }
+
+    let aliased = alias_subquery(
+        subquery.plan,
+        Expr::Column(subquery.value),
+        &subquery.correlations,
+        &[],
+        &outer_schema,
+        next_alias,
+    )?;
+    let unmatched = Expr::Column(aliased.inner_keys[0].clone()).is_null();
+    let builder = LogicalPlanBuilder::from(plan).join(
+        &aliased.plan,
+        JoinType::Left,
+        (aliased.outer_keys, aliased.inner_keys),
+    )?;
+    let value = Expr::Column(aliased.value);
+
+    match subquery.no_rows_plan {
+        // the outer rows without correlated rows get the value of the
+        // subquery over no rows, e.g. 0 for COUNT
+        Some(no_rows_plan) => {
+            let no_rows_value =
+                Expr::Column(no_rows_plan.schema().field(0).qualified_column());
+            let no_rows = alias_subquery(
+                no_rows_plan,
+                no_rows_value,
+                &[],
+                &[],
+                &outer_schema,
+                next_alias,
+            )?;
+            let value = when(unmatched, Expr::Column(no_rows.value)).otherwise(value)?;
+            Ok((builder.cross_join(&no_rows.plan)?.build()?, value))
+        }
+        None => Ok((builder.build()?, value)),
+    }
+}
+
+/// Whether `plan` always produces exactly one row
+fn produces_one_row(plan: &LogicalPlan) -> bool {
+    match plan {
+        LogicalPlan::Aggregate(Aggregate { group_expr, .. }) => group_expr.is_empty(),
+        LogicalPlan::EmptyRelation(EmptyRelation {
+            produce_one_row, ..
+        }) => *produce_one_row,
+        LogicalPlan::Projection(Projection { input, .. })
+        | LogicalPlan::Sort(Sort { input, .. }) => produces_one_row(input),
+        _ => false,
+    }
+}
+
+/// Replaces the input of the aggregation without grouping of `plan`, under
+/// its projections and sorts, with an empty relation
+fn over_no_rows(plan: &LogicalPlan) -> Result<Option<LogicalPlan>> {
+    match plan {
+        LogicalPlan::Aggregate(Aggregate {
+            input, group_expr, ..
+        }) if group_expr.is_empty() => {
+            let input = LogicalPlan::EmptyRelation(EmptyRelation {
+                produce_one_row: false,
+                schema: input.schema().clone(),
+            });
+            Ok(Some(utils::from_plan(plan, &plan.expressions(), &[input])?))
+        }
+        LogicalPlan::Projection(Projection { input, .. })
+        | LogicalPlan::Sort(Sort { input, .. }) => match over_no_rows(input)? {
+            Some(input) => {
+                Ok(Some(utils::from_plan(plan, &plan.expressions(), &[input])?))
+            }
+            None => Ok(None),
+        },
+        _ => Ok(None),
+    }
+}
+
+/// An aggregate returning the value of the single row it is fed, NULL when
+/// it is fed none, and failing when it is fed more than one row
+fn single_value(expr: Expr, data_type: DataType) -> Expr {
+    let return_type = Arc::new(data_type.clone());
+    let state_type = Arc::new(vec![data_type.clone(), DataType::UInt64]);
+    let accumulator_type = data_type.clone();
+    let accumulator: AccumulatorFunctionImplementation = Arc::new(move || {
+        Ok(Box::new(SingleValueAccumulator {
+            value: ScalarValue::try_from(&accumulator_type)?,
+            rows: 0,
+        }))
+    });
+    create_udaf(
+        SINGLE_VALUE,
+        data_type,
+        return_type,
+        Volatility::Immutable,
+        accumulator,
+        state_type,
+    )
+    .call(vec![expr])
+}
+
+/// Accumulates the value of the single row of an uncorrelated scalar
+/// subquery
+#[derive(Debug)]
+struct SingleValueAccumulator {
+    value: ScalarValue,
+    rows: u64,
+}
+
+impl SingleValueAccumulator {
+    fn add(&mut self, value: &ScalarValue, rows: u64) -> Result<()> {
+        self.rows += rows;
+        if self.rows > 1 {
+            return Err(DataFusionError::Execution(
+                "More than one row returned by a subquery used as an expression"
+                    .to_string(),
+            ));
+        }
+        self.value = value.clone();
+        Ok(())
+    }
+}
+
+impl Accumulator for SingleValueAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![
+            self.value.clone(),
+            ScalarValue::UInt64(Some(self.rows)),
+        ])
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.add(&values[0], 1)
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        match &states[1] {
+            ScalarValue::UInt64(Some(rows)) if *rows > 0 => self.add(&states[0], *rows),
+            _ => Ok(()),
+        }
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(self.value.clone())
+    }
+}
+
+/// Replaces the scalar subqueries of an expression with the columns of the
+/// subqueries joined to `plan`
+struct ScalarSubqueryRewriter<'a> {
+    plan: LogicalPlan,
+    next_alias: &'a mut usize,
+}
+
+impl ExprRewriter for ScalarSubqueryRewriter<'_> {
+    fn mutate(&mut self, expr: Expr) -> Result<Expr> {
+        match expr {
+            Expr::ScalarSubquery(subquery) => {
+                let (plan, value) =
+                    scalar_subquery_to_join(self.plan.clone(), &subquery, self.next_alias)?;
+                self.plan = plan;
+                Ok(value)
+            }
+            Expr::Exists { .. } | Expr::InSubquery { .. } => {
+                Err(DataFusionError::NotImplemented(
+                    "EXISTS and IN subqueries are only supported as conjuncts of a filter"
+                        .to_string(),
+                ))
+            }
+            expr => Ok(expr),
+        }
+    }
+}
+
+/// A subquery whose correlated predicates were pulled out of its plan
+struct DecorrelatedSubquery {
+    /// The plan of the subquery, which no longer references the outer query
+    plan: LogicalPlan,
+    /// The column holding the value of the subquery
+    value: Column,
+    /// Pairs of (outer query, subquery) columns that must be equal
+    correlations: Vec<(Column, Column)>,
+    /// The other correlated predicates, referencing columns of both the
+    /// outer query and `plan`
+    residuals: Vec<Expr>,
+    /// Whether the subquery is an aggregate without grouping, which produces
+    /// one row per correlation key
+    aggregated: bool,
+    /// The plan of the aggregate subquery over no rows, which produces its
+    /// value for the outer rows without correlated rows
+    no_rows_plan: Option<LogicalPlan>,
+}
+
+fn decorrelate_subquery(
+    subquery: &Subquery,
+    outer_schema: &DFSchema,
+    next_alias: &mut usize,
+) -> Result<DecorrelatedSubquery> {
+    // subqueries nested in the subquery are rewritten first
+    let plan = decorrelate(&subquery.subquery, next_alias)?;
+    // the correlation keys are added to the output of the subquery, as its
+    // last columns unless it is an aggregate
+    let value = plan.schema().field(0).qualified_column();
+
+    let mut correlations = vec![];
+    let mut residuals = vec![];
+    let mut aggregated = false;
+    let decorrelated_plan = pull_up_correlations(
+        &plan,
+        outer_schema,
+        &mut correlations,
+        &mut residuals,
+        &mut aggregated,
+    )?;
+    if has_outer_references(&decorrelated_plan)? {
+        return Err(DataFusionError::NotImplemented(
+            "Correlated subqueries may only reference the outer query in the \
+            predicates of their WHERE clause"
+                .to_string(),
+        ));
+    }
+    let no_rows_plan = if aggregated {
+        over_no_rows(&plan)?
+    } else {
+        None
+    };
+
+    Ok(DecorrelatedSubquery {
+        plan: decorrelated_plan,
+        value,
+        correlations,
+        residuals,
+        aggregated,
+        no_rows_plan,
+    })
+}
+
+/// Removes the conjuncts of the filters of `plan` referencing columns of
+/// `outer_schema`, collecting the equalities of one of its columns with a
+/// column of `outer_schema` into `correlations` and the others into
+/// `residuals`, and adds the subquery columns they reference to the output
+/// of `plan` so that it can be joined on them.
+///
+/// Aggregations are grouped by the subquery columns of `correlations` too;
+/// `aggregated` is set when the last of them had no other grouping.
+fn pull_up_correlations(
+    plan: &LogicalPlan,
+    outer_schema: &DFSchema,
+    correlations: &mut Vec<(Column, Column)>,
+    residuals: &mut Vec<Expr>,
+    aggregated: &mut bool,
+) -> Result<LogicalPlan> {
+    match plan {
+        LogicalPlan::Filter(Filter { predicate, input }) => {
+            let input = pull_up_correlations(
+                input,
+                outer_schema,
+                correlations,
+                residuals,
+                aggregated,
+            )?;
+            let input_schema = input.schema();
+
+            let mut conjuncts = vec![];
+            split_conjunction(predicate, &mut conjuncts);
+            let mut predicates = vec![];
+            for conjunct in conjuncts {
+                let mut columns = HashSet::new();
+                utils::expr_to_columns(conjunct, &mut columns)?;
+                if columns
+                    .iter()
+                    .all(|column| input_schema.field_from_column(column).is_ok())
+                {
+                    predicates.push(conjunct.clone());
+                    continue;
+                }
+
+                let correlation = match conjunct {
+                    Expr::BinaryExpr {
+                        left,
+                        op: Operator::Eq,
+                        right,
+                    } => match (left.as_ref(), right.as_ref()) {
+                        (Expr::Column(l), Expr::Column(r)) => {
+                            if input_schema.field_from_column(l).is_ok()
+                                && outer_schema.field_from_column(r).is_ok()
+                            {
+                                Some((r.clone(), l.clone()))
+                            } else if input_schema.field_from_column(r).is_ok()
+                                && outer_schema.field_from_column(l).is_ok()
+                            {
+                                Some((l.clone(), r.clone()))
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match correlation {
+                    Some(correlation) => correlations.push(correlation),
+                    None => residuals.push(conjunct.clone()),
+                }
+            }
+
+            match combine_filters(&predicates) {
+                Some(predicate) => Ok(LogicalPlan::Filter(Filter {
+                    predicate,
+                    input: Arc::new(input),
+                })),
+                None => Ok(input),
+            }
+        }
+        LogicalPlan::Projection(Projection {
+            expr, input, alias, ..
+        }) => {
+            let input = pull_up_correlations(
+                input,
+                outer_schema,
+                correlations,
+                residuals,
+                aggregated,
+            )?;
+            if correlations.is_empty() && residuals.is_empty() {
+                return utils::from_plan(plan, &plan.expressions(), &[input]);
+            }
+
+            let mut expr = expr.clone();
+            for (_, inner) in correlations.iter_mut() {
+                let key = Expr::Column(inner.clone());
+                if !expr.contains(&key) {
+                    expr.push(key);
+                }
+                if let Some(alias) = alias {
+                    inner.relation = Some(alias.clone());
+                }
+            }
+            let mut aliased_columns = vec![];
+            for column in columns_in(residuals, input.schema())? {
+                let projected = Expr::Column(column.clone());
+                if !expr.contains(&projected) {
+                    expr.push(projected);
+                }
+                if let Some(alias) = alias {
+                    let aliased = Column {
+                        relation: Some(alias.clone()),
+                        name: column.name.clone(),
+                    };
+                    aliased_columns.push((column, aliased));
+                }
+            }
+            if !aliased_columns.is_empty() {
+                let replace_map = aliased_columns
+                    .iter()
+                    .map(|(column, aliased)| (column, aliased))
+                    .collect::<HashMap<_, _>>();
+                for residual in residuals.iter_mut() {
+                    *residual = replace_col(residual.clone(), &replace_map)?;
+                }
+            }
+            LogicalPlanBuilder::from(input)
+                .project_with_alias(expr, alias.clone())?
+                .build()
+        }
+        LogicalPlan::Aggregate(Aggregate {
+            input,
+            group_expr,
+            aggr_expr,
+            ..
+        }) => {
+            let input = pull_up_correlations(
+                input,
+                outer_schema,
+                correlations,
+                residuals,
+                aggregated,
+            )?;
+            if !residuals.is_empty() {
+                return Err(unsupported_residuals(residuals));
+            }
+            if correlations.is_empty() {
+                return utils::from_plan(plan, &plan.expressions(), &[input]);
+            }
+
+            *aggregated = group_expr.is_empty();
+            let mut group_expr = group_expr.clone();
+            for (_, inner) in correlations.iter() {
+                let key = Expr::Column(inner.clone());
+                if !group_expr.contains(&key) {
+                    group_expr.push(key);
+                }
+            }
+            LogicalPlanBuilder::from(input)
+                .aggregate(group_expr, aggr_expr.clone())?
+                .build()
+        }
+        LogicalPlan::Sort(Sort { expr, input }) => {
+            let input = pull_up_correlations(
+                input,
+                outer_schema,
+                correlations,
+                residuals,
+                aggregated,
+            )?;
+            Ok(LogicalPlan::Sort(Sort {
+                expr: expr.clone(),
+                input: Arc::new(input),
+            }))
+        }
+        // correlated predicates below any other operator are not supported
+        _ => Ok(plan.clone()),
+    }
+}
+
+/// The error for correlated predicates other than equalities where they are
+/// not supported
+fn unsupported_residuals(residuals: &[Expr]) -> DataFusionError {
+    DataFusionError::NotImplemented(format!(
+        "Unsupported correlated subquery predicate {:?}, only equalities between \
+        a column of the subquery and a column of the outer query are supported \
+        in IN, scalar and aggregate subqueries",
+        residuals[0]
+    ))
+}
+
+/// A decorrelated subquery projected under a unique alias
+struct AliasedSubquery {
+    plan: LogicalPlan,
+    /// The column holding the value of the subquery
+    value: Column,
+    /// The outer query columns to join the subquery on
+    outer_keys: Vec<Column>,
+    /// The subquery columns to join the outer query on
+    inner_keys: Vec<Column>,
+    /// The other subquery columns projected
+    columns: Vec<Column>,
+}
+
+/// Projects `value`, the subquery columns of `correlations`, cast to the
+/// type of the outer query columns they are compared with, and `columns`
+/// out of `plan` under a new alias, so that its columns do not clash with the
+/// ones of the outer query when both scan the same tables
+fn alias_subquery(
+    plan: LogicalPlan,
+    value: Expr,
+    correlations: &[(Column, Column)],
+    columns: &[Column],
+    outer_schema: &DFSchema,
+    next_alias: &mut usize,
+) -> Result<AliasedSubquery> {
+    *next_alias += 1;
+    let alias = format!("__sq_{}", next_alias);
+
+    let mut expr = vec![value.alias(VALUE_COLUMN)];
+    let mut outer_keys = vec![];
+    let mut inner_keys = vec![];
+    for (i, (outer, inner)) in correlations.iter().enumerate() {
+        let name = format!("__key_{}", i + 1);
+        let outer_type = outer_schema.field_from_column(outer)?.data_type();
+        let key = Expr::Column(inner.clone()).cast_to(outer_type, plan.schema())?;
+        expr.push(key.alias(&name));
+        outer_keys.push(outer.clone());
+        inner_keys.push(Column {
+            relation: Some(alias.clone()),
+            name,
+        });
+    }
+    let mut projected_columns = vec![];
+    for (i, column) in columns.iter().enumerate() {
+        let name = format!("__column_{}", i + 1);
+        expr.push(Expr::Column(column.clone()).alias(&name));
+        projected_columns.push(Column {
+            relation: Some(alias.clone()),
+            name,
+        });
+    }
+
+    let plan = LogicalPlanBuilder::from(plan)
+        .project_with_alias(expr, Some(alias.clone()))?
+        .build()?;
+    Ok(AliasedSubquery {
+        plan,
+        value: Column {
+            relation: Some(alias),
+            name: VALUE_COLUMN.to_string(),
+        },
+        outer_keys,
+        inner_keys,
+        columns: projected_columns,
+    })
+}
+
+/// Whether an expression of `plan` or its inputs references a column that
+/// is not produced by the inputs of its node, i.e. a column of an outer query
+fn has_outer_references(plan: &LogicalPlan) -> Result<bool> {
+    let inputs = plan.inputs();
+    if !inputs.is_empty() {
+        let mut columns = HashSet::new();
+        utils::exprlist_to_columns(&plan.expressions(), &mut columns)?;
+        if columns.iter().any(|column| {
+            inputs
+                .iter()
+                .all(|input| input.schema().field_from_column(column).is_err())
+        }) {
+            return Ok(true);
+        }
+    }
+
+    for input in inputs {
+        if has_outer_references(input)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Whether `expr` contains an `EXISTS`, `IN` or scalar subquery
+fn contains_subquery(expr: &Expr) -> bool {
+    struct SubqueryFinder {
+        found: bool,
+    }
+
+    impl ExpressionVisitor for SubqueryFinder {
+        fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+            match expr {
+                Expr::Exists { .. }
+                | Expr::InSubquery { .. }
+                | Expr::ScalarSubquery(_) => {
+                    self.found = true;
+                    Ok(Recursion::Stop(self))
+                }
+                _ => Ok(Recursion::Continue(self)),
+            }
+        }
+    }
+
+    expr.accept(SubqueryFinder { found: false })
+        .map(|finder| finder.found)
+        .unwrap_or(false)
+}
+
+/// The columns of `exprs` found in `schema`, in the order they are first
+/// referenced
+fn columns_in(exprs: &[Expr], schema: &DFSchema) -> Result<Vec<Column>> {
+    struct ColumnCollector<'a> {
+        schema: &'a DFSchema,
+        columns: Vec<Column>,
+    }
+
+    impl ExpressionVisitor for ColumnCollector<'_> {
+        fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+            if let Expr::Column(column) = expr {
+                if self.schema.field_from_column(column).is_ok()
+                    && !self.columns.contains(column)
+                {
+                    self.columns.push(column.clone());
+                }
+            }
+            Ok(Recursion::Continue(self))
+        }
+    }
+
+    let collector = ColumnCollector {
+        schema,
+        columns: vec![],
+    };
+    let collector = exprs
+        .iter()
+        .try_fold(collector, |collector, expr| expr.accept(collector))?;
+    Ok(collector.columns)
+}
+
+fn split_conjunction<'a>(predicate: &'a Expr, predicates: &mut Vec<&'a Expr>) {
+    match predicate {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(left, predicates);
+            split_conjunction(right, predicates);
+        }
+        Expr::Alias(expr, _) => split_conjunction(expr, predicates),
+        other => predicates.push(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{
+        col, exists, in_subquery, max, not_exists, not_in_subquery, scalar_subquery,
+        LogicalPlanBuilder,
+    };
+    use crate::test::*;
+    use arrow::datatypes::{Field, Schema};
+
+    fn optimize(plan: &LogicalPlan) -> Result<LogicalPlan> {
+        let rule = SubqueryDecorrelation::new();
+        rule.optimize(plan, &ExecutionProps::new())
+    }
+
+    fn assert_optimized_plan_eq(plan: &LogicalPlan, expected: &str) {
+        let optimized_plan = optimize(plan).expect("failed to optimize plan");
+        let formatted_plan = format!("{:?}", optimized_plan);
+        assert_eq!(formatted_plan, expected);
+        assert_eq!(plan.schema(), optimized_plan.schema());
+    }
+
+    #[test]
+    fn correlated_exists() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .filter(col("test2.a").eq(col("test.a")))?
+            .project(vec![col("test2.c")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(exists(Arc::new(subquery)))?
+            .project(vec![col("b")])?
+            .build()?;
+
+        let expected = "Projection: #test.b\
+        \n  Join: #test.a = #__sq_1.__key_1\
+        \n    TableScan: test projection=None\
+        \n    Projection: #test2.c AS __value, #test2.a AS __key_1, alias=__sq_1\
+        \n      Projection: #test2.c, #test2.a\
+        \n        TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn uncorrelated_not_exists() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .project(vec![col("test2.c")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(not_exists(Arc::new(subquery)))?
+            .project(vec![col("b")])?
+            .build()?;
+
+        let expected = "Projection: #test.b\
+        \n  Projection: #test.a, #test.b, #test.c\
+        \n    Filter: #__sq_1.__value = UInt64(0)\
+        \n      CrossJoin:\
+        \n        TableScan: test projection=None\
+        \n        Projection: #COUNT(UInt8(1)) AS __value, alias=__sq_1\
+        \n          Aggregate: groupBy=[[]], aggr=[[COUNT(UInt8(1))]]\
+        \n            Limit: 1\
+        \n              Projection: #test2.c\
+        \n                TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn in_subquery_with_other_predicates() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .project(vec![col("test2.a")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(
+                in_subquery(col("c"), Arc::new(subquery)).and(col("b").gt(lit(1u32))),
+            )?
+            .project(vec![col("b")])?
+            .build()?;
+
+        let expected = "Projection: #test.b\
+        \n  Join: #test.c = #__sq_1.__value\
+        \n    Filter: #test.b > UInt32(1)\
+        \n      TableScan: test projection=None\
+        \n    Projection: #test2.a AS __value, alias=__sq_1\
+        \n      Projection: #test2.a\
+        \n        TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn correlated_scalar_subquery_in_filter() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .filter(col("test2.a").eq(col("test.a")))?
+            .aggregate(Vec::<Expr>::new(), vec![max(col("test2.b"))])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(col("b").lt(scalar_subquery(Arc::new(subquery))))?
+            .project(vec![col("b")])?
+            .build()?;
+
+        let expected = "Projection: #test.b\
+        \n  Projection: #test.a, #test.b, #test.c\
+        \n    Filter: #test.b < CASE WHEN #__sq_1.__key_1 IS NULL THEN #__sq_2.__value ELSE #__sq_1.__value END\
+        \n      CrossJoin:\
+        \n        Join: #test.a = #__sq_1.__key_1\
+        \n          TableScan: test projection=None\
+        \n          Projection: #MAX(test2.b) AS __value, #test2.a AS __key_1, alias=__sq_1\
+        \n            Aggregate: groupBy=[[#test2.a]], aggr=[[MAX(#test2.b)]]\
+        \n              TableScan: test2 projection=None\
+        \n        Projection: #MAX(test2.b) AS __value, alias=__sq_2\
+        \n          Aggregate: groupBy=[[]], aggr=[[MAX(#test2.b)]]\
+        \n            EmptyRelation";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn uncorrelated_scalar_subquery_in_projection() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .aggregate(Vec::<Expr>::new(), vec![max(col("test2.b"))])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .project(vec![col("a"), scalar_subquery(Arc::new(subquery))])?
+            .build()?;
+
+        let expected = "Projection: #test.a, #__sq_1.__value AS (Aggregate: groupBy=[[]], aggr=[[MAX(#test2.b)]], TableScan: test2 projection=None)\
+        \n  CrossJoin:\
+        \n    TableScan: test projection=None\
+        \n    Projection: #MAX(test2.b) AS __value, alias=__sq_1\
+        \n      Aggregate: groupBy=[[]], aggr=[[MAX(#test2.b)]]\
+        \n        TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn uncorrelated_scalar_subquery_without_aggregate() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .project(vec![col("test2.b")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .project(vec![col("a"), scalar_subquery(Arc::new(subquery))])?
+            .build()?;
+
+        // the subquery is checked to produce at most one row
+        let expected = "Projection: #test.a, #__sq_1.__value AS (Projection: #test2.b, TableScan: test2 projection=None)\
+        \n  CrossJoin:\
+        \n    TableScan: test projection=None\
+        \n    Projection: #__single_value(test2.b) AS __value, alias=__sq_1\
+        \n      Aggregate: groupBy=[[]], aggr=[[__single_value(#test2.b)]]\
+        \n        Limit: 2\
+        \n          Projection: #test2.b\
+        \n            TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn single_value_accumulator() -> Result<()> {
+        let mut accumulator = SingleValueAccumulator {
+            value: ScalarValue::Int32(None),
+            rows: 0,
+        };
+        assert_eq!(accumulator.evaluate()?, ScalarValue::Int32(None));
+        accumulator.merge(&[ScalarValue::Int32(None), ScalarValue::UInt64(Some(0))])?;
+        accumulator.update(&[ScalarValue::Int32(Some(1))])?;
+        assert_eq!(accumulator.evaluate()?, ScalarValue::Int32(Some(1)));
+
+        let err = accumulator
+            .merge(&accumulator.state()?)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("More than one row returned by a subquery"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn not_in_nullable_subquery() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::UInt32, true)]);
+        let subquery = LogicalPlanBuilder::scan_empty(Some("test2"), &schema, None)?
+            .project(vec![col("test2.a")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(not_in_subquery(col("c"), Arc::new(subquery)))?
+            .project(vec![col("b")])?
+            .build()?;
+
+        // rows are kept when the subquery produces no row, or no NULL value
+        let expected = "Projection: #test.b\
+        \n  Projection: #test.a, #test.b, #test.c\
+        \n    Filter: #__sq_2.__value IS NULL OR #__sq_2.__value = UInt64(0) AND #test.c IS NOT NULL\
+        \n      CrossJoin:\
+        \n        Join: #test.c = #__sq_1.__value\
+        \n          TableScan: test projection=None\
+        \n          Projection: #test2.a AS __value, alias=__sq_1\
+        \n            Projection: #test2.a\
+        \n              TableScan: test2 projection=None\
+        \n        Projection: #SUM(CASE WHEN test2.a IS NULL THEN UInt64(1) ELSE UInt64(0) END) AS __value, alias=__sq_2\
+        \n          Aggregate: groupBy=[[]], aggr=[[SUM(CASE WHEN #test2.a IS NULL THEN UInt64(1) ELSE UInt64(0) END)]]\
+        \n            Projection: #test2.a\
+        \n              TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn non_equi_correlated_exists() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .filter(col("test2.a").gt(col("test.a")))?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(exists(Arc::new(subquery)))?
+            .build()?;
+
+        // the values of test.a with a matching row are computed first
+        let expected = "Join: #test.a = #__sq_2.__key_1\
+        \n  TableScan: test projection=None\
+        \n  Projection: #test.a AS __key_1, alias=__sq_2\
+        \n    Aggregate: groupBy=[[#test.a]], aggr=[[]]\
+        \n      Filter: #__sq_1.__column_1 > #test.a\
+        \n        CrossJoin:\
+        \n          Projection: #test.a\
+        \n            TableScan: test projection=None\
+        \n          Projection: #test2.a AS __value, #test2.a AS __column_1, alias=__sq_1\
+        \n            TableScan: test2 projection=None";
+
+        assert_optimized_plan_eq(&plan, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn non_equi_correlated_scalar_subquery() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .filter(col("test2.a").gt(col("test.a")))?
+            .aggregate(Vec::<Expr>::new(), vec![max(col("test2.b"))])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(col("b").lt(scalar_subquery(Arc::new(subquery))))?
+            .build()?;
+
+        let err = optimize(&plan).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Unsupported correlated subquery predicate"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn correlated_scalar_subquery_without_aggregate() -> Result<()> {
+        let subquery = LogicalPlanBuilder::from(test_table_scan_with_name("test2")?)
+            .filter(col("test2.a").eq(col("test.a")))?
+            .project(vec![col("test2.b")])?
+            .build()?;
+        let plan = LogicalPlanBuilder::from(test_table_scan()?)
+            .filter(col("b").eq(scalar_subquery(Arc::new(subquery))))?
+            .build()?;
+
+        let err = optimize(&plan).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Correlated scalar subqueries must be aggregates"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
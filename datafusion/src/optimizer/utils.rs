@@ -23,8 +23,9 @@ use crate::logical_plan::plan::{
     Aggregate, Analyze, Extension, Filter, Join, Projection, Sort, Window,
 };
 use crate::logical_plan::{
-    build_join_schema, Column, CreateMemoryTable, DFSchemaRef, Expr, Limit, LogicalPlan,
-    LogicalPlanBuilder, Operator, Partitioning, Recursion, Repartition, Union, Values,
+    build_join_schema, Column, CopyTo, CreateMemoryTable, CreateView, DFSchemaRef, Expr,
+    GroupingSet, Insert, Limit, LogicalPlan, LogicalPlanBuilder, Operator, Partitioning,
+    Recursion, RecursiveQuery, Repartition, Union, Values,
 };
 use crate::prelude::lit;
 use crate::scalar::ScalarValue;
@@ -81,7 +82,11 @@ impl ExpressionVisitor for ColumnNameVisitor<'_> {
             Expr::AggregateFunction { .. } => {}
             Expr::AggregateUDF { .. } => {}
             Expr::InList { .. } => {}
+            Expr::Exists { .. } => {}
+            Expr::InSubquery { .. } => {}
+            Expr::ScalarSubquery(_) => {}
             Expr::Wildcard => {}
+            Expr::GroupingSet(_) => {}
             Expr::GetIndexedField { .. } => {}
         }
         Ok(Recursion::Continue(self))
@@ -229,6 +234,43 @@ pub fn from_plan(
                 name: name.clone(),
             }))
         }
+        LogicalPlan::CreateView(CreateView {
+            name,
+            or_replace,
+            definition,
+            ..
+        }) => Ok(LogicalPlan::CreateView(CreateView {
+            name: name.clone(),
+            input: Arc::new(inputs[0].clone()),
+            or_replace: *or_replace,
+            definition: definition.clone(),
+        })),
+        LogicalPlan::Insert(Insert {
+            table_name,
+            table,
+            overwrite,
+            schema,
+            ..
+        }) => Ok(LogicalPlan::Insert(Insert {
+            table_name: table_name.clone(),
+            table: table.clone(),
+            input: Arc::new(inputs[0].clone()),
+            overwrite: *overwrite,
+            schema: schema.clone(),
+        })),
+        LogicalPlan::CopyTo(CopyTo {
+            path,
+            file_type,
+            format,
+            schema,
+            ..
+        }) => Ok(LogicalPlan::CopyTo(CopyTo {
+            input: Arc::new(inputs[0].clone()),
+            path: path.clone(),
+            file_type: *file_type,
+            format: format.clone(),
+            schema: schema.clone(),
+        })),
         LogicalPlan::Extension(e) => Ok(LogicalPlan::Extension(Extension {
             node: e.node.from_template(expr, inputs),
         })),
@@ -239,6 +281,14 @@ pub fn from_plan(
                 alias: alias.clone(),
             }))
         }
+        LogicalPlan::RecursiveQuery(RecursiveQuery { name, schema, .. }) => {
+            Ok(LogicalPlan::RecursiveQuery(RecursiveQuery {
+                name: name.clone(),
+                static_term: Arc::new(inputs[0].clone()),
+                recursive_term: Arc::new(inputs[1].clone()),
+                schema: schema.clone(),
+            }))
+        }
         LogicalPlan::Analyze(a) => {
             assert!(expr.is_empty());
             assert_eq!(inputs.len(), 1);
@@ -265,7 +315,9 @@ pub fn from_plan(
         LogicalPlan::EmptyRelation(_)
         | LogicalPlan::TableScan { .. }
         | LogicalPlan::CreateExternalTable(_)
-        | LogicalPlan::DropTable(_) => {
+        | LogicalPlan::DropTable(_)
+        | LogicalPlan::DropView(_)
+        | LogicalPlan::RefreshTable(_) => {
             // All of these plan types have no inputs / exprs so should not be called
             assert!(expr.is_empty(), "{:?} should have no exprs", plan);
             assert!(inputs.is_empty(), "{:?}  should have no inputs", plan);
@@ -299,7 +351,19 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<Expr>> {
             expr_list.extend(order_by.clone());
             Ok(expr_list)
         }
-        Expr::AggregateFunction { args, .. } => Ok(args.clone()),
+        Expr::AggregateFunction {
+            args,
+            filter,
+            order_by,
+            ..
+        } => {
+            let mut expr_list: Vec<Expr> = args.clone();
+            if let Some(filter) = filter {
+                expr_list.push(filter.as_ref().clone());
+            }
+            expr_list.extend(order_by.clone());
+            Ok(expr_list)
+        }
         Expr::AggregateUDF { args, .. } => Ok(args.clone()),
         Expr::Case {
             expr,
@@ -345,10 +409,17 @@ pub fn expr_sub_expressions(expr: &Expr) -> Result<Vec<Expr>> {
             }
             Ok(expr_list)
         }
+        Expr::Exists { .. } => Ok(vec![]),
+        Expr::InSubquery { expr, .. } => Ok(vec![expr.as_ref().to_owned()]),
+        Expr::ScalarSubquery(_) => Ok(vec![]),
         Expr::Wildcard { .. } => Err(DataFusionError::Internal(
             "Wildcard expressions are not valid in a logical query plan".to_owned(),
         )),
         Expr::GetIndexedField { expr, .. } => Ok(vec![expr.as_ref().to_owned()]),
+        Expr::GroupingSet(grouping_set) => Ok(match grouping_set {
+            GroupingSet::Rollup(exprs) | GroupingSet::Cube(exprs) => exprs.clone(),
+            GroupingSet::GroupingSets(sets) => sets.iter().flatten().cloned().collect(),
+        }),
     }
 }
 
@@ -415,11 +486,28 @@ pub fn rewrite_expression(expr: &Expr, expressions: &[Expr]) -> Result<Expr> {
                 })
             }
         }
-        Expr::AggregateFunction { fun, distinct, .. } => Ok(Expr::AggregateFunction {
-            fun: fun.clone(),
-            args: expressions.to_vec(),
-            distinct: *distinct,
-        }),
+        Expr::AggregateFunction {
+            fun,
+            args,
+            distinct,
+            filter,
+            ..
+        } => {
+            // the expressions are the arguments, the filter if any and the
+            // sort expressions, see `expr_sub_expressions`
+            let (args, rest) = expressions.split_at(args.len());
+            let (filter, order_by) = match filter {
+                Some(_) => (Some(Box::new(rest[0].clone())), &rest[1..]),
+                None => (None, rest),
+            };
+            Ok(Expr::AggregateFunction {
+                fun: fun.clone(),
+                args: args.to_vec(),
+                distinct: *distinct,
+                filter,
+                order_by: order_by.to_vec(),
+            })
+        }
         Expr::AggregateUDF { fun, .. } => Ok(Expr::AggregateUDF {
             fun: fun.clone(),
             args: expressions.to_vec(),
@@ -505,6 +593,15 @@ pub fn rewrite_expression(expr: &Expr, expressions: &[Expr]) -> Result<Expr> {
             }
         }
         Expr::InList { .. } => Ok(expr.clone()),
+        Expr::Exists { .. } => Ok(expr.clone()),
+        Expr::InSubquery {
+            subquery, negated, ..
+        } => Ok(Expr::InSubquery {
+            expr: Box::new(expressions[0].clone()),
+            subquery: subquery.clone(),
+            negated: *negated,
+        }),
+        Expr::ScalarSubquery(_) => Ok(expr.clone()),
         Expr::Wildcard { .. } => Err(DataFusionError::Internal(
             "Wildcard expressions are not valid in a logical query plan".to_owned(),
         )),
@@ -512,6 +609,17 @@ pub fn rewrite_expression(expr: &Expr, expressions: &[Expr]) -> Result<Expr> {
             expr: Box::new(expressions[0].clone()),
             key: key.clone(),
         }),
+        Expr::GroupingSet(grouping_set) => {
+            // in the order of `expr_sub_expressions`
+            let mut expressions = expressions.iter().cloned();
+            Ok(Expr::GroupingSet(grouping_set.clone().map_exprs(|_| {
+                expressions.next().ok_or_else(|| {
+                    DataFusionError::Internal(
+                        "Missing expressions to rewrite a grouping set".to_owned(),
+                    )
+                })
+            })?))
+        }
     }
 }
 
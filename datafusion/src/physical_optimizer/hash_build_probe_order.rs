@@ -124,7 +124,8 @@ impl PhysicalOptimizerRule for HashBuildProbeOrder {
                     &swap_join_type(*hash_join.join_type()),
                     *hash_join.partition_mode(),
                     hash_join.null_equals_null(),
-                )?;
+                )?
+                .with_memory_manager(hash_join.memory_manager().clone());
                 let proj = ProjectionExec::try_new(
                     swap_reverting_projection(&*left.schema(), &*right.schema()),
                     Arc::new(new_join),
@@ -136,7 +137,8 @@ impl PhysicalOptimizerRule for HashBuildProbeOrder {
             let right = cross_join.right();
             if should_swap_join_order(&**left, &**right) {
                 let new_join =
-                    CrossJoinExec::try_new(Arc::clone(right), Arc::clone(left))?;
+                    CrossJoinExec::try_new(Arc::clone(right), Arc::clone(left))?
+                        .with_memory_manager(cross_join.memory_manager().clone());
                 let proj = ProjectionExec::try_new(
                     swap_reverting_projection(&*left.schema(), &*right.schema()),
                     Arc::new(new_join),
@@ -192,6 +192,18 @@ impl PruningPredicate {
     pub fn schema(&self) -> &SchemaRef {
         &self.schema
     }
+
+    /// Return the columns whose statistics are used to evaluate the
+    /// predicate, without duplicates
+    pub fn columns(&self) -> Vec<&Column> {
+        let mut columns: Vec<&Column> = vec![];
+        for (column, _, _) in self.required_columns.iter() {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+        columns
+    }
 }
 
 /// Handles creating references to the min/max statistics
@@ -1260,6 +1272,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn predicate_columns() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("c1", DataType::Int32, true),
+            Field::new("c2", DataType::Int32, true),
+        ]));
+        let expr = col("c1")
+            .gt(lit(5))
+            .and(col("c2").lt(lit(1)).or(col("c1").eq(lit(2))));
+        let predicate = PruningPredicate::try_new(&expr, schema)?;
+        assert_eq!(
+            vec![&Column::from_name("c1"), &Column::from_name("c2")],
+            predicate.columns()
+        );
+        Ok(())
+    }
+
     #[test]
     fn prune_api() {
         let schema = Arc::new(Schema::new(vec![
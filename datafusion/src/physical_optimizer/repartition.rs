@@ -76,7 +76,11 @@ fn optimize_partitions(
     // But also not very useful to inlude
     let is_empty_exec = plan.as_any().downcast_ref::<EmptyExec>().is_some();
 
-    if perform_repartition && !requires_single_partition && !is_empty_exec {
+    // round robin repartitioning would lose the ordering of sorted data,
+    // which the plan may depend on
+    let is_sorted = new_plan.output_ordering().is_some();
+
+    if perform_repartition && !requires_single_partition && !is_empty_exec && !is_sorted {
         Ok(Arc::new(RepartitionExec::try_new(
             new_plan,
             RoundRobinBatch(target_partitions),
@@ -106,13 +110,18 @@ impl PhysicalOptimizerRule for Repartition {
 }
 #[cfg(test)]
 mod tests {
-    use arrow::datatypes::Schema;
+    use arrow::compute::SortOptions;
+    use arrow::datatypes::{DataType, Field, Schema};
 
     use super::*;
     use crate::datasource::PartitionedFile;
+    use crate::physical_plan::expressions::{Column, Literal, PhysicalSortExpr};
     use crate::physical_plan::file_format::{ParquetExec, PhysicalPlanConfig};
+    use crate::physical_plan::filter::FilterExec;
     use crate::physical_plan::projection::ProjectionExec;
+    use crate::physical_plan::sort::SortExec;
     use crate::physical_plan::Statistics;
+    use crate::scalar::ScalarValue;
     use crate::test::object_store::TestObjectStore;
 
     #[test]
@@ -130,6 +139,7 @@ mod tests {
                     batch_size: 2048,
                     limit: None,
                     table_partition_cols: vec![],
+                    metadata_cache: None,
                 },
                 None,
             )),
@@ -172,6 +182,7 @@ mod tests {
                         batch_size: 2048,
                         limit: None,
                         table_partition_cols: vec![],
+                        metadata_cache: None,
                     },
                     None,
                 )),
@@ -197,4 +208,57 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn repartition_ignores_sorted_node() -> Result<()> {
+        let file_schema =
+            Arc::new(Schema::new(vec![Field::new("c1", DataType::Int32, false)]));
+        let sort = SortExec::try_new(
+            vec![PhysicalSortExpr {
+                expr: Arc::new(Column::new("c1", 0)),
+                options: SortOptions::default(),
+            }],
+            Arc::new(ParquetExec::new(
+                PhysicalPlanConfig {
+                    object_store: TestObjectStore::new_arc(&[("x", 100)]),
+                    file_schema,
+                    file_groups: vec![vec![PartitionedFile::new("x".to_string(), 100)]],
+                    statistics: Statistics::default(),
+                    projection: None,
+                    batch_size: 2048,
+                    limit: None,
+                    table_partition_cols: vec![],
+                    metadata_cache: None,
+                },
+                None,
+            )),
+        )?;
+        let sort_project = ProjectionExec::try_new(
+            vec![],
+            Arc::new(FilterExec::try_new(
+                Arc::new(Literal::new(ScalarValue::Boolean(Some(true)))),
+                Arc::new(sort),
+            )?),
+        )?;
+
+        let optimizer = Repartition {};
+
+        let optimized = optimizer.optimize(
+            Arc::new(sort_project),
+            &ExecutionConfig::new().with_target_partitions(10),
+        )?;
+
+        // the sorted output of the filter is not repartitioned, which would
+        // shuffle its rows
+        assert!(optimized.children()[0]
+            .as_any()
+            .downcast_ref::<FilterExec>()
+            .is_some());
+        assert!(optimized.children()[0].children()[0]
+            .as_any()
+            .downcast_ref::<SortExec>()
+            .is_some());
+
+        Ok(())
+    }
 }
@@ -34,6 +34,7 @@ use crate::error::{DataFusionError, Result};
 use crate::physical_plan::coercion_rule::aggregate_rule::{coerce_exprs, coerce_types};
 use crate::physical_plan::distinct_expressions;
 use crate::physical_plan::expressions;
+use crate::physical_plan::expressions::StatsType;
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use expressions::{avg_return_type, sum_return_type};
 use std::{fmt, str::FromStr, sync::Arc};
@@ -64,6 +65,28 @@ pub enum AggregateFunction {
     ApproxDistinct,
     /// array_agg
     ArrayAgg,
+    /// grouping, the bit mask of the arguments a grouping set does not group by
+    Grouping,
+    /// sample standard deviation
+    Stddev,
+    /// population standard deviation
+    StddevPop,
+    /// sample variance
+    Variance,
+    /// population variance
+    VariancePop,
+    /// sample covariance
+    Covariance,
+    /// population covariance
+    CovariancePop,
+    /// Pearson correlation coefficient
+    Correlation,
+    /// median, the 50th continuous percentile
+    Median,
+    /// continuous percentile
+    PercentileCont,
+    /// continuous percentile approximated with a t-digest
+    ApproxPercentileCont,
 }
 
 impl fmt::Display for AggregateFunction {
@@ -84,6 +107,17 @@ impl FromStr for AggregateFunction {
             "sum" => AggregateFunction::Sum,
             "approx_distinct" => AggregateFunction::ApproxDistinct,
             "array_agg" => AggregateFunction::ArrayAgg,
+            "grouping" => AggregateFunction::Grouping,
+            "stddev" | "stddev_samp" => AggregateFunction::Stddev,
+            "stddev_pop" => AggregateFunction::StddevPop,
+            "var" | "var_samp" | "variance" => AggregateFunction::Variance,
+            "var_pop" => AggregateFunction::VariancePop,
+            "covar" | "covar_samp" => AggregateFunction::Covariance,
+            "covar_pop" => AggregateFunction::CovariancePop,
+            "corr" => AggregateFunction::Correlation,
+            "median" => AggregateFunction::Median,
+            "percentile_cont" => AggregateFunction::PercentileCont,
+            "approx_percentile_cont" => AggregateFunction::ApproxPercentileCont,
             _ => {
                 return Err(DataFusionError::Plan(format!(
                     "There is no built-in function named {}",
@@ -122,6 +156,17 @@ pub fn return_type(
             coerced_data_types[0].clone(),
             true,
         )))),
+        AggregateFunction::Grouping => Ok(DataType::UInt32),
+        AggregateFunction::Stddev
+        | AggregateFunction::StddevPop
+        | AggregateFunction::Variance
+        | AggregateFunction::VariancePop
+        | AggregateFunction::Covariance
+        | AggregateFunction::CovariancePop
+        | AggregateFunction::Correlation
+        | AggregateFunction::Median
+        | AggregateFunction::PercentileCont
+        | AggregateFunction::ApproxPercentileCont => Ok(DataType::Float64),
     }
 }
 
@@ -212,6 +257,89 @@ pub fn create_aggregate_expr(
                 "AVG(DISTINCT) aggregations are not available".to_string(),
             ));
         }
+        (AggregateFunction::Stddev, false) => Arc::new(expressions::Stddev::new(
+            coerced_phy_exprs[0].clone(),
+            name,
+            StatsType::Sample,
+        )),
+        (AggregateFunction::StddevPop, false) => Arc::new(expressions::Stddev::new(
+            coerced_phy_exprs[0].clone(),
+            name,
+            StatsType::Population,
+        )),
+        (AggregateFunction::Variance, false) => Arc::new(expressions::Variance::new(
+            coerced_phy_exprs[0].clone(),
+            name,
+            StatsType::Sample,
+        )),
+        (AggregateFunction::VariancePop, false) => Arc::new(expressions::Variance::new(
+            coerced_phy_exprs[0].clone(),
+            name,
+            StatsType::Population,
+        )),
+        (AggregateFunction::Covariance, false) => Arc::new(expressions::Covariance::new(
+            coerced_phy_exprs[0].clone(),
+            coerced_phy_exprs[1].clone(),
+            name,
+            StatsType::Sample,
+        )),
+        (AggregateFunction::CovariancePop, false) => {
+            Arc::new(expressions::Covariance::new(
+                coerced_phy_exprs[0].clone(),
+                coerced_phy_exprs[1].clone(),
+                name,
+                StatsType::Population,
+            ))
+        }
+        (AggregateFunction::Correlation, false) => {
+            Arc::new(expressions::Correlation::new(
+                coerced_phy_exprs[0].clone(),
+                coerced_phy_exprs[1].clone(),
+                name,
+            ))
+        }
+        (AggregateFunction::Median, false) => {
+            Arc::new(expressions::Median::new(coerced_phy_exprs[0].clone(), name))
+        }
+        (AggregateFunction::PercentileCont, false) => {
+            Arc::new(expressions::PercentileCont::try_new(
+                coerced_phy_exprs[0].clone(),
+                coerced_phy_exprs[1].clone(),
+                name,
+            )?)
+        }
+        (AggregateFunction::ApproxPercentileCont, false) => {
+            Arc::new(expressions::ApproxPercentileCont::try_new(
+                coerced_phy_exprs[0].clone(),
+                coerced_phy_exprs[1].clone(),
+                name,
+            )?)
+        }
+        (
+            AggregateFunction::Stddev
+            | AggregateFunction::StddevPop
+            | AggregateFunction::Variance
+            | AggregateFunction::VariancePop
+            | AggregateFunction::Covariance
+            | AggregateFunction::CovariancePop
+            | AggregateFunction::Correlation
+            | AggregateFunction::Median
+            | AggregateFunction::PercentileCont
+            | AggregateFunction::ApproxPercentileCont,
+            true,
+        ) => {
+            return Err(DataFusionError::NotImplemented(format!(
+                "{}(DISTINCT) aggregations are not available",
+                fun
+            )));
+        }
+        (AggregateFunction::Grouping, _) => {
+            return Err(DataFusionError::Internal(
+                "GROUPING is computed from the grouping id of the aggregation \
+                 and has no physical aggregate expression"
+                    .to_string(),
+            ));
+        }
     })
 }
 
@@ -246,6 +374,7 @@ pub fn signature(fun: &AggregateFunction) -> Signature {
         AggregateFunction::Count
         | AggregateFunction::ApproxDistinct
         | AggregateFunction::ArrayAgg => Signature::any(1, Volatility::Immutable),
+        AggregateFunction::Grouping => Signature::variadic_any(Volatility::Immutable),
         AggregateFunction::Min | AggregateFunction::Max => {
             let valid = STRINGS
                 .iter()
@@ -256,9 +385,24 @@ pub fn signature(fun: &AggregateFunction) -> Signature {
                 .collect::<Vec<_>>();
             Signature::uniform(1, valid, Volatility::Immutable)
         }
-        AggregateFunction::Avg | AggregateFunction::Sum => {
+        AggregateFunction::Avg
+        | AggregateFunction::Sum
+        | AggregateFunction::Stddev
+        | AggregateFunction::StddevPop
+        | AggregateFunction::Variance
+        | AggregateFunction::VariancePop
+        | AggregateFunction::Median => {
             Signature::uniform(1, NUMERICS.to_vec(), Volatility::Immutable)
         }
+        // the percentile of the percentile functions must also be a literal,
+        // which their physical expressions check
+        AggregateFunction::Covariance
+        | AggregateFunction::CovariancePop
+        | AggregateFunction::Correlation
+        | AggregateFunction::PercentileCont
+        | AggregateFunction::ApproxPercentileCont => {
+            Signature::uniform(2, NUMERICS.to_vec(), Volatility::Immutable)
+        }
     }
 }
 
@@ -269,6 +413,7 @@ mod tests {
     use crate::physical_plan::expressions::{
         ApproxDistinct, ArrayAgg, Avg, Count, Max, Min, Sum,
     };
+    use crate::scalar::ScalarValue;
 
     #[test]
     fn test_count_arragg_approx_expr() -> Result<()> {
@@ -544,4 +689,80 @@ mod tests {
         let observed = return_type(&AggregateFunction::Avg, &[DataType::Utf8]);
         assert!(observed.is_err());
     }
+
+    #[test]
+    fn test_stats_return_type() -> Result<()> {
+        for fun in [
+            AggregateFunction::Stddev,
+            AggregateFunction::StddevPop,
+            AggregateFunction::Variance,
+            AggregateFunction::VariancePop,
+            AggregateFunction::Median,
+        ] {
+            let observed = return_type(&fun, &[DataType::Int32])?;
+            assert_eq!(DataType::Float64, observed);
+        }
+        for fun in [
+            AggregateFunction::Covariance,
+            AggregateFunction::CovariancePop,
+            AggregateFunction::Correlation,
+            AggregateFunction::PercentileCont,
+            AggregateFunction::ApproxPercentileCont,
+        ] {
+            let observed = return_type(&fun, &[DataType::UInt8, DataType::Float64])?;
+            assert_eq!(DataType::Float64, observed);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_no_utf8() {
+        let observed = return_type(&AggregateFunction::Stddev, &[DataType::Utf8]);
+        assert!(observed.is_err());
+        let observed = return_type(
+            &AggregateFunction::Correlation,
+            &[DataType::Float64, DataType::Utf8],
+        );
+        assert!(observed.is_err());
+    }
+
+    #[test]
+    fn test_percentile_expr() -> Result<()> {
+        let input_schema = Schema::new(vec![Field::new("c1", DataType::Int64, true)]);
+        let column: Arc<dyn PhysicalExpr> =
+            Arc::new(expressions::Column::new_with_schema("c1", &input_schema).unwrap());
+        for fun in [
+            AggregateFunction::PercentileCont,
+            AggregateFunction::ApproxPercentileCont,
+        ] {
+            let percentile = expressions::lit(ScalarValue::from(0.25));
+            let agg = create_aggregate_expr(
+                &fun,
+                false,
+                &[column.clone(), percentile],
+                &input_schema,
+                "c1",
+            )?;
+            assert_eq!(
+                Field::new("c1", DataType::Float64, true),
+                agg.field().unwrap()
+            );
+            assert_eq!(agg.expressions().len(), 2);
+
+            // the percentile must be a constant
+            let err = create_aggregate_expr(
+                &fun,
+                false,
+                &[column.clone(), column.clone()],
+                &input_schema,
+                "c1",
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Error during planning: The percentile of c1 must be a literal, got c1@0"
+            );
+        }
+        Ok(())
+    }
 }
@@ -230,6 +230,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -47,6 +47,14 @@ pub(crate) fn coerce_types(
                 )));
             }
         }
+        TypeSignature::VariadicAny => {
+            if input_types.is_empty() {
+                return Err(DataFusionError::Plan(format!(
+                    "The function {:?} expects at least one argument",
+                    agg_fun
+                )));
+            }
+        }
         _ => {
             return Err(DataFusionError::Internal(format!(
                 "Aggregate functions do not support this {:?}",
@@ -58,7 +66,9 @@ pub(crate) fn coerce_types(
         AggregateFunction::Count | AggregateFunction::ApproxDistinct => {
             Ok(input_types.to_vec())
         }
-        AggregateFunction::ArrayAgg => Ok(input_types.to_vec()),
+        AggregateFunction::ArrayAgg | AggregateFunction::Grouping => {
+            Ok(input_types.to_vec())
+        }
         AggregateFunction::Min | AggregateFunction::Max => {
             // min and max support the dictionary data type
             // unpack the dictionary to get the value
@@ -86,9 +96,46 @@ pub(crate) fn coerce_types(
             }
             Ok(input_types.to_vec())
         }
+        AggregateFunction::Stddev
+        | AggregateFunction::StddevPop
+        | AggregateFunction::Variance
+        | AggregateFunction::VariancePop
+        | AggregateFunction::Covariance
+        | AggregateFunction::CovariancePop
+        | AggregateFunction::Correlation
+        | AggregateFunction::Median
+        | AggregateFunction::PercentileCont
+        | AggregateFunction::ApproxPercentileCont => {
+            // the accumulators cast their inputs to f64
+            if let Some(input_type) =
+                input_types.iter().find(|t| !is_stats_support_arg_type(t))
+            {
+                return Err(DataFusionError::Plan(format!(
+                    "The function {:?} does not support inputs of type {:?}.",
+                    agg_fun, input_type
+                )));
+            }
+            Ok(input_types.to_vec())
+        }
     }
 }
 
+fn is_stats_support_arg_type(arg_type: &DataType) -> bool {
+    matches!(
+        arg_type,
+        DataType::UInt8
+            | DataType::UInt16
+            | DataType::UInt32
+            | DataType::UInt64
+            | DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+    )
+}
+
 fn get_min_max_result_type(input_types: &[DataType]) -> Result<Vec<DataType>> {
     // make sure that the input types only has one element.
     assert_eq!(input_types.len(), 1);
@@ -188,6 +235,27 @@ mod tests {
                 assert_eq!(*input_type, result.unwrap());
             }
         }
+        // test the statistical aggregates
+        let funs = vec![
+            AggregateFunction::Stddev,
+            AggregateFunction::VariancePop,
+            AggregateFunction::Median,
+        ];
+        for fun in funs {
+            let signature = aggregates::signature(&fun);
+            let result = coerce_types(&fun, &[DataType::Int16], &signature);
+            assert_eq!(vec![DataType::Int16], result.unwrap());
+            let result = coerce_types(&fun, &[DataType::Utf8], &signature);
+            assert!(result.is_err());
+        }
+        let fun = AggregateFunction::Correlation;
+        let signature = aggregates::signature(&fun);
+        let result = coerce_types(&fun, &[DataType::Float32], &signature);
+        assert_eq!("Error during planning: The function Correlation expects 2 arguments, but 1 were provided", result.unwrap_err().to_string());
+        let result =
+            coerce_types(&fun, &[DataType::Float32, DataType::UInt8], &signature);
+        assert_eq!(vec![DataType::Float32, DataType::UInt8], result.unwrap());
+
         // test sum, avg
         let funs = vec![AggregateFunction::Sum, AggregateFunction::Avg];
         let input_types = vec![
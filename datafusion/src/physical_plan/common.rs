@@ -108,6 +108,15 @@ pub(crate) fn combine_batches(
     }
 }
 
+/// Returns the amount of memory used by the arrays of `batch`
+pub(crate) fn batch_memory_size(batch: &RecordBatch) -> usize {
+    batch
+        .columns()
+        .iter()
+        .map(|array| array.get_array_memory_size())
+        .sum()
+}
+
 /// Recursively builds a list of files in a directory with a given extension
 pub fn build_checked_file_list(dir: &str, ext: &str) -> Result<Vec<String>> {
     let mut filenames: Vec<String> = Vec::new();
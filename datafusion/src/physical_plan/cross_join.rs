@@ -25,7 +25,7 @@ use arrow::datatypes::{Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
-use futures::{Stream, TryStreamExt};
+use futures::Stream;
 
 use super::{
     coalesce_partitions::CoalescePartitionsExec, join_utils::check_join_is_valid,
@@ -33,6 +33,8 @@ use super::{
 };
 use crate::{
     error::{DataFusionError, Result},
+    execution::memory_manager::{MemoryManager, MemoryReservation},
+    physical_plan::common::batch_memory_size,
     scalar::ScalarValue,
 };
 use async_trait::async_trait;
@@ -57,8 +59,10 @@ pub struct CrossJoinExec {
     right: Arc<dyn ExecutionPlan>,
     /// The schema once the join is applied
     schema: SchemaRef,
-    /// Build-side data
-    build_side: Arc<Mutex<Option<JoinLeftData>>>,
+    /// Build-side data, together with the memory reserved for it
+    build_side: Arc<Mutex<Option<(JoinLeftData, MemoryReservation)>>>,
+    /// Memory pool the build side is reserved from
+    memory_manager: Arc<MemoryManager>,
 }
 
 impl CrossJoinExec {
@@ -89,9 +93,21 @@ impl CrossJoinExec {
             right,
             schema,
             build_side: Arc::new(Mutex::new(None)),
+            memory_manager: MemoryManager::unbounded(),
         })
     }
 
+    /// Reserve the memory of the build side from `memory_manager`
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = memory_manager;
+        self
+    }
+
+    /// Memory pool the build side is reserved from
+    pub fn memory_manager(&self) -> &Arc<MemoryManager> {
+        &self.memory_manager
+    }
+
     /// left (build) side which gets loaded in memory
     pub fn left(&self) -> &Arc<dyn ExecutionPlan> {
         &self.left
@@ -122,10 +138,10 @@ impl ExecutionPlan for CrossJoinExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            2 => Ok(Arc::new(CrossJoinExec::try_new(
-                children[0].clone(),
-                children[1].clone(),
-            )?)),
+            2 => Ok(Arc::new(
+                CrossJoinExec::try_new(children[0].clone(), children[1].clone())?
+                    .with_memory_manager(self.memory_manager.clone()),
+            )),
             _ => Err(DataFusionError::Internal(
                 "CrossJoinExec wrong number of children".to_string(),
             )),
@@ -142,25 +158,32 @@ impl ExecutionPlan for CrossJoinExec {
             let mut build_side = self.build_side.lock().await;
 
             match build_side.as_ref() {
-                Some(stream) => stream.clone(),
+                Some((batch, _)) => batch.clone(),
                 None => {
                     let start = Instant::now();
 
                     // merge all left parts into a single stream
                     let merge = CoalescePartitionsExec::new(self.left.clone());
-                    let stream = merge.execute(0).await?;
-
-                    // Load all batches and count the rows
-                    let (batches, num_rows) = stream
-                        .try_fold((Vec::new(), 0usize), |mut acc, batch| async {
-                            acc.1 += batch.num_rows();
-                            acc.0.push(batch);
-                            Ok(acc)
-                        })
-                        .await?;
+                    let mut stream = merge.execute(0).await?;
+
+                    // Load all batches and count the rows, reserving room for
+                    // both the batches and their concatenation
+                    let mut reservation = self
+                        .memory_manager
+                        .register_consumer("CrossJoinExec", false);
+                    let mut batches = Vec::new();
+                    let mut num_rows = 0;
+                    while let Some(batch) = stream.next().await {
+                        let batch = batch?;
+                        reservation.try_grow(2 * batch_memory_size(&batch))?;
+                        num_rows += batch.num_rows();
+                        batches.push(batch);
+                    }
                     let merged_batch =
                         concat_batches(&self.left.schema(), &batches, num_rows)?;
-                    *build_side = Some(merged_batch.clone());
+                    // only the concatenated batch is kept
+                    reservation.try_resize(batch_memory_size(&merged_batch))?;
+                    *build_side = Some((merged_batch.clone(), reservation));
 
                     debug!(
                         "Built build-side of cross join containing {} rows in {} ms",
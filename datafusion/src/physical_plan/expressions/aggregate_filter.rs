@@ -0,0 +1,254 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the physical expression of an aggregate with a `FILTER (WHERE ...)`
+//! clause
+
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::{ArrayRef, BooleanArray};
+use arrow::compute::filter;
+use arrow::datatypes::{DataType, Field, Schema};
+use std::any::Any;
+use std::sync::Arc;
+
+/// An aggregate expression whose accumulators are only fed the rows for which
+/// `predicate` is true, i.e. `expr FILTER (WHERE predicate)`.
+///
+/// The predicate is evaluated along with the arguments of `expr`, as the last
+/// of its [`expressions`](AggregateExpr::expressions). The partial states are
+/// the ones of `expr`, so the filter only applies before they are merged.
+#[derive(Debug)]
+pub struct AggregateFilter {
+    expr: Arc<dyn AggregateExpr>,
+    predicate: Arc<dyn PhysicalExpr>,
+}
+
+impl AggregateFilter {
+    /// Create a new aggregate filtering the rows fed to `expr`
+    pub fn try_new(
+        expr: Arc<dyn AggregateExpr>,
+        predicate: Arc<dyn PhysicalExpr>,
+        input_schema: &Schema,
+    ) -> Result<Self> {
+        match predicate.data_type(input_schema)? {
+            DataType::Boolean => Ok(Self { expr, predicate }),
+            other => Err(DataFusionError::Plan(format!(
+                "The FILTER predicate of {} must be boolean, got {:?}",
+                expr.name(),
+                other
+            ))),
+        }
+    }
+
+    /// The filtered aggregate expression
+    pub fn expr(&self) -> &Arc<dyn AggregateExpr> {
+        &self.expr
+    }
+
+    /// The predicate the rows are filtered with
+    pub fn predicate(&self) -> &Arc<dyn PhysicalExpr> {
+        &self.predicate
+    }
+}
+
+impl AggregateExpr for AggregateFilter {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        self.expr.field()
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(AggregateFilterAccumulator {
+            accumulator: self.expr.create_accumulator()?,
+        }))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        self.expr.state_fields()
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        let mut expressions = self.expr.expressions();
+        expressions.push(self.predicate.clone());
+        expressions
+    }
+
+    fn name(&self) -> &str {
+        self.expr.name()
+    }
+}
+
+/// Feeds the rows whose last value, the predicate, is true to `accumulator`
+#[derive(Debug)]
+struct AggregateFilterAccumulator {
+    accumulator: Box<dyn Accumulator>,
+}
+
+impl Accumulator for AggregateFilterAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        self.accumulator.state()
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        match values.split_last() {
+            Some((ScalarValue::Boolean(Some(true)), values)) => {
+                self.accumulator.update(values)
+            }
+            Some((ScalarValue::Boolean(_), _)) => Ok(()),
+            _ => Err(DataFusionError::Internal(
+                "Expected the FILTER predicate as the last value".to_string(),
+            )),
+        }
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let (predicate, values) = values
+            .split_last()
+            .and_then(|(predicate, values)| {
+                let predicate = predicate.as_any().downcast_ref::<BooleanArray>()?;
+                Some((predicate, values))
+            })
+            .ok_or_else(|| {
+                DataFusionError::Internal(
+                    "Expected the FILTER predicate as the last array".to_string(),
+                )
+            })?;
+        // null predicates filter out their rows like false ones
+        let values = values
+            .iter()
+            .map(|array| filter(array.as_ref(), predicate))
+            .collect::<arrow::error::Result<Vec<_>>>()?;
+        self.accumulator.update_batch(&values)
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        self.accumulator.merge(states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.accumulator.merge_batch(states)
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        self.accumulator.evaluate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::Operator;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use crate::physical_plan::expressions::{binary, col, lit, Count, Sum};
+    use arrow::array::Int32Array;
+    use arrow::record_batch::RecordBatch;
+
+    fn schema() -> Schema {
+        Schema::new(vec![Field::new("a", DataType::Int32, true)])
+    }
+
+    /// `a > 2`
+    fn a_gt_2(schema: &Schema) -> Result<Arc<dyn PhysicalExpr>> {
+        binary(
+            col("a", schema)?,
+            Operator::Gt,
+            lit(ScalarValue::Int32(Some(2))),
+            schema,
+        )
+    }
+
+    #[test]
+    fn filter_sum_and_count() -> Result<()> {
+        let schema = schema();
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![
+            Some(1),
+            Some(4),
+            None,
+            Some(3),
+            Some(2),
+        ]));
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![a])?;
+
+        let sum = Arc::new(Sum::new(col("a", &schema)?, "bla", DataType::Int64));
+        let sum = AggregateFilter::try_new(sum, a_gt_2(&schema)?, &schema)?;
+        assert_eq!(
+            aggregate(&batch, Arc::new(sum))?,
+            ScalarValue::Int64(Some(7))
+        );
+
+        // the null predicate of the null value filters it out too
+        let count = Arc::new(Count::new(
+            lit(ScalarValue::UInt8(Some(1))),
+            "bla",
+            DataType::UInt64,
+        ));
+        let count = AggregateFilter::try_new(count, a_gt_2(&schema)?, &schema)?;
+        assert_eq!(
+            aggregate(&batch, Arc::new(count))?,
+            ScalarValue::UInt64(Some(2))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn filter_merge() -> Result<()> {
+        let schema = schema();
+        let sum = Arc::new(Sum::new(col("a", &schema)?, "bla", DataType::Int64));
+        let sum = AggregateFilter::try_new(sum, a_gt_2(&schema)?, &schema)?;
+        assert_eq!(sum.state_fields()?, sum.expr().state_fields()?);
+
+        let update = |values: Vec<i32>| -> Result<Box<dyn Accumulator>> {
+            let batch = RecordBatch::try_new(
+                Arc::new(schema.clone()),
+                vec![Arc::new(Int32Array::from(values))],
+            )?;
+            let values = sum
+                .expressions()
+                .iter()
+                .map(|e| e.evaluate(&batch).map(|v| v.into_array(batch.num_rows())))
+                .collect::<Result<Vec<_>>>()?;
+            let mut accumulator = sum.create_accumulator()?;
+            accumulator.update_batch(&values)?;
+            Ok(accumulator)
+        };
+        let mut left = update(vec![1, 5])?;
+        let right = update(vec![3, 2])?;
+        left.merge(&right.state()?)?;
+        assert_eq!(left.evaluate()?, ScalarValue::Int64(Some(8)));
+        Ok(())
+    }
+
+    #[test]
+    fn filter_must_be_boolean() -> Result<()> {
+        let schema = schema();
+        let sum = Arc::new(Sum::new(col("a", &schema)?, "bla", DataType::Int64));
+        let err = AggregateFilter::try_new(sum, col("a", &schema)?, &schema)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("The FILTER predicate of bla must be boolean, got Int32"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
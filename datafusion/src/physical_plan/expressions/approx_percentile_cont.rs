@@ -0,0 +1,225 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::format_state_name;
+use super::percentile_cont::validate_percentile_expr;
+use super::stats::to_f64_array;
+use crate::error::Result;
+use crate::physical_plan::tdigest::{TDigest, DEFAULT_MAX_SIZE};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::{Array, ArrayRef};
+use arrow::datatypes::{DataType, Field};
+
+/// APPROX_PERCENTILE_CONT aggregate expression, an approximation of
+/// [`super::PercentileCont`] computed with a t-digest, which summarizes the
+/// values in a bounded amount of memory.
+#[derive(Debug)]
+pub struct ApproxPercentileCont {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    percentile_expr: Arc<dyn PhysicalExpr>,
+    percentile: f64,
+}
+
+impl ApproxPercentileCont {
+    /// Create a new APPROX_PERCENTILE_CONT aggregate function,
+    /// `percentile_expr` must be a literal between 0 and 1
+    pub fn try_new(
+        expr: Arc<dyn PhysicalExpr>,
+        percentile_expr: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let percentile = validate_percentile_expr(&percentile_expr, &name)?;
+        Ok(Self {
+            name,
+            expr,
+            percentile_expr,
+            percentile,
+        })
+    }
+
+    /// The percentile, between 0 and 1
+    pub fn percentile(&self) -> f64 {
+        self.percentile
+    }
+}
+
+impl AggregateExpr for ApproxPercentileCont {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(ApproxPercentileAccumulator::new(self.percentile)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        let field = |state: &str, data_type: DataType| {
+            Field::new(&format_state_name(&self.name, state), data_type, false)
+        };
+        Ok(vec![
+            field("max_size", DataType::UInt64),
+            field("sum", DataType::Float64),
+            field("count", DataType::Float64),
+            field("max", DataType::Float64),
+            field("min", DataType::Float64),
+            field(
+                "centroids",
+                DataType::List(Box::new(Field::new("item", DataType::Float64, true))),
+            ),
+        ])
+    }
+
+    /// The percentile is one of the expressions so that the aggregate can be
+    /// created again from its expressions, it is not used by the accumulator.
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone(), self.percentile_expr.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// An accumulator to approximate a percentile with a [`TDigest`]
+#[derive(Debug)]
+pub struct ApproxPercentileAccumulator {
+    digest: TDigest,
+    percentile: f64,
+}
+
+impl ApproxPercentileAccumulator {
+    /// Creates a new `ApproxPercentileAccumulator` of the `percentile`,
+    /// between 0 and 1
+    pub fn new(percentile: f64) -> Self {
+        Self {
+            digest: TDigest::new(DEFAULT_MAX_SIZE),
+            percentile,
+        }
+    }
+}
+
+impl Accumulator for ApproxPercentileAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(self.digest.to_scalar_state())
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.update_batch(&[values[0].to_array()])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = to_f64_array(&values[0])?.iter().flatten().collect();
+        self.digest = self.digest.merge_unsorted(values);
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        let other = TDigest::from_scalar_state(states)?;
+        self.digest = TDigest::merge_digests(&[self.digest.clone(), other]);
+        Ok(())
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        if states.is_empty() {
+            return Ok(());
+        }
+        // compress all the digests at once rather than one at a time
+        let mut digests = vec![self.digest.clone()];
+        for index in 0..states[0].len() {
+            let state = states
+                .iter()
+                .map(|array| ScalarValue::try_from_array(array, index))
+                .collect::<Result<Vec<_>>>()?;
+            digests.push(TDigest::from_scalar_state(&state)?);
+        }
+        self.digest = TDigest::merge_digests(&digests);
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Float64(
+            self.digest.estimate_quantile(self.percentile),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use crate::physical_plan::expressions::{col, lit};
+    use arrow::array::Int64Array;
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    #[test]
+    fn approx_percentile_cont_i64() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let values: ArrayRef = Arc::new(Int64Array::from(vec![
+            Some(4),
+            None,
+            Some(1),
+            Some(3),
+            Some(2),
+            Some(5),
+        ]));
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![values])?;
+        let agg = Arc::new(ApproxPercentileCont::try_new(
+            col("a", &schema)?,
+            lit(ScalarValue::from(0.5)),
+            "bla",
+        )?);
+        assert_eq!(aggregate(&batch, agg)?, ScalarValue::from(3_f64));
+        Ok(())
+    }
+
+    #[test]
+    fn approx_percentile_merge() -> Result<()> {
+        let mut accumulators = (0..4)
+            .map(|_| ApproxPercentileAccumulator::new(0.9))
+            .collect::<Vec<_>>();
+        for (i, accumulator) in accumulators.iter_mut().enumerate() {
+            let values = (0..10_000).map(|v| (v * 4 + i) as i64).collect::<Vec<_>>();
+            accumulator.update_batch(&[Arc::new(Int64Array::from(values))])?;
+        }
+
+        let mut merged = ApproxPercentileAccumulator::new(0.9);
+        for accumulator in &accumulators {
+            merged.merge(&accumulator.state()?)?;
+        }
+        match merged.evaluate()? {
+            ScalarValue::Float64(Some(v)) => {
+                assert!((v - 36_000.0).abs() < 100.0, "{}", v)
+            }
+            other => panic!("unexpected percentile {:?}", other),
+        }
+
+        Ok(())
+    }
+}
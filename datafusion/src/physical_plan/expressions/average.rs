@@ -154,6 +154,9 @@ pub struct AvgAccumulator {
     // sum is used for null
     sum: ScalarValue,
     count: u64,
+    // the non-finite float values, kept out of `sum` so that they can be
+    // retracted
+    non_finite: sum::NonFinite,
 }
 
 impl AvgAccumulator {
@@ -162,20 +165,26 @@ impl AvgAccumulator {
         Ok(Self {
             sum: ScalarValue::try_from(datatype)?,
             count: 0,
+            non_finite: sum::NonFinite::default(),
         })
     }
 }
 
 impl Accumulator for AvgAccumulator {
     fn state(&self) -> Result<Vec<ScalarValue>> {
-        Ok(vec![ScalarValue::from(self.count), self.sum.clone()])
+        Ok(vec![
+            ScalarValue::from(self.count),
+            self.non_finite.apply(&self.sum),
+        ])
     }
 
     fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
         let values = &values[0];
 
         self.count += (!values.is_null()) as u64;
-        self.sum = sum::sum(&self.sum, values)?;
+        let (value, non_finite) = sum::split_non_finite(values);
+        self.sum = sum::sum(&self.sum, &value)?;
+        self.non_finite.add(non_finite);
 
         Ok(())
     }
@@ -184,7 +193,30 @@ impl Accumulator for AvgAccumulator {
         let values = &values[0];
 
         self.count += (values.len() - values.data().null_count()) as u64;
-        self.sum = sum::sum(&self.sum, &sum::sum_batch(values)?)?;
+        let (delta, non_finite) = sum::sum_finite_batch(values)?;
+        self.sum = sum::sum(&self.sum, &delta)?;
+        self.non_finite.add(non_finite);
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = &values[0];
+
+        self.count -= (values.len() - values.data().null_count()) as u64;
+        // the sum is null again once all the values are retracted, so that the
+        // average of no values is null
+        if self.count == 0 {
+            self.sum = ScalarValue::try_from(&self.sum.get_datatype())?;
+            self.non_finite = sum::NonFinite::default();
+        } else {
+            let (delta, non_finite) = sum::sum_finite_batch(values)?;
+            self.sum = sum::subtract(&self.sum, &delta)?;
+            self.non_finite.sub(non_finite);
+        }
         Ok(())
     }
 
@@ -198,7 +230,9 @@ impl Accumulator for AvgAccumulator {
         };
 
         // sums are summed
-        self.sum = sum::sum(&self.sum, &states[1])?;
+        let (value, non_finite) = sum::split_non_finite(&states[1]);
+        self.sum = sum::sum(&self.sum, &value)?;
+        self.non_finite.add(non_finite);
         Ok(())
     }
 
@@ -208,12 +242,14 @@ impl Accumulator for AvgAccumulator {
         self.count += compute::sum(counts).unwrap_or(0);
 
         // sums are summed
-        self.sum = sum::sum(&self.sum, &sum::sum_batch(&states[1])?)?;
+        let (delta, non_finite) = sum::sum_finite_batch(&states[1])?;
+        self.sum = sum::sum(&self.sum, &delta)?;
+        self.non_finite.add(non_finite);
         Ok(())
     }
 
     fn evaluate(&self) -> Result<ScalarValue> {
-        match self.sum {
+        match self.non_finite.apply(&self.sum) {
             ScalarValue::Float64(e) => {
                 Ok(ScalarValue::Float64(e.map(|f| f / self.count as f64)))
             }
@@ -391,6 +427,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn avg_f64_retract_batch_non_finite() -> Result<()> {
+        let a: ArrayRef =
+            Arc::new(Float64Array::from(vec![1_f64, f64::INFINITY, 2_f64, 4_f64]));
+        let mut accum = AvgAccumulator::try_new(&DataType::Float64)?;
+        accum.update_batch(&[a.slice(0, 3)])?;
+        assert_eq!(ScalarValue::from(f64::INFINITY), accum.evaluate()?);
+
+        // the average is finite again once the infinite value is retracted
+        accum.update_batch(&[a.slice(3, 1)])?;
+        accum.retract_batch(&[a.slice(0, 2)])?;
+        assert_eq!(ScalarValue::from(3_f64), accum.evaluate()?);
+        Ok(())
+    }
+
     fn aggregate(
         batch: &RecordBatch,
         agg: Arc<dyn AggregateExpr>,
@@ -0,0 +1,249 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::covariance::{for_each_pair, CovarianceAccumulator};
+use super::format_state_name;
+use super::stats::StatsType;
+use super::variance::VarianceAccumulator;
+use crate::error::Result;
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::ArrayRef;
+use arrow::datatypes::{DataType, Field};
+
+/// CORR aggregate expression
+#[derive(Debug)]
+pub struct Correlation {
+    name: String,
+    expr1: Arc<dyn PhysicalExpr>,
+    expr2: Arc<dyn PhysicalExpr>,
+}
+
+impl Correlation {
+    /// Create a new CORR aggregate function
+    pub fn new(
+        expr1: Arc<dyn PhysicalExpr>,
+        expr2: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expr1,
+            expr2,
+        }
+    }
+}
+
+impl AggregateExpr for Correlation {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(CorrelationAccumulator::new()))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        let field = |state: &str, data_type: DataType| {
+            Field::new(&format_state_name(&self.name, state), data_type, true)
+        };
+        Ok(vec![
+            field("count", DataType::UInt64),
+            field("mean1", DataType::Float64),
+            field("mean2", DataType::Float64),
+            field("co_moment", DataType::Float64),
+            field("m2_1", DataType::Float64),
+            field("m2_2", DataType::Float64),
+        ])
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr1.clone(), self.expr2.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// An accumulator to compute the Pearson correlation coefficient, the
+/// covariance of the pairs of values divided by the product of their standard
+/// deviations. Its state is the state of the covariance followed by the sums
+/// of squares of the variances, which share the count and the means.
+#[derive(Debug)]
+pub struct CorrelationAccumulator {
+    covariance: CovarianceAccumulator,
+    variance1: VarianceAccumulator,
+    variance2: VarianceAccumulator,
+}
+
+impl CorrelationAccumulator {
+    /// Creates a new `CorrelationAccumulator`
+    pub fn new() -> Self {
+        Self {
+            covariance: CovarianceAccumulator::new(StatsType::Population),
+            variance1: VarianceAccumulator::new(StatsType::Population),
+            variance2: VarianceAccumulator::new(StatsType::Population),
+        }
+    }
+}
+
+impl Default for CorrelationAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Accumulator for CorrelationAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        let mut state = self.covariance.state()?;
+        state.push(self.variance1.state()?.swap_remove(2));
+        state.push(self.variance2.state()?.swap_remove(2));
+        Ok(state)
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.update_batch(&[values[0].to_array(), values[1].to_array()])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for_each_pair(&values[0], &values[1], |value1, value2| {
+            self.covariance.update_pair(value1, value2);
+            self.variance1.update_value(value1);
+            self.variance2.update_value(value2);
+        })
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for_each_pair(&values[0], &values[1], |value1, value2| {
+            self.covariance.retract_pair(value1, value2);
+            self.variance1.retract_value(value1);
+            self.variance2.retract_value(value2);
+        })
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        let states = states.iter().map(|s| s.to_array()).collect::<Vec<_>>();
+        self.merge_batch(&states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.covariance.merge_batch(&states[0..4])?;
+        self.variance1.merge_batch(&[
+            states[0].clone(),
+            states[1].clone(),
+            states[4].clone(),
+        ])?;
+        self.variance2.merge_batch(&[
+            states[0].clone(),
+            states[2].clone(),
+            states[5].clone(),
+        ])
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        let correlation = match (
+            self.covariance.covariance(),
+            self.variance1.variance(),
+            self.variance2.variance(),
+        ) {
+            // the correlation is undefined if either value is constant
+            (Some(covariance), Some(variance1), Some(variance2))
+                if variance1 > 0.0 && variance2 > 0.0 =>
+            {
+                Some(covariance / (variance1 * variance2).sqrt())
+            }
+            _ => None,
+        };
+        Ok(ScalarValue::Float64(correlation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use arrow::array::{Float64Array, Int32Array};
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn correlation(values1: ArrayRef, values2: ArrayRef) -> Result<ScalarValue> {
+        let schema = Schema::new(vec![
+            Field::new("a", values1.data_type().clone(), true),
+            Field::new("b", values2.data_type().clone(), true),
+        ]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema.clone()), vec![values1, values2])?;
+        let agg = Arc::new(Correlation::new(
+            col("a", &schema)?,
+            col("b", &schema)?,
+            "bla",
+        ));
+        aggregate(&batch, agg)
+    }
+
+    #[test]
+    fn correlation_i32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3, 4]));
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![2, 4, 6, 8]));
+        assert_eq!(correlation(a.clone(), b)?, ScalarValue::from(1_f64));
+
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![8, 6, 4, 2]));
+        assert_eq!(correlation(a, b)?, ScalarValue::from(-1_f64));
+        Ok(())
+    }
+
+    #[test]
+    fn correlation_of_constant_values() -> Result<()> {
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![1_f64, 2_f64, 3_f64]));
+        let b: ArrayRef = Arc::new(Float64Array::from(vec![5_f64, 5_f64, 5_f64]));
+        assert_eq!(correlation(a, b)?, ScalarValue::Float64(None));
+        Ok(())
+    }
+
+    #[test]
+    fn correlation_merge() -> Result<()> {
+        let mut left = CorrelationAccumulator::new();
+        left.update_batch(&[
+            Arc::new(Float64Array::from(vec![1_f64, 2_f64])),
+            Arc::new(Float64Array::from(vec![2_f64, 4_f64])),
+        ])?;
+        let mut right = CorrelationAccumulator::new();
+        right.update_batch(&[
+            Arc::new(Float64Array::from(vec![3_f64, 4_f64])),
+            Arc::new(Float64Array::from(vec![6_f64, 8_f64])),
+        ])?;
+
+        left.merge(&right.state()?)?;
+        assert_eq!(left.evaluate()?, ScalarValue::from(1_f64));
+        Ok(())
+    }
+}
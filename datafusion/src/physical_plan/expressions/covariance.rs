@@ -0,0 +1,343 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::format_state_name;
+use super::stats::{to_f64_array, StatsType};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::{Array, ArrayRef, Float64Array, UInt64Array};
+use arrow::datatypes::{DataType, Field};
+
+/// COVAR_SAMP and COVAR_POP aggregate expression
+#[derive(Debug)]
+pub struct Covariance {
+    name: String,
+    expr1: Arc<dyn PhysicalExpr>,
+    expr2: Arc<dyn PhysicalExpr>,
+    stats_type: StatsType,
+}
+
+impl Covariance {
+    /// Create a new COVARIANCE aggregate function
+    pub fn new(
+        expr1: Arc<dyn PhysicalExpr>,
+        expr2: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+        stats_type: StatsType,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expr1,
+            expr2,
+            stats_type,
+        }
+    }
+
+    /// Whether this is the sample or the population covariance
+    pub fn stats_type(&self) -> StatsType {
+        self.stats_type
+    }
+}
+
+impl AggregateExpr for Covariance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(CovarianceAccumulator::new(self.stats_type)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(vec![
+            Field::new(
+                &format_state_name(&self.name, "count"),
+                DataType::UInt64,
+                true,
+            ),
+            Field::new(
+                &format_state_name(&self.name, "mean1"),
+                DataType::Float64,
+                true,
+            ),
+            Field::new(
+                &format_state_name(&self.name, "mean2"),
+                DataType::Float64,
+                true,
+            ),
+            Field::new(
+                &format_state_name(&self.name, "co_moment"),
+                DataType::Float64,
+                true,
+            ),
+        ])
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr1.clone(), self.expr2.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Calls `f` with every pair of values of `values1` and `values2` in which
+/// neither value is null
+pub(crate) fn for_each_pair(
+    values1: &ArrayRef,
+    values2: &ArrayRef,
+    mut f: impl FnMut(f64, f64),
+) -> Result<()> {
+    let values1 = to_f64_array(values1)?;
+    let values2 = to_f64_array(values2)?;
+    values1.iter().zip(values2.iter()).for_each(|pair| {
+        if let (Some(value1), Some(value2)) = pair {
+            f(value1, value2)
+        }
+    });
+    Ok(())
+}
+
+/// An accumulator to compute the covariance, keeping the count and the means
+/// of the pairs of values and the sum of the products of their differences
+/// from the means (the co-moment), updated like Welford's online variance.
+#[derive(Debug)]
+pub struct CovarianceAccumulator {
+    count: u64,
+    mean1: f64,
+    mean2: f64,
+    co_moment: f64,
+    stats_type: StatsType,
+}
+
+impl CovarianceAccumulator {
+    /// Creates a new `CovarianceAccumulator`
+    pub fn new(stats_type: StatsType) -> Self {
+        Self {
+            count: 0,
+            mean1: 0.0,
+            mean2: 0.0,
+            co_moment: 0.0,
+            stats_type,
+        }
+    }
+
+    pub(crate) fn update_pair(&mut self, value1: f64, value2: f64) {
+        self.count += 1;
+        let delta1 = value1 - self.mean1;
+        self.mean1 += delta1 / self.count as f64;
+        self.mean2 += (value2 - self.mean2) / self.count as f64;
+        self.co_moment += delta1 * (value2 - self.mean2);
+    }
+
+    pub(crate) fn retract_pair(&mut self, value1: f64, value2: f64) {
+        if self.count <= 1 {
+            *self = Self::new(self.stats_type);
+            return;
+        }
+        self.count -= 1;
+        self.mean1 -= (value1 - self.mean1) / self.count as f64;
+        self.co_moment -= (value1 - self.mean1) * (value2 - self.mean2);
+        self.mean2 -= (value2 - self.mean2) / self.count as f64;
+    }
+
+    /// The covariance of the pairs, `None` if it is undefined
+    pub(crate) fn covariance(&self) -> Option<f64> {
+        self.stats_type
+            .divisor(self.count)
+            .map(|divisor| self.co_moment / divisor)
+    }
+}
+
+impl Accumulator for CovarianceAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::from(self.count),
+            ScalarValue::from(self.mean1),
+            ScalarValue::from(self.mean2),
+            ScalarValue::from(self.co_moment),
+        ])
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.update_batch(&[values[0].to_array(), values[1].to_array()])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for_each_pair(&values[0], &values[1], |value1, value2| {
+            self.update_pair(value1, value2)
+        })
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        for_each_pair(&values[0], &values[1], |value1, value2| {
+            self.retract_pair(value1, value2)
+        })
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        let states = states.iter().map(|s| s.to_array()).collect::<Vec<_>>();
+        self.merge_batch(&states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let invalid_state =
+            || DataFusionError::Internal("Invalid covariance state".to_string());
+        let counts = states[0]
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(invalid_state)?;
+        let floats = states[1..4]
+            .iter()
+            .map(|state| {
+                state
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .ok_or_else(invalid_state)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (means1, means2, co_moments) = (floats[0], floats[1], floats[2]);
+
+        for i in 0..counts.len() {
+            let count = counts.value(i);
+            if count == 0 {
+                continue;
+            }
+            let total = self.count + count;
+            let delta1 = means1.value(i) - self.mean1;
+            let delta2 = means2.value(i) - self.mean2;
+            self.mean1 += delta1 * count as f64 / total as f64;
+            self.mean2 += delta2 * count as f64 / total as f64;
+            self.co_moment += co_moments.value(i)
+                + delta1 * delta2 * self.count as f64 * count as f64 / total as f64;
+            self.count = total;
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Float64(self.covariance()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn covariance(
+        values1: ArrayRef,
+        values2: ArrayRef,
+        stats_type: StatsType,
+    ) -> Result<ScalarValue> {
+        let schema = Schema::new(vec![
+            Field::new("a", values1.data_type().clone(), true),
+            Field::new("b", values2.data_type().clone(), true),
+        ]);
+        let batch =
+            RecordBatch::try_new(Arc::new(schema.clone()), vec![values1, values2])?;
+        let agg = Arc::new(Covariance::new(
+            col("a", &schema)?,
+            col("b", &schema)?,
+            "bla",
+            stats_type,
+        ));
+        aggregate(&batch, agg)
+    }
+
+    #[test]
+    fn covariance_i32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3]));
+        let b: ArrayRef = Arc::new(Int32Array::from(vec![4, 5, 9]));
+        assert_eq!(
+            covariance(a.clone(), b.clone(), StatsType::Sample)?,
+            ScalarValue::from(2.5_f64)
+        );
+        assert_eq!(
+            covariance(a, b, StatsType::Population)?,
+            ScalarValue::from(5_f64 / 3_f64)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn covariance_skips_pairs_with_nulls() -> Result<()> {
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(1_f64),
+            None,
+            Some(2_f64),
+            Some(3_f64),
+            Some(4_f64),
+        ]));
+        let b: ArrayRef = Arc::new(Float64Array::from(vec![
+            Some(4_f64),
+            Some(1_f64),
+            Some(5_f64),
+            Some(9_f64),
+            None,
+        ]));
+        assert_eq!(
+            covariance(a, b, StatsType::Sample)?,
+            ScalarValue::from(2.5_f64)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn covariance_merge_and_retract() -> Result<()> {
+        let mut left = CovarianceAccumulator::new(StatsType::Sample);
+        left.update_batch(&[
+            Arc::new(Float64Array::from(vec![1_f64])),
+            Arc::new(Float64Array::from(vec![4_f64])),
+        ])?;
+        let mut right = CovarianceAccumulator::new(StatsType::Sample);
+        right.update_batch(&[
+            Arc::new(Float64Array::from(vec![2_f64, 3_f64, 10_f64])),
+            Arc::new(Float64Array::from(vec![5_f64, 9_f64, 0_f64])),
+        ])?;
+
+        left.merge(&right.state()?)?;
+        left.retract_batch(&[
+            Arc::new(Float64Array::from(vec![10_f64])),
+            Arc::new(Float64Array::from(vec![0_f64])),
+        ])?;
+        match left.evaluate()? {
+            ScalarValue::Float64(Some(v)) => assert!((v - 2.5).abs() < 1e-9),
+            other => panic!("unexpected covariance {:?}", other),
+        }
+        Ok(())
+    }
+}
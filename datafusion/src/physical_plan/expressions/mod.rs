@@ -25,7 +25,9 @@ use crate::physical_plan::PhysicalExpr;
 use arrow::compute::kernels::sort::{SortColumn, SortOptions};
 use arrow::record_batch::RecordBatch;
 
+mod aggregate_filter;
 mod approx_distinct;
+mod approx_percentile_cont;
 mod array_agg;
 mod average;
 #[macro_use]
@@ -34,7 +36,9 @@ mod case;
 mod cast;
 mod coercion;
 mod column;
+mod correlation;
 mod count;
+mod covariance;
 mod cume_dist;
 mod get_indexed_field;
 mod in_list;
@@ -47,18 +51,25 @@ mod min_max;
 mod negative;
 mod not;
 mod nth_value;
+mod ntile;
 mod nullif;
+mod percentile_cont;
 mod rank;
 mod row_number;
+mod stats;
+mod stddev;
 mod sum;
 mod try_cast;
+mod variance;
 
 /// Module with some convenient methods used in expression building
 pub mod helpers {
     pub use super::min_max::{max, min};
 }
 
+pub use aggregate_filter::AggregateFilter;
 pub use approx_distinct::ApproxDistinct;
+pub use approx_percentile_cont::{ApproxPercentileAccumulator, ApproxPercentileCont};
 pub use array_agg::ArrayAgg;
 pub(crate) use average::is_avg_support_arg_type;
 pub use average::{avg_return_type, Avg, AvgAccumulator};
@@ -68,7 +79,9 @@ pub use cast::{
     cast, cast_column, cast_with_options, CastExpr, DEFAULT_DATAFUSION_CAST_OPTIONS,
 };
 pub use column::{col, Column};
+pub use correlation::{Correlation, CorrelationAccumulator};
 pub use count::Count;
+pub use covariance::{Covariance, CovarianceAccumulator};
 pub use cume_dist::cume_dist;
 pub use get_indexed_field::GetIndexedFieldExpr;
 pub use in_list::{in_list, InListExpr};
@@ -81,12 +94,17 @@ pub(crate) use min_max::{MaxAccumulator, MinAccumulator};
 pub use negative::{negative, NegativeExpr};
 pub use not::{not, NotExpr};
 pub use nth_value::NthValue;
+pub use ntile::Ntile;
 pub use nullif::{nullif_func, SUPPORTED_NULLIF_TYPES};
+pub use percentile_cont::{Median, PercentileCont, PercentileContAccumulator};
 pub use rank::{dense_rank, percent_rank, rank};
 pub use row_number::RowNumber;
+pub use stats::StatsType;
+pub use stddev::{Stddev, StddevAccumulator};
 pub(crate) use sum::is_sum_support_arg_type;
 pub use sum::{sum_return_type, Sum};
 pub use try_cast::{try_cast, TryCastExpr};
+pub use variance::{Variance, VarianceAccumulator};
 
 /// returns the name of the state
 pub fn format_state_name(name: &str, state_name: &str) -> String {
@@ -135,6 +153,28 @@ impl PhysicalSortExpr {
     }
 }
 
+/// Returns true if data sorted by `provided` is also sorted by `required`,
+/// that is if `required` is a prefix of `provided`. Only sorts on columns
+/// are compared.
+pub fn ordering_satisfies(
+    provided: Option<&[PhysicalSortExpr]>,
+    required: &[PhysicalSortExpr],
+) -> bool {
+    let provided = match provided {
+        Some(provided) => provided,
+        None => return false,
+    };
+    required.len() <= provided.len()
+        && required.iter().zip(provided).all(|(required, provided)| {
+            let required_column = required.expr.as_any().downcast_ref::<Column>();
+            let provided_column = provided.expr.as_any().downcast_ref::<Column>();
+            required_column.is_some()
+                && required_column == provided_column
+                && required.options.descending == provided.options.descending
+                && required.options.nulls_first == provided.options.nulls_first
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,328 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use super::stats::to_f64_array;
+use super::{format_state_name, Literal};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::ArrayRef;
+use arrow::datatypes::{DataType, Field};
+
+/// Returns the percentile, between 0 and 1, of the literal `expr`. The
+/// percentile of an aggregate is the same for all groups, so it must be a
+/// constant.
+pub(crate) fn validate_percentile_expr(
+    expr: &Arc<dyn PhysicalExpr>,
+    name: &str,
+) -> Result<f64> {
+    let value = expr
+        .as_any()
+        .downcast_ref::<Literal>()
+        .map(|literal| literal.value())
+        .filter(|value| !value.is_null())
+        .ok_or_else(|| {
+            DataFusionError::Plan(format!(
+                "The percentile of {} must be a literal, got {}",
+                name, expr
+            ))
+        })?;
+    let percentile = to_f64_array(&value.to_array())?.value(0);
+    if !(0.0..=1.0).contains(&percentile) {
+        return Err(DataFusionError::Plan(format!(
+            "The percentile of {} must be between 0 and 1, got {}",
+            name, percentile
+        )));
+    }
+    Ok(percentile)
+}
+
+/// PERCENTILE_CONT aggregate expression, the value at the given percentile of
+/// the values, interpolated between the closest values. It keeps all the
+/// values in memory, see [`super::ApproxPercentileCont`] for large inputs.
+#[derive(Debug)]
+pub struct PercentileCont {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    percentile_expr: Arc<dyn PhysicalExpr>,
+    percentile: f64,
+}
+
+impl PercentileCont {
+    /// Create a new PERCENTILE_CONT aggregate function, `percentile_expr`
+    /// must be a literal between 0 and 1
+    pub fn try_new(
+        expr: Arc<dyn PhysicalExpr>,
+        percentile_expr: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let percentile = validate_percentile_expr(&percentile_expr, &name)?;
+        Ok(Self {
+            name,
+            expr,
+            percentile_expr,
+            percentile,
+        })
+    }
+
+    /// The percentile, between 0 and 1
+    pub fn percentile(&self) -> f64 {
+        self.percentile
+    }
+}
+
+impl AggregateExpr for PercentileCont {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(PercentileContAccumulator::new(self.percentile)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(vec![percentile_cont_state_field(&self.name)])
+    }
+
+    /// The percentile is one of the expressions so that the aggregate can be
+    /// created again from its expressions, it is not used by the accumulator.
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone(), self.percentile_expr.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// MEDIAN aggregate expression, the 50th [`PercentileCont`]
+#[derive(Debug)]
+pub struct Median {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+}
+
+impl Median {
+    /// Create a new MEDIAN aggregate function
+    pub fn new(expr: Arc<dyn PhysicalExpr>, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            expr,
+        }
+    }
+}
+
+impl AggregateExpr for Median {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(PercentileContAccumulator::new(0.5)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(vec![percentile_cont_state_field(&self.name)])
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn percentile_cont_state_field(name: &str) -> Field {
+    Field::new(
+        &format_state_name(name, "values"),
+        DataType::List(Box::new(Field::new("item", DataType::Float64, true))),
+        false,
+    )
+}
+
+/// An accumulator to compute a percentile, keeping all the values
+#[derive(Debug)]
+pub struct PercentileContAccumulator {
+    values: Vec<f64>,
+    percentile: f64,
+}
+
+impl PercentileContAccumulator {
+    /// Creates a new `PercentileContAccumulator` of the `percentile`,
+    /// between 0 and 1
+    pub fn new(percentile: f64) -> Self {
+        Self {
+            values: vec![],
+            percentile,
+        }
+    }
+}
+
+impl Accumulator for PercentileContAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        let values = self.values.iter().map(|v| ScalarValue::from(*v)).collect();
+        Ok(vec![ScalarValue::List(
+            Some(Box::new(values)),
+            Box::new(DataType::Float64),
+        )])
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.update_batch(&[values[0].to_array()])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        self.values
+            .extend(to_f64_array(&values[0])?.iter().flatten());
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        match &states[0] {
+            ScalarValue::List(Some(values), _) => {
+                for value in values.iter() {
+                    match value {
+                        ScalarValue::Float64(Some(v)) => self.values.push(*v),
+                        ScalarValue::Float64(None) => {}
+                        _ => {
+                            return Err(DataFusionError::Internal(format!(
+                                "Invalid percentile state value {:?}",
+                                value
+                            )))
+                        }
+                    }
+                }
+                Ok(())
+            }
+            ScalarValue::List(None, _) => Ok(()),
+            state => Err(DataFusionError::Internal(format!(
+                "Invalid percentile state {:?}",
+                state
+            ))),
+        }
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        if self.values.is_empty() {
+            return Ok(ScalarValue::Float64(None));
+        }
+        let mut values = self.values.clone();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let position = self.percentile * (values.len() - 1) as f64;
+        let lower = values[position.floor() as usize];
+        let upper = values[position.ceil() as usize];
+        Ok(ScalarValue::from(
+            lower + (upper - lower) * (position - position.floor()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use crate::physical_plan::expressions::{col, lit};
+    use arrow::array::{Float64Array, Int32Array};
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn percentile_cont(values: ArrayRef, percentile: f64) -> Result<ScalarValue> {
+        let schema = Schema::new(vec![Field::new("a", values.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![values])?;
+        let agg = Arc::new(PercentileCont::try_new(
+            col("a", &schema)?,
+            lit(ScalarValue::from(percentile)),
+            "bla",
+        )?);
+        aggregate(&batch, agg)
+    }
+
+    #[test]
+    fn percentile_cont_i32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![4, 1, 3, 2]));
+        assert_eq!(percentile_cont(a.clone(), 0.0)?, ScalarValue::from(1_f64));
+        assert_eq!(percentile_cont(a.clone(), 0.5)?, ScalarValue::from(2.5_f64));
+        assert_eq!(
+            percentile_cont(a.clone(), 0.75)?,
+            ScalarValue::from(3.25_f64)
+        );
+        assert_eq!(percentile_cont(a, 1.0)?, ScalarValue::from(4_f64));
+        Ok(())
+    }
+
+    #[test]
+    fn percentile_cont_with_nulls() -> Result<()> {
+        let a: ArrayRef =
+            Arc::new(Float64Array::from(vec![Some(3_f64), None, Some(1_f64)]));
+        assert_eq!(percentile_cont(a, 0.5)?, ScalarValue::from(2_f64));
+
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![None, None]));
+        assert_eq!(percentile_cont(a, 0.5)?, ScalarValue::Float64(None));
+        Ok(())
+    }
+
+    #[test]
+    fn median_merge() -> Result<()> {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let median = Median::new(col("a", &schema)?, "bla");
+        let mut left = median.create_accumulator()?;
+        left.update_batch(&[Arc::new(Float64Array::from(vec![5_f64, 1_f64]))])?;
+        let mut right = median.create_accumulator()?;
+        right.update_batch(&[Arc::new(Float64Array::from(vec![2_f64]))])?;
+
+        left.merge(&right.state()?)?;
+        assert_eq!(left.evaluate()?, ScalarValue::from(2_f64));
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_percentile() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Float64, true)]);
+        let expr = col("a", &schema).unwrap();
+
+        let err = PercentileCont::try_new(expr.clone(), lit(ScalarValue::from(1.5)), "p")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: The percentile of p must be between 0 and 1, got 1.5"
+        );
+
+        let err = PercentileCont::try_new(expr.clone(), expr, "p").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error during planning: The percentile of p must be a literal, got a@0"
+        );
+    }
+}
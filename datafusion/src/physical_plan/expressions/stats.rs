@@ -0,0 +1,51 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Shared definitions of the statistical aggregate expressions
+
+use crate::error::Result;
+use arrow::array::{ArrayRef, Float64Array};
+use arrow::compute::cast;
+use arrow::datatypes::DataType;
+
+/// Whether a statistical aggregate describes the input as a sample of a
+/// larger population or as the whole population
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsType {
+    /// the sample statistic, dividing the sums of squares by `n - 1`
+    Sample,
+    /// the population statistic, dividing the sums of squares by `n`
+    Population,
+}
+
+impl StatsType {
+    /// The divisor of the sums of squares of `count` values, `None` if the
+    /// statistic is undefined for that many values
+    pub(crate) fn divisor(&self, count: u64) -> Option<f64> {
+        match self {
+            StatsType::Sample if count > 1 => Some((count - 1) as f64),
+            StatsType::Population if count > 0 => Some(count as f64),
+            _ => None,
+        }
+    }
+}
+
+/// Casts the argument of a statistical aggregate to `Float64`
+pub(crate) fn to_f64_array(values: &ArrayRef) -> Result<Float64Array> {
+    let values = cast(values, &DataType::Float64)?;
+    Ok(Float64Array::from(values.data().clone()))
+}
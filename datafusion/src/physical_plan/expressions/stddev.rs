@@ -0,0 +1,178 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::stats::StatsType;
+use super::variance::{variance_state_fields, VarianceAccumulator};
+use crate::error::Result;
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::ArrayRef;
+use arrow::datatypes::{DataType, Field};
+
+/// STDDEV_SAMP and STDDEV_POP aggregate expression
+#[derive(Debug)]
+pub struct Stddev {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    stats_type: StatsType,
+}
+
+impl Stddev {
+    /// Create a new STDDEV aggregate function
+    pub fn new(
+        expr: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+        stats_type: StatsType,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expr,
+            stats_type,
+        }
+    }
+
+    /// Whether this is the sample or the population standard deviation
+    pub fn stats_type(&self) -> StatsType {
+        self.stats_type
+    }
+}
+
+impl AggregateExpr for Stddev {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(StddevAccumulator::new(self.stats_type)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(variance_state_fields(&self.name))
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// An accumulator to compute the standard deviation, the square root of the
+/// variance
+#[derive(Debug)]
+pub struct StddevAccumulator {
+    variance: VarianceAccumulator,
+}
+
+impl StddevAccumulator {
+    /// Creates a new `StddevAccumulator`
+    pub fn new(stats_type: StatsType) -> Self {
+        Self {
+            variance: VarianceAccumulator::new(stats_type),
+        }
+    }
+}
+
+impl Accumulator for StddevAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        self.variance.state()
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.variance.update(values)
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        self.variance.update_batch(values)
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        self.variance.retract_batch(values)
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        self.variance.merge(states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        self.variance.merge_batch(states)
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        // rounding errors can make the variance of equal values slightly negative
+        Ok(ScalarValue::Float64(
+            self.variance.variance().map(|v| v.max(0.0).sqrt()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use arrow::array::{Float64Array, Int64Array};
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn stddev(values: ArrayRef, stats_type: StatsType) -> Result<ScalarValue> {
+        let schema = Schema::new(vec![Field::new("a", values.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![values])?;
+        let agg = Arc::new(Stddev::new(col("a", &schema)?, "bla", stats_type));
+        aggregate(&batch, agg)
+    }
+
+    #[test]
+    fn stddev_i64() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int64Array::from(vec![2, 4, 4, 4, 5, 5, 7, 9]));
+        assert_eq!(
+            stddev(a.clone(), StatsType::Population)?,
+            ScalarValue::from(2_f64)
+        );
+        assert_eq!(
+            stddev(a, StatsType::Sample)?,
+            ScalarValue::from((32_f64 / 7_f64).sqrt())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn stddev_all_nulls() -> Result<()> {
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![None, None]));
+        assert_eq!(
+            stddev(a.clone(), StatsType::Population)?,
+            ScalarValue::Float64(None)
+        );
+        assert_eq!(stddev(a, StatsType::Sample)?, ScalarValue::Float64(None));
+        Ok(())
+    }
+}
@@ -142,7 +142,14 @@ impl AggregateExpr for Sum {
 
 #[derive(Debug)]
 struct SumAccumulator {
+    // the sum of the finite values
     sum: ScalarValue,
+    // the number of non-null values added, so that the sum becomes null
+    // again once all of them are retracted
+    count: u64,
+    // the non-finite float values added, kept out of `sum` so that it is
+    // finite again once they are retracted
+    non_finite: NonFinite,
 }
 
 impl SumAccumulator {
@@ -150,10 +157,104 @@ impl SumAccumulator {
     pub fn try_new(data_type: &DataType) -> Result<Self> {
         Ok(Self {
             sum: ScalarValue::try_from(data_type)?,
+            count: 0,
+            non_finite: NonFinite::default(),
         })
     }
 }
 
+/// The numbers of non-finite float values of a sum. Subtracting an infinite
+/// value from an infinite sum is NaN, so these values are counted apart from
+/// the sum of the finite values, from which they can be retracted.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(super) struct NonFinite {
+    nan: u64,
+    pos_inf: u64,
+    neg_inf: u64,
+}
+
+impl NonFinite {
+    /// Counts `value` if it is not finite
+    fn of(value: f64) -> Self {
+        Self {
+            nan: value.is_nan() as u64,
+            pos_inf: (value.is_infinite() && value.is_sign_positive()) as u64,
+            neg_inf: (value.is_infinite() && value.is_sign_negative()) as u64,
+        }
+    }
+
+    pub(super) fn add(&mut self, other: Self) {
+        self.nan += other.nan;
+        self.pos_inf += other.pos_inf;
+        self.neg_inf += other.neg_inf;
+    }
+
+    pub(super) fn sub(&mut self, other: Self) {
+        self.nan -= other.nan;
+        self.pos_inf -= other.pos_inf;
+        self.neg_inf -= other.neg_inf;
+    }
+
+    /// Returns the sum of the finite values `sum` and of these values
+    pub(super) fn apply(&self, sum: &ScalarValue) -> ScalarValue {
+        let value = if self.nan > 0 || (self.pos_inf > 0 && self.neg_inf > 0) {
+            f64::NAN
+        } else if self.pos_inf > 0 {
+            f64::INFINITY
+        } else if self.neg_inf > 0 {
+            f64::NEG_INFINITY
+        } else {
+            return sum.clone();
+        };
+        match sum {
+            ScalarValue::Float64(_) => ScalarValue::Float64(Some(value)),
+            ScalarValue::Float32(_) => ScalarValue::Float32(Some(value as f32)),
+            other => other.clone(),
+        }
+    }
+}
+
+/// Splits the float scalar `value` into its finite value and its non-finite
+/// value. The other scalars are finite.
+pub(super) fn split_non_finite(value: &ScalarValue) -> (ScalarValue, NonFinite) {
+    match value {
+        ScalarValue::Float64(Some(v)) if !v.is_finite() => {
+            (ScalarValue::Float64(None), NonFinite::of(*v))
+        }
+        ScalarValue::Float32(Some(v)) if !v.is_finite() => {
+            (ScalarValue::Float32(None), NonFinite::of(*v as f64))
+        }
+        other => (other.clone(), NonFinite::default()),
+    }
+}
+
+// sums the finite values of a float array, counting the others
+macro_rules! typed_sum_finite_batch {
+    ($VALUES:expr, $ARRAYTYPE:ident, $SCALAR:ident) => {{
+        let array = $VALUES.as_any().downcast_ref::<$ARRAYTYPE>().unwrap();
+        let mut non_finite = NonFinite::default();
+        let mut sum = None;
+        for value in array.iter().flatten() {
+            if value.is_finite() {
+                sum = Some(sum.unwrap_or(0.0) + value);
+            } else {
+                non_finite.add(NonFinite::of(f64::from(value)));
+            }
+        }
+        (ScalarValue::$SCALAR(sum), non_finite)
+    }};
+}
+
+/// Sums the finite values of `values`, returning the sum and the non-finite
+/// values apart. Only float arrays have non-finite values.
+pub(super) fn sum_finite_batch(values: &ArrayRef) -> Result<(ScalarValue, NonFinite)> {
+    Ok(match values.data_type() {
+        DataType::Float64 => typed_sum_finite_batch!(values, Float64Array, Float64),
+        DataType::Float32 => typed_sum_finite_batch!(values, Float32Array, Float32),
+        _ => (sum_batch(values)?, NonFinite::default()),
+    })
+}
+
 // returns the new value after sum with the new values, taking nullability into account
 macro_rules! typed_sum_delta_batch {
     ($VALUES:expr, $ARRAYTYPE:ident, $SCALAR:ident) => {{
@@ -348,20 +449,134 @@ pub(super) fn sum(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
     })
 }
 
+// returns the difference of two scalar values, including coercion of the
+// delta into $TYPE. The delta is a part of the old value, so the old value
+// can't be null when the delta is not.
+macro_rules! typed_sub {
+    ($OLD_VALUE:expr, $DELTA:expr, $SCALAR:ident, $TYPE:ident) => {{
+        ScalarValue::$SCALAR(match ($OLD_VALUE, $DELTA) {
+            (Some(a), Some(b)) => Some(a - (*b as $TYPE)),
+            (a, _) => *a,
+        })
+    }};
+}
+
+/// Subtracts from the sum `lhs` the sum `rhs` of values that were added to it
+pub(super) fn subtract(lhs: &ScalarValue, rhs: &ScalarValue) -> Result<ScalarValue> {
+    Ok(match (lhs, rhs) {
+        (ScalarValue::Decimal128(v1, p1, s1), ScalarValue::Decimal128(v2, _, s2))
+            if s1 >= s2 =>
+        {
+            let value = match (v1, v2) {
+                (Some(v1), Some(v2)) => Some(v1 - v2 * 10_i128.pow((s1 - s2) as u32)),
+                (v1, _) => *v1,
+            };
+            ScalarValue::Decimal128(value, *p1, *s1)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Float64(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Float32(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Int64(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Int32(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Int16(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::Int8(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::UInt64(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::UInt32(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::UInt16(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float64(lhs), ScalarValue::UInt8(rhs)) => {
+            typed_sub!(lhs, rhs, Float64, f64)
+        }
+        (ScalarValue::Float32(lhs), ScalarValue::Float32(rhs)) => {
+            typed_sub!(lhs, rhs, Float32, f32)
+        }
+        (ScalarValue::UInt64(lhs), ScalarValue::UInt64(rhs)) => {
+            typed_sub!(lhs, rhs, UInt64, u64)
+        }
+        (ScalarValue::UInt64(lhs), ScalarValue::UInt32(rhs)) => {
+            typed_sub!(lhs, rhs, UInt64, u64)
+        }
+        (ScalarValue::UInt64(lhs), ScalarValue::UInt16(rhs)) => {
+            typed_sub!(lhs, rhs, UInt64, u64)
+        }
+        (ScalarValue::UInt64(lhs), ScalarValue::UInt8(rhs)) => {
+            typed_sub!(lhs, rhs, UInt64, u64)
+        }
+        (ScalarValue::Int64(lhs), ScalarValue::Int64(rhs)) => {
+            typed_sub!(lhs, rhs, Int64, i64)
+        }
+        (ScalarValue::Int64(lhs), ScalarValue::Int32(rhs)) => {
+            typed_sub!(lhs, rhs, Int64, i64)
+        }
+        (ScalarValue::Int64(lhs), ScalarValue::Int16(rhs)) => {
+            typed_sub!(lhs, rhs, Int64, i64)
+        }
+        (ScalarValue::Int64(lhs), ScalarValue::Int8(rhs)) => {
+            typed_sub!(lhs, rhs, Int64, i64)
+        }
+        e => {
+            return Err(DataFusionError::Internal(format!(
+                "Sum is not expected to subtract a scalar {:?}",
+                e
+            )));
+        }
+    })
+}
+
 impl Accumulator for SumAccumulator {
     fn state(&self) -> Result<Vec<ScalarValue>> {
-        Ok(vec![self.sum.clone()])
+        Ok(vec![self.non_finite.apply(&self.sum)])
     }
 
     fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
         // sum(v1, v2, v3) = v1 + v2 + v3
-        self.sum = sum(&self.sum, &values[0])?;
+        self.count += (!values[0].is_null()) as u64;
+        let (value, non_finite) = split_non_finite(&values[0]);
+        self.sum = sum(&self.sum, &value)?;
+        self.non_finite.add(non_finite);
         Ok(())
     }
 
     fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
         let values = &values[0];
-        self.sum = sum(&self.sum, &sum_batch(values)?)?;
+        self.count += (values.len() - values.data().null_count()) as u64;
+        let (delta, non_finite) = sum_finite_batch(values)?;
+        self.sum = sum(&self.sum, &delta)?;
+        self.non_finite.add(non_finite);
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        let values = &values[0];
+        self.count -= (values.len() - values.data().null_count()) as u64;
+        if self.count == 0 {
+            self.sum = ScalarValue::try_from(&self.sum.get_datatype())?;
+            self.non_finite = NonFinite::default();
+        } else {
+            let (delta, non_finite) = sum_finite_batch(values)?;
+            self.sum = subtract(&self.sum, &delta)?;
+            self.non_finite.sub(non_finite);
+        }
         Ok(())
     }
 
@@ -378,7 +593,7 @@ impl Accumulator for SumAccumulator {
     fn evaluate(&self) -> Result<ScalarValue> {
         // TODO: add the checker for overflow
         // For the decimal(precision,_) data type, the absolute of value must be less than 10^precision.
-        Ok(self.sum.clone())
+        Ok(self.non_finite.apply(&self.sum))
     }
 }
 
@@ -605,6 +820,44 @@ mod tests {
         )
     }
 
+    #[test]
+    fn sum_u32_retract_batch() -> Result<()> {
+        let a: ArrayRef = Arc::new(UInt32Array::from(vec![Some(1), None, Some(3)]));
+        let mut accum = SumAccumulator::try_new(&DataType::UInt64)?;
+        accum.update_batch(&[a.clone()])?;
+        assert_eq!(ScalarValue::from(4u64), accum.evaluate()?);
+
+        accum.retract_batch(&[a.slice(0, 2)])?;
+        assert_eq!(ScalarValue::from(3u64), accum.evaluate()?);
+
+        // the sum of no values is null
+        accum.retract_batch(&[a.slice(2, 1)])?;
+        assert_eq!(ScalarValue::UInt64(None), accum.evaluate()?);
+        Ok(())
+    }
+
+    #[test]
+    fn sum_f64_retract_batch_non_finite() -> Result<()> {
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![
+            1_f64,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            2_f64,
+        ]));
+        let mut accum = SumAccumulator::try_new(&DataType::Float64)?;
+        accum.update_batch(&[a.slice(0, 2)])?;
+        assert_eq!(ScalarValue::from(f64::INFINITY), accum.evaluate()?);
+
+        accum.update_batch(&[a.slice(2, 1)])?;
+        assert!(matches!(accum.evaluate()?, ScalarValue::Float64(Some(v)) if v.is_nan()));
+
+        // the sum is finite again once the infinite values are retracted
+        accum.update_batch(&[a.slice(3, 1)])?;
+        accum.retract_batch(&[a.slice(0, 3)])?;
+        assert_eq!(ScalarValue::from(2_f64), accum.evaluate()?);
+        Ok(())
+    }
+
     fn aggregate(
         batch: &RecordBatch,
         agg: Arc<dyn AggregateExpr>,
@@ -0,0 +1,289 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines physical expressions that can evaluated at runtime during query execution
+
+use std::any::Any;
+use std::sync::Arc;
+
+use super::format_state_name;
+use super::stats::{to_f64_array, StatsType};
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{Accumulator, AggregateExpr, PhysicalExpr};
+use crate::scalar::ScalarValue;
+use arrow::array::{Array, ArrayRef, Float64Array, UInt64Array};
+use arrow::datatypes::{DataType, Field};
+
+/// VAR_SAMP and VAR_POP aggregate expression
+#[derive(Debug)]
+pub struct Variance {
+    name: String,
+    expr: Arc<dyn PhysicalExpr>,
+    stats_type: StatsType,
+}
+
+impl Variance {
+    /// Create a new VARIANCE aggregate function
+    pub fn new(
+        expr: Arc<dyn PhysicalExpr>,
+        name: impl Into<String>,
+        stats_type: StatsType,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            expr,
+            stats_type,
+        }
+    }
+
+    /// Whether this is the sample or the population variance
+    pub fn stats_type(&self) -> StatsType {
+        self.stats_type
+    }
+}
+
+impl AggregateExpr for Variance {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn field(&self) -> Result<Field> {
+        Ok(Field::new(&self.name, DataType::Float64, true))
+    }
+
+    fn create_accumulator(&self) -> Result<Box<dyn Accumulator>> {
+        Ok(Box::new(VarianceAccumulator::new(self.stats_type)))
+    }
+
+    fn state_fields(&self) -> Result<Vec<Field>> {
+        Ok(variance_state_fields(&self.name))
+    }
+
+    fn expressions(&self) -> Vec<Arc<dyn PhysicalExpr>> {
+        vec![self.expr.clone()]
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The state fields of a [`VarianceAccumulator`]
+pub(crate) fn variance_state_fields(name: &str) -> Vec<Field> {
+    vec![
+        Field::new(&format_state_name(name, "count"), DataType::UInt64, true),
+        Field::new(&format_state_name(name, "mean"), DataType::Float64, true),
+        Field::new(&format_state_name(name, "m2"), DataType::Float64, true),
+    ]
+}
+
+/// An accumulator to compute the variance with Welford's online algorithm,
+/// which keeps the count, the mean and the sum of the squared differences
+/// from the mean (`m2`) of the values.
+#[derive(Debug)]
+pub struct VarianceAccumulator {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    stats_type: StatsType,
+}
+
+impl VarianceAccumulator {
+    /// Creates a new `VarianceAccumulator`
+    pub fn new(stats_type: StatsType) -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            stats_type,
+        }
+    }
+
+    pub(crate) fn update_value(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub(crate) fn retract_value(&mut self, value: f64) {
+        if self.count <= 1 {
+            *self = Self::new(self.stats_type);
+            return;
+        }
+        let mean = self.mean;
+        self.count -= 1;
+        self.mean -= (value - mean) / self.count as f64;
+        self.m2 -= (value - self.mean) * (value - mean);
+    }
+
+    pub(crate) fn merge_state(&mut self, count: u64, mean: f64, m2: f64) {
+        if count == 0 {
+            return;
+        }
+        let total = self.count + count;
+        let delta = mean - self.mean;
+        self.mean += delta * count as f64 / total as f64;
+        self.m2 += m2 + delta * delta * self.count as f64 * count as f64 / total as f64;
+        self.count = total;
+    }
+
+    /// The variance of the values, `None` if it is undefined
+    pub(crate) fn variance(&self) -> Option<f64> {
+        self.stats_type
+            .divisor(self.count)
+            .map(|divisor| self.m2 / divisor)
+    }
+}
+
+impl Accumulator for VarianceAccumulator {
+    fn state(&self) -> Result<Vec<ScalarValue>> {
+        Ok(vec![
+            ScalarValue::from(self.count),
+            ScalarValue::from(self.mean),
+            ScalarValue::from(self.m2),
+        ])
+    }
+
+    fn update(&mut self, values: &[ScalarValue]) -> Result<()> {
+        self.update_batch(&[values[0].to_array()])
+    }
+
+    fn update_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        to_f64_array(&values[0])?
+            .iter()
+            .flatten()
+            .for_each(|value| self.update_value(value));
+        Ok(())
+    }
+
+    fn supports_retract_batch(&self) -> bool {
+        true
+    }
+
+    fn retract_batch(&mut self, values: &[ArrayRef]) -> Result<()> {
+        to_f64_array(&values[0])?
+            .iter()
+            .flatten()
+            .for_each(|value| self.retract_value(value));
+        Ok(())
+    }
+
+    fn merge(&mut self, states: &[ScalarValue]) -> Result<()> {
+        let states = states.iter().map(|s| s.to_array()).collect::<Vec<_>>();
+        self.merge_batch(&states)
+    }
+
+    fn merge_batch(&mut self, states: &[ArrayRef]) -> Result<()> {
+        let (counts, means, m2s) = downcast_variance_state(states)?;
+        for i in 0..counts.len() {
+            self.merge_state(counts.value(i), means.value(i), m2s.value(i));
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ScalarValue> {
+        Ok(ScalarValue::Float64(self.variance()))
+    }
+}
+
+/// Downcasts the `[count, mean, m2]` state arrays of a [`VarianceAccumulator`]
+pub(crate) fn downcast_variance_state(
+    states: &[ArrayRef],
+) -> Result<(&UInt64Array, &Float64Array, &Float64Array)> {
+    let invalid_state =
+        || DataFusionError::Internal("Invalid variance state".to_string());
+    Ok((
+        states[0]
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(invalid_state)?,
+        states[1]
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(invalid_state)?,
+        states[2]
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .ok_or_else(invalid_state)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::expressions::col;
+    use crate::physical_plan::expressions::tests::aggregate;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::Schema;
+    use arrow::record_batch::RecordBatch;
+
+    fn variance(values: ArrayRef, stats_type: StatsType) -> Result<ScalarValue> {
+        let schema = Schema::new(vec![Field::new("a", values.data_type().clone(), true)]);
+        let batch = RecordBatch::try_new(Arc::new(schema.clone()), vec![values])?;
+        let agg = Arc::new(Variance::new(col("a", &schema)?, "bla", stats_type));
+        aggregate(&batch, agg)
+    }
+
+    #[test]
+    fn variance_i32() -> Result<()> {
+        let a: ArrayRef = Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5]));
+        assert_eq!(
+            variance(a.clone(), StatsType::Sample)?,
+            ScalarValue::from(2.5_f64)
+        );
+        assert_eq!(
+            variance(a, StatsType::Population)?,
+            ScalarValue::from(2_f64)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn variance_with_nulls() -> Result<()> {
+        let a: ArrayRef =
+            Arc::new(Float64Array::from(vec![Some(1_f64), None, Some(3_f64)]));
+        assert_eq!(variance(a, StatsType::Sample)?, ScalarValue::from(2_f64));
+
+        // the sample variance of a single value is undefined
+        let a: ArrayRef = Arc::new(Float64Array::from(vec![Some(1_f64), None]));
+        assert_eq!(
+            variance(a.clone(), StatsType::Sample)?,
+            ScalarValue::Float64(None)
+        );
+        assert_eq!(
+            variance(a, StatsType::Population)?,
+            ScalarValue::from(0_f64)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn variance_merge_and_retract() -> Result<()> {
+        let mut left = VarianceAccumulator::new(StatsType::Population);
+        left.update_batch(&[Arc::new(Float64Array::from(vec![1_f64, 2_f64]))])?;
+        let mut right = VarianceAccumulator::new(StatsType::Population);
+        right.update_batch(&[Arc::new(Float64Array::from(vec![3_f64, 4_f64, 5_f64]))])?;
+
+        left.merge(&right.state()?)?;
+        assert_eq!(left.evaluate()?, ScalarValue::from(2_f64));
+
+        left.retract_batch(&[Arc::new(Float64Array::from(vec![1_f64, 5_f64]))])?;
+        assert_eq!(left.evaluate()?, ScalarValue::from(2_f64 / 3_f64));
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan for reading Arrow IPC files
+
+use crate::datasource::object_store::ObjectReader;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::{
+    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+};
+use arrow::datatypes::SchemaRef;
+use arrow::error::Result as ArrowResult;
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter as IpcFileWriter;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use std::any::Any;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::iter;
+use std::sync::Arc;
+
+use super::file_stream::{BatchIter, FileStream};
+use super::{FileWriter, PhysicalPlanConfig, SchemaAdapter};
+
+/// Execution plan for scanning Arrow IPC files
+#[derive(Debug, Clone)]
+pub struct ArrowExec {
+    base_config: PhysicalPlanConfig,
+    projected_statistics: Statistics,
+    projected_schema: SchemaRef,
+}
+
+impl ArrowExec {
+    /// Create a new Arrow IPC reader execution plan provided base configurations
+    pub fn new(base_config: PhysicalPlanConfig) -> Self {
+        let (projected_schema, projected_statistics) = base_config.project();
+
+        Self {
+            base_config,
+            projected_schema,
+            projected_statistics,
+        }
+    }
+    /// Ref to the base configs
+    pub fn base_config(&self) -> &PhysicalPlanConfig {
+        &self.base_config
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for ArrowExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.projected_schema.clone()
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(self.base_config.file_groups.len())
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        Vec::new()
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        if children.is_empty() {
+            Ok(Arc::new(self.clone()))
+        } else {
+            Err(DataFusionError::Internal(format!(
+                "Children cannot be replaced in {:?}",
+                self
+            )))
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let projection = match self.base_config.file_column_projection_indices() {
+            Some(proj) => proj,
+            None => (0..self.base_config.file_schema.fields().len()).collect(),
+        };
+        let schema_adapter =
+            SchemaAdapter::new(Arc::clone(&self.base_config.file_schema));
+
+        // The record batches are read as they were written, so the reading
+        // stops after the batch that reaches the limit and the file stream
+        // truncates it.
+        let fun = move |file: Arc<dyn ObjectReader>, remaining: &Option<usize>| {
+            let limit = *remaining;
+            match read_arrow_file(file, &schema_adapter, &projection) {
+                Ok(batches) => Box::new(batches.scan(0, move |num_rows, batch| {
+                    if matches!(limit, Some(limit) if *num_rows >= limit) {
+                        return None;
+                    }
+                    if let Ok(batch) = &batch {
+                        *num_rows += batch.num_rows();
+                    }
+                    Some(batch)
+                })) as BatchIter,
+                Err(e) => Box::new(iter::once(Err(e))) as BatchIter,
+            }
+        };
+
+        Ok(Box::pin(FileStream::new(
+            Arc::clone(&self.base_config.object_store),
+            self.base_config.file_groups[partition].clone(),
+            fun,
+            Arc::clone(&self.projected_schema),
+            self.base_config.limit,
+            self.base_config.table_partition_cols.clone(),
+        )))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "ArrowExec: files={}, limit={:?}",
+                    super::FileGroupsDisplay(&self.base_config.file_groups),
+                    self.base_config.limit,
+                )
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        self.projected_statistics.clone()
+    }
+}
+
+/// Read the record batches of the Arrow IPC `file`, adapted to the columns
+/// `projection` of the table schema.
+fn read_arrow_file(
+    file: Arc<dyn ObjectReader>,
+    schema_adapter: &SchemaAdapter,
+    projection: &[usize],
+) -> ArrowResult<BatchIter> {
+    // The IPC file reader seeks to the footer and to each record batch, which
+    // are read as they are needed
+    let reader = FileReader::try_new(ChunkedReader::new(file, CHUNK_SIZE))?;
+
+    // fail early if the file has columns that can't be cast to the table types
+    schema_adapter
+        .map_projections(&reader.schema(), projection)
+        .map_err(|e| e.into_arrow_external_error())?;
+    let schema_adapter = schema_adapter.clone();
+    let projection = projection.to_vec();
+    Ok(Box::new(reader.map(move |batch| {
+        schema_adapter
+            .adapt_batch(batch?, &projection)
+            .map_err(|e| e.into_arrow_external_error())
+    })))
+}
+
+/// The maximum number of bytes of a file read from its object store at once
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// A [`Read`] and [`Seek`] over a file of an object store, which reads the
+/// file in chunks of at most `chunk_size` bytes from the current position.
+/// Seeking to another position drops the current chunk.
+struct ChunkedReader {
+    reader: Arc<dyn ObjectReader>,
+    chunk_size: usize,
+    position: u64,
+    /// The reader of the chunk at `position` and the end of the chunk
+    chunk: Option<(Box<dyn Read + Send + Sync>, u64)>,
+}
+
+impl ChunkedReader {
+    fn new(reader: Arc<dyn ObjectReader>, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk_size,
+            position: 0,
+            chunk: None,
+        }
+    }
+}
+
+impl Read for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.reader.length();
+        if buf.is_empty() || self.position >= length {
+            return Ok(0);
+        }
+        let (chunk, end) = match &mut self.chunk {
+            Some((chunk, end)) if self.position < *end => (chunk, *end),
+            chunk => {
+                let end = length.min(self.position + self.chunk_size as u64);
+                let reader = self
+                    .reader
+                    .sync_chunk_reader(self.position, (end - self.position) as usize)
+                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                let (reader, _) = chunk.insert((reader, end));
+                (reader, end)
+            }
+        };
+        let len = buf.len().min((end - self.position) as usize);
+        let read = chunk.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "The chunk of the file ending at {} ended at {}",
+                    end, self.position
+                ),
+            ));
+        }
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for ChunkedReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => (position, 0),
+            SeekFrom::End(offset) => (self.reader.length(), offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+        let position = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )
+        })?;
+        if position != self.position {
+            self.chunk = None;
+            self.position = position;
+        }
+        Ok(position)
+    }
+}
+
+impl FileWriter for IpcFileWriter<File> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(IpcFileWriter::write(self, batch)?)
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        Ok(self.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasource::object_store::memory::InMemoryObjectStore;
+    use crate::datasource::object_store::{ObjectStore, SizedFile};
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    fn chunked_reader(data: Vec<u8>, chunk_size: usize) -> Result<ChunkedReader> {
+        let store = InMemoryObjectStore::new();
+        let size = data.len() as u64;
+        store.put("file", data);
+        let reader = store.file_reader(SizedFile {
+            path: "file".to_owned(),
+            size,
+        })?;
+        Ok(ChunkedReader::new(reader, chunk_size))
+    }
+
+    #[test]
+    fn read_and_seek_chunks() -> Result<()> {
+        let data = (0..20).collect::<Vec<u8>>();
+        let mut reader = chunked_reader(data.clone(), 3)?;
+
+        // a read stops at the end of a chunk
+        let mut buf = [0; 5];
+        assert_eq!(3, reader.read(&mut buf)?);
+        assert_eq!([0, 1, 2], buf[..3]);
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf)?;
+        assert_eq!(data[3..].to_vec(), buf);
+
+        let mut buf = [0; 4];
+        assert_eq!(16, reader.seek(SeekFrom::End(-4))?);
+        reader.read_exact(&mut buf)?;
+        assert_eq!([16, 17, 18, 19], buf);
+        assert_eq!(5, reader.seek(SeekFrom::Start(5))?);
+        assert_eq!(7, reader.seek(SeekFrom::Current(2))?);
+        reader.read_exact(&mut buf)?;
+        assert_eq!([7, 8, 9, 10], buf);
+        assert_eq!(0, reader.seek(SeekFrom::Current(-11))?);
+        assert!(reader.seek(SeekFrom::Current(-1)).is_err());
+        assert_eq!(25, reader.seek(SeekFrom::Start(25))?);
+        assert_eq!(0, reader.read(&mut buf)?);
+        Ok(())
+    }
+
+    #[test]
+    fn read_arrow_file_in_chunks() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let mut data = vec![];
+        {
+            let mut writer = IpcFileWriter::try_new(&mut data, &schema)?;
+            for i in 0..3 {
+                let batch = RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int32Array::from(vec![i, i + 1]))],
+                )?;
+                writer.write(&batch)?;
+            }
+            writer.finish()?;
+        }
+
+        let reader = FileReader::try_new(chunked_reader(data, 7)?)?;
+        let batches = reader.collect::<ArrowResult<Vec<_>>>()?;
+        assert_eq!(3, batches.len());
+        for (i, batch) in batches.iter().enumerate() {
+            let values = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            assert_eq!(vec![i as i32, i as i32 + 1], values.values().to_vec());
+        }
+        Ok(())
+    }
+}
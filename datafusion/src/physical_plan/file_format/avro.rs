@@ -18,6 +18,8 @@
 //! Execution plan for reading line-delimited Avro files
 #[cfg(feature = "avro")]
 use crate::avro_to_arrow;
+#[cfg(feature = "avro")]
+use crate::datasource::object_store::ObjectReader;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
@@ -106,13 +108,15 @@ impl ExecutionPlan for AvroExec {
         let file_schema = Arc::clone(&self.base_config.file_schema);
 
         // The avro reader cannot limit the number of records, so `remaining` is ignored.
-        let fun = move |file, _remaining: &Option<usize>| {
-            let reader_res = avro_to_arrow::Reader::try_new(
-                file,
-                Arc::clone(&file_schema),
-                batch_size,
-                proj.clone(),
-            );
+        let fun = move |file: Arc<dyn ObjectReader>, _remaining: &Option<usize>| {
+            let reader_res = file.sync_reader().and_then(|file| {
+                avro_to_arrow::Reader::try_new(
+                    file,
+                    Arc::clone(&file_schema),
+                    batch_size,
+                    proj.clone(),
+                )
+            });
             match reader_res {
                 Ok(r) => Box::new(r) as BatchIter,
                 Err(e) => Box::new(
@@ -182,6 +186,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec![],
+            metadata_cache: None,
         });
         assert_eq!(avro_exec.output_partitioning().partition_count(), 1);
 
@@ -243,6 +248,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec!["date".to_owned()],
+            metadata_cache: None,
         });
         assert_eq!(avro_exec.output_partitioning().partition_count(), 1);
 
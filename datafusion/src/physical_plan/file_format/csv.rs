@@ -17,6 +17,8 @@
 
 //! Execution plan for reading CSV files
 
+use crate::datasource::file_format::compression::FileCompressionType;
+use crate::datasource::object_store::ObjectReader;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
@@ -24,12 +26,17 @@ use crate::physical_plan::{
 
 use arrow::csv;
 use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
 use std::any::Any;
+use std::fs::File;
+use std::iter;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 
 use super::file_stream::{BatchIter, FileStream};
+use super::file_writer::FileWriter;
 use super::PhysicalPlanConfig;
 
 /// Execution plan for scanning a CSV file
@@ -40,6 +47,7 @@ pub struct CsvExec {
     projected_schema: SchemaRef,
     has_header: bool,
     delimiter: u8,
+    file_compression_type: FileCompressionType,
 }
 
 impl CsvExec {
@@ -53,9 +61,19 @@ impl CsvExec {
             projected_statistics,
             has_header,
             delimiter,
+            file_compression_type: FileCompressionType::Uncompressed,
         }
     }
 
+    /// Set the compression of the files, which are decompressed while read
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = file_compression_type;
+        self
+    }
+
     /// Ref to the base configs
     pub fn base_config(&self) -> &PhysicalPlanConfig {
         &self.base_config
@@ -68,6 +86,10 @@ impl CsvExec {
     pub fn delimiter(&self) -> u8 {
         self.delimiter
     }
+    /// The compression of the files
+    pub fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
 }
 
 #[async_trait]
@@ -112,9 +134,20 @@ impl ExecutionPlan for CsvExec {
         let file_projection = self.base_config.file_column_projection_indices();
         let has_header = self.has_header;
         let delimiter = self.delimiter;
+        let file_compression_type = self.file_compression_type;
         let start_line = if has_header { 1 } else { 0 };
 
-        let fun = move |file, remaining: &Option<usize>| {
+        let fun = move |file: Arc<dyn ObjectReader>, remaining: &Option<usize>| {
+            let file = match file
+                .sync_reader()
+                .and_then(|file| file_compression_type.convert_read(file))
+            {
+                Ok(file) => file,
+                Err(e) => {
+                    let e = ArrowError::ExternalError(Box::new(e));
+                    return Box::new(iter::once(Err(e))) as BatchIter;
+                }
+            };
             let bounds = remaining.map(|x| (0, x + start_line));
             Box::new(csv::Reader::new(
                 file,
@@ -161,6 +194,17 @@ impl ExecutionPlan for CsvExec {
     }
 }
 
+impl FileWriter for csv::Writer<File> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(csv::Writer::write(self, batch)?)
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        // the underlying writer is flushed when dropped
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,6 +231,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -233,6 +278,7 @@ mod tests {
                 batch_size: 1024,
                 limit: Some(5),
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -286,6 +332,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec!["date".to_owned()],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -22,7 +22,10 @@
 //! compliant with the `SendableRecordBatchStream` trait.
 
 use crate::{
-    datasource::{object_store::ObjectStore, PartitionedFile},
+    datasource::{
+        object_store::{ObjectReader, ObjectStore},
+        PartitionedFile,
+    },
     physical_plan::RecordBatchStream,
     scalar::ScalarValue,
 };
@@ -33,7 +36,6 @@ use arrow::{
 };
 use futures::Stream;
 use std::{
-    io::Read,
     iter,
     pin::Pin,
     sync::Arc,
@@ -45,18 +47,15 @@ use super::PartitionColumnProjector;
 pub type FileIter = Box<dyn Iterator<Item = PartitionedFile> + Send + Sync>;
 pub type BatchIter = Box<dyn Iterator<Item = ArrowResult<RecordBatch>> + Send + Sync>;
 
-/// A closure that creates a file format reader (iterator over `RecordBatch`) from a `Read` object
-/// and an optional number of required records.
+/// A closure that creates a file format reader (iterator over `RecordBatch`) from the
+/// `ObjectReader` of a file and an optional number of required records.
 pub trait FormatReaderOpener:
-    FnMut(Box<dyn Read + Send + Sync>, &Option<usize>) -> BatchIter + Send + Unpin + 'static
+    FnMut(Arc<dyn ObjectReader>, &Option<usize>) -> BatchIter + Send + Unpin + 'static
 {
 }
 
 impl<T> FormatReaderOpener for T where
-    T: FnMut(Box<dyn Read + Send + Sync>, &Option<usize>) -> BatchIter
-        + Send
-        + Unpin
-        + 'static
+    T: FnMut(Arc<dyn ObjectReader>, &Option<usize>) -> BatchIter + Send + Unpin + 'static
 {
 }
 
@@ -123,10 +122,9 @@ impl<F: FormatReaderOpener> FileStream<F> {
                     self.partition_values = f.partition_values;
                     self.object_store
                         .file_reader(f.file_meta.sized_file)
-                        .and_then(|r| r.sync_reader())
                         .map_err(|e| ArrowError::ExternalError(Box::new(e)))
-                        .and_then(|f| {
-                            self.batch_iter = (self.file_reader)(f, &self.remain);
+                        .and_then(|r| {
+                            self.batch_iter = (self.file_reader)(r, &self.remain);
                             self.next_batch().transpose()
                         })
                         .transpose()
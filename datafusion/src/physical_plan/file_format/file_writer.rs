@@ -0,0 +1,368 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Helpers writing the output partitions of an execution plan to files,
+//! either one file per partition or in Hive-style `col=value` directories.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::UInt32Array;
+use arrow::compute::take;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::array_value_to_string;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::task::{self, JoinHandle};
+
+use crate::datasource::file_format::FileFormat;
+use crate::error::{DataFusionError, Result};
+use crate::physical_plan::insert::DataSink;
+use crate::physical_plan::ExecutionPlan;
+
+/// The directory name used for the rows whose partition value is null
+pub const HIVE_DEFAULT_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Writes record batches to a single file in a given format
+pub trait FileWriter: Send {
+    /// Append `batch` to the file
+    fn write(&mut self, batch: &RecordBatch) -> Result<()>;
+
+    /// Write anything still buffered, such as the footer of the file
+    fn close(self: Box<Self>) -> Result<()>;
+}
+
+/// Executes every output partition of `plan` on its own task and writes it to
+/// the file `{file_prefix}-{partition}{file_extension}` in the existing
+/// directory `dir`, using the writers of `format`. Returns the number of rows
+/// written.
+pub(crate) async fn write_partitions(
+    plan: Arc<dyn ExecutionPlan>,
+    dir: &Path,
+    file_prefix: &str,
+    file_extension: &str,
+    format: &dyn FileFormat,
+) -> Result<usize> {
+    let mut tasks = vec![];
+    for i in 0..plan.output_partitioning().partition_count() {
+        let filename = format!("{}-{}{}", file_prefix, i, file_extension);
+        let file = File::create(dir.join(&filename))?;
+        let mut writer = format.create_writer(file, plan.schema())?;
+        let mut stream = plan.execute(i).await?;
+        let handle: JoinHandle<Result<usize>> = task::spawn(async move {
+            let mut num_rows = 0;
+            while let Some(batch) = stream.next().await {
+                let batch = batch?;
+                num_rows += batch.num_rows();
+                writer.write(&batch)?;
+            }
+            writer.close()?;
+            Ok(num_rows)
+        });
+        tasks.push(handle);
+    }
+
+    let mut num_rows = 0;
+    for result in futures::future::join_all(tasks).await {
+        num_rows += result.map_err(|e| {
+            DataFusionError::Execution(format!("Could not write file: {}", e))
+        })??;
+    }
+    Ok(num_rows)
+}
+
+/// A [`DataSink`] writing each output partition of its input to a file
+/// `part-{partition}{extension}` in a new directory, in a given format
+#[derive(Debug)]
+pub struct DirectorySink {
+    /// The directory created for the files
+    path: String,
+    /// The format of the files
+    format: Arc<dyn FileFormat>,
+}
+
+impl DirectorySink {
+    /// Create a sink writing files in `format` to the new directory `path`
+    pub fn new(path: impl Into<String>, format: Arc<dyn FileFormat>) -> Self {
+        Self {
+            path: path.into(),
+            format,
+        }
+    }
+}
+
+#[async_trait]
+impl DataSink for DirectorySink {
+    async fn write_all(&self, input: Arc<dyn ExecutionPlan>) -> Result<usize> {
+        let dir = Path::new(&self.path);
+        fs::create_dir(dir).map_err(|e| {
+            DataFusionError::Execution(format!(
+                "Could not create directory {}: {:?}",
+                self.path, e
+            ))
+        })?;
+        write_partitions(
+            input,
+            dir,
+            "part",
+            self.format.file_extension(),
+            self.format.as_ref(),
+        )
+        .await
+    }
+}
+
+/// Executes every output partition of `plan` on its own task and writes its
+/// rows to files in the Hive-style directories `col1=value1/col2=value2/` of
+/// `dir`, one level per column of `partition_cols`. The partition columns are
+/// not written to the files, they are read back from the directory names by a
+/// [`ListingTable`](crate::datasource::listing::ListingTable) created with
+/// the same `table_partition_cols`.
+///
+/// The files are named `part-{partition}-{file}{extension}`, where the
+/// extension is the one of `format`. When `max_rows_per_file` is set, a new
+/// file is started each time the current file of a directory reaches that
+/// many rows. Returns the number of rows written.
+pub(crate) async fn write_hive_partitions(
+    plan: Arc<dyn ExecutionPlan>,
+    dir: &Path,
+    partition_cols: &[String],
+    format: Arc<dyn FileFormat>,
+    max_rows_per_file: Option<usize>,
+) -> Result<usize> {
+    if max_rows_per_file == Some(0) {
+        return Err(DataFusionError::Plan(
+            "The maximum number of rows per file must be greater than 0".to_string(),
+        ));
+    }
+
+    let schema = plan.schema();
+    let partition_indices = partition_cols
+        .iter()
+        .map(|name| {
+            schema.index_of(name).map_err(|_| {
+                DataFusionError::Plan(format!(
+                    "Partition column {} not found in {:?}",
+                    name,
+                    schema.fields().iter().map(|f| f.name()).collect::<Vec<_>>()
+                ))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let data_indices = (0..schema.fields().len())
+        .filter(|i| !partition_indices.contains(i))
+        .collect::<Vec<_>>();
+    let file_schema = Arc::new(Schema::new(
+        data_indices
+            .iter()
+            .map(|i| schema.field(*i).clone())
+            .collect(),
+    ));
+
+    let splitter = Arc::new(HivePartitionSplitter {
+        partition_cols: partition_cols
+            .iter()
+            .cloned()
+            .zip(partition_indices)
+            .collect(),
+        data_indices,
+        file_schema,
+    });
+
+    let mut tasks = vec![];
+    for i in 0..plan.output_partitioning().partition_count() {
+        let mut stream = plan.execute(i).await?;
+        let splitter = splitter.clone();
+        let format = format.clone();
+        let dir = dir.to_path_buf();
+        let handle: JoinHandle<Result<usize>> = task::spawn(async move {
+            let mut writers: HashMap<String, DirectoryWriter> = HashMap::new();
+            let mut num_rows = 0;
+            while let Some(batch) = stream.next().await {
+                let batch = batch?;
+                num_rows += batch.num_rows();
+                for (subdir, batch) in splitter.split(&batch)? {
+                    let writer = writers.entry(subdir).or_insert_with_key(|subdir| {
+                        DirectoryWriter::new(dir.join(subdir), i, max_rows_per_file)
+                    });
+                    writer.write(&batch, &*format, &splitter.file_schema)?;
+                }
+            }
+            for (_, writer) in writers {
+                writer.close()?;
+            }
+            Ok(num_rows)
+        });
+        tasks.push(handle);
+    }
+
+    let mut num_rows = 0;
+    for result in futures::future::join_all(tasks).await {
+        num_rows += result.map_err(|e| {
+            DataFusionError::Execution(format!("Could not write file: {}", e))
+        })??;
+    }
+    Ok(num_rows)
+}
+
+/// Splits record batches by the values of their partition columns
+struct HivePartitionSplitter {
+    /// The name and the index in the input of the partition columns
+    partition_cols: Vec<(String, usize)>,
+    /// The indices in the input of the columns written to the files
+    data_indices: Vec<usize>,
+    /// The schema of the files
+    file_schema: SchemaRef,
+}
+
+impl HivePartitionSplitter {
+    /// Returns the rows of `batch`, without the partition columns, grouped by
+    /// the relative directory they are written to, in order of first
+    /// appearance
+    fn split(&self, batch: &RecordBatch) -> Result<Vec<(String, RecordBatch)>> {
+        let mut groups: Vec<(String, Vec<u32>)> = vec![];
+        let mut group_indices: HashMap<String, usize> = HashMap::new();
+        for row in 0..batch.num_rows() {
+            let subdir = self.subdir(batch, row)?;
+            let index = *group_indices.entry(subdir.clone()).or_insert_with(|| {
+                groups.push((subdir, vec![]));
+                groups.len() - 1
+            });
+            groups[index].1.push(row as u32);
+        }
+
+        groups
+            .into_iter()
+            .map(|(subdir, rows)| {
+                let rows = UInt32Array::from(rows);
+                let columns = self
+                    .data_indices
+                    .iter()
+                    .map(|i| take(batch.column(*i).as_ref(), &rows, None))
+                    .collect::<arrow::error::Result<Vec<_>>>()?;
+                let batch = RecordBatch::try_new(self.file_schema.clone(), columns)?;
+                Ok((subdir, batch))
+            })
+            .collect()
+    }
+
+    /// The relative directory the row `row` of `batch` is written to
+    fn subdir(&self, batch: &RecordBatch, row: usize) -> Result<String> {
+        let parts = self
+            .partition_cols
+            .iter()
+            .map(|(name, index)| {
+                let array = batch.column(*index);
+                let value = if array.is_null(row) {
+                    HIVE_DEFAULT_PARTITION.to_string()
+                } else {
+                    array_value_to_string(array, row)?
+                };
+                if value.is_empty() || value.contains('/') {
+                    return Err(DataFusionError::Execution(format!(
+                        "Value {:?} of partition column {} can not be used as a directory name",
+                        value, name
+                    )));
+                }
+                Ok(format!("{}={}", name, value))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(parts.join("/"))
+    }
+}
+
+/// Writes the rows of one directory, starting a new file whenever the current
+/// one reaches the maximum number of rows
+struct DirectoryWriter {
+    dir: PathBuf,
+    partition: usize,
+    max_rows_per_file: Option<usize>,
+    /// The number of files created so far
+    num_files: usize,
+    /// The file being written and its number of rows
+    current: Option<(Box<dyn FileWriter>, usize)>,
+}
+
+impl DirectoryWriter {
+    fn new(dir: PathBuf, partition: usize, max_rows_per_file: Option<usize>) -> Self {
+        Self {
+            dir,
+            partition,
+            max_rows_per_file,
+            num_files: 0,
+            current: None,
+        }
+    }
+
+    fn write(
+        &mut self,
+        batch: &RecordBatch,
+        format: &dyn FileFormat,
+        schema: &SchemaRef,
+    ) -> Result<()> {
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let (mut writer, file_rows) = match self.current.take() {
+                Some(current) => current,
+                None => (self.create_file(format, schema)?, 0),
+            };
+            let len = match self.max_rows_per_file {
+                Some(max_rows) => (max_rows - file_rows).min(batch.num_rows() - offset),
+                None => batch.num_rows() - offset,
+            };
+            writer.write(&batch.slice(offset, len))?;
+            offset += len;
+
+            let file_rows = file_rows + len;
+            if self.max_rows_per_file == Some(file_rows) {
+                writer.close()?;
+            } else {
+                self.current = Some((writer, file_rows));
+            }
+        }
+        Ok(())
+    }
+
+    fn create_file(
+        &mut self,
+        format: &dyn FileFormat,
+        schema: &SchemaRef,
+    ) -> Result<Box<dyn FileWriter>> {
+        if self.num_files == 0 {
+            fs::create_dir_all(&self.dir)?;
+        }
+        let filename = format!(
+            "part-{}-{}{}",
+            self.partition,
+            self.num_files,
+            format.file_extension()
+        );
+        self.num_files += 1;
+        let file = File::create(self.dir.join(filename))?;
+        format.create_writer(file, schema.clone())
+    }
+
+    fn close(self) -> Result<()> {
+        match self.current {
+            Some((writer, _)) => writer.close(),
+            None => Ok(()),
+        }
+    }
+}
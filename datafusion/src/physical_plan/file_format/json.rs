@@ -18,15 +18,20 @@
 //! Execution plan for reading line-delimited JSON files
 use async_trait::async_trait;
 
+use crate::datasource::file_format::compression::FileCompressionType;
+use crate::datasource::object_store::ObjectReader;
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
     DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
 };
-use arrow::{datatypes::SchemaRef, json};
+use arrow::{datatypes::SchemaRef, error::ArrowError, json, record_batch::RecordBatch};
 use std::any::Any;
+use std::fs::File;
+use std::iter;
 use std::sync::Arc;
 
 use super::file_stream::{BatchIter, FileStream};
+use super::file_writer::FileWriter;
 use super::PhysicalPlanConfig;
 
 /// Execution plan for scanning NdJson data source
@@ -35,6 +40,7 @@ pub struct NdJsonExec {
     base_config: PhysicalPlanConfig,
     projected_statistics: Statistics,
     projected_schema: SchemaRef,
+    file_compression_type: FileCompressionType,
 }
 
 impl NdJsonExec {
@@ -46,8 +52,23 @@ impl NdJsonExec {
             base_config,
             projected_schema,
             projected_statistics,
+            file_compression_type: FileCompressionType::Uncompressed,
         }
     }
+
+    /// Set the compression of the files, which are decompressed while read
+    pub fn with_file_compression_type(
+        mut self,
+        file_compression_type: FileCompressionType,
+    ) -> Self {
+        self.file_compression_type = file_compression_type;
+        self
+    }
+
+    /// The compression of the files
+    pub fn file_compression_type(&self) -> FileCompressionType {
+        self.file_compression_type
+    }
 }
 
 #[async_trait]
@@ -87,9 +108,20 @@ impl ExecutionPlan for NdJsonExec {
 
         let batch_size = self.base_config.batch_size;
         let file_schema = Arc::clone(&self.base_config.file_schema);
+        let file_compression_type = self.file_compression_type;
 
         // The json reader cannot limit the number of records, so `remaining` is ignored.
-        let fun = move |file, _remaining: &Option<usize>| {
+        let fun = move |file: Arc<dyn ObjectReader>, _remaining: &Option<usize>| {
+            let file = match file
+                .sync_reader()
+                .and_then(|file| file_compression_type.convert_read(file))
+            {
+                Ok(file) => file,
+                Err(e) => {
+                    let e = ArrowError::ExternalError(Box::new(e));
+                    return Box::new(iter::once(Err(e))) as BatchIter;
+                }
+            };
             Box::new(json::Reader::new(
                 file,
                 Arc::clone(&file_schema),
@@ -131,6 +163,16 @@ impl ExecutionPlan for NdJsonExec {
     }
 }
 
+impl FileWriter for json::LineDelimitedWriter<File> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(self.write_batches(&[batch.clone()])?)
+    }
+
+    fn close(mut self: Box<Self>) -> Result<()> {
+        Ok(self.finish()?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use futures::StreamExt;
@@ -165,6 +207,7 @@ mod tests {
             batch_size: 1024,
             limit: Some(3),
             table_partition_cols: vec![],
+            metadata_cache: None,
         });
 
         // TODO: this is not where schema inference should be tested
@@ -219,6 +262,7 @@ mod tests {
             batch_size: 1024,
             limit: None,
             table_partition_cols: vec![],
+            metadata_cache: None,
         });
         let inferred_schema = exec.schema();
         assert_eq!(inferred_schema.fields().len(), 2);
@@ -17,26 +17,34 @@
 
 //! Execution plans that read file formats
 
+mod arrow_file;
 mod avro;
 mod csv;
 mod file_stream;
+mod file_writer;
 mod json;
 mod parquet;
 
 pub use self::parquet::ParquetExec;
 use arrow::{
-    array::{ArrayData, ArrayRef, DictionaryArray, UInt8BufferBuilder},
-    buffer::Buffer,
+    array::{new_null_array, ArrayData, ArrayRef, DictionaryArray, UInt8BufferBuilder},
+    buffer::{Buffer, MutableBuffer},
+    compute::{can_cast_types, cast},
     datatypes::{DataType, Field, Schema, SchemaRef, UInt8Type},
     error::{ArrowError, Result as ArrowResult},
     record_batch::RecordBatch,
+    util::bit_util,
 };
+pub use arrow_file::ArrowExec;
 pub use avro::AvroExec;
 pub use csv::CsvExec;
+pub(crate) use file_writer::{write_hive_partitions, write_partitions};
+pub use file_writer::{DirectorySink, FileWriter, HIVE_DEFAULT_PARTITION};
 pub use json::NdJsonExec;
 
 use crate::{
-    datasource::{object_store::ObjectStore, PartitionedFile},
+    datasource::{listing::MetadataCache, object_store::ObjectStore, PartitionedFile},
+    error::{DataFusionError, Result},
     scalar::ScalarValue,
 };
 use lazy_static::lazy_static;
@@ -76,6 +84,9 @@ pub struct PhysicalPlanConfig {
     pub limit: Option<usize>,
     /// The partitioning column names
     pub table_partition_cols: Vec<String>,
+    /// The cache of the metadata of the files, e.g. of the footers of the
+    /// Parquet files, if any
+    pub metadata_cache: Option<Arc<dyn MetadataCache>>,
 }
 
 impl PhysicalPlanConfig {
@@ -107,7 +118,7 @@ impl PhysicalPlanConfig {
                 table_fields.push(Field::new(
                     &self.table_partition_cols[partition_idx],
                     DEFAULT_PARTITION_COLUMN_DATATYPE.clone(),
-                    false,
+                    true,
                 ));
                 // TODO provide accurate stat for partition column (#1186)
                 table_cols_stats.push(ColumnStatistics::default())
@@ -147,6 +158,91 @@ impl PhysicalPlanConfig {
     }
 }
 
+/// Adapts the record batches read from a file to the schema of the table,
+/// which may be a merge of the schemas of its files.
+///
+/// This enables the schema of a dataset to evolve: the columns missing from
+/// a file are filled with nulls, the columns of a file with another but
+/// compatible type are cast to the table type, and the columns are matched by
+/// name whatever their order in the file.
+#[derive(Debug, Clone)]
+pub struct SchemaAdapter {
+    /// The schema of the table, without the partition columns
+    table_schema: SchemaRef,
+}
+
+impl SchemaAdapter {
+    /// Create an adapter to the table schema `table_schema`
+    pub fn new(table_schema: SchemaRef) -> Self {
+        Self { table_schema }
+    }
+
+    /// The schema of the table
+    pub fn table_schema(&self) -> &SchemaRef {
+        &self.table_schema
+    }
+
+    /// Map the columns `projections` of the table schema onto `file_schema`.
+    /// Returns the sorted indices in `file_schema` of the projected columns
+    /// found in the file, which fails if one of them can not be cast to its
+    /// type in the table.
+    pub fn map_projections(
+        &self,
+        file_schema: &Schema,
+        projections: &[usize],
+    ) -> Result<Vec<usize>> {
+        let mut mapped = vec![];
+        for idx in projections {
+            let field = self.table_schema.field(*idx);
+            if let Ok(mapped_idx) = file_schema.index_of(field.name()) {
+                let file_type = file_schema.field(mapped_idx).data_type();
+                if !can_cast_types(file_type, field.data_type()) {
+                    return Err(DataFusionError::Execution(format!(
+                        "Cannot cast the column {} of type {:?} in the file to the \
+                         type {:?} of the table",
+                        field.name(),
+                        file_type,
+                        field.data_type()
+                    )));
+                }
+                mapped.push(mapped_idx);
+            }
+        }
+        mapped.sort_unstable();
+        mapped.dedup();
+        Ok(mapped)
+    }
+
+    /// Adapt `batch`, read from a file with the columns mapped by
+    /// [`SchemaAdapter::map_projections`], to the columns `projections` of
+    /// the table schema.
+    pub fn adapt_batch(
+        &self,
+        batch: RecordBatch,
+        projections: &[usize],
+    ) -> Result<RecordBatch> {
+        let batch_schema = batch.schema();
+        let mut fields = Vec::with_capacity(projections.len());
+        let mut columns = Vec::with_capacity(projections.len());
+        for idx in projections {
+            let field = self.table_schema.field(*idx);
+            let column = match batch_schema.index_of(field.name()) {
+                Ok(i) if batch_schema.field(i).data_type() == field.data_type() => {
+                    Arc::clone(batch.column(i))
+                }
+                Ok(i) => cast(batch.column(i), field.data_type())?,
+                Err(_) => new_null_array(field.data_type(), batch.num_rows()),
+            };
+            fields.push(field.clone());
+            columns.push(column);
+        }
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            columns,
+        )?)
+    }
+}
+
 /// A wrapper to customize partitioned file display
 #[derive(Debug)]
 struct FileGroupsDisplay<'a>(&'a [Vec<PartitionedFile>]);
@@ -271,6 +367,11 @@ fn create_dict_array(
     let mut builder = ArrayData::builder(data_type)
         .len(len)
         .add_buffer(sliced_key_buffer);
+    if val.is_null() {
+        // the keys of the null partition values are null
+        let null_buffer = MutableBuffer::from_len_zeroed(bit_util::ceil(len, 8));
+        builder = builder.null_bit_buffer(null_buffer.into());
+    }
     builder = builder.add_child_data(dict_vals.data().clone());
     Arc::new(DictionaryArray::<UInt8Type>::from(builder.build().unwrap()))
 }
@@ -469,6 +570,66 @@ mod tests {
         crate::assert_batches_eq!(expected, &[projected_batch]);
     }
 
+    #[test]
+    fn schema_adapter_adapt_projections() -> Result<()> {
+        let table_schema = Arc::new(Schema::new(vec![
+            Field::new("c1", DataType::Utf8, true),
+            Field::new("c2", DataType::Int64, true),
+            Field::new("c3", DataType::Int8, true),
+        ]));
+        // the columns are reordered, c2 is widened and c3 is missing
+        let file_schema = Schema::new(vec![
+            Field::new("c2", DataType::Int32, true),
+            Field::new("c1", DataType::Utf8, true),
+        ]);
+        let adapter = SchemaAdapter::new(table_schema);
+
+        let mapped = adapter.map_projections(&file_schema, &[0, 1, 2])?;
+        assert_eq!(vec![0, 1], mapped);
+        let mapped = adapter.map_projections(&file_schema, &[2, 0])?;
+        assert_eq!(vec![1], mapped);
+
+        let file_batch = RecordBatch::try_new(
+            Arc::new(file_schema),
+            vec![
+                Arc::new(arrow::array::Int32Array::from(vec![1, 2])),
+                Arc::new(arrow::array::StringArray::from(vec!["a", "b"])),
+            ],
+        )?;
+        let adapted = adapter.adapt_batch(file_batch, &[2, 0, 1])?;
+        let expected = vec![
+            "+----+----+----+",
+            "| c3 | c1 | c2 |",
+            "+----+----+----+",
+            "|    | a  | 1  |",
+            "|    | b  | 2  |",
+            "+----+----+----+",
+        ];
+        crate::assert_batches_eq!(expected, &[adapted.clone()]);
+        assert_eq!(&DataType::Int64, adapted.schema().field(2).data_type());
+
+        Ok(())
+    }
+
+    #[test]
+    fn schema_adapter_incompatible_types() {
+        let table_schema =
+            Arc::new(Schema::new(vec![Field::new("c1", DataType::Date32, true)]));
+        let file_schema = Schema::new(vec![Field::new(
+            "c1",
+            DataType::List(Box::new(Field::new("item", DataType::Int32, true))),
+            true,
+        )]);
+        let err = SchemaAdapter::new(table_schema)
+            .map_projections(&file_schema, &[0])
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Cannot cast the column c1"),
+            "{}",
+            err
+        );
+    }
+
     // sets default for configs that play no role in projections
     fn config_for_projection(
         file_schema: SchemaRef,
@@ -485,6 +646,7 @@ mod tests {
             projection,
             statistics,
             table_partition_cols,
+            metadata_cache: None,
         }
     }
 }
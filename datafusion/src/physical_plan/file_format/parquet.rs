@@ -18,11 +18,15 @@
 //! Execution plan for reading Parquet files
 
 use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
 use std::sync::Arc;
 use std::{any::Any, convert::TryInto};
 
 use crate::datasource::file_format::parquet::ChunkObjectReader;
-use crate::datasource::object_store::ObjectStore;
+use crate::datasource::listing::{FileCacheKey, MetadataCache};
+use crate::datasource::object_store::{FileMeta, ObjectReader, ObjectStore};
 use crate::datasource::PartitionedFile;
 use crate::{
     error::{DataFusionError, Result},
@@ -45,14 +49,24 @@ use arrow::{
     record_batch::RecordBatch,
 };
 use log::debug;
+use parquet::column::page::PageReader;
+use parquet::errors::{ParquetError, Result as ParquetResult};
 use parquet::file::{
-    metadata::RowGroupMetaData,
-    reader::{FileReader, SerializedFileReader},
+    footer,
+    metadata::{ParquetMetaData, RowGroupMetaData},
+    reader::{ChunkReader, FileReader, Length, RowGroupReader},
+    serialized_reader::SerializedPageReader,
     statistics::Statistics as ParquetStatistics,
 };
+use parquet::record::reader::RowIter;
+use parquet::schema::types::Type as SchemaType;
+use parquet_format::FileMetaData as TFileMetaData;
+use thrift::protocol::TCompactInputProtocol;
 
 use fmt::Debug;
-use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::arrow::{
+    parquet_to_arrow_schema, ArrowReader, ArrowWriter, ParquetFileArrowReader,
+};
 
 use tokio::{
     sync::mpsc::{channel, Receiver, Sender},
@@ -61,7 +75,15 @@ use tokio::{
 
 use async_trait::async_trait;
 
-use super::PartitionColumnProjector;
+use self::bloom_filter::BloomFilterPredicate;
+use self::page_index::prune_pages;
+use self::row_filter::{read_filtered_row_groups, read_unskipped_rows, RowFilter};
+use super::file_writer::FileWriter;
+use super::{PartitionColumnProjector, SchemaAdapter};
+
+mod bloom_filter;
+mod page_index;
+mod row_filter;
 
 /// Execution plan for scanning one or more Parquet partitions
 #[derive(Debug, Clone)]
@@ -71,8 +93,14 @@ pub struct ParquetExec {
     projected_schema: SchemaRef,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
-    /// Optional predicate for pruning row groups
+    /// Optional predicate for pruning row groups and pages
     pruning_predicate: Option<PruningPredicate>,
+    /// Optional predicate for pruning row groups with bloom filters
+    bloom_filter_predicate: Option<BloomFilterPredicate>,
+    /// Optional filter evaluated on the rows of the row groups read
+    row_filter: Option<RowFilter>,
+    /// Whether `row_filter` is evaluated before decoding the other columns
+    row_filter_enabled: bool,
 }
 
 /// Stores metrics about the parquet execution for a particular parquet file
@@ -80,8 +108,27 @@ pub struct ParquetExec {
 struct ParquetFileMetrics {
     /// Number of times the predicate could not be evaluated
     pub predicate_evaluation_errors: metrics::Count,
-    /// Number of row groups pruned using
+    /// Number of row groups pruned using their statistics
     pub row_groups_pruned: metrics::Count,
+    /// Number of row groups pruned using the bloom filters of their columns
+    pub row_groups_pruned_bloom_filter: metrics::Count,
+    /// Number of row groups pruned because the page index pruned all their
+    /// rows
+    pub row_groups_pruned_page_index: metrics::Count,
+    /// Number of pages pruned using the page index
+    pub pages_pruned: metrics::Count,
+    /// Number of rows of the pruned pages skipped in the row groups read
+    pub page_index_rows_skipped: metrics::Count,
+    /// Number of data pages of the columns read that were not decoded as the
+    /// page index pruned all their rows
+    pub pages_skipped_page_index: metrics::Count,
+    /// Number of rows filtered out by the pushed down row filter
+    pub pushdown_rows_filtered: metrics::Count,
+    /// Number of row groups for which the row filter matched no row
+    pub row_groups_pruned_row_filter: metrics::Count,
+    /// Number of pages of the columns not referenced by the row filter that
+    /// were not decoded as the filter matched none of their rows
+    pub pages_pruned_row_filter: metrics::Count,
 }
 
 impl ParquetExec {
@@ -95,6 +142,11 @@ impl ParquetExec {
         let predicate_creation_errors =
             MetricBuilder::new(&metrics).global_counter("num_predicate_creation_errors");
 
+        let bloom_filter_predicate =
+            predicate.as_ref().and_then(BloomFilterPredicate::try_new);
+        let row_filter = predicate
+            .as_ref()
+            .and_then(|expr| RowFilter::try_new(expr, &base_config.file_schema));
         let pruning_predicate = predicate.and_then(|predicate_expr| {
             match PruningPredicate::try_new(
                 &predicate_expr,
@@ -120,9 +172,19 @@ impl ParquetExec {
             projected_statistics,
             metrics,
             pruning_predicate,
+            bloom_filter_predicate,
+            row_filter,
+            row_filter_enabled: true,
         }
     }
 
+    /// Enable or disable the evaluation of the predicate on the rows of the
+    /// row groups read, before decoding the columns it does not reference
+    pub fn with_row_filter(mut self, enable: bool) -> Self {
+        self.row_filter_enabled = enable;
+        self
+    }
+
     /// Ref to the base configs
     pub fn base_config(&self) -> &PhysicalPlanConfig {
         &self.base_config
@@ -144,9 +206,49 @@ impl ParquetFileMetrics {
             .with_new_label("filename", filename.to_string())
             .counter("row_groups_pruned", partition);
 
+        let row_groups_pruned_bloom_filter = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("row_groups_pruned_bloom_filter", partition);
+
+        let row_groups_pruned_page_index = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("row_groups_pruned_page_index", partition);
+
+        let pages_pruned = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("pages_pruned", partition);
+
+        let page_index_rows_skipped = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("page_index_rows_skipped", partition);
+
+        let pages_skipped_page_index = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("pages_skipped_page_index", partition);
+
+        let pushdown_rows_filtered = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("pushdown_rows_filtered", partition);
+
+        let row_groups_pruned_row_filter = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("row_groups_pruned_row_filter", partition);
+
+        let pages_pruned_row_filter = MetricBuilder::new(metrics)
+            .with_new_label("filename", filename.to_string())
+            .counter("pages_pruned_row_filter", partition);
+
         Self {
             predicate_evaluation_errors,
             row_groups_pruned,
+            row_groups_pruned_bloom_filter,
+            row_groups_pruned_page_index,
+            pages_pruned,
+            page_index_rows_skipped,
+            pages_skipped_page_index,
+            pushdown_rows_filtered,
+            row_groups_pruned_row_filter,
+            pages_pruned_row_filter,
         }
     }
 }
@@ -201,9 +303,18 @@ impl ExecutionPlan for ParquetExec {
             None => (0..self.base_config.file_schema.fields().len()).collect(),
         };
         let pruning_predicate = self.pruning_predicate.clone();
+        let bloom_filter_predicate = self.bloom_filter_predicate.clone();
+        let row_filter = if self.row_filter_enabled {
+            self.row_filter.clone()
+        } else {
+            None
+        };
         let batch_size = self.base_config.batch_size;
         let limit = self.base_config.limit;
         let object_store = Arc::clone(&self.base_config.object_store);
+        let metadata_cache = self.base_config.metadata_cache.clone();
+        let schema_adapter =
+            SchemaAdapter::new(Arc::clone(&self.base_config.file_schema));
         let partition_col_proj = PartitionColumnProjector::new(
             Arc::clone(&self.projected_schema),
             &self.base_config.table_partition_cols,
@@ -212,11 +323,15 @@ impl ExecutionPlan for ParquetExec {
         let join_handle = task::spawn_blocking(move || {
             if let Err(e) = read_partition(
                 object_store.as_ref(),
+                metadata_cache.as_deref(),
                 partition_index,
                 partition,
                 metrics,
                 &projection,
+                &schema_adapter,
                 &pruning_predicate,
+                &bloom_filter_predicate,
+                &row_filter,
                 batch_size,
                 response_tx,
                 limit,
@@ -275,7 +390,7 @@ fn send_result(
 /// that implements [`PruningStatistics`]
 struct RowGroupPruningStatistics<'a> {
     row_group_metadata: &'a [RowGroupMetaData],
-    parquet_schema: &'a Schema,
+    table_schema: &'a Schema,
 }
 
 /// Extract the min/max statistics from a `ParquetStatistics` object
@@ -307,12 +422,20 @@ macro_rules! get_statistic {
 // Extract the min or max value calling `func` or `bytes_func` on the ParquetStatistics as appropriate
 macro_rules! get_min_max_values {
     ($self:expr, $column:expr, $func:ident, $bytes_func:ident) => {{
-        let (column_index, field) = if let Some((v, f)) = $self.parquet_schema.column_with_name(&$column.name) {
-            (v, f)
+        let field = if let Some((_, f)) = $self.table_schema.column_with_name(&$column.name) {
+            f
         } else {
             // Named column was not present
             return None
         };
+        // the columns of the file may be in another order than the ones of
+        // the table, or miss some of them
+        let column_index = $self.row_group_metadata
+            .first()?
+            .schema_descr()
+            .columns()
+            .iter()
+            .position(|c| c.path().string() == $column.name)?;
 
         let data_type = field.data_type();
         let null_scalar: ScalarValue = if let Ok(v) = data_type.try_into() {
@@ -336,8 +459,11 @@ macro_rules! get_min_max_values {
             })
             .collect();
 
-        // ignore errors converting to arrays (e.g. different types)
-        ScalarValue::iter_to_array(scalar_values).ok()
+        // ignore errors converting to arrays (e.g. different types), and
+        // cast the statistics of the file to the type of the table column
+        ScalarValue::iter_to_array(scalar_values)
+            .ok()
+            .and_then(|array| arrow::compute::cast(&array, data_type).ok())
     }}
 }
 
@@ -360,11 +486,11 @@ fn build_row_group_predicate(
     metrics: ParquetFileMetrics,
     row_group_metadata: &[RowGroupMetaData],
 ) -> Box<dyn Fn(&RowGroupMetaData, usize) -> bool> {
-    let parquet_schema = pruning_predicate.schema().as_ref();
+    let table_schema = pruning_predicate.schema().as_ref();
 
     let pruning_stats = RowGroupPruningStatistics {
         row_group_metadata,
-        parquet_schema,
+        table_schema,
     };
     let predicate_values = pruning_predicate.prune(&pruning_stats);
 
@@ -385,21 +511,336 @@ fn build_row_group_predicate(
     }
 }
 
+/// Prunes the row groups `row_groups` of a file using their statistics, then
+/// the bloom filters and page index of the file. Returns the index of the
+/// row groups to read, each with the ranges of rows of its pruned pages.
+fn prune_row_groups(
+    row_groups: &[RowGroupMetaData],
+    object_reader: Arc<dyn ObjectReader>,
+    pruning_predicate: &Option<PruningPredicate>,
+    bloom_filter_predicate: &Option<BloomFilterPredicate>,
+    metrics: &ParquetFileMetrics,
+) -> Vec<(usize, Vec<Range<usize>>)> {
+    let mut keep = match pruning_predicate {
+        Some(pruning_predicate) => {
+            let row_group_predicate =
+                build_row_group_predicate(pruning_predicate, metrics.clone(), row_groups);
+            row_groups
+                .iter()
+                .enumerate()
+                .map(|(i, row_group)| row_group_predicate(row_group, i))
+                .collect::<Vec<_>>()
+        }
+        None => vec![true; row_groups.len()],
+    };
+    let mut skipped_ranges = vec![vec![]; row_groups.len()];
+
+    if keep.iter().any(|k| *k) {
+        // the metadata exposed by the parquet crate doesn't include the
+        // location of the bloom filters and page indexes
+        let reader = ChunkObjectReader(object_reader);
+        match read_thrift_metadata(&reader) {
+            Ok(t_metadata) if t_metadata.row_groups.len() == row_groups.len() => {
+                for (i, row_group) in row_groups.iter().enumerate() {
+                    if !keep[i] {
+                        continue;
+                    }
+                    let t_row_group = &t_metadata.row_groups[i];
+                    if let Some(bloom_filter_predicate) = bloom_filter_predicate {
+                        match bloom_filter_predicate.prune(
+                            &reader,
+                            row_group,
+                            t_row_group,
+                        ) {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                keep[i] = false;
+                                metrics.row_groups_pruned_bloom_filter.add(1);
+                                continue;
+                            }
+                            Err(e) => {
+                                debug!("Error evaluating bloom filters {}", e);
+                                metrics.predicate_evaluation_errors.add(1);
+                            }
+                        }
+                    }
+                    if let Some(pruning_predicate) = pruning_predicate {
+                        match prune_pages(
+                            pruning_predicate,
+                            &reader,
+                            row_group,
+                            t_row_group,
+                        ) {
+                            Ok((num_pages, ranges)) => {
+                                metrics.pages_pruned.add(num_pages);
+                                let num_rows = row_group.num_rows() as usize;
+                                if ranges.iter().map(|r| r.len()).sum::<usize>()
+                                    == num_rows
+                                {
+                                    keep[i] = false;
+                                    metrics.row_groups_pruned_page_index.add(1);
+                                } else {
+                                    skipped_ranges[i] = ranges;
+                                }
+                            }
+                            Err(e) => {
+                                debug!("Error evaluating page index {}", e);
+                                metrics.predicate_evaluation_errors.add(1);
+                            }
+                        }
+                    }
+                }
+            }
+            Ok(_) => {
+                debug!("Inconsistent number of row groups in the parquet metadata");
+                metrics.predicate_evaluation_errors.add(1);
+            }
+            Err(e) => {
+                debug!("Error reading parquet metadata {}", e);
+                metrics.predicate_evaluation_errors.add(1);
+            }
+        }
+    }
+
+    let selected = skipped_ranges
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| keep[*i])
+        .collect::<Vec<_>>();
+    let num_skipped = selected
+        .iter()
+        .flat_map(|(_, ranges)| ranges.iter().map(|r| r.len()))
+        .sum();
+    metrics.page_index_rows_skipped.add(num_skipped);
+    selected
+}
+
+/// Reads the columns `projection` of the row groups `row_groups` of
+/// `file_reader`, each with the ranges of its rows to skip, and calls `emit`
+/// with the rows read until it returns `false`. Returns `false` if `emit`
+/// stopped the read.
+///
+/// The row groups with skipped rows are read one column at a time, without
+/// decoding the data pages with only skipped rows.
+fn read_row_groups(
+    file_reader: &MetadataFileReader,
+    row_groups: &[(usize, Vec<Range<usize>>)],
+    projection: &[usize],
+    batch_size: usize,
+    metrics: &ParquetFileMetrics,
+    mut emit: impl FnMut(RecordBatch) -> Result<bool>,
+) -> Result<bool> {
+    let mut start = 0;
+    while start < row_groups.len() {
+        // the consecutive row groups read entirely are read together
+        let end = row_groups[start..]
+            .iter()
+            .position(|(_, skipped)| !skipped.is_empty() && !projection.is_empty())
+            .map_or(row_groups.len(), |position| start + position);
+        if start < end {
+            let file_reader = file_reader
+                .with_row_groups(row_groups[start..end].iter().map(|(i, _)| *i));
+            let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
+            let batch_reader = arrow_reader
+                .get_record_reader_by_columns(projection.to_owned(), batch_size)?;
+            for batch in batch_reader {
+                let batch = batch?;
+                if batch.num_rows() > 0 && !emit(batch)? {
+                    return Ok(false);
+                }
+            }
+        }
+        if let Some((row_group, skipped)) = row_groups.get(end) {
+            let completed = read_unskipped_rows(
+                Arc::new(file_reader.clone()),
+                *row_group,
+                skipped,
+                projection,
+                batch_size,
+                metrics,
+                &mut emit,
+            )?;
+            if !completed {
+                return Ok(false);
+            }
+        }
+        start = end + 1;
+    }
+    Ok(true)
+}
+
+/// The arrow schema of the parquet file read by `file_reader`
+fn file_arrow_schema(file_reader: &MetadataFileReader) -> Result<Schema> {
+    let metadata = file_reader.metadata().file_metadata();
+    Ok(parquet_to_arrow_schema(
+        metadata.schema_descr(),
+        metadata.key_value_metadata(),
+    )?)
+}
+
+/// Read and decode the thrift definition of the metadata of a parquet file
+fn read_thrift_metadata(reader: &ChunkObjectReader) -> Result<TFileMetaData> {
+    const FOOTER_SIZE: u64 = 8;
+    let file_size = reader.len();
+    if file_size < FOOTER_SIZE {
+        return Err(DataFusionError::Execution(
+            "Invalid parquet file, size is smaller than the footer".to_string(),
+        ));
+    }
+    let mut footer = [0; FOOTER_SIZE as usize];
+    reader
+        .get_read(file_size - FOOTER_SIZE, FOOTER_SIZE as usize)?
+        .read_exact(&mut footer)?;
+    if &footer[4..] != b"PAR1" {
+        return Err(DataFusionError::Execution(
+            "Invalid parquet file, corrupt footer".to_string(),
+        ));
+    }
+    let metadata_len = u32::from_le_bytes([footer[0], footer[1], footer[2], footer[3]]);
+    let metadata_start = (file_size - FOOTER_SIZE)
+        .checked_sub(metadata_len as u64)
+        .ok_or_else(|| {
+            DataFusionError::Execution(
+                "Invalid parquet file, metadata length is larger than the file"
+                    .to_string(),
+            )
+        })?;
+    let mut read = reader.get_read(metadata_start, metadata_len as usize)?;
+    let mut prot = TCompactInputProtocol::new(&mut read);
+    TFileMetaData::read_from_in_protocol(&mut prot).map_err(|e| {
+        DataFusionError::Execution(format!("Could not parse metadata: {}", e))
+    })
+}
+
+/// A [`FileReader`] of a parquet file of an object store whose metadata was
+/// already decoded, possibly by a previous scan of the file
+#[derive(Clone)]
+struct MetadataFileReader {
+    reader: Arc<ChunkObjectReader>,
+    metadata: Arc<ParquetMetaData>,
+}
+
+impl MetadataFileReader {
+    /// Reads the metadata from the footer of the file `file_meta` read by
+    /// `object_reader`, unless it is in `metadata_cache`
+    fn try_new(
+        object_reader: Arc<dyn ObjectReader>,
+        file_meta: &FileMeta,
+        metadata_cache: Option<&dyn MetadataCache>,
+    ) -> Result<Self> {
+        let reader = ChunkObjectReader(object_reader);
+        let key = FileCacheKey::from(file_meta);
+        let cached = metadata_cache.and_then(|cache| cache.get_parquet_metadata(&key));
+        let metadata = match cached {
+            Some(metadata) => metadata,
+            None => {
+                let metadata = Arc::new(footer::parse_metadata(&reader)?);
+                if let Some(metadata_cache) = metadata_cache {
+                    metadata_cache.put_parquet_metadata(key, Arc::clone(&metadata));
+                }
+                metadata
+            }
+        };
+        Ok(Self {
+            reader: Arc::new(reader),
+            metadata,
+        })
+    }
+
+    /// A reader of the row groups `row_groups` of the file only
+    fn with_row_groups(&self, row_groups: impl Iterator<Item = usize>) -> Self {
+        let row_groups = row_groups
+            .map(|i| self.metadata.row_group(i).clone())
+            .collect();
+        Self {
+            reader: Arc::clone(&self.reader),
+            metadata: Arc::new(ParquetMetaData::new(
+                self.metadata.file_metadata().clone(),
+                row_groups,
+            )),
+        }
+    }
+}
+
+impl FileReader for MetadataFileReader {
+    fn metadata(&self) -> &ParquetMetaData {
+        &self.metadata
+    }
+
+    fn num_row_groups(&self) -> usize {
+        self.metadata.num_row_groups()
+    }
+
+    fn get_row_group(&self, i: usize) -> ParquetResult<Box<dyn RowGroupReader + '_>> {
+        if i >= self.num_row_groups() {
+            return Err(ParquetError::IndexOutOfBound(i, self.num_row_groups()));
+        }
+        Ok(Box::new(MetadataRowGroupReader {
+            reader: Arc::clone(&self.reader),
+            metadata: self.metadata.row_group(i),
+        }))
+    }
+
+    fn get_row_iter(&self, _projection: Option<SchemaType>) -> ParquetResult<RowIter> {
+        Err(ParquetError::General(
+            "Row iteration is not supported by MetadataFileReader".to_string(),
+        ))
+    }
+}
+
+/// A [`RowGroupReader`] of a row group of a [`MetadataFileReader`]
+struct MetadataRowGroupReader<'a> {
+    reader: Arc<ChunkObjectReader>,
+    metadata: &'a RowGroupMetaData,
+}
+
+impl<'a> RowGroupReader for MetadataRowGroupReader<'a> {
+    fn metadata(&self) -> &RowGroupMetaData {
+        self.metadata
+    }
+
+    fn num_columns(&self) -> usize {
+        self.metadata.num_columns()
+    }
+
+    fn get_column_page_reader(&self, i: usize) -> ParquetResult<Box<dyn PageReader>> {
+        let column = self.metadata.column(i);
+        let (start, length) = column.byte_range();
+        let read = self.reader.get_read(start, length as usize)?;
+        Ok(Box::new(SerializedPageReader::new(
+            read,
+            column.num_values(),
+            column.compression(),
+            column.column_descr().physical_type(),
+        )?))
+    }
+
+    fn get_row_iter(&self, _projection: Option<SchemaType>) -> ParquetResult<RowIter> {
+        Err(ParquetError::General(
+            "Row iteration is not supported by MetadataRowGroupReader".to_string(),
+        ))
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn read_partition(
     object_store: &dyn ObjectStore,
+    metadata_cache: Option<&dyn MetadataCache>,
     partition_index: usize,
     partition: Vec<PartitionedFile>,
     metrics: ExecutionPlanMetricsSet,
     projection: &[usize],
+    schema_adapter: &SchemaAdapter,
     pruning_predicate: &Option<PruningPredicate>,
+    bloom_filter_predicate: &Option<BloomFilterPredicate>,
+    row_filter: &Option<RowFilter>,
     batch_size: usize,
     response_tx: Sender<ArrowResult<RecordBatch>>,
     limit: Option<usize>,
     mut partition_column_projector: PartitionColumnProjector,
 ) -> Result<()> {
     let mut total_rows = 0;
-    'outer: for partitioned_file in partition {
+    for partitioned_file in partition {
         let file_metrics = ParquetFileMetrics::new(
             partition_index,
             &*partitioned_file.file_meta.path(),
@@ -407,48 +848,101 @@ fn read_partition(
         );
         let object_reader =
             object_store.file_reader(partitioned_file.file_meta.sized_file.clone())?;
-        let mut file_reader =
-            SerializedFileReader::new(ChunkObjectReader(object_reader))?;
-        if let Some(pruning_predicate) = pruning_predicate {
-            let row_group_predicate = build_row_group_predicate(
-                pruning_predicate,
-                file_metrics,
-                file_reader.metadata().row_groups(),
-            );
-            file_reader.filter_row_groups(&row_group_predicate);
-        }
-        let mut arrow_reader = ParquetFileArrowReader::new(Arc::new(file_reader));
-        let mut batch_reader = arrow_reader
-            .get_record_reader_by_columns(projection.to_owned(), batch_size)?;
-        loop {
-            match batch_reader.next() {
-                Some(Ok(batch)) => {
-                    total_rows += batch.num_rows();
-                    let proj_batch = partition_column_projector
-                        .project(batch, &partitioned_file.partition_values);
-
-                    send_result(&response_tx, proj_batch)?;
-                    if limit.map(|l| total_rows >= l).unwrap_or(false) {
-                        break 'outer;
-                    }
-                }
-                None => {
-                    break;
-                }
-                Some(Err(e)) => {
-                    let err_msg = format!(
-                        "Error reading batch from {}: {}",
-                        partitioned_file,
-                        e.to_string()
-                    );
-                    // send error to operator
-                    send_result(
-                        &response_tx,
-                        Err(ArrowError::ParquetError(err_msg.clone())),
-                    )?;
-                    // terminate thread with error
-                    return Err(DataFusionError::Execution(err_msg));
-                }
+        let file_reader = MetadataFileReader::try_new(
+            Arc::clone(&object_reader),
+            &partitioned_file.file_meta,
+            metadata_cache,
+        )?;
+        let row_groups = file_reader.metadata().row_groups();
+        let row_groups =
+            if pruning_predicate.is_some() || bloom_filter_predicate.is_some() {
+                prune_row_groups(
+                    row_groups,
+                    object_reader,
+                    pruning_predicate,
+                    bloom_filter_predicate,
+                    &file_metrics,
+                )
+            } else {
+                (0..row_groups.len()).map(|i| (i, vec![])).collect()
+            };
+
+        let mut emit = |batch: RecordBatch| -> Result<bool> {
+            total_rows += batch.num_rows();
+            let proj_batch = partition_column_projector
+                .project(batch, &partitioned_file.partition_values);
+            send_result(&response_tx, proj_batch)?;
+            Ok(!limit.map(|l| total_rows >= l).unwrap_or(false))
+        };
+        // the columns of a file with another schema than the table are
+        // mapped by name, `None` if the schemas are the same
+        let file_projection = file_arrow_schema(&file_reader).and_then(|file_schema| {
+            if file_schema.fields() == schema_adapter.table_schema().fields() {
+                return Ok(None);
+            }
+            let mut file_projection =
+                schema_adapter.map_projections(&file_schema, projection)?;
+            // a column is still read for the number of rows of the batches
+            // when none of the projected columns is in the file
+            if file_projection.is_empty() && !file_schema.fields().is_empty() {
+                file_projection.push(0);
+            }
+            Ok(Some(file_projection))
+        });
+        let result = match (file_projection, row_filter) {
+            (Err(e), _) => Err(e),
+            // the row filter is only evaluated on the files with the table
+            // schema, as it is still evaluated after the scan
+            (Ok(Some(file_projection)), _) => read_row_groups(
+                &file_reader,
+                &row_groups,
+                &file_projection,
+                batch_size,
+                &file_metrics,
+                |batch| emit(schema_adapter.adapt_batch(batch, projection)?),
+            ),
+            // the row filter only saves work when some projected columns are
+            // not referenced by the filter
+            (Ok(None), Some(row_filter))
+                if projection.iter().any(|i| !row_filter.columns().contains(i)) =>
+            {
+                read_filtered_row_groups(
+                    Arc::new(file_reader),
+                    &row_groups,
+                    projection,
+                    row_filter,
+                    batch_size,
+                    &file_metrics,
+                    emit,
+                )
+            }
+            (Ok(None), _) => read_row_groups(
+                &file_reader,
+                &row_groups,
+                projection,
+                batch_size,
+                &file_metrics,
+                emit,
+            ),
+        };
+
+        match result {
+            Ok(true) => {}
+            // the limit is reached
+            Ok(false) => break,
+            Err(e) => {
+                let err_msg = format!(
+                    "Error reading batch from {}: {}",
+                    partitioned_file,
+                    e.to_string()
+                );
+                // send error to operator
+                send_result(
+                    &response_tx,
+                    Err(ArrowError::ParquetError(err_msg.clone())),
+                )?;
+                // terminate thread with error
+                return Err(DataFusionError::Execution(err_msg));
             }
         }
     }
@@ -458,16 +952,31 @@ fn read_partition(
     Ok(())
 }
 
+impl FileWriter for ArrowWriter<File> {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        Ok(ArrowWriter::write(self, batch)?)
+    }
+
+    fn close(self: Box<Self>) -> Result<()> {
+        ArrowWriter::close(*self)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::datasource::{
         file_format::{parquet::ParquetFormat, FileFormat},
+        listing::DefaultMetadataCache,
         object_store::local::{
             local_object_reader_stream, local_unpartitioned_file, LocalFileSystem,
         },
     };
 
     use super::*;
+    use crate::datasource::object_store::local::local_object_reader;
+    use crate::physical_plan::collect;
+    use arrow::array::{Int64Array, StringArray};
     use arrow::datatypes::{DataType, Field};
     use futures::StreamExt;
     use parquet::{
@@ -476,6 +985,276 @@ mod tests {
         schema::types::SchemaDescPtr,
     };
 
+    #[tokio::test]
+    async fn parquet_exec_with_metadata_cache() -> Result<()> {
+        let testdata = crate::test_util::parquet_test_data();
+        let filename = format!("{}/alltypes_plain.parquet", testdata);
+        let file = local_unpartitioned_file(filename.clone());
+        let metadata_cache = Arc::new(DefaultMetadataCache::new());
+        let parquet_exec = ParquetExec::new(
+            PhysicalPlanConfig {
+                object_store: Arc::new(LocalFileSystem {}),
+                file_groups: vec![vec![file.clone()]],
+                file_schema: ParquetFormat::default()
+                    .infer_schema(local_object_reader_stream(vec![filename]))
+                    .await?,
+                statistics: Statistics::default(),
+                projection: Some(vec![0]),
+                batch_size: 1024,
+                limit: None,
+                table_partition_cols: vec![],
+                metadata_cache: Some(metadata_cache.clone()),
+            },
+            None,
+        );
+
+        let key = FileCacheKey::from(&file.file_meta);
+        assert!(metadata_cache.get_parquet_metadata(&key).is_none());
+        let batches = collect(Arc::new(parquet_exec.clone())).await?;
+        assert_eq!(8, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+        let metadata = metadata_cache.get_parquet_metadata(&key).unwrap();
+        assert_eq!(8, metadata.file_metadata().num_rows());
+
+        // the second scan reads the rows with the cached footer
+        let batches = collect(Arc::new(parquet_exec)).await?;
+        assert_eq!(8, batches.iter().map(|b| b.num_rows()).sum::<usize>());
+        assert!(Arc::ptr_eq(
+            &metadata,
+            &metadata_cache.get_parquet_metadata(&key).unwrap()
+        ));
+        Ok(())
+    }
+
+    /// Writes the rows `id` = 0..200 and `name` = "name-{id}" to a parquet file
+    /// with 2 row groups and data pages of 10 rows. The `name` column chunks
+    /// have bloom filters and the `id` column chunks a page index, written
+    /// after the column chunks like other writers do.
+    fn write_indexed_file(path: &std::path::Path) -> Result<()> {
+        use parquet::file::properties::WriterProperties;
+        use parquet_format::{
+            BloomFilterAlgorithm, BloomFilterCompression, BloomFilterHash,
+            BloomFilterHeader, BoundaryOrder, ColumnIndex, OffsetIndex, PageHeader,
+            PageLocation, SplitBlockAlgorithm, Uncompressed, XxHash,
+        };
+        use std::io::Cursor;
+        use thrift::protocol::{TCompactOutputProtocol, TOutputProtocol};
+
+        fn write_thrift(
+            data: &mut Vec<u8>,
+            write: impl FnOnce(&mut dyn TOutputProtocol) -> thrift::Result<()>,
+        ) -> Result<(i64, i32)> {
+            let offset = data.len();
+            {
+                let mut prot = TCompactOutputProtocol::new(&mut *data);
+                write(&mut prot)
+                    .and_then(|_| prot.flush())
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+            }
+            Ok((offset as i64, (data.len() - offset) as i32))
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, false),
+        ]));
+        let props = WriterProperties::builder()
+            .set_max_row_group_size(100)
+            .set_dictionary_enabled(false)
+            .set_data_pagesize_limit(1)
+            .set_write_batch_size(10)
+            .build();
+        let mut writer = ArrowWriter::try_new(
+            std::fs::File::create(path)?,
+            schema.clone(),
+            Some(props),
+        )?;
+        for first_id in [0, 100] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int64Array::from_iter_values(first_id..first_id + 100)),
+                    Arc::new(StringArray::from_iter_values(
+                        (first_id..first_id + 100).map(|id| format!("name-{}", id)),
+                    )),
+                ],
+            )?;
+            writer.write(&batch)?;
+        }
+        writer.close()?;
+
+        // the column chunks are kept, and the indexes are written before a new
+        // footer
+        let object_reader = local_object_reader(path.to_str().unwrap().to_owned());
+        let mut metadata = read_thrift_metadata(&ChunkObjectReader(object_reader))?;
+        let mut data = std::fs::read(path)?;
+        let metadata_len =
+            u32::from_le_bytes(data[data.len() - 8..data.len() - 4].try_into().unwrap());
+        data.truncate(data.len() - 8 - metadata_len as usize);
+
+        for (i, row_group) in metadata.row_groups.iter_mut().enumerate() {
+            let first_id = i as i64 * 100;
+
+            // the page index of `id`, from the headers of its data pages
+            let id_chunk = &mut row_group.columns[0];
+            let id_metadata = id_chunk.meta_data.as_ref().unwrap();
+            let mut position = id_metadata.data_page_offset as usize;
+            let end = position + id_metadata.total_compressed_size as usize;
+            let mut locations = vec![];
+            let mut first_row_index = 0;
+            while position < end {
+                let mut cursor = Cursor::new(&data[position..end]);
+                let header = {
+                    let mut prot = TCompactInputProtocol::new(&mut cursor);
+                    PageHeader::read_from_in_protocol(&mut prot)
+                        .map_err(|e| DataFusionError::Execution(e.to_string()))?
+                };
+                let page_size = cursor.position() as i32 + header.compressed_page_size;
+                let num_rows = header.data_page_header.unwrap().num_values as i64;
+                locations.push(PageLocation {
+                    offset: position as i64,
+                    compressed_page_size: page_size,
+                    first_row_index,
+                });
+                first_row_index += num_rows;
+                position += page_size as usize;
+            }
+            let page_bounds = locations
+                .iter()
+                .enumerate()
+                .map(|(page, location)| {
+                    let next = locations.get(page + 1).map_or(100, |l| l.first_row_index);
+                    (first_id + location.first_row_index, first_id + next - 1)
+                })
+                .collect::<Vec<_>>();
+            let column_index = ColumnIndex {
+                null_pages: vec![false; locations.len()],
+                min_values: page_bounds
+                    .iter()
+                    .map(|(min, _)| min.to_le_bytes().to_vec())
+                    .collect(),
+                max_values: page_bounds
+                    .iter()
+                    .map(|(_, max)| max.to_le_bytes().to_vec())
+                    .collect(),
+                boundary_order: BoundaryOrder::ASCENDING,
+                null_counts: None,
+            };
+            let (offset, length) =
+                write_thrift(&mut data, |prot| column_index.write_to_out_protocol(prot))?;
+            id_chunk.column_index_offset = Some(offset);
+            id_chunk.column_index_length = Some(length);
+            let offset_index = OffsetIndex {
+                page_locations: locations,
+            };
+            let (offset, length) =
+                write_thrift(&mut data, |prot| offset_index.write_to_out_protocol(prot))?;
+            id_chunk.offset_index_offset = Some(offset);
+            id_chunk.offset_index_length = Some(length);
+
+            // the bloom filter of `name`
+            let names = (first_id..first_id + 100)
+                .map(|id| format!("name-{}", id))
+                .collect::<Vec<_>>();
+            let bitset =
+                bloom_filter::bloom_filter_bitset(names.iter().map(|n| n.as_bytes()), 16);
+            let header = BloomFilterHeader {
+                num_bytes: bitset.len() as i32,
+                algorithm: BloomFilterAlgorithm::BLOCK(SplitBlockAlgorithm {}),
+                hash: BloomFilterHash::XXHASH(XxHash {}),
+                compression: BloomFilterCompression::UNCOMPRESSED(Uncompressed {}),
+            };
+            let (offset, _) =
+                write_thrift(&mut data, |prot| header.write_to_out_protocol(prot))?;
+            data.extend_from_slice(&bitset);
+            let name_metadata = row_group.columns[1].meta_data.as_mut().unwrap();
+            name_metadata.bloom_filter_offset = Some(offset);
+        }
+
+        let (_, metadata_len) =
+            write_thrift(&mut data, |prot| metadata.write_to_out_protocol(prot))?;
+        data.extend_from_slice(&metadata_len.to_le_bytes());
+        data.extend_from_slice(b"PAR1");
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn parquet_exec_with_bloom_filters_and_page_index() -> Result<()> {
+        use crate::logical_plan::{col, lit};
+
+        let tmp_dir = tempfile::tempdir()?;
+        let path = tmp_dir.path().join("indexed.parquet");
+        write_indexed_file(&path)?;
+        let filename = path.to_str().unwrap().to_owned();
+        let file_schema = ParquetFormat::default()
+            .infer_schema(local_object_reader_stream(vec![filename.clone()]))
+            .await?;
+
+        let scan = |predicate: Expr, row_filter: bool| {
+            ParquetExec::new(
+                PhysicalPlanConfig {
+                    object_store: Arc::new(LocalFileSystem {}),
+                    file_groups: vec![vec![local_unpartitioned_file(filename.clone())]],
+                    file_schema: file_schema.clone(),
+                    statistics: Statistics::default(),
+                    projection: None,
+                    batch_size: 1024,
+                    limit: None,
+                    table_partition_cols: vec![],
+                    metadata_cache: None,
+                },
+                Some(predicate),
+            )
+            .with_row_filter(row_filter)
+        };
+        let metric = |exec: &Arc<ParquetExec>, name: &str| {
+            exec.metrics()
+                .unwrap()
+                .sum(|m| m.value().name() == name)
+                .map_or(0, |v| v.as_usize())
+        };
+        let ids = |batches: &[RecordBatch]| {
+            batches
+                .iter()
+                .flat_map(|b| {
+                    let ids = b.column(0).as_any().downcast_ref::<Int64Array>().unwrap();
+                    ids.values().to_vec()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the name is within the statistics of both row groups, but only in
+        // the bloom filter of the second one
+        let exec = Arc::new(scan(col("name").eq(lit("name-150")), false));
+        let batches = collect(exec.clone()).await?;
+        assert_eq!((100..200).collect::<Vec<i64>>(), ids(&batches));
+        assert_eq!(1, metric(&exec, "row_groups_pruned_bloom_filter"));
+
+        // the pages of both columns pruned by the page index are not decoded
+        let predicate = col("id")
+            .gt_eq(lit(125_i64))
+            .and(col("id").lt(lit(145_i64)));
+        let exec = Arc::new(scan(predicate.clone(), false));
+        let batches = collect(exec.clone()).await?;
+        assert_eq!((120..150).collect::<Vec<i64>>(), ids(&batches));
+        assert_eq!(1, metric(&exec, "row_groups_pruned"));
+        assert_eq!(7, metric(&exec, "pages_pruned"));
+        assert_eq!(70, metric(&exec, "page_index_rows_skipped"));
+        assert_eq!(14, metric(&exec, "pages_skipped_page_index"));
+
+        // with the row filter, the pages of `name` are skipped for the rows
+        // it filtered out
+        let exec = Arc::new(scan(predicate, true));
+        let batches = collect(exec.clone()).await?;
+        assert_eq!((125..145).collect::<Vec<i64>>(), ids(&batches));
+        assert_eq!(7, metric(&exec, "pages_pruned"));
+        assert_eq!(7, metric(&exec, "pages_skipped_page_index"));
+        assert_eq!(10, metric(&exec, "pushdown_rows_filtered"));
+        assert_eq!(7, metric(&exec, "pages_pruned_row_filter"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn parquet_exec_with_projection() -> Result<()> {
         let testdata = crate::test_util::parquet_test_data();
@@ -492,6 +1271,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             None,
         );
@@ -547,6 +1327,7 @@ mod tests {
                     "month".to_owned(),
                     "day".to_owned(),
                 ],
+                metadata_cache: None,
             },
             None,
         );
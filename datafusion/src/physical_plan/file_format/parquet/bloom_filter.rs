@@ -0,0 +1,591 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Pruning of row groups using the split block bloom filters of their
+//! column chunks, for `col = literal` and `col IN (literals)` predicates.
+//!
+//! See <https://github.com/apache/parquet-format/blob/master/BloomFilter.md>
+
+use std::convert::{TryFrom, TryInto};
+use std::hash::Hasher;
+use std::io::{Cursor, Read};
+
+use parquet::basic::{ConvertedType, Type as PhysicalType};
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::reader::{ChunkReader, Length};
+use parquet::schema::types::ColumnDescriptor;
+use parquet_format::{
+    BloomFilterAlgorithm, BloomFilterCompression, BloomFilterHash, BloomFilterHeader,
+    RowGroup,
+};
+use thrift::protocol::TCompactInputProtocol;
+use twox_hash::XxHash64;
+
+use crate::datasource::file_format::parquet::ChunkObjectReader;
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::{Column, Expr, Operator};
+use crate::scalar::ScalarValue;
+
+/// The salt values used to compute the bit set in a block
+const SALT: [u32; 8] = [
+    0x47b6137b, 0x44974d91, 0x8824ad5b, 0xa2b7289d, 0x705495c7, 0x2df1424b, 0x9efc4947,
+    0x5c6bfb31,
+];
+
+/// The number of bytes in a block of a split block bloom filter
+const BLOCK_SIZE: usize = 32;
+
+/// The number of bytes read for the header of a bloom filter, more than the
+/// thrift encoding of the headers of the filters this reader supports
+const HEADER_SIZE_ESTIMATE: u64 = 32;
+
+/// The conjuncts of a predicate that compare a column with literal values,
+/// which can be checked against the bloom filters of a row group. A row
+/// group can be skipped as soon as one column contains none of the values
+/// it is compared with.
+#[derive(Debug, Clone)]
+pub(crate) struct BloomFilterPredicate {
+    /// The columns and the values they must be equal to
+    terms: Vec<(Column, Vec<ScalarValue>)>,
+}
+
+impl BloomFilterPredicate {
+    /// Extracts the `col = literal` and `col IN (literals)` conjuncts of
+    /// `expr`, returns `None` if there are none
+    pub fn try_new(expr: &Expr) -> Option<Self> {
+        let mut conjuncts = vec![];
+        split_conjunction(expr, &mut conjuncts);
+        let terms = conjuncts
+            .into_iter()
+            .filter_map(equality_term)
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            None
+        } else {
+            Some(Self { terms })
+        }
+    }
+
+    /// Returns `false` if the bloom filters of `row_group` prove that no row
+    /// of the row group matches the predicate. Column chunks without a bloom
+    /// filter, or with a bloom filter this reader does not understand, can
+    /// not prune anything.
+    pub fn prune(
+        &self,
+        reader: &ChunkObjectReader,
+        row_group: &RowGroupMetaData,
+        t_row_group: &RowGroup,
+    ) -> Result<bool> {
+        for (column, values) in &self.terms {
+            let column_index = match (0..row_group.num_columns()).find(|i| {
+                let descr = row_group.column(*i).column_descr();
+                descr.path().parts().len() == 1 && descr.name() == column.name
+            }) {
+                Some(column_index) => column_index,
+                None => continue,
+            };
+            let descr = row_group.column(column_index).column_descr();
+            let hashes = match values
+                .iter()
+                .map(|value| encode_value(value, descr).map(|v| hash(&v)))
+                .collect::<Option<Vec<_>>>()
+            {
+                Some(hashes) => hashes,
+                None => continue,
+            };
+
+            let offset = t_row_group
+                .columns
+                .get(column_index)
+                .and_then(|c| c.meta_data.as_ref())
+                .and_then(|m| m.bloom_filter_offset);
+            let bitset = match offset {
+                Some(offset) => read_bloom_filter(reader, offset)?,
+                None => None,
+            };
+            if let Some(bitset) = bitset {
+                if !hashes.iter().any(|hash| check_hash(&bitset, *hash)) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Appends the conjuncts of `expr` to `conjuncts`
+pub(super) fn split_conjunction<'a>(expr: &'a Expr, conjuncts: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        } => {
+            split_conjunction(left, conjuncts);
+            split_conjunction(right, conjuncts);
+        }
+        other => conjuncts.push(other),
+    }
+}
+
+/// Returns the column and the values of a `col = literal` or
+/// `col IN (literals)` expression
+fn equality_term(expr: &Expr) -> Option<(Column, Vec<ScalarValue>)> {
+    match expr {
+        Expr::BinaryExpr {
+            left,
+            op: Operator::Eq,
+            right,
+        } => match (left.as_ref(), right.as_ref()) {
+            (Expr::Column(column), Expr::Literal(value))
+            | (Expr::Literal(value), Expr::Column(column))
+                if !value.is_null() =>
+            {
+                Some((column.clone(), vec![value.clone()]))
+            }
+            _ => None,
+        },
+        Expr::InList {
+            expr,
+            list,
+            negated: false,
+        } => match expr.as_ref() {
+            Expr::Column(column) => {
+                let values = list
+                    .iter()
+                    .map(|e| match e {
+                        Expr::Literal(value) if !value.is_null() => Some(value.clone()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((column.clone(), values))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the plain encoding of `value` in the column `descr`, which is
+/// what the bloom filter hashes, or `None` if `value` can't be encoded exactly
+/// with the physical and logical types of the column. Floating point values
+/// are not supported as values that compare equal, like `0.0` and `-0.0`,
+/// have different encodings.
+fn encode_value(value: &ScalarValue, descr: &ColumnDescriptor) -> Option<Vec<u8>> {
+    // the logical types without a converted type, like timestamps in
+    // nanoseconds, are not supported
+    if descr.converted_type() == ConvertedType::NONE && descr.logical_type().is_some() {
+        return None;
+    }
+
+    match (descr.physical_type(), descr.converted_type()) {
+        (
+            PhysicalType::INT32,
+            ConvertedType::NONE
+            | ConvertedType::INT_8
+            | ConvertedType::INT_16
+            | ConvertedType::INT_32,
+        ) => {
+            let v = i32::try_from(integer_value(value)?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        // the unsigned values are stored with the same bits
+        (
+            PhysicalType::INT32,
+            ConvertedType::UINT_8 | ConvertedType::UINT_16 | ConvertedType::UINT_32,
+        ) => {
+            let v = u32::try_from(integer_value(value)?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        (PhysicalType::INT32, ConvertedType::DATE) => match value {
+            ScalarValue::Date32(Some(v)) => Some(v.to_le_bytes().to_vec()),
+            _ => None,
+        },
+        (PhysicalType::INT64, ConvertedType::NONE | ConvertedType::INT_64) => {
+            let v = i64::try_from(integer_value(value)?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        (PhysicalType::INT64, ConvertedType::UINT_64) => {
+            let v = u64::try_from(integer_value(value)?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        (PhysicalType::INT64, ConvertedType::TIMESTAMP_MILLIS) => match value {
+            ScalarValue::TimestampMillisecond(Some(v), _) => {
+                Some(v.to_le_bytes().to_vec())
+            }
+            _ => None,
+        },
+        (PhysicalType::INT64, ConvertedType::TIMESTAMP_MICROS) => match value {
+            ScalarValue::TimestampMicrosecond(Some(v), _) => {
+                Some(v.to_le_bytes().to_vec())
+            }
+            _ => None,
+        },
+        (PhysicalType::INT32, ConvertedType::DECIMAL) => {
+            let v = i32::try_from(decimal_value(value, descr.type_scale())?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        (PhysicalType::INT64, ConvertedType::DECIMAL) => {
+            let v = i64::try_from(decimal_value(value, descr.type_scale())?).ok()?;
+            Some(v.to_le_bytes().to_vec())
+        }
+        (PhysicalType::FIXED_LEN_BYTE_ARRAY, ConvertedType::DECIMAL) => {
+            let v = decimal_value(value, descr.type_scale())?;
+            fixed_len_decimal(v, usize::try_from(descr.type_length()).ok()?)
+        }
+        // the strings stored in binary columns have the same bytes
+        (
+            PhysicalType::BYTE_ARRAY,
+            ConvertedType::NONE
+            | ConvertedType::UTF8
+            | ConvertedType::ENUM
+            | ConvertedType::JSON
+            | ConvertedType::BSON,
+        ) => match value {
+            ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                Some(v.as_bytes().to_vec())
+            }
+            ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                Some(v.clone())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The value of an integer literal
+fn integer_value(value: &ScalarValue) -> Option<i128> {
+    match value {
+        ScalarValue::Int8(Some(v)) => Some(*v as i128),
+        ScalarValue::Int16(Some(v)) => Some(*v as i128),
+        ScalarValue::Int32(Some(v)) => Some(*v as i128),
+        ScalarValue::Int64(Some(v)) => Some(*v as i128),
+        ScalarValue::UInt8(Some(v)) => Some(*v as i128),
+        ScalarValue::UInt16(Some(v)) => Some(*v as i128),
+        ScalarValue::UInt32(Some(v)) => Some(*v as i128),
+        ScalarValue::UInt64(Some(v)) => Some(*v as i128),
+        _ => None,
+    }
+}
+
+/// The unscaled value of a decimal or integer literal in a decimal column
+/// with the scale `scale`
+fn decimal_value(value: &ScalarValue, scale: i32) -> Option<i128> {
+    match value {
+        ScalarValue::Decimal128(Some(v), _, s) => {
+            (i32::try_from(*s).ok()? == scale).then(|| *v)
+        }
+        _ => integer_value(value)?
+            .checked_mul(10_i128.checked_pow(scale.try_into().ok()?)?),
+    }
+}
+
+/// The big endian two's complement encoding of the unscaled decimal `value`
+/// in `len` bytes, `None` if it doesn't fit
+fn fixed_len_decimal(value: i128, len: usize) -> Option<Vec<u8>> {
+    let bytes = value.to_be_bytes();
+    if len == 0 || len > bytes.len() {
+        return None;
+    }
+    let (high, low) = bytes.split_at(bytes.len() - len);
+    // the bytes dropped must only extend the sign of the ones kept
+    let sign = if value < 0 { 0xff } else { 0 };
+    if high.iter().any(|b| *b != sign) || (low[0] ^ sign) & 0x80 != 0 {
+        return None;
+    }
+    Some(low.to_vec())
+}
+
+/// The xxHash64 of `value` with a seed of 0, as specified for bloom filters
+fn hash(value: &[u8]) -> u64 {
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(value);
+    hasher.finish()
+}
+
+/// Reads the bit set of the bloom filter at `offset` in the file, returns
+/// `None` if it uses an algorithm, hash or compression this reader does not
+/// support. Only the header and the bit set it declares are read.
+fn read_bloom_filter(reader: &ChunkObjectReader, offset: i64) -> Result<Option<Vec<u8>>> {
+    let offset = u64::try_from(offset)
+        .ok()
+        .filter(|offset| *offset < reader.len())
+        .ok_or_else(|| {
+            DataFusionError::Execution(format!("Invalid bloom filter offset {}", offset))
+        })?;
+    let header_len = HEADER_SIZE_ESTIMATE.min(reader.len() - offset) as usize;
+    let mut header_bytes = vec![0; header_len];
+    reader
+        .get_read(offset, header_len)?
+        .read_exact(&mut header_bytes)?;
+    let mut cursor = Cursor::new(header_bytes.as_slice());
+    let header = {
+        let mut prot = TCompactInputProtocol::new(&mut cursor);
+        BloomFilterHeader::read_from_in_protocol(&mut prot).map_err(|e| {
+            DataFusionError::Execution(format!("Could not read bloom filter: {}", e))
+        })?
+    };
+
+    let supported = matches!(header.algorithm, BloomFilterAlgorithm::BLOCK(_))
+        && matches!(header.hash, BloomFilterHash::XXHASH(_))
+        && matches!(header.compression, BloomFilterCompression::UNCOMPRESSED(_));
+    let num_bytes = usize::try_from(header.num_bytes).unwrap_or(0);
+    if !supported || num_bytes == 0 || num_bytes % BLOCK_SIZE != 0 {
+        return Ok(None);
+    }
+
+    let bitset_offset = offset + cursor.position();
+    if bitset_offset + num_bytes as u64 > reader.len() {
+        return Err(DataFusionError::Execution(format!(
+            "The bloom filter at offset {} is larger than the file",
+            offset
+        )));
+    }
+    let mut bitset = vec![0; num_bytes];
+    reader
+        .get_read(bitset_offset, num_bytes)?
+        .read_exact(&mut bitset)?;
+    Ok(Some(bitset))
+}
+
+/// The mask of the bits set for `key` in each word of a block
+fn block_mask(key: u32) -> [u32; 8] {
+    let mut mask = [0; 8];
+    for (m, salt) in mask.iter_mut().zip(SALT.iter()) {
+        *m = 1 << (key.wrapping_mul(*salt) >> 27);
+    }
+    mask
+}
+
+/// The index of the block of a bit set of `num_blocks` blocks used by `hash`
+fn block_index(hash: u64, num_blocks: usize) -> usize {
+    (((hash >> 32) * num_blocks as u64) >> 32) as usize
+}
+
+/// Returns `false` if the value with the given hash is definitely not in the
+/// split block bloom filter `bitset`
+fn check_hash(bitset: &[u8], hash: u64) -> bool {
+    let index = block_index(hash, bitset.len() / BLOCK_SIZE);
+    let block = &bitset[index * BLOCK_SIZE..(index + 1) * BLOCK_SIZE];
+    block_mask(hash as u32)
+        .iter()
+        .zip(block.chunks_exact(4))
+        .all(|(mask, word)| {
+            let word = u32::from_le_bytes(word.try_into().unwrap());
+            word & mask != 0
+        })
+}
+
+/// Sets the bits for `hash` in `bitset`
+#[cfg(test)]
+fn insert_hash(bitset: &mut [u8], hash: u64) {
+    let index = block_index(hash, bitset.len() / BLOCK_SIZE);
+    let block = &mut bitset[index * BLOCK_SIZE..(index + 1) * BLOCK_SIZE];
+    for (mask, word) in block_mask(hash as u32)
+        .iter()
+        .zip(block.chunks_exact_mut(4))
+    {
+        let value = u32::from_le_bytes((&*word).try_into().unwrap()) | mask;
+        word.copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// The bit set of a bloom filter of `num_blocks` blocks with the plain
+/// encoded `values`, for the tests writing bloom filters in parquet files
+#[cfg(test)]
+pub(super) fn bloom_filter_bitset<'a>(
+    values: impl Iterator<Item = &'a [u8]>,
+    num_blocks: usize,
+) -> Vec<u8> {
+    let mut bitset = vec![0; num_blocks * BLOCK_SIZE];
+    for value in values {
+        insert_hash(&mut bitset, hash(value));
+    }
+    bitset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, in_list, lit};
+    use parquet::schema::types::{ColumnPath, Type};
+    use std::sync::Arc;
+
+    #[test]
+    fn xxhash64() {
+        // reference values of xxHash64 with a seed of 0
+        assert_eq!(0xef46db3751d8e999, hash(b""));
+        assert_eq!(0xd24ec4f1a98c6e5b, hash(b"a"));
+    }
+
+    #[test]
+    fn bloom_filter_check() {
+        let mut bitset = vec![0; 4 * BLOCK_SIZE];
+        let inserted = (0..20)
+            .map(|v: i64| hash(&v.to_le_bytes()))
+            .collect::<Vec<_>>();
+        for h in &inserted {
+            insert_hash(&mut bitset, *h);
+        }
+
+        for h in &inserted {
+            assert!(check_hash(&bitset, *h));
+        }
+        let false_positives = (1000..2000)
+            .filter(|v: &i64| check_hash(&bitset, hash(&v.to_le_bytes())))
+            .count();
+        assert!(false_positives < 100, "{} false positives", false_positives);
+    }
+
+    /// The descriptor of a column `c` of the given types
+    fn column_descr(
+        physical_type: PhysicalType,
+        converted_type: ConvertedType,
+        length: i32,
+        scale: i32,
+    ) -> ColumnDescriptor {
+        let mut builder = Type::primitive_type_builder("c", physical_type)
+            .with_converted_type(converted_type)
+            .with_length(length);
+        if converted_type == ConvertedType::DECIMAL {
+            builder = builder.with_precision(5).with_scale(scale);
+        }
+        ColumnDescriptor::new(
+            Arc::new(builder.build().unwrap()),
+            1,
+            0,
+            ColumnPath::from("c"),
+        )
+    }
+
+    #[test]
+    fn encode_values() {
+        let int32 = column_descr(PhysicalType::INT32, ConvertedType::NONE, -1, 0);
+        assert_eq!(
+            Some(5i32.to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::Int64(Some(5)), &int32)
+        );
+        assert_eq!(
+            None,
+            encode_value(&ScalarValue::Int64(Some(1 << 40)), &int32)
+        );
+        assert_eq!(None, encode_value(&ScalarValue::Date32(Some(5)), &int32));
+
+        let uint32 = column_descr(PhysicalType::INT32, ConvertedType::UINT_32, -1, 0);
+        assert_eq!(
+            Some(u32::MAX.to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::UInt32(Some(u32::MAX)), &uint32)
+        );
+        assert_eq!(None, encode_value(&ScalarValue::Int32(Some(-1)), &uint32));
+
+        let int64 = column_descr(PhysicalType::INT64, ConvertedType::NONE, -1, 0);
+        assert_eq!(
+            Some((-3i64).to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::Int8(Some(-3)), &int64)
+        );
+
+        let date = column_descr(PhysicalType::INT32, ConvertedType::DATE, -1, 0);
+        assert_eq!(
+            Some(5i32.to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::Date32(Some(5)), &date)
+        );
+        assert_eq!(None, encode_value(&ScalarValue::Int32(Some(5)), &date));
+
+        // the decimals are encoded unscaled, in the scale of the column
+        let decimal = column_descr(PhysicalType::INT32, ConvertedType::DECIMAL, -1, 2);
+        assert_eq!(
+            Some(500i32.to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::Int32(Some(5)), &decimal)
+        );
+        assert_eq!(
+            Some(123i32.to_le_bytes().to_vec()),
+            encode_value(&ScalarValue::Decimal128(Some(123), 9, 2), &decimal)
+        );
+        assert_eq!(
+            None,
+            encode_value(&ScalarValue::Decimal128(Some(123), 9, 3), &decimal)
+        );
+        let fixed_len_decimal = column_descr(
+            PhysicalType::FIXED_LEN_BYTE_ARRAY,
+            ConvertedType::DECIMAL,
+            3,
+            2,
+        );
+        assert_eq!(
+            Some(vec![0xff, 0xff, 0x85]),
+            encode_value(
+                &ScalarValue::Decimal128(Some(-123), 9, 2),
+                &fixed_len_decimal
+            )
+        );
+        assert_eq!(
+            None,
+            encode_value(
+                &ScalarValue::Decimal128(Some(1 << 23), 9, 2),
+                &fixed_len_decimal
+            )
+        );
+
+        // the strings of binary columns are encoded with their bytes
+        let utf8 = column_descr(PhysicalType::BYTE_ARRAY, ConvertedType::UTF8, -1, 0);
+        let binary = column_descr(PhysicalType::BYTE_ARRAY, ConvertedType::NONE, -1, 0);
+        for descr in [&utf8, &binary] {
+            assert_eq!(
+                Some(b"abc".to_vec()),
+                encode_value(&ScalarValue::Utf8(Some("abc".into())), descr)
+            );
+        }
+        let decimal_bytes =
+            column_descr(PhysicalType::BYTE_ARRAY, ConvertedType::DECIMAL, -1, 2);
+        assert_eq!(
+            None,
+            encode_value(&ScalarValue::Utf8(Some("abc".into())), &decimal_bytes)
+        );
+
+        let double = column_descr(PhysicalType::DOUBLE, ConvertedType::NONE, -1, 0);
+        assert_eq!(
+            None,
+            encode_value(&ScalarValue::Float64(Some(0.0)), &double)
+        );
+    }
+
+    #[test]
+    fn predicate_terms() {
+        let expr = col("a")
+            .eq(lit(1))
+            .and(in_list(col("b"), vec![lit("x"), lit("y")], false))
+            .and(col("c").gt(lit(3)))
+            .and(in_list(col("d"), vec![lit(1)], true));
+        let predicate = BloomFilterPredicate::try_new(&expr).unwrap();
+        assert_eq!(
+            vec![
+                (Column::from_name("a"), vec![ScalarValue::Int32(Some(1))]),
+                (
+                    Column::from_name("b"),
+                    vec![
+                        ScalarValue::Utf8(Some("x".to_string())),
+                        ScalarValue::Utf8(Some("y".to_string()))
+                    ]
+                ),
+            ],
+            predicate.terms
+        );
+
+        assert!(BloomFilterPredicate::try_new(&col("c").gt(lit(3))).is_none());
+    }
+}
@@ -0,0 +1,362 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Pruning of the pages of the column chunks of a row group, using the min /
+//! max values of the column index and the row numbers of the offset index
+//! of the file.
+//!
+//! See <https://github.com/apache/parquet-format/blob/master/PageIndex.md>
+
+use std::convert::TryInto;
+use std::io::Read;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array,
+    StringArray,
+};
+use parquet::basic::Type as PhysicalType;
+use parquet::file::metadata::RowGroupMetaData;
+use parquet::file::reader::ChunkReader;
+use parquet_format::{ColumnIndex, OffsetIndex, RowGroup};
+use thrift::protocol::TCompactInputProtocol;
+
+use crate::datasource::file_format::parquet::ChunkObjectReader;
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::Column;
+use crate::physical_optimizer::pruning::{PruningPredicate, PruningStatistics};
+
+/// Evaluates `predicate` on the pages of the column chunks of `row_group`
+/// that have a page index, one column at a time. Returns the number of
+/// pages pruned and the sorted, non overlapping ranges of rows of the row
+/// group they contain.
+pub(crate) fn prune_pages(
+    predicate: &PruningPredicate,
+    reader: &ChunkObjectReader,
+    row_group: &RowGroupMetaData,
+    t_row_group: &RowGroup,
+) -> Result<(usize, Vec<Range<usize>>)> {
+    let num_rows = row_group.num_rows() as usize;
+    let mut num_pages = 0;
+    let mut ranges = vec![];
+    for column in predicate.columns() {
+        let column_index = match (0..row_group.num_columns()).find(|i| {
+            let descr = row_group.column(*i).column_descr();
+            descr.path().parts().len() == 1 && descr.name() == column.name
+        }) {
+            Some(column_index) => column_index,
+            None => continue,
+        };
+        let t_column = match t_row_group.columns.get(column_index) {
+            Some(t_column) => t_column,
+            None => continue,
+        };
+        let (page_stats, page_locations) = match (
+            t_column
+                .column_index_offset
+                .zip(t_column.column_index_length),
+            t_column
+                .offset_index_offset
+                .zip(t_column.offset_index_length),
+        ) {
+            (Some(column_index), Some(offset_index)) => {
+                let mut read = index_read(reader, column_index)?;
+                let mut prot = TCompactInputProtocol::new(&mut read);
+                let page_stats = ColumnIndex::read_from_in_protocol(&mut prot)
+                    .map_err(|e| index_error("column", e))?;
+                let mut read = index_read(reader, offset_index)?;
+                let mut prot = TCompactInputProtocol::new(&mut read);
+                let page_locations = OffsetIndex::read_from_in_protocol(&mut prot)
+                    .map_err(|e| index_error("offset", e))?;
+                (page_stats, page_locations)
+            }
+            _ => continue,
+        };
+
+        let pruned = prune_column_pages(
+            predicate,
+            column,
+            row_group.column(column_index).column_type(),
+            &page_stats,
+            &page_locations,
+            num_rows,
+        )?;
+        num_pages += pruned.len();
+        ranges.extend(pruned);
+    }
+    Ok((num_pages, merge_ranges(ranges)))
+}
+
+/// Returns the ranges of rows of the pages of one column chunk that can't
+/// match `predicate`, according to the page index of the column chunk
+fn prune_column_pages(
+    predicate: &PruningPredicate,
+    column: &Column,
+    physical_type: PhysicalType,
+    page_stats: &ColumnIndex,
+    page_locations: &OffsetIndex,
+    num_rows: usize,
+) -> Result<Vec<Range<usize>>> {
+    let locations = &page_locations.page_locations;
+    if page_stats.min_values.len() != locations.len()
+        || page_stats.max_values.len() != locations.len()
+        || page_stats.null_pages.len() != locations.len()
+    {
+        // a page index we don't understand can't prune anything
+        return Ok(vec![]);
+    }
+
+    let statistics = PagePruningStatistics {
+        column,
+        physical_type,
+        page_stats,
+    };
+    let values = predicate.prune(&statistics)?;
+    Ok(values
+        .iter()
+        .enumerate()
+        .filter(|(_, keep)| !**keep)
+        .map(|(page, _)| {
+            let start = locations[page].first_row_index as usize;
+            let end = locations
+                .get(page + 1)
+                .map(|l| l.first_row_index as usize)
+                .unwrap_or(num_rows);
+            start..end
+        })
+        .collect())
+}
+
+/// A reader over the thrift encoded index at `(offset, length)` in the file
+fn index_read(
+    reader: &ChunkObjectReader,
+    (offset, length): (i64, i32),
+) -> Result<impl Read> {
+    Ok(reader.get_read(offset as u64, length as usize)?)
+}
+
+fn index_error(index: &str, e: thrift::Error) -> DataFusionError {
+    DataFusionError::Execution(format!("Could not read {} index: {}", index, e))
+}
+
+/// Sorts `ranges` and merges the ranges that overlap or touch
+fn merge_ranges(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<usize>> = vec![];
+    for range in ranges.into_iter().filter(|r| !r.is_empty()) {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Wraps the column index of a column chunk in a way that implements
+/// [`PruningStatistics`], with one container per page
+struct PagePruningStatistics<'a> {
+    column: &'a Column,
+    physical_type: PhysicalType,
+    page_stats: &'a ColumnIndex,
+}
+
+/// Decode the plain encoded min or max values of the pages into an array,
+/// the values of pages only containing nulls are null
+macro_rules! get_page_values {
+    ($self:expr, $values:expr, $array_type:ident, $native_type:ident) => {{
+        let array: $array_type = $values
+            .iter()
+            .zip($self.page_stats.null_pages.iter())
+            .map(|(value, null_page)| {
+                if *null_page {
+                    None
+                } else {
+                    value
+                        .as_slice()
+                        .try_into()
+                        .ok()
+                        .map($native_type::from_le_bytes)
+                }
+            })
+            .collect();
+        Some(Arc::new(array) as ArrayRef)
+    }};
+}
+
+impl<'a> PagePruningStatistics<'a> {
+    fn page_values(&self, column: &Column, values: &[Vec<u8>]) -> Option<ArrayRef> {
+        if column.name != self.column.name {
+            return None;
+        }
+        match self.physical_type {
+            PhysicalType::BOOLEAN => {
+                let array: BooleanArray = values
+                    .iter()
+                    .zip(self.page_stats.null_pages.iter())
+                    .map(|(value, null_page)| match null_page {
+                        true => None,
+                        false => value.first().map(|b| *b != 0),
+                    })
+                    .collect();
+                Some(Arc::new(array))
+            }
+            PhysicalType::INT32 => get_page_values!(self, values, Int32Array, i32),
+            PhysicalType::INT64 => get_page_values!(self, values, Int64Array, i64),
+            PhysicalType::FLOAT => get_page_values!(self, values, Float32Array, f32),
+            PhysicalType::DOUBLE => get_page_values!(self, values, Float64Array, f64),
+            PhysicalType::BYTE_ARRAY => {
+                let array: StringArray = values
+                    .iter()
+                    .zip(self.page_stats.null_pages.iter())
+                    .map(|(value, null_page)| match null_page {
+                        true => None,
+                        false => std::str::from_utf8(value).ok(),
+                    })
+                    .collect();
+                Some(Arc::new(array))
+            }
+            // 96 bit ints and fixed length byte arrays are not supported
+            _ => None,
+        }
+    }
+}
+
+impl<'a> PruningStatistics for PagePruningStatistics<'a> {
+    fn min_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.page_values(column, &self.page_stats.min_values)
+    }
+
+    fn max_values(&self, column: &Column) -> Option<ArrayRef> {
+        self.page_values(column, &self.page_stats.max_values)
+    }
+
+    fn num_containers(&self) -> usize {
+        self.page_stats.null_pages.len()
+    }
+}
+
+/// The sorted ranges of the rows of a row group of `num_rows` rows that are
+/// not in the sorted, non overlapping ranges `skipped`
+pub(crate) fn unskipped_rows(
+    skipped: &[Range<usize>],
+    num_rows: usize,
+) -> Vec<Range<usize>> {
+    let mut rows = vec![];
+    let mut start = 0;
+    for range in skipped {
+        if range.start > start {
+            rows.push(start..range.start);
+        }
+        start = start.max(range.end);
+    }
+    if start < num_rows {
+        rows.push(start..num_rows);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::{col, lit};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use parquet_format::{BoundaryOrder, PageLocation};
+
+    fn page_locations(first_rows: &[i64]) -> OffsetIndex {
+        OffsetIndex {
+            page_locations: first_rows
+                .iter()
+                .map(|first_row_index| PageLocation {
+                    offset: 0,
+                    compressed_page_size: 0,
+                    first_row_index: *first_row_index,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn prune_int32_pages() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("c", DataType::Int32, true)]));
+        let predicate = PruningPredicate::try_new(&col("c").gt(lit(15)), schema)?;
+
+        let bytes = |v: i32| v.to_le_bytes().to_vec();
+        let page_stats = ColumnIndex {
+            null_pages: vec![false, false, true, false],
+            min_values: vec![bytes(0), bytes(10), vec![], bytes(20)],
+            max_values: vec![bytes(9), bytes(19), vec![], bytes(29)],
+            boundary_order: BoundaryOrder::ASCENDING,
+            null_counts: None,
+        };
+        let pruned = prune_column_pages(
+            &predicate,
+            &Column::from_name("c"),
+            PhysicalType::INT32,
+            &page_stats,
+            &page_locations(&[0, 100, 200, 300]),
+            400,
+        )?;
+        // the null page can't be pruned without null counts
+        assert_eq!(vec![0..100], pruned);
+
+        Ok(())
+    }
+
+    #[test]
+    fn prune_string_pages() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("s", DataType::Utf8, true)]));
+        let predicate = PruningPredicate::try_new(&col("s").eq(lit("m")), schema)?;
+
+        let page_stats = ColumnIndex {
+            null_pages: vec![false, false, false],
+            min_values: vec![b"a".to_vec(), b"k".to_vec(), b"n".to_vec()],
+            max_values: vec![b"j".to_vec(), b"n".to_vec(), b"z".to_vec()],
+            boundary_order: BoundaryOrder::ASCENDING,
+            null_counts: None,
+        };
+        let pruned = prune_column_pages(
+            &predicate,
+            &Column::from_name("s"),
+            PhysicalType::BYTE_ARRAY,
+            &page_stats,
+            &page_locations(&[0, 10, 20]),
+            25,
+        )?;
+        assert_eq!(vec![0..10, 20..25], pruned);
+
+        Ok(())
+    }
+
+    #[test]
+    fn merge() {
+        assert_eq!(
+            vec![0..30, 40..50],
+            merge_ranges(vec![10..20, 40..50, 0..10, 5..30, 45..45])
+        );
+    }
+
+    #[test]
+    fn unskipped() {
+        assert_eq!(vec![0..10], unskipped_rows(&[], 10));
+        assert_eq!(vec![2..5, 8..9], unskipped_rows(&[0..2, 5..8, 9..10], 10));
+        assert_eq!(vec![0..3, 7..10], unskipped_rows(&[3..7], 10));
+        assert!(unskipped_rows(&[0..10], 10).is_empty());
+    }
+}
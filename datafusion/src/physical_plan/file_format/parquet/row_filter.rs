@@ -0,0 +1,716 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Filtering of the rows of a parquet file while it is read: the columns
+//! referenced by the filter are decoded and filtered first, and the other
+//! projected columns are only decoded for the pages with matching rows.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{new_empty_array, ArrayRef, BooleanArray};
+use arrow::compute::{concat, filter, filter_record_batch};
+use arrow::datatypes::{Field, Schema, SchemaRef};
+use arrow::error::Result as ArrowResult;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use log::debug;
+use parquet::arrow::{ArrowReader, ParquetFileArrowReader};
+use parquet::column::page::{Page, PageReader};
+use parquet::errors::{ParquetError, Result as ParquetResult};
+use parquet::file::metadata::{ParquetMetaData, RowGroupMetaData};
+use parquet::file::reader::{FileReader, RowGroupReader};
+use parquet::record::reader::RowIter;
+use parquet::schema::types::Type as SchemaType;
+
+use super::bloom_filter::split_conjunction;
+use super::page_index::unskipped_rows;
+use super::ParquetFileMetrics;
+use crate::error::{DataFusionError, Result};
+use crate::execution::context::ExecutionContextState;
+use crate::logical_plan::{
+    unnormalize_col, DFSchema, Expr, ExpressionVisitor, Recursion,
+};
+use crate::optimizer::utils::expr_to_columns;
+use crate::physical_plan::functions::Volatility;
+use crate::physical_plan::metrics;
+use crate::physical_plan::planner::DefaultPhysicalPlanner;
+use crate::physical_plan::PhysicalExpr;
+
+/// A filter evaluated on the rows of a parquet file while they are read
+#[derive(Debug, Clone)]
+pub(crate) struct RowFilter {
+    /// The filter, evaluated on batches of the columns `columns`
+    predicate: Arc<dyn PhysicalExpr>,
+    /// The sorted indices in the file schema of the columns referenced by
+    /// the filter
+    columns: Vec<usize>,
+}
+
+impl RowFilter {
+    /// Creates a filter from the conjuncts of `expr` that only reference
+    /// columns of `file_schema` and don't call volatile functions, returns
+    /// `None` if there are none
+    pub fn try_new(expr: &Expr, file_schema: &Schema) -> Option<Self> {
+        let mut conjuncts = vec![];
+        split_conjunction(expr, &mut conjuncts);
+
+        let mut columns = HashSet::new();
+        let predicate = conjuncts
+            .into_iter()
+            .filter_map(|conjunct| {
+                let conjunct = unnormalize_col(conjunct.clone());
+                let mut referenced = HashSet::new();
+                expr_to_columns(&conjunct, &mut referenced).ok()?;
+                let supported = !referenced.is_empty()
+                    && referenced
+                        .iter()
+                        .all(|c| file_schema.index_of(&c.name).is_ok())
+                    && !is_volatile(&conjunct);
+                if supported {
+                    columns.extend(referenced);
+                    Some(conjunct)
+                } else {
+                    None
+                }
+            })
+            .reduce(|left, right| left.and(right))?;
+
+        let mut columns = columns
+            .iter()
+            .map(|c| file_schema.index_of(&c.name))
+            .collect::<arrow::error::Result<Vec<_>>>()
+            .ok()?;
+        columns.sort_unstable();
+        columns.dedup();
+
+        let schema = Schema::new(
+            columns
+                .iter()
+                .map(|i| file_schema.field(*i).clone())
+                .collect(),
+        );
+        let predicate = DFSchema::try_from(schema.clone())
+            .and_then(|df_schema| {
+                DefaultPhysicalPlanner::default().create_physical_expr(
+                    &predicate,
+                    &df_schema,
+                    &schema,
+                    &ExecutionContextState::new(),
+                )
+            })
+            .map_err(|e| debug!("Could not create row filter for {:?}: {}", predicate, e))
+            .ok()?;
+
+        Some(Self { predicate, columns })
+    }
+
+    /// The sorted indices in the file schema of the columns referenced by
+    /// the filter
+    pub fn columns(&self) -> &[usize] {
+        &self.columns
+    }
+
+    /// Evaluates the filter on `batch`, made of the columns of the filter, in
+    /// slices of `batch_size` rows. Rows for which the filter is null are not
+    /// selected.
+    fn evaluate(&self, batch: &RecordBatch, batch_size: usize) -> Result<BooleanArray> {
+        let mut selection = Vec::with_capacity(batch.num_rows());
+        let mut offset = 0;
+        while offset < batch.num_rows() {
+            let len = batch_size.min(batch.num_rows() - offset);
+            let result = self
+                .predicate
+                .evaluate(&batch.slice(offset, len))?
+                .into_array(len);
+            let result =
+                result
+                    .as_any()
+                    .downcast_ref::<BooleanArray>()
+                    .ok_or_else(|| {
+                        DataFusionError::Internal(
+                            "Row filter didn't evaluate to a boolean array".to_string(),
+                        )
+                    })?;
+            selection.extend(result.iter().map(|selected| Some(selected == Some(true))));
+            offset += len;
+        }
+        Ok(selection.into_iter().collect())
+    }
+}
+
+/// Returns true if `expr` calls a volatile function, whose result would
+/// differ when the filter is evaluated again after the scan
+fn is_volatile(expr: &Expr) -> bool {
+    struct VolatileVisitor {
+        volatile: bool,
+    }
+
+    impl ExpressionVisitor for VolatileVisitor {
+        fn pre_visit(mut self, expr: &Expr) -> Result<Recursion<Self>> {
+            self.volatile |= match expr {
+                Expr::ScalarFunction { fun, .. } => {
+                    fun.volatility() == Volatility::Volatile
+                }
+                Expr::ScalarUDF { fun, .. } => {
+                    fun.signature.volatility == Volatility::Volatile
+                }
+                _ => false,
+            };
+            Ok(Recursion::Continue(self))
+        }
+    }
+
+    expr.accept(VolatileVisitor { volatile: false })
+        .map(|visitor| visitor.volatile)
+        .unwrap_or(true)
+}
+
+/// Reads the columns `projection` of the row groups `row_groups` of
+/// `reader`, each with the ranges of its rows to skip, and calls `emit` with
+/// the rows matching `filter` until it returns `false`. Returns `false` if
+/// `emit` stopped the read.
+///
+/// The columns of the filter are read and filtered first. The other columns
+/// of a row group are then only read if some of its rows match, one column at
+/// a time, skipping the data pages without any matching row before they are
+/// decoded. The data pages with only skipped rows are never decoded.
+pub(crate) fn read_filtered_row_groups(
+    reader: Arc<dyn FileReader>,
+    row_groups: &[(usize, Vec<Range<usize>>)],
+    projection: &[usize],
+    filter: &RowFilter,
+    batch_size: usize,
+    metrics: &ParquetFileMetrics,
+    mut emit: impl FnMut(RecordBatch) -> Result<bool>,
+) -> Result<bool> {
+    let other_columns = projection
+        .iter()
+        .filter(|i| !filter.columns.contains(i))
+        .copied()
+        .collect::<Vec<_>>();
+
+    for (row_group, skipped) in row_groups {
+        let num_rows = reader.metadata().row_group(*row_group).num_rows() as usize;
+        let rows = unskipped_rows(skipped, num_rows);
+
+        // decode and filter the columns of the filter
+        let (fields, columns): (Vec<_>, Vec<_>) = filter
+            .columns
+            .iter()
+            .map(|column| {
+                read_selected_rows(
+                    reader.clone(),
+                    *row_group,
+                    *column,
+                    &rows,
+                    batch_size,
+                    &metrics.pages_skipped_page_index,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+        let selection = filter.evaluate(&batch, batch_size)?;
+        let selected = selected_ranges(&rows, &selection);
+        let filtered = filter_record_batch(&batch, &selection)?;
+
+        let num_selected = filtered.num_rows();
+        metrics
+            .pushdown_rows_filtered
+            .add(batch.num_rows() - num_selected);
+        if num_selected == 0 {
+            metrics.row_groups_pruned_row_filter.add(1);
+            continue;
+        }
+
+        // decode the selected rows of the other columns
+        let others = other_columns
+            .iter()
+            .map(|column| {
+                read_selected_rows(
+                    reader.clone(),
+                    *row_group,
+                    *column,
+                    &selected,
+                    batch_size,
+                    &metrics.pages_pruned_row_filter,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let (fields, columns): (Vec<_>, Vec<_>) = projection
+            .iter()
+            .map(|i| match filter.columns.iter().position(|c| c == i) {
+                Some(index) => (
+                    filtered.schema().field(index).clone(),
+                    filtered.column(index).clone(),
+                ),
+                None => {
+                    let index = other_columns
+                        .iter()
+                        .position(|c| c == i)
+                        .expect("projected column is read");
+                    others[index].clone()
+                }
+            })
+            .unzip();
+        if !emit_batches(
+            Arc::new(Schema::new(fields)),
+            &columns,
+            batch_size,
+            &mut emit,
+        )? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Reads the columns `projection` of the row group `row_group` of `reader`,
+/// without its rows `skipped`, and calls `emit` with the rows read until it
+/// returns `false`. Returns `false` if `emit` stopped the read.
+///
+/// The columns are read one at a time, without decoding the data pages with
+/// only skipped rows.
+pub(crate) fn read_unskipped_rows(
+    reader: Arc<dyn FileReader>,
+    row_group: usize,
+    skipped: &[Range<usize>],
+    projection: &[usize],
+    batch_size: usize,
+    metrics: &ParquetFileMetrics,
+    emit: &mut impl FnMut(RecordBatch) -> Result<bool>,
+) -> Result<bool> {
+    let num_rows = reader.metadata().row_group(row_group).num_rows() as usize;
+    let rows = unskipped_rows(skipped, num_rows);
+    let (fields, columns): (Vec<_>, Vec<_>) = projection
+        .iter()
+        .map(|column| {
+            read_selected_rows(
+                reader.clone(),
+                row_group,
+                *column,
+                &rows,
+                batch_size,
+                &metrics.pages_skipped_page_index,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    emit_batches(Arc::new(Schema::new(fields)), &columns, batch_size, emit)
+}
+
+/// Calls `emit` with the rows of `columns` in batches of `batch_size` rows
+/// until it returns `false`. Returns `false` if `emit` stopped.
+fn emit_batches(
+    schema: SchemaRef,
+    columns: &[ArrayRef],
+    batch_size: usize,
+    emit: &mut impl FnMut(RecordBatch) -> Result<bool>,
+) -> Result<bool> {
+    let num_rows = columns.first().map(|c| c.len()).unwrap_or(0);
+    let mut offset = 0;
+    while offset < num_rows {
+        let len = batch_size.min(num_rows - offset);
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            columns.iter().map(|c| c.slice(offset, len)).collect(),
+        )?;
+        offset += len;
+        if !emit(batch)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// The sorted ranges of the rows selected by `selection`, whose values are
+/// the ones of the sorted ranges of rows `rows`
+fn selected_ranges(rows: &[Range<usize>], selection: &BooleanArray) -> Vec<Range<usize>> {
+    let mut selected: Vec<Range<usize>> = vec![];
+    let rows = rows.iter().flat_map(|r| r.clone());
+    for (row, is_selected) in rows.zip(selection.iter()) {
+        if is_selected != Some(true) {
+            continue;
+        }
+        match selected.last_mut() {
+            Some(last) if last.end == row => last.end = row + 1,
+            _ => selected.push(row..row + 1),
+        }
+    }
+    selected
+}
+
+/// `arrays` concatenated in a single array
+fn concat_arrays(arrays: &[&ArrayRef]) -> Result<ArrayRef> {
+    let arrays = arrays.iter().map(|a| a.as_ref()).collect::<Vec<_>>();
+    Ok(concat(&arrays)?)
+}
+
+/// Reads the rows `selected`, sorted ranges of rows, of the column `column`
+/// of the row group `row_group` of `reader`.
+///
+/// The data pages of a column without repetition, where each value is a row,
+/// are skipped when they have none of the selected rows, and counted in
+/// `pages_skipped`. The pages of the other columns are all decoded.
+fn read_selected_rows(
+    reader: Arc<dyn FileReader>,
+    row_group: usize,
+    column: usize,
+    selected: &[Range<usize>],
+    batch_size: usize,
+    pages_skipped: &metrics::Count,
+) -> Result<(Field, ArrayRef)> {
+    let row_group_metadata = reader.metadata().row_group(row_group);
+    let num_rows = row_group_metadata.num_rows() as usize;
+    let column_descr = row_group_metadata.column(column).column_descr();
+
+    let read_rows = Arc::new(Mutex::new(vec![]));
+    let page_filter = if column_descr.max_rep_level() == 0 {
+        Some(PageFilter {
+            selected: Arc::new(selected.to_vec()),
+            read_rows: read_rows.clone(),
+            pages_skipped: pages_skipped.clone(),
+        })
+    } else {
+        read_rows.lock().unwrap().push(0..num_rows);
+        None
+    };
+    let row_group_reader: Arc<dyn FileReader> =
+        Arc::new(RowGroupFileReader::new(reader, row_group, page_filter));
+
+    let mut arrow_reader = ParquetFileArrowReader::new(row_group_reader);
+    let batch_reader =
+        arrow_reader.get_record_reader_by_columns(vec![column], batch_size)?;
+    let field = batch_reader.schema().field(0).clone();
+    let batches = batch_reader.collect::<ArrowResult<Vec<_>>>()?;
+    let array = if batches.is_empty() {
+        new_empty_array(field.data_type())
+    } else {
+        concat_arrays(&batches.iter().map(|b| b.column(0)).collect::<Vec<_>>())?
+    };
+
+    // the rows of the pages read, whose values are the ones of `array`
+    let read_rows = read_rows.lock().unwrap();
+    let selection = read_rows
+        .iter()
+        .flat_map(|rows| rows.clone())
+        .map(|row| Some(selected.iter().any(|r| r.contains(&row))))
+        .collect::<BooleanArray>();
+    if selection.len() != array.len() {
+        return Err(DataFusionError::Internal(format!(
+            "Read {} values of the column {} from pages with {} rows",
+            array.len(),
+            field.name(),
+            selection.len()
+        )));
+    }
+    Ok((field, filter(array.as_ref(), &selection)?))
+}
+
+/// Skips the data pages of a column that have none of the rows `selected`
+#[derive(Debug, Clone)]
+struct PageFilter {
+    /// The sorted ranges of the rows to read
+    selected: Arc<Vec<Range<usize>>>,
+    /// The sorted ranges of the rows of the data pages read
+    read_rows: Arc<Mutex<Vec<Range<usize>>>>,
+    /// The number of data pages skipped
+    pages_skipped: metrics::Count,
+}
+
+/// A [`FileReader`] exposing a single row group of a file, optionally
+/// skipping the data pages of its columns with a [`PageFilter`]
+struct RowGroupFileReader {
+    reader: Arc<dyn FileReader>,
+    metadata: ParquetMetaData,
+    row_group: usize,
+    page_filter: Option<PageFilter>,
+}
+
+impl RowGroupFileReader {
+    fn new(
+        reader: Arc<dyn FileReader>,
+        row_group: usize,
+        page_filter: Option<PageFilter>,
+    ) -> Self {
+        let metadata = ParquetMetaData::new(
+            reader.metadata().file_metadata().clone(),
+            vec![reader.metadata().row_group(row_group).clone()],
+        );
+        Self {
+            reader,
+            metadata,
+            row_group,
+            page_filter,
+        }
+    }
+}
+
+impl FileReader for RowGroupFileReader {
+    fn metadata(&self) -> &ParquetMetaData {
+        &self.metadata
+    }
+
+    fn num_row_groups(&self) -> usize {
+        1
+    }
+
+    fn get_row_group(&self, i: usize) -> ParquetResult<Box<dyn RowGroupReader + '_>> {
+        match i {
+            0 => {
+                let reader = self.reader.get_row_group(self.row_group)?;
+                match &self.page_filter {
+                    Some(page_filter) => Ok(Box::new(FilteredRowGroupReader {
+                        reader,
+                        page_filter: page_filter.clone(),
+                    })),
+                    None => Ok(reader),
+                }
+            }
+            _ => Err(ParquetError::IndexOutOfBound(i, 1)),
+        }
+    }
+
+    fn get_row_iter(&self, _projection: Option<SchemaType>) -> ParquetResult<RowIter> {
+        Err(ParquetError::General(
+            "Row iteration is not supported by RowGroupFileReader".to_string(),
+        ))
+    }
+}
+
+/// A [`RowGroupReader`] whose column page readers skip pages with a
+/// [`PageFilter`]
+struct FilteredRowGroupReader<'a> {
+    reader: Box<dyn RowGroupReader + 'a>,
+    page_filter: PageFilter,
+}
+
+impl<'a> RowGroupReader for FilteredRowGroupReader<'a> {
+    fn metadata(&self) -> &RowGroupMetaData {
+        self.reader.metadata()
+    }
+
+    fn num_columns(&self) -> usize {
+        self.reader.num_columns()
+    }
+
+    fn get_column_page_reader(&self, i: usize) -> ParquetResult<Box<dyn PageReader>> {
+        Ok(Box::new(FilteredPageReader {
+            reader: self.reader.get_column_page_reader(i)?,
+            page_filter: self.page_filter.clone(),
+            row: 0,
+        }))
+    }
+
+    fn get_row_iter(&self, _projection: Option<SchemaType>) -> ParquetResult<RowIter> {
+        Err(ParquetError::General(
+            "Row iteration is not supported by FilteredRowGroupReader".to_string(),
+        ))
+    }
+}
+
+/// A [`PageReader`] of a column without repetition, where each value of a
+/// data page is a row, skipping the data pages with a [`PageFilter`]
+struct FilteredPageReader {
+    reader: Box<dyn PageReader>,
+    page_filter: PageFilter,
+    /// The first row of the next data page
+    row: usize,
+}
+
+impl Iterator for FilteredPageReader {
+    type Item = ParquetResult<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.get_next_page().transpose()
+    }
+}
+
+impl PageReader for FilteredPageReader {
+    fn get_next_page(&mut self) -> ParquetResult<Option<Page>> {
+        while let Some(page) = self.reader.get_next_page()? {
+            let num_rows = match &page {
+                Page::DataPage { num_values, .. } => *num_values as usize,
+                Page::DataPageV2 { num_rows, .. } => *num_rows as usize,
+                Page::DictionaryPage { .. } => return Ok(Some(page)),
+            };
+            let rows = self.row..self.row + num_rows;
+            self.row = rows.end;
+            let is_selected = self
+                .page_filter
+                .selected
+                .iter()
+                .any(|r| r.start < rows.end && rows.start < r.end);
+            if is_selected {
+                self.page_filter.read_rows.lock().unwrap().push(rows);
+                return Ok(Some(page));
+            }
+            self.page_filter.pages_skipped.add(1);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::logical_plan::{col, lit, Column};
+    use crate::physical_plan::metrics::ExecutionPlanMetricsSet;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::DataType;
+    use parquet::arrow::ArrowWriter;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::serialized_reader::SerializedFileReader;
+
+    #[test]
+    fn row_filter_columns() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+            Field::new("c", DataType::Int32, true),
+        ]);
+
+        // conjuncts referencing unknown (e.g. partition) columns are ignored
+        let expr = col("c")
+            .gt(lit(1))
+            .and(col("part").eq(lit("x")))
+            .and(Expr::Column(Column::from_qualified_name("t.a")).lt(lit(5)));
+        let filter = RowFilter::try_new(&expr, &schema).unwrap();
+        assert_eq!(&[0, 2], filter.columns());
+
+        assert!(RowFilter::try_new(&col("part").eq(lit("x")), &schema).is_none());
+        let volatile = col("a").lt(Expr::ScalarFunction {
+            fun: crate::physical_plan::functions::BuiltinScalarFunction::Random,
+            args: vec![],
+        });
+        assert!(RowFilter::try_new(&volatile, &schema).is_none());
+    }
+
+    #[test]
+    fn row_filter_evaluate() -> Result<()> {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Int32, true),
+        ]);
+        let filter = RowFilter::try_new(&col("b").gt(lit(2)), &schema).unwrap();
+        assert_eq!(&[1], filter.columns());
+
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("b", DataType::Int32, true)])),
+            vec![Arc::new(Int32Array::from(vec![
+                Some(1),
+                Some(3),
+                None,
+                Some(4),
+                Some(5),
+            ]))],
+        )?;
+        let selection = filter.evaluate(&batch, 2)?;
+        assert_eq!(
+            BooleanArray::from(vec![false, true, false, true, true]),
+            selection
+        );
+        assert_eq!(
+            vec![11..12, 20..22],
+            selected_ranges(&[10..13, 20..22], &selection)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_filtered_pages() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from((0..100).collect::<Vec<_>>())),
+                Arc::new(Int32Array::from(
+                    (0..100).map(|i| Some(i * 10)).collect::<Vec<_>>(),
+                )),
+            ],
+        )?;
+        // pages of 10 rows
+        let props = WriterProperties::builder()
+            .set_dictionary_enabled(false)
+            .set_data_pagesize_limit(1)
+            .set_write_batch_size(10)
+            .build();
+        let file = tempfile::tempfile()?;
+        let mut writer =
+            ArrowWriter::try_new(file.try_clone()?, schema.clone(), Some(props))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        let reader: Arc<dyn FileReader> = Arc::new(SerializedFileReader::new(file)?);
+
+        let expr = col("a").gt_eq(lit(15)).and(col("a").lt(lit(25)));
+        let filter = RowFilter::try_new(&expr, &schema).unwrap();
+        let metrics = ParquetFileMetrics::new(0, "file", &ExecutionPlanMetricsSet::new());
+        let mut batches = vec![];
+        // the rows 20 and 21, and the page of the rows 30..40, are skipped by
+        // the page index
+        let completed = read_filtered_row_groups(
+            reader,
+            &[(0, vec![20..22, 30..40])],
+            &[0, 1],
+            &filter,
+            4,
+            &metrics,
+            |batch| {
+                batches.push(batch);
+                Ok(true)
+            },
+        )?;
+        assert!(completed);
+
+        let expected = vec![
+            "+----+-----+",
+            "| a  | b   |",
+            "+----+-----+",
+            "| 15 | 150 |",
+            "| 16 | 160 |",
+            "| 17 | 170 |",
+            "| 18 | 180 |",
+            "| 19 | 190 |",
+            "| 22 | 220 |",
+            "| 23 | 230 |",
+            "| 24 | 240 |",
+            "+----+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+        assert_eq!(
+            vec![4, 4],
+            batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>()
+        );
+        assert_eq!(80, metrics.pushdown_rows_filtered.value());
+        // only the pages of b with the rows 10..20 and 20..30 are decoded
+        assert_eq!(8, metrics.pages_pruned_row_filter.value());
+        // the page of a with the rows 30..40 is not decoded
+        assert_eq!(1, metrics.pages_skipped_page_index.value());
+
+        Ok(())
+    }
+}
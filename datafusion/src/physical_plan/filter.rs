@@ -26,6 +26,7 @@ use std::task::{Context, Poll};
 use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
 use crate::error::{DataFusionError, Result};
 use crate::physical_plan::{
+    expressions::PhysicalSortExpr,
     metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet},
     DisplayFormatType, ExecutionPlan, Partitioning, PhysicalExpr,
 };
@@ -103,6 +104,11 @@ impl ExecutionPlan for FilterExec {
         self.input.output_partitioning()
     }
 
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        // filtering rows does not change the order of the remaining ones
+        self.input.output_ordering()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -250,6 +256,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -24,11 +24,14 @@ use std::vec;
 
 use ahash::RandomState;
 use futures::{
+    channel::mpsc,
     stream::{Stream, StreamExt},
-    Future,
+    Future, SinkExt,
 };
 
 use crate::error::{DataFusionError, Result};
+use crate::execution::memory_manager::{MemoryManager, MemoryReservation};
+use crate::logical_plan::{GROUPING_ID_COLUMN, MAX_GROUPING_EXPRESSIONS};
 use crate::physical_plan::hash_utils::create_hashes;
 use crate::physical_plan::{
     Accumulator, AggregateExpr, DisplayFormatType, Distribution, ExecutionPlan,
@@ -36,24 +39,31 @@ use crate::physical_plan::{
 };
 use crate::scalar::ScalarValue;
 
+use arrow::compute::kernels::partition::lexicographical_partition_ranges;
+use arrow::compute::{lexsort_to_indices, SortColumn, SortOptions};
 use arrow::{array::ArrayRef, compute, compute::cast};
 use arrow::{
-    array::{Array, UInt32Builder},
+    array::{new_null_array, Array, UInt32Array, UInt32Builder},
     error::{ArrowError, Result as ArrowResult},
 };
 use arrow::{
-    datatypes::{Field, Schema, SchemaRef},
+    datatypes::{DataType, Field, Schema, SchemaRef},
     record_batch::RecordBatch,
 };
 use hashbrown::raw::RawTable;
 use pin_project_lite::pin_project;
+use tempfile::NamedTempFile;
 
 use async_trait::async_trait;
 
-use super::common::AbortOnDropSingle;
+use super::common::{AbortOnDropMany, AbortOnDropSingle};
+use super::expressions::PhysicalSortExpr;
 use super::metrics::{
-    self, BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet, RecordOutput,
+    self, BaselineMetrics, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+    RecordOutput,
 };
+use super::sort_preserving_merge::SortPreservingMergeStream;
+use super::spill::{read_spill, spill_batches};
 use super::Statistics;
 use super::{expressions::Column, RecordBatchStream, SendableRecordBatchStream};
 
@@ -74,12 +84,22 @@ pub enum AggregateMode {
 }
 
 /// Hash aggregate execution plan
+///
+/// When grouping, the states of the groups are reserved from the memory
+/// manager (see [`with_memory_manager`](Self::with_memory_manager)). When
+/// they can not be reserved or the memory manager asks for memory back, the
+/// states are sorted on the group values and spilled to disk. Once the input
+/// is exhausted, the spilled states are merged back in a streaming fashion,
+/// aggregating the states of each group with [`Accumulator::merge`].
 #[derive(Debug)]
 pub struct HashAggregateExec {
     /// Aggregation mode (full, partial)
     mode: AggregateMode,
     /// Grouping expressions
     group_expr: Vec<(Arc<dyn PhysicalExpr>, String)>,
+    /// Whether each grouping set groups by each of the grouping
+    /// expressions, empty to group by all of them
+    grouping_sets: Vec<Vec<bool>>,
     /// Aggregate expressions
     aggr_expr: Vec<Arc<dyn AggregateExpr>>,
     /// Input plan, could be a partial aggregate or the input to the aggregate
@@ -92,6 +112,11 @@ pub struct HashAggregateExec {
     input_schema: SchemaRef,
     /// Execution Metrics
     metrics: ExecutionPlanMetricsSet,
+    /// Memory pool the group states are reserved from
+    memory_manager: Arc<MemoryManager>,
+    /// The target size of the batches yielded when merging spilled
+    /// group states
+    target_batch_size: usize,
 }
 
 fn create_schema(
@@ -143,14 +168,75 @@ impl HashAggregateExec {
         Ok(HashAggregateExec {
             mode,
             group_expr,
+            grouping_sets: vec![],
             aggr_expr,
             input,
             schema,
             input_schema,
             metrics: ExecutionPlanMetricsSet::new(),
+            memory_manager: MemoryManager::unbounded(),
+            target_batch_size: 8192,
         })
     }
 
+    /// Group by each of `grouping_sets`, which flag for every grouping
+    /// expression whether the set groups by it, instead of by all of the
+    /// grouping expressions.
+    ///
+    /// Every input row is aggregated once per grouping set, with the values
+    /// of the expressions not in the set being null. The id of the set, see
+    /// [`GROUPING_ID_COLUMN`], becomes an additional group column following the
+    /// grouping expressions, which the final aggregation groups by like any
+    /// other column. Only supported in [`AggregateMode::Partial`].
+    pub fn with_grouping_sets(mut self, grouping_sets: Vec<Vec<bool>>) -> Result<Self> {
+        if grouping_sets.is_empty() {
+            return Ok(self);
+        }
+        if self.mode != AggregateMode::Partial {
+            return Err(DataFusionError::Internal(format!(
+                "Grouping sets are only supported by a partial aggregation, not {:?}",
+                self.mode
+            )));
+        }
+        let num_group_expr = self.group_expr.len();
+        if num_group_expr > MAX_GROUPING_EXPRESSIONS
+            || grouping_sets.iter().any(|set| set.len() != num_group_expr)
+        {
+            return Err(DataFusionError::Internal(format!(
+                "Invalid grouping sets {:?} for {} grouping expressions",
+                grouping_sets, num_group_expr
+            )));
+        }
+
+        // the grouping expressions are null when not in the grouping set
+        let fields = self.schema.fields();
+        let mut group_fields = fields[..num_group_expr]
+            .iter()
+            .map(|field| Field::new(field.name(), field.data_type().clone(), true))
+            .collect::<Vec<_>>();
+        group_fields.push(Field::new(GROUPING_ID_COLUMN, DataType::UInt32, false));
+        group_fields.extend(fields[num_group_expr..].iter().cloned());
+        self.schema = Arc::new(Schema::new(group_fields));
+        self.grouping_sets = grouping_sets;
+        Ok(self)
+    }
+
+    /// Reserve the memory of the group states from `memory_manager`,
+    /// spilling them to disk when the reservation can not grow
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = memory_manager;
+        self
+    }
+
+    /// Customize the target size of the batches yielded when merging
+    /// spilled group states
+    pub fn with_target_batch_size(mut self, target_batch_size: usize) -> Self {
+        // batch size must be greater than zero
+        assert!(target_batch_size > 0);
+        self.target_batch_size = target_batch_size;
+        self
+    }
+
     /// Aggregation mode (full, partial)
     pub fn mode(&self) -> &AggregateMode {
         &self.mode
@@ -161,6 +247,12 @@ impl HashAggregateExec {
         &self.group_expr
     }
 
+    /// Whether each grouping set groups by each of the grouping expressions,
+    /// empty if all of them are grouped by
+    pub fn grouping_sets(&self) -> &[Vec<bool>] {
+        &self.grouping_sets
+    }
+
     /// Aggregate expressions
     pub fn aggr_expr(&self) -> &[Arc<dyn AggregateExpr>] {
         &self.aggr_expr
@@ -175,6 +267,26 @@ impl HashAggregateExec {
     pub fn input_schema(&self) -> SchemaRef {
         self.input_schema.clone()
     }
+
+    /// Number of group columns, including the grouping id if grouping by
+    /// grouping sets
+    fn num_group_columns(&self) -> usize {
+        if self.grouping_sets.is_empty() {
+            self.group_expr.len()
+        } else {
+            self.group_expr.len() + 1
+        }
+    }
+
+    /// Schema of the group states spilled to disk: the group values
+    /// followed by the states of the accumulators
+    fn group_states_schema(&self) -> Result<SchemaRef> {
+        let mut fields = self.schema.fields()[..self.num_group_columns()].to_vec();
+        for expr in &self.aggr_expr {
+            fields.extend(expr.state_fields()?);
+        }
+        Ok(Arc::new(Schema::new(fields)))
+    }
 }
 
 #[async_trait]
@@ -213,7 +325,7 @@ impl ExecutionPlan for HashAggregateExec {
 
         let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
 
-        if self.group_expr.is_empty() {
+        if self.num_group_columns() == 0 {
             Ok(Box::pin(HashAggregateStream::new(
                 self.mode,
                 self.schema.clone(),
@@ -222,13 +334,25 @@ impl ExecutionPlan for HashAggregateExec {
                 baseline_metrics,
             )))
         } else {
+            let reservation = self
+                .memory_manager
+                .register_consumer(format!("HashAggregateExec[{}]", partition), true);
+            let spill = SpillConfig {
+                schema: self.group_states_schema()?,
+                target_batch_size: self.target_batch_size,
+                spill_count: MetricBuilder::new(&self.metrics).spill_count(partition),
+                spilled_bytes: MetricBuilder::new(&self.metrics).spilled_bytes(partition),
+            };
             Ok(Box::pin(GroupedHashAggregateStream::new(
                 self.mode,
                 self.schema.clone(),
                 group_expr,
+                self.grouping_sets.clone(),
                 self.aggr_expr.clone(),
                 input,
                 baseline_metrics,
+                reservation,
+                spill,
             )))
         }
     }
@@ -238,13 +362,18 @@ impl ExecutionPlan for HashAggregateExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(HashAggregateExec::try_new(
-                self.mode,
-                self.group_expr.clone(),
-                self.aggr_expr.clone(),
-                children[0].clone(),
-                self.input_schema.clone(),
-            )?)),
+            1 => Ok(Arc::new(
+                HashAggregateExec::try_new(
+                    self.mode,
+                    self.group_expr.clone(),
+                    self.aggr_expr.clone(),
+                    children[0].clone(),
+                    self.input_schema.clone(),
+                )?
+                .with_grouping_sets(self.grouping_sets.clone())?
+                .with_memory_manager(self.memory_manager.clone())
+                .with_target_batch_size(self.target_batch_size),
+            )),
             _ => Err(DataFusionError::Internal(
                 "HashAggregateExec wrong number of children".to_string(),
             )),
@@ -277,6 +406,24 @@ impl ExecutionPlan for HashAggregateExec {
                     .collect();
                 write!(f, ", gby=[{}]", g.join(", "))?;
 
+                if !self.grouping_sets.is_empty() {
+                    let sets: Vec<String> = self
+                        .grouping_sets
+                        .iter()
+                        .map(|set| {
+                            let names: Vec<&str> = self
+                                .group_expr
+                                .iter()
+                                .zip(set)
+                                .filter(|(_, grouped)| **grouped)
+                                .map(|((_, alias), _)| alias.as_str())
+                                .collect();
+                            format!("({})", names.join(", "))
+                        })
+                        .collect();
+                    write!(f, ", grouping_sets=[{}]", sets.join(", "))?;
+                }
+
                 let a: Vec<String> = self
                     .aggr_expr
                     .iter()
@@ -334,33 +481,24 @@ Example: average
 * Once all N record batches arrive, `merge` is performed, which builds a RecordBatch with N rows and 2 columns.
 * Finally, `get_value` returns an array with one entry computed from the state
 */
-pin_project! {
-    struct GroupedHashAggregateStream {
-        schema: SchemaRef,
-        #[pin]
-        output: futures::channel::oneshot::Receiver<ArrowResult<RecordBatch>>,
-        finished: bool,
-        drop_helper: AbortOnDropSingle<()>,
-    }
+struct GroupedHashAggregateStream {
+    schema: SchemaRef,
+    output: mpsc::Receiver<ArrowResult<RecordBatch>>,
+    #[allow(dead_code)]
+    drop_helper: AbortOnDropSingle<()>,
 }
 
+/// Aggregates the `num_rows` rows of the evaluated group values and
+/// aggregation inputs into `accumulators`
 fn group_aggregate_batch(
     mode: &AggregateMode,
     random_state: &RandomState,
-    group_expr: &[Arc<dyn PhysicalExpr>],
     aggr_expr: &[Arc<dyn AggregateExpr>],
-    batch: RecordBatch,
+    group_values: &[ArrayRef],
+    aggr_input_values: &[Vec<ArrayRef>],
+    num_rows: usize,
     mut accumulators: Accumulators,
-    aggregate_expressions: &[Vec<Arc<dyn PhysicalExpr>>],
 ) -> Result<Accumulators> {
-    // evaluate the grouping expressions
-    let group_values = evaluate(group_expr, &batch)?;
-
-    // evaluate the aggregation expressions.
-    // We could evaluate them after the `take`, but since we need to evaluate all
-    // of them anyways, it is more performant to do it while they are together.
-    let aggr_input_values = evaluate_many(aggregate_expressions, &batch)?;
-
     // 1.1 construct the key from the group values
     // 1.2 construct the mapping key if it does not exist
     // 1.3 add the row' index to `indices`
@@ -369,8 +507,8 @@ fn group_aggregate_batch(
     let mut groups_with_rows = vec![];
 
     // 1.1 Calculate the group keys for the group values
-    let mut batch_hashes = vec![0; batch.num_rows()];
-    create_hashes(&group_values, random_state, &mut batch_hashes)?;
+    let mut batch_hashes = vec![0; num_rows];
+    create_hashes(group_values, random_state, &mut batch_hashes)?;
 
     for (row, hash) in batch_hashes.into_iter().enumerate() {
         let Accumulators { map, group_states } = &mut accumulators;
@@ -498,84 +636,459 @@ fn group_aggregate_batch(
     Ok(accumulators)
 }
 
+/// Returns the group values of every grouping set, where the values of the
+/// grouping expressions not in the set are null and the grouping id of the
+/// set is appended. Without grouping sets, these are just `group_values`.
+fn grouping_set_values(
+    group_values: Vec<ArrayRef>,
+    grouping_sets: &[Vec<bool>],
+    num_rows: usize,
+) -> Vec<Vec<ArrayRef>> {
+    if grouping_sets.is_empty() {
+        return vec![group_values];
+    }
+    grouping_sets
+        .iter()
+        .map(|set| {
+            let mut values = group_values
+                .iter()
+                .zip(set)
+                .map(|(values, grouped)| {
+                    if *grouped {
+                        values.clone()
+                    } else {
+                        new_null_array(values.data_type(), num_rows)
+                    }
+                })
+                .collect::<Vec<_>>();
+            let grouping_id = UInt32Array::from(vec![grouping_id(set); num_rows]);
+            values.push(Arc::new(grouping_id));
+            values
+        })
+        .collect()
+}
+
+/// Returns the id of a grouping set, in which bit `n - 1 - i` is set if the
+/// set does not group by the `i`th of the `n` grouping expressions
+fn grouping_id(grouping_set: &[bool]) -> u32 {
+    grouping_set
+        .iter()
+        .fold(0, |id, grouped| (id << 1) | (!grouped as u32))
+}
+
+/// Adds the groups of the grouping sets which do not group by any
+/// expression, unless they exist already, as these produce a row even if
+/// the input is empty
+fn add_empty_grouping_sets(
+    accumulators: &mut Accumulators,
+    group_fields: &[Field],
+    grouping_sets: &[Vec<bool>],
+    aggr_expr: &[Arc<dyn AggregateExpr>],
+) -> Result<()> {
+    for grouping_set in grouping_sets.iter().filter(|set| !set.contains(&true)) {
+        let mut group_by_values = group_fields
+            .iter()
+            .map(|field| ScalarValue::try_from(field.data_type()))
+            .collect::<Result<Vec<_>>>()?;
+        group_by_values.push(ScalarValue::UInt32(Some(grouping_id(grouping_set))));
+
+        let exists = accumulators
+            .group_states
+            .iter()
+            .any(|group_state| *group_state.group_by_values == *group_by_values);
+        if !exists {
+            // no more rows are aggregated, so the map is not updated
+            accumulators.group_states.push(GroupState {
+                group_by_values: group_by_values.into_boxed_slice(),
+                accumulator_set: create_accumulators(aggr_expr)?,
+                indices: vec![],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Settings and metrics for spilling the group states of a
+/// [`HashAggregateExec`] partition to disk
+struct SpillConfig {
+    /// Schema of the spilled group states, see
+    /// [`HashAggregateExec::group_states_schema`]
+    schema: SchemaRef,
+    /// The target size of the batches yielded when merging spilled
+    /// group states
+    target_batch_size: usize,
+    /// Number of times the group states were spilled to disk
+    spill_count: metrics::Count,
+    /// Number of bytes spilled to disk
+    spilled_bytes: metrics::Count,
+}
+
+/// Aggregates all of `input` and sends the aggregated groups to `output`.
+///
+/// The group states are spilled to disk whenever they can not be reserved
+/// from `reservation` or the memory manager asks for memory back, in which
+/// case the spilled states are merged once the input is exhausted.
+#[allow(clippy::too_many_arguments)]
 async fn compute_grouped_hash_aggregate(
     mode: AggregateMode,
     schema: SchemaRef,
     group_expr: Vec<Arc<dyn PhysicalExpr>>,
+    grouping_sets: Vec<Vec<bool>>,
     aggr_expr: Vec<Arc<dyn AggregateExpr>>,
     mut input: SendableRecordBatchStream,
-    elapsed_compute: metrics::Time,
-) -> ArrowResult<RecordBatch> {
+    baseline_metrics: &BaselineMetrics,
+    mut reservation: MemoryReservation,
+    spill: SpillConfig,
+    output: &mut mpsc::Sender<ArrowResult<RecordBatch>>,
+) -> Result<()> {
+    let elapsed_compute = baseline_metrics.elapsed_compute();
     let timer = elapsed_compute.timer();
+    // the grouping id of the grouping sets is an additional group column
+    let num_group_columns = if grouping_sets.is_empty() {
+        group_expr.len()
+    } else {
+        group_expr.len() + 1
+    };
     // The expressions to evaluate the batch, one vec of expressions per aggregation.
     // Assume create_schema() always put group columns in front of aggr columns, we set
-    // col_idx_base to group expression count.
+    // col_idx_base to group column count.
     let aggregate_expressions =
-        aggregate_expressions(&aggr_expr, &mode, group_expr.len())
-            .map_err(DataFusionError::into_arrow_external_error)?;
+        aggregate_expressions(&aggr_expr, &mode, num_group_columns)?;
 
     let random_state = RandomState::new();
 
     // iterate over all input batches and update the accumulators
     let mut accumulators = Accumulators::default();
+    let mut group_states_size = 0;
+    let mut spills = vec![];
     timer.done();
     while let Some(batch) = input.next().await {
         let batch = batch?;
         let timer = elapsed_compute.timer();
-        accumulators = group_aggregate_batch(
-            &mode,
-            &random_state,
-            &group_expr,
+        let num_groups = accumulators.group_states.len();
+
+        // evaluate the grouping expressions
+        let group_values = evaluate(&group_expr, &batch)?;
+        // evaluate the aggregation expressions.
+        // We could evaluate them after the `take`, but since we need to evaluate all
+        // of them anyways, it is more performant to do it while they are together.
+        let aggr_input_values = evaluate_many(&aggregate_expressions, &batch)?;
+
+        // every row is aggregated once per grouping set
+        for group_values in
+            grouping_set_values(group_values, &grouping_sets, batch.num_rows())
+        {
+            accumulators = group_aggregate_batch(
+                &mode,
+                &random_state,
+                &aggr_expr,
+                &group_values,
+                &aggr_input_values,
+                batch.num_rows(),
+                accumulators,
+            )?;
+        }
+
+        // account for the groups created by this batch
+        group_states_size += accumulators.group_states[num_groups..]
+            .iter()
+            .map(group_state_size)
+            .sum::<usize>();
+        let map_size = accumulators.map.buckets() * std::mem::size_of::<(u64, usize)>();
+        let reserved = reservation.try_resize(group_states_size + map_size);
+
+        // give the memory back by spilling the groups to disk
+        if (reserved.is_err() || reservation.take_spill_request())
+            && !accumulators.group_states.is_empty()
+        {
+            spills.push(spill_group_states(
+                &accumulators,
+                num_group_columns,
+                &spill,
+            )?);
+            accumulators = Accumulators::default();
+            group_states_size = 0;
+            reservation.free();
+        }
+        timer.done();
+    }
+
+    if !grouping_sets.is_empty() {
+        add_empty_grouping_sets(
+            &mut accumulators,
+            &schema.fields()[..group_expr.len()],
+            &grouping_sets,
             &aggr_expr,
-            batch,
-            accumulators,
-            &aggregate_expressions,
-        )
-        .map_err(DataFusionError::into_arrow_external_error)?;
+        )?;
+    }
+
+    if spills.is_empty() {
+        let timer = elapsed_compute.timer();
+        let batch =
+            create_batch_from_map(&mode, &accumulators, num_group_columns, &schema)
+                .record_output(baseline_metrics);
         timer.done();
+        // failing here is OK, the receiver is gone and does not care about the result
+        output.send(batch).await.ok();
+        return Ok(());
     }
 
-    let timer = elapsed_compute.timer();
-    let batch = create_batch_from_map(&mode, &accumulators, group_expr.len(), &schema);
-    timer.done();
-    batch
+    // merge the spilled group states with the ones still in memory
+    let (mut receivers, join_handles): (Vec<_>, Vec<_>) =
+        spills.into_iter().map(read_spill).unzip();
+
+    if !accumulators.group_states.is_empty() {
+        let timer = elapsed_compute.timer();
+        let states =
+            sorted_group_states(&accumulators, num_group_columns, &spill.schema)?;
+        timer.done();
+
+        let (mut sender, receiver) = mpsc::channel(1);
+        sender.try_send(Ok(states)).map_err(|e| {
+            DataFusionError::Internal(format!(
+                "Failed to buffer in-memory group states: {}",
+                e
+            ))
+        })?;
+        receivers.push(receiver);
+    }
+    drop(accumulators);
+    reservation.free();
+
+    // the merge is an intermediate step, whose output is not the output of
+    // this operator
+    let merged = SortPreservingMergeStream::new(
+        receivers,
+        AbortOnDropMany(join_handles),
+        spill.schema.clone(),
+        &group_sort_expr(&spill.schema, num_group_columns),
+        spill.target_batch_size,
+        BaselineMetrics::new(&ExecutionPlanMetricsSet::new(), 0),
+    );
+
+    merge_sorted_group_states(
+        &mode,
+        &schema,
+        &aggr_expr,
+        num_group_columns,
+        Box::pin(merged),
+        spill.target_batch_size,
+        baseline_metrics,
+        output,
+    )
+    .await
+}
+
+/// Order of the group values of the group states spilled to disk
+const GROUP_SORT_OPTIONS: SortOptions = SortOptions {
+    descending: false,
+    nulls_first: true,
+};
+
+/// Returns the sort expressions on the group values of group states of
+/// `schema`
+fn group_sort_expr(schema: &Schema, num_group_expr: usize) -> Vec<PhysicalSortExpr> {
+    schema.fields()[..num_group_expr]
+        .iter()
+        .enumerate()
+        .map(|(index, field)| PhysicalSortExpr {
+            expr: Arc::new(Column::new(field.name(), index)),
+            options: GROUP_SORT_OPTIONS,
+        })
+        .collect()
+}
+
+/// Returns the group values and accumulator states of all the groups as
+/// a batch of `schema`, sorted on the group values
+fn sorted_group_states(
+    accumulators: &Accumulators,
+    num_group_expr: usize,
+    schema: &SchemaRef,
+) -> Result<RecordBatch> {
+    let states = create_batch_from_map(
+        &AggregateMode::Partial,
+        accumulators,
+        num_group_expr,
+        schema,
+    )?;
+    let sort_columns = states.columns()[..num_group_expr]
+        .iter()
+        .map(|values| SortColumn {
+            values: values.clone(),
+            options: Some(GROUP_SORT_OPTIONS),
+        })
+        .collect::<Vec<_>>();
+    let indices = lexsort_to_indices(&sort_columns, None)?;
+    let columns = states
+        .columns()
+        .iter()
+        .map(|column| compute::take(column.as_ref(), &indices, None))
+        .collect::<ArrowResult<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Writes the states of all the groups, sorted on the group values, to a
+/// temporary file
+fn spill_group_states(
+    accumulators: &Accumulators,
+    num_group_expr: usize,
+    spill: &SpillConfig,
+) -> Result<NamedTempFile> {
+    let states = sorted_group_states(accumulators, num_group_expr, &spill.schema)?;
+    let (file, bytes) = spill_batches(&[states], &spill.schema)?;
+    spill.spill_count.add(1);
+    spill.spilled_bytes.add(bytes);
+    Ok(file)
+}
+
+/// Merges the group states of `input`, which are sorted on the group
+/// values, and sends the aggregated groups to `output` in batches of
+/// `batch_size` groups.
+///
+/// Only the states of the group being merged and of the groups waiting to
+/// be sent are kept in memory.
+#[allow(clippy::too_many_arguments)]
+async fn merge_sorted_group_states(
+    mode: &AggregateMode,
+    schema: &SchemaRef,
+    aggr_expr: &[Arc<dyn AggregateExpr>],
+    num_group_expr: usize,
+    mut input: SendableRecordBatchStream,
+    batch_size: usize,
+    baseline_metrics: &BaselineMetrics,
+    output: &mut mpsc::Sender<ArrowResult<RecordBatch>>,
+) -> Result<()> {
+    let elapsed_compute = baseline_metrics.elapsed_compute();
+    let state_lens = aggr_expr
+        .iter()
+        .map(|expr| Ok(expr.state_fields()?.len()))
+        .collect::<Result<Vec<_>>>()?;
+
+    // the merged groups not sent yet, the last one possibly still merging
+    let mut groups = Accumulators::default();
+    while let Some(batch) = input.next().await {
+        let batch = batch?;
+        let timer = elapsed_compute.timer();
+        let mut ready = vec![];
+        let group_values = &batch.columns()[..num_group_expr];
+        let sort_columns = group_values
+            .iter()
+            .map(|values| SortColumn {
+                values: values.clone(),
+                options: Some(GROUP_SORT_OPTIONS),
+            })
+            .collect::<Vec<_>>();
+
+        for range in lexicographical_partition_ranges(&sort_columns)? {
+            let group_by_values = group_values
+                .iter()
+                .map(|values| ScalarValue::try_from_array(values, range.start))
+                .collect::<Result<Vec<_>>>()?;
+
+            let same_group = groups
+                .group_states
+                .last()
+                .map(|group_state| *group_state.group_by_values == *group_by_values)
+                .unwrap_or(false);
+            if !same_group {
+                // all the groups merged so far are complete
+                if groups.group_states.len() >= batch_size {
+                    ready.push(create_batch_from_map(
+                        mode,
+                        &groups,
+                        num_group_expr,
+                        schema,
+                    )?);
+                    groups.group_states.clear();
+                }
+                groups.group_states.push(GroupState {
+                    group_by_values: group_by_values.into_boxed_slice(),
+                    accumulator_set: create_accumulators(aggr_expr)?,
+                    indices: vec![],
+                });
+            }
+
+            let group_state = groups.group_states.last_mut().ok_or_else(|| {
+                DataFusionError::Internal("Missing merged group state".to_string())
+            })?;
+            let mut states = batch.columns()[num_group_expr..]
+                .iter()
+                .map(|state| state.slice(range.start, range.end - range.start))
+                .collect::<Vec<_>>();
+            for (accumulator, state_len) in
+                group_state.accumulator_set.iter_mut().zip(&state_lens)
+            {
+                let rest = states.split_off(*state_len);
+                accumulator.merge_batch(&states)?;
+                states = rest;
+            }
+        }
+        timer.done();
+
+        for batch in ready {
+            if output
+                .send(Ok(batch.record_output(baseline_metrics)))
+                .await
+                .is_err()
+            {
+                // the receiver is gone and does not care about the result
+                return Ok(());
+            }
+        }
+    }
+
+    if !groups.group_states.is_empty() {
+        let timer = elapsed_compute.timer();
+        let batch = create_batch_from_map(mode, &groups, num_group_expr, schema)
+            .record_output(baseline_metrics);
+        timer.done();
+        output.send(batch).await.ok();
+    }
+    Ok(())
 }
 
 impl GroupedHashAggregateStream {
     /// Create a new HashAggregateStream
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         mode: AggregateMode,
         schema: SchemaRef,
         group_expr: Vec<Arc<dyn PhysicalExpr>>,
+        grouping_sets: Vec<Vec<bool>>,
         aggr_expr: Vec<Arc<dyn AggregateExpr>>,
         input: SendableRecordBatchStream,
         baseline_metrics: BaselineMetrics,
+        reservation: MemoryReservation,
+        spill: SpillConfig,
     ) -> Self {
-        let (tx, rx) = futures::channel::oneshot::channel();
+        let (mut tx, rx) = mpsc::channel(1);
 
         let schema_clone = schema.clone();
-        let elapsed_compute = baseline_metrics.elapsed_compute().clone();
 
         let join_handle = tokio::spawn(async move {
             let result = compute_grouped_hash_aggregate(
                 mode,
                 schema_clone,
                 group_expr,
+                grouping_sets,
                 aggr_expr,
                 input,
-                elapsed_compute,
+                &baseline_metrics,
+                reservation,
+                spill,
+                &mut tx,
             )
-            .await
-            .record_output(&baseline_metrics);
+            .await;
 
-            // failing here is OK, the receiver is gone and does not care about the result
-            tx.send(result).ok();
+            if let Err(e) = result {
+                // failing here is OK, the receiver is gone and does not care about the result
+                tx.send(Err(e.into_arrow_external_error())).await.ok();
+            }
         });
 
         Self {
             schema,
             output: rx,
-            finished: false,
             drop_helper: AbortOnDropSingle::new(join_handle),
         }
     }
@@ -597,6 +1110,34 @@ struct GroupState {
     indices: Vec<u32>,
 }
 
+/// Returns the estimated number of bytes held by the keys and
+/// accumulators of a group
+fn group_state_size(group_state: &GroupState) -> usize {
+    let group_by_values: usize = group_state
+        .group_by_values
+        .iter()
+        .map(|value| {
+            let heap_size = match value {
+                ScalarValue::Utf8(Some(v)) | ScalarValue::LargeUtf8(Some(v)) => {
+                    v.capacity()
+                }
+                ScalarValue::Binary(Some(v)) | ScalarValue::LargeBinary(Some(v)) => {
+                    v.capacity()
+                }
+                _ => 0,
+            };
+            std::mem::size_of::<ScalarValue>() + heap_size
+        })
+        .sum();
+    let accumulator_set: usize = group_state
+        .accumulator_set
+        .iter()
+        .map(|accumulator| std::mem::size_of_val(accumulator.as_ref()))
+        .sum();
+
+    std::mem::size_of::<GroupState>() + group_by_values + accumulator_set
+}
+
 /// The state of all the groups
 #[derive(Default)]
 struct Accumulators {
@@ -628,31 +1169,10 @@ impl Stream for GroupedHashAggregateStream {
     type Item = ArrowResult<RecordBatch>;
 
     fn poll_next(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        if self.finished {
-            return Poll::Ready(None);
-        }
-
-        // is the output ready?
-        let this = self.project();
-        let output_poll = this.output.poll(cx);
-
-        match output_poll {
-            Poll::Ready(result) => {
-                *this.finished = true;
-
-                // check for error in receiving channel and unwrap actual result
-                let result = match result {
-                    Err(e) => Err(ArrowError::ExternalError(Box::new(e))), // error receiving
-                    Ok(result) => result,
-                };
-
-                Poll::Ready(Some(result))
-            }
-            Poll::Pending => Poll::Pending,
-        }
+        self.output.poll_next_unpin(cx)
     }
 }
 
@@ -1015,6 +1535,7 @@ mod tests {
     use crate::{assert_batches_sorted_eq, physical_plan::common};
 
     use crate::physical_plan::coalesce_partitions::CoalescePartitionsExec;
+    use crate::physical_plan::memory::MemoryExec;
 
     /// some mock data to aggregates
     fn some_data() -> (Arc<Schema>, Vec<RecordBatch>) {
@@ -1303,4 +1824,99 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn aggregate_spills_to_disk() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::UInt32, false),
+            Field::new("b", DataType::Float64, false),
+        ]));
+        // 3000 groups, each averaging its own key
+        let batches = (0..10u32)
+            .map(|batch| {
+                let a: Vec<u32> = (batch * 1000..(batch + 1) * 1000)
+                    .map(|row| row % 3000)
+                    .collect();
+                let b: Vec<f64> = a.iter().map(|a| *a as f64).collect();
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(UInt32Array::from(a)),
+                        Arc::new(Float64Array::from(b)),
+                    ],
+                )
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+        let input = Arc::new(MemoryExec::try_new(&[batches], schema.clone(), None)?);
+
+        // far too small for the states of all the groups
+        let memory_manager = Arc::new(MemoryManager::new(64 * 1024));
+
+        let groups = vec![(col("a", &schema)?, "a".to_string())];
+        let aggregates: Vec<Arc<dyn AggregateExpr>> = vec![Arc::new(Avg::new(
+            col("b", &schema)?,
+            "AVG(b)".to_string(),
+            DataType::Float64,
+        ))];
+
+        let partial_aggregate = Arc::new(
+            HashAggregateExec::try_new(
+                AggregateMode::Partial,
+                groups,
+                aggregates.clone(),
+                input,
+                schema.clone(),
+            )?
+            .with_memory_manager(memory_manager.clone())
+            .with_target_batch_size(500),
+        );
+        let final_group = vec![(col("a", &partial_aggregate.schema())?, "a".to_string())];
+        let final_aggregate = Arc::new(
+            HashAggregateExec::try_new(
+                AggregateMode::Final,
+                final_group,
+                aggregates,
+                partial_aggregate.clone(),
+                schema,
+            )?
+            .with_memory_manager(memory_manager.clone())
+            .with_target_batch_size(500),
+        );
+
+        let result = common::collect(final_aggregate.execute(0).await?).await?;
+        // the merged groups are yielded in several batches
+        assert!(result.len() > 1);
+
+        let mut actual = result
+            .iter()
+            .flat_map(|batch| {
+                let a = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<UInt32Array>()
+                    .unwrap();
+                let avg = batch
+                    .column(1)
+                    .as_any()
+                    .downcast_ref::<Float64Array>()
+                    .unwrap();
+                (0..batch.num_rows())
+                    .map(|row| (a.value(row), avg.value(row)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        actual.sort_by_key(|(a, _)| *a);
+        let expected = (0..3000).map(|a| (a, a as f64)).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+
+        for aggregate in [&partial_aggregate, &final_aggregate] {
+            let metrics = aggregate.metrics().unwrap();
+            assert!(metrics.spill_count().unwrap() > 0);
+            assert!(metrics.spilled_bytes().unwrap() > 0);
+            assert_eq!(metrics.output_rows(), Some(3000));
+        }
+        assert_eq!(memory_manager.reserved(), 0);
+
+        Ok(())
+    }
 }
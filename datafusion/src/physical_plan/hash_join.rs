@@ -35,7 +35,7 @@ use std::{any::Any, usize};
 use std::{time::Instant, vec};
 
 use async_trait::async_trait;
-use futures::{Stream, StreamExt, TryStreamExt};
+use futures::{Stream, StreamExt};
 use tokio::sync::Mutex;
 
 use arrow::array::Array;
@@ -65,15 +65,21 @@ use crate::error::{DataFusionError, Result};
 use crate::logical_plan::JoinType;
 
 use super::{
-    DisplayFormatType, ExecutionPlan, Partitioning, RecordBatchStream,
-    SendableRecordBatchStream,
+    spill::{read_spill_as_stream, SpillWriter},
+    stream::RecordBatchReceiverStream,
+    DisplayFormatType, EmptyRecordBatchStream, ExecutionPlan, Partitioning,
+    RecordBatchStream, SendableRecordBatchStream,
 };
 use crate::arrow::array::BooleanBufferBuilder;
 use crate::arrow::datatypes::TimeUnit;
+use crate::execution::memory_manager::{MemoryManager, MemoryReservation};
 use crate::physical_plan::coalesce_batches::concat_batches;
+use crate::physical_plan::common::batch_memory_size;
 use crate::physical_plan::PhysicalExpr;
 use log::debug;
 use std::fmt;
+use tempfile::NamedTempFile;
+use tokio::sync::mpsc;
 
 // Maps a `u64` hash value based on the left ["on" values] to a list of indices with this key's value.
 //
@@ -95,10 +101,293 @@ impl fmt::Debug for JoinHashMap {
     }
 }
 
-type JoinLeftData = Arc<(JoinHashMap, RecordBatch)>;
+/// The hash table and batch of the build side, together with the memory
+/// reserved for them
+type JoinLeftData = Arc<(JoinHashMap, RecordBatch, MemoryReservation)>;
+
+/// Number of buckets the inputs of a hash join are partitioned into on disk
+/// when its build side does not fit in memory
+const SPILL_PARTITIONS: usize = 16;
+
+/// Number of times the buckets of a spilled hash join are partitioned again
+/// when they still do not fit in memory, before giving up
+const MAX_SPILL_LEVEL: usize = 4;
+
+/// Spill files holding the rows of each of the [`SPILL_PARTITIONS`] buckets
+/// of a hash join input, `None` for the buckets without any rows
+type SpilledBuckets = Vec<Option<Arc<NamedTempFile>>>;
+
+/// The build side of a hash join
+#[derive(Debug, Clone)]
+enum BuildSide {
+    /// The build side fit in memory and has been hashed
+    InMemory(JoinLeftData),
+    /// The build side did not fit in memory and has been hash partitioned
+    /// to disk on the join keys
+    Spilled(SpilledBuckets),
+}
+
+/// Collects and hashes all the batches of the build side `stream`,
+/// reserving their memory from `reservation`.
+///
+/// If the build side does not fit in the reservation, it is instead hash
+/// partitioned to disk using the partitioning of the given `level`, unless
+/// [`MAX_SPILL_LEVEL`] has been reached.
+async fn collect_build_side(
+    mut stream: SendableRecordBatchStream,
+    on_left: &[Column],
+    random_state: &RandomState,
+    mut reservation: MemoryReservation,
+    level: usize,
+    join_metrics: &HashJoinMetrics,
+) -> Result<BuildSide> {
+    let schema = stream.schema();
+    let mut num_rows = 0;
+    let mut batches = Vec::new();
+    while let Some(batch) = stream.next().await {
+        let batch = batch?;
+        // the build side is concatenated into a single batch once
+        // collected, so reserve room for both copies
+        let reserved = reservation.try_grow(2 * batch_memory_size(&batch));
+        num_rows += batch.num_rows();
+        batches.push(batch);
+        if let Err(e) = reserved {
+            return spill_build_side(
+                batches,
+                stream,
+                on_left,
+                reservation,
+                level,
+                join_metrics,
+                e,
+            )
+            .await;
+        }
+    }
+
+    match build_hash_table(
+        on_left,
+        &batches,
+        num_rows,
+        &schema,
+        random_state,
+        &mut reservation,
+    ) {
+        Ok((hashmap, single_batch)) => Ok(BuildSide::InMemory(Arc::new((
+            hashmap,
+            single_batch,
+            reservation,
+        )))),
+        Err(e @ DataFusionError::ResourcesExhausted(_)) => {
+            spill_build_side(
+                batches,
+                stream,
+                on_left,
+                reservation,
+                level,
+                join_metrics,
+                e,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Hash partitions the `buffered` batches of the build side and the rest
+/// of its `stream` to disk after failing to reserve their memory with
+/// `error`, which is returned if the build side can not be partitioned
+/// any further
+async fn spill_build_side(
+    buffered: Vec<RecordBatch>,
+    mut stream: SendableRecordBatchStream,
+    on_left: &[Column],
+    mut reservation: MemoryReservation,
+    level: usize,
+    join_metrics: &HashJoinMetrics,
+    error: DataFusionError,
+) -> Result<BuildSide> {
+    if level >= MAX_SPILL_LEVEL {
+        return Err(error);
+    }
+    debug!(
+        "Spilling build-side of hash join to disk at partitioning level {}: {}",
+        level, error
+    );
+
+    let mut partitioner = SpillPartitioner::new(on_left, stream.schema(), level);
+    for batch in buffered {
+        partitioner.write(&batch)?;
+    }
+    reservation.free();
+    while let Some(batch) = stream.next().await {
+        partitioner.write(&batch?)?;
+    }
+    Ok(BuildSide::Spilled(partitioner.finish(join_metrics)?))
+}
+
+/// Builds the hash table of the collected build side `batches` and merges
+/// them into a single batch, resizing `reservation` to the memory used by
+/// both
+fn build_hash_table(
+    on_left: &[Column],
+    batches: &[RecordBatch],
+    num_rows: usize,
+    schema: &SchemaRef,
+    random_state: &RandomState,
+    reservation: &mut MemoryReservation,
+) -> Result<(JoinHashMap, RecordBatch)> {
+    let mut hashmap = JoinHashMap(RawTable::with_capacity(num_rows));
+    reservation.try_grow(hashmap_memory_size(&hashmap))?;
+    let mut hashes_buffer = Vec::new();
+    let mut offset = 0;
+    for batch in batches.iter() {
+        hashes_buffer.clear();
+        hashes_buffer.resize(batch.num_rows(), 0);
+        update_hash(
+            on_left,
+            batch,
+            &mut hashmap,
+            offset,
+            random_state,
+            &mut hashes_buffer,
+        )?;
+        offset += batch.num_rows();
+    }
+    // Merge all batches into a single batch, so we
+    // can directly index into the arrays
+    let single_batch = concat_batches(schema, batches, num_rows)?;
+    // only the concatenated batch is kept
+    reservation
+        .try_resize(batch_memory_size(&single_batch) + hashmap_memory_size(&hashmap))?;
+    Ok((hashmap, single_batch))
+}
+
+/// Hash partitions batches on the join keys into [`SPILL_PARTITIONS`]
+/// spill files. Each partitioning level uses a different hash function, so
+/// that the rows of a bucket partitioned again spread over the new buckets.
+struct SpillPartitioner {
+    /// The join keys
+    on: Vec<Column>,
+    /// Schema of the partitioned batches
+    schema: SchemaRef,
+    /// Hash function of this partitioning level
+    random_state: RandomState,
+    /// Writers of the buckets written so far
+    writers: Vec<Option<SpillWriter>>,
+    /// Reused buffer of row hashes
+    hashes_buffer: Vec<u64>,
+}
+
+impl SpillPartitioner {
+    fn new(on: &[Column], schema: SchemaRef, level: usize) -> Self {
+        // differs from the seeds used by the hash table and by the
+        // repartitioning of the inputs
+        let seed = level as u64 + 1;
+        Self {
+            on: on.to_vec(),
+            schema,
+            random_state: RandomState::with_seeds(seed, seed, seed, seed),
+            writers: (0..SPILL_PARTITIONS).map(|_| None).collect(),
+            hashes_buffer: vec![],
+        }
+    }
+
+    /// Writes the rows of `batch` to their buckets
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let keys_values = self
+            .on
+            .iter()
+            .map(|c| Ok(c.evaluate(batch)?.into_array(batch.num_rows())))
+            .collect::<Result<Vec<_>>>()?;
+        self.hashes_buffer.clear();
+        self.hashes_buffer.resize(batch.num_rows(), 0);
+        let hashes =
+            create_hashes(&keys_values, &self.random_state, &mut self.hashes_buffer)?;
+
+        let mut indices = vec![vec![]; SPILL_PARTITIONS];
+        for (row, hash) in hashes.iter().enumerate() {
+            indices[(*hash % SPILL_PARTITIONS as u64) as usize].push(row as u64);
+        }
+
+        for (bucket, bucket_indices) in indices.into_iter().enumerate() {
+            if bucket_indices.is_empty() {
+                continue;
+            }
+            let bucket_indices = UInt64Array::from(bucket_indices);
+            let columns = batch
+                .columns()
+                .iter()
+                .map(|c| compute::take(c.as_ref(), &bucket_indices, None))
+                .collect::<ArrowResult<Vec<_>>>()?;
+            let bucket_batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+
+            let writer = match &mut self.writers[bucket] {
+                Some(writer) => writer,
+                slot @ None => slot.insert(SpillWriter::try_new(&self.schema)?),
+            };
+            writer.write(&bucket_batch)?;
+        }
+        Ok(())
+    }
+
+    /// Completes the spill files of all the buckets
+    fn finish(self, join_metrics: &HashJoinMetrics) -> Result<SpilledBuckets> {
+        join_metrics.spill_count.add(1);
+        self.writers
+            .into_iter()
+            .map(|writer| {
+                writer
+                    .map(|writer| {
+                        let (file, bytes) = writer.finish()?;
+                        join_metrics.spilled_bytes.add(bytes);
+                        Ok(Arc::new(file))
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+}
+
+/// Hash partitions all of `stream` to disk using the partitioning of the
+/// given `level`
+async fn partition_to_disk(
+    mut stream: SendableRecordBatchStream,
+    on: &[Column],
+    level: usize,
+    join_metrics: &HashJoinMetrics,
+) -> Result<SpilledBuckets> {
+    let mut partitioner = SpillPartitioner::new(on, stream.schema(), level);
+    while let Some(batch) = stream.next().await {
+        partitioner.write(&batch?)?;
+    }
+    partitioner.finish(join_metrics)
+}
+
+/// Reads back a bucket spilled by a [`SpillPartitioner`], or returns an
+/// empty stream for a bucket without rows
+fn read_bucket(
+    bucket: &Option<Arc<NamedTempFile>>,
+    schema: &SchemaRef,
+) -> SendableRecordBatchStream {
+    match bucket {
+        Some(file) => read_spill_as_stream(file.clone(), schema.clone()),
+        None => Box::pin(EmptyRecordBatchStream::new(schema.clone())),
+    }
+}
+
+/// Returns the amount of memory allocated by the buckets of `hashmap`
+fn hashmap_memory_size(hashmap: &JoinHashMap) -> usize {
+    hashmap.0.buckets() * std::mem::size_of::<(u64, SmallVec<[u64; 1]>)>()
+}
 
 /// join execution plan executes partitions in parallel and combines them into a set of
 /// partitions.
+///
+/// When the build side does not fit in the memory reserved for it, both
+/// inputs are hash partitioned on the join keys to temporary Arrow IPC files
+/// and joined bucket by bucket, partitioning the buckets still too large
+/// again.
 #[derive(Debug)]
 pub struct HashJoinExec {
     /// left (build) side which gets hashed
@@ -112,7 +401,7 @@ pub struct HashJoinExec {
     /// The schema once the join is applied
     schema: SchemaRef,
     /// Build-side
-    build_side: Arc<Mutex<Option<JoinLeftData>>>,
+    build_side: Arc<Mutex<Option<BuildSide>>>,
     /// Shares the `RandomState` for the hashing algorithm
     random_state: RandomState,
     /// Partitioning mode to use
@@ -123,10 +412,12 @@ pub struct HashJoinExec {
     column_indices: Vec<ColumnIndex>,
     /// If null_equals_null is true, null == null else null != null
     null_equals_null: bool,
+    /// Memory pool the build side is reserved from
+    memory_manager: Arc<MemoryManager>,
 }
 
 /// Metrics for HashJoinExec
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct HashJoinMetrics {
     /// Total time for joining probe-side batches to the build-side batches
     join_time: metrics::Time,
@@ -138,6 +429,10 @@ struct HashJoinMetrics {
     output_batches: metrics::Count,
     /// Number of rows produced by this operator
     output_rows: metrics::Count,
+    /// Number of inputs hash partitioned to disk by this operator
+    spill_count: metrics::Count,
+    /// Number of bytes spilled to disk by this operator
+    spilled_bytes: metrics::Count,
 }
 
 impl HashJoinMetrics {
@@ -154,12 +449,18 @@ impl HashJoinMetrics {
 
         let output_rows = MetricBuilder::new(metrics).output_rows(partition);
 
+        let spill_count = MetricBuilder::new(metrics).spill_count(partition);
+
+        let spilled_bytes = MetricBuilder::new(metrics).spilled_bytes(partition);
+
         Self {
             join_time,
             input_batches,
             input_rows,
             output_batches,
             output_rows,
+            spill_count,
+            spilled_bytes,
         }
     }
 }
@@ -206,9 +507,21 @@ impl HashJoinExec {
             metrics: ExecutionPlanMetricsSet::new(),
             column_indices,
             null_equals_null: *null_equals_null,
+            memory_manager: MemoryManager::unbounded(),
         })
     }
 
+    /// Reserve the memory of the build side from `memory_manager`
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = memory_manager;
+        self
+    }
+
+    /// Memory pool the build side is reserved from
+    pub fn memory_manager(&self) -> &Arc<MemoryManager> {
+        &self.memory_manager
+    }
+
     /// left (build) side which gets hashed
     pub fn left(&self) -> &Arc<dyn ExecutionPlan> {
         &self.left
@@ -259,14 +572,17 @@ impl ExecutionPlan for HashJoinExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            2 => Ok(Arc::new(HashJoinExec::try_new(
-                children[0].clone(),
-                children[1].clone(),
-                self.on.clone(),
-                &self.join_type,
-                self.mode,
-                &self.null_equals_null,
-            )?)),
+            2 => Ok(Arc::new(
+                HashJoinExec::try_new(
+                    children[0].clone(),
+                    children[1].clone(),
+                    self.on.clone(),
+                    &self.join_type,
+                    self.mode,
+                    &self.null_equals_null,
+                )?
+                .with_memory_manager(self.memory_manager.clone()),
+            )),
             _ => Err(DataFusionError::Internal(
                 "HashJoinExec wrong number of children".to_string(),
             )),
@@ -279,8 +595,9 @@ impl ExecutionPlan for HashJoinExec {
 
     async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
         let on_left = self.on.iter().map(|on| on.0.clone()).collect::<Vec<_>>();
+        let join_metrics = HashJoinMetrics::new(partition, &self.metrics);
         // we only want to compute the build side once for PartitionMode::CollectLeft
-        let left_data = {
+        let build_side = {
             match self.mode {
                 PartitionMode::CollectLeft => {
                     let mut build_side = self.build_side.lock().await;
@@ -297,45 +614,28 @@ impl ExecutionPlan for HashJoinExec {
                             // This operation performs 2 steps at once:
                             // 1. creates a [JoinHashMap] of all batches from the stream
                             // 2. stores the batches in a vector.
-                            let initial = (0, Vec::new());
-                            let (num_rows, batches) = stream
-                                .try_fold(initial, |mut acc, batch| async {
-                                    acc.0 += batch.num_rows();
-                                    acc.1.push(batch);
-                                    Ok(acc)
-                                })
-                                .await?;
-                            let mut hashmap =
-                                JoinHashMap(RawTable::with_capacity(num_rows));
-                            let mut hashes_buffer = Vec::new();
-                            let mut offset = 0;
-                            for batch in batches.iter() {
-                                hashes_buffer.clear();
-                                hashes_buffer.resize(batch.num_rows(), 0);
-                                update_hash(
-                                    &on_left,
-                                    batch,
-                                    &mut hashmap,
-                                    offset,
-                                    &self.random_state,
-                                    &mut hashes_buffer,
-                                )?;
-                                offset += batch.num_rows();
-                            }
-                            // Merge all batches into a single batch, so we
-                            // can directly index into the arrays
-                            let single_batch =
-                                concat_batches(&self.left.schema(), &batches, num_rows)?;
-
-                            let left_side = Arc::new((hashmap, single_batch));
+                            let reservation = self
+                                .memory_manager
+                                .register_consumer("HashJoinExec[CollectLeft]", false);
+                            let left_side = collect_build_side(
+                                stream,
+                                &on_left,
+                                &self.random_state,
+                                reservation,
+                                0,
+                                &join_metrics,
+                            )
+                            .await?;
 
                             *build_side = Some(left_side.clone());
 
-                            debug!(
-                                "Built build-side of hash join containing {} rows in {} ms",
-                                num_rows,
-                                start.elapsed().as_millis()
-                            );
+                            if let BuildSide::InMemory(left_data) = &left_side {
+                                debug!(
+                                    "Built build-side of hash join containing {} rows in {} ms",
+                                    left_data.1.num_rows(),
+                                    start.elapsed().as_millis()
+                                );
+                            }
 
                             left_side
                         }
@@ -350,43 +650,27 @@ impl ExecutionPlan for HashJoinExec {
                     // This operation performs 2 steps at once:
                     // 1. creates a [JoinHashMap] of all batches from the stream
                     // 2. stores the batches in a vector.
-                    let initial = (0, Vec::new());
-                    let (num_rows, batches) = stream
-                        .try_fold(initial, |mut acc, batch| async {
-                            acc.0 += batch.num_rows();
-                            acc.1.push(batch);
-                            Ok(acc)
-                        })
-                        .await?;
-                    let mut hashmap = JoinHashMap(RawTable::with_capacity(num_rows));
-                    let mut hashes_buffer = Vec::new();
-                    let mut offset = 0;
-                    for batch in batches.iter() {
-                        hashes_buffer.clear();
-                        hashes_buffer.resize(batch.num_rows(), 0);
-                        update_hash(
-                            &on_left,
-                            batch,
-                            &mut hashmap,
-                            offset,
-                            &self.random_state,
-                            &mut hashes_buffer,
-                        )?;
-                        offset += batch.num_rows();
+                    let reservation = self
+                        .memory_manager
+                        .register_consumer(format!("HashJoinExec[{}]", partition), false);
+                    let left_side = collect_build_side(
+                        stream,
+                        &on_left,
+                        &self.random_state,
+                        reservation,
+                        0,
+                        &join_metrics,
+                    )
+                    .await?;
+
+                    if let BuildSide::InMemory(left_data) = &left_side {
+                        debug!(
+                            "Built build-side {} of hash join containing {} rows in {} ms",
+                            partition,
+                            left_data.1.num_rows(),
+                            start.elapsed().as_millis()
+                        );
                     }
-                    // Merge all batches into a single batch, so we
-                    // can directly index into the arrays
-                    let single_batch =
-                        concat_batches(&self.left.schema(), &batches, num_rows)?;
-
-                    let left_side = Arc::new((hashmap, single_batch));
-
-                    debug!(
-                        "Built build-side {} of hash join containing {} rows in {} ms",
-                        partition,
-                        num_rows,
-                        start.elapsed().as_millis()
-                    );
 
                     left_side
                 }
@@ -399,30 +683,54 @@ impl ExecutionPlan for HashJoinExec {
         let right_stream = self.right.execute(partition).await?;
         let on_right = self.on.iter().map(|on| on.1.clone()).collect::<Vec<_>>();
 
-        let num_rows = left_data.1.num_rows();
-        let visited_left_side = match self.join_type {
-            JoinType::Left | JoinType::Full | JoinType::Semi | JoinType::Anti => {
-                let mut buffer = BooleanBufferBuilder::new(num_rows);
+        match build_side {
+            BuildSide::InMemory(left_data) => Ok(Box::pin(HashJoinStream::new(
+                self.schema.clone(),
+                on_left,
+                on_right,
+                self.join_type,
+                left_data,
+                right_stream,
+                self.column_indices.clone(),
+                self.random_state.clone(),
+                join_metrics,
+                self.null_equals_null,
+            ))),
+            BuildSide::Spilled(left_buckets) => {
+                // the build side did not fit in memory: join the inputs
+                // bucket by bucket instead
+                let spilled_join = SpilledHashJoin {
+                    schema: self.schema.clone(),
+                    left_schema: self.left.schema(),
+                    on_left,
+                    on_right,
+                    join_type: self.join_type,
+                    column_indices: self.column_indices.clone(),
+                    random_state: self.random_state.clone(),
+                    null_equals_null: self.null_equals_null,
+                    memory_manager: self.memory_manager.clone(),
+                    consumer_name: format!("HashJoinExec[{}]", partition),
+                    join_metrics,
+                };
 
-                buffer.append_n(num_rows, false);
+                let (sender, receiver) = mpsc::channel(2);
+                let join_handle = tokio::spawn(async move {
+                    if let Err(e) =
+                        spilled_join.run(left_buckets, right_stream, &sender).await
+                    {
+                        // If send fails, plan being torn down,
+                        // there is no place to send the error
+                        sender.send(Err(e.into_arrow_external_error())).await.ok();
+                    }
+                });
 
-                buffer
+                Ok(RecordBatchReceiverStream::create(
+                    &self.schema,
+                    receiver,
+                    join_handle,
+                ))
             }
-            JoinType::Inner | JoinType::Right => BooleanBufferBuilder::new(0),
-        };
-        Ok(Box::pin(HashJoinStream::new(
-            self.schema.clone(),
-            on_left,
-            on_right,
-            self.join_type,
-            left_data,
-            right_stream,
-            self.column_indices.clone(),
-            self.random_state.clone(),
-            visited_left_side,
-            HashJoinMetrics::new(partition, &self.metrics),
-            self.null_equals_null,
-        )))
+        }
     }
 
     fn fmt_as(
@@ -490,6 +798,132 @@ fn update_hash(
     Ok(())
 }
 
+/// Joins a partition of the inputs of a [`HashJoinExec`] bucket by bucket,
+/// once its build side has been hash partitioned to disk
+struct SpilledHashJoin {
+    /// The schema once the join is applied
+    schema: SchemaRef,
+    /// Schema of the build side
+    left_schema: SchemaRef,
+    /// columns from the left
+    on_left: Vec<Column>,
+    /// columns from the right
+    on_right: Vec<Column>,
+    /// type of the join
+    join_type: JoinType,
+    /// Information of index and left / right placement of columns
+    column_indices: Vec<ColumnIndex>,
+    /// Random state used by the hash tables of the buckets
+    random_state: RandomState,
+    /// If null_equals_null is true, null == null else null != null
+    null_equals_null: bool,
+    /// Memory pool the buckets of the build side are reserved from
+    memory_manager: Arc<MemoryManager>,
+    /// Name of the memory consumers of the build side buckets
+    consumer_name: String,
+    /// Metrics
+    join_metrics: HashJoinMetrics,
+}
+
+impl SpilledHashJoin {
+    /// Hash partitions `right` to disk like the spilled `left_buckets`,
+    /// then joins each pair of buckets in memory, sending the joined batches
+    /// to `sender`. A build side bucket still too large for memory is
+    /// partitioned again, along with its probe side bucket.
+    async fn run(
+        self,
+        left_buckets: SpilledBuckets,
+        right: SendableRecordBatchStream,
+        sender: &mpsc::Sender<ArrowResult<RecordBatch>>,
+    ) -> Result<()> {
+        let right_schema = right.schema();
+        let right_buckets =
+            partition_to_disk(right, &self.on_right, 0, &self.join_metrics).await?;
+
+        // pairs of buckets left to join, with their partitioning level
+        let mut pending = left_buckets
+            .into_iter()
+            .zip(right_buckets)
+            .map(|(left, right)| (left, right, 0))
+            .collect::<Vec<_>>();
+
+        while let Some((left, right, level)) = pending.pop() {
+            if !self.may_produce_rows(&left, &right) {
+                continue;
+            }
+
+            let reservation = self
+                .memory_manager
+                .register_consumer(self.consumer_name.clone(), false);
+            let build_side = collect_build_side(
+                read_bucket(&left, &self.left_schema),
+                &self.on_left,
+                &self.random_state,
+                reservation,
+                level + 1,
+                &self.join_metrics,
+            )
+            .await?;
+
+            match build_side {
+                BuildSide::InMemory(left_data) => {
+                    let mut stream = HashJoinStream::new(
+                        self.schema.clone(),
+                        self.on_left.clone(),
+                        self.on_right.clone(),
+                        self.join_type,
+                        left_data,
+                        read_bucket(&right, &right_schema),
+                        self.column_indices.clone(),
+                        self.random_state.clone(),
+                        self.join_metrics.clone(),
+                        self.null_equals_null,
+                    );
+                    while let Some(batch) = stream.next().await {
+                        let is_err = batch.is_err();
+                        // the output was dropped (e.g. by a LIMIT) or has
+                        // received the error: nothing more to do
+                        if sender.send(batch).await.is_err() || is_err {
+                            return Ok(());
+                        }
+                    }
+                }
+                BuildSide::Spilled(left_buckets) => {
+                    let right_buckets = partition_to_disk(
+                        read_bucket(&right, &right_schema),
+                        &self.on_right,
+                        level + 1,
+                        &self.join_metrics,
+                    )
+                    .await?;
+                    pending.extend(
+                        left_buckets
+                            .into_iter()
+                            .zip(right_buckets)
+                            .map(|(left, right)| (left, right, level + 1)),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether joining a pair of buckets, `None` when without rows, can
+    /// produce any rows
+    fn may_produce_rows(
+        &self,
+        left: &Option<Arc<NamedTempFile>>,
+        right: &Option<Arc<NamedTempFile>>,
+    ) -> bool {
+        match self.join_type {
+            JoinType::Inner | JoinType::Semi => left.is_some() && right.is_some(),
+            JoinType::Left | JoinType::Anti => left.is_some(),
+            JoinType::Right => right.is_some(),
+            JoinType::Full => left.is_some() || right.is_some(),
+        }
+    }
+}
+
 /// A stream that issues [RecordBatch]es as they arrive from the right  of the join.
 struct HashJoinStream {
     /// Input schema
@@ -529,10 +963,20 @@ impl HashJoinStream {
         right: SendableRecordBatchStream,
         column_indices: Vec<ColumnIndex>,
         random_state: RandomState,
-        visited_left_side: BooleanBufferBuilder,
         join_metrics: HashJoinMetrics,
         null_equals_null: bool,
     ) -> Self {
+        let num_rows = left_data.1.num_rows();
+        let visited_left_side = match join_type {
+            JoinType::Left | JoinType::Full | JoinType::Semi | JoinType::Anti => {
+                let mut buffer = BooleanBufferBuilder::new(num_rows);
+
+                buffer.append_n(num_rows, false);
+
+                buffer
+            }
+            JoinType::Inner | JoinType::Right => BooleanBufferBuilder::new(0),
+        };
         HashJoinStream {
             schema,
             on_left,
@@ -1889,7 +2333,11 @@ mod tests {
             ("c", &vec![30, 40]),
         );
 
-        let left_data = JoinLeftData::new((JoinHashMap(hashmap_left), left));
+        let left_data = JoinLeftData::new((
+            JoinHashMap(hashmap_left),
+            left,
+            MemoryManager::unbounded().register_consumer("test", false),
+        ));
         let (l, r) = build_join_indexes(
             &left_data,
             &right,
@@ -1953,4 +2401,146 @@ mod tests {
 
         Ok(())
     }
+
+    /// Builds a table of `num_batches` batches of `batch_size` rows, whose
+    /// columns hold the key of each row, the row number and zeros
+    fn build_large_table(
+        names: (&str, &str, &str),
+        num_batches: usize,
+        batch_size: usize,
+        key: impl Fn(i32) -> i32,
+    ) -> Arc<dyn ExecutionPlan> {
+        let batches = (0..num_batches)
+            .map(|batch| {
+                let rows = (batch * batch_size) as i32..((batch + 1) * batch_size) as i32;
+                build_table_i32(
+                    (names.0, &rows.clone().map(&key).collect()),
+                    (names.1, &rows.collect()),
+                    (names.2, &vec![0; batch_size]),
+                )
+            })
+            .collect::<Vec<_>>();
+        let schema = batches[0].schema();
+        Arc::new(MemoryExec::try_new(&[batches], schema, None).unwrap())
+    }
+
+    /// Runs all the partitions of `join` and returns their formatted rows,
+    /// sorted
+    async fn sorted_rows(join: &HashJoinExec) -> Result<Vec<String>> {
+        let mut batches = vec![];
+        for partition in 0..join.output_partitioning().partition_count() {
+            batches.extend(common::collect(join.execute(partition).await?).await?);
+        }
+        let formatted = arrow::util::pretty::pretty_format_batches(&batches)?;
+        let mut rows = formatted.lines().map(String::from).collect::<Vec<_>>();
+        rows.sort();
+        Ok(rows)
+    }
+
+    #[tokio::test]
+    async fn join_spills_to_disk() -> Result<()> {
+        let left = build_large_table(("a1", "b1", "c1"), 4, 1000, |row| row % 3000);
+        let right = build_large_table(("a2", "b2", "c2"), 4, 1000, |row| row * 2 % 5000);
+        let on = vec![(
+            Column::new_with_schema("a1", &left.schema())?,
+            Column::new_with_schema("a2", &right.schema())?,
+        )];
+
+        let join_types = [
+            JoinType::Inner,
+            JoinType::Left,
+            JoinType::Right,
+            JoinType::Full,
+            JoinType::Semi,
+            JoinType::Anti,
+        ];
+        for join_type in join_types {
+            for mode in [PartitionMode::CollectLeft, PartitionMode::Partitioned] {
+                let (left, right) = match mode {
+                    PartitionMode::CollectLeft => (left.clone(), right.clone()),
+                    PartitionMode::Partitioned => (
+                        Arc::new(RepartitionExec::try_new(
+                            left.clone(),
+                            Partitioning::Hash(vec![Arc::new(on[0].0.clone())], 4),
+                        )?) as Arc<dyn ExecutionPlan>,
+                        Arc::new(RepartitionExec::try_new(
+                            right.clone(),
+                            Partitioning::Hash(vec![Arc::new(on[0].1.clone())], 4),
+                        )?) as Arc<dyn ExecutionPlan>,
+                    ),
+                };
+
+                let in_memory = HashJoinExec::try_new(
+                    left.clone(),
+                    right.clone(),
+                    on.clone(),
+                    &join_type,
+                    mode,
+                    &false,
+                )?;
+                let expected = sorted_rows(&in_memory).await?;
+                assert_eq!(in_memory.metrics().unwrap().spill_count(), Some(0));
+
+                let memory_manager = Arc::new(MemoryManager::new(16 * 1024));
+                let join = HashJoinExec::try_new(
+                    left,
+                    right,
+                    on.clone(),
+                    &join_type,
+                    mode,
+                    &false,
+                )?
+                .with_memory_manager(memory_manager.clone());
+                let actual = sorted_rows(&join).await?;
+
+                assert_eq!(expected, actual, "{:?} join in {:?} mode", join_type, mode);
+                assert!(join.metrics().unwrap().spill_count().unwrap() > 0);
+                assert!(join.metrics().unwrap().spilled_bytes().unwrap() > 0);
+                drop(join);
+                assert_eq!(memory_manager.reserved(), 0);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_spill_partitions_large_buckets_again() -> Result<()> {
+        let left = build_large_table(("a1", "b1", "c1"), 16, 1000, |row| row);
+        let right = build_large_table(("a2", "b2", "c2"), 16, 1000, |row| row);
+        let on = vec![(
+            Column::new_with_schema("a1", &left.schema())?,
+            Column::new_with_schema("a2", &right.schema())?,
+        )];
+
+        let join = join(left, right, on, &JoinType::Inner, false)?
+            .with_memory_manager(Arc::new(MemoryManager::new(32 * 1024)));
+        let batches = common::collect(join.execute(0).await?).await?;
+
+        let num_rows: usize = batches.iter().map(|batch| batch.num_rows()).sum();
+        assert_eq!(num_rows, 16000);
+        // both inputs were partitioned once, then at least one pair of buckets
+        // was partitioned again
+        assert!(join.metrics().unwrap().spill_count().unwrap() > 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_spill_exceeds_max_level() -> Result<()> {
+        // all the rows share the same key, so partitioning never helps
+        let left = build_large_table(("a1", "b1", "c1"), 4, 1000, |_| 0);
+        let right = build_large_table(("a2", "b2", "c2"), 1, 10, |_| 0);
+        let on = vec![(
+            Column::new_with_schema("a1", &left.schema())?,
+            Column::new_with_schema("a2", &right.schema())?,
+        )];
+
+        let join = join(left, right, on, &JoinType::Inner, false)?
+            .with_memory_manager(Arc::new(MemoryManager::new(16 * 1024)));
+        let err = common::collect(join.execute(0).await?).await.unwrap_err();
+        assert!(err.to_string().contains("Resources exhausted"), "{}", err);
+
+        Ok(())
+    }
 }
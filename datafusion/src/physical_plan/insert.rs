@@ -0,0 +1,172 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Execution plan writing the rows of its input into a table
+
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use arrow::array::UInt64Array;
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+
+use super::stream::RecordBatchReceiverStream;
+use super::{
+    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+
+/// The destination of the rows inserted into a table, created by
+/// [`TableProvider::insert_into`](crate::datasource::TableProvider::insert_into)
+#[async_trait]
+pub trait DataSink: Debug + Send + Sync {
+    /// Execute every output partition of `input` and write the rows it
+    /// produces, returning the number of rows written
+    async fn write_all(&self, input: Arc<dyn ExecutionPlan>) -> Result<usize>;
+}
+
+/// The schema of the output of an insert, a single row with the number of
+/// rows inserted
+pub fn insert_count_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![Field::new(
+        "count",
+        DataType::UInt64,
+        false,
+    )]))
+}
+
+/// Execution plan writing the rows produced by its input into a [`DataSink`],
+/// then producing the number of rows written
+#[derive(Debug)]
+pub struct InsertExec {
+    /// The rows to write
+    input: Arc<dyn ExecutionPlan>,
+    /// Where the rows are written
+    sink: Arc<dyn DataSink>,
+    /// The schema of the output
+    schema: SchemaRef,
+}
+
+impl InsertExec {
+    /// Create a plan writing the rows of `input` into `sink`
+    pub fn new(input: Arc<dyn ExecutionPlan>, sink: Arc<dyn DataSink>) -> Self {
+        Self {
+            input,
+            sink,
+            schema: insert_count_schema(),
+        }
+    }
+
+    /// The rows to write
+    pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.input
+    }
+
+    /// Where the rows are written
+    pub fn sink(&self) -> &Arc<dyn DataSink> {
+        &self.sink
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for InsertExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.input.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            1 => Ok(Arc::new(InsertExec::new(
+                children[0].clone(),
+                self.sink.clone(),
+            ))),
+            _ => Err(DataFusionError::Internal(
+                "InsertExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "InsertExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1);
+        let input = self.input.clone();
+        let sink = self.sink.clone();
+        let schema = self.schema.clone();
+        let join_handle = tokio::task::spawn(async move {
+            let result = sink
+                .write_all(input)
+                .await
+                .and_then(|count| {
+                    Ok(RecordBatch::try_new(
+                        schema,
+                        vec![Arc::new(UInt64Array::from(vec![count as u64]))],
+                    )?)
+                })
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)));
+            // the receiver hung up if sending fails, nobody is left to
+            // look at the result
+            tx.send(result).await.ok();
+        });
+
+        Ok(RecordBatchReceiverStream::create(
+            &self.schema,
+            rx,
+            join_handle,
+        ))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "InsertExec: sink={:?}", self.sink)
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
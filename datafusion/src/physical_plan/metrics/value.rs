@@ -265,6 +265,12 @@ pub enum MetricValue {
     /// classical defintion of "cpu_time", which is the time reported
     /// from `clock_gettime(CLOCK_THREAD_CPUTIME_ID, ..)`.
     ElapsedCompute(Time),
+    /// Number of times an operator spilled intermediate data to disk:
+    /// "spill_count" metric
+    SpillCount(Count),
+    /// Total number of bytes an operator spilled to disk:
+    /// "spilled_bytes" metric
+    SpilledBytes(Count),
     /// Operator defined count.
     Count {
         /// The provided name of this metric
@@ -291,6 +297,8 @@ impl MetricValue {
         match self {
             Self::OutputRows(_) => "output_rows",
             Self::ElapsedCompute(_) => "elapsed_compute",
+            Self::SpillCount(_) => "spill_count",
+            Self::SpilledBytes(_) => "spilled_bytes",
             Self::Count { name, .. } => name.borrow(),
             Self::Time { name, .. } => name.borrow(),
             Self::StartTimestamp(_) => "start_timestamp",
@@ -303,6 +311,8 @@ impl MetricValue {
         match self {
             Self::OutputRows(count) => count.value(),
             Self::ElapsedCompute(time) => time.value(),
+            Self::SpillCount(count) => count.value(),
+            Self::SpilledBytes(count) => count.value(),
             Self::Count { count, .. } => count.value(),
             Self::Time { time, .. } => time.value(),
             Self::StartTimestamp(timestamp) => timestamp
@@ -322,6 +332,8 @@ impl MetricValue {
         match self {
             Self::OutputRows(_) => Self::OutputRows(Count::new()),
             Self::ElapsedCompute(_) => Self::ElapsedCompute(Time::new()),
+            Self::SpillCount(_) => Self::SpillCount(Count::new()),
+            Self::SpilledBytes(_) => Self::SpilledBytes(Count::new()),
             Self::Count { name, .. } => Self::Count {
                 name: name.clone(),
                 count: Count::new(),
@@ -347,6 +359,8 @@ impl MetricValue {
     pub fn aggregate(&mut self, other: &Self) {
         match (self, other) {
             (Self::OutputRows(count), Self::OutputRows(other_count))
+            | (Self::SpillCount(count), Self::SpillCount(other_count))
+            | (Self::SpilledBytes(count), Self::SpilledBytes(other_count))
             | (
                 Self::Count { count, .. },
                 Self::Count {
@@ -383,10 +397,12 @@ impl MetricValue {
         match self {
             Self::OutputRows(_) => 0,     // show first
             Self::ElapsedCompute(_) => 1, // show second
-            Self::Count { .. } => 2,
-            Self::Time { .. } => 3,
-            Self::StartTimestamp(_) => 4, // show timestamps last
-            Self::EndTimestamp(_) => 5,
+            Self::SpillCount(_) => 2,
+            Self::SpilledBytes(_) => 3,
+            Self::Count { .. } => 4,
+            Self::Time { .. } => 5,
+            Self::StartTimestamp(_) => 6, // show timestamps last
+            Self::EndTimestamp(_) => 7,
         }
     }
 
@@ -400,7 +416,10 @@ impl std::fmt::Display for MetricValue {
     /// Prints the value of this metric
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::OutputRows(count) | Self::Count { count, .. } => {
+            Self::OutputRows(count)
+            | Self::SpillCount(count)
+            | Self::SpilledBytes(count)
+            | Self::Count { count, .. } => {
                 write!(f, "{}", count)
             }
             Self::ElapsedCompute(time) | Self::Time { time, .. } => {
@@ -430,6 +449,8 @@ mod tests {
         let count = Count::new();
         let values = vec![
             MetricValue::OutputRows(count.clone()),
+            MetricValue::SpillCount(count.clone()),
+            MetricValue::SpilledBytes(count.clone()),
             MetricValue::Count {
                 name: "my_counter".into(),
                 count: count.clone(),
@@ -138,6 +138,11 @@ pub trait ExecutionPlan: Debug + Send + Sync {
     fn schema(&self) -> SchemaRef;
     /// Specifies the output partitioning scheme of this plan
     fn output_partitioning(&self) -> Partitioning;
+    /// Specifies how the rows of each output partition are sorted, if they
+    /// are. Used to avoid sorting data that is already sorted.
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        None
+    }
     /// Specifies the data distribution requirements of all the children for this operator
     fn required_child_distribution(&self) -> Distribution {
         Distribution::UnspecifiedDistribution
@@ -601,6 +606,21 @@ pub trait Accumulator: Send + Sync + Debug {
 
     /// returns its value based on its current state.
     fn evaluate(&self) -> Result<ScalarValue>;
+
+    /// whether the accumulator can remove values with `retract_batch`, which
+    /// lets window functions slide their frames without recomputing them.
+    fn supports_retract_batch(&self) -> bool {
+        false
+    }
+
+    /// removes from the accumulator's state a vector of arrays that were
+    /// previously added with `update_batch`.
+    fn retract_batch(&mut self, _values: &[ArrayRef]) -> Result<()> {
+        Err(DataFusionError::NotImplemented(format!(
+            "Retracting values from {:?} is not supported",
+            self
+        )))
+    }
 }
 
 pub mod aggregates;
@@ -626,6 +646,7 @@ pub mod hash_aggregate;
 pub mod hash_join;
 pub mod hash_utils;
 pub(crate) mod hyperloglog;
+pub mod insert;
 pub mod join_utils;
 pub mod limit;
 pub mod math_expressions;
@@ -633,13 +654,17 @@ pub mod memory;
 pub mod metrics;
 pub mod planner;
 pub mod projection;
+pub mod recursive_query;
 #[cfg(feature = "regex_expressions")]
 pub mod regex_expressions;
 pub mod repartition;
 pub mod sort;
+pub mod sort_merge_join;
 pub mod sort_preserving_merge;
+mod spill;
 pub mod stream;
 pub mod string_expressions;
+pub(crate) mod tdigest;
 pub mod type_coercion;
 pub mod udaf;
 pub mod udf;
@@ -649,3 +674,4 @@ pub mod union;
 pub mod values;
 pub mod window_functions;
 pub mod windows;
+pub mod work_table;
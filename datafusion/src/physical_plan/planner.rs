@@ -24,12 +24,13 @@ use super::{
 };
 use crate::execution::context::ExecutionContextState;
 use crate::logical_plan::plan::{
-    Aggregate, EmptyRelation, Filter, Join, Projection, Sort, TableScan, Window,
+    Aggregate, CopyTo, EmptyRelation, Filter, Insert, Join, Projection, RecursiveQuery,
+    Sort, TableScan, Window,
 };
 use crate::logical_plan::{
-    unalias, unnormalize_cols, CrossJoin, DFSchema, Expr, LogicalPlan, Operator,
-    Partitioning as LogicalPartitioning, PlanType, Repartition, ToStringifiedPlan, Union,
-    UserDefinedLogicalNode,
+    expand_grouping_sets, unalias, unnormalize_cols, CrossJoin, DFSchema, Expr,
+    LogicalPlan, Operator, Partitioning as LogicalPartitioning, PlanType, Repartition,
+    ToStringifiedPlan, Union, UserDefinedLogicalNode, GROUPING_ID_COLUMN,
 };
 use crate::logical_plan::{Limit, Values};
 use crate::physical_optimizer::optimizer::PhysicalOptimizerRule;
@@ -37,15 +38,21 @@ use crate::physical_plan::cross_join::CrossJoinExec;
 use crate::physical_plan::explain::ExplainExec;
 use crate::physical_plan::expressions;
 use crate::physical_plan::expressions::{
-    CaseExpr, Column, GetIndexedFieldExpr, Literal, PhysicalSortExpr,
+    ordering_satisfies, AggregateFilter, CaseExpr, Column, GetIndexedFieldExpr, Literal,
+    PhysicalSortExpr,
 };
+use crate::physical_plan::file_format::DirectorySink;
 use crate::physical_plan::filter::FilterExec;
 use crate::physical_plan::hash_aggregate::{AggregateMode, HashAggregateExec};
 use crate::physical_plan::hash_join::HashJoinExec;
+use crate::physical_plan::insert::InsertExec;
 use crate::physical_plan::limit::{GlobalLimitExec, LocalLimitExec};
 use crate::physical_plan::projection::ProjectionExec;
+use crate::physical_plan::recursive_query::RecursiveQueryExec;
 use crate::physical_plan::repartition::RepartitionExec;
 use crate::physical_plan::sort::SortExec;
+use crate::physical_plan::sort_merge_join::SortMergeJoinExec;
+use crate::physical_plan::sort_preserving_merge::SortPreservingMergeExec;
 use crate::physical_plan::udf;
 use crate::physical_plan::windows::WindowAggExec;
 use crate::physical_plan::{join_utils, Partitioning};
@@ -164,8 +171,43 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
             fun,
             distinct,
             args,
-            ..
-        } => create_function_physical_name(&fun.to_string(), *distinct, args),
+            filter,
+            order_by,
+        } => {
+            let mut name =
+                create_function_physical_name(&fun.to_string(), *distinct, args)?;
+            if !order_by.is_empty() {
+                let order_by = order_by
+                    .iter()
+                    .map(|e| match e {
+                        Expr::Sort {
+                            expr,
+                            asc,
+                            nulls_first,
+                        } => Ok(format!(
+                            "{} {} {}",
+                            create_physical_name(expr, false)?,
+                            if *asc { "ASC" } else { "DESC" },
+                            if *nulls_first {
+                                "NULLS FIRST"
+                            } else {
+                                "NULLS LAST"
+                            }
+                        )),
+                        e => create_physical_name(e, false),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                name = format!("{} ORDER BY {}", name, order_by.join(", "));
+            }
+            if let Some(filter) = filter {
+                name = format!(
+                    "{} FILTER (WHERE {})",
+                    name,
+                    create_physical_name(filter, false)?
+                );
+            }
+            Ok(name)
+        }
         Expr::AggregateUDF { fun, args } => {
             let mut names = Vec::with_capacity(args.len());
             for e in args {
@@ -207,6 +249,15 @@ fn create_physical_name(e: &Expr, is_first_expr: bool) -> Result<String> {
         Expr::Wildcard => Err(DataFusionError::Internal(
             "Create physical name does not support wildcard".to_string(),
         )),
+        Expr::GroupingSet(_) => Err(DataFusionError::Internal(
+            "Create physical name does not support grouping sets".to_string(),
+        )),
+        Expr::Exists { .. } | Expr::InSubquery { .. } | Expr::ScalarSubquery(_) => {
+            Err(DataFusionError::NotImplemented(
+                "Subquery expressions must be rewritten into joins before physical planning"
+                    .to_string(),
+            ))
+        }
     }
 }
 
@@ -455,11 +506,12 @@ impl DefaultPhysicalPlanner {
                                 _ => unreachable!(),
                             })
                             .collect::<Result<Vec<_>>>()?;
-                        Arc::new(if can_repartition {
+                        let sort = if can_repartition {
                             SortExec::new_with_partitioning(sort_keys, input_exec, true)
                         } else {
                             SortExec::try_new(sort_keys, input_exec)?
-                        })
+                        };
+                        Arc::new(self.configure_sort(sort, ctx_state))
                     };
 
                     let physical_input_schema = input_exec.schema();
@@ -492,6 +544,40 @@ impl DefaultPhysicalPlanner {
                     let physical_input_schema = input_exec.schema();
                     let logical_input_schema = input.as_ref().schema();
 
+                    // feed the rows to aggregates with an ORDER BY in order: a
+                    // single sorted partition keeps the rows of every group
+                    // in order through the partial aggregation
+                    let order_by = aggregate_input_ordering(aggr_expr)?;
+                    let input_exec = if order_by.is_empty() {
+                        input_exec
+                    } else {
+                        let sort_expr = order_by
+                            .iter()
+                            .map(|e| match e {
+                                Expr::Sort {
+                                    expr,
+                                    asc,
+                                    nulls_first,
+                                } => self.create_physical_sort_expr(
+                                    expr,
+                                    logical_input_schema,
+                                    &physical_input_schema,
+                                    SortOptions {
+                                        descending: !*asc,
+                                        nulls_first: *nulls_first,
+                                    },
+                                    ctx_state,
+                                ),
+                                e => Err(DataFusionError::Internal(format!(
+                                    "Expected a sort expression, got {:?}",
+                                    e
+                                ))),
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        self.sort_if_needed(input_exec, sort_expr, false, ctx_state)
+                    };
+
+                    let (group_expr, grouping_sets) = expand_grouping_sets(group_expr)?;
                     let groups = group_expr
                         .iter()
                         .map(|e| {
@@ -518,17 +604,32 @@ impl DefaultPhysicalPlanner {
                         })
                         .collect::<Result<Vec<_>>>()?;
 
-                    let initial_aggr = Arc::new(HashAggregateExec::try_new(
-                        AggregateMode::Partial,
-                        groups.clone(),
-                        aggregates.clone(),
-                        input_exec,
-                        physical_input_schema.clone(),
-                    )?);
+                    let memory_manager = &ctx_state.runtime_env.memory_manager;
+                    let initial_aggr = Arc::new(
+                        HashAggregateExec::try_new(
+                            AggregateMode::Partial,
+                            groups.clone(),
+                            aggregates.clone(),
+                            input_exec,
+                            physical_input_schema.clone(),
+                        )?
+                        .with_grouping_sets(grouping_sets)?
+                        .with_memory_manager(memory_manager.clone())
+                        .with_target_batch_size(ctx_state.config.batch_size),
+                    );
+
+                    // the final aggregation groups by the grouping id like any
+                    // other group column
+                    let mut final_group_names: Vec<String> =
+                        groups.iter().map(|(_, name)| name.clone()).collect();
+                    if !initial_aggr.grouping_sets().is_empty() {
+                        final_group_names.push(GROUPING_ID_COLUMN.to_string());
+                    }
 
                     // update group column indices based on partial aggregate plan evaluation
-                    let final_group: Vec<Arc<dyn PhysicalExpr>> = (0..groups.len())
-                        .map(|i| col(&groups[i].1, &initial_aggr.schema()))
+                    let final_group: Vec<Arc<dyn PhysicalExpr>> = final_group_names
+                        .iter()
+                        .map(|name| col(name, &initial_aggr.schema()))
                         .collect::<Result<_>>()?;
 
                     // TODO: dictionary type not yet supported in Hash Repartition
@@ -537,7 +638,7 @@ impl DefaultPhysicalPlanner {
                         .flat_map(|x| x.0.data_type(physical_input_schema.as_ref()))
                         .any(|x| matches!(x, DataType::Dictionary(_, _)));
 
-                    let can_repartition = !groups.is_empty()
+                    let can_repartition = !final_group.is_empty()
                         && ctx_state.config.target_partitions > 1
                         && ctx_state.config.repartition_aggregations
                         && !contains_dict;
@@ -562,17 +663,17 @@ impl DefaultPhysicalPlanner {
                         (initial_aggr, AggregateMode::Final)
                     };
 
-                    Ok(Arc::new(HashAggregateExec::try_new(
-                        next_partition_mode,
-                        final_group
-                            .iter()
-                            .enumerate()
-                            .map(|(i, expr)| (expr.clone(), groups[i].1.clone()))
-                            .collect(),
-                        aggregates,
-                        initial_aggr,
-                        physical_input_schema.clone(),
-                    )?) )
+                    Ok(Arc::new(
+                        HashAggregateExec::try_new(
+                            next_partition_mode,
+                            final_group.into_iter().zip(final_group_names).collect(),
+                            aggregates,
+                            initial_aggr,
+                            physical_input_schema.clone(),
+                        )?
+                        .with_memory_manager(memory_manager.clone())
+                        .with_target_batch_size(ctx_state.config.batch_size),
+                    ))
                 }
                 LogicalPlan::Projection(Projection { input, expr, .. }) => {
                     let input_exec = self.create_initial_plan(input, ctx_state).await?;
@@ -648,6 +749,23 @@ impl DefaultPhysicalPlanner {
                         .await?;
                     Ok(Arc::new(UnionExec::new(physical_plans)) )
                 }
+                LogicalPlan::RecursiveQuery(RecursiveQuery {
+                    name,
+                    static_term,
+                    recursive_term,
+                    ..
+                }) => {
+                    let static_term =
+                        self.create_initial_plan(static_term, ctx_state).await?;
+                    let recursive_term =
+                        self.create_initial_plan(recursive_term, ctx_state).await?;
+                    Ok(Arc::new(RecursiveQueryExec::try_new(
+                        name.clone(),
+                        static_term,
+                        recursive_term,
+                        ctx_state.config.max_recursive_iterations,
+                    )?) )
+                }
                 LogicalPlan::Repartition(Repartition {
                     input,
                     partitioning_scheme,
@@ -705,7 +823,7 @@ impl DefaultPhysicalPlanner {
                             )),
                         })
                         .collect::<Result<Vec<_>>>()?;
-                    Ok(Arc::new(SortExec::try_new(sort_expr, physical_input)?) )
+                    Ok(self.sort_if_needed(physical_input, sort_expr, false, ctx_state))
                 }
                 LogicalPlan::Join(Join {
                     left,
@@ -728,10 +846,92 @@ impl DefaultPhysicalPlanner {
                             ))
                         })
                         .collect::<Result<join_utils::JoinOn>>()?;
+                    let memory_manager = &ctx_state.runtime_env.memory_manager;
 
-                    if ctx_state.config.target_partitions > 1
-                        && ctx_state.config.repartition_joins
+                    // sort the join keys the same way as `ORDER BY key ASC`, so that
+                    // the output of the join does not need to be sorted again
+                    let sort_options = SortOptions {
+                        descending: false,
+                        nulls_first: false,
+                    };
+                    let sort_expr = |column: &Column| PhysicalSortExpr {
+                        expr: Arc::new(column.clone()),
+                        options: sort_options,
+                    };
+                    let left_sort_expr =
+                        join_on.iter().map(|(l, _)| sort_expr(l)).collect::<Vec<_>>();
+                    let right_sort_expr =
+                        join_on.iter().map(|(_, r)| sort_expr(r)).collect::<Vec<_>>();
+
+                    // Use a sort merge join when the inputs are already sorted on the
+                    // join keys, or when the build side of a hash join would not fit
+                    // in memory
+                    let inputs_sorted = ordering_satisfies(
+                        physical_left.output_ordering(),
+                        &left_sort_expr,
+                    ) && ordering_satisfies(
+                        physical_right.output_ordering(),
+                        &right_sort_expr,
+                    );
+                    let build_side_too_large = matches!(
+                        (
+                            physical_left.statistics().total_byte_size,
+                            ctx_state.config.memory_limit,
+                        ),
+                        (Some(size), Some(limit)) if size > limit
+                    );
+                    let repartition = ctx_state.config.target_partitions > 1
+                        && ctx_state.config.repartition_joins;
+
+                    if inputs_sorted
+                        || build_side_too_large
+                        || ctx_state.config.prefer_sort_merge_join
                     {
+                        let (physical_left, physical_right) = if repartition
+                            && !inputs_sorted
+                        {
+                            (
+                                self.partition_and_sort(
+                                    physical_left,
+                                    left_sort_expr,
+                                    ctx_state,
+                                )?,
+                                self.partition_and_sort(
+                                    physical_right,
+                                    right_sort_expr,
+                                    ctx_state,
+                                )?,
+                            )
+                        } else {
+                            (
+                                self.sort_if_needed(
+                                    physical_left,
+                                    left_sort_expr,
+                                    false,
+                                    ctx_state,
+                                ),
+                                self.sort_if_needed(
+                                    physical_right,
+                                    right_sort_expr,
+                                    false,
+                                    ctx_state,
+                                ),
+                            )
+                        };
+
+                        let sort_options = vec![sort_options; join_on.len()];
+                        Ok(Arc::new(
+                            SortMergeJoinExec::try_new(
+                                physical_left,
+                                physical_right,
+                                join_on,
+                                join_type,
+                                sort_options,
+                                null_equals_null,
+                            )?
+                            .with_batch_size(ctx_state.config.batch_size),
+                        ))
+                    } else if repartition {
                         let (left_expr, right_expr) = join_on
                             .iter()
                             .map(|(l, r)| {
@@ -762,7 +962,8 @@ impl DefaultPhysicalPlanner {
                             join_type,
                             PartitionMode::Partitioned,
                             null_equals_null,
-                        )?))
+                        )?
+                        .with_memory_manager(memory_manager.clone())))
                     } else {
                         Ok(Arc::new(HashJoinExec::try_new(
                             physical_left,
@@ -771,13 +972,18 @@ impl DefaultPhysicalPlanner {
                             join_type,
                             PartitionMode::CollectLeft,
                             null_equals_null,
-                        )?))
+                        )?
+                        .with_memory_manager(memory_manager.clone())))
                     }
                 }
                 LogicalPlan::CrossJoin(CrossJoin { left, right, .. }) => {
                     let left = self.create_initial_plan(left, ctx_state).await?;
                     let right = self.create_initial_plan(right, ctx_state).await?;
-                    Ok(Arc::new(CrossJoinExec::try_new(left, right)?))
+                    Ok(Arc::new(
+                        CrossJoinExec::try_new(left, right)?.with_memory_manager(
+                            ctx_state.runtime_env.memory_manager.clone(),
+                        ),
+                    ))
                 }
                 LogicalPlan::EmptyRelation(EmptyRelation {
                     produce_one_row,
@@ -801,6 +1007,22 @@ impl DefaultPhysicalPlanner {
 
                     Ok(Arc::new(GlobalLimitExec::new(input, limit)))
                 }
+                LogicalPlan::Insert(Insert {
+                    table,
+                    input,
+                    overwrite,
+                    ..
+                }) => {
+                    let input = self.create_initial_plan(input, ctx_state).await?;
+                    table.insert_into(input, *overwrite).await
+                }
+                LogicalPlan::CopyTo(CopyTo {
+                    input, path, format, ..
+                }) => {
+                    let input = self.create_initial_plan(input, ctx_state).await?;
+                    let sink = Arc::new(DirectorySink::new(path.clone(), format.clone()));
+                    Ok(Arc::new(InsertExec::new(input, sink)))
+                }
                 LogicalPlan::CreateExternalTable(_) => {
                     // There is no default plan for "CREATE EXTERNAL
                     // TABLE" -- it must be handled at a higher level (so
@@ -810,7 +1032,11 @@ impl DefaultPhysicalPlanner {
                         "Unsupported logical plan: CreateExternalTable".to_string(),
                     ))
                 }
-                | LogicalPlan::CreateMemoryTable(_) | LogicalPlan::DropTable (_) => {
+                | LogicalPlan::CreateMemoryTable(_)
+                | LogicalPlan::DropTable(_)
+                | LogicalPlan::CreateView(_)
+                | LogicalPlan::DropView(_)
+                | LogicalPlan::RefreshTable(_) => {
                     // Create a dummy exec.
                     Ok(Arc::new(EmptyExec::new(
                         false,
@@ -1234,12 +1460,6 @@ impl DefaultPhysicalPlanner {
                         )),
                     })
                     .collect::<Result<Vec<_>>>()?;
-                if window_frame.is_some() {
-                    return Err(DataFusionError::NotImplemented(
-                            "window expression with window frame definition is not yet supported"
-                                .to_owned(),
-                        ));
-                }
                 windows::create_window_expr(
                     fun,
                     name,
@@ -1293,6 +1513,7 @@ impl DefaultPhysicalPlanner {
                 fun,
                 distinct,
                 args,
+                filter,
                 ..
             } => {
                 let args = args
@@ -1306,13 +1527,31 @@ impl DefaultPhysicalPlanner {
                         )
                     })
                     .collect::<Result<Vec<_>>>()?;
-                aggregates::create_aggregate_expr(
+                let aggregate = aggregates::create_aggregate_expr(
                     fun,
                     *distinct,
                     &args,
                     physical_input_schema,
                     name,
-                )
+                )?;
+                // the ordering is taken care of by sorting the input of the
+                // aggregation, see `aggregate_input_ordering`
+                match filter {
+                    Some(filter) => {
+                        let predicate = self.create_physical_expr(
+                            filter,
+                            logical_input_schema,
+                            physical_input_schema,
+                            ctx_state,
+                        )?;
+                        Ok(Arc::new(AggregateFilter::try_new(
+                            aggregate,
+                            predicate,
+                            physical_input_schema,
+                        )?))
+                    }
+                    None => Ok(aggregate),
+                }
             }
             Expr::AggregateUDF { fun, args, .. } => {
                 let args = args
@@ -1379,6 +1618,67 @@ impl DefaultPhysicalPlanner {
         })
     }
 
+    /// Hash partitions `input` on the expressions of `sort_expr` and sorts
+    /// each partition
+    fn partition_and_sort(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        sort_expr: Vec<PhysicalSortExpr>,
+        ctx_state: &ExecutionContextState,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let exprs = sort_expr.iter().map(|e| e.expr.clone()).collect();
+        let partitioned = RepartitionExec::try_new(
+            input,
+            Partitioning::Hash(exprs, ctx_state.config.target_partitions),
+        )?;
+        Ok(self.sort_if_needed(Arc::new(partitioned), sort_expr, true, ctx_state))
+    }
+
+    /// Sorts `input` on `sort_expr` unless it is already sorted. The
+    /// partitions of `input` are sorted separately if
+    /// `preserve_partitioning` is true, and merged otherwise.
+    fn sort_if_needed(
+        &self,
+        input: Arc<dyn ExecutionPlan>,
+        sort_expr: Vec<PhysicalSortExpr>,
+        preserve_partitioning: bool,
+        ctx_state: &ExecutionContextState,
+    ) -> Arc<dyn ExecutionPlan> {
+        if !ordering_satisfies(input.output_ordering(), &sort_expr) {
+            let sort =
+                SortExec::new_with_partitioning(sort_expr, input, preserve_partitioning);
+            Arc::new(self.configure_sort(sort, ctx_state))
+        } else if preserve_partitioning
+            || input.output_partitioning().partition_count() == 1
+        {
+            input
+        } else {
+            // merge the sorted partitions
+            Arc::new(SortPreservingMergeExec::new(
+                sort_expr,
+                input,
+                ctx_state.config.batch_size,
+            ))
+        }
+    }
+
+    /// Applies the memory and spilling settings of the execution context to
+    /// a sort
+    fn configure_sort(
+        &self,
+        sort: SortExec,
+        ctx_state: &ExecutionContextState,
+    ) -> SortExec {
+        let config = &ctx_state.config;
+        let sort = sort.with_memory_manager(ctx_state.runtime_env.memory_manager.clone());
+        match config.sort_spill_threshold {
+            Some(spill_threshold) => sort
+                .with_spill_threshold(spill_threshold)
+                .with_target_batch_size(config.batch_size),
+            None => sort,
+        }
+    }
+
     /// Handles capturing the various plans for EXPLAIN queries
     ///
     /// Returns
@@ -1456,6 +1756,32 @@ fn tuple_err<T, R>(value: (Result<T>, Result<R>)) -> Result<(T, R)> {
     }
 }
 
+/// The sort expressions the input of an aggregation must be sorted on for
+/// the aggregate expressions `aggr_expr` with an `ORDER BY`, which must all
+/// have the same ordering as the input can only be sorted once.
+fn aggregate_input_ordering(aggr_expr: &[Expr]) -> Result<Vec<Expr>> {
+    let mut ordering: Vec<Expr> = vec![];
+    for e in aggr_expr {
+        let e = match e {
+            Expr::Alias(e, _) => e.as_ref(),
+            e => e,
+        };
+        if let Expr::AggregateFunction { order_by, .. } = e {
+            if order_by.is_empty() || *order_by == ordering {
+                continue;
+            }
+            if !ordering.is_empty() {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Aggregate functions with different orderings are not supported, got ORDER BY {:?} and ORDER BY {:?}",
+                    ordering, order_by
+                )));
+            }
+            ordering = order_by.clone();
+        }
+    }
+    Ok(ordering)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -34,7 +34,7 @@ use arrow::datatypes::{Field, Schema, SchemaRef};
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
 
-use super::expressions::Column;
+use super::expressions::{Column, PhysicalSortExpr};
 use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
 use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
 use async_trait::async_trait;
@@ -50,6 +50,8 @@ pub struct ProjectionExec {
     schema: SchemaRef,
     /// The input plan
     input: Arc<dyn ExecutionPlan>,
+    /// The ordering of the input that is still present in the output
+    output_ordering: Option<Vec<PhysicalSortExpr>>,
     /// Execution metrics
     metrics: ExecutionPlanMetricsSet,
 }
@@ -81,10 +83,13 @@ impl ProjectionExec {
             input_schema.metadata().clone(),
         ));
 
+        let output_ordering = project_ordering(input.output_ordering(), &expr);
+
         Ok(Self {
             expr,
             schema,
             input: input.clone(),
+            output_ordering,
             metrics: ExecutionPlanMetricsSet::new(),
         })
     }
@@ -100,6 +105,29 @@ impl ProjectionExec {
     }
 }
 
+/// Rewrites the longest prefix of the input `ordering` whose columns are
+/// projected as-is by `expr` in terms of the output columns
+fn project_ordering(
+    ordering: Option<&[PhysicalSortExpr]>,
+    expr: &[(Arc<dyn PhysicalExpr>, String)],
+) -> Option<Vec<PhysicalSortExpr>> {
+    let projected = ordering?
+        .iter()
+        .map_while(|sort_expr| {
+            let column = sort_expr.expr.as_any().downcast_ref::<Column>()?;
+            let index = expr
+                .iter()
+                .position(|(e, _)| e.as_any().downcast_ref::<Column>() == Some(column))?;
+            Some(PhysicalSortExpr {
+                expr: Arc::new(Column::new(&expr[index].1, index)),
+                options: sort_expr.options,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    (!projected.is_empty()).then(|| projected)
+}
+
 #[async_trait]
 impl ExecutionPlan for ProjectionExec {
     /// Return a reference to Any that can be used for downcasting
@@ -121,6 +149,10 @@ impl ExecutionPlan for ProjectionExec {
         self.input.output_partitioning()
     }
 
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.output_ordering.as_deref()
+    }
+
     fn with_new_children(
         &self,
         children: Vec<Arc<dyn ExecutionPlan>>,
@@ -309,6 +341,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -0,0 +1,413 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the recursive query plan, evaluating a recursive common table
+//! expression (`WITH RECURSIVE`)
+
+use std::any::Any;
+use std::sync::Arc;
+
+use arrow::datatypes::SchemaRef;
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+use super::metrics::{
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+};
+use super::stream::RecordBatchReceiverStream;
+use super::work_table::{WorkTable, WorkTableExec};
+use super::{
+    execute_stream, DisplayFormatType, ExecutionPlan, Partitioning,
+    SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+
+/// Execution plan of a recursive query.
+///
+/// The static term is evaluated first, and its output is stored in the work
+/// table. The recursive term, which scans the work table, is then evaluated
+/// repeatedly, each iteration replacing the work table with its output,
+/// until an iteration produces no rows. The query returns the output of all
+/// iterations (`UNION ALL` semantics).
+///
+/// As a recursive query does not necessarily reach a fixpoint, it fails
+/// after `max_iterations` iterations of the recursive term.
+#[derive(Debug)]
+pub struct RecursiveQueryExec {
+    /// Name of the recursive query
+    name: String,
+    /// The term producing the initial rows
+    static_term: Arc<dyn ExecutionPlan>,
+    /// The term evaluated on the rows of the previous iteration
+    recursive_term: Arc<dyn ExecutionPlan>,
+    /// The work table scanned by the recursive term
+    work_table: Arc<WorkTable>,
+    /// Maximum number of iterations of the recursive term
+    max_iterations: usize,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl RecursiveQueryExec {
+    /// Create a new recursive query, whose recursive term scans its work
+    /// table with [`WorkTableExec`]
+    pub fn try_new(
+        name: String,
+        static_term: Arc<dyn ExecutionPlan>,
+        recursive_term: Arc<dyn ExecutionPlan>,
+        max_iterations: usize,
+    ) -> Result<Self> {
+        if static_term.schema().fields().len() != recursive_term.schema().fields().len() {
+            return Err(DataFusionError::Plan(format!(
+                "The static and recursive terms of recursive query {} must \
+                produce the same number of columns",
+                name
+            )));
+        }
+
+        let work_table = Arc::new(WorkTable::new());
+        let recursive_term = assign_work_table(recursive_term, &name, &work_table)?;
+        Ok(Self {
+            name,
+            static_term,
+            recursive_term,
+            work_table,
+            max_iterations,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    /// Name of the recursive query
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The term producing the initial rows
+    pub fn static_term(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.static_term
+    }
+
+    /// The term evaluated on the rows of the previous iteration
+    pub fn recursive_term(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.recursive_term
+    }
+
+    /// Maximum number of iterations of the recursive term
+    pub fn max_iterations(&self) -> usize {
+        self.max_iterations
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for RecursiveQueryExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.static_term.schema()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.static_term.clone(), self.recursive_term.clone()]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            2 => Ok(Arc::new(RecursiveQueryExec::try_new(
+                self.name.clone(),
+                children[0].clone(),
+                children[1].clone(),
+                self.max_iterations,
+            )?)),
+            _ => Err(DataFusionError::Internal(
+                "RecursiveQueryExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "RecursiveQueryExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+
+        let (sender, receiver) = mpsc::channel(2);
+        let iteration = RecursiveIteration {
+            name: self.name.clone(),
+            schema: self.schema(),
+            static_term: self.static_term.clone(),
+            recursive_term: self.recursive_term.clone(),
+            work_table: self.work_table.clone(),
+            max_iterations: self.max_iterations,
+            sender: sender.clone(),
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+            iterations: MetricBuilder::new(&self.metrics)
+                .counter("iterations", partition),
+        };
+
+        let join_handle = tokio::spawn(async move {
+            if let Err(e) = iteration.run().await {
+                // If send fails, plan being torn down,
+                // there is no place to send the error
+                let arrow_error = ArrowError::ExternalError(Box::new(e));
+                sender.send(Err(arrow_error)).await.ok();
+            }
+        });
+
+        Ok(RecordBatchReceiverStream::create(
+            &self.schema(),
+            receiver,
+            join_handle,
+        ))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "RecursiveQueryExec: name={}, max_iterations={}",
+                    self.name, self.max_iterations
+                )
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+/// Evaluates the terms of a recursive query, sending their output to the
+/// output stream of [`RecursiveQueryExec`]
+struct RecursiveIteration {
+    name: String,
+    schema: SchemaRef,
+    static_term: Arc<dyn ExecutionPlan>,
+    recursive_term: Arc<dyn ExecutionPlan>,
+    work_table: Arc<WorkTable>,
+    max_iterations: usize,
+    sender: mpsc::Sender<ArrowResult<RecordBatch>>,
+    baseline_metrics: BaselineMetrics,
+    iterations: Count,
+}
+
+impl RecursiveIteration {
+    async fn run(self) -> Result<()> {
+        let mut batches = match self.emit(self.static_term.clone()).await? {
+            Some(batches) => batches,
+            None => return Ok(()),
+        };
+
+        let mut iteration = 0;
+        while batches.iter().any(|batch| batch.num_rows() > 0) {
+            if iteration == self.max_iterations {
+                return Err(DataFusionError::Execution(format!(
+                    "Recursive query {} did not complete after {} iterations",
+                    self.name, self.max_iterations
+                )));
+            }
+            iteration += 1;
+            self.iterations.add(1);
+
+            self.work_table.update(batches);
+            // operators may keep state between executions (e.g. the build
+            // side of a hash join), the plan is recreated for every iteration
+            let recursive_term = reset_plan_states(self.recursive_term.clone())?;
+            batches = match self.emit(recursive_term).await? {
+                Some(batches) => batches,
+                None => return Ok(()),
+            };
+        }
+
+        self.baseline_metrics.done();
+        Ok(())
+    }
+
+    /// Sends the output of `plan` to the output stream and returns it, or
+    /// `None` if the output stream was dropped
+    async fn emit(
+        &self,
+        plan: Arc<dyn ExecutionPlan>,
+    ) -> Result<Option<Vec<RecordBatch>>> {
+        let mut stream = execute_stream(plan).await?;
+        let mut batches = vec![];
+        while let Some(batch) = stream.next().await {
+            // the columns of the recursive term may be named differently
+            let batch =
+                RecordBatch::try_new(self.schema.clone(), batch?.columns().to_vec())?;
+            self.baseline_metrics.record_output(batch.num_rows());
+            if self.sender.send(Ok(batch.clone())).await.is_err() {
+                return Ok(None);
+            }
+            batches.push(batch);
+        }
+        Ok(Some(batches))
+    }
+}
+
+/// Makes the scans of the work table of the recursive query `name` in
+/// `plan` read `work_table`
+fn assign_work_table(
+    plan: Arc<dyn ExecutionPlan>,
+    name: &str,
+    work_table: &Arc<WorkTable>,
+) -> Result<Arc<dyn ExecutionPlan>> {
+    if let Some(exec) = plan.as_any().downcast_ref::<WorkTableExec>() {
+        if exec.name() == name {
+            return Ok(Arc::new(exec.with_work_table(work_table.clone())));
+        }
+    }
+
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(plan);
+    }
+    let children = children
+        .into_iter()
+        .map(|child| assign_work_table(child, name, work_table))
+        .collect::<Result<Vec<_>>>()?;
+    plan.with_new_children(children)
+}
+
+/// Recreates every operator of `plan` that has inputs, discarding the state
+/// they keep from their previous execution
+fn reset_plan_states(plan: Arc<dyn ExecutionPlan>) -> Result<Arc<dyn ExecutionPlan>> {
+    let children = plan.children();
+    if children.is_empty() {
+        return Ok(plan);
+    }
+    let children = children
+        .into_iter()
+        .map(reset_plan_states)
+        .collect::<Result<Vec<_>>>()?;
+    plan.with_new_children(children)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logical_plan::Operator;
+    use crate::physical_plan::common;
+    use crate::physical_plan::expressions::{binary, col, lit};
+    use crate::physical_plan::filter::FilterExec;
+    use crate::physical_plan::memory::MemoryExec;
+    use crate::physical_plan::projection::ProjectionExec;
+    use crate::scalar::ScalarValue;
+    use arrow::array::{Array, Int64Array};
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// Counts from 1 to `n`
+    fn counter(n: i64, max_iterations: usize) -> Result<RecursiveQueryExec> {
+        let schema = Arc::new(Schema::new(vec![Field::new("n", DataType::Int64, false)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int64Array::from(vec![1]))],
+        )?;
+        let static_term =
+            Arc::new(MemoryExec::try_new(&[vec![batch]], schema.clone(), None)?);
+
+        // SELECT n + 1 FROM t WHERE n < <n>
+        let work_table = Arc::new(WorkTableExec::try_new(
+            "t".to_string(),
+            schema.clone(),
+            None,
+        )?);
+        let predicate = binary(
+            col("n", &schema)?,
+            Operator::Lt,
+            lit(ScalarValue::Int64(Some(n))),
+            &schema,
+        )?;
+        let filter = Arc::new(FilterExec::try_new(predicate, work_table)?);
+        let increment = binary(
+            col("n", &schema)?,
+            Operator::Plus,
+            lit(ScalarValue::Int64(Some(1))),
+            &schema,
+        )?;
+        let recursive_term = Arc::new(ProjectionExec::try_new(
+            vec![(increment, "n".to_string())],
+            filter,
+        )?);
+
+        RecursiveQueryExec::try_new(
+            "t".to_string(),
+            static_term,
+            recursive_term,
+            max_iterations,
+        )
+    }
+
+    #[tokio::test]
+    async fn recursive_query() -> Result<()> {
+        let exec = counter(10, 100)?;
+        let batches = common::collect(exec.execute(0).await?).await?;
+
+        let values = batches
+            .iter()
+            .flat_map(|batch| {
+                let array = batch
+                    .column(0)
+                    .as_any()
+                    .downcast_ref::<Int64Array>()
+                    .unwrap();
+                (0..array.len()).map(|i| array.value(i)).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, (1..=10).collect::<Vec<_>>());
+
+        let metrics = exec.metrics().unwrap();
+        assert_eq!(metrics.output_rows(), Some(10));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn iteration_limit() -> Result<()> {
+        let exec = counter(10, 5)?;
+        let err = common::collect(exec.execute(0).await?).await.unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Recursive query t did not complete after 5 iterations"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
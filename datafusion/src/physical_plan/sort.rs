@@ -17,12 +17,18 @@
 
 //! Defines the SORT plan
 
-use super::common::AbortOnDropSingle;
+use super::common::{
+    batch_memory_size, AbortOnDropMany, AbortOnDropSingle, SizedRecordBatchStream,
+};
 use super::metrics::{
-    BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet, RecordOutput,
+    BaselineMetrics, Count, ExecutionPlanMetricsSet, MetricBuilder, MetricsSet,
+    RecordOutput, Time,
 };
+use super::sort_preserving_merge::SortPreservingMergeStream;
+use super::spill::{read_spill, spill_batches};
 use super::{RecordBatchStream, SendableRecordBatchStream, Statistics};
 use crate::error::{DataFusionError, Result};
+use crate::execution::memory_manager::{MemoryManager, MemoryReservation};
 use crate::physical_plan::expressions::PhysicalSortExpr;
 use crate::physical_plan::{
     common, DisplayFormatType, Distribution, ExecutionPlan, Partitioning,
@@ -32,17 +38,31 @@ use arrow::compute::{lexsort_to_indices, take, SortColumn, TakeOptions};
 use arrow::datatypes::SchemaRef;
 use arrow::error::Result as ArrowResult;
 use arrow::record_batch::RecordBatch;
-use arrow::{array::ArrayRef, error::ArrowError};
+use arrow::{
+    array::{Array, ArrayRef, UInt32Array},
+    error::ArrowError,
+};
 use async_trait::async_trait;
-use futures::stream::Stream;
+use futures::channel::mpsc;
+use futures::stream::{Stream, StreamExt};
 use futures::Future;
 use pin_project_lite::pin_project;
 use std::any::Any;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use tempfile::NamedTempFile;
 
 /// Sort execution plan
+///
+/// The input is buffered in memory and sorted at once if it fits. The
+/// buffered input is instead sorted and written to a temporary Arrow IPC
+/// file whenever it exceeds the spill threshold (see
+/// [`with_spill_threshold`](Self::with_spill_threshold)), can not be
+/// reserved from the memory manager (see
+/// [`with_memory_manager`](Self::with_memory_manager)), or the memory
+/// manager asks for memory back. The resulting sorted runs are merged
+/// once the input is exhausted.
 #[derive(Debug)]
 pub struct SortExec {
     /// Input schema
@@ -53,6 +73,14 @@ pub struct SortExec {
     metrics: ExecutionPlanMetricsSet,
     /// Preserve partitions of input plan
     preserve_partitioning: bool,
+    /// Number of bytes of input to buffer before spilling a sorted run
+    /// to disk, or `None` to never spill
+    spill_threshold: Option<usize>,
+    /// The target size of spilled batches and of the batches yielded
+    /// when merging spilled runs
+    target_batch_size: usize,
+    /// Memory pool the buffered input is reserved from
+    memory_manager: Arc<MemoryManager>,
 }
 
 impl SortExec {
@@ -76,9 +104,35 @@ impl SortExec {
             input,
             metrics: ExecutionPlanMetricsSet::new(),
             preserve_partitioning,
+            spill_threshold: None,
+            target_batch_size: 8192,
+            memory_manager: MemoryManager::unbounded(),
         }
     }
 
+    /// Spill sorted runs to disk whenever more than `spill_threshold`
+    /// bytes of input are buffered in memory
+    pub fn with_spill_threshold(mut self, spill_threshold: usize) -> Self {
+        self.spill_threshold = Some(spill_threshold);
+        self
+    }
+
+    /// Customize the target size of spilled batches and of the batches
+    /// yielded when merging spilled runs
+    pub fn with_target_batch_size(mut self, target_batch_size: usize) -> Self {
+        // batch size must be greater than zero
+        assert!(target_batch_size > 0);
+        self.target_batch_size = target_batch_size;
+        self
+    }
+
+    /// Reserve the memory of the buffered input from `memory_manager`,
+    /// spilling sorted runs to disk when the reservation can not grow
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = memory_manager;
+        self
+    }
+
     /// Input schema
     pub fn input(&self) -> &Arc<dyn ExecutionPlan> {
         &self.input
@@ -88,6 +142,11 @@ impl SortExec {
     pub fn expr(&self) -> &[PhysicalSortExpr] {
         &self.expr
     }
+
+    /// Number of bytes of input buffered before spilling, if spilling is enabled
+    pub fn spill_threshold(&self) -> Option<usize> {
+        self.spill_threshold
+    }
 }
 
 #[async_trait]
@@ -114,6 +173,10 @@ impl ExecutionPlan for SortExec {
         }
     }
 
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        Some(&self.expr)
+    }
+
     fn required_child_distribution(&self) -> Distribution {
         if self.preserve_partitioning {
             Distribution::UnspecifiedDistribution
@@ -127,10 +190,17 @@ impl ExecutionPlan for SortExec {
         children: Vec<Arc<dyn ExecutionPlan>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
         match children.len() {
-            1 => Ok(Arc::new(SortExec::try_new(
-                self.expr.clone(),
-                children[0].clone(),
-            )?)),
+            1 => {
+                let mut sort = SortExec::new_with_partitioning(
+                    self.expr.clone(),
+                    children[0].clone(),
+                    self.preserve_partitioning,
+                );
+                sort.spill_threshold = self.spill_threshold;
+                sort.target_batch_size = self.target_batch_size;
+                sort.memory_manager = self.memory_manager.clone();
+                Ok(Arc::new(sort))
+            }
             _ => Err(DataFusionError::Internal(
                 "SortExec wrong number of children".to_string(),
             )),
@@ -155,12 +225,23 @@ impl ExecutionPlan for SortExec {
         }
 
         let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+        let spill = SpillConfig {
+            threshold: self.spill_threshold,
+            target_batch_size: self.target_batch_size,
+            spill_count: MetricBuilder::new(&self.metrics).spill_count(partition),
+            spilled_bytes: MetricBuilder::new(&self.metrics).spilled_bytes(partition),
+        };
+        let reservation = self
+            .memory_manager
+            .register_consumer(format!("SortExec[{}]", partition), true);
         let input = self.input.execute(partition).await?;
 
         Ok(Box::pin(SortStream::new(
             input,
             self.expr.clone(),
             baseline_metrics,
+            spill,
+            reservation,
         )))
     }
 
@@ -172,7 +253,11 @@ impl ExecutionPlan for SortExec {
         match t {
             DisplayFormatType::Default => {
                 let expr: Vec<String> = self.expr.iter().map(|e| e.to_string()).collect();
-                write!(f, "SortExec: [{}]", expr.join(","))
+                write!(f, "SortExec: [{}]", expr.join(","))?;
+                if let Some(spill_threshold) = self.spill_threshold {
+                    write!(f, ", spill_threshold={}", spill_threshold)?;
+                }
+                Ok(())
             }
         }
     }
@@ -191,16 +276,58 @@ fn sort_batch(
     schema: SchemaRef,
     expr: &[PhysicalSortExpr],
 ) -> ArrowResult<RecordBatch> {
+    let indices = sort_indices(&batch, expr)?;
+    take_batch(&batch, schema, &indices)
+}
+
+/// Sorts `batches` as a single run, returning the sorted rows split
+/// into batches of at most `batch_size` rows
+fn sort_batches(
+    batches: &[RecordBatch],
+    schema: SchemaRef,
+    expr: &[PhysicalSortExpr],
+    batch_size: usize,
+) -> ArrowResult<Vec<RecordBatch>> {
+    let combined = match common::combine_batches(batches, schema.clone())? {
+        Some(combined) => combined,
+        None => return Ok(vec![]),
+    };
+    let indices = sort_indices(&combined, expr)?;
+
+    (0..indices.len())
+        .step_by(batch_size)
+        .map(|offset| {
+            let len = batch_size.min(indices.len() - offset);
+            let chunk = indices.slice(offset, len);
+            let chunk = chunk
+                .as_any()
+                .downcast_ref::<UInt32Array>()
+                .expect("sort indices are UInt32");
+            take_batch(&combined, schema.clone(), chunk)
+        })
+        .collect()
+}
+
+fn sort_indices(
+    batch: &RecordBatch,
+    expr: &[PhysicalSortExpr],
+) -> ArrowResult<UInt32Array> {
     // TODO: pushup the limit expression to sort
-    let indices = lexsort_to_indices(
+    lexsort_to_indices(
         &expr
             .iter()
-            .map(|e| e.evaluate_to_sort_column(&batch))
+            .map(|e| e.evaluate_to_sort_column(batch))
             .collect::<Result<Vec<SortColumn>>>()
             .map_err(DataFusionError::into_arrow_external_error)?,
         None,
-    )?;
+    )
+}
 
+fn take_batch(
+    batch: &RecordBatch,
+    schema: SchemaRef,
+    indices: &UInt32Array,
+) -> ArrowResult<RecordBatch> {
     // reorder all rows based on sorted indices
     RecordBatch::try_new(
         schema,
@@ -210,7 +337,7 @@ fn sort_batch(
             .map(|column| {
                 take(
                     column.as_ref(),
-                    &indices,
+                    indices,
                     // disable bound check overhead since indices are already generated from
                     // the same record batch
                     Some(TakeOptions {
@@ -222,11 +349,156 @@ fn sort_batch(
     )
 }
 
+/// Settings and metrics for spilling the sorted runs of a [`SortExec`]
+/// partition to disk
+struct SpillConfig {
+    /// Number of bytes of input to buffer before spilling a sorted run,
+    /// if any
+    threshold: Option<usize>,
+    /// The target size of spilled batches and merged output batches
+    target_batch_size: usize,
+    /// Number of sorted runs spilled to disk
+    spill_count: Count,
+    /// Number of bytes spilled to disk
+    spilled_bytes: Count,
+}
+
+/// Sorts the buffered batches as a single run and writes it to disk,
+/// returning their memory to the memory pool
+fn spill_run(
+    buffered: &mut Vec<RecordBatch>,
+    reservation: &mut MemoryReservation,
+    schema: &SchemaRef,
+    expr: &[PhysicalSortExpr],
+    spill: &SpillConfig,
+    elapsed_compute: &Time,
+) -> Result<NamedTempFile> {
+    let timer = elapsed_compute.timer();
+    let sorted = sort_batches(buffered, schema.clone(), expr, spill.target_batch_size)?;
+    timer.done();
+
+    let (file, bytes) = spill_batches(&sorted, schema)?;
+    spill.spill_count.add(1);
+    spill.spilled_bytes.add(bytes);
+
+    buffered.clear();
+    reservation.free();
+    Ok(file)
+}
+
+/// Sorts all of `input`, spilling sorted runs to disk when the buffered
+/// input exceeds the spill threshold or can not be reserved from the
+/// memory pool, and returns a stream of the sorted output along with the
+/// memory it still holds
+async fn do_sort(
+    mut input: SendableRecordBatchStream,
+    expr: Vec<PhysicalSortExpr>,
+    baseline_metrics: BaselineMetrics,
+    spill: SpillConfig,
+    mut reservation: MemoryReservation,
+) -> Result<(SendableRecordBatchStream, MemoryReservation)> {
+    let schema = input.schema();
+    let elapsed_compute = baseline_metrics.elapsed_compute().clone();
+
+    let mut buffered = vec![];
+    let mut spills = vec![];
+
+    while let Some(batch) = input.next().await {
+        let batch = batch?;
+        let size = batch_memory_size(&batch);
+
+        // make room for the batch, spilling the buffered rows if needed
+        if let Err(e) = reservation.try_grow(size) {
+            if buffered.is_empty() {
+                return Err(e);
+            }
+            spills.push(spill_run(
+                &mut buffered,
+                &mut reservation,
+                &schema,
+                &expr,
+                &spill,
+                &elapsed_compute,
+            )?);
+            reservation.try_grow(size)?;
+        }
+        buffered.push(batch);
+
+        let over_threshold = spill
+            .threshold
+            .map(|threshold| reservation.size() > threshold)
+            .unwrap_or(false);
+        if over_threshold || reservation.take_spill_request() {
+            spills.push(spill_run(
+                &mut buffered,
+                &mut reservation,
+                &schema,
+                &expr,
+                &spill,
+                &elapsed_compute,
+            )?);
+        }
+    }
+
+    if spills.is_empty() {
+        // everything fit in memory: combine all record batches
+        // into one for each column and sort them at once
+        let timer = elapsed_compute.timer();
+        let result = common::combine_batches(&buffered, schema.clone())?
+            .map(|batch| sort_batch(batch, schema.clone(), &expr))
+            .transpose()?
+            .record_output(&baseline_metrics);
+        timer.done();
+
+        let sorted = SizedRecordBatchStream::new(
+            schema,
+            result.into_iter().map(Arc::new).collect(),
+        );
+        return Ok((Box::pin(sorted), reservation));
+    }
+
+    // merge the sorted runs on disk with what is left in memory
+    let (mut receivers, join_handles): (Vec<_>, Vec<_>) =
+        spills.into_iter().map(read_spill).unzip();
+
+    if !buffered.is_empty() {
+        let timer = elapsed_compute.timer();
+        let sorted =
+            sort_batches(&buffered, schema.clone(), &expr, spill.target_batch_size)?;
+        timer.done();
+
+        let (mut sender, receiver) = mpsc::channel(sorted.len());
+        for batch in sorted {
+            sender.try_send(Ok(batch)).map_err(|e| {
+                DataFusionError::Internal(format!(
+                    "Failed to buffer sorted in-memory run: {}",
+                    e
+                ))
+            })?;
+        }
+        receivers.push(receiver);
+    }
+
+    let merged = SortPreservingMergeStream::new(
+        receivers,
+        AbortOnDropMany(join_handles),
+        schema,
+        &expr,
+        spill.target_batch_size,
+        baseline_metrics,
+    );
+    Ok((Box::pin(merged), reservation))
+}
+
 pin_project! {
     /// stream for sort plan
     struct SortStream {
         #[pin]
-        output: futures::channel::oneshot::Receiver<ArrowResult<Option<RecordBatch>>>,
+        output: futures::channel::oneshot::Receiver<
+            ArrowResult<(SendableRecordBatchStream, MemoryReservation)>,
+        >,
+        sorted: Option<SendableRecordBatchStream>,
+        reservation: Option<MemoryReservation>,
         finished: bool,
         schema: SchemaRef,
         drop_helper: AbortOnDropSingle<()>,
@@ -238,33 +510,24 @@ impl SortStream {
         input: SendableRecordBatchStream,
         expr: Vec<PhysicalSortExpr>,
         baseline_metrics: BaselineMetrics,
+        spill: SpillConfig,
+        reservation: MemoryReservation,
     ) -> Self {
         let (tx, rx) = futures::channel::oneshot::channel();
         let schema = input.schema();
         let join_handle = tokio::spawn(async move {
-            let schema = input.schema();
-            let sorted_batch = common::collect(input)
+            let sorted = do_sort(input, expr, baseline_metrics, spill, reservation)
                 .await
-                .map_err(DataFusionError::into_arrow_external_error)
-                .and_then(move |batches| {
-                    let timer = baseline_metrics.elapsed_compute().timer();
-                    // combine all record batches into one for each column
-                    let combined = common::combine_batches(&batches, schema.clone())?;
-                    // sort combined record batch
-                    let result = combined
-                        .map(|batch| sort_batch(batch, schema, &expr))
-                        .transpose()?
-                        .record_output(&baseline_metrics);
-                    timer.done();
-                    Ok(result)
-                });
+                .map_err(DataFusionError::into_arrow_external_error);
 
             // failing here is OK, the receiver is gone and does not care about the result
-            tx.send(sorted_batch).ok();
+            tx.send(sorted).ok();
         });
 
         Self {
             output: rx,
+            sorted: None,
+            reservation: None,
             finished: false,
             schema,
             drop_helper: AbortOnDropSingle::new(join_handle),
@@ -280,21 +543,47 @@ impl Stream for SortStream {
             return Poll::Ready(None);
         }
 
-        // is the output ready?
         let this = self.project();
+
+        // once sorted, yield from the sorted output
+        if let Some(sorted) = this.sorted.as_mut() {
+            let poll = sorted.poll_next_unpin(cx);
+            if let Poll::Ready(None) = poll {
+                *this.finished = true;
+                // the sorted output is gone, release its memory
+                *this.reservation = None;
+            }
+            return poll;
+        }
+
+        // is the output ready?
         let output_poll = this.output.poll(cx);
 
         match output_poll {
             Poll::Ready(result) => {
-                *this.finished = true;
-
                 // check for error in receiving channel and unwrap actual result
-                let result = match result {
-                    Err(e) => Some(Err(ArrowError::ExternalError(Box::new(e)))), // error receiving
-                    Ok(result) => result.transpose(),
-                };
-
-                Poll::Ready(result)
+                match result {
+                    Err(e) => {
+                        // error receiving
+                        *this.finished = true;
+                        Poll::Ready(Some(Err(ArrowError::ExternalError(Box::new(e)))))
+                    }
+                    Ok(Err(e)) => {
+                        *this.finished = true;
+                        Poll::Ready(Some(Err(e)))
+                    }
+                    Ok(Ok((mut sorted, reservation))) => {
+                        let poll = sorted.poll_next_unpin(cx);
+                        match poll {
+                            Poll::Ready(None) => *this.finished = true,
+                            _ => {
+                                *this.sorted = Some(sorted);
+                                *this.reservation = Some(reservation);
+                            }
+                        }
+                        poll
+                    }
+                }
             }
             Poll::Pending => Poll::Pending,
         }
@@ -345,6 +634,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
@@ -391,6 +681,173 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_sort_spill() -> Result<()> {
+        let schema = test_util::aggr_test_schema();
+        let partitions = 4;
+        let (_, files) =
+            test::create_partitioned_csv("aggregate_test_100.csv", partitions)?;
+
+        let csv = CsvExec::new(
+            PhysicalPlanConfig {
+                object_store: Arc::new(LocalFileSystem {}),
+                file_schema: Arc::clone(&schema),
+                file_groups: files,
+                statistics: Statistics::default(),
+                projection: None,
+                batch_size: 10,
+                limit: None,
+                table_partition_cols: vec![],
+                metadata_cache: None,
+            },
+            true,
+            b',',
+        );
+
+        let sort_exec = Arc::new(
+            SortExec::try_new(
+                vec![
+                    // c1 string column
+                    PhysicalSortExpr {
+                        expr: col("c1", &schema)?,
+                        options: SortOptions::default(),
+                    },
+                    // c2 uin32 column
+                    PhysicalSortExpr {
+                        expr: col("c2", &schema)?,
+                        options: SortOptions::default(),
+                    },
+                    // c7 uin8 column
+                    PhysicalSortExpr {
+                        expr: col("c7", &schema)?,
+                        options: SortOptions::default(),
+                    },
+                ],
+                Arc::new(CoalescePartitionsExec::new(Arc::new(csv))),
+            )?
+            // spill every few input batches
+            .with_spill_threshold(1024)
+            .with_target_batch_size(16),
+        );
+
+        let result: Vec<RecordBatch> = collect(sort_exec.clone()).await?;
+        let result = common::combine_batches(&result, schema.clone())?.unwrap();
+        assert_eq!(result.num_rows(), 100);
+
+        let columns = result.columns();
+
+        let c1 = as_string_array(&columns[0]);
+        assert_eq!(c1.value(0), "a");
+        assert_eq!(c1.value(c1.len() - 1), "e");
+
+        let c2 = as_primitive_array::<UInt32Type>(&columns[1]);
+        assert_eq!(c2.value(0), 1);
+        assert_eq!(c2.value(c2.len() - 1), 5,);
+
+        let c7 = as_primitive_array::<UInt8Type>(&columns[6]);
+        assert_eq!(c7.value(0), 15);
+        assert_eq!(c7.value(c7.len() - 1), 254,);
+
+        // the sort keys are ordered the same as when sorting in memory
+        let expected = sort_batch(result.clone(), schema, sort_exec.expr())?;
+        for i in [0, 1, 6] {
+            assert_eq!(expected.column(i), result.column(i));
+        }
+
+        let metrics = sort_exec.metrics().unwrap();
+        assert_eq!(metrics.output_rows().unwrap(), 100);
+        assert!(metrics.spill_count().unwrap() > 1);
+        assert!(metrics.spilled_bytes().unwrap() > 0);
+
+        Ok(())
+    }
+
+    fn int_batches(num_batches: i32, batch_size: i32) -> Result<Vec<RecordBatch>> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        (0..num_batches)
+            .map(|batch| {
+                let values = (0..batch_size)
+                    .map(|i| (batch * batch_size + i) * 7919 % 1000)
+                    .collect::<Vec<_>>();
+                Ok(RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int32Array::from(values))],
+                )?)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sort_memory_limit_spills() -> Result<()> {
+        let batches = int_batches(10, 100)?;
+        let schema = batches[0].schema();
+        let pool_size = 3 * batch_memory_size(&batches[0]);
+        let memory_manager = Arc::new(MemoryManager::new(pool_size));
+
+        let sort_exec = Arc::new(
+            SortExec::try_new(
+                vec![PhysicalSortExpr {
+                    expr: col("a", &schema)?,
+                    options: SortOptions::default(),
+                }],
+                Arc::new(MemoryExec::try_new(
+                    &[batches.clone()],
+                    schema.clone(),
+                    None,
+                )?),
+            )?
+            .with_memory_manager(memory_manager.clone()),
+        );
+
+        let result: Vec<RecordBatch> = collect(sort_exec.clone()).await?;
+        let result = common::combine_batches(&result, schema.clone())?.unwrap();
+
+        let mut expected = batches
+            .iter()
+            .flat_map(|batch| as_primitive_array::<Int32Type>(batch.column(0)).values())
+            .copied()
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        let actual = as_primitive_array::<Int32Type>(result.column(0)).values();
+        assert_eq!(actual, expected.as_slice());
+
+        let metrics = sort_exec.metrics().unwrap();
+        assert!(metrics.spill_count().unwrap() > 0);
+
+        // everything is returned to the pool once the output is consumed
+        assert_eq!(memory_manager.reserved(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_memory_limit_exceeded() -> Result<()> {
+        let batches = int_batches(1, 1000)?;
+        let schema = batches[0].schema();
+        // too small to hold even a single input batch
+        let memory_manager = Arc::new(MemoryManager::new(16));
+
+        let sort_exec = Arc::new(
+            SortExec::try_new(
+                vec![PhysicalSortExpr {
+                    expr: col("a", &schema)?,
+                    options: SortOptions::default(),
+                }],
+                Arc::new(MemoryExec::try_new(&[batches], schema, None)?),
+            )?
+            .with_memory_manager(memory_manager),
+        );
+
+        let err = collect(sort_exec).await.unwrap_err();
+        assert!(
+            err.to_string().contains("Resources exhausted"),
+            "unexpected error: {}",
+            err
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_sort_metadata() -> Result<()> {
         let field_metadata: BTreeMap<String, String> =
@@ -0,0 +1,1038 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the sort merge join plan, which joins two inputs sorted on their
+//! join keys by merging them, without buffering either input in memory
+
+use std::any::Any;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::sync::Arc;
+
+use arrow::array::{
+    build_compare, new_null_array, Array, ArrayRef, DynComparator, UInt32Array,
+};
+use arrow::compute::kernels::partition::lexicographical_partition_ranges;
+use arrow::compute::{take, SortColumn, SortOptions};
+use arrow::datatypes::SchemaRef;
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+use super::coalesce_batches::concat_batches;
+use super::expressions::{Column, PhysicalSortExpr};
+use super::join_utils::{
+    build_join_schema, check_join_is_valid, ColumnIndex, JoinOn, JoinSide,
+};
+use super::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use super::stream::RecordBatchReceiverStream;
+use super::{
+    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+use crate::logical_plan::JoinType;
+
+/// Join execution plan that joins two inputs sorted on their join keys.
+///
+/// Each partition of the left input is merged with the same partition of
+/// the right input, so both inputs must have the same number of partitions
+/// and each pair of partitions must hold all the rows of a given key. The
+/// rows of every partition must be sorted on the join keys, using
+/// `sort_options`.
+///
+/// Only the rows sharing the same join keys are buffered at once, and the
+/// output is sorted on the join keys.
+#[derive(Debug)]
+pub struct SortMergeJoinExec {
+    /// Left sorted input
+    left: Arc<dyn ExecutionPlan>,
+    /// Right sorted input
+    right: Arc<dyn ExecutionPlan>,
+    /// Set of common columns used to join on
+    on: JoinOn,
+    /// How the join is performed
+    join_type: JoinType,
+    /// The schema once the join is applied
+    schema: SchemaRef,
+    /// How the inputs are sorted on each join key
+    sort_options: Vec<SortOptions>,
+    /// If null_equals_null is true, null == null else null != null
+    null_equals_null: bool,
+    /// Information of index and left / right placement of columns
+    column_indices: Vec<ColumnIndex>,
+    /// The ordering of the output
+    output_ordering: Option<Vec<PhysicalSortExpr>>,
+    /// The target size of the output batches
+    batch_size: usize,
+    /// Execution metrics
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl SortMergeJoinExec {
+    /// Tries to create a new [SortMergeJoinExec] joining `left` and
+    /// `right`, sorted on the columns of `on` as specified by `sort_options`.
+    /// # Error
+    /// This function errors when it is not possible to join the left and right sides on keys `on`,
+    /// or when the inputs have a different number of partitions.
+    pub fn try_new(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        on: JoinOn,
+        join_type: &JoinType,
+        sort_options: Vec<SortOptions>,
+        null_equals_null: &bool,
+    ) -> Result<Self> {
+        let left_schema = left.schema();
+        let right_schema = right.schema();
+        check_join_is_valid(&left_schema, &right_schema, &on)?;
+
+        if sort_options.len() != on.len() {
+            return Err(DataFusionError::Plan(format!(
+                "Expected {} sort options for the join keys of SortMergeJoinExec, got {}",
+                on.len(),
+                sort_options.len()
+            )));
+        }
+
+        let left_partitions = left.output_partitioning().partition_count();
+        let right_partitions = right.output_partitioning().partition_count();
+        if left_partitions != right_partitions {
+            return Err(DataFusionError::Plan(format!(
+                "SortMergeJoinExec requires inputs with the same number of partitions, \
+                 got {} on the left and {} on the right",
+                left_partitions, right_partitions
+            )));
+        }
+
+        let (schema, column_indices) =
+            build_join_schema(&left_schema, &right_schema, join_type);
+
+        // the groups of rows are emitted in the order of their join keys,
+        // which are null on the side without a match for outer joins
+        let sort_exprs = |keys: Vec<&Column>, offset: usize| {
+            keys.into_iter()
+                .zip(&sort_options)
+                .map(|(column, options)| PhysicalSortExpr {
+                    expr: Arc::new(Column::new(column.name(), offset + column.index())),
+                    options: *options,
+                })
+                .collect::<Vec<_>>()
+        };
+        let output_ordering = match join_type {
+            JoinType::Inner | JoinType::Left | JoinType::Semi | JoinType::Anti => {
+                Some(sort_exprs(on.iter().map(|(l, _)| l).collect(), 0))
+            }
+            JoinType::Right => Some(sort_exprs(
+                on.iter().map(|(_, r)| r).collect(),
+                left_schema.fields().len(),
+            )),
+            JoinType::Full => None,
+        };
+
+        Ok(Self {
+            left,
+            right,
+            on,
+            join_type: *join_type,
+            schema: Arc::new(schema),
+            sort_options,
+            null_equals_null: *null_equals_null,
+            column_indices,
+            output_ordering,
+            batch_size: 8192,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    /// Customize the target size of the output batches
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        // batch size must be greater than zero
+        assert!(batch_size > 0);
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// left sorted input
+    pub fn left(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.left
+    }
+
+    /// right sorted input
+    pub fn right(&self) -> &Arc<dyn ExecutionPlan> {
+        &self.right
+    }
+
+    /// Set of common columns used to join on
+    pub fn on(&self) -> &[(Column, Column)] {
+        &self.on
+    }
+
+    /// How the join is performed
+    pub fn join_type(&self) -> &JoinType {
+        &self.join_type
+    }
+
+    /// How the inputs are sorted on each join key
+    pub fn sort_options(&self) -> &[SortOptions] {
+        &self.sort_options
+    }
+
+    /// Get null_equals_null
+    pub fn null_equals_null(&self) -> &bool {
+        &self.null_equals_null
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for SortMergeJoinExec {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![self.left.clone(), self.right.clone()]
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            2 => Ok(Arc::new(
+                SortMergeJoinExec::try_new(
+                    children[0].clone(),
+                    children[1].clone(),
+                    self.on.clone(),
+                    &self.join_type,
+                    self.sort_options.clone(),
+                    &self.null_equals_null,
+                )?
+                .with_batch_size(self.batch_size),
+            )),
+            _ => Err(DataFusionError::Internal(
+                "SortMergeJoinExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        self.right.output_partitioning()
+    }
+
+    fn output_ordering(&self) -> Option<&[PhysicalSortExpr]> {
+        self.output_ordering.as_deref()
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        let left = self.left.execute(partition).await?;
+        let right = self.right.execute(partition).await?;
+
+        let (on_left, on_right) = self.on.iter().cloned().unzip();
+        let (sender, receiver) = mpsc::channel(2);
+        let joiner = SortMergeJoiner {
+            schema: self.schema.clone(),
+            left: SortedInput::new(left, on_left, self.sort_options.clone()),
+            right: SortedInput::new(right, on_right, self.sort_options.clone()),
+            join_type: self.join_type,
+            null_equals_null: self.null_equals_null,
+            column_indices: self.column_indices.clone(),
+            batch_size: self.batch_size,
+            buffered: vec![],
+            buffered_rows: 0,
+            sender: sender.clone(),
+            baseline_metrics: BaselineMetrics::new(&self.metrics, partition),
+            comparators: None,
+        };
+
+        let join_handle = tokio::spawn(async move {
+            if let Err(e) = joiner.join().await {
+                // If send fails, plan being torn down,
+                // there is no place to send the error
+                let arrow_error = ArrowError::ExternalError(Box::new(e));
+                sender.send(Err(arrow_error)).await.ok();
+            }
+        });
+
+        Ok(RecordBatchReceiverStream::create(
+            &self.schema,
+            receiver,
+            join_handle,
+        ))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(
+                    f,
+                    "SortMergeJoinExec: join_type={:?}, on={:?}",
+                    self.join_type, self.on
+                )
+            }
+        }
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn statistics(&self) -> Statistics {
+        // TODO stats: it is not possible in general to know the output size of joins
+        Statistics::default()
+    }
+}
+
+/// Builds the comparators of the values of the join keys `left` and `right`
+fn build_comparators(
+    left: &[ArrayRef],
+    right: &[ArrayRef],
+) -> ArrowResult<Vec<DynComparator>> {
+    left.iter()
+        .zip(right)
+        .map(|(l, r)| build_compare(l.as_ref(), r.as_ref()))
+        .collect()
+}
+
+/// Compares the join keys at row `left_row` of `left` with the join keys at
+/// row `right_row` of `right`, following the order of the inputs.
+/// `comparators` are the comparators of their values, see
+/// [`build_comparators`].
+fn compare_keys(
+    left: &[ArrayRef],
+    left_row: usize,
+    right: &[ArrayRef],
+    right_row: usize,
+    comparators: &[DynComparator],
+    sort_options: &[SortOptions],
+) -> Ordering {
+    for (((l, r), cmp), sort_options) in
+        left.iter().zip(right).zip(comparators).zip(sort_options)
+    {
+        match (l.is_valid(left_row), r.is_valid(right_row)) {
+            (false, true) if sort_options.nulls_first => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (true, false) if sort_options.nulls_first => return Ordering::Greater,
+            (true, false) => return Ordering::Less,
+            (false, false) => {}
+            (true, true) => match cmp(left_row, right_row) {
+                Ordering::Equal => {}
+                o if sort_options.descending => return o.reverse(),
+                o => return o,
+            },
+        }
+    }
+    Ordering::Equal
+}
+
+/// The comparators of the join keys of a pair of left and right batches,
+/// reused until either batch changes
+struct KeyComparators {
+    /// The ids of the left and right batches, see [`KeyGroup::batch_id`]
+    batch_ids: (usize, usize),
+    comparators: Vec<DynComparator>,
+}
+
+/// The rows of one input sharing the same join keys
+struct KeyGroup {
+    /// The rows of the group, which may span several input batches
+    batches: Vec<RecordBatch>,
+    /// The join key columns of the batch the group starts in
+    keys: Vec<ArrayRef>,
+    /// The row of `keys` holding the join keys of the group
+    key_row: usize,
+    /// The id of the batch the group starts in, unique within its input
+    batch_id: usize,
+}
+
+impl KeyGroup {
+    fn num_rows(&self) -> usize {
+        self.batches.iter().map(|batch| batch.num_rows()).sum()
+    }
+
+    /// Returns true if any of the join keys of the group is null
+    fn has_null_key(&self) -> bool {
+        self.keys.iter().any(|key| key.is_null(self.key_row))
+    }
+
+    /// Returns the rows of the group as a single batch
+    fn concat(&self) -> ArrowResult<RecordBatch> {
+        match self.batches.as_slice() {
+            [batch] => Ok(batch.clone()),
+            batches => concat_batches(&batches[0].schema(), batches, self.num_rows()),
+        }
+    }
+}
+
+/// A sorted input of the join, read one [`KeyGroup`] at a time
+struct SortedInput {
+    stream: SendableRecordBatchStream,
+    /// The join key columns
+    on: Vec<Column>,
+    /// How the input is sorted on each join key
+    sort_options: Vec<SortOptions>,
+    /// The batch being read and its join key columns
+    batch: Option<(RecordBatch, Vec<ArrayRef>)>,
+    /// The number of batches read, the id of `batch`
+    batch_id: usize,
+    /// The ranges of rows of `batch` with equal keys that were not read yet
+    ranges: VecDeque<Range<usize>>,
+}
+
+impl SortedInput {
+    fn new(
+        stream: SendableRecordBatchStream,
+        on: Vec<Column>,
+        sort_options: Vec<SortOptions>,
+    ) -> Self {
+        Self {
+            stream,
+            on,
+            sort_options,
+            batch: None,
+            batch_id: 0,
+            ranges: VecDeque::new(),
+        }
+    }
+
+    /// Reads the next non empty batch of the input, returning false once
+    /// the input is exhausted
+    async fn next_batch(&mut self) -> ArrowResult<bool> {
+        while let Some(batch) = self.stream.next().await {
+            let batch = batch?;
+            if batch.num_rows() == 0 {
+                continue;
+            }
+
+            let keys = self
+                .on
+                .iter()
+                .map(|column| batch.column(column.index()).clone())
+                .collect::<Vec<_>>();
+            let sort_columns = keys
+                .iter()
+                .zip(&self.sort_options)
+                .map(|(values, options)| SortColumn {
+                    values: values.clone(),
+                    options: Some(*options),
+                })
+                .collect::<Vec<_>>();
+            self.ranges = lexicographical_partition_ranges(&sort_columns)?.collect();
+            self.batch = Some((batch, keys));
+            self.batch_id += 1;
+            return Ok(true);
+        }
+
+        self.batch = None;
+        Ok(false)
+    }
+
+    /// Reads the next group of rows with equal join keys, if any
+    async fn next_group(&mut self) -> ArrowResult<Option<KeyGroup>> {
+        if self.ranges.is_empty() && !self.next_batch().await? {
+            return Ok(None);
+        }
+
+        let (batch, keys) = self.batch.as_ref().expect("batch is read");
+        let range = self.ranges.pop_front().expect("batch is not empty");
+        let group_keys = keys.clone();
+        let key_row = range.start;
+        let batch_id = self.batch_id;
+        let mut batches = vec![batch.slice(range.start, range.len())];
+
+        // the group continues in the next batch if its first rows have the
+        // same keys
+        while self.ranges.is_empty() && self.next_batch().await? {
+            let (batch, keys) = self.batch.as_ref().expect("batch is read");
+            let comparators = build_comparators(&group_keys, keys)?;
+            let ordering = compare_keys(
+                &group_keys,
+                key_row,
+                keys,
+                0,
+                &comparators,
+                &self.sort_options,
+            );
+            if ordering != Ordering::Equal {
+                break;
+            }
+            let range = self.ranges.pop_front().expect("batch is not empty");
+            batches.push(batch.slice(range.start, range.len()));
+        }
+
+        Ok(Some(KeyGroup {
+            batches,
+            keys: group_keys,
+            key_row,
+            batch_id,
+        }))
+    }
+}
+
+/// Merges the groups of rows of the two sorted inputs of a partition
+struct SortMergeJoiner {
+    /// The schema once the join is applied
+    schema: SchemaRef,
+    left: SortedInput,
+    right: SortedInput,
+    join_type: JoinType,
+    null_equals_null: bool,
+    /// Information of index and left / right placement of columns
+    column_indices: Vec<ColumnIndex>,
+    /// The target size of the output batches
+    batch_size: usize,
+    /// The output batches not sent yet
+    buffered: Vec<RecordBatch>,
+    /// Number of rows of `buffered`
+    buffered_rows: usize,
+    sender: mpsc::Sender<ArrowResult<RecordBatch>>,
+    baseline_metrics: BaselineMetrics,
+    /// The comparators of the join keys of the last compared batches
+    comparators: Option<KeyComparators>,
+}
+
+impl SortMergeJoiner {
+    /// Compares the join keys of the groups `left` and `right`, building the
+    /// comparators of their keys only if either batch changed since the last
+    /// comparison
+    fn compare_groups(
+        &mut self,
+        left: &KeyGroup,
+        right: &KeyGroup,
+    ) -> ArrowResult<Ordering> {
+        let batch_ids = (left.batch_id, right.batch_id);
+        let comparators = match &mut self.comparators {
+            Some(comparators) if comparators.batch_ids == batch_ids => comparators,
+            comparators => comparators.insert(KeyComparators {
+                batch_ids,
+                comparators: build_comparators(&left.keys, &right.keys)?,
+            }),
+        };
+        Ok(compare_keys(
+            &left.keys,
+            left.key_row,
+            &right.keys,
+            right.key_row,
+            &comparators.comparators,
+            &self.left.sort_options,
+        ))
+    }
+
+    /// Joins the inputs, sending the output batches as they are produced
+    async fn join(mut self) -> Result<()> {
+        let mut left_group = self.left.next_group().await?;
+        let mut right_group = self.right.next_group().await?;
+
+        loop {
+            let ordering = match (&left_group, &right_group) {
+                (None, None) => break,
+                // the remaining rows of one side have no match
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(left), Some(right)) => {
+                    match self.compare_groups(left, right)? {
+                        Ordering::Equal
+                            if !self.null_equals_null && left.has_null_key() =>
+                        {
+                            // null keys never match
+                            self.unmatched_left(left)?;
+                            self.unmatched_right(right)?;
+                            left_group = self.left.next_group().await?;
+                            right_group = self.right.next_group().await?;
+                            self.send_full_batches().await;
+                            continue;
+                        }
+                        ordering => ordering,
+                    }
+                }
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    self.unmatched_left(left_group.as_ref().expect("left group"))?;
+                    left_group = self.left.next_group().await?;
+                }
+                Ordering::Greater => {
+                    self.unmatched_right(right_group.as_ref().expect("right group"))?;
+                    right_group = self.right.next_group().await?;
+                }
+                Ordering::Equal => {
+                    self.matched(
+                        left_group.as_ref().expect("left group"),
+                        right_group.as_ref().expect("right group"),
+                    )?;
+                    left_group = self.left.next_group().await?;
+                    right_group = self.right.next_group().await?;
+                }
+            }
+            self.send_full_batches().await;
+
+            // stop as soon as the remaining rows can not produce any output
+            let left_done = left_group.is_none()
+                && !matches!(self.join_type, JoinType::Right | JoinType::Full);
+            let right_done = right_group.is_none()
+                && matches!(
+                    self.join_type,
+                    JoinType::Inner | JoinType::Right | JoinType::Semi
+                );
+            if left_done || right_done {
+                break;
+            }
+        }
+
+        if !self.buffered.is_empty() {
+            let batch = self.take_buffered()?;
+            self.send(batch).await;
+        }
+        self.baseline_metrics.done();
+        Ok(())
+    }
+
+    /// Outputs the rows of the left group `left`, which has no match
+    fn unmatched_left(&mut self, left: &KeyGroup) -> ArrowResult<()> {
+        if matches!(
+            self.join_type,
+            JoinType::Left | JoinType::Full | JoinType::Anti
+        ) {
+            for batch in &left.batches {
+                let batch =
+                    self.build_batch(Some(batch.columns()), None, batch.num_rows())?;
+                self.buffer(batch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Outputs the rows of the right group `right`, which has no match
+    fn unmatched_right(&mut self, right: &KeyGroup) -> ArrowResult<()> {
+        if matches!(self.join_type, JoinType::Right | JoinType::Full) {
+            for batch in &right.batches {
+                let batch =
+                    self.build_batch(None, Some(batch.columns()), batch.num_rows())?;
+                self.buffer(batch);
+            }
+        }
+        Ok(())
+    }
+
+    /// Outputs the join of the groups `left` and `right`, which have the
+    /// same keys
+    fn matched(&mut self, left: &KeyGroup, right: &KeyGroup) -> ArrowResult<()> {
+        match self.join_type {
+            JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full => {
+                let timer = self.baseline_metrics.elapsed_compute().timer();
+                let left = left.concat()?;
+                let right = right.concat()?;
+
+                // the cartesian product of the groups, a few left rows at a
+                // time to keep the output batches close to the target size
+                let right_rows = right.num_rows() as u32;
+                let chunk_size = (self.batch_size / right.num_rows()).max(1);
+                for start in (0..left.num_rows()).step_by(chunk_size) {
+                    let end = (start + chunk_size).min(left.num_rows());
+                    let left_indices = UInt32Array::from(
+                        (start as u32..end as u32)
+                            .flat_map(|i| std::iter::repeat(i).take(right_rows as usize))
+                            .collect::<Vec<_>>(),
+                    );
+                    let right_indices = UInt32Array::from(
+                        (start..end).flat_map(|_| 0..right_rows).collect::<Vec<_>>(),
+                    );
+
+                    let take_all = |batch: &RecordBatch, indices: &UInt32Array| {
+                        batch
+                            .columns()
+                            .iter()
+                            .map(|column| take(column.as_ref(), indices, None))
+                            .collect::<ArrowResult<Vec<_>>>()
+                    };
+                    let left_columns = take_all(&left, &left_indices)?;
+                    let right_columns = take_all(&right, &right_indices)?;
+                    let batch = self.build_batch(
+                        Some(left_columns.as_slice()),
+                        Some(right_columns.as_slice()),
+                        left_indices.len(),
+                    )?;
+                    self.buffer(batch);
+                }
+                timer.done();
+            }
+            JoinType::Semi => {
+                for batch in &left.batches {
+                    let batch =
+                        self.build_batch(Some(batch.columns()), None, batch.num_rows())?;
+                    self.buffer(batch);
+                }
+            }
+            JoinType::Anti => {}
+        }
+        Ok(())
+    }
+
+    /// Builds an output batch of `num_rows` rows from the columns of either
+    /// input, using nulls for the columns of a missing input
+    fn build_batch(
+        &self,
+        left: Option<&[ArrayRef]>,
+        right: Option<&[ArrayRef]>,
+        num_rows: usize,
+    ) -> ArrowResult<RecordBatch> {
+        let columns = self
+            .column_indices
+            .iter()
+            .enumerate()
+            .map(|(i, column_index)| {
+                let columns = match column_index.side {
+                    JoinSide::Left => left,
+                    JoinSide::Right => right,
+                };
+                match columns {
+                    Some(columns) => columns[column_index.index].clone(),
+                    None => new_null_array(self.schema.field(i).data_type(), num_rows),
+                }
+            })
+            .collect();
+        RecordBatch::try_new(self.schema.clone(), columns)
+    }
+
+    fn buffer(&mut self, batch: RecordBatch) {
+        self.buffered_rows += batch.num_rows();
+        self.buffered.push(batch);
+    }
+
+    /// Combines the buffered output into a single batch
+    fn take_buffered(&mut self) -> ArrowResult<RecordBatch> {
+        let batch = concat_batches(&self.schema, &self.buffered, self.buffered_rows)?;
+        self.buffered.clear();
+        self.buffered_rows = 0;
+        Ok(batch)
+    }
+
+    /// Sends the buffered output once it reaches the target batch size
+    async fn send_full_batches(&mut self) {
+        if self.buffered_rows >= self.batch_size {
+            let batch = self.take_buffered();
+            match batch {
+                Ok(batch) => self.send(batch).await,
+                Err(e) => {
+                    // If send fails, plan being torn down,
+                    // there is no place to send the error
+                    self.sender.send(Err(e)).await.ok();
+                }
+            }
+        }
+    }
+
+    async fn send(&mut self, batch: RecordBatch) {
+        self.baseline_metrics.record_output(batch.num_rows());
+        // If send fails, plan being torn down,
+        // there is no place to send the output
+        self.sender.send(Ok(batch)).await.ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert_batches_eq;
+    use crate::physical_plan::{common, memory::MemoryExec};
+    use crate::test::{build_table_i32, columns};
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field, Schema};
+
+    /// Builds a table with the given batches, each made of columns a, b, c
+    fn build_table(
+        batches: Vec<(Vec<i32>, Vec<i32>, Vec<i32>)>,
+    ) -> Arc<dyn ExecutionPlan> {
+        let batches = batches
+            .iter()
+            .map(|(a, b, c)| build_table_i32(("a", a), ("b", b), ("c", c)))
+            .collect::<Vec<_>>();
+        let schema = batches[0].schema();
+        Arc::new(MemoryExec::try_new(&[batches], schema, None).unwrap())
+    }
+
+    fn build_nullable_table(b: Vec<Option<i32>>) -> Arc<dyn ExecutionPlan> {
+        let schema = Arc::new(Schema::new(vec![Field::new("b", DataType::Int32, true)]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(b))])
+                .unwrap();
+        Arc::new(MemoryExec::try_new(&[vec![batch]], schema, None).unwrap())
+    }
+
+    fn join(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        join_type: &JoinType,
+        null_equals_null: bool,
+    ) -> Result<SortMergeJoinExec> {
+        let on = vec![(
+            Column::new_with_schema("b", &left.schema())?,
+            Column::new_with_schema("b", &right.schema())?,
+        )];
+        SortMergeJoinExec::try_new(
+            left,
+            right,
+            on,
+            join_type,
+            vec![SortOptions::default()],
+            &null_equals_null,
+        )
+    }
+
+    async fn join_collect(
+        left: Arc<dyn ExecutionPlan>,
+        right: Arc<dyn ExecutionPlan>,
+        join_type: &JoinType,
+    ) -> Result<(Vec<String>, Vec<RecordBatch>)> {
+        let join = join(left, right, join_type, false)?.with_batch_size(4);
+        let columns = columns(&join.schema());
+
+        let stream = join.execute(0).await?;
+        let batches = common::collect(stream).await?;
+
+        Ok((columns, batches))
+    }
+
+    /// left and right tables whose groups of equal keys span batches
+    fn build_tables() -> (Arc<dyn ExecutionPlan>, Arc<dyn ExecutionPlan>) {
+        let left = build_table(vec![
+            (vec![1, 2, 3], vec![1, 2, 2], vec![10, 20, 30]),
+            (vec![4, 5], vec![2, 4], vec![40, 50]),
+        ]);
+        let right = build_table(vec![
+            (vec![6, 7], vec![0, 2], vec![60, 70]),
+            (vec![8, 9], vec![2, 3], vec![80, 90]),
+            (vec![10], vec![4], vec![100]),
+        ]);
+        (left, right)
+    }
+
+    #[tokio::test]
+    async fn join_inner() -> Result<()> {
+        let (left, right) = build_tables();
+        let (columns, batches) = join_collect(left, right, &JoinType::Inner).await?;
+
+        assert_eq!(columns, vec!["a", "b", "c", "a", "b", "c"]);
+        let expected = vec![
+            "+---+---+----+----+---+-----+",
+            "| a | b | c  | a  | b | c   |",
+            "+---+---+----+----+---+-----+",
+            "| 2 | 2 | 20 | 7  | 2 | 70  |",
+            "| 2 | 2 | 20 | 8  | 2 | 80  |",
+            "| 3 | 2 | 30 | 7  | 2 | 70  |",
+            "| 3 | 2 | 30 | 8  | 2 | 80  |",
+            "| 4 | 2 | 40 | 7  | 2 | 70  |",
+            "| 4 | 2 | 40 | 8  | 2 | 80  |",
+            "| 5 | 4 | 50 | 10 | 4 | 100 |",
+            "+---+---+----+----+---+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_left() -> Result<()> {
+        let (left, right) = build_tables();
+        let (_, batches) = join_collect(left, right, &JoinType::Left).await?;
+
+        let expected = vec![
+            "+---+---+----+----+---+-----+",
+            "| a | b | c  | a  | b | c   |",
+            "+---+---+----+----+---+-----+",
+            "| 1 | 1 | 10 |    |   |     |",
+            "| 2 | 2 | 20 | 7  | 2 | 70  |",
+            "| 2 | 2 | 20 | 8  | 2 | 80  |",
+            "| 3 | 2 | 30 | 7  | 2 | 70  |",
+            "| 3 | 2 | 30 | 8  | 2 | 80  |",
+            "| 4 | 2 | 40 | 7  | 2 | 70  |",
+            "| 4 | 2 | 40 | 8  | 2 | 80  |",
+            "| 5 | 4 | 50 | 10 | 4 | 100 |",
+            "+---+---+----+----+---+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_right() -> Result<()> {
+        let (left, right) = build_tables();
+        let (_, batches) = join_collect(left, right, &JoinType::Right).await?;
+
+        let expected = vec![
+            "+---+---+----+----+---+-----+",
+            "| a | b | c  | a  | b | c   |",
+            "+---+---+----+----+---+-----+",
+            "|   |   |    | 6  | 0 | 60  |",
+            "| 2 | 2 | 20 | 7  | 2 | 70  |",
+            "| 2 | 2 | 20 | 8  | 2 | 80  |",
+            "| 3 | 2 | 30 | 7  | 2 | 70  |",
+            "| 3 | 2 | 30 | 8  | 2 | 80  |",
+            "| 4 | 2 | 40 | 7  | 2 | 70  |",
+            "| 4 | 2 | 40 | 8  | 2 | 80  |",
+            "|   |   |    | 9  | 3 | 90  |",
+            "| 5 | 4 | 50 | 10 | 4 | 100 |",
+            "+---+---+----+----+---+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_full() -> Result<()> {
+        let (left, right) = build_tables();
+        let (_, batches) = join_collect(left, right, &JoinType::Full).await?;
+
+        let expected = vec![
+            "+---+---+----+----+---+-----+",
+            "| a | b | c  | a  | b | c   |",
+            "+---+---+----+----+---+-----+",
+            "|   |   |    | 6  | 0 | 60  |",
+            "| 1 | 1 | 10 |    |   |     |",
+            "| 2 | 2 | 20 | 7  | 2 | 70  |",
+            "| 2 | 2 | 20 | 8  | 2 | 80  |",
+            "| 3 | 2 | 30 | 7  | 2 | 70  |",
+            "| 3 | 2 | 30 | 8  | 2 | 80  |",
+            "| 4 | 2 | 40 | 7  | 2 | 70  |",
+            "| 4 | 2 | 40 | 8  | 2 | 80  |",
+            "|   |   |    | 9  | 3 | 90  |",
+            "| 5 | 4 | 50 | 10 | 4 | 100 |",
+            "+---+---+----+----+---+-----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_semi() -> Result<()> {
+        let (left, right) = build_tables();
+        let (columns, batches) = join_collect(left, right, &JoinType::Semi).await?;
+
+        assert_eq!(columns, vec!["a", "b", "c"]);
+        let expected = vec![
+            "+---+---+----+",
+            "| a | b | c  |",
+            "+---+---+----+",
+            "| 2 | 2 | 20 |",
+            "| 3 | 2 | 30 |",
+            "| 4 | 2 | 40 |",
+            "| 5 | 4 | 50 |",
+            "+---+---+----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_anti() -> Result<()> {
+        let (left, right) = build_tables();
+        let (columns, batches) = join_collect(left, right, &JoinType::Anti).await?;
+
+        assert_eq!(columns, vec!["a", "b", "c"]);
+        let expected = vec![
+            "+---+---+----+",
+            "| a | b | c  |",
+            "+---+---+----+",
+            "| 1 | 1 | 10 |",
+            "+---+---+----+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn join_null_keys() -> Result<()> {
+        let left = build_nullable_table(vec![None, None, Some(1), Some(2)]);
+        let right = build_nullable_table(vec![None, Some(1), Some(3)]);
+
+        // nulls do not match by default
+        let full_join = join(left.clone(), right.clone(), &JoinType::Full, false)?;
+        let batches = common::collect(full_join.execute(0).await?).await?;
+        let expected = vec![
+            "+---+---+",
+            "| b | b |",
+            "+---+---+",
+            "|   |   |",
+            "|   |   |",
+            "|   |   |",
+            "| 1 | 1 |",
+            "| 2 |   |",
+            "|   | 3 |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        let inner_join = join(left, right, &JoinType::Inner, true)?;
+        let batches = common::collect(inner_join.execute(0).await?).await?;
+        let expected = vec![
+            "+---+---+",
+            "| b | b |",
+            "+---+---+",
+            "|   |   |",
+            "|   |   |",
+            "| 1 | 1 |",
+            "+---+---+",
+        ];
+        assert_batches_eq!(expected, &batches);
+
+        Ok(())
+    }
+
+    #[test]
+    fn join_output_ordering() -> Result<()> {
+        let (left, right) = build_tables();
+
+        let ordering = |join_type| -> Result<Option<Vec<String>>> {
+            let join = join(left.clone(), right.clone(), join_type, false)?;
+            Ok(join
+                .output_ordering()
+                .map(|exprs| exprs.iter().map(|e| e.to_string()).collect()))
+        };
+
+        assert_eq!(
+            ordering(&JoinType::Inner)?,
+            Some(vec!["b@1 ASC".to_string()])
+        );
+        assert_eq!(
+            ordering(&JoinType::Semi)?,
+            Some(vec!["b@1 ASC".to_string()])
+        );
+        assert_eq!(
+            ordering(&JoinType::Right)?,
+            Some(vec!["b@4 ASC".to_string()])
+        );
+        assert_eq!(ordering(&JoinType::Full)?, None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Helpers for operators that spill intermediate record batches to
+//! temporary Arrow IPC files
+
+use super::common::AbortOnDropSingle;
+use super::{RecordBatchStream, SendableRecordBatchStream};
+use crate::error::Result;
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::error::{ArrowError, Result as ArrowResult};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use futures::channel::mpsc;
+use futures::{SinkExt, Stream, StreamExt};
+use std::borrow::Borrow;
+use std::fs::File;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tempfile::NamedTempFile;
+use tokio::task::JoinHandle;
+
+/// Writes record batches one at a time to a new temporary Arrow IPC file
+pub(crate) struct SpillWriter {
+    file: NamedTempFile,
+    writer: FileWriter<File>,
+}
+
+impl SpillWriter {
+    /// Creates a temporary file to write batches of `schema` to
+    pub(crate) fn try_new(schema: &Schema) -> Result<Self> {
+        let file = NamedTempFile::new()?;
+        let writer = FileWriter::try_new(file.as_file().try_clone()?, schema)?;
+        Ok(Self { file, writer })
+    }
+
+    /// Appends `batch` to the file
+    pub(crate) fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.writer.write(batch)?;
+        Ok(())
+    }
+
+    /// Completes the file, returning it together with the number of bytes
+    /// written to disk
+    pub(crate) fn finish(self) -> Result<(NamedTempFile, usize)> {
+        let Self { file, mut writer } = self;
+        writer.finish()?;
+        // flushes the buffered writes
+        drop(writer);
+        let bytes = file.as_file().metadata()?.len() as usize;
+        Ok((file, bytes))
+    }
+}
+
+/// Writes `batches` to a new temporary Arrow IPC file, returning the
+/// file together with the number of bytes written to disk.
+///
+/// The file is removed when the returned [`NamedTempFile`] is dropped.
+pub(crate) fn spill_batches<'a>(
+    batches: impl IntoIterator<Item = &'a RecordBatch>,
+    schema: &Schema,
+) -> Result<(NamedTempFile, usize)> {
+    let mut writer = SpillWriter::try_new(schema)?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    writer.finish()
+}
+
+/// Reads back a file written by [`spill_batches`] or a [`SpillWriter`] on
+/// a blocking task, returning a receiver yielding its batches in order and
+/// the handle of the task producing them.
+///
+/// When passed by value, the file is removed once it has been fully read
+/// or the receiver is dropped. A shared file (e.g. an `Arc<NamedTempFile>`)
+/// can be read several times and is removed along with its last owner.
+pub(crate) fn read_spill(
+    file: impl Borrow<NamedTempFile> + Send + 'static,
+) -> (mpsc::Receiver<ArrowResult<RecordBatch>>, JoinHandle<()>) {
+    let (mut sender, receiver) = mpsc::channel(2);
+    let join_handle = tokio::task::spawn_blocking(move || {
+        let reader = match file
+            .borrow()
+            .reopen()
+            .map_err(ArrowError::from)
+            .and_then(FileReader::try_new)
+        {
+            Ok(reader) => reader,
+            Err(e) => {
+                // If send fails, plan being torn down,
+                // there is no place to send the error
+                futures::executor::block_on(sender.send(Err(e))).ok();
+                return;
+            }
+        };
+
+        for batch in reader {
+            if futures::executor::block_on(sender.send(batch)).is_err() {
+                // receiver is gone, no need to read the rest of the file
+                break;
+            }
+        }
+    });
+
+    (receiver, join_handle)
+}
+
+/// Reads back a spill file of batches of `schema` as a record batch stream,
+/// see [`read_spill`]
+pub(crate) fn read_spill_as_stream(
+    file: impl Borrow<NamedTempFile> + Send + 'static,
+    schema: SchemaRef,
+) -> SendableRecordBatchStream {
+    let (receiver, join_handle) = read_spill(file);
+    Box::pin(SpillStream {
+        schema,
+        receiver,
+        drop_helper: AbortOnDropSingle::new(join_handle),
+    })
+}
+
+/// Stream of the batches read back from a spill file
+struct SpillStream {
+    schema: SchemaRef,
+    receiver: mpsc::Receiver<ArrowResult<RecordBatch>>,
+    #[allow(dead_code)]
+    drop_helper: AbortOnDropSingle<()>,
+}
+
+impl Stream for SpillStream {
+    type Item = ArrowResult<RecordBatch>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_next_unpin(cx)
+    }
+}
+
+impl RecordBatchStream for SpillStream {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_spill_round_trip() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batches = (0..3)
+            .map(|i| {
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int32Array::from(vec![Some(i), None, Some(i + 1)]))],
+                )
+            })
+            .collect::<ArrowResult<Vec<_>>>()?;
+
+        let (file, bytes) = spill_batches(&batches, &schema)?;
+        assert!(bytes > 0);
+        let path = file.path().to_owned();
+
+        let (receiver, join_handle) = read_spill(file);
+        let read = receiver.collect::<Vec<_>>().await;
+        join_handle.await.unwrap();
+
+        let read = read.into_iter().collect::<ArrowResult<Vec<_>>>()?;
+        assert_eq!(batches, read);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_shared_spill_file() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, true)]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![Some(1), None, Some(3)]))],
+        )?;
+
+        let mut writer = SpillWriter::try_new(&schema)?;
+        writer.write(&batch)?;
+        writer.write(&batch)?;
+        let (file, bytes) = writer.finish()?;
+        assert!(bytes > 0);
+        let path = file.path().to_owned();
+
+        // a shared file can be read several times
+        let file = Arc::new(file);
+        for _ in 0..2 {
+            let stream = read_spill_as_stream(file.clone(), schema.clone());
+            let read = crate::physical_plan::common::collect(stream).await?;
+            assert_eq!(vec![batch.clone(), batch.clone()], read);
+        }
+        assert!(path.exists());
+
+        drop(file);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+}
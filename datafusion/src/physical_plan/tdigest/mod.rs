@@ -0,0 +1,342 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! # T-Digest
+//!
+//! `tdigest` is a module that contains a merging t-digest as described in
+//! [Computing Extremely Accurate Quantiles Using t-Digests](https://arxiv.org/abs/1902.04023)
+//! by Ted Dunning and Otmar Ertl, so that [`approx_percentile_cont`] can be
+//! computed in partial and final aggregation steps.
+//!
+//! A digest summarizes the input as at most `max_size` centroids (a mean and
+//! a weight each). Centroids are kept small near the extreme quantiles and
+//! large around the median by the `k1` scale function, which is what keeps the
+//! error of the extreme quantiles low. Digests are merged by compressing the
+//! union of their centroids, so the result does not depend on how the input
+//! was partitioned beyond the approximation itself.
+
+use crate::error::{DataFusionError, Result};
+use crate::scalar::ScalarValue;
+use arrow::datatypes::DataType;
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+
+/// The default maximum number of centroids of a digest
+pub(crate) const DEFAULT_MAX_SIZE: usize = 100;
+
+/// A cluster of values, summarized by their mean and their number
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+impl Centroid {
+    pub(crate) fn new(mean: f64, weight: f64) -> Self {
+        Self { mean, weight }
+    }
+
+    /// Adds the values of `other` to this centroid
+    fn add(&mut self, other: &Centroid) {
+        let weight = self.weight + other.weight;
+        self.mean += (other.mean - self.mean) * other.weight / weight;
+        self.weight = weight;
+    }
+}
+
+fn cmp_f64(a: &f64, b: &f64) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TDigest {
+    /// the centroids, sorted by mean
+    centroids: Vec<Centroid>,
+    max_size: usize,
+    sum: f64,
+    count: f64,
+    max: f64,
+    min: f64,
+}
+
+impl TDigest {
+    pub(crate) fn new(max_size: usize) -> Self {
+        Self {
+            centroids: vec![],
+            max_size,
+            sum: 0.0,
+            count: 0.0,
+            max: f64::NAN,
+            min: f64::NAN,
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.count == 0.0
+    }
+
+    /// Returns a digest of the values of this digest and of `values`
+    pub(crate) fn merge_unsorted(&self, mut values: Vec<f64>) -> TDigest {
+        values.sort_by(cmp_f64);
+        self.merge_sorted(&values)
+    }
+
+    /// Returns a digest of the values of this digest and of the sorted `values`
+    pub(crate) fn merge_sorted(&self, values: &[f64]) -> TDigest {
+        if values.is_empty() {
+            return self.clone();
+        }
+        let values = TDigest {
+            centroids: values.iter().map(|v| Centroid::new(*v, 1.0)).collect(),
+            max_size: self.max_size,
+            sum: values.iter().sum(),
+            count: values.len() as f64,
+            max: values[values.len() - 1],
+            min: values[0],
+        };
+        TDigest::merge_digests(&[self.clone(), values])
+    }
+
+    /// Returns a digest of the values of all the `digests`
+    pub(crate) fn merge_digests(digests: &[TDigest]) -> TDigest {
+        let max_size = digests
+            .iter()
+            .map(|d| d.max_size)
+            .max()
+            .unwrap_or(DEFAULT_MAX_SIZE);
+        let mut merged = TDigest::new(max_size);
+        let mut centroids = vec![];
+        for digest in digests.iter().filter(|d| !d.is_empty()) {
+            centroids.extend_from_slice(&digest.centroids);
+            merged.sum += digest.sum;
+            merged.count += digest.count;
+            merged.max = if merged.max.is_nan() {
+                digest.max
+            } else {
+                merged.max.max(digest.max)
+            };
+            merged.min = if merged.min.is_nan() {
+                digest.min
+            } else {
+                merged.min.min(digest.min)
+            };
+        }
+        centroids.sort_by(|a, b| cmp_f64(&a.mean, &b.mean));
+        merged.centroids = compress(centroids, merged.count, max_size);
+        merged
+    }
+
+    /// Estimates the value at quantile `q` (between 0 and 1) of the values
+    /// of the digest, interpolating linearly between the centroids. Returns
+    /// `None` if the digest is empty.
+    pub(crate) fn estimate_quantile(&self, q: f64) -> Option<f64> {
+        let (first, last) = match (self.centroids.first(), self.centroids.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return None,
+        };
+        if self.centroids.len() == 1 {
+            return Some(first.mean);
+        }
+
+        let rank = q * self.count;
+        // between the minimum and the center of the first centroid
+        if rank < first.weight / 2.0 {
+            let fraction = rank / (first.weight / 2.0);
+            return Some(self.min + (first.mean - self.min) * fraction);
+        }
+        let mut weight_so_far = 0.0;
+        for pair in self.centroids.windows(2) {
+            let (left, right) = (&pair[0], &pair[1]);
+            let left_center = weight_so_far + left.weight / 2.0;
+            let right_center = weight_so_far + left.weight + right.weight / 2.0;
+            if rank <= right_center {
+                let fraction = (rank - left_center) / (right_center - left_center);
+                return Some(left.mean + (right.mean - left.mean) * fraction);
+            }
+            weight_so_far += left.weight;
+        }
+        // between the center of the last centroid and the maximum
+        let last_center = self.count - last.weight / 2.0;
+        let fraction = ((rank - last_center) / (last.weight / 2.0)).min(1.0);
+        Some(last.mean + (self.max - last.mean) * fraction)
+    }
+
+    /// The state of the digest as `[max_size, sum, count, max, min, centroids]`,
+    /// the centroids being a list of alternating means and weights.
+    pub(crate) fn to_scalar_state(&self) -> Vec<ScalarValue> {
+        let centroids = self
+            .centroids
+            .iter()
+            .flat_map(|c| [c.mean, c.weight])
+            .map(|v| ScalarValue::Float64(Some(v)))
+            .collect();
+        vec![
+            ScalarValue::UInt64(Some(self.max_size as u64)),
+            ScalarValue::Float64(Some(self.sum)),
+            ScalarValue::Float64(Some(self.count)),
+            ScalarValue::Float64(Some(self.max)),
+            ScalarValue::Float64(Some(self.min)),
+            ScalarValue::List(Some(Box::new(centroids)), Box::new(DataType::Float64)),
+        ]
+    }
+
+    /// Reads a digest from the state written by [`TDigest::to_scalar_state`]
+    pub(crate) fn from_scalar_state(state: &[ScalarValue]) -> Result<TDigest> {
+        let invalid_state =
+            || DataFusionError::Internal(format!("Invalid t-digest state {:?}", state));
+        let float = |value: &ScalarValue| match value {
+            ScalarValue::Float64(Some(v)) => Ok(*v),
+            _ => Err(invalid_state()),
+        };
+        if state.len() != 6 {
+            return Err(invalid_state());
+        }
+        let max_size = match &state[0] {
+            ScalarValue::UInt64(Some(max_size)) => *max_size as usize,
+            _ => return Err(invalid_state()),
+        };
+        let centroids = match &state[5] {
+            ScalarValue::List(Some(values), _) => values
+                .chunks(2)
+                .map(|pair| match pair {
+                    [mean, weight] => Ok(Centroid::new(float(mean)?, float(weight)?)),
+                    _ => Err(invalid_state()),
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => return Err(invalid_state()),
+        };
+        Ok(TDigest {
+            centroids,
+            max_size,
+            sum: float(&state[1])?,
+            count: float(&state[2])?,
+            max: float(&state[3])?,
+            min: float(&state[4])?,
+        })
+    }
+}
+
+/// The `k1` scale function, mapping a quantile to a centroid index
+fn k(q: f64, max_size: usize) -> f64 {
+    max_size as f64 / (2.0 * PI) * (2.0 * q - 1.0).asin()
+}
+
+/// The inverse of [`k`]
+fn k_inverse(k: f64, max_size: usize) -> f64 {
+    let angle = (k * 2.0 * PI / max_size as f64).min(PI / 2.0);
+    (angle.sin() + 1.0) / 2.0
+}
+
+/// Merges the adjacent `centroids`, sorted by mean, as long as each merged
+/// centroid spans at most one unit of the scale function.
+fn compress(centroids: Vec<Centroid>, count: f64, max_size: usize) -> Vec<Centroid> {
+    let mut iter = centroids.into_iter();
+    let mut current = match iter.next() {
+        Some(first) => first,
+        None => return vec![],
+    };
+    let mut compressed = Vec::with_capacity(max_size);
+    let mut weight_so_far = 0.0;
+    let mut weight_limit = count * k_inverse(k(0.0, max_size) + 1.0, max_size);
+    for centroid in iter {
+        if weight_so_far + current.weight + centroid.weight <= weight_limit {
+            current.add(&centroid);
+        } else {
+            weight_so_far += current.weight;
+            let q = weight_so_far / count;
+            weight_limit = count * k_inverse(k(q, max_size) + 1.0, max_size);
+            compressed.push(current);
+            current = centroid;
+        }
+    }
+    compressed.push(current);
+    compressed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64, tolerance: f64) {
+        assert!(
+            (expected - actual).abs() <= tolerance,
+            "expected {} +/- {}, got {}",
+            expected,
+            tolerance,
+            actual
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let digest = TDigest::new(DEFAULT_MAX_SIZE);
+        assert_eq!(digest.estimate_quantile(0.5), None);
+        assert!(digest.merge_sorted(&[]).is_empty());
+    }
+
+    #[test]
+    fn small_inputs_are_exact() {
+        let digest = TDigest::new(DEFAULT_MAX_SIZE)
+            .merge_unsorted(vec![4.0, 1.0, 10.0, 7.0, 3.0, 2.0, 9.0, 5.0, 8.0, 6.0]);
+        assert_eq!(digest.count, 10.0);
+        assert_eq!(digest.estimate_quantile(0.0), Some(1.0));
+        assert_eq!(digest.estimate_quantile(0.5), Some(5.5));
+        assert_eq!(digest.estimate_quantile(1.0), Some(10.0));
+    }
+
+    #[test]
+    fn large_inputs_are_bounded() {
+        let values = (1..=100_000).map(|v| v as f64).collect::<Vec<_>>();
+        let digest = TDigest::new(DEFAULT_MAX_SIZE).merge_sorted(&values);
+        assert!(digest.centroids.len() <= DEFAULT_MAX_SIZE);
+        assert_eq!(digest.estimate_quantile(0.0), Some(1.0));
+        assert_eq!(digest.estimate_quantile(1.0), Some(100_000.0));
+        assert_close(50_000.0, digest.estimate_quantile(0.5).unwrap(), 500.0);
+        assert_close(99_000.0, digest.estimate_quantile(0.99).unwrap(), 100.0);
+    }
+
+    #[test]
+    fn merging_digests() {
+        let digests = (0..10)
+            .map(|partition| {
+                let values = (0..10_000)
+                    .map(|v| (v * 10 + partition) as f64)
+                    .collect::<Vec<_>>();
+                TDigest::new(DEFAULT_MAX_SIZE).merge_unsorted(values)
+            })
+            .collect::<Vec<_>>();
+        let merged = TDigest::merge_digests(&digests);
+        assert_eq!(merged.count, 100_000.0);
+        assert!(merged.centroids.len() <= DEFAULT_MAX_SIZE);
+        assert_eq!(merged.estimate_quantile(0.0), Some(0.0));
+        assert_eq!(merged.estimate_quantile(1.0), Some(99_999.0));
+        assert_close(50_000.0, merged.estimate_quantile(0.5).unwrap(), 500.0);
+    }
+
+    #[test]
+    fn scalar_state_round_trip() -> Result<()> {
+        let digest =
+            TDigest::new(DEFAULT_MAX_SIZE).merge_unsorted(vec![3.0, 1.0, 2.0, 2.0]);
+        let state = digest.to_scalar_state();
+        assert_eq!(TDigest::from_scalar_state(&state)?, digest);
+
+        let empty = TDigest::new(DEFAULT_MAX_SIZE);
+        let state = empty.to_scalar_state();
+        assert!(TDigest::from_scalar_state(&state)?.is_empty());
+        Ok(())
+    }
+}
@@ -218,9 +218,11 @@ pub(super) fn signature_for_built_in(fun: &BuiltInWindowFunction) -> Signature {
         BuiltInWindowFunction::FirstValue | BuiltInWindowFunction::LastValue => {
             Signature::any(1, Volatility::Immutable)
         }
-        BuiltInWindowFunction::Ntile => {
-            Signature::exact(vec![DataType::UInt64], Volatility::Immutable)
-        }
+        BuiltInWindowFunction::Ntile => Signature::uniform(
+            1,
+            vec![DataType::Int64, DataType::UInt64],
+            Volatility::Immutable,
+        ),
         BuiltInWindowFunction::NthValue => Signature::any(2, Volatility::Immutable),
     }
 }
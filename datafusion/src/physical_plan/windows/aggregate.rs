@@ -18,14 +18,27 @@
 //! Physical exec for aggregate window function expressions.
 
 use crate::error::{DataFusionError, Result};
-use crate::logical_plan::window_frames::{WindowFrame, WindowFrameUnits};
+use crate::logical_plan::window_frames::{
+    WindowFrame, WindowFrameBound, WindowFrameOffset, WindowFrameUnits,
+};
 use crate::physical_plan::windows::find_ranges_in_range;
 use crate::physical_plan::{
     expressions::PhysicalSortExpr, Accumulator, AggregateExpr, PhysicalExpr, WindowExpr,
 };
-use arrow::compute::concat;
+use crate::scalar::ScalarValue;
+use arrow::array::{
+    as_primitive_array, new_empty_array, Array, DecimalArray, Float64Array,
+};
+use arrow::compute::{cast, concat};
+use arrow::datatypes::{
+    ArrowPrimitiveType, DataType, Date32Type, Date64Type, Int16Type, Int32Type,
+    Int64Type, Int8Type, TimeUnit, TimestampMicrosecondType, TimestampMillisecondType,
+    TimestampNanosecondType, TimestampSecondType, UInt16Type, UInt32Type, UInt64Type,
+    UInt8Type,
+};
 use arrow::record_batch::RecordBatch;
 use arrow::{array::ArrayRef, datatypes::Field};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
 use std::any::Any;
 use std::iter::IntoIterator;
 use std::ops::Range;
@@ -56,16 +69,13 @@ impl AggregateWindowExpr {
         }
     }
 
-    /// the aggregate window function operates based on window frame, and by default the mode is
-    /// "range".
-    fn evaluation_mode(&self) -> WindowFrameUnits {
-        self.window_frame.unwrap_or_default().units
-    }
-
     /// create a new accumulator based on the underlying aggregation function
     fn create_accumulator(&self) -> Result<AggregateWindowAccumulator> {
         let accumulator = self.aggregate.create_accumulator()?;
-        Ok(AggregateWindowAccumulator { accumulator })
+        Ok(AggregateWindowAccumulator {
+            accumulator,
+            frame: None,
+        })
     }
 
     /// peer based evaluation based on the fact that batch is pre-sorted given the sort columns
@@ -97,18 +107,409 @@ impl AggregateWindowExpr {
         concat(&results).map_err(DataFusionError::ArrowError)
     }
 
-    fn group_based_evaluate(&self, _batch: &RecordBatch) -> Result<ArrayRef> {
-        Err(DataFusionError::NotImplemented(format!(
-            "Group based evaluation for {} is not yet implemented",
-            self.name()
-        )))
+    /// evaluation of an explicit window frame: the frame of each row is computed from the frame
+    /// bounds, and as the batch is sorted the frames only move forward within a partition, so the
+    /// accumulator slides over them, retracting the rows that leave the frame when it supports
+    /// it and recomputing the frame otherwise.
+    fn frame_based_evaluate(
+        &self,
+        batch: &RecordBatch,
+        window_frame: &WindowFrame,
+    ) -> Result<ArrayRef> {
+        let num_rows = batch.num_rows();
+        if num_rows == 0 {
+            return Ok(new_empty_array(self.field()?.data_type()));
+        }
+        let partition_points =
+            self.evaluate_partition_points(num_rows, &self.partition_columns(batch)?)?;
+        let sort_partition_points =
+            self.evaluate_partition_points(num_rows, &self.sort_columns(batch)?)?;
+        let range_keys = if window_frame.units == WindowFrameUnits::Range
+            && [window_frame.start_bound, window_frame.end_bound]
+                .iter()
+                .any(has_offset)
+        {
+            Some(self.range_keys(batch, window_frame)?)
+        } else {
+            None
+        };
+        let values = self.evaluate_args(batch)?;
+        let mut results = Vec::with_capacity(num_rows);
+        for partition_range in &partition_points {
+            let frames = PartitionFrames::new(
+                window_frame,
+                partition_range.clone(),
+                find_ranges_in_range(partition_range, &sort_partition_points),
+                range_keys.as_ref(),
+            );
+            let mut accumulator = self.create_accumulator()?;
+            for (group_idx, peers) in frames.peer_ranges.iter().enumerate() {
+                for row in peers.clone() {
+                    let frame = frames.frame(row, group_idx)?;
+                    if !accumulator.can_slide_to(&frame) {
+                        accumulator = self.create_accumulator()?;
+                    }
+                    results.push(accumulator.slide(&values, frame)?);
+                }
+            }
+        }
+        ScalarValue::iter_to_array(results)
+    }
+
+    /// the values of the single order by expression of a RANGE frame with offsets
+    fn range_keys(
+        &self,
+        batch: &RecordBatch,
+        window_frame: &WindowFrame,
+    ) -> Result<RangeKeys> {
+        let sort_expr = match self.order_by.as_slice() {
+            [sort_expr] => sort_expr,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "With window frame of type RANGE, the order by expression must be of length 1, got {}",
+                    self.order_by.len()
+                )))
+            }
+        };
+        let values = sort_expr.expr.evaluate(batch)?.into_array(batch.num_rows());
+        let keys = match values.data_type() {
+            DataType::Int8 => KeyValues::Int(int_keys::<Int8Type>(&values, 1), 1),
+            DataType::Int16 => KeyValues::Int(int_keys::<Int16Type>(&values, 1), 1),
+            DataType::Int32 => KeyValues::Int(int_keys::<Int32Type>(&values, 1), 1),
+            DataType::Int64 => KeyValues::Int(int_keys::<Int64Type>(&values, 1), 1),
+            DataType::UInt8 => KeyValues::Int(int_keys::<UInt8Type>(&values, 1), 1),
+            DataType::UInt16 => KeyValues::Int(int_keys::<UInt16Type>(&values, 1), 1),
+            DataType::UInt32 => KeyValues::Int(int_keys::<UInt32Type>(&values, 1), 1),
+            DataType::UInt64 => KeyValues::Int(int_keys::<UInt64Type>(&values, 1), 1),
+            DataType::Decimal(_, scale) => {
+                let values = values.as_any().downcast_ref::<DecimalArray>().unwrap();
+                let keys = (0..values.len())
+                    .map(|i| (!values.is_null(i)).then(|| values.value(i)))
+                    .collect();
+                KeyValues::Int(keys, 10_i128.pow(*scale as u32))
+            }
+            DataType::Float32 | DataType::Float64 => {
+                let values = cast(&values, &DataType::Float64)?;
+                let values = values.as_any().downcast_ref::<Float64Array>().unwrap();
+                KeyValues::Float(values.iter().collect())
+            }
+            DataType::Date32 => KeyValues::Temporal(int_keys::<Date32Type>(
+                &values,
+                NANOS_PER_SECOND * 86_400,
+            )),
+            DataType::Date64 => {
+                KeyValues::Temporal(int_keys::<Date64Type>(&values, 1_000_000))
+            }
+            DataType::Timestamp(TimeUnit::Second, _) => KeyValues::Temporal(int_keys::<
+                TimestampSecondType,
+            >(
+                &values, NANOS_PER_SECOND
+            )),
+            DataType::Timestamp(TimeUnit::Millisecond, _) => KeyValues::Temporal(
+                int_keys::<TimestampMillisecondType>(&values, 1_000_000),
+            ),
+            DataType::Timestamp(TimeUnit::Microsecond, _) => KeyValues::Temporal(
+                int_keys::<TimestampMicrosecondType>(&values, 1_000),
+            ),
+            DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+                KeyValues::Temporal(int_keys::<TimestampNanosecondType>(&values, 1))
+            }
+            data_type => {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "RANGE window frames with offsets are only supported on numeric, date and timestamp order by expressions, got {}",
+                    data_type
+                )))
+            }
+        };
+        let is_temporal = matches!(keys, KeyValues::Temporal(_));
+        for bound in [window_frame.start_bound, window_frame.end_bound].iter() {
+            if let WindowFrameBound::Preceding(Some(offset))
+            | WindowFrameBound::Following(Some(offset)) = bound
+            {
+                if !offset.is_zero() && offset.is_interval() != is_temporal {
+                    return Err(DataFusionError::Plan(format!(
+                        "RANGE window frames on {} order by expressions can't have the offset {}",
+                        values.data_type(),
+                        offset
+                    )));
+                }
+            }
+        }
+        Ok(RangeKeys {
+            keys,
+            descending: sort_expr.options.descending,
+        })
+    }
+}
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+/// the values of the integer, date or timestamp array `values` multiplied by `unit`
+fn int_keys<T>(values: &ArrayRef, unit: i128) -> Vec<Option<i128>>
+where
+    T: ArrowPrimitiveType,
+    T::Native: Into<i128>,
+{
+    as_primitive_array::<T>(values)
+        .iter()
+        .map(|v| v.map(|v| v.into() * unit))
+        .collect()
+}
+
+/// whether the bound is `<expr> PRECEDING` or `<expr> FOLLOWING` with a non-zero `<expr>`
+fn has_offset(bound: &WindowFrameBound) -> bool {
+    matches!(
+        bound,
+        WindowFrameBound::Preceding(Some(offset)) | WindowFrameBound::Following(Some(offset)) if !offset.is_zero()
+    )
+}
+
+/// The order by values of a RANGE frame with offsets, sorted within each partition, in a type
+/// whose arithmetic and comparisons are exact for them
+struct RangeKeys {
+    keys: KeyValues,
+    descending: bool,
+}
+
+enum KeyValues {
+    /// integers, and decimals as their unscaled values with the scale factor of the offsets
+    Int(Vec<Option<i128>>, i128),
+    Float(Vec<Option<f64>>),
+    /// dates and timestamps as nanoseconds since the epoch
+    Temporal(Vec<Option<i128>>),
+}
+
+impl RangeKeys {
+    fn is_null(&self, row: usize) -> bool {
+        match &self.keys {
+            KeyValues::Int(keys, _) | KeyValues::Temporal(keys) => keys[row].is_none(),
+            KeyValues::Float(keys) => keys[row].is_none(),
+        }
+    }
+
+    /// the index of the first row of `range`, whose keys are not null, from which the key is
+    /// past the key of `row` moved by `offset`, preceding or following it in the sort order.
+    /// Within the offset means before this index if `is_start`, otherwise from it.
+    fn bound_index(
+        &self,
+        row: usize,
+        offset: &WindowFrameOffset,
+        preceding: bool,
+        is_start: bool,
+        range: Range<usize>,
+    ) -> usize {
+        // whether the target is greater than the key of the row
+        let forward = preceding == self.descending;
+        let index = match (&self.keys, offset) {
+            (KeyValues::Int(keys, scale), WindowFrameOffset::Value(n)) => {
+                let n = (*n as i128).saturating_mul(*scale);
+                let key = keys[row].unwrap();
+                let target = if forward {
+                    key.saturating_add(n)
+                } else {
+                    key.saturating_sub(n)
+                };
+                self.partition_point(&keys[range.clone()], target, is_start)
+            }
+            (KeyValues::Float(keys), WindowFrameOffset::Value(n)) => {
+                let key = keys[row].unwrap();
+                let target = if forward {
+                    key + *n as f64
+                } else {
+                    key - *n as f64
+                };
+                self.partition_point(&keys[range.clone()], target, is_start)
+            }
+            (KeyValues::Temporal(keys), WindowFrameOffset::DayTime(v)) => {
+                let (days, millis) = ((*v >> 32) as i128, (*v as i32) as i128);
+                let n = days * NANOS_PER_SECOND * 86_400 + millis * 1_000_000;
+                let key = keys[row].unwrap();
+                let target = if forward { key + n } else { key - n };
+                self.partition_point(&keys[range.clone()], target, is_start)
+            }
+            (KeyValues::Temporal(keys), WindowFrameOffset::YearMonth(months)) => {
+                let months = if forward { *months } else { -*months };
+                let target = shift_months(keys[row].unwrap(), months);
+                self.partition_point(&keys[range.clone()], target, is_start)
+            }
+            // rejected by `AggregateWindowExpr::range_keys`
+            _ => unreachable!(),
+        };
+        range.start + index
+    }
+
+    /// the number of the sorted non-null `keys` before the first one past `target` if
+    /// `is_start`, otherwise before the first one past or at `target`
+    fn partition_point<T: PartialOrd + Copy>(
+        &self,
+        keys: &[Option<T>],
+        target: T,
+        is_start: bool,
+    ) -> usize {
+        keys.partition_point(|k| {
+            let k = k.unwrap();
+            match (self.descending, is_start) {
+                (false, true) => k < target,
+                (false, false) => k <= target,
+                (true, true) => k > target,
+                (true, false) => k >= target,
+            }
+        })
+    }
+}
+
+/// the timestamp `nanos`, in nanoseconds since the epoch, moved by `months` months, with
+/// its day of the month clamped to the last one of the target month
+fn shift_months(nanos: i128, months: i32) -> i128 {
+    let shifted = i64::try_from(nanos.div_euclid(NANOS_PER_SECOND))
+        .ok()
+        .and_then(|secs| {
+            NaiveDateTime::from_timestamp_opt(
+                secs,
+                nanos.rem_euclid(NANOS_PER_SECOND) as u32,
+            )
+        })
+        .and_then(|datetime| {
+            let month =
+                datetime.year() as i64 * 12 + datetime.month0() as i64 + months as i64;
+            let year = i32::try_from(month.div_euclid(12)).ok()?;
+            let month = month.rem_euclid(12) as u32 + 1;
+            (1..=datetime.day())
+                .rev()
+                .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                .map(|date| date.and_time(datetime.time()))
+        });
+    match shifted {
+        Some(datetime) => {
+            datetime.timestamp() as i128 * NANOS_PER_SECOND
+                + datetime.timestamp_subsec_nanos() as i128
+        }
+        // out of the range of dates, so past all the keys
+        None if months < 0 => i128::MIN,
+        None => i128::MAX,
+    }
+}
+
+/// Computes the frames of the rows of one partition
+struct PartitionFrames<'a> {
+    window_frame: &'a WindowFrame,
+    partition_range: Range<usize>,
+    /// the peer groups of the partition
+    peer_ranges: &'a [Range<usize>],
+    /// the order by values of the batch for RANGE frames with offsets
+    range_keys: Option<&'a RangeKeys>,
+    /// the rows of the partition whose key is not null, the null keys are sorted either
+    /// before or after them
+    non_null_range: Range<usize>,
+}
+
+impl<'a> PartitionFrames<'a> {
+    fn new(
+        window_frame: &'a WindowFrame,
+        partition_range: Range<usize>,
+        peer_ranges: &'a [Range<usize>],
+        range_keys: Option<&'a RangeKeys>,
+    ) -> Self {
+        let non_null_range = match range_keys {
+            Some(keys) => {
+                let Range { start, end } = partition_range;
+                let non_null_rows =
+                    (start..end).filter(|row| !keys.is_null(*row)).count();
+                if keys.is_null(start) {
+                    end - non_null_rows..end
+                } else {
+                    start..start + non_null_rows
+                }
+            }
+            None => partition_range.clone(),
+        };
+        Self {
+            window_frame,
+            partition_range,
+            peer_ranges,
+            range_keys,
+            non_null_range,
+        }
+    }
+
+    /// the frame of the row `row` of the peer group `group_idx`, which can be empty
+    fn frame(&self, row: usize, group_idx: usize) -> Result<Range<usize>> {
+        let start =
+            self.bound_index(&self.window_frame.start_bound, row, group_idx, true)?;
+        let end =
+            self.bound_index(&self.window_frame.end_bound, row, group_idx, false)?;
+        Ok(start.min(end)..end)
     }
 
-    fn row_based_evaluate(&self, _batch: &RecordBatch) -> Result<ArrayRef> {
-        Err(DataFusionError::NotImplemented(format!(
-            "Row based evaluation for {} is not yet implemented",
-            self.name()
-        )))
+    /// the index of the first row of the frame if `is_start`, otherwise the index after its
+    /// last row
+    fn bound_index(
+        &self,
+        bound: &WindowFrameBound,
+        row: usize,
+        group_idx: usize,
+        is_start: bool,
+    ) -> Result<usize> {
+        let Range { start, end } = self.partition_range;
+        let (offset, preceding) = match bound {
+            WindowFrameBound::Preceding(None) => return Ok(start),
+            WindowFrameBound::Following(None) => return Ok(end),
+            WindowFrameBound::Preceding(Some(offset)) => (*offset, true),
+            WindowFrameBound::CurrentRow => (WindowFrameOffset::Value(0), false),
+            WindowFrameBound::Following(Some(offset)) => (*offset, false),
+        };
+        if let WindowFrameUnits::Range = self.window_frame.units {
+            return Ok(match self.range_keys {
+                Some(keys) if !offset.is_zero() && !keys.is_null(row) => keys
+                    .bound_index(
+                        row,
+                        &offset,
+                        preceding,
+                        is_start,
+                        self.non_null_range.clone(),
+                    ),
+                // the null keys and the current row bounds refer to the peers of the row
+                _ => {
+                    let peers = &self.peer_ranges[group_idx];
+                    if is_start {
+                        peers.start
+                    } else {
+                        peers.end
+                    }
+                }
+            });
+        }
+        let offset = match offset {
+            WindowFrameOffset::Value(n) if preceding => -(n as i128),
+            WindowFrameOffset::Value(n) => n as i128,
+            _ => {
+                return Err(DataFusionError::Plan(format!(
+                    "Invalid window frame: {} frames can't have interval offsets",
+                    self.window_frame.units
+                )))
+            }
+        };
+        Ok(match self.window_frame.units {
+            WindowFrameUnits::Rows => {
+                let index = row as i128 + offset + (!is_start) as i128;
+                index.clamp(start as i128, end as i128) as usize
+            }
+            _ => {
+                let group_idx = group_idx as i128 + offset;
+                if group_idx < 0 {
+                    start
+                } else if group_idx >= self.peer_ranges.len() as i128 {
+                    end
+                } else {
+                    let peers = &self.peer_ranges[group_idx as usize];
+                    if is_start {
+                        peers.start
+                    } else {
+                        peers.end
+                    }
+                }
+            }
+        })
     }
 }
 
@@ -140,10 +541,11 @@ impl WindowExpr for AggregateWindowExpr {
 
     /// evaluate the window function values against the batch
     fn evaluate(&self, batch: &RecordBatch) -> Result<ArrayRef> {
-        match self.evaluation_mode() {
-            WindowFrameUnits::Range => self.peer_based_evaluate(batch),
-            WindowFrameUnits::Rows => self.row_based_evaluate(batch),
-            WindowFrameUnits::Groups => self.group_based_evaluate(batch),
+        match &self.window_frame {
+            Some(window_frame) if *window_frame != WindowFrame::default() => {
+                self.frame_based_evaluate(batch, window_frame)
+            }
+            _ => self.peer_based_evaluate(batch),
         }
     }
 }
@@ -153,6 +555,8 @@ impl WindowExpr for AggregateWindowExpr {
 #[derive(Debug)]
 struct AggregateWindowAccumulator {
     accumulator: Box<dyn Accumulator>,
+    /// the rows accumulated by `slide`
+    frame: Option<Range<usize>>,
 }
 
 impl AggregateWindowAccumulator {
@@ -177,4 +581,41 @@ impl AggregateWindowAccumulator {
         let value = self.accumulator.evaluate()?;
         Ok(value.to_array_of_size(len))
     }
+
+    /// whether `slide` can move the accumulated rows to `frame`: the rows before the frame can
+    /// only be removed by retracting them.
+    fn can_slide_to(&self, frame: &Range<usize>) -> bool {
+        match &self.frame {
+            Some(current) => {
+                current.start == frame.start || self.accumulator.supports_retract_batch()
+            }
+            None => true,
+        }
+    }
+
+    /// accumulate the rows of `frame`, which must not start or end before the previous frame,
+    /// and return the evaluation result of the frame.
+    fn slide(&mut self, values: &[ArrayRef], frame: Range<usize>) -> Result<ScalarValue> {
+        let current = self.frame.take().unwrap_or(frame.start..frame.start);
+        // the rows entering the frame are added before the ones leaving it are retracted, as
+        // the frames might not overlap
+        if frame.end > current.end {
+            self.accumulator
+                .update_batch(&slice_values(values, current.end..frame.end))?;
+        }
+        if frame.start > current.start {
+            self.accumulator
+                .retract_batch(&slice_values(values, current.start..frame.start))?;
+        }
+        let value = self.accumulator.evaluate()?;
+        self.frame = Some(frame);
+        Ok(value)
+    }
+}
+
+fn slice_values(values: &[ArrayRef], range: Range<usize>) -> Vec<ArrayRef> {
+    values
+        .iter()
+        .map(|v| v.slice(range.start, range.end - range.start))
+        .collect()
 }
@@ -22,7 +22,7 @@ use crate::logical_plan::window_frames::WindowFrame;
 use crate::physical_plan::{
     aggregates,
     expressions::{
-        cume_dist, dense_rank, lag, lead, percent_rank, rank, Literal, NthValue,
+        cume_dist, dense_rank, lag, lead, percent_rank, rank, Literal, NthValue, Ntile,
         PhysicalSortExpr, RowNumber,
     },
     type_coercion::coerce,
@@ -34,7 +34,7 @@ use crate::physical_plan::{
 };
 use crate::scalar::ScalarValue;
 use arrow::datatypes::Schema;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::ops::Range;
 use std::sync::Arc;
 
@@ -63,11 +63,19 @@ pub fn create_window_expr(
             order_by,
             window_frame,
         )),
-        WindowFunction::BuiltInWindowFunction(fun) => Arc::new(BuiltInWindowExpr::new(
-            create_built_in_window_expr(fun, args, input_schema, name)?,
-            partition_by,
-            order_by,
-        )),
+        WindowFunction::BuiltInWindowFunction(fun) => {
+            if matches!(window_frame, Some(frame) if frame != WindowFrame::default()) {
+                return Err(DataFusionError::NotImplemented(format!(
+                    "Window frame definition is not yet supported for the built-in window function {:?}",
+                    fun
+                )));
+            }
+            Arc::new(BuiltInWindowExpr::new(
+                create_built_in_window_expr(fun, args, input_schema, name)?,
+                partition_by,
+                order_by,
+            ))
+        }
     })
 }
 
@@ -96,6 +104,30 @@ fn create_built_in_window_expr(
         BuiltInWindowFunction::DenseRank => Arc::new(dense_rank(name)),
         BuiltInWindowFunction::PercentRank => Arc::new(percent_rank(name)),
         BuiltInWindowFunction::CumeDist => Arc::new(cume_dist(name)),
+        BuiltInWindowFunction::Ntile => {
+            let num_buckets = args
+                .get(0)
+                .and_then(|arg| arg.as_any().downcast_ref::<Literal>())
+                .and_then(|literal| match literal.value() {
+                    ScalarValue::Int8(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::Int16(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::Int32(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::Int64(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::UInt8(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::UInt16(Some(n)) => u32::try_from(*n).ok(),
+                    ScalarValue::UInt32(Some(n)) => Some(*n),
+                    ScalarValue::UInt64(Some(n)) => u32::try_from(*n).ok(),
+                    _ => None,
+                })
+                .filter(|n| *n > 0)
+                .ok_or_else(|| {
+                    DataFusionError::Plan(
+                        "NTILE requires a positive integer literal as number of buckets"
+                            .to_owned(),
+                    )
+                })?;
+            Arc::new(Ntile::new(name, num_buckets))
+        }
         BuiltInWindowFunction::Lag => {
             let coerced_args = coerce(args, input_schema, &signature_for_built_in(fun))?;
             let arg = coerced_args[0].clone();
@@ -200,6 +232,7 @@ mod tests {
                 batch_size: 1024,
                 limit: None,
                 table_partition_cols: vec![],
+                metadata_cache: None,
             },
             true,
             b',',
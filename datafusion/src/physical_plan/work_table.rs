@@ -0,0 +1,225 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+//! Defines the work table of recursive queries, holding the rows produced by
+//! their previous iteration
+
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+use arrow::datatypes::{Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use async_trait::async_trait;
+
+use super::memory::MemoryStream;
+use super::{
+    DisplayFormatType, ExecutionPlan, Partitioning, SendableRecordBatchStream, Statistics,
+};
+use crate::error::{DataFusionError, Result};
+
+/// The rows produced by the previous iteration of a recursive query, which
+/// are the input of its next iteration
+#[derive(Debug, Default)]
+pub struct WorkTable {
+    batches: Mutex<Option<Vec<RecordBatch>>>,
+}
+
+impl WorkTable {
+    /// Create an empty work table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the content of the work table with `batches`
+    pub fn update(&self, batches: Vec<RecordBatch>) {
+        *self.batches.lock().unwrap() = Some(batches);
+    }
+
+    /// The content of the work table
+    pub fn batches(&self) -> Result<Vec<RecordBatch>> {
+        self.batches.lock().unwrap().clone().ok_or_else(|| {
+            DataFusionError::Execution(
+                "The work table of a recursive query was read before being filled"
+                    .to_string(),
+            )
+        })
+    }
+}
+
+/// Execution plan scanning the work table of a recursive query, see
+/// [`RecursiveQueryExec`](super::recursive_query::RecursiveQueryExec)
+#[derive(Debug, Clone)]
+pub struct WorkTableExec {
+    /// Name of the recursive query
+    name: String,
+    /// Schema of the work table
+    schema: SchemaRef,
+    /// Optional projection
+    projection: Option<Vec<usize>>,
+    /// Schema after the projection is applied
+    projected_schema: SchemaRef,
+    /// The work table, shared with the recursive query
+    work_table: Arc<WorkTable>,
+}
+
+impl WorkTableExec {
+    /// Create a new scan of the work table of the recursive query `name`. It
+    /// reads a table of its own until it is planned within its
+    /// recursive query.
+    pub fn try_new(
+        name: String,
+        schema: SchemaRef,
+        projection: Option<Vec<usize>>,
+    ) -> Result<Self> {
+        let projected_schema = match &projection {
+            Some(columns) => Arc::new(Schema::new(
+                columns
+                    .iter()
+                    .map(|i| {
+                        schema.fields().get(*i).cloned().ok_or_else(|| {
+                            DataFusionError::Internal(
+                                "Projection index out of range".to_string(),
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+            )),
+            None => schema.clone(),
+        };
+        Ok(Self {
+            name,
+            schema,
+            projection,
+            projected_schema,
+            work_table: Arc::new(WorkTable::new()),
+        })
+    }
+
+    /// Name of the recursive query whose work table is scanned
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns a copy of this plan scanning `work_table`
+    pub fn with_work_table(&self, work_table: Arc<WorkTable>) -> Self {
+        Self {
+            work_table,
+            ..self.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionPlan for WorkTableExec {
+    /// Return a reference to Any that can be used for downcasting
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.projected_schema.clone()
+    }
+
+    fn children(&self) -> Vec<Arc<dyn ExecutionPlan>> {
+        vec![]
+    }
+
+    fn output_partitioning(&self) -> Partitioning {
+        Partitioning::UnknownPartitioning(1)
+    }
+
+    fn with_new_children(
+        &self,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        match children.len() {
+            0 => Ok(Arc::new(self.clone())),
+            _ => Err(DataFusionError::Internal(
+                "WorkTableExec wrong number of children".to_string(),
+            )),
+        }
+    }
+
+    async fn execute(&self, partition: usize) -> Result<SendableRecordBatchStream> {
+        if 0 != partition {
+            return Err(DataFusionError::Internal(format!(
+                "WorkTableExec invalid partition {} (expected 0)",
+                partition
+            )));
+        }
+
+        Ok(Box::pin(MemoryStream::try_new(
+            self.work_table.batches()?,
+            self.projected_schema.clone(),
+            self.projection.clone(),
+        )?))
+    }
+
+    fn fmt_as(
+        &self,
+        t: DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default => {
+                write!(f, "WorkTableExec: name={}", self.name)
+            }
+        }
+    }
+
+    fn statistics(&self) -> Statistics {
+        Statistics::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical_plan::common;
+    use arrow::array::Int32Array;
+    use arrow::datatypes::{DataType, Field};
+
+    #[tokio::test]
+    async fn work_table_scan() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Int32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![3, 4])),
+            ],
+        )?;
+
+        let work_table = Arc::new(WorkTable::new());
+        let exec = WorkTableExec::try_new("t".to_string(), schema, Some(vec![1]))?
+            .with_work_table(work_table.clone());
+        assert_eq!(exec.schema().fields().len(), 1);
+
+        // the work table must be filled before being scanned
+        assert!(exec.execute(0).await.is_err());
+
+        work_table.update(vec![batch]);
+        let batches = common::collect(exec.execute(0).await?).await?;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_columns(), 1);
+        assert_eq!(batches[0].column(0).len(), 2);
+        Ok(())
+    }
+}
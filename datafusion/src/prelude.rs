@@ -27,7 +27,7 @@
 
 pub use crate::dataframe::DataFrame;
 pub use crate::execution::context::{ExecutionConfig, ExecutionContext};
-pub use crate::execution::options::AvroReadOptions;
+pub use crate::execution::options::{ArrowReadOptions, AvroReadOptions};
 pub use crate::execution::options::{CsvReadOptions, NdJsonReadOptions};
 pub use crate::logical_plan::{
     array, ascii, avg, bit_length, btrim, character_length, chr, col, concat, concat_ws,
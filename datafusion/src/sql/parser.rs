@@ -20,13 +20,21 @@
 //! Declares a SQL parser based on sqlparser that handles custom formats that we need.
 
 use sqlparser::{
-    ast::{ColumnDef, ColumnOptionDef, Statement as SQLStatement, TableConstraint},
+    ast::{
+        ColumnDef, ColumnOptionDef, Expr as SQLExpr, ObjectName, Query,
+        Statement as SQLStatement, TableConstraint, Value,
+    },
     dialect::{keywords::Keyword, Dialect, GenericDialect},
     parser::{Parser, ParserError},
     tokenizer::{Token, Tokenizer},
 };
 use std::str::FromStr;
 
+use crate::datasource::file_format::compression::FileCompressionType;
+use crate::logical_plan::window_frames::{WindowFrameOffset, MAX_OFFSET_VALUE};
+use crate::physical_plan::aggregates::AggregateFunction;
+use crate::sql::planner::sql_interval_to_scalar;
+
 // Use `Parser::expected` instead, if possible
 macro_rules! parser_err {
     ($MSG:expr) => {
@@ -45,6 +53,8 @@ pub enum FileType {
     CSV,
     /// Avro binary records
     Avro,
+    /// Apache Arrow IPC file, also known as Feather V2
+    Arrow,
 }
 
 impl FromStr for FileType {
@@ -56,8 +66,9 @@ impl FromStr for FileType {
             "NDJSON" => Ok(Self::NdJson),
             "CSV" => Ok(Self::CSV),
             "AVRO" => Ok(Self::Avro),
+            "ARROW" => Ok(Self::Arrow),
             other => Err(ParserError::ParserError(format!(
-                "expect one of PARQUET, AVRO, NDJSON, or CSV, found: {}",
+                "expect one of PARQUET, AVRO, NDJSON, ARROW, or CSV, found: {}",
                 other
             ))),
         }
@@ -75,10 +86,33 @@ pub struct CreateExternalTable {
     pub file_type: FileType,
     /// CSV Header row?
     pub has_header: bool,
+    /// Compression of the files, inferred from the location if not set
+    pub file_compression_type: Option<FileCompressionType>,
     /// Path to file
     pub location: String,
 }
 
+/// The rows written by a `COPY` statement
+#[derive(Debug, Clone, PartialEq)]
+pub enum CopyToSource {
+    /// All the rows of a table
+    Relation(ObjectName),
+    /// The rows produced by a query
+    Query(Box<Query>),
+}
+
+/// DataFusion extension statement `COPY <table or (query)> TO '<path>' (options)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyTo {
+    /// The rows to write
+    pub source: CopyToSource,
+    /// The directory the files are written to
+    pub target: String,
+    /// The options such as `FORMAT parquet`, with lowercase names, in the
+    /// order they are listed
+    pub options: Vec<(String, String)>,
+}
+
 /// DataFusion Statement representations.
 ///
 /// Tokens parsed by `DFParser` are converted into these values.
@@ -88,6 +122,10 @@ pub enum Statement {
     Statement(Box<SQLStatement>),
     /// Extension: `CREATE EXTERNAL TABLE`
     CreateExternalTable(CreateExternalTable),
+    /// Extension: `COPY ... TO`
+    CopyTo(CopyTo),
+    /// Extension: `REFRESH TABLE <name>`
+    RefreshTable(ObjectName),
 }
 
 /// SQL Parser
@@ -109,6 +147,12 @@ impl<'a> DFParser<'a> {
     ) -> Result<Self, ParserError> {
         let mut tokenizer = Tokenizer::new(dialect, sql);
         let tokens = tokenizer.tokenize()?;
+        reject_aggregate_clause_calls(&tokens)?;
+        let tokens = rewrite_grouping_sets(tokens);
+        let tokens = rewrite_interval_frame_offsets(tokens)?;
+        let tokens = rewrite_within_group(tokens)?;
+        let tokens = rewrite_aggregate_order_by(&tokens)?;
+        let tokens = rewrite_aggregate_filter(&tokens)?;
 
         Ok(DFParser {
             parser: Parser::new(tokens, dialect),
@@ -165,6 +209,16 @@ impl<'a> DFParser<'a> {
                         // use custom parsing
                         self.parse_create()
                     }
+                    Keyword::COPY => {
+                        // move one token forward
+                        self.parser.next_token();
+                        self.parse_copy()
+                    }
+                    _ if w.value.eq_ignore_ascii_case("REFRESH") => {
+                        // move one token forward
+                        self.parser.next_token();
+                        self.parse_refresh()
+                    }
                     _ => {
                         // use the native parser
                         Ok(Statement::Statement(Box::from(
@@ -191,6 +245,67 @@ impl<'a> DFParser<'a> {
         }
     }
 
+    /// Parse a SQL `REFRESH TABLE <name>` statement
+    pub fn parse_refresh(&mut self) -> Result<Statement, ParserError> {
+        self.parser.expect_keyword(Keyword::TABLE)?;
+        let name = self.parser.parse_object_name()?;
+        Ok(Statement::RefreshTable(name))
+    }
+
+    /// Parse a SQL `COPY <table or (query)> TO '<path>' [(options)]` statement
+    pub fn parse_copy(&mut self) -> Result<Statement, ParserError> {
+        let source = if self.parser.consume_token(&Token::LParen) {
+            let query = self.parser.parse_query()?;
+            self.parser.expect_token(&Token::RParen)?;
+            CopyToSource::Query(Box::new(query))
+        } else {
+            CopyToSource::Relation(self.parser.parse_object_name()?)
+        };
+
+        self.parser.expect_keyword(Keyword::TO)?;
+        let target = self.parser.parse_literal_string()?;
+        let options = self.parse_copy_options()?;
+
+        Ok(Statement::CopyTo(CopyTo {
+            source,
+            target,
+            options,
+        }))
+    }
+
+    /// Parse the optional list `(name value, ...)` of the options of a `COPY`
+    fn parse_copy_options(&mut self) -> Result<Vec<(String, String)>, ParserError> {
+        let mut options = vec![];
+        if !self.parser.consume_token(&Token::LParen) {
+            return Ok(options);
+        }
+
+        loop {
+            let name = self.parser.parse_identifier()?.value.to_lowercase();
+            let value = match self.parser.next_token() {
+                Token::Word(w) => w.value,
+                Token::SingleQuotedString(s) => s,
+                Token::Number(n, _) => n,
+                unexpected => {
+                    return self.expected(
+                        &format!("a value for the option {}", name),
+                        unexpected,
+                    )
+                }
+            };
+            options.push((name, value));
+
+            let comma = self.parser.consume_token(&Token::Comma);
+            if self.parser.consume_token(&Token::RParen) {
+                break;
+            } else if !comma {
+                return self
+                    .expected("',' or ')' after an option", self.parser.peek_token());
+            }
+        }
+        Ok(options)
+    }
+
     // This is a copy of the equivalent implementation in sqlparser.
     fn parse_columns(
         &mut self,
@@ -275,6 +390,7 @@ impl<'a> DFParser<'a> {
         let file_type = self.parse_file_format()?;
 
         let has_header = self.parse_csv_has_header();
+        let file_compression_type = self.parse_file_compression_type()?;
 
         self.parser.expect_keyword(Keyword::LOCATION)?;
         let location = self.parser.parse_literal_string()?;
@@ -284,6 +400,7 @@ impl<'a> DFParser<'a> {
             columns,
             file_type,
             has_header,
+            file_compression_type,
             location,
         };
         Ok(Statement::CreateExternalTable(create))
@@ -308,6 +425,30 @@ impl<'a> DFParser<'a> {
         }
     }
 
+    /// Parses the optional `COMPRESSION TYPE <type>` clause
+    fn parse_file_compression_type(
+        &mut self,
+    ) -> Result<Option<FileCompressionType>, ParserError> {
+        if !self.consume_token(&Token::make_keyword("COMPRESSION")) {
+            return Ok(None);
+        }
+        if !self.consume_token(&Token::make_keyword("TYPE")) {
+            return self.expected("TYPE", self.parser.peek_token());
+        }
+        match self.parser.next_token() {
+            Token::Word(w) => match w.value.parse() {
+                Ok(file_compression_type) => Ok(Some(file_compression_type)),
+                Err(_) => parser_err!(format!(
+                    "expect one of GZIP, BZIP2, XZ, ZSTD or UNCOMPRESSED, found: {}",
+                    w.value
+                )),
+            },
+            unexpected => {
+                self.expected("one of GZIP, BZIP2, XZ, ZSTD or UNCOMPRESSED", unexpected)
+            }
+        }
+    }
+
     fn parse_csv_has_header(&mut self) -> bool {
         self.consume_token(&Token::make_keyword("WITH"))
             & self.consume_token(&Token::make_keyword("HEADER"))
@@ -315,9 +456,542 @@ impl<'a> DFParser<'a> {
     }
 }
 
+/// Name of the function call `GROUPING SETS (...)` is rewritten to
+pub(crate) const GROUPING_SETS_FUNCTION: &str = "grouping_sets";
+/// Name of the function call a parenthesized set within `GROUPING SETS` is
+/// rewritten to
+pub(crate) const GROUPING_SET_FUNCTION: &str = "grouping_set";
+
+/// The SQL parser does not know about `GROUPING SETS`, so rewrite
+/// `GROUPING SETS ((a, b), c, ())` into the function calls
+/// `GROUPING_SETS(GROUPING_SET(a, b), c, GROUPING_SET())` which the SQL
+/// planner turns into an [`Expr::GroupingSet`](crate::logical_plan::Expr).
+fn rewrite_grouping_sets(tokens: Vec<Token>) -> Vec<Token> {
+    let next_non_whitespace = |from: usize| next_non_whitespace(&tokens, from);
+
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    // the parenthesis depth of every open `GROUPING SETS (`
+    let mut open_sets: Vec<usize> = vec![];
+    let mut depth = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        if is_word(token, "GROUPING") {
+            let sets =
+                next_non_whitespace(i + 1).filter(|j| is_word(&tokens[*j], "SETS"));
+            let paren = sets
+                .and_then(|j| next_non_whitespace(j + 1))
+                .filter(|j| tokens[*j] == Token::LParen);
+            if let Some(paren) = paren {
+                rewritten.push(Token::make_word(GROUPING_SETS_FUNCTION, None));
+                rewritten.push(Token::LParen);
+                depth += 1;
+                open_sets.push(depth);
+                i = paren + 1;
+                continue;
+            }
+        }
+        match token {
+            Token::LParen => {
+                if open_sets.last() == Some(&depth) {
+                    rewritten.push(Token::make_word(GROUPING_SET_FUNCTION, None));
+                }
+                depth += 1;
+            }
+            Token::RParen => {
+                if open_sets.last() == Some(&depth) {
+                    open_sets.pop();
+                }
+                depth = depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+        rewritten.push(token.clone());
+        i += 1;
+    }
+    rewritten
+}
+
+/// The functions accepting a `WITHIN GROUP (ORDER BY ...)` clause
+const ORDERED_SET_FUNCTIONS: &[&str] = &["percentile_cont", "approx_percentile_cont"];
+
+/// The SQL parser does not know about the `WITHIN GROUP` clause of
+/// ordered-set aggregates either, so rewrite
+/// `PERCENTILE_CONT(0.25) WITHIN GROUP (ORDER BY x DESC)` into the function
+/// call `PERCENTILE_CONT(x, 0.75)`, the percentile of a descending order being
+/// the complement of the percentile of the ascending order.
+fn rewrite_within_group(tokens: Vec<Token>) -> Result<Vec<Token>, ParserError> {
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_ordered_set_function = ORDERED_SET_FUNCTIONS
+            .iter()
+            .any(|function| is_word(&tokens[i], function));
+        if is_ordered_set_function {
+            if let Some((call, end)) = within_group_call(&tokens, i)? {
+                rewritten.extend(call);
+                i = end;
+                continue;
+            }
+        }
+        rewritten.push(tokens[i].clone());
+        i += 1;
+    }
+    Ok(rewritten)
+}
+
+/// Returns the rewritten call of the ordered-set function at `start` and the
+/// index of the first token after its `WITHIN GROUP` clause, `None` if the
+/// function has no such clause.
+fn within_group_call(
+    tokens: &[Token],
+    start: usize,
+) -> Result<Option<(Vec<Token>, usize)>, ParserError> {
+    let args_open = match next_non_whitespace(tokens, start + 1) {
+        Some(open) if tokens[open] == Token::LParen => open,
+        _ => return Ok(None),
+    };
+    let args_close = match matching_paren(tokens, args_open) {
+        Some(close) => close,
+        None => return Ok(None),
+    };
+    let within = match next_non_whitespace(tokens, args_close + 1) {
+        Some(within) if is_word(&tokens[within], "WITHIN") => within,
+        _ => return Ok(None),
+    };
+
+    let mut expected = within;
+    let mut order_open = within;
+    for keyword in ["GROUP", "(", "ORDER", "BY"] {
+        expected = match next_non_whitespace(tokens, expected + 1) {
+            Some(next) if keyword == "(" && tokens[next] == Token::LParen => {
+                order_open = next;
+                next
+            }
+            Some(next) if is_word(&tokens[next], keyword) => next,
+            _ => return parser_err!("Expected WITHIN GROUP (ORDER BY ...)"),
+        };
+    }
+    let order_close = match matching_paren(tokens, order_open) {
+        Some(close) => close,
+        None => return parser_err!("Expected ) after WITHIN GROUP (ORDER BY ..."),
+    };
+
+    let mut order_by = trim_whitespace(&tokens[expected + 1..order_close]);
+    // nulls are ignored by the aggregates, so is their order
+    for nulls_order in ["FIRST", "LAST"] {
+        if let Some(rest) = strip_last_word(order_by, nulls_order)
+            .and_then(|rest| strip_last_word(rest, "NULLS"))
+        {
+            order_by = rest;
+        }
+    }
+    let mut descending = false;
+    if let Some(rest) = strip_last_word(order_by, "DESC") {
+        order_by = rest;
+        descending = true;
+    } else if let Some(rest) = strip_last_word(order_by, "ASC") {
+        order_by = rest;
+    }
+    let mut depth = 0;
+    for token in order_by {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            Token::Comma if depth == 0 => {
+                return parser_err!(
+                    "WITHIN GROUP (ORDER BY ...) takes a single ordering expression"
+                )
+            }
+            _ => {}
+        }
+    }
+
+    let args = trim_whitespace(&tokens[args_open + 1..args_close]);
+    let mut call = tokens[start..=args_open].to_vec();
+    call.extend_from_slice(order_by);
+    call.push(Token::Comma);
+    match args {
+        [Token::Number(percentile, long)] if descending => {
+            let percentile = f64::from_str(percentile).map_err(|_| {
+                ParserError::ParserError(format!("Invalid percentile {}", percentile))
+            })?;
+            call.push(Token::Number((1.0 - percentile).to_string(), *long));
+        }
+        _ if descending => {
+            call.extend([Token::Number("1".to_string(), false), Token::Minus]);
+            call.push(Token::LParen);
+            call.extend_from_slice(args);
+            call.push(Token::RParen);
+        }
+        _ => call.extend_from_slice(args),
+    }
+    call.push(Token::RParen);
+    Ok(Some((call, order_close + 1)))
+}
+
+/// Name of the function call an aggregate with an `ORDER BY` clause is
+/// rewritten to
+pub(crate) const AGGREGATE_ORDER_BY_FUNCTION: &str = "aggregate_order_by";
+/// Name of the function call an aggregate with a `FILTER (WHERE ...)` clause
+/// is rewritten to
+pub(crate) const AGGREGATE_FILTER_FUNCTION: &str = "aggregate_filter";
+
+/// The functions aggregates with clauses are rewritten to are internal, so
+/// reject the queries calling them directly before any rewrite.
+fn reject_aggregate_clause_calls(tokens: &[Token]) -> Result<(), ParserError> {
+    for (i, token) in tokens.iter().enumerate() {
+        let name = match token {
+            Token::Word(w) if w.quote_style.is_some() => w.value.clone(),
+            Token::Word(w) => w.value.to_ascii_lowercase(),
+            _ => continue,
+        };
+        let is_call = matches!(
+            next_non_whitespace(tokens, i + 1),
+            Some(open) if tokens[open] == Token::LParen
+        );
+        if is_call
+            && (name == AGGREGATE_ORDER_BY_FUNCTION || name == AGGREGATE_FILTER_FUNCTION)
+        {
+            return parser_err!(format!(
+                "Function {} is reserved for the clauses of aggregate functions",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The SQL parser does not know about the `ORDER BY` clause within the
+/// arguments of aggregate functions, so rewrite
+/// `ARRAY_AGG(x ORDER BY y DESC, z)` into the function call
+/// `AGGREGATE_ORDER_BY(ARRAY_AGG(x), y, false, true, z, true, false)`, each
+/// ordering expression being followed by whether it is ascending and whether
+/// its nulls come first.
+fn rewrite_aggregate_order_by(tokens: &[Token]) -> Result<Vec<Token>, ParserError> {
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let is_aggregate = match &tokens[i] {
+            Token::Word(w) => {
+                w.quote_style.is_none()
+                    && AggregateFunction::from_str(&w.value.to_ascii_lowercase()).is_ok()
+            }
+            _ => false,
+        };
+        if is_aggregate {
+            if let Some((call, end)) = aggregate_order_by_call(tokens, i)? {
+                rewritten.extend(call);
+                i = end;
+                continue;
+            }
+        }
+        rewritten.push(tokens[i].clone());
+        i += 1;
+    }
+    Ok(rewritten)
+}
+
+/// Returns the rewritten call of the aggregate function at `start` and the
+/// index of the first token after it, `None` if its arguments have no
+/// `ORDER BY` clause.
+fn aggregate_order_by_call(
+    tokens: &[Token],
+    start: usize,
+) -> Result<Option<(Vec<Token>, usize)>, ParserError> {
+    let args_open = match next_non_whitespace(tokens, start + 1) {
+        Some(open) if tokens[open] == Token::LParen => open,
+        _ => return Ok(None),
+    };
+    let args_close = match matching_paren(tokens, args_open) {
+        Some(close) => close,
+        None => return Ok(None),
+    };
+    let args = &tokens[args_open + 1..args_close];
+    let order = match position_top_level(args, |token| is_word(token, "ORDER")) {
+        Some(order) => order,
+        None => return Ok(None),
+    };
+    let by = match next_non_whitespace(args, order + 1) {
+        Some(by) if is_word(&args[by], "BY") => by,
+        _ => return parser_err!("Expected BY after ORDER in aggregate function"),
+    };
+
+    let mut call = vec![
+        Token::make_word(AGGREGATE_ORDER_BY_FUNCTION, None),
+        Token::LParen,
+    ];
+    call.extend_from_slice(&tokens[start..=args_open]);
+    call.extend(rewrite_aggregate_order_by(&args[..order])?);
+    call.push(Token::RParen);
+    for key in split_top_level(&args[by + 1..], |token| *token == Token::Comma) {
+        let mut key = trim_whitespace(key);
+        let mut nulls_first = None;
+        for (nulls_order, first) in [("FIRST", true), ("LAST", false)] {
+            if let Some(rest) = strip_last_word(key, nulls_order)
+                .and_then(|rest| strip_last_word(rest, "NULLS"))
+            {
+                key = rest;
+                nulls_first = Some(first);
+            }
+        }
+        let mut asc = true;
+        if let Some(rest) = strip_last_word(key, "DESC") {
+            key = rest;
+            asc = false;
+        } else if let Some(rest) = strip_last_word(key, "ASC") {
+            key = rest;
+        }
+        if key.is_empty() {
+            return parser_err!("Expected an expression after ORDER BY");
+        }
+        // nulls last when ascending, to be consistent with the ORDER BY of
+        // queries
+        let nulls_first = nulls_first.unwrap_or(!asc);
+        call.push(Token::Comma);
+        call.extend(rewrite_aggregate_order_by(key)?);
+        for flag in [asc, nulls_first] {
+            call.push(Token::Comma);
+            call.push(Token::make_word(&flag.to_string(), None));
+        }
+    }
+    call.push(Token::RParen);
+    Ok(Some((call, args_close + 1)))
+}
+
+/// The SQL parser does not know about the `FILTER (WHERE ...)` clause of
+/// aggregate functions either, so rewrite `COUNT(*) FILTER (WHERE x > 1)`
+/// into the function call `AGGREGATE_FILTER(COUNT(*), x > 1)`.
+fn rewrite_aggregate_filter(tokens: &[Token]) -> Result<Vec<Token>, ParserError> {
+    let mut rewritten: Vec<Token> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let filter_open = Some(i)
+            .filter(|i| is_word(&tokens[*i], "FILTER"))
+            .and_then(|i| next_non_whitespace(tokens, i + 1))
+            .filter(|open| tokens[*open] == Token::LParen);
+        let filter_where = filter_open
+            .and_then(|open| next_non_whitespace(tokens, open + 1))
+            .filter(|w| is_word(&tokens[*w], "WHERE"));
+        let call_start = filter_where.and_then(|_| function_call_start(&rewritten));
+        if let (Some(open), Some(filter_where), Some(call_start)) =
+            (filter_open, filter_where, call_start)
+        {
+            let close = match matching_paren(tokens, open) {
+                Some(close) => close,
+                None => return parser_err!("Expected ) after FILTER (WHERE ..."),
+            };
+            let predicate = trim_whitespace(&tokens[filter_where + 1..close]);
+            if predicate.is_empty() {
+                return parser_err!("Expected an expression after FILTER (WHERE");
+            }
+            let call = rewritten.split_off(call_start);
+            rewritten.push(Token::make_word(AGGREGATE_FILTER_FUNCTION, None));
+            rewritten.push(Token::LParen);
+            rewritten.extend(trim_whitespace(&call).iter().cloned());
+            rewritten.push(Token::Comma);
+            rewritten.extend(rewrite_aggregate_filter(predicate)?);
+            rewritten.push(Token::RParen);
+            i = close + 1;
+            continue;
+        }
+        rewritten.push(tokens[i].clone());
+        i += 1;
+    }
+    Ok(rewritten)
+}
+
+/// The SQL parser only parses unsigned integers as the offsets of window
+/// frame bounds, so rewrite the interval of `INTERVAL '1' DAY PRECEDING` into
+/// the integer encoding it, which the SQL planner turns back into a
+/// [`WindowFrameOffset`].
+fn rewrite_interval_frame_offsets(tokens: Vec<Token>) -> Result<Vec<Token>, ParserError> {
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        match &tokens[i] {
+            Token::Word(_) if is_word(&tokens[i], "INTERVAL") => {
+                if let Some(bound) = interval_frame_bound(&tokens, i) {
+                    let offset =
+                        interval_frame_offset(trim_whitespace(&tokens[i..bound]))?;
+                    rewritten.push(Token::Number(offset.to_string(), false));
+                    i = bound;
+                    continue;
+                }
+            }
+            // the larger integers encode intervals
+            Token::Number(n, _) if is_frame_bound(&tokens, i + 1) => {
+                if matches!(n.parse::<u64>(), Ok(n) if n > MAX_OFFSET_VALUE) {
+                    return parser_err!(format!(
+                        "Window frame offset {} is larger than {}",
+                        n, MAX_OFFSET_VALUE
+                    ));
+                }
+            }
+            _ => {}
+        }
+        rewritten.push(tokens[i].clone());
+        i += 1;
+    }
+    Ok(rewritten)
+}
+
+/// Whether the first token from `from` that is not a whitespace is
+/// `PRECEDING` or `FOLLOWING`
+fn is_frame_bound(tokens: &[Token], from: usize) -> bool {
+    next_non_whitespace(tokens, from).map_or(false, |i| {
+        is_word(&tokens[i], "PRECEDING") || is_word(&tokens[i], "FOLLOWING")
+    })
+}
+
+/// The index of the `PRECEDING` or `FOLLOWING` word following the interval
+/// `INTERVAL 'value' [unit]` starting at `start`, if any
+fn interval_frame_bound(tokens: &[Token], start: usize) -> Option<usize> {
+    let value = next_non_whitespace(tokens, start + 1)
+        .filter(|i| matches!(tokens[*i], Token::SingleQuotedString(_)))?;
+    let next = next_non_whitespace(tokens, value + 1)?;
+    let bound = if is_frame_bound(tokens, next) {
+        next
+    } else if matches!(tokens[next], Token::Word(_)) && is_frame_bound(tokens, next + 1) {
+        // the unit of the interval
+        next_non_whitespace(tokens, next + 1)?
+    } else {
+        return None;
+    };
+    Some(bound)
+}
+
+/// The interval `tokens` as the integer encoding it as a frame offset
+fn interval_frame_offset(tokens: &[Token]) -> Result<u64, ParserError> {
+    let dialect = GenericDialect {};
+    let offset = match Parser::new(tokens.to_vec(), &dialect).parse_expr()? {
+        SQLExpr::Value(Value::Interval {
+            value,
+            leading_field,
+            leading_precision,
+            last_field,
+            fractional_seconds_precision,
+        }) => sql_interval_to_scalar(
+            &value,
+            &leading_field,
+            &leading_precision,
+            &last_field,
+            &fractional_seconds_precision,
+        )
+        .and_then(|interval| WindowFrameOffset::encode(&interval)),
+        expr => return parser_err!(format!("Expected an interval, found {}", expr)),
+    };
+    offset.map_err(|e| ParserError::ParserError(e.to_string()))
+}
+
+/// The index of the name of the function call `tokens` end with, if any
+fn function_call_start(tokens: &[Token]) -> Option<usize> {
+    let close = tokens
+        .iter()
+        .rposition(|token| !matches!(token, Token::Whitespace(_)))
+        .filter(|close| tokens[*close] == Token::RParen)?;
+    let mut depth = 0;
+    let open = (0..=close).rev().find(|i| {
+        match tokens[*i] {
+            Token::RParen => depth += 1,
+            Token::LParen => depth -= 1,
+            _ => {}
+        }
+        depth == 0
+    })?;
+    (0..open)
+        .rev()
+        .find(|i| !matches!(tokens[*i], Token::Whitespace(_)))
+        .filter(|name| matches!(tokens[*name], Token::Word(_)))
+}
+
+/// The index of the first token outside of any parenthesis matching
+/// `predicate`
+fn position_top_level(
+    tokens: &[Token],
+    predicate: impl Fn(&Token) -> bool,
+) -> Option<usize> {
+    let mut depth = 0;
+    tokens.iter().position(|token| {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => depth -= 1,
+            _ => return depth == 0 && predicate(token),
+        }
+        false
+    })
+}
+
+/// Splits `tokens` on the tokens outside of any parenthesis matching
+/// `is_separator`
+fn split_top_level(
+    mut tokens: &[Token],
+    is_separator: impl Fn(&Token) -> bool,
+) -> Vec<&[Token]> {
+    let mut parts = vec![];
+    while let Some(i) = position_top_level(tokens, &is_separator) {
+        parts.push(&tokens[..i]);
+        tokens = &tokens[i + 1..];
+    }
+    parts.push(tokens);
+    parts
+}
+
+/// Whether `token` is the unquoted word `value`, ignoring case
+fn is_word(token: &Token, value: &str) -> bool {
+    match token {
+        Token::Word(w) => w.quote_style.is_none() && w.value.eq_ignore_ascii_case(value),
+        _ => false,
+    }
+}
+
+fn next_non_whitespace(tokens: &[Token], from: usize) -> Option<usize> {
+    (from..tokens.len()).find(|i| !matches!(tokens[*i], Token::Whitespace(_)))
+}
+
+/// The index of the parenthesis closing the one at `open`
+fn matching_paren(tokens: &[Token], open: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::LParen => depth += 1,
+            Token::RParen => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The `tokens` before their last token if it is the word `value`
+fn strip_last_word<'a>(tokens: &'a [Token], value: &str) -> Option<&'a [Token]> {
+    match tokens.split_last() {
+        Some((last, rest)) if is_word(last, value) => Some(trim_whitespace(rest)),
+        _ => None,
+    }
+}
+
+fn trim_whitespace(tokens: &[Token]) -> &[Token] {
+    let is_whitespace = |token: &Token| matches!(token, Token::Whitespace(_));
+    let start = tokens
+        .iter()
+        .position(|t| !is_whitespace(t))
+        .unwrap_or(tokens.len());
+    let end = tokens
+        .iter()
+        .rposition(|t| !is_whitespace(t))
+        .map_or(start, |end| end + 1);
+    &tokens[start..end]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scalar::ScalarValue;
     use sqlparser::ast::{DataType, Ident};
 
     fn expect_parse_ok(sql: &str, expected: Statement) -> Result<(), ParserError> {
@@ -331,6 +1005,15 @@ mod tests {
         Ok(())
     }
 
+    /// Parses sql with the sqlparser parser only, without the rewrites of
+    /// [`DFParser`]
+    fn parse_sql_without_rewrites(sql: &str) -> Result<Statement, ParserError> {
+        let dialect = GenericDialect {};
+        let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+        let statement = Parser::new(tokens, &dialect).parse_statement()?;
+        Ok(Statement::Statement(Box::new(statement)))
+    }
+
     /// Parses sql and asserts that the expected error message was found
     fn expect_parse_error(sql: &str, expected_error: &str) {
         match DFParser::parse_sql(sql) {
@@ -374,6 +1057,7 @@ mod tests {
             columns: vec![make_column_def("c1", DataType::Int(display))],
             file_type: FileType::CSV,
             has_header: false,
+            file_compression_type: None,
             location: "foo.csv".into(),
         });
         expect_parse_ok(sql, expected)?;
@@ -389,6 +1073,7 @@ mod tests {
                 columns: vec![make_column_def("c1", DataType::Int(display))],
                 file_type: FileType::CSV,
                 has_header: true,
+                file_compression_type: None,
                 location: "foo.csv".into(),
             });
             expect_parse_ok(sql, expected)?;
@@ -401,6 +1086,7 @@ mod tests {
             columns: vec![],
             file_type: FileType::Parquet,
             has_header: false,
+            file_compression_type: None,
             location: "foo.parquet".into(),
         });
         expect_parse_ok(sql, expected)?;
@@ -412,6 +1098,7 @@ mod tests {
             columns: vec![],
             file_type: FileType::Parquet,
             has_header: false,
+            file_compression_type: None,
             location: "foo.parquet".into(),
         });
         expect_parse_ok(sql, expected)?;
@@ -423,15 +1110,229 @@ mod tests {
             columns: vec![],
             file_type: FileType::Avro,
             has_header: false,
+            file_compression_type: None,
             location: "foo.avro".into(),
         });
         expect_parse_ok(sql, expected)?;
 
+        // positive case: it is ok for arrow files not to have columns specified
+        let sql = "CREATE EXTERNAL TABLE t STORED AS ARROW LOCATION 'foo.arrow'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![],
+            file_type: FileType::Arrow,
+            has_header: false,
+            file_compression_type: None,
+            location: "foo.arrow".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
+        // positive case: compressed files
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV WITH HEADER ROW \
+                   COMPRESSION TYPE gzip LOCATION 'foo.csv.gz'";
+        let expected = Statement::CreateExternalTable(CreateExternalTable {
+            name: "t".into(),
+            columns: vec![make_column_def("c1", DataType::Int(display))],
+            file_type: FileType::CSV,
+            has_header: true,
+            file_compression_type: Some(FileCompressionType::Gzip),
+            location: "foo.csv.gz".into(),
+        });
+        expect_parse_ok(sql, expected)?;
+
         // Error cases: Invalid type
         let sql =
             "CREATE EXTERNAL TABLE t(c1 int) STORED AS UNKNOWN_TYPE LOCATION 'foo.csv'";
-        expect_parse_error(sql, "expect one of PARQUET, AVRO, NDJSON, or CSV");
+        expect_parse_error(sql, "expect one of PARQUET, AVRO, NDJSON, ARROW, or CSV");
+
+        // Error cases: Invalid compression type
+        let sql = "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV COMPRESSION TYPE LZ4 \
+                   LOCATION 'foo.csv'";
+        expect_parse_error(sql, "expect one of GZIP, BZIP2, XZ, ZSTD or UNCOMPRESSED");
+        let sql =
+            "CREATE EXTERNAL TABLE t(c1 int) STORED AS CSV COMPRESSION GZIP LOCATION 'foo.csv'";
+        expect_parse_error(sql, "Expected TYPE");
+
+        Ok(())
+    }
+
+    #[test]
+    fn copy_to() -> Result<(), ParserError> {
+        let sql = "COPY t TO 'out'";
+        let expected = Statement::CopyTo(CopyTo {
+            source: CopyToSource::Relation(ObjectName(vec![Ident::new("t")])),
+            target: "out".into(),
+            options: vec![],
+        });
+        expect_parse_ok(sql, expected)?;
+
+        let sql = "COPY (SELECT 1) TO 'out' \
+                   (FORMAT parquet, Compression 'zstd', ROW_GROUP_SIZE 1000)";
+        let statements = DFParser::parse_sql(sql)?;
+        match &statements[0] {
+            Statement::CopyTo(CopyTo {
+                source: CopyToSource::Query(query),
+                target,
+                options,
+            }) => {
+                assert_eq!("SELECT 1", query.to_string());
+                assert_eq!("out", target);
+                assert_eq!(
+                    &vec![
+                        ("format".to_string(), "parquet".to_string()),
+                        ("compression".to_string(), "zstd".to_string()),
+                        ("row_group_size".to_string(), "1000".to_string()),
+                    ],
+                    options
+                );
+            }
+            other => panic!("Expected COPY statement, found {:?}", other),
+        }
+
+        // Error cases
+        expect_parse_error("COPY t 'out'", "Expected TO");
+        expect_parse_error("COPY t TO 'out' (FORMAT)", "a value for the option format");
+        expect_parse_error(
+            "COPY t TO 'out' (FORMAT csv HEADER true)",
+            "',' or ')' after an option",
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn refresh_table() -> Result<(), ParserError> {
+        let sql = "REFRESH TABLE s.t";
+        let expected =
+            Statement::RefreshTable(ObjectName(vec![Ident::new("s"), Ident::new("t")]));
+        expect_parse_ok(sql, expected)?;
+
+        expect_parse_error("REFRESH t", "Expected TABLE");
+
+        Ok(())
+    }
+
+    #[test]
+    fn grouping_sets() -> Result<(), ParserError> {
+        let sql =
+            "SELECT a, b FROM t GROUP BY grouping  sets ((a, b), a, ()), ROLLUP (a)";
+        let statements = DFParser::parse_sql(sql)?;
+        let expected = DFParser::parse_sql(
+            "SELECT a, b FROM t GROUP BY grouping_sets(grouping_set(a, b), a, grouping_set()), ROLLUP (a)",
+        )?;
+        assert_eq!(statements, expected);
+
+        // not a grouping set
+        let sql = "SELECT grouping(a) FROM t GROUP BY a";
+        let statements = DFParser::parse_sql(sql)?;
+        assert_eq!(statements.len(), 1);
+        assert!(!format!("{:?}", statements[0]).contains("grouping_set"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn within_group() -> Result<(), ParserError> {
+        let sql = "SELECT percentile_cont(0.25) WITHIN GROUP (ORDER BY a + 1), \
+                   APPROX_PERCENTILE_CONT(0.9) within group (order by (b) asc nulls last) \
+                   FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        let expected = DFParser::parse_sql(
+            "SELECT percentile_cont(a + 1, 0.25), APPROX_PERCENTILE_CONT((b), 0.9) FROM t",
+        )?;
+        assert_eq!(statements, expected);
+
+        // the percentile of the descending order is the complement
+        let sql = "SELECT percentile_cont(0.25) WITHIN GROUP (ORDER BY a DESC), \
+                   percentile_cont(1 / 4) WITHIN GROUP (ORDER BY a DESC) FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        let expected = DFParser::parse_sql(
+            "SELECT percentile_cont(a, 0.75), percentile_cont(a, 1 - (1 / 4)) FROM t",
+        )?;
+        assert_eq!(statements, expected);
+
+        // function calls without the clause are left alone
+        let sql = "SELECT approx_percentile_cont(a, 0.5) FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        assert_eq!(statements.len(), 1);
+
+        expect_parse_error(
+            "SELECT percentile_cont(0.5) WITHIN (ORDER BY a) FROM t",
+            "Expected WITHIN GROUP (ORDER BY ...)",
+        );
+        expect_parse_error(
+            "SELECT percentile_cont(0.5) WITHIN GROUP (ORDER BY a, b) FROM t",
+            "WITHIN GROUP (ORDER BY ...) takes a single ordering expression",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_order_by_and_filter() -> Result<(), ParserError> {
+        let sql = "SELECT array_agg(a ORDER BY b DESC, (c + 1) nulls first) \
+                   FILTER (WHERE d > (SELECT max(e) FROM u)), \
+                   COUNT(*) filter (where f) FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        let expected = parse_sql_without_rewrites(
+            "SELECT aggregate_filter(\
+                 aggregate_order_by(array_agg(a), b, false, true, (c + 1), true, true), \
+                 d > (SELECT max(e) FROM u)\
+             ), aggregate_filter(COUNT(*), f) FROM t",
+        )?;
+        assert_eq!(statements, vec![expected]);
+
+        // neither a function call nor the clause
+        let sql = "SELECT filter FROM t WHERE (a) ORDER BY max(b) OVER (ORDER BY c)";
+        let statements = DFParser::parse_sql(sql)?;
+        assert_eq!(statements.len(), 1);
+        assert!(!format!("{:?}", statements[0]).contains("aggregate_"));
 
+        expect_parse_error(
+            "SELECT array_agg(a ORDER a) FROM t",
+            "Expected BY after ORDER in aggregate function",
+        );
+
+        // the functions of the rewrites can't be called directly
+        expect_parse_error(
+            "SELECT AGGREGATE_FILTER(COUNT(*), a) FROM t",
+            "Function aggregate_filter is reserved",
+        );
+        expect_parse_error(
+            "SELECT \"aggregate_order_by\" (array_agg(a), b, true, false) FROM t",
+            "Function aggregate_order_by is reserved",
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn interval_frame_offsets() -> Result<(), ParserError> {
+        let sql =
+            "SELECT MAX(a) OVER (ORDER BY b RANGE BETWEEN INTERVAL '1' DAY PRECEDING \
+                   AND INTERVAL '2 month' FOLLOWING) FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        let expected = DFParser::parse_sql(&format!(
+            "SELECT MAX(a) OVER (ORDER BY b RANGE BETWEEN {} PRECEDING AND {} FOLLOWING) FROM t",
+            WindowFrameOffset::encode(&ScalarValue::IntervalDayTime(Some(1 << 32))).unwrap(),
+            WindowFrameOffset::encode(&ScalarValue::IntervalYearMonth(Some(2))).unwrap(),
+        ))?;
+        assert_eq!(statements, expected);
+
+        // intervals elsewhere are left alone
+        let sql = "SELECT a + INTERVAL '1' DAY FROM t";
+        let statements = DFParser::parse_sql(sql)?;
+        assert!(format!("{}", statements[0]).contains("INTERVAL '1' DAY"));
+
+        expect_parse_error(
+            &format!(
+                "SELECT MAX(a) OVER (ORDER BY b RANGE {} PRECEDING) FROM t",
+                MAX_OFFSET_VALUE + 1
+            ),
+            "is larger than",
+        );
+        expect_parse_error(
+            "SELECT MAX(a) OVER (ORDER BY b RANGE INTERVAL '-1' DAY PRECEDING) FROM t",
+            "Window frame offsets must be non-negative intervals",
+        );
         Ok(())
     }
 }
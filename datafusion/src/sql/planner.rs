@@ -24,14 +24,22 @@ use std::sync::Arc;
 use std::{convert::TryInto, vec};
 
 use crate::catalog::TableReference;
+use crate::datasource::cte_worktable::CteWorkTable;
+use crate::datasource::file_format::{
+    arrow::ArrowFormat, compression::FileCompressionType, csv::CsvFormat,
+    json::JsonFormat, parquet::ParquetFormat, FileFormat,
+};
+use crate::datasource::view::ViewTable;
 use crate::datasource::TableProvider;
 use crate::logical_plan::window_frames::{WindowFrame, WindowFrameUnits};
 use crate::logical_plan::Expr::Alias;
 use crate::logical_plan::{
-    and, builder::expand_wildcard, col, lit, normalize_col, union_with_alias, Column,
-    CreateExternalTable as PlanCreateExternalTable, CreateMemoryTable, DFSchema,
-    DFSchemaRef, DropTable, Expr, LogicalPlan, LogicalPlanBuilder, Operator, PlanType,
-    ToDFSchema, ToStringifiedPlan,
+    and, builder::expand_wildcard, col, cube, expand_grouping_sets, grouping_sets, lit,
+    normalize_col, normalize_col_with_schemas, rollup, union_with_alias, Column,
+    CopyTo as PlanCopyTo, CreateExternalTable as PlanCreateExternalTable,
+    CreateMemoryTable, CreateView, DFSchema, DFSchemaRef, DropTable, DropView, Expr,
+    Insert, LogicalPlan, LogicalPlanBuilder, Operator, PlanType, PlanVisitor,
+    RefreshTable, Subquery, TableScan, ToDFSchema, ToStringifiedPlan,
 };
 use crate::optimizer::utils::exprlist_to_columns;
 use crate::prelude::JoinType;
@@ -42,24 +50,33 @@ use crate::{
     physical_plan::udaf::AggregateUDF,
 };
 use crate::{
+    physical_plan::insert::insert_count_schema,
     physical_plan::udf::ScalarUDF,
     physical_plan::{aggregates, functions, window_functions},
-    sql::parser::{CreateExternalTable, FileType, Statement as DFStatement},
+    sql::parser::{
+        CopyTo, CopyToSource, CreateExternalTable, FileType, Statement as DFStatement,
+    },
 };
 use arrow::datatypes::*;
 use hashbrown::HashMap;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
 use sqlparser::ast::{
-    BinaryOperator, DataType as SQLDataType, DateTimeField, Expr as SQLExpr, FunctionArg,
-    HiveDistributionStyle, Ident, Join, JoinConstraint, JoinOperator, ObjectName, Query,
-    Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter, TableFactor,
-    TableWithJoins, TrimWhereField, UnaryOperator, Value, Values as SQLValues,
+    BinaryOperator, Cte, DataType as SQLDataType, DateTimeField, Expr as SQLExpr,
+    FunctionArg, HiveDistributionStyle, Ident, Join, JoinConstraint, JoinOperator,
+    ObjectName, Query, Select, SelectItem, SetExpr, SetOperator, ShowStatementFilter,
+    TableFactor, TableWithJoins, TrimWhereField, UnaryOperator, Value,
+    Values as SQLValues,
 };
 use sqlparser::ast::{ColumnDef as SQLColumnDef, ColumnOption};
 use sqlparser::ast::{ObjectType, OrderByExpr, Statement};
 use sqlparser::parser::ParserError::ParserError;
 
 use super::{
-    parser::DFParser,
+    parser::{
+        DFParser, AGGREGATE_FILTER_FUNCTION, AGGREGATE_ORDER_BY_FUNCTION,
+        GROUPING_SETS_FUNCTION, GROUPING_SET_FUNCTION,
+    },
     utils::{
         can_columns_satisfy_exprs, expr_as_column_expr, extract_aliases,
         find_aggregate_exprs, find_column_exprs, find_window_exprs, rebase_expr,
@@ -111,6 +128,41 @@ fn plan_indexed(expr: Expr, mut keys: Vec<Value>) -> Expr {
     }
 }
 
+/// Returns the lowercase name and arguments of `sql` if it is a call of one of
+/// the functions grouping sets are expressed with
+fn grouping_set_function(sql: &SQLExpr) -> Option<(String, Vec<&SQLExpr>)> {
+    let function = match sql {
+        SQLExpr::Function(function)
+            if function.over.is_none() && function.name.0.len() == 1 =>
+        {
+            function
+        }
+        _ => return None,
+    };
+    let ident = &function.name.0[0];
+    let name = ident.value.to_ascii_lowercase();
+    let is_grouping_set = ident.quote_style.is_none()
+        && [
+            "rollup",
+            "cube",
+            GROUPING_SETS_FUNCTION,
+            GROUPING_SET_FUNCTION,
+        ]
+        .contains(&name.as_str());
+    if !is_grouping_set {
+        return None;
+    }
+    function
+        .args
+        .iter()
+        .map(|arg| match arg {
+            FunctionArg::Unnamed(expr) => Some(expr),
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>()
+        .map(|args| (name, args))
+}
+
 impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     /// Create a new query planner
     pub fn new(schema_provider: &'a S) -> Self {
@@ -121,6 +173,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
     pub fn statement_to_plan(&self, statement: &DFStatement) -> Result<LogicalPlan> {
         match statement {
             DFStatement::CreateExternalTable(s) => self.external_table_to_plan(s),
+            DFStatement::CopyTo(s) => self.copy_to_plan(s),
+            DFStatement::RefreshTable(name) => {
+                Ok(LogicalPlan::RefreshTable(RefreshTable {
+                    name: name.to_string(),
+                    schema: DFSchemaRef::new(DFSchema::empty()),
+                }))
+            }
             DFStatement::Statement(s) => self.sql_statement_to_plan(s),
         }
     }
@@ -170,6 +229,41 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     .to_string(),
             )),
 
+            Statement::CreateView {
+                or_replace,
+                materialized: false,
+                name,
+                columns,
+                query,
+                with_options,
+            } if with_options.is_empty() => {
+                let name = name.to_string();
+                let plan = self.query_to_plan(query)?;
+                let plan = self.apply_column_aliases(plan, &name, columns)?;
+
+                Ok(LogicalPlan::CreateView(CreateView {
+                    name,
+                    input: Arc::new(plan),
+                    or_replace: *or_replace,
+                    definition: Some(sql.to_string()),
+                }))
+            }
+            Statement::CreateView { .. } => Err(DataFusionError::NotImplemented(
+                "Only `CREATE [OR REPLACE] VIEW view_name AS SELECT ...` statement is supported"
+                    .to_string(),
+            )),
+
+            Statement::Drop {
+                object_type: ObjectType::View,
+                if_exists,
+                names,
+                ..
+            } => Ok(LogicalPlan::DropView(DropView {
+                name: names.get(0).unwrap().to_string(),
+                if_exist: *if_exists,
+                schema: DFSchemaRef::new(DFSchema::empty()),
+            })),
+
             Statement::Drop {
                 object_type: ObjectType::Table,
                 if_exists,
@@ -192,6 +286,15 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 table_name,
                 filter,
             } => self.show_columns_to_plan(*extended, *full, table_name, filter.as_ref()),
+
+            Statement::Insert {
+                table_name,
+                columns,
+                overwrite,
+                source,
+                partitioned: None,
+                ..
+            } => self.insert_to_plan(table_name, columns, *overwrite, source),
             _ => Err(DataFusionError::NotImplemented(format!(
                 "Unsupported SQL statement: {:?}",
                 sql
@@ -199,47 +302,258 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
+    /// Generate a logical plan from an `INSERT INTO` or `INSERT OVERWRITE`
+    /// statement. The columns of the table that are not listed are filled
+    /// with nulls, so they must be nullable.
+    fn insert_to_plan(
+        &self,
+        table_name: &ObjectName,
+        columns: &[Ident],
+        overwrite: bool,
+        source: &Query,
+    ) -> Result<LogicalPlan> {
+        let name = table_name.to_string();
+        let table = self
+            .schema_provider
+            .get_table_provider(table_name.try_into()?)
+            .ok_or_else(|| {
+                DataFusionError::Plan(format!("Table with name '{}' not found", name))
+            })?;
+        let table_schema = table.schema();
+
+        // the index of the column of the query providing each column of the
+        // table, if any
+        let mut source_indices = vec![None; table_schema.fields().len()];
+        if columns.is_empty() {
+            for (i, source_index) in source_indices.iter_mut().enumerate() {
+                *source_index = Some(i);
+            }
+        } else {
+            for (i, column) in columns.iter().enumerate() {
+                let index = table_schema.index_of(&column.value).map_err(|_| {
+                    DataFusionError::Plan(format!(
+                        "Column {} not found in table {}",
+                        column.value, name
+                    ))
+                })?;
+                if source_indices[index].replace(i).is_some() {
+                    return Err(DataFusionError::Plan(format!(
+                        "Column {} is listed more than once in INSERT INTO {}",
+                        column.value, name
+                    )));
+                }
+            }
+        }
+
+        let source = self.query_to_plan(source)?;
+        let expected_columns = if columns.is_empty() {
+            table_schema.fields().len()
+        } else {
+            columns.len()
+        };
+        let source_fields = source.schema().fields();
+        if source_fields.len() != expected_columns {
+            return Err(DataFusionError::Plan(format!(
+                "INSERT INTO {} expects {} columns but the query produces {}",
+                name,
+                expected_columns,
+                source_fields.len()
+            )));
+        }
+
+        let expr = table_schema
+            .fields()
+            .iter()
+            .zip(source_indices)
+            .map(|(field, source_index)| {
+                let expr = match source_index {
+                    Some(i) => Expr::Column(source_fields[i].qualified_column()),
+                    None if !field.is_nullable() => {
+                        return Err(DataFusionError::Plan(format!(
+                            "Column {} of table {} is not nullable and must be \
+                            listed in INSERT INTO",
+                            field.name(),
+                            name
+                        )))
+                    }
+                    None => Expr::Literal(ScalarValue::try_from(field.data_type())?),
+                };
+                Ok(expr
+                    .cast_to(field.data_type(), source.schema())?
+                    .alias(field.name()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let input = LogicalPlanBuilder::from(source).project(expr)?.build()?;
+
+        Ok(LogicalPlan::Insert(Insert {
+            table_name: name,
+            table,
+            input: Arc::new(input),
+            overwrite,
+            schema: insert_count_schema().to_dfschema_ref()?,
+        }))
+    }
+
     /// Generate a logic plan from an SQL query
     pub fn query_to_plan(&self, query: &Query) -> Result<LogicalPlan> {
-        self.query_to_plan_with_alias(query, None, &mut HashMap::new())
+        self.query_to_plan_with_alias(query, None, &mut HashMap::new(), None)
     }
 
     /// Generate a logic plan from an SQL query with optional alias
+    ///
+    /// `outer_query_schema` is the schema of the enclosing query when planning
+    /// a subquery, whose WHERE clause may then reference its columns.
     pub fn query_to_plan_with_alias(
         &self,
         query: &Query,
         alias: Option<String>,
         ctes: &mut HashMap<String, LogicalPlan>,
+        outer_query_schema: Option<&DFSchema>,
     ) -> Result<LogicalPlan> {
         let set_expr = &query.body;
         if let Some(with) = &query.with {
             // Process CTEs from top to bottom
-            // do not allow self-references
+            // do not allow self-references, unless the CTEs are recursive
             for cte in &with.cte_tables {
                 // create logical plan & pass backreferencing CTEs
-                let logical_plan = self.query_to_plan_with_alias(
-                    &cte.query,
-                    Some(cte.alias.name.value.clone()),
-                    &mut ctes.clone(),
+                let logical_plan = if with.recursive {
+                    self.recursive_cte_to_plan(cte, &mut ctes.clone())?
+                } else {
+                    self.query_to_plan_with_alias(
+                        &cte.query,
+                        Some(cte.alias.name.value.clone()),
+                        &mut ctes.clone(),
+                        None,
+                    )?
+                };
+                let logical_plan = self.apply_column_aliases(
+                    logical_plan,
+                    &cte.alias.name.value,
+                    &cte.alias.columns,
                 )?;
                 ctes.insert(cte.alias.name.value.clone(), logical_plan);
             }
         }
-        let plan = self.set_expr_to_plan(set_expr, alias, ctes)?;
+        let plan = self.set_expr_to_plan(set_expr, alias, ctes, outer_query_schema)?;
 
         let plan = self.order_by(plan, &query.order_by)?;
 
         self.limit(plan, &query.limit)
     }
 
+    /// Generate a logical plan from a CTE of a `WITH RECURSIVE` clause. The
+    /// CTE may reference itself in the right side of a top level `UNION ALL`,
+    /// which is then evaluated on the rows it produced last until it produces
+    /// no rows.
+    fn recursive_cte_to_plan(
+        &self,
+        cte: &Cte,
+        ctes: &mut HashMap<String, LogicalPlan>,
+    ) -> Result<LogicalPlan> {
+        let name = cte.alias.name.value.clone();
+        let (static_term, recursive_term, all) = match &cte.query.body {
+            SetExpr::SetOperation {
+                op: SetOperator::Union,
+                left,
+                right,
+                all,
+            } => (left, right, *all),
+            // the CTEs of a `WITH RECURSIVE` clause do not have to be recursive
+            _ => {
+                return self.query_to_plan_with_alias(&cte.query, Some(name), ctes, None)
+            }
+        };
+
+        let static_plan = self.set_expr_to_plan(static_term, None, ctes, None)?;
+        let static_plan =
+            self.apply_column_aliases(static_plan, &name, &cte.alias.columns)?;
+
+        // the recursive term references the rows of the previous iteration
+        // by the name of the CTE
+        let work_table = Arc::new(CteWorkTable::new(
+            &name,
+            Arc::new(static_plan.schema().as_ref().clone().into()),
+        ));
+        let mut recursive_ctes = ctes.clone();
+        recursive_ctes.insert(
+            name.clone(),
+            LogicalPlanBuilder::scan(&name, work_table, None)?.build()?,
+        );
+        let recursive_plan =
+            self.set_expr_to_plan(recursive_term, None, &mut recursive_ctes, None)?;
+        if !references_work_table(&recursive_plan, &name)? {
+            return self.query_to_plan_with_alias(&cte.query, Some(name), ctes, None);
+        }
+
+        if !all {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Recursive query {} must combine its terms with UNION ALL",
+                name
+            )));
+        }
+        if !cte.query.order_by.is_empty() || cte.query.limit.is_some() {
+            return Err(DataFusionError::NotImplemented(format!(
+                "ORDER BY and LIMIT are not supported in recursive query {}",
+                name
+            )));
+        }
+
+        LogicalPlanBuilder::from(static_plan)
+            .to_recursive_query(&name, recursive_plan)?
+            .build()
+    }
+
+    /// Rename the columns of the plan of a CTE or a view after the column
+    /// names listed with its name, if any
+    fn apply_column_aliases(
+        &self,
+        plan: LogicalPlan,
+        name: &str,
+        columns: &[Ident],
+    ) -> Result<LogicalPlan> {
+        let fields = plan.schema().fields();
+        if columns.is_empty() {
+            return Ok(plan);
+        }
+        if columns.len() != fields.len() {
+            return Err(DataFusionError::Plan(format!(
+                "{} has {} columns but {} names are given as column alias",
+                name,
+                fields.len(),
+                columns.len(),
+            )));
+        }
+        if columns
+            .iter()
+            .zip(fields)
+            .all(|(column, field)| &column.value == field.name())
+        {
+            return Ok(plan);
+        }
+
+        let expr = fields
+            .iter()
+            .zip(columns)
+            .map(|(field, column)| {
+                Expr::Column(field.qualified_column()).alias(&column.value)
+            })
+            .collect::<Vec<_>>();
+        LogicalPlanBuilder::from(plan)
+            .project_with_alias(expr, Some(name.to_owned()))?
+            .build()
+    }
+
     fn set_expr_to_plan(
         &self,
         set_expr: &SetExpr,
         alias: Option<String>,
         ctes: &mut HashMap<String, LogicalPlan>,
+        outer_query_schema: Option<&DFSchema>,
     ) -> Result<LogicalPlan> {
         match set_expr {
-            SetExpr::Select(s) => self.select_to_plan(s.as_ref(), ctes, alias),
+            SetExpr::Select(s) => {
+                self.select_to_plan(s.as_ref(), ctes, alias, outer_query_schema)
+            }
             SetExpr::Values(v) => self.sql_values_to_plan(v),
             SetExpr::SetOperation {
                 op,
@@ -247,8 +561,14 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 right,
                 all,
             } => {
-                let left_plan = self.set_expr_to_plan(left.as_ref(), None, ctes)?;
-                let right_plan = self.set_expr_to_plan(right.as_ref(), None, ctes)?;
+                let left_plan =
+                    self.set_expr_to_plan(left.as_ref(), None, ctes, outer_query_schema)?;
+                let right_plan = self.set_expr_to_plan(
+                    right.as_ref(),
+                    None,
+                    ctes,
+                    outer_query_schema,
+                )?;
                 match (op, all) {
                     (SetOperator::Union, true) => {
                         union_with_alias(left_plan, right_plan, alias)
@@ -288,6 +608,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             columns,
             file_type,
             has_header,
+            file_compression_type,
             location,
         } = statement;
 
@@ -304,6 +625,24 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             }
             FileType::NdJson => {}
             FileType::Avro => {}
+            FileType::Arrow => {}
+        };
+        let file_compression_type = match (file_type, file_compression_type) {
+            (FileType::CSV | FileType::NdJson, Some(file_compression_type)) => {
+                *file_compression_type
+            }
+            (FileType::CSV | FileType::NdJson, None) => {
+                FileCompressionType::from_extension(location)
+            }
+            (_, None | Some(FileCompressionType::Uncompressed)) => {
+                FileCompressionType::Uncompressed
+            }
+            (_, Some(_)) => {
+                return Err(DataFusionError::Plan(format!(
+                    "File compression can not be specified for {:?} files.",
+                    file_type
+                )))
+            }
         };
 
         let schema = self.build_schema(columns)?;
@@ -314,6 +653,38 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             location: location.clone(),
             file_type: *file_type,
             has_header: *has_header,
+            file_compression_type,
+        }))
+    }
+
+    /// Generate a logical plan from a `COPY ... TO` statement
+    pub fn copy_to_plan(&self, statement: &CopyTo) -> Result<LogicalPlan> {
+        let CopyTo {
+            source,
+            target,
+            options,
+        } = statement;
+
+        let input = match source {
+            CopyToSource::Relation(name) => {
+                let relation = TableFactor::Table {
+                    name: name.clone(),
+                    alias: None,
+                    args: vec![],
+                    with_hints: vec![],
+                };
+                self.create_relation(&relation, &mut HashMap::new())?
+            }
+            CopyToSource::Query(query) => self.query_to_plan(query)?,
+        };
+        let (file_type, format) = copy_to_format(target, options)?;
+
+        Ok(LogicalPlan::CopyTo(PlanCopyTo {
+            input: Arc::new(input),
+            path: target.clone(),
+            file_type,
+            format,
+            schema: insert_count_schema().to_dfschema_ref()?,
         }))
     }
 
@@ -605,16 +976,18 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                         self.schema_provider.get_table_provider(name.try_into()?),
                     ) {
                         (Some(cte_plan), _) => Ok(cte_plan.clone()),
-                        (_, Some(provider)) => LogicalPlanBuilder::scan(
+                        (_, Some(provider)) => {
                             // take alias into account to support `JOIN table1 as table2`
-                            alias
+                            let name = alias
                                 .as_ref()
                                 .map(|a| a.name.value.as_str())
-                                .unwrap_or(&table_name),
-                            provider,
-                            None,
-                        )?
-                        .build(),
+                                .unwrap_or(&table_name);
+                            match provider.as_any().downcast_ref::<ViewTable>() {
+                                Some(view) => view_to_plan(view, name),
+                                None => LogicalPlanBuilder::scan(name, provider, None)?
+                                    .build(),
+                            }
+                        }
                         (None, None) => Err(DataFusionError::Plan(format!(
                             "Table or CTE with name '{}' not found",
                             name
@@ -636,6 +1009,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     subquery,
                     alias.as_ref().map(|a| a.name.value.to_string()),
                     ctes,
+                    None,
                 )?;
                 (
                     project_with_alias(
@@ -695,6 +1069,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         select: &Select,
         ctes: &mut HashMap<String, LogicalPlan>,
         alias: Option<String>,
+        outer_query_schema: Option<&DFSchema>,
     ) -> Result<LogicalPlan> {
         let plans = self.plan_from_tables(&select.from, ctes)?;
 
@@ -707,7 +1082,24 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 }
                 let join_schema = DFSchema::new(fields)?;
 
-                let filter_expr = self.sql_to_rex(predicate_expr, &join_schema)?;
+                let filter_expr = match outer_query_schema {
+                    Some(outer_query_schema) => {
+                        let using_columns = plans
+                            .iter()
+                            .map(|plan| plan.using_columns())
+                            .collect::<Result<Vec<_>>>()?
+                            .into_iter()
+                            .flatten()
+                            .collect::<Vec<_>>();
+                        self.correlated_sql_to_rex(
+                            predicate_expr,
+                            join_schema,
+                            outer_query_schema,
+                            &using_columns,
+                        )?
+                    }
+                    None => self.sql_to_rex(predicate_expr, &join_schema)?,
+                };
 
                 // look for expressions of the form `<column> = <column>`
                 let mut possible_join_keys = vec![];
@@ -823,7 +1215,7 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             .group_by
             .iter()
             .map(|e| {
-                let group_by_expr = self.sql_expr_to_logical_expr(e, &combined_schema)?;
+                let group_by_expr = self.group_by_to_expr(e, &combined_schema)?;
                 let group_by_expr = resolve_aliases_to_exprs(&group_by_expr, &alias_map)?;
                 let group_by_expr =
                     resolve_positions_to_exprs(&group_by_expr, &select_exprs)
@@ -947,10 +1339,11 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         group_by_exprs: Vec<Expr>,
         aggr_exprs: Vec<Expr>,
     ) -> Result<(LogicalPlan, Vec<Expr>, Option<Expr>)> {
-        let aggr_projection_exprs = group_by_exprs
-            .iter()
-            .chain(aggr_exprs.iter())
-            .cloned()
+        // the expressions of grouping sets are grouped by individually
+        let (grouping_exprs, _) = expand_grouping_sets(&group_by_exprs)?;
+        let aggr_projection_exprs = grouping_exprs
+            .into_iter()
+            .chain(aggr_exprs.iter().cloned())
             .collect::<Vec<Expr>>();
 
         let plan = LogicalPlanBuilder::from(input.clone())
@@ -1000,6 +1393,54 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         Ok((plan, select_exprs_post_aggr, having_expr_post_aggr_opt))
     }
 
+    /// Generate a logical expression from a `GROUP BY` expression, which may
+    /// be a `ROLLUP (...)`, `CUBE (...)` or `GROUPING SETS (...)`. The latter is
+    /// rewritten into function calls by [`DFParser`].
+    fn group_by_to_expr(&self, sql: &SQLExpr, schema: &DFSchema) -> Result<Expr> {
+        let to_exprs = |args: Vec<&SQLExpr>| {
+            args.into_iter()
+                .map(|arg| self.sql_expr_to_logical_expr(arg, schema))
+                .collect::<Result<Vec<_>>>()
+        };
+        let (name, args) = match grouping_set_function(sql) {
+            Some(function) => function,
+            None => return self.sql_expr_to_logical_expr(sql, schema),
+        };
+        match name.as_str() {
+            "rollup" => Ok(rollup(to_exprs(args)?)),
+            "cube" => Ok(cube(to_exprs(args)?)),
+            GROUPING_SETS_FUNCTION => {
+                let mut sets = vec![];
+                for arg in args {
+                    match grouping_set_function(arg) {
+                        Some((name, exprs)) if name == GROUPING_SET_FUNCTION => {
+                            sets.push(to_exprs(exprs)?)
+                        }
+                        Some(_) => match self.group_by_to_expr(arg, schema)? {
+                            Expr::GroupingSet(grouping_set) => {
+                                sets.extend(grouping_set.sets()?)
+                            }
+                            expr => {
+                                return Err(DataFusionError::Internal(format!(
+                                    "Expected a grouping set, got {:?}",
+                                    expr
+                                )))
+                            }
+                        },
+                        None => {
+                            sets.push(vec![self.sql_expr_to_logical_expr(arg, schema)?])
+                        }
+                    }
+                }
+                Ok(grouping_sets(sets))
+            }
+            _ => Err(DataFusionError::Plan(format!(
+                "Unsupported grouping set {}",
+                sql
+            ))),
+        }
+    }
+
     /// Wrap a plan in a limit
     fn limit(&self, input: LogicalPlan, limit: &Option<SQLExpr>) -> Result<LogicalPlan> {
         match *limit {
@@ -1136,6 +1577,61 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         Ok(expr)
     }
 
+    /// Generate a relational expression from the WHERE clause of a subquery,
+    /// which may reference columns of the enclosing query. Columns are
+    /// resolved against the subquery's own relations first.
+    fn correlated_sql_to_rex(
+        &self,
+        sql: &SQLExpr,
+        schema: DFSchema,
+        outer_query_schema: &DFSchema,
+        using_columns: &[HashSet<Column>],
+    ) -> Result<Expr> {
+        let mut combined_schema = schema.clone();
+        combined_schema.merge(outer_query_schema);
+        let expr = self.sql_expr_to_logical_expr(sql, &combined_schema)?;
+        let expr = self.rewrite_partial_qualifier(expr, &combined_schema);
+        let schemas = [Arc::new(schema), Arc::new(outer_query_schema.clone())];
+        let expr =
+            normalize_col_with_schemas(expr, &[&schemas[0], &schemas[1]], using_columns)?;
+        self.validate_schema_satisfies_exprs(&combined_schema, &[expr.clone()])?;
+        Ok(expr)
+    }
+
+    /// Generate the plan of a subquery nested in an expression of a query
+    /// whose input has `outer_query_schema`
+    fn subquery_to_plan(
+        &self,
+        subquery: &Query,
+        outer_query_schema: &DFSchema,
+    ) -> Result<Subquery> {
+        let plan = self.query_to_plan_with_alias(
+            subquery,
+            None,
+            &mut HashMap::new(),
+            Some(outer_query_schema),
+        )?;
+        Ok(Subquery::new(plan))
+    }
+
+    /// Generate the plan of a subquery that must produce a single column,
+    /// such as a scalar subquery or the right side of `IN (SELECT ...)`
+    fn single_column_subquery_to_plan(
+        &self,
+        subquery: &Query,
+        outer_query_schema: &DFSchema,
+    ) -> Result<Subquery> {
+        let subquery = self.subquery_to_plan(subquery, outer_query_schema)?;
+        let columns = subquery.subquery.schema().fields().len();
+        if columns != 1 {
+            return Err(DataFusionError::Plan(format!(
+                "Subquery must return exactly one column, but it returns {}",
+                columns
+            )));
+        }
+        Ok(subquery)
+    }
+
     /// Rewrite aliases which are not-complete (e.g. ones that only include only table qualifier in a schema.table qualified relation)
     fn rewrite_partial_qualifier(&self, expr: Expr, schema: &DFSchema) -> Expr {
         match expr {
@@ -1224,9 +1720,13 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         schema: &DFSchema,
     ) -> Result<Expr> {
         match op {
-            UnaryOperator::Not => Ok(Expr::Not(Box::new(
-                self.sql_expr_to_logical_expr(expr, schema)?,
-            ))),
+            UnaryOperator::Not => match self.sql_expr_to_logical_expr(expr, schema)? {
+                Expr::Exists { subquery, negated } => Ok(Expr::Exists {
+                    subquery,
+                    negated: !negated,
+                }),
+                expr => Ok(Expr::Not(Box::new(expr))),
+            },
             UnaryOperator::Plus => Ok(self.sql_expr_to_logical_expr(expr, schema)?),
             UnaryOperator::Minus => {
                 match expr {
@@ -1309,13 +1809,14 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 leading_precision,
                 last_field,
                 fractional_seconds_precision,
-            }) => self.sql_interval_to_literal(
+            }) => sql_interval_to_scalar(
                 value,
                 leading_field,
                 leading_precision,
                 last_field,
                 fractional_seconds_precision,
-            ),
+            )
+            .map(Expr::Literal),
 
             SQLExpr::Identifier(ref id) => {
                 if id.value.starts_with('@') {
@@ -1482,6 +1983,25 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 })
             }
 
+            SQLExpr::InSubquery {
+                ref expr,
+                ref subquery,
+                ref negated,
+            } => Ok(Expr::InSubquery {
+                expr: Box::new(self.sql_expr_to_logical_expr(expr, schema)?),
+                subquery: self.single_column_subquery_to_plan(subquery, schema)?,
+                negated: *negated,
+            }),
+
+            SQLExpr::Exists(ref subquery) => Ok(Expr::Exists {
+                subquery: self.subquery_to_plan(subquery, schema)?,
+                negated: false,
+            }),
+
+            SQLExpr::Subquery(ref subquery) => Ok(Expr::ScalarSubquery(
+                self.single_column_subquery_to_plan(subquery, schema)?,
+            )),
+
             SQLExpr::BinaryOp {
                 ref left,
                 ref op,
@@ -1512,6 +2032,38 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 Ok(Expr::ScalarFunction { fun, args })
             }
 
+            SQLExpr::Substring {
+                expr,
+                substring_from,
+                substring_for,
+            } => {
+                let arg = self.sql_expr_to_logical_expr(expr, schema)?;
+                let args = match (substring_from, substring_for) {
+                    (Some(from_expr), Some(for_expr)) => vec![
+                        arg,
+                        self.sql_expr_to_logical_expr(from_expr, schema)?,
+                        self.sql_expr_to_logical_expr(for_expr, schema)?,
+                    ],
+                    (Some(from_expr), None) => {
+                        vec![arg, self.sql_expr_to_logical_expr(from_expr, schema)?]
+                    }
+                    (None, Some(for_expr)) => vec![
+                        arg,
+                        lit(1i64),
+                        self.sql_expr_to_logical_expr(for_expr, schema)?,
+                    ],
+                    (None, None) => {
+                        return Err(DataFusionError::Plan(
+                            "SUBSTRING requires a FROM or a FOR clause".to_string(),
+                        ))
+                    }
+                };
+                Ok(Expr::ScalarFunction {
+                    fun: functions::BuiltinScalarFunction::Substr,
+                    args,
+                })
+            }
+
             SQLExpr::Function(function) => {
                 let name = if function.name.0.len() > 1 {
                     // DF doesn't handle compound identifiers
@@ -1527,6 +2079,12 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                     }
                 };
 
+                if name == AGGREGATE_FILTER_FUNCTION
+                    || name == AGGREGATE_ORDER_BY_FUNCTION
+                {
+                    return self.aggregate_clause_to_expr(&name, function, schema);
+                }
+
                 // first, scalar built-in
                 if let Ok(fun) = functions::BuiltinScalarFunction::from_str(&name) {
                     let args = self.function_args_to_expr(function, schema)?;
@@ -1603,6 +2161,8 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                         fun,
                         distinct: function.distinct,
                         args,
+                        filter: None,
+                        order_by: vec![],
                     });
                 };
 
@@ -1647,6 +2207,71 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             .collect::<Result<Vec<Expr>>>()
     }
 
+    /// Generate a logical expression from the call of an aggregate function
+    /// with a `FILTER (WHERE ...)` or an `ORDER BY` clause, which [`DFParser`]
+    /// rewrites into the function calls `AGGREGATE_FILTER(call, predicate)`
+    /// and `AGGREGATE_ORDER_BY(call, expr, asc, nulls_first, ...)`.
+    fn aggregate_clause_to_expr(
+        &self,
+        name: &str,
+        function: &sqlparser::ast::Function,
+        schema: &DFSchema,
+    ) -> Result<Expr> {
+        if function.over.is_some() {
+            return Err(DataFusionError::NotImplemented(
+                "FILTER and ORDER BY clauses are not supported for window functions"
+                    .to_string(),
+            ));
+        }
+        let args = function
+            .args
+            .iter()
+            .map(|arg| match arg {
+                FunctionArg::Unnamed(arg) => Ok(arg),
+                _ => Err(DataFusionError::Internal(format!(
+                    "Unexpected argument {} of {}",
+                    arg, name
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (aggregate, clause) = match args.split_first() {
+            Some((call @ SQLExpr::Function(_), clause)) => {
+                (self.sql_expr_to_logical_expr(call, schema)?, clause)
+            }
+            _ => {
+                return Err(DataFusionError::Internal(format!(
+                    "Expected an aggregate function call as first argument of {}",
+                    name
+                )))
+            }
+        };
+        if name == AGGREGATE_FILTER_FUNCTION {
+            match clause {
+                [predicate] => {
+                    aggregate.filter(self.sql_expr_to_logical_expr(predicate, schema)?)
+                }
+                _ => Err(DataFusionError::Internal(format!(
+                    "Expected a single predicate in {}",
+                    name
+                ))),
+            }
+        } else {
+            let order_by = clause
+                .chunks(3)
+                .map(|key| match key {
+                    [expr, SQLExpr::Value(Value::Boolean(asc)), SQLExpr::Value(Value::Boolean(nulls_first))] => {
+                        Ok(self.sql_expr_to_logical_expr(expr, schema)?.sort(*asc, *nulls_first))
+                    }
+                    _ => Err(DataFusionError::Internal(format!(
+                        "Expected an expression and two flags per key of {}, got {:?}",
+                        name, key
+                    ))),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            aggregate.order_by(order_by)
+        }
+    }
+
     fn aggregate_fn_to_expr(
         &self,
         fun: &aggregates::AggregateFunction,
@@ -1670,45 +2295,133 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
         }
     }
 
-    fn sql_interval_to_literal(
+    fn show_variable_to_plan(&self, variable: &[Ident]) -> Result<LogicalPlan> {
+        // Special case SHOW TABLES
+        let variable = ObjectName(variable.to_vec()).to_string();
+        if variable.as_str().eq_ignore_ascii_case("tables") {
+            if self.has_table("information_schema", "tables") {
+                let rewrite =
+                    DFParser::parse_sql("SELECT * FROM information_schema.tables;")?;
+                self.statement_to_plan(&rewrite[0])
+            } else {
+                Err(DataFusionError::Plan(
+                    "SHOW TABLES is not supported unless information_schema is enabled"
+                        .to_string(),
+                ))
+            }
+        } else {
+            Err(DataFusionError::NotImplemented(format!(
+                "SHOW {} not implemented. Supported syntax: SHOW <TABLES>",
+                variable
+            )))
+        }
+    }
+
+    fn show_columns_to_plan(
         &self,
-        value: &str,
-        leading_field: &Option<DateTimeField>,
-        leading_precision: &Option<u64>,
-        last_field: &Option<DateTimeField>,
-        fractional_seconds_precision: &Option<u64>,
-    ) -> Result<Expr> {
-        if leading_precision.is_some() {
-            return Err(DataFusionError::NotImplemented(format!(
-                "Unsupported Interval Expression with leading_precision {:?}",
-                leading_precision
-            )));
+        extended: bool,
+        full: bool,
+        table_name: &ObjectName,
+        filter: Option<&ShowStatementFilter>,
+    ) -> Result<LogicalPlan> {
+        if filter.is_some() {
+            return Err(DataFusionError::Plan(
+                "SHOW COLUMNS with WHERE or LIKE is not supported".to_string(),
+            ));
         }
 
-        if last_field.is_some() {
-            return Err(DataFusionError::NotImplemented(format!(
-                "Unsupported Interval Expression with last_field {:?}",
-                last_field
-            )));
+        if !self.has_table("information_schema", "columns") {
+            return Err(DataFusionError::Plan(
+                "SHOW COLUMNS is not supported unless information_schema is enabled"
+                    .to_string(),
+            ));
         }
 
-        if fractional_seconds_precision.is_some() {
-            return Err(DataFusionError::NotImplemented(format!(
-                "Unsupported Interval Expression with fractional_seconds_precision {:?}",
-                fractional_seconds_precision
+        if self
+            .schema_provider
+            .get_table_provider(table_name.try_into()?)
+            .is_none()
+        {
+            return Err(DataFusionError::Plan(format!(
+                "Unknown relation for SHOW COLUMNS: {}",
+                table_name
             )));
         }
 
-        const SECONDS_PER_HOUR: f32 = 3_600_f32;
-        const MILLIS_PER_SECOND: f32 = 1_000_f32;
+        // Figure out the where clause
+        let columns = vec!["table_name", "table_schema", "table_catalog"].into_iter();
+        let where_clause = table_name
+            .0
+            .iter()
+            .rev()
+            .zip(columns)
+            .map(|(ident, column_name)| format!(r#"{} = '{}'"#, column_name, ident))
+            .collect::<Vec<_>>()
+            .join(" AND ");
+
+        // treat both FULL and EXTENDED as the same
+        let select_list = if full || extended {
+            "*"
+        } else {
+            "table_catalog, table_schema, table_name, column_name, data_type, is_nullable"
+        };
+
+        let query = format!(
+            "SELECT {} FROM information_schema.columns WHERE {}",
+            select_list, where_clause
+        );
+
+        let rewrite = DFParser::parse_sql(&query)?;
+        self.statement_to_plan(&rewrite[0])
+    }
+
+    /// Return true if there is a table provider available for "schema.table"
+    fn has_table(&self, schema: &str, table: &str) -> bool {
+        let tables_reference = TableReference::Partial { schema, table };
+        self.schema_provider
+            .get_table_provider(tables_reference)
+            .is_some()
+    }
+}
 
-        // We are storing parts as integers, it's why we need to align parts fractional
-        // INTERVAL '0.5 MONTH' = 15 days, INTERVAL '1.5 MONTH' = 1 month 15 days
-        // INTERVAL '0.5 DAY' = 12 hours, INTERVAL '1.5 DAY' = 1 day 12 hours
-        let align_interval_parts = |month_part: f32,
-                                    mut day_part: f32,
-                                    mut milles_part: f32|
-         -> (i32, i32, f32) {
+/// The SQL interval `INTERVAL 'value' leading_field` as a
+/// [`ScalarValue::IntervalYearMonth`] or [`ScalarValue::IntervalDayTime`]
+pub(crate) fn sql_interval_to_scalar(
+    value: &str,
+    leading_field: &Option<DateTimeField>,
+    leading_precision: &Option<u64>,
+    last_field: &Option<DateTimeField>,
+    fractional_seconds_precision: &Option<u64>,
+) -> Result<ScalarValue> {
+    if leading_precision.is_some() {
+        return Err(DataFusionError::NotImplemented(format!(
+            "Unsupported Interval Expression with leading_precision {:?}",
+            leading_precision
+        )));
+    }
+
+    if last_field.is_some() {
+        return Err(DataFusionError::NotImplemented(format!(
+            "Unsupported Interval Expression with last_field {:?}",
+            last_field
+        )));
+    }
+
+    if fractional_seconds_precision.is_some() {
+        return Err(DataFusionError::NotImplemented(format!(
+            "Unsupported Interval Expression with fractional_seconds_precision {:?}",
+            fractional_seconds_precision
+        )));
+    }
+
+    const SECONDS_PER_HOUR: f32 = 3_600_f32;
+    const MILLIS_PER_SECOND: f32 = 1_000_f32;
+
+    // We are storing parts as integers, it's why we need to align parts fractional
+    // INTERVAL '0.5 MONTH' = 15 days, INTERVAL '1.5 MONTH' = 1 month 15 days
+    // INTERVAL '0.5 DAY' = 12 hours, INTERVAL '1.5 DAY' = 1 day 12 hours
+    let align_interval_parts =
+        |month_part: f32, mut day_part: f32, mut milles_part: f32| -> (i32, i32, f32) {
             // Convert fractional month to days, It's not supported by Arrow types, but anyway
             day_part += (month_part - (month_part as i32) as f32) * 30_f32;
 
@@ -1721,9 +2434,8 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
             (month_part as i32, day_part as i32, milles_part)
         };
 
-        let calculate_from_part = |interval_period_str: &str,
-                                   interval_type: &str|
-         -> Result<(i32, i32, f32)> {
+    let calculate_from_part =
+        |interval_period_str: &str, interval_type: &str| -> Result<(i32, i32, f32)> {
             // @todo It's better to use Decimal in order to protect rounding errors
             // Wait https://github.com/apache/arrow/pull/9232
             let interval_period = match f32::from_str(interval_period_str) {
@@ -1757,174 +2469,83 @@ impl<'a, S: ContextProvider> SqlToRel<'a, S> {
                 "milliseconds" | "millisecond" => Ok((0, 0, interval_period)),
                 _ => Err(DataFusionError::NotImplemented(format!(
                     "Invalid input syntax for type interval: {:?}",
-                    value
-                ))),
-            }
-        };
-
-        let mut result_month: i64 = 0;
-        let mut result_days: i64 = 0;
-        let mut result_millis: i64 = 0;
-
-        let mut parts = value.split_whitespace();
-
-        loop {
-            let interval_period_str = parts.next();
-            if interval_period_str.is_none() {
-                break;
-            }
-
-            let leading_field = leading_field
-                .as_ref()
-                .map(|dt| dt.to_string())
-                .unwrap_or_else(|| "second".to_string());
-
-            let unit = parts
-                .next()
-                .map(|part| part.to_string())
-                .unwrap_or(leading_field);
-
-            let (diff_month, diff_days, diff_millis) =
-                calculate_from_part(interval_period_str.unwrap(), &unit)?;
-
-            result_month += diff_month as i64;
-
-            if result_month > (i32::MAX as i64) {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Interval field value out of range: {:?}",
-                    value
-                )));
-            }
-
-            result_days += diff_days as i64;
-
-            if result_days > (i32::MAX as i64) {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Interval field value out of range: {:?}",
-                    value
-                )));
-            }
-
-            result_millis += diff_millis as i64;
-
-            if result_millis > (i32::MAX as i64) {
-                return Err(DataFusionError::NotImplemented(format!(
-                    "Interval field value out of range: {:?}",
-                    value
-                )));
-            }
-        }
-
-        // Interval is tricky thing
-        // 1 day is not 24 hours because timezones, 1 year != 365/364! 30 days != 1 month
-        // The true way to store and calculate intervals is to store it as it defined
-        // Due the fact that Arrow supports only two types YearMonth (month) and DayTime (day, time)
-        // It's not possible to store complex intervals
-        // It's possible to do select (NOW() + INTERVAL '1 year') + INTERVAL '1 day'; as workaround
-        if result_month != 0 && (result_days != 0 || result_millis != 0) {
-            return Err(DataFusionError::NotImplemented(format!(
-                "DF does not support intervals that have both a Year/Month part as well as Days/Hours/Mins/Seconds: {:?}. Hint: try breaking the interval into two parts, one with Year/Month and the other with Days/Hours/Mins/Seconds - e.g. (NOW() + INTERVAL '1 year') + INTERVAL '1 day'",
-                value
-            )));
-        }
-
-        if result_month != 0 {
-            return Ok(Expr::Literal(ScalarValue::IntervalYearMonth(Some(
-                result_month as i32,
-            ))));
-        }
-
-        let result: i64 = (result_days << 32) | result_millis;
-        Ok(Expr::Literal(ScalarValue::IntervalDayTime(Some(result))))
-    }
-
-    fn show_variable_to_plan(&self, variable: &[Ident]) -> Result<LogicalPlan> {
-        // Special case SHOW TABLES
-        let variable = ObjectName(variable.to_vec()).to_string();
-        if variable.as_str().eq_ignore_ascii_case("tables") {
-            if self.has_table("information_schema", "tables") {
-                let rewrite =
-                    DFParser::parse_sql("SELECT * FROM information_schema.tables;")?;
-                self.statement_to_plan(&rewrite[0])
-            } else {
-                Err(DataFusionError::Plan(
-                    "SHOW TABLES is not supported unless information_schema is enabled"
-                        .to_string(),
-                ))
-            }
-        } else {
-            Err(DataFusionError::NotImplemented(format!(
-                "SHOW {} not implemented. Supported syntax: SHOW <TABLES>",
-                variable
-            )))
-        }
-    }
-
-    fn show_columns_to_plan(
-        &self,
-        extended: bool,
-        full: bool,
-        table_name: &ObjectName,
-        filter: Option<&ShowStatementFilter>,
-    ) -> Result<LogicalPlan> {
-        if filter.is_some() {
-            return Err(DataFusionError::Plan(
-                "SHOW COLUMNS with WHERE or LIKE is not supported".to_string(),
-            ));
-        }
+                    value
+                ))),
+            }
+        };
 
-        if !self.has_table("information_schema", "columns") {
-            return Err(DataFusionError::Plan(
-                "SHOW COLUMNS is not supported unless information_schema is enabled"
-                    .to_string(),
-            ));
+    let mut result_month: i64 = 0;
+    let mut result_days: i64 = 0;
+    let mut result_millis: i64 = 0;
+
+    let mut parts = value.split_whitespace();
+
+    loop {
+        let interval_period_str = parts.next();
+        if interval_period_str.is_none() {
+            break;
         }
 
-        if self
-            .schema_provider
-            .get_table_provider(table_name.try_into()?)
-            .is_none()
-        {
-            return Err(DataFusionError::Plan(format!(
-                "Unknown relation for SHOW COLUMNS: {}",
-                table_name
+        let leading_field = leading_field
+            .as_ref()
+            .map(|dt| dt.to_string())
+            .unwrap_or_else(|| "second".to_string());
+
+        let unit = parts
+            .next()
+            .map(|part| part.to_string())
+            .unwrap_or(leading_field);
+
+        let (diff_month, diff_days, diff_millis) =
+            calculate_from_part(interval_period_str.unwrap(), &unit)?;
+
+        result_month += diff_month as i64;
+
+        if result_month > (i32::MAX as i64) {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Interval field value out of range: {:?}",
+                value
             )));
         }
 
-        // Figure out the where clause
-        let columns = vec!["table_name", "table_schema", "table_catalog"].into_iter();
-        let where_clause = table_name
-            .0
-            .iter()
-            .rev()
-            .zip(columns)
-            .map(|(ident, column_name)| format!(r#"{} = '{}'"#, column_name, ident))
-            .collect::<Vec<_>>()
-            .join(" AND ");
+        result_days += diff_days as i64;
 
-        // treat both FULL and EXTENDED as the same
-        let select_list = if full || extended {
-            "*"
-        } else {
-            "table_catalog, table_schema, table_name, column_name, data_type, is_nullable"
-        };
+        if result_days > (i32::MAX as i64) {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Interval field value out of range: {:?}",
+                value
+            )));
+        }
 
-        let query = format!(
-            "SELECT {} FROM information_schema.columns WHERE {}",
-            select_list, where_clause
-        );
+        result_millis += diff_millis as i64;
 
-        let rewrite = DFParser::parse_sql(&query)?;
-        self.statement_to_plan(&rewrite[0])
+        if result_millis > (i32::MAX as i64) {
+            return Err(DataFusionError::NotImplemented(format!(
+                "Interval field value out of range: {:?}",
+                value
+            )));
+        }
     }
 
-    /// Return true if there is a table provider available for "schema.table"
-    fn has_table(&self, schema: &str, table: &str) -> bool {
-        let tables_reference = TableReference::Partial { schema, table };
-        self.schema_provider
-            .get_table_provider(tables_reference)
-            .is_some()
+    // Interval is tricky thing
+    // 1 day is not 24 hours because timezones, 1 year != 365/364! 30 days != 1 month
+    // The true way to store and calculate intervals is to store it as it defined
+    // Due the fact that Arrow supports only two types YearMonth (month) and DayTime (day, time)
+    // It's not possible to store complex intervals
+    // It's possible to do select (NOW() + INTERVAL '1 year') + INTERVAL '1 day'; as workaround
+    if result_month != 0 && (result_days != 0 || result_millis != 0) {
+        return Err(DataFusionError::NotImplemented(format!(
+            "DF does not support intervals that have both a Year/Month part as well as Days/Hours/Mins/Seconds: {:?}. Hint: try breaking the interval into two parts, one with Year/Month and the other with Days/Hours/Mins/Seconds - e.g. (NOW() + INTERVAL '1 year') + INTERVAL '1 day'",
+            value
+        )));
+    }
+
+    if result_month != 0 {
+        return Ok(ScalarValue::IntervalYearMonth(Some(result_month as i32)));
     }
+
+    let result: i64 = (result_days << 32) | result_millis;
+    Ok(ScalarValue::IntervalDayTime(Some(result)))
 }
 
 /// Remove join expressions from a filter expression
@@ -2031,6 +2652,176 @@ fn extract_possible_join_keys(
     }
 }
 
+/// Whether `plan` scans the work table of the recursive CTE `name`
+fn references_work_table(plan: &LogicalPlan, name: &str) -> Result<bool> {
+    struct WorkTableVisitor<'a> {
+        name: &'a str,
+        found: bool,
+    }
+
+    impl PlanVisitor for WorkTableVisitor<'_> {
+        type Error = DataFusionError;
+
+        fn pre_visit(&mut self, plan: &LogicalPlan) -> Result<bool> {
+            if let LogicalPlan::TableScan(TableScan { source, .. }) = plan {
+                if let Some(work_table) = source.as_any().downcast_ref::<CteWorkTable>() {
+                    self.found |= work_table.name() == self.name;
+                }
+            }
+            Ok(!self.found)
+        }
+    }
+
+    let mut visitor = WorkTableVisitor { name, found: false };
+    plan.accept(&mut visitor)?;
+    Ok(visitor.found)
+}
+
+/// Inline the plan of a view, whose columns are then qualified by `name`
+fn view_to_plan(view: &ViewTable, name: &str) -> Result<LogicalPlan> {
+    let plan = view.logical_plan().clone();
+    let expr = plan
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| Expr::Column(field.qualified_column()))
+        .collect::<Vec<_>>();
+    LogicalPlanBuilder::from(plan)
+        .project_with_alias(expr, Some(name.to_owned()))?
+        .build()
+}
+
+/// Create the format writing the files of a `COPY` to `path` from the options
+/// of the statement. The file type is given by the `FORMAT` option, or else by
+/// the extension of `path`.
+fn copy_to_format(
+    path: &str,
+    options: &[(String, String)],
+) -> Result<(FileType, Arc<dyn FileFormat>)> {
+    let file_type = match options.iter().find(|(name, _)| name == "format") {
+        Some((_, value)) if value.eq_ignore_ascii_case("json") => FileType::NdJson,
+        Some((_, value)) => value
+            .parse::<FileType>()
+            .map_err(|e| DataFusionError::Plan(e.to_string()))?,
+        None => {
+            let name = path.trim_end_matches('/').to_lowercase();
+            if name.ends_with(".parquet") {
+                FileType::Parquet
+            } else if name.ends_with(".csv") {
+                FileType::CSV
+            } else if name.ends_with(".json") || name.ends_with(".ndjson") {
+                FileType::NdJson
+            } else if name.ends_with(".arrow") {
+                FileType::Arrow
+            } else {
+                return Err(DataFusionError::Plan(format!(
+                    "Cannot infer the format of the files written to {}, \
+                     set it with the FORMAT option",
+                    path
+                )));
+            }
+        }
+    };
+
+    let unsupported = |name: &str| {
+        DataFusionError::Plan(format!(
+            "Unsupported option {} for the {:?} format",
+            name, file_type
+        ))
+    };
+    let mut options = options.iter().filter(|(name, _)| name != "format");
+    let format: Arc<dyn FileFormat> = match file_type {
+        FileType::Parquet => {
+            let mut builder = WriterProperties::builder();
+            for (name, value) in options {
+                builder = match name.as_str() {
+                    "compression" => builder.set_compression(parse_compression(value)?),
+                    "row_group_size" => match parse_option(name, value)? {
+                        0 => {
+                            return Err(DataFusionError::Plan(
+                                "The row group size must be greater than 0".to_string(),
+                            ))
+                        }
+                        size => builder.set_max_row_group_size(size),
+                    },
+                    "data_page_size" => {
+                        builder.set_data_pagesize_limit(parse_option(name, value)?)
+                    }
+                    "dictionary_enabled" => {
+                        builder.set_dictionary_enabled(parse_option(name, value)?)
+                    }
+                    "statistics_enabled" => {
+                        builder.set_statistics_enabled(parse_option(name, value)?)
+                    }
+                    _ => return Err(unsupported(name)),
+                };
+            }
+            Arc::new(ParquetFormat::default().with_writer_properties(builder.build()))
+        }
+        FileType::CSV => {
+            let mut format = CsvFormat::default();
+            for (name, value) in options {
+                format = match name.as_str() {
+                    "header" => format.with_has_header(parse_option(name, value)?),
+                    "delimiter" => match value.as_bytes() {
+                        [delimiter] => format.with_delimiter(*delimiter),
+                        _ => {
+                            return Err(DataFusionError::Plan(format!(
+                                "The delimiter must be a single character, found {}",
+                                value
+                            )))
+                        }
+                    },
+                    _ => return Err(unsupported(name)),
+                };
+            }
+            Arc::new(format)
+        }
+        FileType::NdJson => {
+            if let Some((name, _)) = options.next() {
+                return Err(unsupported(name));
+            }
+            Arc::new(JsonFormat::default())
+        }
+        FileType::Arrow => {
+            if let Some((name, _)) = options.next() {
+                return Err(unsupported(name));
+            }
+            Arc::new(ArrowFormat::default())
+        }
+        FileType::Avro => {
+            return Err(DataFusionError::NotImplemented(
+                "Writing AVRO files is not supported".to_string(),
+            ))
+        }
+    };
+    Ok((file_type, format))
+}
+
+/// Parse the value of the option `name` of a `COPY`
+fn parse_option<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value.to_lowercase().parse().map_err(|_| {
+        DataFusionError::Plan(format!("Invalid value {} for the option {}", value, name))
+    })
+}
+
+/// Parse the parquet compression codec `value`
+fn parse_compression(value: &str) -> Result<Compression> {
+    match value.to_lowercase().as_str() {
+        "uncompressed" | "none" => Ok(Compression::UNCOMPRESSED),
+        "snappy" => Ok(Compression::SNAPPY),
+        "gzip" => Ok(Compression::GZIP),
+        "lzo" => Ok(Compression::LZO),
+        "brotli" => Ok(Compression::BROTLI),
+        "lz4" => Ok(Compression::LZ4),
+        "zstd" => Ok(Compression::ZSTD),
+        _ => Err(DataFusionError::Plan(format!(
+            "Unsupported parquet compression {}",
+            value
+        ))),
+    }
+}
+
 /// Convert SQL data type to relational representation of data type
 pub fn convert_data_type(sql_type: &SQLDataType) -> Result<DataType> {
     match sql_type {
@@ -2057,8 +2848,12 @@ mod tests {
     use functions::ScalarFunctionImplementation;
 
     use crate::datasource::empty::EmptyTable;
+    use crate::logical_plan::plan::Filter;
     use crate::physical_plan::functions::Volatility;
-    use crate::{logical_plan::create_udf, sql::parser::DFParser};
+    use crate::{
+        logical_plan::{create_udf, MAX_CUBE_EXPRESSIONS},
+        sql::parser::DFParser,
+    };
 
     use super::*;
 
@@ -3105,6 +3900,61 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn select_group_by_rollup_with_grouping() {
+        let sql = "SELECT state, age, COUNT(*), GROUPING(state, age) FROM person \
+                   GROUP BY ROLLUP (state, age)";
+        let expected = "Projection: #person.state, #person.age, #COUNT(UInt8(1)), #GROUPING(person.state,person.age)\
+                        \n  Projection: #person.state, #person.age, #COUNT(UInt8(1)), #__grouping_id / UInt32(2) % UInt32(2) * UInt32(2) + #__grouping_id % UInt32(2) AS GROUPING(person.state,person.age)\
+                        \n    Aggregate: groupBy=[[ROLLUP (#person.state, #person.age)]], aggr=[[COUNT(UInt8(1))]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_grouping_sets_and_cube() {
+        let sql = "SELECT state, age, SUM(salary) FROM person \
+                   GROUP BY GROUPING SETS ((state, age), (state), ())";
+        let expected = "Projection: #person.state, #person.age, #SUM(person.salary)\
+                        \n  Projection: #person.state, #person.age, #SUM(person.salary)\
+                        \n    Aggregate: groupBy=[[GROUPING SETS ((#person.state, #person.age), (#person.state), ())]], aggr=[[SUM(#person.salary)]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+
+        let sql = "SELECT state, age, id, MAX(salary) FROM person GROUP BY id, CUBE (state, age)";
+        let expected = "Projection: #person.state, #person.age, #person.id, #MAX(person.salary)\
+                        \n  Projection: #person.id, #person.state, #person.age, #MAX(person.salary)\
+                        \n    Aggregate: groupBy=[[#person.id, CUBE (#person.state, #person.age)]], aggr=[[MAX(#person.salary)]]\
+                        \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_group_by_too_large_cube() {
+        let exprs = (0..=MAX_CUBE_EXPRESSIONS)
+            .map(|i| format!("age + {}", i))
+            .collect::<Vec<_>>();
+        let sql = format!(
+            "SELECT COUNT(*) FROM person GROUP BY CUBE ({})",
+            exprs.join(", ")
+        );
+        let err = logical_plan(&sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"CUBE supports at most 12 expressions, got 13\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn select_grouping_of_non_grouping_expression() {
+        let sql = "SELECT GROUPING(age) FROM person GROUP BY ROLLUP (state)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"Arguments to GROUPING must be grouping expressions, got #person.age\")",
+            format!("{:?}", err)
+        );
+    }
+
     #[test]
     fn select_group_by_columns_not_in_select() {
         let sql = "SELECT MAX(age) FROM person GROUP BY state";
@@ -3474,13 +4324,13 @@ mod tests {
     }
 
     #[test]
-    fn over_order_by_with_window_frame_range_value_check() {
+    fn over_order_by_with_window_frame_range_value() {
         let sql = "SELECT order_id, MAX(qty) OVER (ORDER BY order_id RANGE 3 PRECEDING) from orders";
-        let err = logical_plan(sql).expect_err("query should have failed");
-        assert_eq!(
-            "NotImplemented(\"With WindowFrameUnits=RANGE, the bound cannot be 3 PRECEDING or FOLLOWING at the moment\")",
-            format!("{:?}", err)
-        );
+        let expected = "\
+        Projection: #orders.order_id, #MAX(orders.qty) ORDER BY [#orders.order_id ASC NULLS LAST] RANGE BETWEEN 3 PRECEDING AND CURRENT ROW\
+        \n  WindowAggr: windowExpr=[[MAX(#orders.qty) ORDER BY [#orders.order_id ASC NULLS LAST] RANGE BETWEEN 3 PRECEDING AND CURRENT ROW]]\
+        \n    TableScan: orders projection=None";
+        quick_test(sql, expected);
     }
 
     #[test]
@@ -3721,6 +4571,241 @@ mod tests {
         quick_test(sql, expected);
     }
 
+    #[test]
+    fn select_correlated_exists() {
+        let sql = "SELECT id FROM person \
+            WHERE EXISTS (SELECT order_id FROM orders WHERE customer_id = id)";
+        let plan = logical_plan(sql).unwrap();
+        let expected = "Projection: #person.id\
+            \n  Filter: EXISTS (<subquery>)\
+            \n    TableScan: person projection=None";
+        assert_eq!(format!("{:?}", plan), expected);
+
+        // the columns of the subquery are resolved before the ones of the
+        // outer query
+        let subquery = match plan.inputs()[0] {
+            LogicalPlan::Filter(Filter {
+                predicate: Expr::Exists { subquery, .. },
+                ..
+            }) => subquery.subquery.clone(),
+            _ => unreachable!(),
+        };
+        let expected = "Projection: #orders.order_id\
+            \n  Filter: #orders.customer_id = #person.id\
+            \n    TableScan: orders projection=None";
+        assert_eq!(format!("{:?}", subquery), expected);
+    }
+
+    #[test]
+    fn select_not_exists() {
+        let sql = "SELECT id FROM person \
+            WHERE NOT EXISTS (SELECT order_id FROM orders WHERE customer_id = id)";
+        let expected = "Projection: #person.id\
+            \n  Filter: NOT EXISTS (<subquery>)\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_not_in_subquery() {
+        let sql = "SELECT id FROM person \
+            WHERE age > 21 AND id NOT IN (SELECT customer_id FROM orders)";
+        let expected = "Projection: #person.id\
+            \n  Filter: #person.age > Int64(21) AND #person.id NOT IN (<subquery>)\
+            \n    TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_scalar_subquery() {
+        let sql = "SELECT id, (SELECT MAX(qty) FROM orders) FROM person";
+        let expected = "Projection: #person.id, (<subquery>)\
+            \n  TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn select_in_subquery_with_multiple_columns() {
+        let sql = "SELECT id FROM person WHERE id IN (SELECT order_id, qty FROM orders)";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("Subquery must return exactly one column, but it returns 2")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn create_view() {
+        let sql = "CREATE OR REPLACE VIEW v (a) AS SELECT id FROM person";
+        let expected = "CreateView: \"v\" or replace:=true\
+            \n  Projection: #person.id AS a, alias=v\
+            \n    Projection: #person.id\
+            \n      TableScan: person projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn create_view_column_count_mismatch() {
+        let sql = "CREATE VIEW v (a, b) AS SELECT id FROM person";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("v has 1 columns but 2 names are given as column alias")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn drop_view() {
+        let sql = "DROP VIEW IF EXISTS v";
+        let expected = "DropView: \"v\" if not exist:=true";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn refresh_table() {
+        let sql = "REFRESH TABLE person";
+        let expected = "RefreshTable: \"person\"";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn insert_into_columns() {
+        let sql =
+            "INSERT INTO lineitem (l_item_id, price) SELECT order_id, qty FROM orders";
+        let expected = "Insert: lineitem overwrite=false\
+            \n  Projection: #orders.order_id AS l_item_id, Utf8(NULL) AS l_description, CAST(#orders.qty AS Float64) AS price\
+            \n    Projection: #orders.order_id, #orders.qty\
+            \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn insert_into_missing_not_null_column() {
+        let sql = "INSERT INTO lineitem (l_item_id) SELECT order_id FROM orders";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("Column price of table lineitem is not nullable and must be listed in INSERT INTO")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn insert_overwrite() {
+        let sql = "INSERT OVERWRITE TABLE lineitem SELECT order_id, o_item_id, price FROM orders";
+        let expected = "Insert: lineitem overwrite=true\
+            \n  Projection: #orders.order_id AS l_item_id, #orders.o_item_id AS l_description, #orders.price AS price\
+            \n    Projection: #orders.order_id, #orders.o_item_id, #orders.price\
+            \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn insert_column_count_mismatch() {
+        let sql = "INSERT INTO lineitem VALUES (1, 'a')";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"Plan("INSERT INTO lineitem expects 3 columns but the query produces 2")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn copy_table_to() {
+        let sql = "COPY orders TO 'out.parquet'";
+        let expected = "CopyTo: out.parquet format=Parquet\
+            \n  TableScan: orders projection=None";
+        quick_test(sql, expected);
+    }
+
+    #[test]
+    fn copy_query_to() {
+        let sql = "COPY (SELECT order_id, price FROM orders WHERE qty > 1) TO 'out' \
+                   (FORMAT csv, HEADER false, DELIMITER '|')";
+        let expected = "CopyTo: out format=CSV\
+            \n  Projection: #orders.order_id, #orders.price\
+            \n    Filter: #orders.qty > Int64(1)\
+            \n      TableScan: orders projection=None";
+        quick_test(sql, expected);
+
+        let sql = "COPY orders TO 'out' \
+                   (FORMAT parquet, COMPRESSION zstd, ROW_GROUP_SIZE 1024)";
+        match logical_plan(sql).unwrap() {
+            LogicalPlan::CopyTo(PlanCopyTo { format, .. }) => {
+                let properties = format
+                    .as_any()
+                    .downcast_ref::<ParquetFormat>()
+                    .and_then(|format| format.writer_properties())
+                    .unwrap();
+                assert_eq!(1024, properties.max_row_group_size());
+            }
+            plan => panic!("Expected CopyTo, found {:?}", plan),
+        }
+    }
+
+    #[test]
+    fn copy_to_invalid_options() {
+        let cases = vec![
+            (
+                "COPY orders TO 'out'",
+                "Cannot infer the format of the files written to out, set it with the FORMAT option",
+            ),
+            (
+                "COPY orders TO 'out.json' (HEADER true)",
+                "Unsupported option header for the NdJson format",
+            ),
+            (
+                "COPY orders TO 'out' (FORMAT parquet, COMPRESSION foo)",
+                "Unsupported parquet compression foo",
+            ),
+            (
+                "COPY orders TO 'out' (FORMAT parquet, ROW_GROUP_SIZE many)",
+                "Invalid value many for the option row_group_size",
+            ),
+            (
+                "COPY orders TO 'out.csv' (DELIMITER ';;')",
+                "The delimiter must be a single character, found ;;",
+            ),
+        ];
+        for (sql, message) in cases {
+            let err = logical_plan(sql).expect_err("query should have failed");
+            assert_eq!(format!("Plan(\"{}\")", message), format!("{:?}", err));
+        }
+    }
+
+    #[test]
+    fn recursive_cte_union_distinct() {
+        let sql = "WITH RECURSIVE t(n) AS (\
+            SELECT id FROM person UNION SELECT n FROM t WHERE n < 10\
+            ) SELECT n FROM t";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            r#"NotImplemented("Recursive query t must combine its terms with UNION ALL")"#,
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn recursive_cte_column_count_mismatch() {
+        let sql = "WITH RECURSIVE t(n) AS (\
+            SELECT id FROM person UNION ALL SELECT n, n FROM t WHERE n < 10\
+            ) SELECT n FROM t";
+        let err = logical_plan(sql).expect_err("query should have failed");
+        assert_eq!(
+            "Plan(\"The static and recursive terms of recursive query t produce 1 and \
+            2 columns, they must produce the same number of columns\")",
+            format!("{:?}", err)
+        );
+    }
+
+    #[test]
+    fn recursive_cte_without_self_reference() {
+        let sql = "WITH RECURSIVE t AS (\
+            SELECT id FROM person UNION ALL SELECT id FROM person WHERE age > 21\
+            ) SELECT id FROM t";
+        let plan = logical_plan(sql).unwrap();
+        assert!(!format!("{:?}", plan).contains("RecursiveQuery"));
+    }
+
     fn logical_plan(sql: &str) -> Result<LogicalPlan> {
         let planner = SqlToRel::new(&MockContextProvider {});
         let result = DFParser::parse_sql(sql);
@@ -3766,7 +4851,7 @@ mod tests {
                 ])),
                 "lineitem" => Some(Schema::new(vec![
                     Field::new("l_item_id", DataType::UInt32, false),
-                    Field::new("l_description", DataType::Utf8, false),
+                    Field::new("l_description", DataType::Utf8, true),
                     Field::new("price", DataType::Float64, false),
                 ])),
                 "aggregate_test_100" => Some(Schema::new(vec![
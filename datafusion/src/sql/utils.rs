@@ -218,6 +218,8 @@ where
                 fun,
                 args,
                 distinct,
+                filter,
+                order_by,
             } => Ok(Expr::AggregateFunction {
                 fun: fun.clone(),
                 args: args
@@ -225,6 +227,16 @@ where
                     .map(|e| clone_with_replacement(e, replacement_fn))
                     .collect::<Result<Vec<Expr>>>()?,
                 distinct: *distinct,
+                filter: match filter {
+                    Some(filter) => {
+                        Some(Box::new(clone_with_replacement(&**filter, replacement_fn)?))
+                    }
+                    None => None,
+                },
+                order_by: order_by
+                    .iter()
+                    .map(|e| clone_with_replacement(e, replacement_fn))
+                    .collect::<Result<Vec<_>>>()?,
             }),
             Expr::WindowFunction {
                 fun,
@@ -366,10 +378,26 @@ where
                 asc: *asc,
                 nulls_first: *nulls_first,
             }),
-            Expr::Column { .. } | Expr::Literal(_) | Expr::ScalarVariable(_) => {
-                Ok(expr.clone())
-            }
+            Expr::InSubquery {
+                expr: nested_expr,
+                subquery,
+                negated,
+            } => Ok(Expr::InSubquery {
+                expr: Box::new(clone_with_replacement(&**nested_expr, replacement_fn)?),
+                subquery: subquery.clone(),
+                negated: *negated,
+            }),
+            Expr::Column { .. }
+            | Expr::Literal(_)
+            | Expr::ScalarVariable(_)
+            | Expr::Exists { .. }
+            | Expr::ScalarSubquery(_) => Ok(expr.clone()),
             Expr::Wildcard => Ok(Expr::Wildcard),
+            Expr::GroupingSet(grouping_set) => {
+                Ok(Expr::GroupingSet(grouping_set.clone().map_exprs(
+                    |expr| clone_with_replacement(&expr, replacement_fn),
+                )?))
+            }
             Expr::GetIndexedField { expr, key } => Ok(Expr::GetIndexedField {
                 expr: Box::new(clone_with_replacement(expr.as_ref(), replacement_fn)?),
                 key: key.clone(),
@@ -253,3 +253,205 @@ async fn csv_query_array_agg_one() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn aggregate_exceeding_memory_limit_spills() -> Result<()> {
+    let config = ExecutionConfig::new().with_memory_limit(64 * 1024);
+    let mut ctx = ExecutionContext::with_config(config);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+    let batches = (0..10)
+        .map(|batch| {
+            let values: Vec<i32> = (0..1000).map(|i| (batch * 1000 + i) % 3000).collect();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+    let table = MemTable::try_new(schema, vec![batches])?;
+    ctx.register_table("t", Arc::new(table))?;
+
+    // the states of the 3000 groups do not fit in the memory pool
+    let sql = "SELECT COUNT(*), SUM(cnt), MIN(cnt), MAX(cnt) \
+               FROM (SELECT a, COUNT(*) AS cnt FROM t GROUP BY a)";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-----------------+----------+----------+----------+",
+        "| COUNT(UInt8(1)) | SUM(cnt) | MIN(cnt) | MAX(cnt) |",
+        "+-----------------+----------+----------+----------+",
+        "| 3000            | 10000    | 3        | 4        |",
+        "+-----------------+----------+----------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+fn register_statistics_table(ctx: &mut ExecutionContext) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("g", DataType::Utf8, false),
+        Field::new("x", DataType::Int64, true),
+        Field::new("y", DataType::Float64, false),
+    ]));
+    // y is twice x, except for the row in which x is null
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec![
+                "a", "a", "a", "a", "b", "b", "b", "b", "b",
+            ])),
+            Arc::new(Int64Array::from(vec![
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(4),
+                Some(5),
+                Some(6),
+                Some(7),
+                Some(8),
+                None,
+            ])),
+            Arc::new(Float64Array::from(vec![
+                2.0, 4.0, 6.0, 8.0, 10.0, 12.0, 14.0, 16.0, 100.0,
+            ])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![batch]])?;
+    ctx.register_table("t", Arc::new(table))?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_stddev_and_variance() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT stddev(x) AS s, stddev_pop(x) AS sp, var_samp(x) AS v, var_pop(x) AS vp FROM t";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------------------+------------------+---+------+",
+        "| s                 | sp               | v | vp   |",
+        "+-------------------+------------------+---+------+",
+        "| 2.449489742783178 | 2.29128784747792 | 6 | 5.25 |",
+        "+-------------------+------------------+---+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_covariance_and_correlation() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    // the pair in which x is null is ignored
+    let sql = "SELECT covar(x, y) AS c, covar_pop(x, y) AS cp, corr(x, y) AS r FROM t";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+------+---+",
+        "| c  | cp   | r |",
+        "+----+------+---+",
+        "| 12 | 10.5 | 1 |",
+        "+----+------+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_percentiles() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT g, median(x) AS m, \
+               percentile_cont(0.25) WITHIN GROUP (ORDER BY x) AS p25, \
+               percentile_cont(0.25) WITHIN GROUP (ORDER BY x DESC) AS p75, \
+               approx_percentile_cont(x, 0.5) AS am \
+               FROM t GROUP BY g";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+-----+------+------+-----+",
+        "| g | m   | p25  | p75  | am  |",
+        "+---+-----+------+------+-----+",
+        "| a | 2.5 | 1.75 | 3.25 | 2.5 |",
+        "| b | 6.5 | 5.75 | 7.25 | 6.5 |",
+        "+---+-----+------+------+-----+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_percentile_must_be_literal() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT percentile_cont(x, y) FROM t";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(err.to_string(), "must be a literal");
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_aggregate_filter() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT g, count(*) FILTER (WHERE x > 2) AS c, \
+               sum(x) FILTER (WHERE y < 10) AS s, count(*) AS n \
+               FROM t GROUP BY g";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---+----+---+",
+        "| g | c | s  | n |",
+        "+---+---+----+---+",
+        "| a | 2 | 10 | 4 |",
+        "| b | 4 |    | 5 |",
+        "+---+---+----+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_aggregate_order_by() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql =
+        "SELECT g, array_agg(x ORDER BY y DESC) FILTER (WHERE x IS NOT NULL) AS xs \
+               FROM t GROUP BY g";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+--------------+",
+        "| g | xs           |",
+        "+---+--------------+",
+        "| a | [4, 3, 2, 1] |",
+        "| b | [8, 7, 6, 5] |",
+        "+---+--------------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_distinct_aggregate_order_by() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT array_agg(DISTINCT x ORDER BY y) FROM t";
+    let err = ctx.create_logical_plan(sql).unwrap_err();
+    assert!(
+        matches!(err, DataFusionError::NotImplemented(_)),
+        "unexpected error {}",
+        err
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_aggregates_with_different_orderings() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_statistics_table(&mut ctx)?;
+    let sql = "SELECT array_agg(x ORDER BY x), array_agg(x ORDER BY y) FROM t";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Aggregate functions with different orderings are not supported"
+    );
+    Ok(())
+}
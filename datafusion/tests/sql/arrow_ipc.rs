@@ -0,0 +1,56 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use super::*;
+
+#[tokio::test]
+async fn copy_to_and_query_arrow() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("out.arrow");
+    let path = path.to_str().unwrap();
+    let mut ctx = ExecutionContext::new();
+    ctx.sql("CREATE TABLE t AS SELECT * FROM (VALUES (1, 'a'), (2, 'b'), (3, 'c')) AS v (id, name)")
+        .await?;
+    execute_to_batches(&mut ctx, &format!("COPY t TO '{}'", path)).await;
+
+    let sql = format!(
+        "CREATE EXTERNAL TABLE copied STORED AS ARROW LOCATION '{}'",
+        path
+    );
+    ctx.sql(&sql).await?;
+    let actual =
+        execute_to_batches(&mut ctx, "SELECT name FROM copied WHERE id > 1 ORDER BY id")
+            .await;
+    let expected = vec![
+        "+------+", //
+        "| name |", "+------+", "| b    |", "| c    |", "+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    ctx.register_arrow("registered", path, ArrowReadOptions::default())
+        .await?;
+    let actual = execute_to_batches(&mut ctx, "SELECT count(*) FROM registered").await;
+    let expected = vec![
+        "+-----------------+",
+        "| COUNT(UInt8(1)) |",
+        "+-----------------+",
+        "| 3               |",
+        "+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
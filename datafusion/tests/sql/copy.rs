@@ -0,0 +1,91 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+use super::*;
+
+#[tokio::test]
+async fn copy_query_to_parquet() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("out");
+    let path = path.to_str().unwrap();
+    let mut ctx = ExecutionContext::new();
+    ctx.sql("CREATE TABLE t AS SELECT * FROM (VALUES (1, 'a'), (2, 'b'), (3, 'c')) AS v (id, name)")
+        .await?;
+
+    let sql = format!(
+        "COPY (SELECT id, name FROM t WHERE id > 1) TO '{}' \
+         (FORMAT parquet, COMPRESSION snappy, ROW_GROUP_SIZE 1)",
+        path
+    );
+    let actual = execute_to_batches(&mut ctx, &sql).await;
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 2     |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    ctx.register_parquet("copied", path).await?;
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM copied ORDER BY id").await;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "+----+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the target directory must not exist
+    let result = ctx.sql(&sql).await?.collect().await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn copy_table_to_csv() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("out.csv");
+    let path = path.to_str().unwrap();
+    let mut ctx = ExecutionContext::new();
+    ctx.sql(
+        "CREATE TABLE t AS SELECT * FROM (VALUES (1, 'a'), (2, 'b')) AS v (id, name)",
+    )
+    .await?;
+
+    let sql = format!("COPY t TO '{}' (HEADER false, DELIMITER '|')", path);
+    execute_to_batches(&mut ctx, &sql).await;
+
+    let options = CsvReadOptions::new().has_header(false).delimiter(b'|');
+    ctx.register_csv("copied", path, options).await?;
+    let actual =
+        execute_to_batches(&mut ctx, "SELECT * FROM copied ORDER BY column_1").await;
+    let expected = vec![
+        "+----------+----------+",
+        "| column_1 | column_2 |",
+        "+----------+----------+",
+        "| 1        | a        |",
+        "| 2        | b        |",
+        "+----------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
@@ -76,3 +76,179 @@ async fn csv_query_create_external_table() {
     ];
     assert_batches_eq!(expected, &actual);
 }
+
+#[tokio::test]
+async fn create_view() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_simple_csv(&mut ctx).await?;
+
+    let sql = "CREATE VIEW my_view AS SELECT c1, c3 FROM aggregate_simple WHERE c3";
+    ctx.sql(sql).await?;
+
+    // the view can be filtered and aliased like a table
+    let sql = "SELECT v.c1 FROM my_view AS v WHERE v.c1 < 0.00002";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------+",
+        "| c1      |",
+        "+---------+",
+        "| 0.00001 |",
+        "+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the view is inlined in the plan of the query
+    let plan = ctx.create_logical_plan("SELECT * FROM my_view")?;
+    assert!(!format!("{:?}", plan).contains("TableScan: my_view"));
+
+    // creating a view of the same name fails unless it replaces it
+    let err = ctx
+        .sql("CREATE VIEW my_view AS SELECT c2 FROM aggregate_simple")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: View \"my_view\" already exists"
+    );
+
+    let sql =
+        "CREATE OR REPLACE VIEW my_view (total) AS SELECT count(*) FROM aggregate_simple";
+    ctx.sql(sql).await?;
+    let actual = execute_to_batches(&mut ctx, "SELECT total FROM my_view").await;
+    let expected = vec![
+        "+-------+",
+        "| total |",
+        "+-------+",
+        "| 15    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // a view can not replace a table
+    let err = ctx
+        .sql("CREATE OR REPLACE VIEW aggregate_simple AS SELECT 1")
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: \"aggregate_simple\" is not a view"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn drop_view() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_aggregate_simple_csv(&mut ctx).await?;
+
+    ctx.sql("CREATE VIEW my_view AS SELECT * FROM aggregate_simple")
+        .await?;
+    ctx.sql("DROP VIEW my_view").await?;
+    assert!(
+        ctx.table("my_view").is_err(),
+        "drop view should deregister view."
+    );
+
+    ctx.sql("DROP VIEW IF EXISTS my_view").await?;
+    let err = ctx.sql("DROP VIEW my_view").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: View \"my_view\" doesn't exist."
+    );
+
+    // tables are not dropped as views
+    let err = ctx.sql("DROP VIEW aggregate_simple").await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Execution error: \"aggregate_simple\" is not a view"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn create_external_table_with_gzip_compression() -> Result<()> {
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    let tmp_dir = tempfile::tempdir()?;
+    let path = tmp_dir.path().join("data.csv.gz");
+    let mut encoder =
+        GzEncoder::new(std::fs::File::create(&path)?, Compression::default());
+    encoder.write_all(b"c1,c2\n1,a\n2,b\n")?;
+    encoder.finish()?;
+
+    let mut ctx = ExecutionContext::new();
+    let sql = format!(
+        "CREATE EXTERNAL TABLE t (c1 INT NOT NULL, c2 VARCHAR NOT NULL) \
+        STORED AS CSV WITH HEADER ROW COMPRESSION TYPE GZIP LOCATION '{}'",
+        tmp_dir.path().to_str().unwrap()
+    );
+    ctx.sql(&sql).await?;
+
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM t ORDER BY c1").await;
+    let expected = vec![
+        "+----+----+",
+        "| c1 | c2 |",
+        "+----+----+",
+        "| 1  | a  |",
+        "| 2  | b  |",
+        "+----+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn refresh_table_with_metadata_cache() -> Result<()> {
+    use datafusion::datasource::listing::DefaultMetadataCache;
+
+    let tmp_dir = tempfile::tempdir()?;
+    std::fs::write(tmp_dir.path().join("a.csv"), "c1\n1\n2\n")?;
+
+    let config =
+        ExecutionConfig::new().with_metadata_cache(Arc::new(DefaultMetadataCache::new()));
+    let mut ctx = ExecutionContext::with_config(config);
+    let sql = format!(
+        "CREATE EXTERNAL TABLE t STORED AS CSV WITH HEADER ROW LOCATION '{}'",
+        tmp_dir.path().to_str().unwrap()
+    );
+    ctx.sql(&sql).await?;
+
+    let expected = vec![
+        "+-----------------+",
+        "| COUNT(UInt8(1)) |",
+        "+-----------------+",
+        "| 2               |",
+        "+-----------------+",
+    ];
+    let actual = execute_to_batches(&mut ctx, "SELECT COUNT(*) FROM t").await;
+    assert_batches_eq!(expected, &actual);
+
+    // the new file is not listed until the table is refreshed
+    std::fs::write(tmp_dir.path().join("b.csv"), "c1\n3\n")?;
+    let actual = execute_to_batches(&mut ctx, "SELECT COUNT(*) FROM t").await;
+    assert_batches_eq!(expected, &actual);
+
+    ctx.sql("REFRESH TABLE t").await?;
+    let actual = execute_to_batches(&mut ctx, "SELECT COUNT(*) FROM t").await;
+    let expected = vec![
+        "+-----------------+",
+        "| COUNT(UInt8(1)) |",
+        "+-----------------+",
+        "| 3               |",
+        "+-----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let err = ctx.sql("REFRESH TABLE unknown").await.unwrap_err();
+    assert_eq!(
+        "Execution error: Table \"unknown\" doesn't exist.",
+        err.to_string()
+    );
+
+    Ok(())
+}
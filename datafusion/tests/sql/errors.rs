@@ -134,3 +134,29 @@ async fn invalid_qualified_table_references() -> Result<()> {
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn query_exceeding_memory_limit() -> Result<()> {
+    let config = ExecutionConfig::new().with_memory_limit(4096);
+    let mut ctx = ExecutionContext::with_config(config);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+    let batches = (0..10)
+        .map(|batch| {
+            let values: Vec<i32> = (0..2000).map(|i| batch * 2000 + i).collect();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+    let table = MemTable::try_new(schema, vec![batches])?;
+    ctx.register_table("t", Arc::new(table))?;
+
+    // not even a single input batch fits in the memory pool
+    let sql = "SELECT t1.a FROM t t1 JOIN t t2 ON t1.a = t2.a";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let err = collect(plan).await.unwrap_err();
+    assert_contains!(err.to_string(), "Resources exhausted");
+
+    Ok(())
+}
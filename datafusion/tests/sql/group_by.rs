@@ -442,3 +442,131 @@ async fn csv_group_by_date() -> Result<()> {
     assert_batches_sorted_eq!(expected, &actual);
     Ok(())
 }
+
+async fn register_sales_table(ctx: &mut ExecutionContext) -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("region", DataType::Utf8, false),
+        Field::new("product", DataType::Utf8, false),
+        Field::new("amount", DataType::Int64, false),
+    ]));
+
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec!["east", "east", "west", "west"])),
+            Arc::new(StringArray::from(vec!["a", "b", "a", "a"])),
+            Arc::new(Int64Array::from(vec![1, 2, 4, 8])),
+        ],
+    )?;
+
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+    ctx.register_table("sales", Arc::new(table))?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_group_by_rollup() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_sales_table(&mut ctx).await?;
+    let sql =
+        "SELECT region, product, SUM(amount) AS total, GROUPING(region, product) AS g \
+               FROM sales GROUP BY ROLLUP (region, product)";
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+--------+---------+-------+---+",
+        "| region | product | total | g |",
+        "+--------+---------+-------+---+",
+        "|        |         | 15    | 3 |",
+        "| east   |         | 3     | 1 |",
+        "| east   | a       | 1     | 0 |",
+        "| east   | b       | 2     | 0 |",
+        "| west   |         | 12    | 1 |",
+        "| west   | a       | 12    | 0 |",
+        "+--------+---------+-------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_group_by_cube() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_sales_table(&mut ctx).await?;
+    let sql = "SELECT region, product, SUM(amount) AS total, GROUPING(product) AS g \
+               FROM sales GROUP BY CUBE (region, product)";
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+--------+---------+-------+---+",
+        "| region | product | total | g |",
+        "+--------+---------+-------+---+",
+        "|        |         | 15    | 1 |",
+        "|        | a       | 13    | 0 |",
+        "|        | b       | 2     | 0 |",
+        "| east   |         | 3     | 1 |",
+        "| east   | a       | 1     | 0 |",
+        "| east   | b       | 2     | 0 |",
+        "| west   |         | 12    | 1 |",
+        "| west   | a       | 12    | 0 |",
+        "+--------+---------+-------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_group_by_grouping_sets() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_sales_table(&mut ctx).await?;
+    let sql =
+        "SELECT product, COUNT(*) AS n FROM sales GROUP BY GROUPING SETS ((product), ())";
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------+---+",
+        "| product | n |",
+        "+---------+---+",
+        "|         | 4 |",
+        "| a       | 3 |",
+        "| b       | 1 |",
+        "+---------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+
+    // the empty grouping set produces a row even without input
+    let sql = "SELECT product, COUNT(*) AS n FROM sales WHERE amount > 100 \
+               GROUP BY GROUPING SETS ((product), ())";
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------+---+",
+        "| product | n |",
+        "+---------+---+",
+        "|         | 0 |",
+        "+---------+---+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_group_by_rollup_having() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    register_sales_table(&mut ctx).await?;
+    // the filter on a grouping column must not be pushed below the aggregate,
+    // otherwise it would also restrict the grand total
+    let sql = "SELECT region, SUM(amount) AS total FROM sales \
+               GROUP BY ROLLUP (region) HAVING region = 'east'";
+
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+--------+-------+",
+        "| region | total |",
+        "+--------+-------+",
+        "| east   | 3     |",
+        "+--------+-------+",
+    ];
+    assert_batches_sorted_eq!(expected, &actual);
+    Ok(())
+}
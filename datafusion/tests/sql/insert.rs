@@ -0,0 +1,137 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::*;
+
+#[tokio::test]
+async fn insert_into_memory_table() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.sql("CREATE TABLE t AS SELECT * FROM (VALUES (1, 'a')) AS v (id, name)")
+        .await?;
+
+    let sql = "INSERT INTO t VALUES (2, 'b'), (3, 'c')";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| count |",
+        "+-------+",
+        "| 2     |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the columns that are not listed are null
+    let sql = "INSERT INTO t (name) SELECT name FROM t WHERE id = 1";
+    execute_to_batches(&mut ctx, sql).await;
+
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM t ORDER BY id").await;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "|    | a    |",
+        "+----+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "INSERT OVERWRITE TABLE t SELECT id * 10, name FROM t WHERE id > 1";
+    execute_to_batches(&mut ctx, sql).await;
+
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM t ORDER BY id").await;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 20 | b    |",
+        "| 30 | c    |",
+        "+----+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn insert_into_external_table() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let mut ctx = ExecutionContext::new();
+    let sql = format!(
+        "CREATE EXTERNAL TABLE t (id INT NOT NULL, name VARCHAR NOT NULL) \
+        STORED AS CSV LOCATION '{}'",
+        tmp_dir.path().to_str().unwrap()
+    );
+    ctx.sql(&sql).await?;
+
+    execute_to_batches(&mut ctx, "INSERT INTO t VALUES (1, 'a'), (2, 'b')").await;
+    execute_to_batches(&mut ctx, "INSERT INTO t VALUES (3, 'c')").await;
+
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM t ORDER BY id").await;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 1  | a    |",
+        "| 2  | b    |",
+        "| 3  | c    |",
+        "+----+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the columns that are not listed would be null
+    let err = ctx
+        .create_logical_plan("INSERT INTO t (name) VALUES ('e')")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: Column id of table t is not nullable and must be \
+        listed in INSERT INTO"
+    );
+
+    execute_to_batches(&mut ctx, "INSERT OVERWRITE TABLE t VALUES (4, 'd')").await;
+
+    let actual = execute_to_batches(&mut ctx, "SELECT * FROM t").await;
+    let expected = vec![
+        "+----+------+",
+        "| id | name |",
+        "+----+------+",
+        "| 4  | d    |",
+        "+----+------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn insert_into_unknown_column() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+    ctx.sql("CREATE TABLE t AS SELECT * FROM (VALUES (1, 'a')) AS v (id, name)")
+        .await?;
+
+    let err = ctx
+        .create_logical_plan("INSERT INTO t (id, age) VALUES (2, 3)")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Error during planning: Column age not found in table t"
+    );
+
+    Ok(())
+}
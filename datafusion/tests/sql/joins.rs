@@ -16,6 +16,11 @@
 // under the License.
 
 use super::*;
+use datafusion::physical_plan::coalesce_batches::CoalesceBatchesExec;
+use datafusion::physical_plan::repartition::RepartitionExec;
+use datafusion::physical_plan::sort::SortExec;
+use datafusion::physical_plan::sort_merge_join::SortMergeJoinExec;
+use datafusion::physical_plan::Partitioning;
 
 #[tokio::test]
 async fn equijoin() -> Result<()> {
@@ -184,6 +189,159 @@ async fn left_join() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn sort_merge_join() -> Result<()> {
+    let queries = [
+        (
+            "SELECT t1_id, t1_name, t2_name FROM t1 JOIN t2 ON t1_id = t2_id ORDER BY t1_id",
+            vec![
+                "+-------+---------+---------+",
+                "| t1_id | t1_name | t2_name |",
+                "+-------+---------+---------+",
+                "| 11    | a       | z       |",
+                "| 22    | b       | y       |",
+                "| 44    | d       | x       |",
+                "+-------+---------+---------+",
+            ],
+        ),
+        (
+            "SELECT t1_id, t1_name, t2_name FROM t1 LEFT JOIN t2 ON t1_id = t2_id ORDER BY t1_id",
+            vec![
+                "+-------+---------+---------+",
+                "| t1_id | t1_name | t2_name |",
+                "+-------+---------+---------+",
+                "| 11    | a       | z       |",
+                "| 22    | b       | y       |",
+                "| 33    | c       |         |",
+                "| 44    | d       | x       |",
+                "+-------+---------+---------+",
+            ],
+        ),
+        (
+            "SELECT t1_id, t1_name, t2_name FROM t1 RIGHT JOIN t2 ON t1_id = t2_id ORDER BY t1_id",
+            vec![
+                "+-------+---------+---------+",
+                "| t1_id | t1_name | t2_name |",
+                "+-------+---------+---------+",
+                "| 11    | a       | z       |",
+                "| 22    | b       | y       |",
+                "| 44    | d       | x       |",
+                "|       |         | w       |",
+                "+-------+---------+---------+",
+            ],
+        ),
+        (
+            "SELECT t1_id, t1_name, t2_name FROM t1 FULL JOIN t2 ON t1_id = t2_id ORDER BY t1_id",
+            vec![
+                "+-------+---------+---------+",
+                "| t1_id | t1_name | t2_name |",
+                "+-------+---------+---------+",
+                "| 11    | a       | z       |",
+                "| 22    | b       | y       |",
+                "| 33    | c       |         |",
+                "| 44    | d       | x       |",
+                "|       |         | w       |",
+                "+-------+---------+---------+",
+            ],
+        ),
+    ];
+
+    for target_partitions in [1, 4] {
+        let config = ExecutionConfig::new()
+            .with_target_partitions(target_partitions)
+            .with_prefer_sort_merge_join(true);
+        let mut ctx = create_join_context_with_config("t1_id", "t2_id", config)?;
+
+        for (sql, expected) in &queries {
+            let plan = ctx.create_logical_plan(sql)?;
+            let plan = ctx.optimize(&plan)?;
+            let plan = ctx.create_physical_plan(&plan).await?;
+            let formatted = displayable(plan.as_ref()).indent().to_string();
+            assert_contains!(&formatted, "SortMergeJoinExec");
+
+            // the inputs are sorted per hash partition, without merging the
+            // partitions
+            if target_partitions > 1 {
+                let join = find_sort_merge_join(&plan).expect("sort merge join");
+                assert_eq!(
+                    join.output_partitioning().partition_count(),
+                    target_partitions,
+                    "{}",
+                    formatted
+                );
+                for input in join.children() {
+                    let sort = input
+                        .as_any()
+                        .downcast_ref::<SortExec>()
+                        .unwrap_or_else(|| panic!("unsorted join input\n{}", formatted));
+                    let mut sort_input = sort.input().clone();
+                    while let Some(coalesce) =
+                        sort_input.as_any().downcast_ref::<CoalesceBatchesExec>()
+                    {
+                        sort_input = coalesce.input().clone();
+                    }
+                    let repartition = sort_input
+                        .as_any()
+                        .downcast_ref::<RepartitionExec>()
+                        .unwrap_or_else(|| {
+                            panic!("sort of merged partitions\n{}", formatted)
+                        });
+                    assert!(
+                        matches!(
+                            repartition.partitioning(),
+                            Partitioning::Hash(_, n) if *n == target_partitions
+                        ),
+                        "{}",
+                        formatted
+                    );
+                }
+            }
+
+            let actual = collect(plan).await?;
+            assert_batches_eq!(expected, &actual);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the first sort merge join of `plan`, searched depth first
+fn find_sort_merge_join(plan: &Arc<dyn ExecutionPlan>) -> Option<Arc<dyn ExecutionPlan>> {
+    if plan.as_any().is::<SortMergeJoinExec>() {
+        return Some(plan.clone());
+    }
+    plan.children().iter().find_map(find_sort_merge_join)
+}
+
+#[tokio::test]
+async fn sort_merge_join_output_ordering() -> Result<()> {
+    let config = ExecutionConfig::new()
+        .with_target_partitions(1)
+        .with_prefer_sort_merge_join(true);
+    let mut ctx = create_join_context_with_config("t1_id", "t2_id", config)?;
+
+    // the join output is already sorted on the join keys, so only its
+    // inputs need to be sorted
+    let sql = "SELECT t1_id, t2_name FROM t1 JOIN t2 ON t1_id = t2_id ORDER BY t1_id";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let formatted = displayable(plan.as_ref()).indent().to_string();
+    assert_eq!(formatted.matches("SortExec").count(), 2, "{}", formatted);
+
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t2_name |",
+        "+-------+---------+",
+        "| 11    | z       |",
+        "| 22    | y       |",
+        "| 44    | x       |",
+        "+-------+---------+",
+    ];
+    let actual = collect(plan).await?;
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
 #[tokio::test]
 async fn left_join_unbalanced() -> Result<()> {
     // the t1_id is larger than t2_id so the hash_build_probe_order optimizer should kick in
@@ -72,14 +72,17 @@ macro_rules! test_expression {
 }
 
 pub mod aggregates;
+pub mod arrow_ipc;
 #[cfg(feature = "avro")]
 pub mod avro;
+pub mod copy;
 pub mod create_drop;
 pub mod errors;
 pub mod explain_analyze;
 pub mod expr;
 pub mod functions;
 pub mod group_by;
+pub mod insert;
 pub mod intersection;
 pub mod joins;
 pub mod limit;
@@ -89,6 +92,7 @@ pub mod predicates;
 pub mod projection;
 pub mod references;
 pub mod select;
+pub mod subqueries;
 pub mod timestamp;
 pub mod udf;
 pub mod union;
@@ -166,7 +170,15 @@ fn create_join_context(
     column_left: &str,
     column_right: &str,
 ) -> Result<ExecutionContext> {
-    let mut ctx = ExecutionContext::new();
+    create_join_context_with_config(column_left, column_right, ExecutionConfig::new())
+}
+
+fn create_join_context_with_config(
+    column_left: &str,
+    column_right: &str,
+    config: ExecutionConfig,
+) -> Result<ExecutionContext> {
+    let mut ctx = ExecutionContext::with_config(config);
 
     let t1_schema = Arc::new(Schema::new(vec![
         Field::new(column_left, DataType::UInt32, true),
@@ -16,6 +16,7 @@
 // under the License.
 
 use super::*;
+use datafusion::physical_plan::sort::SortExec;
 
 #[tokio::test]
 async fn test_sort_unprojected_col() -> Result<()> {
@@ -124,3 +125,60 @@ async fn test_specific_nulls_first_asc() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn sort_with_spilling() -> Result<()> {
+    let config = ExecutionConfig::new()
+        .with_batch_size(100)
+        .with_sort_spill_threshold(1024);
+    let mut ctx = ExecutionContext::with_config(config);
+
+    let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+    let batches = (0..20)
+        .map(|batch| {
+            let values: Vec<i32> =
+                (0..100).map(|i| (batch * 100 + i) * 7919 % 2000).collect();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(values))])
+        })
+        .collect::<arrow::error::Result<Vec<_>>>()?;
+    let mut expected = batches
+        .iter()
+        .flat_map(|batch| {
+            let a = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            a.values().to_vec()
+        })
+        .collect::<Vec<_>>();
+    expected.sort_unstable();
+
+    let table = MemTable::try_new(schema, vec![batches])?;
+    ctx.register_table("t", Arc::new(table))?;
+
+    let plan = ctx.create_logical_plan("SELECT a FROM t ORDER BY a")?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let actual = collect(plan.clone()).await?;
+    let actual = actual
+        .iter()
+        .flat_map(|batch| {
+            let a = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            a.values().to_vec()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(expected, actual);
+
+    // the spills are reported by the sort
+    let sort = plan.as_any().downcast_ref::<SortExec>().unwrap();
+    let metrics = sort.metrics().unwrap();
+    assert!(metrics.spill_count().unwrap() > 0);
+    assert!(metrics.spilled_bytes().unwrap() > 0);
+
+    Ok(())
+}
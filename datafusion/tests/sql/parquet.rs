@@ -43,6 +43,27 @@ async fn parquet_query() {
     assert_batches_eq!(expected, &actual);
 }
 
+#[tokio::test]
+async fn parquet_query_with_row_filter() {
+    let mut ctx = ExecutionContext::new();
+    register_alltypes_parquet(&mut ctx).await;
+    // the filter on id is evaluated before string_col is decoded
+    let sql = "SELECT id, CAST(string_col AS varchar) FROM alltypes_plain \
+               WHERE id > 2 AND id % 2 = 1";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+-----------------------------------------+",
+        "| id | CAST(alltypes_plain.string_col AS Utf8) |",
+        "+----+-----------------------------------------+",
+        "| 5  | 1                                       |",
+        "| 7  | 1                                       |",
+        "| 3  | 1                                       |",
+        "+----+-----------------------------------------+",
+    ];
+
+    assert_batches_eq!(expected, &actual);
+}
+
 #[tokio::test]
 async fn parquet_single_nan_schema() {
     let mut ctx = ExecutionContext::new();
@@ -160,3 +181,87 @@ async fn parquet_list_columns() {
     assert_eq!(result.value(2), "hij");
     assert_eq!(result.value(3), "xyz");
 }
+
+#[tokio::test]
+async fn parquet_query_with_schema_evolution() -> Result<()> {
+    use datafusion::datasource::{
+        file_format::parquet::ParquetFormat, listing::ListingOptions,
+    };
+    use parquet::arrow::ArrowWriter;
+
+    fn write_file(path: std::path::PathBuf, batch: RecordBatch) -> Result<()> {
+        let mut writer =
+            ArrowWriter::try_new(std::fs::File::create(path)?, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    let tmp_dir = tempfile::tempdir()?;
+    // the first file has no c3 column and a narrower type for c2
+    write_file(
+        tmp_dir.path().join("1.parquet"),
+        RecordBatch::try_from_iter(vec![
+            (
+                "c1",
+                Arc::new(StringArray::from(vec!["a", "b"])) as ArrayRef,
+            ),
+            ("c2", Arc::new(Int32Array::from(vec![1, 2])) as ArrayRef),
+        ])?,
+    )?;
+    // the second file has its columns in another order
+    write_file(
+        tmp_dir.path().join("2.parquet"),
+        RecordBatch::try_from_iter(vec![
+            ("c3", Arc::new(StringArray::from(vec!["x"])) as ArrayRef),
+            ("c2", Arc::new(Int64Array::from(vec![3])) as ArrayRef),
+            ("c1", Arc::new(StringArray::from(vec!["c"])) as ArrayRef),
+        ])?,
+    )?;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("c1", DataType::Utf8, true),
+        Field::new("c2", DataType::Int64, true),
+        Field::new("c3", DataType::Utf8, true),
+    ]));
+    let options = ListingOptions::new(Arc::new(ParquetFormat::default()));
+    let mut ctx = ExecutionContext::new();
+    ctx.register_listing_table(
+        "t",
+        tmp_dir.path().to_str().unwrap(),
+        options,
+        Some(schema),
+    )
+    .await?;
+
+    let sql = "SELECT c1, c2, c3 FROM t WHERE c2 > 1 ORDER BY c2";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----+----+",
+        "| c1 | c2 | c3 |",
+        "+----+----+----+",
+        "| b  | 2  |    |",
+        "| c  | 3  | x  |",
+        "+----+----+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // c3 is missing from the first file, so its row groups can't be pruned
+    let sql = "SELECT c1, c2, c3 FROM t WHERE c3 = 'x'";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----+----+",
+        "| c1 | c2 | c3 |",
+        "+----+----+----+",
+        "| c  | 3  | x  |",
+        "+----+----+----+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // c1 is the last column of the second file, not its first one (c3)
+    let sql = "SELECT c1, c2, c3 FROM t WHERE c1 = 'c'";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
@@ -823,6 +823,90 @@ async fn query_cte() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn query_recursive_cte() -> Result<()> {
+    let mut ctx = ExecutionContext::new();
+
+    // counter
+    let sql = "WITH RECURSIVE t(n) AS (\
+        VALUES (1) UNION ALL SELECT n + 1 FROM t WHERE n < 5\
+        ) SELECT n FROM t ORDER BY n";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+", "| n |", "+---+", "| 1 |", "| 2 |", "| 3 |", "| 4 |", "| 5 |", "+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // hierarchy traversal
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("manager_id", DataType::Int64, true),
+        Field::new("name", DataType::Utf8, false),
+    ]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4, 5, 6])),
+            Arc::new(Int64Array::from(vec![
+                None,
+                Some(1),
+                Some(1),
+                Some(2),
+                Some(4),
+                Some(3),
+            ])),
+            Arc::new(StringArray::from(vec![
+                "ceo",
+                "cto",
+                "cfo",
+                "developer",
+                "intern",
+                "accountant",
+            ])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+    ctx.register_table("employees", Arc::new(table))?;
+
+    let sql = "WITH RECURSIVE reports(id, name, level) AS (\
+        SELECT id, name, 1 FROM employees WHERE id = 2 \
+        UNION ALL \
+        SELECT e.id, e.name, reports.level + 1 \
+        FROM employees e JOIN reports ON e.manager_id = reports.id\
+        ) SELECT * FROM reports ORDER BY id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+-----------+-------+",
+        "| id | name      | level |",
+        "+----+-----------+-------+",
+        "| 2  | cto       | 1     |",
+        "| 4  | developer | 2     |",
+        "| 5  | intern    | 3     |",
+        "+----+-----------+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_recursive_cte_iteration_limit() -> Result<()> {
+    let mut ctx = ExecutionContext::with_config(
+        ExecutionConfig::new().with_max_recursive_iterations(3),
+    );
+
+    let sql = "WITH RECURSIVE t(n) AS (\
+        VALUES (1) UNION ALL SELECT n + 1 FROM t WHERE n < 5\
+        ) SELECT n FROM t";
+    let err = ctx.sql(sql).await?.collect().await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "Recursive query t did not complete after 3 iterations"
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn csv_select_nested() -> Result<()> {
     let mut ctx = ExecutionContext::new();
@@ -0,0 +1,303 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use super::*;
+
+#[tokio::test]
+async fn in_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, t1_name FROM t1 \
+        WHERE t1_id IN (SELECT t2_id FROM t2) ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t1_name |",
+        "+-------+---------+",
+        "| 11    | a       |",
+        "| 22    | b       |",
+        "| 44    | d       |",
+        "+-------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT t1_id, t1_name FROM t1 \
+        WHERE t1_id NOT IN (SELECT t2_id FROM t2) ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t1_name |",
+        "+-------+---------+",
+        "| 33    | c       |",
+        "+-------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn correlated_exists() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, t1_name FROM t1 WHERE EXISTS \
+        (SELECT * FROM t2 WHERE t2_id = t1_id AND t2_name <> 'y') ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t1_name |",
+        "+-------+---------+",
+        "| 11    | a       |",
+        "| 44    | d       |",
+        "+-------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT t1_id, t1_name FROM t1 WHERE NOT EXISTS \
+        (SELECT * FROM t2 WHERE t2_id = t1_id AND t2_name <> 'y') ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t1_name |",
+        "+-------+---------+",
+        "| 22    | b       |",
+        "| 33    | c       |",
+        "+-------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uncorrelated_exists() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE EXISTS (SELECT t2_id FROM t2 WHERE t2_id > 50) ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| t1_id |",
+        "+-------+",
+        "| 11    |",
+        "| 22    |",
+        "| 33    |",
+        "| 44    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE NOT EXISTS (SELECT t2_id FROM t2 WHERE t2_id > 50)";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec!["++", "++"];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uncorrelated_scalar_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, t1_name FROM t1 \
+        WHERE t1_id > (SELECT AVG(t2_id) FROM t2) ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+---------+",
+        "| t1_id | t1_name |",
+        "+-------+---------+",
+        "| 44    | d       |",
+        "+-------+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn correlated_scalar_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, \
+        (SELECT MAX(t2_name) FROM t2 WHERE t2_id = t1_id) AS max_name \
+        FROM t1 ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+----------+",
+        "| t1_id | max_name |",
+        "+-------+----------+",
+        "| 11    | z        |",
+        "| 22    | y        |",
+        "| 33    |          |",
+        "| 44    | x        |",
+        "+-------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn not_in_subquery_with_nulls() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    // NOT IN is NULL rather than true when the subquery produces a NULL value
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE t1_id NOT IN (SELECT NULLIF(t2_id, 55) FROM t2)";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec!["++", "++"];
+    assert_batches_eq!(expected, &actual);
+
+    // ... unless the subquery produces no row
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE t1_id NOT IN (SELECT NULLIF(t2_id, 55) FROM t2 WHERE t2_id > 100) \
+        ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| t1_id |",
+        "+-------+",
+        "| 11    |",
+        "| 22    |",
+        "| 33    |",
+        "| 44    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // the NULL values only apply to the rows of their correlation key
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE t1_id NOT IN (SELECT NULLIF(t2_id + 1, 23) FROM t2 WHERE t2_id = t1_id) \
+        ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| t1_id |",
+        "+-------+",
+        "| 11    |",
+        "| 33    |",
+        "| 44    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uncorrelated_scalar_subquery_row_count() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id, (SELECT t2_name FROM t2 WHERE t2_id = 11) AS one, \
+        (SELECT t2_name FROM t2 WHERE t2_id = 33) AS missing \
+        FROM t1 ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+-----+---------+",
+        "| t1_id | one | missing |",
+        "+-------+-----+---------+",
+        "| 11    | z   |         |",
+        "| 22    | z   |         |",
+        "| 33    | z   |         |",
+        "| 44    | z   |         |",
+        "+-------+-----+---------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "SELECT t1_id, (SELECT t2_name FROM t2) FROM t1";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let err = collect(plan).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("More than one row returned by a subquery used as an expression"),
+        "{}",
+        err
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn scalar_subquery_names() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT (SELECT MIN(t2_id) FROM t2), (SELECT MAX(t2_id) FROM t2) \
+        FROM t1 LIMIT 1";
+    let actual = execute(&mut ctx, sql).await;
+    assert_eq!(actual, vec![vec!["11", "55"]]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn correlated_count_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    // outer rows without correlated rows count 0 rows, not NULL
+    let sql = "SELECT t1_id, \
+        (SELECT COUNT(*) FROM t2 WHERE t2_id = t1_id) AS cnt, \
+        (SELECT COUNT(*) + 1 FROM t2 WHERE t2_id = t1_id) AS cnt_plus_one \
+        FROM t1 ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+-----+--------------+",
+        "| t1_id | cnt | cnt_plus_one |",
+        "+-------+-----+--------------+",
+        "| 11    | 1   | 2            |",
+        "| 22    | 1   | 2            |",
+        "| 33    | 0   | 1            |",
+        "| 44    | 1   | 2            |",
+        "+-------+-----+--------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn non_equi_correlated_exists() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE EXISTS (SELECT t2_id FROM t2 WHERE t2_id > t1_id AND t2_id < 40) \
+        ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| t1_id |",
+        "+-------+",
+        "| 11    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    // a mix of equalities and other correlated predicates, as in TPC-H q21
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE NOT EXISTS (SELECT * FROM t2 WHERE t2_id = t1_id AND t2_name <> t1_name) \
+        ORDER BY t1_id";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+-------+",
+        "| t1_id |",
+        "+-------+",
+        "| 33    |",
+        "+-------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unsupported_correlated_subquery() -> Result<()> {
+    let mut ctx = create_join_context("t1_id", "t2_id")?;
+    let sql = "SELECT t1_id FROM t1 \
+        WHERE t1_id < (SELECT MAX(t2_id) FROM t2 WHERE t2_id > t1_id)";
+    let plan = ctx.create_logical_plan(sql)?;
+    let err = ctx.optimize(&plan).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Unsupported correlated subquery predicate"),
+        "{}",
+        err
+    );
+    Ok(())
+}
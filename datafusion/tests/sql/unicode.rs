@@ -97,6 +97,9 @@ async fn test_unicode_expressions() -> Result<()> {
     test_expression!("substr('alphabet', 3, 20)", "phabet");
     test_expression!("substr('alphabet', CAST(NULL AS int), 20)", "NULL");
     test_expression!("substr('alphabet', 3, CAST(NULL AS int))", "NULL");
+    test_expression!("substring('alphabet' from 3 for 2)", "ph");
+    test_expression!("substring('alphabet' from 3)", "phabet");
+    test_expression!("substring('alphabet' for 2)", "al");
     test_expression!("translate('12345', '143', 'ax')", "a2x5");
     test_expression!("translate(NULL, '143', 'ax')", "NULL");
     test_expression!("translate('12345', NULL, 'ax')", "NULL");
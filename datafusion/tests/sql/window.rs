@@ -142,3 +142,237 @@ async fn csv_query_window_with_partition_by_order_by() -> Result<()> {
     assert_batches_eq!(expected, &actual);
     Ok(())
 }
+
+#[tokio::test]
+async fn query_window_with_frames() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ts", DataType::Int64, false),
+        Field::new("x", DataType::Int32, true),
+    ]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 5, 6, 10])),
+            Arc::new(Int32Array::from(vec![
+                Some(1),
+                Some(2),
+                Some(3),
+                Some(4),
+                None,
+                Some(6),
+            ])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+    let sql = "select \
+               ts, \
+               avg(x) over (order by ts rows between 1 preceding and current row) as avg_rows, \
+               sum(x) over (order by ts range between 2 preceding and current row) as sum_range, \
+               max(x) over (order by ts rows between 1 preceding and 1 following) as max_rows, \
+               sum(x) over (order by ts groups between 1 preceding and 1 following) as sum_groups, \
+               count(x) over (order by ts desc range between current row and 3 following) as count_range_desc \
+               from t \
+               order by ts";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----------+-----------+----------+------------+------------------+",
+        "| ts | avg_rows | sum_range | max_rows | sum_groups | count_range_desc |",
+        "+----+----------+-----------+----------+------------+------------------+",
+        "| 1  | 1        | 1         | 2        | 3          | 1                |",
+        "| 2  | 1.5      | 3         | 3        | 6          | 2                |",
+        "| 3  | 2.5      | 6         | 4        | 9          | 3                |",
+        "| 5  | 3.5      | 7         | 4        | 7          | 3                |",
+        "| 6  | 4        | 4         | 6        | 10         | 2                |",
+        "| 10 | 6        | 6         | 6        | 6          | 1                |",
+        "+----+----------+-----------+----------+------------+------------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_window_with_frames_over_infinite_values() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("ts", DataType::Int64, false),
+        Field::new("x", DataType::Float64, false),
+    ]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![1, 2, 3, 4, 5])),
+            Arc::new(Float64Array::from(vec![1.0, f64::INFINITY, 2.0, 3.0, 4.0])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+    // the frames are finite again once the infinite value leaves them
+    let sql = "select \
+               ts, \
+               sum(x) over (order by ts rows between 1 preceding and current row) as sum_rows, \
+               avg(x) over (order by ts rows between 1 preceding and current row) as avg_rows \
+               from t \
+               order by ts";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+----------+----------+",
+        "| ts | sum_rows | avg_rows |",
+        "+----+----------+----------+",
+        "| 1  | 1        | 1        |",
+        "| 2  | inf      | inf      |",
+        "| 3  | inf      | inf      |",
+        "| 4  | 5        | 2.5      |",
+        "| 5  | 7        | 3.5      |",
+        "+----+----------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_window_with_interval_frames() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "ts",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        ),
+        Field::new("x", DataType::Int32, false),
+    ]));
+    let timestamps = [
+        Utc.ymd(2021, 1, 1).and_hms(0, 0, 0),
+        Utc.ymd(2021, 1, 1).and_hms(12, 0, 0),
+        Utc.ymd(2021, 1, 2).and_hms(6, 0, 0),
+        Utc.ymd(2021, 1, 31).and_hms(0, 0, 0),
+        Utc.ymd(2021, 2, 28).and_hms(0, 0, 0),
+        Utc.ymd(2021, 3, 31).and_hms(0, 0, 0),
+    ];
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(TimestampMillisecondArray::from_vec(
+                timestamps.iter().map(|ts| ts.timestamp_millis()).collect(),
+                None,
+            )),
+            Arc::new(Int32Array::from(vec![1, 2, 3, 4, 5, 6])),
+        ],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+    // one month before March 31 is February 28
+    let sql = "select \
+               x, \
+               sum(x) over (order by ts range between interval '1' day preceding and current row) as sum_day, \
+               count(x) over (order by ts range between interval '1' month preceding and current row) as count_month, \
+               sum(x) over (order by ts desc range between current row and interval '1' month following) as sum_month_desc \
+               from t \
+               order by ts";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---+---------+-------------+----------------+",
+        "| x | sum_day | count_month | sum_month_desc |",
+        "+---+---------+-------------+----------------+",
+        "| 1 | 1       | 1           | 1              |",
+        "| 2 | 3       | 2           | 3              |",
+        "| 3 | 5       | 3           | 6              |",
+        "| 4 | 4       | 4           | 10             |",
+        "| 5 | 5       | 2           | 9              |",
+        "| 6 | 6       | 2           | 11             |",
+        "+---+---------+-------------+----------------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "select sum(x) over (order by x range between interval '1' day preceding and current row) from t";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let plan = ctx.create_physical_plan(&plan).await?;
+    let err = collect(plan).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "RANGE window frames on Int32 order by expressions can't have the offset INTERVAL '1 DAY'"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_window_with_large_range_keys() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![Field::new("k", DataType::Int64, false)]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int64Array::from(vec![
+            i64::MAX - 2,
+            i64::MAX - 1,
+            i64::MAX,
+        ]))],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+    // the keys are not rounded to the same float
+    let sql = "select \
+               k, \
+               count(k) over (order by k range between 1 preceding and current row) as c \
+               from t \
+               order by k";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+---------------------+---+",
+        "| k                   | c |",
+        "+---------------------+---+",
+        "| 9223372036854775805 | 1 |",
+        "| 9223372036854775806 | 2 |",
+        "| 9223372036854775807 | 2 |",
+        "+---------------------+---+",
+    ];
+    assert_batches_eq!(expected, &actual);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_window_ntile_and_percent_rank() -> Result<()> {
+    let schema = Arc::new(Schema::new(vec![Field::new("c", DataType::Int32, false)]));
+    let data = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int32Array::from(vec![40, 20, 10, 30, 20]))],
+    )?;
+    let table = MemTable::try_new(schema, vec![vec![data]])?;
+
+    let mut ctx = ExecutionContext::new();
+    ctx.register_table("t", Arc::new(table))?;
+    let sql = "select \
+               c, \
+               ntile(2) over (order by c) as tile, \
+               percent_rank() over (order by c) as pct_rank \
+               from t \
+               order by c";
+    let actual = execute_to_batches(&mut ctx, sql).await;
+    let expected = vec![
+        "+----+------+----------+",
+        "| c  | tile | pct_rank |",
+        "+----+------+----------+",
+        "| 10 | 1    | 0        |",
+        "| 20 | 1    | 0.25     |",
+        "| 20 | 1    | 0.25     |",
+        "| 30 | 2    | 0.75     |",
+        "| 40 | 2    | 1        |",
+        "+----+------+----------+",
+    ];
+    assert_batches_eq!(expected, &actual);
+
+    let sql = "select ntile(0) over (order by c) from t";
+    let plan = ctx.create_logical_plan(sql)?;
+    let plan = ctx.optimize(&plan)?;
+    let err = ctx.create_physical_plan(&plan).await.unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "NTILE requires a positive integer literal as number of buckets"
+    );
+    Ok(())
+}